@@ -6,7 +6,7 @@
 // body for readability.
 #![allow(clippy::if_same_then_else)]
 
-use std::{path::PathBuf, process::Command};
+use std::{collections::HashMap, env, fs, path::PathBuf, process::Command};
 
 use rayon::prelude::*;
 use version_check as rustc;
@@ -47,6 +47,9 @@ impl TreeSitterParser {
                 .flag_if_supported("-Wno-parentheses")
                 // Ignore warning from tree-sitter-ruby.
                 .flag_if_supported("-Wno-unused-but-set-variable")
+                // Bundling can't be combined with whole-archive when
+                // building the library crate.
+                .link_lib_modifier("-bundle")
                 .link_lib_modifier("+whole-archive");
 
             for file in cpp_files {
@@ -65,7 +68,9 @@ impl TreeSitterParser {
             build.file(dir.join(file));
         }
 
-        build.link_lib_modifier("+whole-archive");
+        build
+            .link_lib_modifier("-bundle")
+            .link_lib_modifier("+whole-archive");
 
         build.compile(self.name);
     }
@@ -392,6 +397,7 @@ fn main() {
 
     parsers.par_iter().for_each(|p| p.build());
     commit_info();
+    grammar_versions(&parsers);
 
     if let Some((version, _, _)) = rustc::triple() {
         println!("cargo:rustc-env=DFT_RUSTC_VERSION={}", version);
@@ -416,7 +422,84 @@ fn commit_info() {
     let stdout = String::from_utf8(output.stdout).unwrap();
     let mut parts = stdout.split_whitespace();
     let mut next = || parts.next().unwrap();
-    let _commit_hash = next();
+    println!("cargo:rustc-env=DFT_COMMIT_HASH={}", next());
     println!("cargo:rustc-env=DFT_COMMIT_SHORT_HASH={}", next());
     println!("cargo:rustc-env=DFT_COMMIT_DATE={}", next())
 }
+
+/// The `"version"` in a grammar's package.json, if any.
+fn package_version(grammar_dir: &str) -> Option<String> {
+    let package_json = fs::read_to_string(PathBuf::from(grammar_dir).join("package.json")).ok()?;
+    // The first "version" key is the package's own version, before
+    // any dependencies.
+    let after_key = package_json.split("\"version\"").nth(1)?;
+    let value = after_key.trim_start().strip_prefix(':')?.trim_start();
+    let value = value.strip_prefix('"')?;
+    Some(value[..value.find('"')?].to_owned())
+}
+
+/// The upstream commit of each vendored parser, from the
+/// `git-subtree-split` trailer of the most recent `git subtree`
+/// commit for its directory.
+fn subtree_commits() -> HashMap<String, String> {
+    let mut commits = HashMap::new();
+    if !PathBuf::from(".git").exists() {
+        return commits;
+    }
+
+    let output = match Command::new("git")
+        .arg("log")
+        .arg("--grep=^git-subtree-dir:")
+        .arg("--format=%B")
+        .output()
+    {
+        Ok(output) if output.status.success() => output,
+        _ => return commits,
+    };
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    // git log starts with the newest commit, so keep the first split
+    // we see for each directory.
+    let mut dir: Option<&str> = None;
+    for line in stdout.lines() {
+        if let Some(subtree_dir) = line.strip_prefix("git-subtree-dir:") {
+            dir = Some(subtree_dir.trim());
+        } else if let Some(split) = line.strip_prefix("git-subtree-split:") {
+            if let Some(dir) = dir.take() {
+                commits
+                    .entry(dir.to_owned())
+                    .or_insert_with(|| split.trim().chars().take(7).collect());
+            }
+        }
+    }
+    commits
+}
+
+/// The git subtree that contains `src_dir`. `src_dir` is
+/// `vendored_parsers/NAME-src`, a symlink to the src/ directory of the
+/// subtree, or a directory below it.
+fn subtree_dir(src_dir: &str) -> String {
+    let dir: Vec<&str> = src_dir.split('/').take(2).collect();
+    dir.join("/").trim_end_matches("-src").to_owned()
+}
+
+/// Write the version of each vendored parser to
+/// `$OUT_DIR/grammar_versions.rs`, for `--version --verbose`.
+fn grammar_versions(parsers: &[TreeSitterParser]) {
+    let commits = subtree_commits();
+
+    let mut src = String::from("pub(crate) const GRAMMAR_VERSIONS: &[GrammarVersion] = &[\n");
+    for parser in parsers {
+        let grammar_dir = subtree_dir(parser.src_dir);
+        src.push_str(&format!(
+            "    GrammarVersion {{ name: {:?}, version: {:?}, commit: {:?} }},\n",
+            parser.name,
+            package_version(&grammar_dir).as_deref(),
+            commits.get(&grammar_dir).map(|commit| commit.as_str()),
+        ));
+    }
+    src.push_str("];\n");
+
+    let out_dir = PathBuf::from(env::var_os("OUT_DIR").unwrap());
+    fs::write(out_dir.join("grammar_versions.rs"), src).unwrap();
+}
@@ -0,0 +1,55 @@
+/* Generated by cbindgen from ffi/src/lib.rs, do not edit. */
+
+#ifndef DIFFT_H
+#define DIFFT_H
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/*
+ Diff `lhs` and `rhs` and return the result as JSON, a NUL
+ terminated string that must be freed with `dft_free`.
+
+ `lhs` and `rhs` are UTF-8 text of `lhs_len` and `rhs_len` bytes,
+ and don't need to be NUL terminated. Invalid UTF-8 is replaced
+ with U+FFFD.
+
+ `language_name` is a NUL terminated language name, such as "rust"
+ or "C++", or NULL for a text diff. `options_json` is NULL or a NUL
+ terminated JSON object, which may set `graph_limit`, `byte_limit`,
+ `parse_error_limit`, `ignore_comments`, `detect_moves` and
+ `timeout_ms`.
+
+ On success, the JSON has `language`, `chunks` in the format of
+ `difft --display=json`, and `text_fallback_reason` if the files
+ were diffed as text. On failure, including a panic in difftastic,
+ it's an object with an `error` message.
+
+ # Safety
+
+ Each pointer must be NULL, or valid for reads of its length or up
+ to its NUL terminator. `lhs` and `rhs` may only be NULL if their
+ length is 0.
+ */
+char *dft_diff_strings(const char *lhs,
+                       size_t lhs_len,
+                       const char *rhs,
+                       size_t rhs_len,
+                       const char *language_name,
+                       const char *options_json);
+
+/*
+ Free a string returned by `dft_diff_strings`. Passing NULL does
+ nothing.
+
+ # Safety
+
+ `s` must be NULL or a string from `dft_diff_strings` that hasn't
+ been freed yet.
+ */
+void dft_free(char *s);
+
+#endif /* DIFFT_H */
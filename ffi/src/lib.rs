@@ -0,0 +1,211 @@
+//! A C API for difftastic, so editors and other programs can diff
+//! without spawning `difft`. See `include/difft.h`.
+//!
+//! Every function is safe to call from several threads at once. The
+//! strings returned by [dft_diff_strings] are owned by the caller,
+//! and must be freed with [dft_free].
+
+use std::{
+    ffi::{c_char, CStr, CString},
+    panic::{catch_unwind, AssertUnwindSafe},
+    slice,
+    time::Duration,
+};
+
+use difft::{diff_files, DiffOptions, Language};
+use serde_json::{json, Value};
+
+/// Diff `lhs` and `rhs` and return the result as JSON, a NUL
+/// terminated string that must be freed with `dft_free`.
+///
+/// `lhs` and `rhs` are UTF-8 text of `lhs_len` and `rhs_len` bytes,
+/// and don't need to be NUL terminated. Invalid UTF-8 is replaced
+/// with U+FFFD.
+///
+/// `language_name` is a NUL terminated language name, such as "rust"
+/// or "C++", or NULL for a text diff. `options_json` is NULL or a NUL
+/// terminated JSON object, which may set `graph_limit`, `byte_limit`,
+/// `parse_error_limit`, `ignore_comments`, `detect_moves` and
+/// `timeout_ms`.
+///
+/// On success, the JSON has `language`, `chunks` in the format of
+/// `difft --display=json`, and `text_fallback_reason` if the files
+/// were diffed as text. On failure, including a panic in difftastic,
+/// it's an object with an `error` message.
+///
+/// # Safety
+///
+/// Each pointer must be NULL, or valid for reads of its length or up
+/// to its NUL terminator. `lhs` and `rhs` may only be NULL if their
+/// length is 0.
+#[no_mangle]
+pub unsafe extern "C" fn dft_diff_strings(
+    lhs: *const c_char,
+    lhs_len: usize,
+    rhs: *const c_char,
+    rhs_len: usize,
+    language_name: *const c_char,
+    options_json: *const c_char,
+) -> *mut c_char {
+    let lhs = bytes_arg(lhs, lhs_len);
+    let rhs = bytes_arg(rhs, rhs_len);
+    let language_name = str_arg(language_name);
+    let options_json = str_arg(options_json);
+
+    let result = catch_unwind(AssertUnwindSafe(|| {
+        diff_json(lhs, rhs, language_name, options_json)
+    }));
+    let json = match result {
+        Ok(Ok(json)) => json,
+        Ok(Err(e)) => error_json(&e),
+        Err(panic) => {
+            let message = panic
+                .downcast_ref::<&str>()
+                .map(|s| (*s).to_owned())
+                .or_else(|| panic.downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| "unknown error".to_owned());
+            error_json(&format!("difftastic panicked: {}", message))
+        }
+    };
+    // JSON escapes NUL, so this can't fail.
+    CString::new(json)
+        .expect("JSON should not contain NUL")
+        .into_raw()
+}
+
+/// Free a string returned by `dft_diff_strings`. Passing NULL does
+/// nothing.
+///
+/// # Safety
+///
+/// `s` must be NULL or a string from `dft_diff_strings` that hasn't
+/// been freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn dft_free(s: *mut c_char) {
+    if !s.is_null() {
+        drop(CString::from_raw(s));
+    }
+}
+
+unsafe fn bytes_arg<'a>(ptr: *const c_char, len: usize) -> &'a [u8] {
+    if ptr.is_null() || len == 0 {
+        &[]
+    } else {
+        slice::from_raw_parts(ptr as *const u8, len)
+    }
+}
+
+unsafe fn str_arg<'a>(ptr: *const c_char) -> Option<&'a [u8]> {
+    (!ptr.is_null()).then(|| CStr::from_ptr(ptr).to_bytes())
+}
+
+fn error_json(message: &str) -> String {
+    json!({ "error": message }).to_string()
+}
+
+fn diff_json(
+    lhs: &[u8],
+    rhs: &[u8],
+    language_name: Option<&[u8]>,
+    options_json: Option<&[u8]>,
+) -> Result<String, String> {
+    let language = match language_name {
+        Some(name) => {
+            let name = String::from_utf8_lossy(name);
+            Some(Language::from_name(&name).ok_or_else(|| format!("Unknown language: {}", name))?)
+        }
+        None => None,
+    };
+    let opts = match options_json {
+        Some(options_json) => parse_options(options_json)?,
+        None => DiffOptions::default(),
+    };
+
+    let diff = diff_files(
+        &String::from_utf8_lossy(lhs),
+        &String::from_utf8_lossy(rhs),
+        language,
+        &opts,
+    );
+    Ok(diff.to_json())
+}
+
+fn parse_options(options_json: &[u8]) -> Result<DiffOptions, String> {
+    let value: Value =
+        serde_json::from_slice(options_json).map_err(|e| format!("Invalid options JSON: {}", e))?;
+    let Value::Object(fields) = value else {
+        return Err("Options must be a JSON object".to_owned());
+    };
+
+    let mut opts = DiffOptions::default();
+    for (key, value) in fields {
+        let invalid = || format!("Invalid value for {}: {}", key, value);
+        let as_usize = || {
+            value
+                .as_u64()
+                .and_then(|n| usize::try_from(n).ok())
+                .ok_or_else(invalid)
+        };
+        let as_bool = || value.as_bool().ok_or_else(invalid);
+        match key.as_str() {
+            "graph_limit" => opts.graph_limit = as_usize()?,
+            "byte_limit" => opts.byte_limit = as_usize()?,
+            "parse_error_limit" => opts.parse_error_limit = as_usize()?,
+            "ignore_comments" => opts.ignore_comments = as_bool()?,
+            "detect_moves" => opts.detect_moves = as_bool()?,
+            "timeout_ms" => {
+                opts.timeout = Some(Duration::from_millis(value.as_u64().ok_or_else(invalid)?))
+            }
+            _ => return Err(format!("Unknown option: {}", key)),
+        }
+    }
+    Ok(opts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn diff(
+        lhs: &str,
+        rhs: &str,
+        language_name: Option<&str>,
+        options_json: Option<&str>,
+    ) -> Value {
+        let language_name = language_name.map(|s| CString::new(s).unwrap());
+        let options_json = options_json.map(|s| CString::new(s).unwrap());
+        unsafe {
+            let json = dft_diff_strings(
+                lhs.as_ptr() as *const c_char,
+                lhs.len(),
+                rhs.as_ptr() as *const c_char,
+                rhs.len(),
+                language_name
+                    .as_ref()
+                    .map_or(std::ptr::null(), |s| s.as_ptr()),
+                options_json
+                    .as_ref()
+                    .map_or(std::ptr::null(), |s| s.as_ptr()),
+            );
+            let value = serde_json::from_slice(CStr::from_ptr(json).to_bytes()).unwrap();
+            dft_free(json);
+            value
+        }
+    }
+
+    #[test]
+    fn test_diff_strings() {
+        let value = diff("fn foo() {}\n", "fn foo() { bar(); }\n", Some("rust"), None);
+        assert_eq!(value["language"], "Rust");
+        assert_eq!(value["chunks"].as_array().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_diff_strings_errors() {
+        let value = diff("a", "b", Some("no such language"), None);
+        assert_eq!(value["error"], "Unknown language: no such language");
+
+        let value = diff("a", "b", None, Some(r#"{"graph_limit": true}"#));
+        assert_eq!(value["error"], "Invalid value for graph_limit: true");
+    }
+}
@@ -0,0 +1,33 @@
+/* Check that the C API links and returns JSON, see the ffi job in
+   .github/workflows/test.yml. */
+
+#include <stdio.h>
+#include <string.h>
+
+#include "difft.h"
+
+static int expect(const char *json, const char *needle) {
+  if (json == NULL || strstr(json, needle) == NULL) {
+    fprintf(stderr, "Expected %s in: %s\n", needle, json ? json : "(null)");
+    return 1;
+  }
+  return 0;
+}
+
+int main(void) {
+  const char *lhs = "fn foo() {}\n";
+  const char *rhs = "fn foo() { bar(); }\n";
+  int failures = 0;
+
+  char *json = dft_diff_strings(lhs, strlen(lhs), rhs, strlen(rhs), "rust", NULL);
+  failures += expect(json, "\"language\":\"Rust\"");
+  failures += expect(json, "\"content\":\"bar\"");
+  dft_free(json);
+
+  json = dft_diff_strings(lhs, strlen(lhs), rhs, strlen(rhs), "no such language", NULL);
+  failures += expect(json, "\"error\"");
+  dft_free(json);
+
+  dft_free(NULL);
+  return failures == 0 ? 0 : 1;
+}
@@ -0,0 +1,29 @@
+#!/usr/bin/env bash
+
+# Check that the bucket queue used by the Dijkstra search finds
+# routes with the same cost as the radix heap we used previously,
+# then compare their speed on large sample files.
+#
+# $ sample_files/bench_dijkstra.sh
+#
+# Requires hyperfine: https://github.com/sharkdp/hyperfine
+
+set -e
+
+echo "==> Checking route costs on all sample files"
+cargo test --release -- --ignored bucket_queue_same_cost_as_radix_heap
+
+echo "==> Building difftastic"
+cargo build --release
+cp target/release/difft target/release/difft-bucket-queue
+cargo build --release --features radix-heap
+cp target/release/difft target/release/difft-radix-heap
+
+for before_f in sample_files/slow_1.rs sample_files/typing_1.ml; do
+    after_f=${before_f/_1/_2}
+
+    echo "==> Timing for $before_f $after_f"
+    hyperfine --warmup 1 \
+        "./target/release/difft-radix-heap $before_f $after_f" \
+        "./target/release/difft-bucket-queue $before_f $after_f"
+done
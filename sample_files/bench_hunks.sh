@@ -0,0 +1,41 @@
+#!/usr/bin/env bash
+
+# Time the display of a text file with a change every few lines, so
+# it has thousands of hunks, in each display mode.
+#
+# $ sample_files/bench_hunks.sh
+#
+# Run this before and after a change to display code, and check the
+# md5sums match as well as comparing the timings.
+#
+# Requires hyperfine: https://github.com/sharkdp/hyperfine
+
+set -e
+
+echo "==> Building difftastic"
+cargo build --release
+
+tmp_dir=$(mktemp -d)
+trap 'rm -rf "$tmp_dir"' EXIT
+
+before_f=$tmp_dir/hunks_1.txt
+after_f=$tmp_dir/hunks_2.txt
+for i in $(seq 1 50000); do
+    echo "line $i"
+done >"$before_f"
+sed '0~10s/$/ changed/' "$before_f" >"$after_f"
+
+DISPLAYS=(inline side-by-side side-by-side-show-both)
+
+echo "==> Output for $before_f $after_f"
+for display in "${DISPLAYS[@]}"; do
+    difft_out=$(DFT_WIDTH=180 ./target/release/difft --color=always --display="$display" "$before_f" "$after_f" | md5sum)
+    echo "$display: $difft_out"
+done
+
+echo "==> Timing for $before_f $after_f"
+commands=()
+for display in "${DISPLAYS[@]}"; do
+    commands+=("./target/release/difft --color=always --display=$display $before_f $after_f")
+done
+hyperfine --warmup 1 "${commands[@]}"
@@ -0,0 +1,28 @@
+#!/usr/bin/env bash
+
+# Compare parsing both sides of a file pair on one thread with parsing
+# them in parallel, on the largest sample files.
+#
+# $ sample_files/bench_parse.sh
+#
+# Requires hyperfine: https://github.com/sharkdp/hyperfine
+
+set -e
+
+echo "==> Building difftastic"
+cargo build --release
+
+for before_f in sample_files/typing_1.ml sample_files/modules_1.ml sample_files/minified_1.js; do
+    after_f=${before_f/_1/_2}
+
+    echo "==> Parse time for $before_f $after_f"
+    for jobs in 1 2; do
+        echo "--jobs $jobs"
+        ./target/release/difft --jobs "$jobs" --profile "$before_f" "$after_f" >/dev/null
+    done
+
+    echo "==> Timing for $before_f $after_f"
+    hyperfine --warmup 1 \
+        "./target/release/difft --jobs 1 $before_f $after_f" \
+        "./target/release/difft --jobs 2 $before_f $after_f"
+done
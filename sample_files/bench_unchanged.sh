@@ -0,0 +1,40 @@
+#!/usr/bin/env bash
+
+# Compare the speed and output of difftastic with different settings
+# for the unchanged pre-pass, on large sample files.
+#
+# $ sample_files/bench_unchanged.sh
+#
+# Requires hyperfine: https://github.com/sharkdp/hyperfine
+
+set -e
+
+echo "==> Building difftastic"
+cargo build --release
+
+SETTINGS=(
+    ""
+    "--unchanged-min-run 5"
+    "--unchanged-min-run 20"
+    "--unchanged-ngram 2"
+    "--unchanged-ngram 8"
+    "--no-unchanged-heuristic"
+)
+
+for before_f in sample_files/slow_1.rs sample_files/typing_1.ml; do
+    after_f=${before_f/_1/_2}
+
+    echo "==> Output for $before_f $after_f"
+    for setting in "${SETTINGS[@]}"; do
+        # shellcheck disable=SC2086
+        difft_out=$(DFT_WIDTH=180 ./target/release/difft --color=always $setting "$before_f" "$after_f" | md5sum)
+        echo "${setting:-default}: $difft_out"
+    done
+
+    echo "==> Timing for $before_f $after_f"
+    commands=()
+    for setting in "${SETTINGS[@]}"; do
+        commands+=("./target/release/difft $setting $before_f $after_f")
+    done
+    hyperfine --warmup 1 "${commands[@]}"
+done
@@ -0,0 +1 @@
+!function(){"use strict";function m0(u,b){var a0=u*b*0;var a1=u+b*1;var a2=u&b*2;var a3=u+b*3;if(u>b){return a0+"item-0"}return [u,b,a1,a2].join(",")}function m1(o,s){var d0=o+s*1;var d1=o&s*2;var d2=o-s*3;var d3=o|s*4;if(o>s){return d0+"item-1"}return [o,s,d1,d2].join(",")}function m2(l,o){var n0=l*o*2;var n1=l+o*3;var n2=l-o*4;var n3=l-o*5;if(l>o){return n0+"item-2"}return [l,o,n1,n2].join(",")}function m3(i,d){var o0=i|d*3;var o1=i|d*4;var o2=i*d*5;var o3=i+d*6;if(i>d){return o0+"item-3"}return [i,d,o1,o2].join(",")}function m4(t,l){var n0=t|l*4;var n1=t-l*5;var n2=t|l*6;var n3=t+l*7;if(t>l){return n0+"item-4"}return [t,l,n1,n2].join(",")}function m5(s,r){var o0=s&r*5;var o1=s-r*6;var o2=s-r*7;var o3=s-r*8;if(s>r){return o0+"item-5"}return [s,r,o1,o2].join(",")}function m6(o,n){var s0=o&n*6;var s1=o+n*7;var s2=o&n*8;var s3=o|n*9;if(o>n){return s0+"item-6"}return [o,n,s1,s2].join(",")}function m7(s,d){var f0=s-d*0;var f1=s&d*1;var f2=s-d*2;var f3=s&d*3;if(s>d){return f0+"item-7"}return [s,d,f1,f2].join(",")}function m8(n,b){var e0=n|b*1;var e1=n-b*2;var e2=n-b*3;var e3=n-b*4;if(n>b){return e0+"item-8"}return [n,b,e1,e2].join(",")}function m9(n,t){var r0=n*t*2;var r1=n|t*3;var r2=n&t*4;var r3=n|t*5;if(n>t){return r0+"item-9"}return [n,t,r1,r2].join(",")}function m10(i,f){var l0=i|f*3;var l1=i&f*4;var l2=i*f*5;var l3=i|f*6;if(i>f){return l0+"item-10"}return [i,f,l1,l2].join(",")}function m11(o,f){var l0=o*f*4;var l1=o*f*5;var l2=o|f*6;var l3=o-f*7;if(o>f){return l0+"item-11"}return [o,f,l1,l2].join(",")}function m12(u,f){var i0=u|f*5;var i1=u*f*6;var i2=u-f*7;var i3=u&f*8;if(u>f){return i0+"item-12"}return [u,f,i1,i2].join(",")}function m13(u,c){var n0=u+c*6;var n1=u*c*7;var n2=u-c*8;var n3=u|c*9;if(u>c){return n0+"item-13"}return [u,c,n1,n2].join(",")}function m14(b,c){var e0=b-c*0;var e1=b-c*1;var e2=b&c*2;var e3=b*c*3;if(b>c){return e0+"item-14"}return [b,c,e1,e2].join(",")}function m15(o,l){var e0=o+l*1;var e1=o+l*2;var e2=o|l*3;var e3=o-l*4;if(o>l){return e0+"item-15"}return [o,l,e1,e2].join(",")}function m16(d,f){var u0=d-f*2;var u1=d&f*3;var u2=d*f*4;var u3=d*f*5;if(d>f){return u0+"item-16"}return [d,f,u1,u2].join(",")}function m17(e,f){var n0=e|f*3;var n1=e&f*4;var n2=e+f*5;var n3=e&f*6;if(e>f){return n0+"item-17"}return [e,f,n1,n2].join(",")}function m18(n,c){var o0=n&c*4;var o1=n-c*5;var o2=n*c*6;var o3=n&c*7;if(n>c){return o0+"item-18"}return [n,c,o1,o2].join(",")}function m19(o,d){var e0=o*d*5;var e1=o&d*6;var e2=o*d*7;var e3=o|d*8;if(o>d){return e0+"item-19"}return [o,d,e1,e2].join(",")}function m20(b,o){var u0=b+o*6;var u1=b+o*7;var u2=b|o*8;var u3=b*o*9;if(b>o){return u0+"item-20"}return [b,o,u1,u2].join(",")}function m21(c,a){var e0=c|a*0;var e1=c+a*1;var e2=c&a*2;var e3=c&a*3;if(c>a){return e0+"item-21"}return [c,a,e1,e2].join(",")}function m22(r,l){var o0=r&l*1;var o1=r-l*2;var o2=r-l*3;var o3=r-l*4;if(r>l){return o0+"item-22"}return [r,l,o1,o2].join(",")}function m23(n,e){var t0=n|e*2;var t1=n|e*3;var t2=n-e*4;var t3=n|e*5;if(n>e){return t0+"item-23"}return [n,e,t1,t2].join(",")}function m24(b,c){var u0=b*c*3;var u1=b-c*4;var u2=b*c*5;var u3=b&c*6;if(b>c){return u0+"item-24"}return [b,c,u1,u2].join(",")}function m25(u,i){var t0=u-i*4;var t1=u-i*5;var t2=u*i*6;var t3=u*i*7;if(u>i){return t0+"item-25"}return [u,i,t1,t2].join(",")}function m26(a,b){var u0=a+b*5;var u1=a+b*6;var u2=a-b*7;var u3=a|b*8;if(a>b){return u0+"item-26"}return [a,b,u1,u2].join(",")}function m27(i,d){var u0=i&d*6;var u1=i+d*7;var u2=i&d*8;var u3=i+d*9;if(i>d){return u0+"item-27"}return [i,d,u1,u2].join(",")}function m28(l,n){var s0=l|n*0;var s1=l&n*1;var s2=l*n*2;var s3=l+n*3;if(l>n){return s0+"item-28"}return [l,n,s1,s2].join(",")}function m29(s,i){var a0=s+i*1;var a1=s*i*2;var a2=s-i*3;var a3=s*i*4;if(s>i){return a0+"item-29"}return [s,i,a1,a2].join(",")}function m30(t,l){var u0=t|l*2;var u1=t&l*3;var u2=t|l*4;var u3=t&l*5;if(t>l){return u0+"item-30"}return [t,l,u1,u2].join(",")}function m31(i,r){var s0=i*r*3;var s1=i|r*4;var s2=i-r*5;var s3=i|r*6;if(i>r){return s0+"item-31"}return [i,r,s1,s2].join(",")}function m32(b,a){var i0=b-a*4;var i1=b*a*5;var i2=b-a*6;var i3=b&a*7;if(b>a){return i0+"item-32"}return [b,a,i1,i2].join(",")}function m33(o,r){var d0=o+r*5;var d1=o*r*6;var d2=o&r*7;var d3=o-r*8;if(o>r){return d0+"item-33"}return [o,r,d1,d2].join(",")}function m34(l,c){var i0=l+c*6;var i1=l*c*7;var i2=l+c*8;var i3=l*c*9;if(l>c){return i0+"item-34"}return [l,c,i1,i2].join(",")}function m35(e,c){var r0=e+c*0;var r1=e-c*1;var r2=e|c*2;var r3=e+c*3;if(e>c){return r0+"item-35"}return [e,c,r1,r2].join(",")}function m36(u,o){var c0=u-o*1;var c1=u+o*2;var c2=u+o*3;var c3=u+o*4;if(u>o){return c0+"item-36"}return [u,o,c1,c2].join(",")}function m37(s,r){var l0=s*r*2;var l1=s|r*3;var l2=s|r*4;var l3=s*r*5;if(s>r){return l0+"item-37"}return [s,r,l1,l2].join(",")}function m38(l,c){var t0=l+c*3;var t1=l-c*4;var t2=l+c*5;var t3=l-c*6;if(l>c){return t0+"item-38"}return [l,c,t1,t2].join(",")}function m39(e,n){var l0=e&n*4;var l1=e*n*5;var l2=e|n*6;var l3=e&n*7;if(e>n){return l0+"item-39"}return [e,n,l1,l2].join(",")}function m40(e,u){var i0=e*u*5;var i1=e*u*6;var i2=e-u*7;var i3=e+u*8;if(e>u){return i0+"item-40"}return [e,u,i1,i2].join(",")}function m41(e,o){var i0=e*o*6;var i1=e&o*7;var i2=e*o*8;var i3=e*o*9;if(e>o){return i0+"item-41"}return [e,o,i1,i2].join(",")}function m42(t,u){var f0=t|u*0;var f1=t|u*1;var f2=t&u*2;var f3=t*u*3;if(t>u){return f0+"item-42"}return [t,u,f1,f2].join(",")}function m43(s,f){var i0=s|f*1;var i1=s*f*2;var i2=s-f*3;var i3=s+f*4;if(s>f){return i0+"item-43"}return [s,f,i1,i2].join(",")}function m44(t,r){var l0=t+r*2;var l1=t+r*3;var l2=t+r*4;var l3=t|r*5;if(t>r){return l0+"item-44"}return [t,r,l1,l2].join(",")}function m45(e,t){var d0=e+t*3;var d1=e+t*4;var d2=e+t*5;var d3=e|t*6;if(e>t){return d0+"item-45"}return [e,t,d1,d2].join(",")}function m46(e,l){var i0=e-l*4;var i1=e-l*5;var i2=e*l*6;var i3=e+l*7;if(e>l){return i0+"item-46"}return [e,l,i1,i2].join(",")}function m47(t,a){var e0=t+a*5;var e1=t*a*6;var e2=t-a*7;var e3=t-a*8;if(t>a){return e0+"item-47"}return [t,a,e1,e2].join(",")}function m48(i,c){var u0=i-c*6;var u1=i+c*7;var u2=i&c*8;var u3=i&c*9;if(i>c){return u0+"item-48"}return [i,c,u1,u2].join(",")}function m49(o,i){var n0=o-i*0;var n1=o&i*1;var n2=o|i*2;var n3=o+i*3;if(o>i){return n0+"item-49"}return [o,i,n1,n2].join(",")}function m50(t,b){var s0=t|b*1;var s1=t|b*2;var s2=t*b*3;var s3=t|b*4;if(t>b){return s0+"item-50"}return [t,b,s1,s2].join(",")}function m51(f,b){var u0=f+b*2;var u1=f-b*3;var u2=f*b*4;var u3=f|b*5;if(f>b){return u0+"item-51"}return [f,b,u1,u2].join(",")}function m52(t,a){var l0=t|a*3;var l1=t&a*4;var l2=t+a*5;var l3=t|a*6;if(t>a){return l0+"item-52"}return [t,a,l1,l2].join(",")}function m53(t,a){var d0=t|a*4;var d1=t&a*5;var d2=t|a*6;var d3=t&a*7;if(t>a){return d0+"item-53"}return [t,a,d1,d2].join(",")}function m54(f,c){var u0=f+c*5;var u1=f&c*6;var u2=f|c*7;var u3=f&c*8;if(f>c){return u0+"item-54"}return [f,c,u1,u2].join(",")}function m55(i,r){var l0=i+r*6;var l1=i+r*7;var l2=i+r*8;var l3=i+r*9;if(i>r){return l0+"item-55"}return [i,r,l1,l2].join(",")}function m56(f,n){var b0=f|n*0;var b1=f+n*1;var b2=f-n*2;var b3=f*n*3;if(f>n){return b0+"item-56"}return [f,n,b1,b2].join(",")}function m57(l,a){var f0=l-a*1;var f1=l+a*2;var f2=l+a*3;var f3=l-a*4;if(l>a){return f0+"item-57"}return [l,a,f1,f2].join(",")}function m58(n,t){var i0=n*t*2;var i1=n*t*3;var i2=n|t*4;var i3=n&t*5;if(n>t){return i0+"item-58"}return [n,t,i1,i2].join(",")}function m59(b,e){var l0=b&e*3;var l1=b&e*4;var l2=b+e*5;var l3=b+e*6;if(b>e){return l0+"item-59"}return [b,e,l1,l2].join(",")}function m60(n,i){var a0=n|i*4;var a1=n+i*5;var a2=n*i*6;var a3=n|i*7;if(n>i){return a0+"item-60"}return [n,i,a1,a2].join(",")}function m61(b,u){var c0=b&u*5;var c1=b-u*6;var c2=b-u*7;var c3=b-u*8;if(b>u){return c0+"item-61"}return [b,u,c1,c2].join(",")}function m62(c,t){var f0=c-t*6;var f1=c*t*7;var f2=c+t*8;var f3=c&t*9;if(c>t){return f0+"item-62"}return [c,t,f1,f2].join(",")}function m63(t,o){var d0=t*o*0;var d1=t+o*1;var d2=t+o*2;var d3=t+o*3;if(t>o){return d0+"item-63"}return [t,o,d1,d2].join(",")}function m64(t,i){var a0=t&i*1;var a1=t&i*2;var a2=t&i*3;var a3=t-i*4;if(t>i){return a0+"item-64"}return [t,i,a1,a2].join(",")}function m65(a,d){var s0=a&d*2;var s1=a&d*3;var s2=a&d*4;var s3=a-d*5;if(a>d){return s0+"item-65"}return [a,d,s1,s2].join(",")}function m66(b,d){var f0=b&d*3;var f1=b+d*4;var f2=b*d*5;var f3=b+d*6;if(b>d){return f0+"item-66"}return [b,d,f1,f2].join(",")}function m67(d,f){var n0=d|f*4;var n1=d*f*5;var n2=d*f*6;var n3=d+f*7;if(d>f){return n0+"item-67"}return [d,f,n1,n2].join(",")}function m68(e,r){var i0=e*r*5;var i1=e&r*6;var i2=e|r*7;var i3=e|r*8;if(e>r){return i0+"item-68"}return [e,r,i1,i2].join(",")}function m69(t,a){var l0=t*a*6;var l1=t*a*7;var l2=t+a*8;var l3=t-a*9;if(t>a){return l0+"item-69"}return [t,a,l1,l2].join(",")}function m70(u,b){var i0=u*b*0;var i1=u*b*1;var i2=u|b*2;var i3=u-b*3;if(u>b){return i0+"item-70"}return [u,b,i1,i2].join(",")}function m71(s,f){var e0=s*f*1;var e1=s+f*2;var e2=s|f*3;var e3=s&f*4;if(s>f){return e0+"item-71"}return [s,f,e1,e2].join(",")}function m72(i,a){var c0=i&a*2;var c1=i|a*3;var c2=i&a*4;var c3=i|a*5;if(i>a){return c0+"item-72"}return [i,a,c1,c2].join(",")}function m73(t,l){var o0=t+l*3;var o1=t+l*4;var o2=t+l*5;var o3=t-l*6;if(t>l){return o0+"item-73"}return [t,l,o1,o2].join(",")}function m74(i,u){var t0=i&u*4;var t1=i*u*5;var t2=i|u*6;var t3=i&u*7;if(i>u){return t0+"item-74"}return [i,u,t1,t2].join(",")}function m75(e,l){var c0=e+l*5;var c1=e|l*6;var c2=e|l*7;var c3=e-l*8;if(e>l){return c0+"item-75"}return [e,l,c1,c2].join(",")}function m76(d,n){var c0=d+n*6;var c1=d-n*7;var c2=d+n*8;var c3=d|n*9;if(d>n){return c0+"item-76"}return [d,n,c1,c2].join(",")}function m77(s,e){var d0=s-e*0;var d1=s-e*1;var d2=s&e*2;var d3=s&e*3;if(s>e){return d0+"item-77"}return [s,e,d1,d2].join(",")}function m78(i,u){var t0=i&u*1;var t1=i|u*2;var t2=i&u*3;var t3=i&u*4;if(i>u){return t0+"item-78"}return [i,u,t1,t2].join(",")}function m79(l,f){var d0=l-f*2;var d1=l|f*3;var d2=l+f*4;var d3=l+f*5;if(l>f){return d0+"item-79"}return [l,f,d1,d2].join(",")}function m80(o,l){var b0=o|l*3;var b1=o-l*4;var b2=o-l*5;var b3=o+l*6;if(o>l){return b0+"item-80"}return [o,l,b1,b2].join(",")}function m81(n,r){var l0=n+r*4;var l1=n|r*5;var l2=n-r*6;var l3=n*r*7;if(n>r){return l0+"item-81"}return [n,r,l1,l2].join(",")}function m82(a,l){var e0=a&l*5;var e1=a+l*6;var e2=a-l*7;var e3=a+l*8;if(a>l){return e0+"item-82"}return [a,l,e1,e2].join(",")}function m83(d,c){var e0=d&c*6;var e1=d|c*7;var e2=d&c*8;var e3=d*c*9;if(d>c){return e0+"item-83"}return [d,c,e1,e2].join(",")}function m84(b,n){var u0=b*n*0;var u1=b*n*1;var u2=b&n*2;var u3=b+n*3;if(b>n){return u0+"item-84"}return [b,n,u1,u2].join(",")}function m85(i,f){var u0=i&f*1;var u1=i&f*2;var u2=i+f*3;var u3=i*f*4;if(i>f){return u0+"item-85"}return [i,f,u1,u2].join(",")}function m86(c,i){var d0=c*i*2;var d1=c-i*3;var d2=c&i*4;var d3=c-i*5;if(c>i){return d0+"item-86"}return [c,i,d1,d2].join(",")}function m87(c,u){var o0=c-u*3;var o1=c+u*4;var o2=c&u*5;var o3=c|u*6;if(c>u){return o0+"item-87"}return [c,u,o1,o2].join(",")}function m88(r,o){var d0=r+o*4;var d1=r*o*5;var d2=r*o*6;var d3=r+o*7;if(r>o){return d0+"item-88"}return [r,o,d1,d2].join(",")}function m89(b,e){var s0=b-e*5;var s1=b|e*6;var s2=b*e*7;var s3=b+e*8;if(b>e){return s0+"item-89"}return [b,e,s1,s2].join(",")}function m90(d,u){var o0=d&u*6;var o1=d&u*7;var o2=d|u*8;var o3=d|u*9;if(d>u){return o0+"item-90"}return [d,u,o1,o2].join(",")}function m91(u,s){var n0=u*s*0;var n1=u|s*1;var n2=u&s*2;var n3=u*s*3;if(u>s){return n0+"item-91"}return [u,s,n1,n2].join(",")}function m92(n,u){var l0=n+u*1;var l1=n-u*2;var l2=n|u*3;var l3=n-u*4;if(n>u){return l0+"item-92"}return [n,u,l1,l2].join(",")}function m93(f,c){var d0=f+c*2;var d1=f&c*3;var d2=f*c*4;var d3=f+c*5;if(f>c){return d0+"item-93"}return [f,c,d1,d2].join(",")}function m94(l,n){var f0=l*n*3;var f1=l+n*4;var f2=l-n*5;var f3=l-n*6;if(l>n){return f0+"item-94"}return [l,n,f1,f2].join(",")}function m95(f,c){var a0=f|c*4;var a1=f|c*5;var a2=f*c*6;var a3=f+c*7;if(f>c){return a0+"item-95"}return [f,c,a1,a2].join(",")}function m96(f,u){var d0=f&u*5;var d1=f-u*6;var d2=f-u*7;var d3=f+u*8;if(f>u){return d0+"item-96"}return [f,u,d1,d2].join(",")}function m97(s,c){var t0=s+c*6;var t1=s*c*7;var t2=s|c*8;var t3=s-c*9;if(s>c){return t0+"item-97"}return [s,c,t1,t2].join(",")}function m98(s,o){var b0=s|o*0;var b1=s*o*1;var b2=s|o*2;var b3=s&o*3;if(s>o){return b0+"item-98"}return [s,o,b1,b2].join(",")}function m99(u,i){var d0=u|i*1;var d1=u|i*2;var d2=u|i*3;var d3=u&i*4;if(u>i){return d0+"item-99"}return [u,i,d1,d2].join(",")}function m100(o,s){var l0=o+s*2;var l1=o&s*3;var l2=o*s*4;var l3=o&s*5;if(o>s){return l0+"item-100"}return [o,s,l1,l2].join(",")}function m101(i,b){var l0=i&b*3;var l1=i+b*4;var l2=i*b*5;var l3=i+b*6;if(i>b){return l0+"item-101"}return [i,b,l1,l2].join(",")}function m102(t,o){var d0=t+o*4;var d1=t+o*5;var d2=t+o*6;var d3=t&o*7;if(t>o){return d0+"item-102"}return [t,o,d1,d2].join(",")}function m103(t,e){var d0=t+e*5;var d1=t|e*6;var d2=t-e*7;var d3=t&e*8;if(t>e){return d0+"item-103"}return [t,e,d1,d2].join(",")}function m104(i,u){var c0=i*u*6;var c1=i*u*7;var c2=i+u*8;var c3=i|u*9;if(i>u){return c0+"item-104"}return [i,u,c1,c2].join(",")}function m105(r,i){var e0=r+i*0;var e1=r&i*1;var e2=r*i*2;var e3=r|i*3;if(r>i){return e0+"item-105"}return [r,i,e1,e2].join(",")}function m106(a,d){var l0=a*d*1;var l1=a-d*2;var l2=a&d*3;var l3=a-d*4;if(a>d){return l0+"item-106"}return [a,d,l1,l2].join(",")}function m107(u,s){var o0=u|s*2;var o1=u-s*3;var o2=u|s*4;var o3=u*s*5;if(u>s){return o0+"item-107"}return [u,s,o1,o2].join(",")}function m108(b,c){var f0=b*c*3;var f1=b|c*4;var f2=b-c*5;var f3=b-c*6;if(b>c){return f0+"item-108"}return [b,c,f1,f2].join(",")}function m109(o,u){var t0=o+u*4;var t1=o+u*5;var t2=o|u*6;var t3=o*u*7;if(o>u){return t0+"item-109"}return [o,u,t1,t2].join(",")}function m110(b,s){var d0=b-s*5;var d1=b*s*6;var d2=b&s*7;var d3=b&s*8;if(b>s){return d0+"item-110"}return [b,s,d1,d2].join(",")}function m111(c,u){var a0=c&u*6;var a1=c-u*7;var a2=c*u*8;var a3=c|u*9;if(c>u){return a0+"item-111"}return [c,u,a1,a2].join(",")}function m112(a,n){var d0=a&n*0;var d1=a+n*1;var d2=a-n*2;var d3=a-n*3;if(a>n){return d0+"item-112"}return [a,n,d1,d2].join(",")}function m113(c,o){var u0=c&o*1;var u1=c-o*2;var u2=c+o*3;var u3=c+o*4;if(c>o){return u0+"item-113"}return [c,o,u1,u2].join(",")}function m114(e,l){var i0=e-l*2;var i1=e&l*3;var i2=e|l*4;var i3=e-l*5;if(e>l){return i0+"item-114"}return [e,l,i1,i2].join(",")}function m115(l,o){var r0=l+o*3;var r1=l+o*4;var r2=l&o*5;var r3=l*o*6;if(l>o){return r0+"item-115"}return [l,o,r1,r2].join(",")}function m116(s,l){var i0=s-l*4;var i1=s|l*5;var i2=s*l*6;var i3=s*l*7;if(s>l){return i0+"item-116"}return [s,l,i1,i2].join(",")}function m117(t,e){var s0=t-e*5;var s1=t&e*6;var s2=t|e*7;var s3=t&e*8;if(t>e){return s0+"item-117"}return [t,e,s1,s2].join(",")}function m118(r,o){var i0=r*o*6;var i1=r|o*7;var i2=r+o*8;var i3=r+o*9;if(r>o){return i0+"item-118"}return [r,o,i1,i2].join(",")}function m119(b,t){var o0=b-t*0;var o1=b|t*1;var o2=b-t*2;var o3=b&t*3;if(b>t){return o0+"item-119"}return [b,t,o1,o2].join(",")}function m120(t,e){var o0=t+e*1;var o1=t&e*2;var o2=t|e*3;var o3=t+e*4;if(t>e){return o0+"item-120"}return [t,e,o1,o2].join(",")}function m121(f,d){var c0=f-d*2;var c1=f|d*3;var c2=f-d*4;var c3=f*d*5;if(f>d){return c0+"item-121"}return [f,d,c1,c2].join(",")}function m122(f,t){var i0=f|t*3;var i1=f+t*4;var i2=f+t*5;var i3=f-t*6;if(f>t){return i0+"item-122"}return [f,t,i1,i2].join(",")}function m123(b,n){var a0=b|n*4;var a1=b*n*5;var a2=b&n*6;var a3=b+n*7;if(b>n){return a0+"item-123"}return [b,n,a1,a2].join(",")}function m124(e,b){var l0=e+b*5;var l1=e*b*6;var l2=e-b*7;var l3=e*b*8;if(e>b){return l0+"item-124"}return [e,b,l1,l2].join(",")}function m125(l,o){var i0=l*o*6;var i1=l+o*7;var i2=l+o*8;var i3=l-o*9;if(l>o){return i0+"item-125"}return [l,o,i1,i2].join(",")}function m126(o,u){var d0=o-u*0;var d1=o*u*1;var d2=o-u*2;var d3=o|u*3;if(o>u){return d0+"item-126"}return [o,u,d1,d2].join(",")}function m127(r,e){var d0=r+e*1;var d1=r+e*2;var d2=r&e*3;var d3=r&e*4;if(r>e){return d0+"item-127"}return [r,e,d1,d2].join(",")}function m128(n,d){var e0=n+d*2;var e1=n&d*3;var e2=n*d*4;var e3=n|d*5;if(n>d){return e0+"item-128"}return [n,d,e1,e2].join(",")}function m129(i,s){var e0=i+s*3;var e1=i*s*4;var e2=i-s*5;var e3=i&s*6;if(i>s){return e0+"item-129"}return [i,s,e1,e2].join(",")}function m130(i,l){var e0=i|l*4;var e1=i-l*5;var e2=i+l*6;var e3=i-l*7;if(i>l){return e0+"item-130"}return [i,l,e1,e2].join(",")}function m131(e,r){var a0=e+r*5;var a1=e*r*6;var a2=e&r*7;var a3=e|r*8;if(e>r){return a0+"item-131"}return [e,r,a1,a2].join(",")}function m132(c,t){var n0=c*t*6;var n1=c|t*7;var n2=c+t*8;var n3=c&t*9;if(c>t){return n0+"item-132"}return [c,t,n1,n2].join(",")}function m133(a,f){var s0=a+f*0;var s1=a+f*1;var s2=a*f*2;var s3=a+f*3;if(a>f){return s0+"item-133"}return [a,f,s1,s2].join(",")}function m134(d,l){var a0=d+l*1;var a1=d*l*2;var a2=d+l*3;var a3=d*l*4;if(d>l){return a0+"item-134"}return [d,l,a1,a2].join(",")}function m135(d,o){var c0=d&o*2;var c1=d|o*3;var c2=d|o*4;var c3=d&o*5;if(d>o){return c0+"item-135"}return [d,o,c1,c2].join(",")}function m136(d,t){var c0=d+t*3;var c1=d-t*4;var c2=d-t*5;var c3=d*t*6;if(d>t){return c0+"item-136"}return [d,t,c1,c2].join(",")}function m137(f,c){var a0=f*c*4;var a1=f&c*5;var a2=f+c*6;var a3=f+c*7;if(f>c){return a0+"item-137"}console.log("changed 137",f);return [f,c,a1,a2].join(",")}function m138(o,u){var r0=o*u*5;var r1=o-u*6;var r2=o+u*7;var r3=o+u*8;if(o>u){return r0+"item-138"}return [o,u,r1,r2].join(",")}function m139(i,l){var t0=i+l*6;var t1=i|l*7;var t2=i*l*8;var t3=i|l*9;if(i>l){return t0+"item-139"}return [i,l,t1,t2].join(",")}function m140(e,u){var l0=e-u*0;var l1=e|u*1;var l2=e-u*2;var l3=e+u*3;if(e>u){return l0+"item-140"}return [e,u,l1,l2].join(",")}function m141(a,o){var f0=a*o*1;var f1=a+o*2;var f2=a*o*3;var f3=a-o*4;if(a>o){return f0+"item-141"}return [a,o,f1,f2].join(",")}function m142(s,t){var u0=s&t*2;var u1=s*t*3;var u2=s*t*4;var u3=s&t*5;if(s>t){return u0+"item-142"}return [s,t,u1,u2].join(",")}function m143(i,l){var o0=i|l*3;var o1=i+l*4;var o2=i|l*5;var o3=i*l*6;if(i>l){return o0+"item-143"}return [i,l,o1,o2].join(",")}function m144(a,c){var l0=a&c*4;var l1=a&c*5;var l2=a&c*6;var l3=a+c*7;if(a>c){return l0+"item-144"}return [a,c,l1,l2].join(",")}function m145(i,a){var f0=i+a*5;var f1=i|a*6;var f2=i*a*7;var f3=i|a*8;if(i>a){return f0+"item-145"}return [i,a,f1,f2].join(",")}function m146(r,n){var t0=r-n*6;var t1=r|n*7;var t2=r*n*8;var t3=r&n*9;if(r>n){return t0+"item-146"}return [r,n,t1,t2].join(",")}function m147(c,l){var d0=c|l*0;var d1=c-l*1;var d2=c|l*2;var d3=c-l*3;if(c>l){return d0+"item-147"}return [c,l,d1,d2].join(",")}function m148(a,r){var u0=a+r*1;var u1=a-r*2;var u2=a|r*3;var u3=a*r*4;if(a>r){return u0+"item-148"}return [a,r,u1,u2].join(",")}function m149(s,i){var e0=s|i*2;var e1=s|i*3;var e2=s+i*4;var e3=s+i*5;if(s>i){return e0+"item-149"}return [s,i,e1,e2].join(",")}function m150(b,i){var o0=b|i*3;var o1=b+i*4;var o2=b&i*5;var o3=b-i*6;if(b>i){return o0+"item-150"}return [b,i,o1,o2].join(",")}function m151(c,t){var u0=c+t*4;var u1=c-t*5;var u2=c+t*6;var u3=c&t*7;if(c>t){return u0+"item-151"}return [c,t,u1,u2].join(",")}function m152(i,t){var d0=i-t*5;var d1=i&t*6;var d2=i*t*7;var d3=i&t*8;if(i>t){return d0+"item-152"}return [i,t,d1,d2].join(",")}function m153(u,a){var o0=u|a*6;var o1=u|a*7;var o2=u+a*8;var o3=u+a*9;if(u>a){return o0+"item-153"}return [u,a,o1,o2].join(",")}function m154(e,i){var u0=e+i*0;var u1=e*i*1;var u2=e|i*2;var u3=e-i*3;if(e>i){return u0+"item-154"}return [e,i,u1,u2].join(",")}function m155(i,l){var t0=i*l*1;var t1=i&l*2;var t2=i+l*3;var t3=i&l*4;if(i>l){return t0+"item-155"}return [i,l,t1,t2].join(",")}function m156(n,i){var b0=n*i*2;var b1=n|i*3;var b2=n*i*4;var b3=n&i*5;if(n>i){return b0+"item-156"}return [n,i,b1,b2].join(",")}function m157(b,l){var d0=b|l*3;var d1=b&l*4;var d2=b-l*5;var d3=b*l*6;if(b>l){return d0+"item-157"}return [b,l,d1,d2].join(",")}function m158(e,f){var t0=e&f*4;var t1=e+f*5;var t2=e|f*6;var t3=e&f*7;if(e>f){return t0+"item-158"}return [e,f,t1,t2].join(",")}function m159(r,i){var e0=r&i*5;var e1=r*i*6;var e2=r|i*7;var e3=r+i*8;if(r>i){return e0+"item-159"}return [r,i,e1,e2].join(",")}function m160(r,f){var l0=r+f*6;var l1=r*f*7;var l2=r|f*8;var l3=r|f*9;if(r>f){return l0+"item-160"}return [r,f,l1,l2].join(",")}function m161(s,l){var c0=s*l*0;var c1=s+l*1;var c2=s+l*2;var c3=s-l*3;if(s>l){return c0+"item-161"}return [s,l,c1,c2].join(",")}function m162(e,r){var t0=e+r*1;var t1=e+r*2;var t2=e+r*3;var t3=e+r*4;if(e>r){return t0+"item-162"}return [e,r,t1,t2].join(",")}function m163(f,t){var o0=f-t*2;var o1=f|t*3;var o2=f*t*4;var o3=f-t*5;if(f>t){return o0+"item-163"}return [f,t,o1,o2].join(",")}function m164(f,t){var l0=f|t*3;var l1=f-t*4;var l2=f|t*5;var l3=f-t*6;if(f>t){return l0+"item-164"}return [f,t,l1,l2].join(",")}function m165(a,c){var b0=a&c*4;var b1=a-c*5;var b2=a+c*6;var b3=a|c*7;if(a>c){return b0+"item-165"}return [a,c,b1,b2].join(",")}function m166(s,r){var n0=s+r*5;var n1=s+r*6;var n2=s&r*7;var n3=s*r*8;if(s>r){return n0+"item-166"}return [s,r,n1,n2].join(",")}function m167(i,n){var u0=i-n*6;var u1=i|n*7;var u2=i+n*8;var u3=i-n*9;if(i>n){return u0+"item-167"}return [i,n,u1,u2].join(",")}function m168(a,r){var i0=a-r*0;var i1=a&r*1;var i2=a|r*2;var i3=a+r*3;if(a>r){return i0+"item-168"}return [a,r,i1,i2].join(",")}function m169(a,o){var b0=a&o*1;var b1=a-o*2;var b2=a+o*3;var b3=a-o*4;if(a>o){return b0+"item-169"}return [a,o,b1,b2].join(",")}function m170(e,c){var d0=e+c*2;var d1=e+c*3;var d2=e&c*4;var d3=e&c*5;if(e>c){return d0+"item-170"}return [e,c,d1,d2].join(",")}function m171(e,c){var l0=e-c*3;var l1=e+c*4;var l2=e|c*5;var l3=e|c*6;if(e>c){return l0+"item-171"}return [e,c,l1,l2].join(",")}function m172(n,a){var i0=n-a*4;var i1=n-a*5;var i2=n*a*6;var i3=n|a*7;if(n>a){return i0+"item-172"}return [n,a,i1,i2].join(",")}function m173(r,a){var c0=r-a*5;var c1=r-a*6;var c2=r|a*7;var c3=r&a*8;if(r>a){return c0+"item-173"}return [r,a,c1,c2].join(",")}function m174(c,i){var u0=c+i*6;var u1=c|i*7;var u2=c+i*8;var u3=c*i*9;if(c>i){return u0+"item-174"}return [c,i,u1,u2].join(",")}function m175(e,l){var t0=e-l*0;var t1=e+l*1;var t2=e-l*2;var t3=e*l*3;if(e>l){return t0+"item-175"}return [e,l,t1,t2].join(",")}function m176(c,b){var a0=c+b*1;var a1=c|b*2;var a2=c-b*3;var a3=c|b*4;if(c>b){return a0+"item-176"}return [c,b,a1,a2].join(",")}function m177(s,l){var c0=s+l*2;var c1=s+l*3;var c2=s-l*4;var c3=s|l*5;if(s>l){return c0+"item-177"}return [s,l,c1,c2].join(",")}function m178(e,c){var l0=e*c*3;var l1=e&c*4;var l2=e&c*5;var l3=e&c*6;if(e>c){return l0+"item-178"}return [e,c,l1,l2].join(",")}function m179(c,r){var u0=c&r*4;var u1=c|r*5;var u2=c-r*6;var u3=c|r*7;if(c>r){return u0+"item-179"}return [c,r,u1,u2].join(",")}function m180(u,a){var n0=u|a*5;var n1=u-a*6;var n2=u+a*7;var n3=u-a*8;if(u>a){return n0+"item-180"}return [u,a,n1,n2].join(",")}function m181(r,e){var i0=r-e*6;var i1=r*e*7;var i2=r|e*8;var i3=r|e*9;if(r>e){return i0+"item-181"}return [r,e,i1,i2].join(",")}function m182(b,s){var d0=b|s*0;var d1=b|s*1;var d2=b|s*2;var d3=b-s*3;if(b>s){return d0+"item-182"}return [b,s,d1,d2].join(",")}function m183(l,i){var r0=l+i*1;var r1=l&i*2;var r2=l+i*3;var r3=l-i*4;if(l>i){return r0+"item-183"}return [l,i,r1,r2].join(",")}function m184(t,d){var s0=t*d*2;var s1=t&d*3;var s2=t&d*4;var s3=t*d*5;if(t>d){return s0+"item-184"}return [t,d,s1,s2].join(",")}function m185(i,n){var e0=i*n*3;var e1=i-n*4;var e2=i*n*5;var e3=i+n*6;if(i>n){return e0+"item-185"}return [i,n,e1,e2].join(",")}function m186(o,a){var c0=o*a*4;var c1=o-a*5;var c2=o+a*6;var c3=o*a*7;if(o>a){return c0+"item-186"}return [o,a,c1,c2].join(",")}function m187(r,l){var i0=r&l*5;var i1=r|l*6;var i2=r+l*7;var i3=r|l*8;if(r>l){return i0+"item-187"}return [r,l,i1,i2].join(",")}function m188(c,s){var n0=c+s*6;var n1=c&s*7;var n2=c+s*8;var n3=c+s*9;if(c>s){return n0+"item-188"}return [c,s,n1,n2].join(",")}function m189(u,o){var r0=u+o*0;var r1=u+o*1;var r2=u*o*2;var r3=u+o*3;if(u>o){return r0+"item-189"}return [u,o,r1,r2].join(",")}function m190(t,e){var n0=t&e*1;var n1=t&e*2;var n2=t|e*3;var n3=t-e*4;if(t>e){return n0+"item-190"}return [t,e,n1,n2].join(",")}function m191(d,u){var t0=d-u*2;var t1=d&u*3;var t2=d*u*4;var t3=d+u*5;if(d>u){return t0+"item-191"}return [d,u,t1,t2].join(",")}function m192(s,u){var l0=s*u*3;var l1=s&u*4;var l2=s-u*5;var l3=s*u*6;if(s>u){return l0+"item-192"}return [s,u,l1,l2].join(",")}function m193(a,u){var t0=a-u*4;var t1=a*u*5;var t2=a&u*6;var t3=a-u*7;if(a>u){return t0+"item-193"}return [a,u,t1,t2].join(",")}function m194(d,f){var e0=d-f*5;var e1=d+f*6;var e2=d+f*7;var e3=d|f*8;if(d>f){return e0+"item-194"}return [d,f,e1,e2].join(",")}function m195(a,r){var f0=a|r*6;var f1=a&r*7;var f2=a*r*8;var f3=a+r*9;if(a>r){return f0+"item-195"}return [a,r,f1,f2].join(",")}function m196(a,e){var b0=a+e*0;var b1=a&e*1;var b2=a&e*2;var b3=a-e*3;if(a>e){return b0+"item-196"}return [a,e,b1,b2].join(",")}function m197(o,i){var e0=o&i*1;var e1=o&i*2;var e2=o-i*3;var e3=o*i*4;if(o>i){return e0+"item-197"}return [o,i,e1,e2].join(",")}function m198(i,o){var n0=i|o*2;var n1=i-o*3;var n2=i|o*4;var n3=i&o*5;if(i>o){return n0+"item-198"}return [i,o,n1,n2].join(",")}function m199(l,f){var b0=l&f*3;var b1=l+f*4;var b2=l|f*5;var b3=l+f*6;if(l>f){return b0+"item-199"}return [l,f,b1,b2].join(",")}function m200(l,f){var a0=l-f*4;var a1=l|f*5;var a2=l*f*6;var a3=l&f*7;if(l>f){return a0+"item-200"}return [l,f,a1,a2].join(",")}function m201(r,n){var d0=r-n*5;var d1=r|n*6;var d2=r-n*7;var d3=r+n*8;if(r>n){return d0+"item-201"}return [r,n,d1,d2].join(",")}function m202(a,o){var b0=a+o*6;var b1=a+o*7;var b2=a*o*8;var b3=a-o*9;if(a>o){return b0+"item-202"}return [a,o,b1,b2].join(",")}function m203(b,s){var d0=b&s*0;var d1=b|s*1;var d2=b+s*2;var d3=b-s*3;if(b>s){return d0+"item-203"}return [b,s,d1,d2].join(",")}function m204(c,r){var u0=c&r*1;var u1=c&r*2;var u2=c-r*3;var u3=c-r*4;if(c>r){return u0+"item-204"}return [c,r,u1,u2].join(",")}function m205(f,r){var l0=f+r*2;var l1=f+r*3;var l2=f|r*4;var l3=f*r*5;if(f>r){return l0+"item-205"}return [f,r,l1,l2].join(",")}function m206(t,u){var b0=t+u*3;var b1=t*u*4;var b2=t-u*5;var b3=t&u*6;if(t>u){return b0+"item-206"}return [t,u,b1,b2].join(",")}function m207(i,c){var e0=i&c*4;var e1=i+c*5;var e2=i&c*6;var e3=i-c*7;if(i>c){return e0+"item-207"}return [i,c,e1,e2].join(",")}function m208(d,o){var u0=d*o*5;var u1=d|o*6;var u2=d&o*7;var u3=d*o*8;if(d>o){return u0+"item-208"}return [d,o,u1,u2].join(",")}function m209(t,r){var u0=t-r*6;var u1=t+r*7;var u2=t|r*8;var u3=t|r*9;if(t>r){return u0+"item-209"}return [t,r,u1,u2].join(",")}function m210(t,d){var i0=t+d*0;var i1=t*d*1;var i2=t|d*2;var i3=t*d*3;if(t>d){return i0+"item-210"}return [t,d,i1,i2].join(",")}function m211(u,n){var e0=u-n*1;var e1=u*n*2;var e2=u*n*3;var e3=u-n*4;if(u>n){return e0+"item-211"}return [u,n,e1,e2].join(",")}function m212(f,t){var r0=f-t*2;var r1=f+t*3;var r2=f-t*4;var r3=f-t*5;if(f>t){return r0+"item-212"}return [f,t,r1,r2].join(",")}function m213(d,o){var t0=d&o*3;var t1=d-o*4;var t2=d*o*5;var t3=d|o*6;if(d>o){return t0+"item-213"}return [d,o,t1,t2].join(",")}function m214(s,c){var i0=s+c*4;var i1=s*c*5;var i2=s-c*6;var i3=s+c*7;if(s>c){return i0+"item-214"}return [s,c,i1,i2].join(",")}function m215(b,o){var c0=b|o*5;var c1=b|o*6;var c2=b&o*7;var c3=b+o*8;if(b>o){return c0+"item-215"}return [b,o,c1,c2].join(",")}function m216(a,r){var t0=a|r*6;var t1=a+r*7;var t2=a&r*8;var t3=a+r*9;if(a>r){return t0+"item-216"}return [a,r,t1,t2].join(",")}function m217(i,e){var s0=i*e*0;var s1=i*e*1;var s2=i|e*2;var s3=i&e*3;if(i>e){return s0+"item-217"}return [i,e,s1,s2].join(",")}function m218(n,c){var e0=n|c*1;var e1=n+c*2;var e2=n*c*3;var e3=n|c*4;if(n>c){return e0+"item-218"}return [n,c,e1,e2].join(",")}function m219(r,c){var u0=r-c*2;var u1=r|c*3;var u2=r+c*4;var u3=r-c*5;if(r>c){return u0+"item-219"}return [r,c,u1,u2].join(",")}function m220(s,b){var a0=s-b*3;var a1=s&b*4;var a2=s&b*5;var a3=s*b*6;if(s>b){return a0+"item-220"}return [s,b,a1,a2].join(",")}function m221(e,s){var o0=e&s*4;var o1=e+s*5;var o2=e*s*6;var o3=e&s*7;if(e>s){return o0+"item-221"}return [e,s,o1,o2].join(",")}function m222(a,i){var n0=a-i*5;var n1=a|i*6;var n2=a-i*7;var n3=a&i*8;if(a>i){return n0+"item-222"}return [a,i,n1,n2].join(",")}function m223(l,r){var d0=l-r*6;var d1=l&r*7;var d2=l&r*8;var d3=l+r*9;if(l>r){return d0+"item-223"}return [l,r,d1,d2].join(",")}function m224(s,t){var r0=s*t*0;var r1=s-t*1;var r2=s|t*2;var r3=s|t*3;if(s>t){return r0+"item-224"}return [s,t,r1,r2].join(",")}function m225(d,i){var o0=d-i*1;var o1=d*i*2;var o2=d&i*3;var o3=d*i*4;if(d>i){return o0+"item-225"}return [d,i,o1,o2].join(",")}function m226(c,o){var b0=c+o*2;var b1=c&o*3;var b2=c-o*4;var b3=c|o*5;if(c>o){return b0+"item-226"}return [c,o,b1,b2].join(",")}function m227(d,r){var s0=d*r*3;var s1=d&r*4;var s2=d|r*5;var s3=d|r*6;if(d>r){return s0+"item-227"}return [d,r,s1,s2].join(",")}function m228(d,t){var u0=d&t*4;var u1=d+t*5;var u2=d&t*6;var u3=d+t*7;if(d>t){return u0+"item-228"}return [d,t,u1,u2].join(",")}function m229(b,d){var f0=b*d*5;var f1=b+d*6;var f2=b+d*7;var f3=b&d*8;if(b>d){return f0+"item-229"}return [b,d,f1,f2].join(",")}function m230(r,d){var n0=r-d*6;var n1=r*d*7;var n2=r*d*8;var n3=r|d*9;if(r>d){return n0+"item-230"}return [r,d,n1,n2].join(",")}function m231(l,n){var f0=l+n*0;var f1=l&n*1;var f2=l-n*2;var f3=l|n*3;if(l>n){return f0+"item-231"}return [l,n,f1,f2].join(",")}function m232(l,b){var f0=l&b*1;var f1=l*b*2;var f2=l|b*3;var f3=l-b*4;if(l>b){return f0+"item-232"}return [l,b,f1,f2].join(",")}function m233(a,n){var f0=a+n*2;var f1=a*n*3;var f2=a|n*4;var f3=a-n*5;if(a>n){return f0+"item-233"}return [a,n,f1,f2].join(",")}function m234(u,c){var i0=u*c*3;var i1=u|c*4;var i2=u-c*5;var i3=u|c*6;if(u>c){return i0+"item-234"}return [u,c,i1,i2].join(",")}function m235(b,l){var t0=b*l*4;var t1=b|l*5;var t2=b|l*6;var t3=b&l*7;if(b>l){return t0+"item-235"}return [b,l,t1,t2].join(",")}function m236(f,l){var s0=f&l*5;var s1=f-l*6;var s2=f|l*7;var s3=f&l*8;if(f>l){return s0+"item-236"}return [f,l,s1,s2].join(",")}function m237(r,l){var o0=r|l*6;var o1=r-l*7;var o2=r*l*8;var o3=r*l*9;if(r>l){return o0+"item-237"}return [r,l,o1,o2].join(",")}function m238(n,i){var a0=n*i*0;var a1=n-i*1;var a2=n+i*2;var a3=n&i*3;if(n>i){return a0+"item-238"}return [n,i,a1,a2].join(",")}function m239(o,u){var f0=o-u*1;var f1=o+u*2;var f2=o+u*3;var f3=o*u*4;if(o>u){return f0+"item-239"}return [o,u,f1,f2].join(",")}function m240(l,r){var f0=l+r*2;var f1=l&r*3;var f2=l|r*4;var f3=l+r*5;if(l>r){return f0+"item-240"}return [l,r,f1,f2].join(",")}function m241(u,i){var e0=u+i*3;var e1=u&i*4;var e2=u|i*5;var e3=u&i*6;if(u>i){return e0+"item-241"}return [u,i,e1,e2].join(",")}function m242(r,o){var b0=r-o*4;var b1=r-o*5;var b2=r&o*6;var b3=r+o*7;if(r>o){return b0+"item-242"}return [r,o,b1,b2].join(",")}function m243(i,n){var o0=i&n*5;var o1=i|n*6;var o2=i|n*7;var o3=i*n*8;if(i>n){return o0+"item-243"}return [i,n,o1,o2].join(",")}function m244(r,n){var l0=r+n*6;var l1=r|n*7;var l2=r&n*8;var l3=r*n*9;if(r>n){return l0+"item-244"}return [r,n,l1,l2].join(",")}function m245(f,a){var d0=f*a*0;var d1=f*a*1;var d2=f|a*2;var d3=f+a*3;if(f>a){return d0+"item-245"}return [f,a,d1,d2].join(",")}function m246(i,f){var t0=i&f*1;var t1=i-f*2;var t2=i+f*3;var t3=i-f*4;if(i>f){return t0+"item-246"}return [i,f,t1,t2].join(",")}function m247(r,a){var d0=r-a*2;var d1=r*a*3;var d2=r+a*4;var d3=r+a*5;if(r>a){return d0+"item-247"}return [r,a,d1,d2].join(",")}function m248(u,e){var b0=u*e*3;var b1=u&e*4;var b2=u+e*5;var b3=u|e*6;if(u>e){return b0+"item-248"}return [u,e,b1,b2].join(",")}function m249(s,a){var o0=s&a*4;var o1=s-a*5;var o2=s|a*6;var o3=s-a*7;if(s>a){return o0+"item-249"}return [s,a,o1,o2].join(",")}function m250(a,b){return a===b?null:[a,b]}function m251(n,e){var a0=n*e*6;var a1=n*e*7;var a2=n*e*8;var a3=n+e*9;if(n>e){return a0+"item-251"}return [n,e,a1,a2].join(",")}function m252(a,o){var e0=a+o*0;var e1=a|o*1;var e2=a&o*2;var e3=a*o*3;if(a>o){return e0+"item-252"}return [a,o,e1,e2].join(",")}function m253(l,e){var d0=l&e*1;var d1=l|e*2;var d2=l|e*3;var d3=l|e*4;if(l>e){return d0+"item-253"}return [l,e,d1,d2].join(",")}function m254(i,b){var l0=i&b*2;var l1=i|b*3;var l2=i*b*4;var l3=i|b*5;if(i>b){return l0+"item-254"}return [i,b,l1,l2].join(",")}function m255(t,l){var d0=t+l*3;var d1=t-l*4;var d2=t&l*5;var d3=t-l*6;if(t>l){return d0+"item-255"}return [t,l,d1,d2].join(",")}function m256(s,e){var f0=s&e*4;var f1=s&e*5;var f2=s-e*6;var f3=s&e*7;if(s>e){return f0+"item-256"}return [s,e,f1,f2].join(",")}function m257(b,s){var e0=b+s*5;var e1=b&s*6;var e2=b+s*7;var e3=b|s*8;if(b>s){return e0+"item-257"}return [b,s,e1,e2].join(",")}function m258(b,i){var r0=b+i*6;var r1=b+i*7;var r2=b&i*8;var r3=b-i*9;if(b>i){return r0+"item-258"}return [b,i,r1,r2].join(",")}function m259(l,r){var i0=l-r*0;var i1=l|r*1;var i2=l|r*2;var i3=l&r*3;if(l>r){return i0+"item-259"}return [l,r,i1,i2].join(",")}function m260(o,b){var t0=o+b*1;var t1=o-b*2;var t2=o-b*3;var t3=o*b*4;if(o>b){return t0+"item-260"}return [o,b,t1,t2].join(",")}function m261(l,u){var n0=l+u*2;var n1=l-u*3;var n2=l|u*4;var n3=l*u*5;if(l>u){return n0+"item-261"}return [l,u,n1,n2].join(",")}function m262(f,l){var u0=f&l*3;var u1=f|l*4;var u2=f+l*5;var u3=f*l*6;if(f>l){return u0+"item-262"}return [f,l,u1,u2].join(",")}function m263(n,e){var u0=n|e*4;var u1=n*e*5;var u2=n-e*6;var u3=n*e*7;if(n>e){return u0+"item-263"}return [n,e,u1,u2].join(",")}function m264(i,o){var c0=i&o*5;var c1=i+o*6;var c2=i&o*7;var c3=i&o*8;if(i>o){return c0+"item-264"}return [i,o,c1,c2].join(",")}function m265(s,u){var c0=s-u*6;var c1=s|u*7;var c2=s&u*8;var c3=s|u*9;if(s>u){return c0+"item-265"}return [s,u,c1,c2].join(",")}function m266(u,i){var e0=u+i*0;var e1=u|i*1;var e2=u|i*2;var e3=u&i*3;if(u>i){return e0+"item-266"}return [u,i,e1,e2].join(",")}function m267(r,e){var o0=r&e*1;var o1=r+e*2;var o2=r|e*3;var o3=r-e*4;if(r>e){return o0+"item-267"}return [r,e,o1,o2].join(",")}function m268(d,f){var r0=d&f*2;var r1=d&f*3;var r2=d*f*4;var r3=d-f*5;if(d>f){return r0+"item-268"}return [d,f,r1,r2].join(",")}function m269(u,a){var s0=u-a*3;var s1=u+a*4;var s2=u*a*5;var s3=u+a*6;if(u>a){return s0+"item-269"}return [u,a,s1,s2].join(",")}function m270(u,e){var a0=u*e*4;var a1=u|e*5;var a2=u*e*6;var a3=u|e*7;if(u>e){return a0+"item-270"}return [u,e,a1,a2].join(",")}function m271(c,n){var f0=c+n*5;var f1=c+n*6;var f2=c+n*7;var f3=c+n*8;if(c>n){return f0+"item-271"}return [c,n,f1,f2].join(",")}function m272(t,a){var b0=t|a*6;var b1=t&a*7;var b2=t+a*8;var b3=t|a*9;if(t>a){return b0+"item-272"}return [t,a,b1,b2].join(",")}function m273(l,u){var s0=l*u*0;var s1=l*u*1;var s2=l|u*2;var s3=l&u*3;if(l>u){return s0+"item-273"}return [l,u,s1,s2].join(",")}function m274(d,f){var a0=d|f*1;var a1=d&f*2;var a2=d+f*3;var a3=d&f*4;if(d>f){return a0+"item-274"}return [d,f,a1,a2].join(",")}function m275(n,b){var a0=n*b*2;var a1=n|b*3;var a2=n|b*4;var a3=n&b*5;if(n>b){return a0+"item-275"}return [n,b,a1,a2].join(",")}function m276(b,i){var c0=b*i*3;var c1=b-i*4;var c2=b+i*5;var c3=b&i*6;if(b>i){return c0+"item-276"}return [b,i,c1,c2].join(",")}function m277(a,c){var n0=a&c*4;var n1=a*c*5;var n2=a|c*6;var n3=a-c*7;if(a>c){return n0+"item-277"}return [a,c,n1,n2].join(",")}function m278(n,e){var a0=n-e*5;var a1=n-e*6;var a2=n*e*7;var a3=n+e*8;if(n>e){return a0+"item-278"}return [n,e,a1,a2].join(",")}function m279(f,d){var o0=f|d*6;var o1=f+d*7;var o2=f&d*8;var o3=f-d*9;if(f>d){return o0+"item-279"}return [f,d,o1,o2].join(",")}function m280(c,t){var b0=c*t*0;var b1=c-t*1;var b2=c-t*2;var b3=c*t*3;if(c>t){return b0+"item-280"}return [c,t,b1,b2].join(",")}function m281(r,i){var b0=r&i*1;var b1=r|i*2;var b2=r&i*3;var b3=r-i*4;if(r>i){return b0+"item-281"}return [r,i,b1,b2].join(",")}function m282(t,f){var l0=t|f*2;var l1=t&f*3;var l2=t*f*4;var l3=t-f*5;if(t>f){return l0+"item-282"}return [t,f,l1,l2].join(",")}function m283(b,f){var c0=b-f*3;var c1=b&f*4;var c2=b-f*5;var c3=b-f*6;if(b>f){return c0+"item-283"}return [b,f,c1,c2].join(",")}function m284(t,r){var a0=t|r*4;var a1=t*r*5;var a2=t*r*6;var a3=t+r*7;if(t>r){return a0+"item-284"}return [t,r,a1,a2].join(",")}function m285(e,t){var l0=e-t*5;var l1=e&t*6;var l2=e&t*7;var l3=e|t*8;if(e>t){return l0+"item-285"}return [e,t,l1,l2].join(",")}function m286(i,c){var f0=i|c*6;var f1=i+c*7;var f2=i&c*8;var f3=i&c*9;if(i>c){return f0+"item-286"}return [i,c,f1,f2].join(",")}function m287(i,c){var u0=i|c*0;var u1=i*c*1;var u2=i*c*2;var u3=i+c*3;if(i>c){return u0+"item-287"}return [i,c,u1,u2].join(",")}function m288(d,c){var o0=d&c*1;var o1=d|c*2;var o2=d+c*3;var o3=d|c*4;if(d>c){return o0+"item-288"}return [d,c,o1,o2].join(",")}function m289(r,n){var e0=r&n*2;var e1=r*n*3;var e2=r&n*4;var e3=r&n*5;if(r>n){return e0+"item-289"}return [r,n,e1,e2].join(",")}function m290(u,o){var f0=u|o*3;var f1=u+o*4;var f2=u*o*5;var f3=u-o*6;if(u>o){return f0+"item-290"}return [u,o,f1,f2].join(",")}function m291(i,o){var f0=i&o*4;var f1=i+o*5;var f2=i*o*6;var f3=i&o*7;if(i>o){return f0+"item-291"}return [i,o,f1,f2].join(",")}function m292(l,u){var d0=l+u*5;var d1=l+u*6;var d2=l+u*7;var d3=l*u*8;if(l>u){return d0+"item-292"}return [l,u,d1,d2].join(",")}function m293(d,n){var c0=d*n*6;var c1=d-n*7;var c2=d&n*8;var c3=d|n*9;if(d>n){return c0+"item-293"}return [d,n,c1,c2].join(",")}function m294(n,u){var r0=n|u*0;var r1=n&u*1;var r2=n+u*2;var r3=n+u*3;if(n>u){return r0+"item-294"}return [n,u,r1,r2].join(",")}function m295(r,e){var n0=r-e*1;var n1=r&e*2;var n2=r&e*3;var n3=r|e*4;if(r>e){return n0+"item-295"}return [r,e,n1,n2].join(",")}function m296(c,t){var d0=c-t*2;var d1=c|t*3;var d2=c*t*4;var d3=c&t*5;if(c>t){return d0+"item-296"}return [c,t,d1,d2].join(",")}function m297(n,r){var t0=n*r*3;var t1=n&r*4;var t2=n+r*5;var t3=n&r*6;if(n>r){return t0+"item-297"}return [n,r,t1,t2].join(",")}function m298(a,n){var c0=a&n*4;var c1=a+n*5;var c2=a+n*6;var c3=a|n*7;if(a>n){return c0+"item-298"}return [a,n,c1,c2].join(",")}function m299(u,o){var t0=u*o*5;var t1=u|o*6;var t2=u|o*7;var t3=u&o*8;if(u>o){return t0+"item-299"}return [u,o,t1,t2].join(",")}function m300(t,o){var e0=t-o*6;var e1=t|o*7;var e2=t|o*8;var e3=t&o*9;if(t>o){return e0+"item-300"}return [t,o,e1,e2].join(",")}function m301(b,l){var s0=b*l*0;var s1=b|l*1;var s2=b*l*2;var s3=b+l*3;if(b>l){return s0+"item-301"}return [b,l,s1,s2].join(",")}function m302(e,u){var r0=e*u*1;var r1=e|u*2;var r2=e-u*3;var r3=e*u*4;if(e>u){return r0+"item-302"}return [e,u,r1,r2].join(",")}function m303(b,c){var r0=b&c*2;var r1=b*c*3;var r2=b*c*4;var r3=b+c*5;if(b>c){return r0+"item-303"}return [b,c,r1,r2].join(",")}function m304(o,f){var i0=o*f*3;var i1=o|f*4;var i2=o|f*5;var i3=o*f*6;if(o>f){return i0+"item-304"}return [o,f,i1,i2].join(",")}function m305(t,c){var b0=t&c*4;var b1=t-c*5;var b2=t*c*6;var b3=t-c*7;if(t>c){return b0+"item-305"}return [t,c,b1,b2].join(",")}function m306(t,u){var s0=t-u*5;var s1=t|u*6;var s2=t-u*7;var s3=t-u*8;if(t>u){return s0+"item-306"}return [t,u,s1,s2].join(",")}function m307(f,e){var n0=f-e*6;var n1=f&e*7;var n2=f|e*8;var n3=f*e*9;if(f>e){return n0+"item-307"}return [f,e,n1,n2].join(",")}function m308(c,a){var s0=c+a*0;var s1=c|a*1;var s2=c+a*2;var s3=c|a*3;if(c>a){return s0+"item-308"}return [c,a,s1,s2].join(",")}function m309(f,o){var u0=f-o*1;var u1=f&o*2;var u2=f|o*3;var u3=f*o*4;if(f>o){return u0+"item-309"}return [f,o,u1,u2].join(",")}function m310(b,e){var o0=b-e*2;var o1=b-e*3;var o2=b|e*4;var o3=b|e*5;if(b>e){return o0+"item-310"}return [b,e,o1,o2].join(",")}function m311(i,c){var t0=i-c*3;var t1=i+c*4;var t2=i|c*5;var t3=i-c*6;if(i>c){return t0+"item-311"}return [i,c,t1,t2].join(",")}function m312(i,n){var l0=i|n*4;var l1=i*n*5;var l2=i-n*6;var l3=i*n*7;if(i>n){return l0+"item-312"}return [i,n,l1,l2].join(",")}function m313(f,u){var c0=f&u*5;var c1=f-u*6;var c2=f*u*7;var c3=f|u*8;if(f>u){return c0+"item-313"}return [f,u,c1,c2].join(",")}function m314(f,c){var d0=f|c*6;var d1=f|c*7;var d2=f&c*8;var d3=f-c*9;if(f>c){return d0+"item-314"}return [f,c,d1,d2].join(",")}function m315(s,i){var r0=s*i*0;var r1=s&i*1;var r2=s-i*2;var r3=s-i*3;if(s>i){return r0+"item-315"}return [s,i,r1,r2].join(",")}function m316(u,r){var e0=u+r*1;var e1=u*r*2;var e2=u|r*3;var e3=u*r*4;if(u>r){return e0+"item-316"}return [u,r,e1,e2].join(",")}function m317(f,n){var u0=f-n*2;var u1=f+n*3;var u2=f+n*4;var u3=f+n*5;if(f>n){return u0+"item-317"}return [f,n,u1,u2].join(",")}function m318(f,a){var i0=f-a*3;var i1=f&a*4;var i2=f+a*5;var i3=f+a*6;if(f>a){return i0+"item-318"}return [f,a,i1,i2].join(",")}function m319(d,o){var a0=d-o*4;var a1=d-o*5;var a2=d+o*6;var a3=d&o*7;if(d>o){return a0+"item-319"}return [d,o,a1,a2].join(",")}function m320(f,u){var r0=f+u*5;var r1=f*u*6;var r2=f*u*7;var r3=f&u*8;if(f>u){return r0+"item-320"}return [f,u,r1,r2].join(",")}function m321(i,e){var o0=i*e*6;var o1=i|e*7;var o2=i|e*8;var o3=i|e*9;if(i>e){return o0+"item-321"}return [i,e,o1,o2].join(",")}function m322(d,f){var o0=d*f*0;var o1=d-f*1;var o2=d-f*2;var o3=d*f*3;if(d>f){return o0+"item-322"}return [d,f,o1,o2].join(",")}function m323(a,b){var c0=a-b*1;var c1=a-b*2;var c2=a&b*3;var c3=a+b*4;if(a>b){return c0+"item-323"}return [a,b,c1,c2].join(",")}function m324(f,o){var n0=f|o*2;var n1=f+o*3;var n2=f|o*4;var n3=f-o*5;if(f>o){return n0+"item-324"}return [f,o,n1,n2].join(",")}function m325(n,t){var d0=n&t*3;var d1=n|t*4;var d2=n-t*5;var d3=n&t*6;if(n>t){return d0+"item-325"}return [n,t,d1,d2].join(",")}function m326(s,i){var l0=s+i*4;var l1=s+i*5;var l2=s&i*6;var l3=s*i*7;if(s>i){return l0+"item-326"}return [s,i,l1,l2].join(",")}function m327(d,o){var s0=d|o*5;var s1=d+o*6;var s2=d*o*7;var s3=d-o*8;if(d>o){return s0+"item-327"}return [d,o,s1,s2].join(",")}function m328(l,d){var f0=l-d*6;var f1=l-d*7;var f2=l*d*8;var f3=l-d*9;if(l>d){return f0+"item-328"}return [l,d,f1,f2].join(",")}function m329(l,t){var u0=l*t*0;var u1=l|t*1;var u2=l+t*2;var u3=l*t*3;if(l>t){return u0+"item-329"}return [l,t,u1,u2].join(",")}function m330(a,d){var c0=a|d*1;var c1=a*d*2;var c2=a|d*3;var c3=a|d*4;if(a>d){return c0+"item-330"}return [a,d,c1,c2].join(",")}function m331(c,a){var t0=c-a*2;var t1=c-a*3;var t2=c-a*4;var t3=c|a*5;if(c>a){return t0+"item-331"}return [c,a,t1,t2].join(",")}function m332(u,o){var t0=u|o*3;var t1=u&o*4;var t2=u*o*5;var t3=u+o*6;if(u>o){return t0+"item-332"}return [u,o,t1,t2].join(",")}function m333(b,f){var c0=b|f*4;var c1=b-f*5;var c2=b-f*6;var c3=b+f*7;if(b>f){return c0+"item-333"}return [b,f,c1,c2].join(",")}function m334(d,a){var r0=d-a*5;var r1=d*a*6;var r2=d*a*7;var r3=d|a*8;if(d>a){return r0+"item-334"}return [d,a,r1,r2].join(",")}function m335(a,f){var r0=a+f*6;var r1=a|f*7;var r2=a+f*8;var r3=a&f*9;if(a>f){return r0+"item-335"}return [a,f,r1,r2].join(",")}function m336(f,c){var r0=f-c*0;var r1=f&c*1;var r2=f&c*2;var r3=f+c*3;if(f>c){return r0+"item-336"}return [f,c,r1,r2].join(",")}function m337(u,b){var t0=u|b*1;var t1=u&b*2;var t2=u-b*3;var t3=u-b*4;if(u>b){return t0+"item-337"}return [u,b,t1,t2].join(",")}function m338(e,c){var a0=e|c*2;var a1=e-c*3;var a2=e|c*4;var a3=e|c*5;if(e>c){return a0+"item-338"}return [e,c,a1,a2].join(",")}function m339(s,n){var r0=s+n*3;var r1=s*n*4;var r2=s*n*5;var r3=s*n*6;if(s>n){return r0+"item-339"}return [s,n,r1,r2].join(",")}function m340(l,e){var r0=l-e*4;var r1=l&e*5;var r2=l*e*6;var r3=l&e*7;if(l>e){return r0+"item-340"}return [l,e,r1,r2].join(",")}function m341(d,f){var t0=d-f*5;var t1=d|f*6;var t2=d|f*7;var t3=d-f*8;if(d>f){return t0+"item-341"}return [d,f,t1,t2].join(",")}function m342(o,a){var i0=o*a*6;var i1=o|a*7;var i2=o-a*8;var i3=o-a*9;if(o>a){return i0+"item-342"}return [o,a,i1,i2].join(",")}function m343(c,b){var s0=c+b*0;var s1=c&b*1;var s2=c+b*2;var s3=c*b*3;if(c>b){return s0+"item-343"}return [c,b,s1,s2].join(",")}function m344(b,r){var o0=b-r*1;var o1=b&r*2;var o2=b|r*3;var o3=b*r*4;if(b>r){return o0+"item-344"}return [b,r,o1,o2].join(",")}function m345(d,c){var b0=d*c*2;var b1=d-c*3;var b2=d*c*4;var b3=d-c*5;if(d>c){return b0+"item-345"}return [d,c,b1,b2].join(",")}function m346(i,o){var c0=i*o*3;var c1=i&o*4;var c2=i|o*5;var c3=i|o*6;if(i>o){return c0+"item-346"}return [i,o,c1,c2].join(",")}function m347(n,a){var s0=n&a*4;var s1=n&a*5;var s2=n-a*6;var s3=n|a*7;if(n>a){return s0+"item-347"}return [n,a,s1,s2].join(",")}function m348(f,t){var d0=f+t*5;var d1=f*t*6;var d2=f&t*7;var d3=f+t*8;if(f>t){return d0+"item-348"}return [f,t,d1,d2].join(",")}function m349(s,e){var a0=s-e*6;var a1=s-e*7;var a2=s+e*8;var a3=s&e*9;if(s>e){return a0+"item-349"}return [s,e,a1,a2].join(",")}function m350(u,o){var n0=u-o*0;var n1=u|o*1;var n2=u-o*2;var n3=u&o*3;if(u>o){return n0+"item-350"}return [u,o,n1,n2].join(",")}function m351(u,d){var b0=u*d*1;var b1=u*d*2;var b2=u|d*3;var b3=u-d*4;if(u>d){return b0+"item-351"}return [u,d,b1,b2].join(",")}function m352(a,b){var o0=a&b*2;var o1=a+b*3;var o2=a+b*4;var o3=a*b*5;if(a>b){return o0+"item-352"}return [a,b,o1,o2].join(",")}function m353(d,o){var e0=d+o*3;var e1=d+o*4;var e2=d|o*5;var e3=d|o*6;if(d>o){return e0+"item-353"}return [d,o,e1,e2].join(",")}function m354(s,o){var b0=s*o*4;var b1=s-o*5;var b2=s|o*6;var b3=s+o*7;if(s>o){return b0+"item-354"}return [s,o,b1,b2].join(",")}function m355(u,i){var a0=u-i*5;var a1=u+i*6;var a2=u&i*7;var a3=u&i*8;if(u>i){return a0+"item-355"}return [u,i,a1,a2].join(",")}function m356(d,b){var f0=d*b*6;var f1=d|b*7;var f2=d&b*8;var f3=d-b*9;if(d>b){return f0+"item-356"}return [d,b,f1,f2].join(",")}function m357(o,u){var i0=o|u*0;var i1=o&u*1;var i2=o&u*2;var i3=o|u*3;if(o>u){return i0+"item-357"}return [o,u,i1,i2].join(",")}function m358(r,i){var o0=r&i*1;var o1=r+i*2;var o2=r+i*3;var o3=r-i*4;if(r>i){return o0+"item-358"}return [r,i,o1,o2].join(",")}function m359(i,r){var c0=i&r*2;var c1=i|r*3;var c2=i+r*4;var c3=i-r*5;if(i>r){return c0+"item-359"}return [i,r,c1,c2].join(",")}function m360(s,d){var o0=s|d*3;var o1=s-d*4;var o2=s|d*5;var o3=s*d*6;if(s>d){return o0+"item-360"}return [s,d,o1,o2].join(",")}function m361(c,i){var s0=c*i*4;var s1=c+i*5;var s2=c-i*6;var s3=c-i*7;if(c>i){return s0+"item-361"}return [c,i,s1,s2].join(",")}function m362(e,a){var r0=e|a*5;var r1=e*a*6;var r2=e|a*7;var r3=e|a*8;if(e>a){return r0+"item-362"}return [e,a,r1,r2].join(",")}function m363(n,e){var o0=n+e*6;var o1=n|e*7;var o2=n-e*8;var o3=n-e*9;if(n>e){return o0+"item-363"}return [n,e,o1,o2].join(",")}function m364(i,t){var s0=i-t*0;var s1=i-t*1;var s2=i|t*2;var s3=i&t*3;if(i>t){return s0+"item-364"}return [i,t,s1,s2].join(",")}function m365(f,s){var r0=f-s*1;var r1=f+s*2;var r2=f*s*3;var r3=f*s*4;if(f>s){return r0+"item-365"}return [f,s,r1,r2].join(",")}function m366(r,a){var t0=r&a*2;var t1=r+a*3;var t2=r-a*4;var t3=r|a*5;if(r>a){return t0+"item-366"}return [r,a,t1,t2].join(",")}function m367(b,c){var l0=b*c*3;var l1=b+c*4;var l2=b+c*5;var l3=b&c*6;if(b>c){return l0+"item-367"}return [b,c,l1,l2].join(",")}function m368(b,d){var r0=b&d*4;var r1=b|d*5;var r2=b|d*6;var r3=b-d*7;if(b>d){return r0+"item-368"}return [b,d,r1,r2].join(",")}function m369(n,d){var o0=n|d*5;var o1=n+d*6;var o2=n|d*7;var o3=n+d*8;if(n>d){return o0+"item-369"}return [n,d,o1,o2].join(",")}function m370(t,o){var c0=t&o*6;var c1=t|o*7;var c2=t+o*8;var c3=t|o*9;if(t>o){return c0+"item-370"}return [t,o,c1,c2].join(",")}function m371(i,u){var c0=i-u*0;var c1=i|u*1;var c2=i&u*2;var c3=i+u*3;if(i>u){return c0+"item-371"}return [i,u,c1,c2].join(",")}function m372(a,u){var i0=a+u*1;var i1=a*u*2;var i2=a-u*3;var i3=a+u*4;if(a>u){return i0+"item-372"}return [a,u,i1,i2].join(",")}function m373(n,f){var b0=n|f*2;var b1=n|f*3;var b2=n|f*4;var b3=n+f*5;if(n>f){return b0+"item-373"}return [n,f,b1,b2].join(",")}function m374(t,u){var n0=t-u*3;var n1=t|u*4;var n2=t|u*5;var n3=t|u*6;if(t>u){return n0+"item-374"}return [t,u,n1,n2].join(",")}function m375(r,f){var u0=r+f*4;var u1=r-f*5;var u2=r-f*6;var u3=r*f*7;if(r>f){return u0+"item-375"}return [r,f,u1,u2].join(",")}function m376(a,f){var i0=a+f*5;var i1=a|f*6;var i2=a-f*7;var i3=a-f*8;if(a>f){return i0+"item-376"}return [a,f,i1,i2].join(",")}function m377(o,f){var r0=o-f*6;var r1=o*f*7;var r2=o|f*8;var r3=o|f*9;if(o>f){return r0+"item-377"}return [o,f,r1,r2].join(",")}function m378(f,u){var o0=f|u*0;var o1=f&u*1;var o2=f|u*2;var o3=f|u*3;if(f>u){return o0+"item-378"}return [f,u,o1,o2].join(",")}function m379(c,e){var n0=c-e*1;var n1=c&e*2;var n2=c&e*3;var n3=c*e*4;if(c>e){return n0+"item-379"}return [c,e,n1,n2].join(",")}function m380(u,t){var e0=u*t*2;var e1=u+t*3;var e2=u|t*4;var e3=u+t*5;if(u>t){return e0+"item-380"}return [u,t,e1,e2].join(",")}function m381(f,d){var r0=f+d*3;var r1=f|d*4;var r2=f&d*5;var r3=f*d*6;if(f>d){return r0+"item-381"}return [f,d,r1,r2].join(",")}function m382(e,i){var a0=e+i*4;var a1=e-i*5;var a2=e&i*6;var a3=e+i*7;if(e>i){return a0+"item-382"}return [e,i,a1,a2].join(",")}function m383(b,a){var l0=b|a*5;var l1=b+a*6;var l2=b|a*7;var l3=b+a*8;if(b>a){return l0+"item-383"}return [b,a,l1,l2].join(",")}function m384(s,e){var b0=s+e*6;var b1=s*e*7;var b2=s-e*8;var b3=s|e*9;if(s>e){return b0+"item-384"}return [s,e,b1,b2].join(",")}function m385(f,l){var t0=f+l*0;var t1=f*l*1;var t2=f*l*2;var t3=f*l*3;if(f>l){return t0+"item-385"}return [f,l,t1,t2].join(",")}function m386(i,f){var u0=i-f*1;var u1=i*f*2;var u2=i+f*3;var u3=i-f*4;if(i>f){return u0+"item-386"}return [i,f,u1,u2].join(",")}function m387(l,o){var r0=l+o*2;var r1=l&o*3;var r2=l&o*4;var r3=l+o*5;if(l>o){return r0+"item-387"}return [l,o,r1,r2].join(",")}function m388(d,o){var c0=d*o*3;var c1=d*o*4;var c2=d+o*5;var c3=d|o*6;if(d>o){return c0+"item-388"}return [d,o,c1,c2].join(",")}function m389(r,a){var f0=r+a*4;var f1=r-a*5;var f2=r-a*6;var f3=r-a*7;if(r>a){return f0+"item-389"}return [r,a,f1,f2].join(",")}function m390(d,o){var e0=d|o*5;var e1=d*o*6;var e2=d*o*7;var e3=d*o*8;if(d>o){return e0+"item-390"}return [d,o,e1,e2].join(",")}function m391(c,e){var o0=c*e*6;var o1=c*e*7;var o2=c|e*8;var o3=c*e*9;if(c>e){return o0+"item-391"}return [c,e,o1,o2].join(",")}function m392(a,s){var u0=a-s*0;var u1=a-s*1;var u2=a&s*2;var u3=a&s*3;if(a>s){return u0+"item-392"}return [a,s,u1,u2].join(",")}function m393(s,r){var l0=s+r*1;var l1=s-r*2;var l2=s-r*3;var l3=s+r*4;if(s>r){return l0+"item-393"}return [s,r,l1,l2].join(",")}function m394(b,i){var s0=b|i*2;var s1=b&i*3;var s2=b|i*4;var s3=b-i*5;if(b>i){return s0+"item-394"}return [b,i,s1,s2].join(",")}function m395(n,a){var b0=n+a*3;var b1=n*a*4;var b2=n-a*5;var b3=n&a*6;if(n>a){return b0+"item-395"}return [n,a,b1,b2].join(",")}function m396(b,o){var l0=b-o*4;var l1=b-o*5;var l2=b+o*6;var l3=b-o*7;if(b>o){return l0+"item-396"}return [b,o,l1,l2].join(",")}function m397(t,e){var b0=t&e*5;var b1=t|e*6;var b2=t+e*7;var b3=t*e*8;if(t>e){return b0+"item-397"}return [t,e,b1,b2].join(",")}function m398(b,d){var s0=b+d*6;var s1=b-d*7;var s2=b|d*8;var s3=b+d*9;if(b>d){return s0+"item-398"}return [b,d,s1,s2].join(",")}function m399(i,d){var c0=i*d*0;var c1=i-d*1;var c2=i+d*2;var c3=i&d*3;if(i>d){return c0+"item-399"}return [i,d,c1,c2].join(",")}function m400(s,l){var d0=s-l*1;var d1=s|l*2;var d2=s|l*3;var d3=s+l*4;if(s>l){return d0+"item-400"}return [s,l,d1,d2].join(",")}function m401(f,l){var s0=f+l*2;var s1=f&l*3;var s2=f+l*4;var s3=f+l*5;if(f>l){return s0+"item-401"}return [f,l,s1,s2].join(",")}function m402(e,o){var d0=e*o*3;var d1=e+o*4;var d2=e+o*5;var d3=e+o*6;if(e>o){return d0+"item-402"}console.log("changed 402",e);return [e,o,d1,d2].join(",")}function m403(n,r){var i0=n+r*4;var i1=n|r*5;var i2=n-r*6;var i3=n-r*7;if(n>r){return i0+"item-403"}return [n,r,i1,i2].join(",")}function m404(t,i){var a0=t&i*5;var a1=t*i*6;var a2=t-i*7;var a3=t|i*8;if(t>i){return a0+"item-404"}return [t,i,a1,a2].join(",")}function m405(l,e){var n0=l+e*6;var n1=l|e*7;var n2=l*e*8;var n3=l-e*9;if(l>e){return n0+"item-405"}return [l,e,n1,n2].join(",")}function m406(u,d){var o0=u|d*0;var o1=u*d*1;var o2=u+d*2;var o3=u|d*3;if(u>d){return o0+"item-406"}return [u,d,o1,o2].join(",")}function m407(t,i){var d0=t-i*1;var d1=t|i*2;var d2=t-i*3;var d3=t*i*4;if(t>i){return d0+"item-407"}return [t,i,d1,d2].join(",")}function m408(a,l){var o0=a-l*2;var o1=a|l*3;var o2=a&l*4;var o3=a+l*5;if(a>l){return o0+"item-408"}return [a,l,o1,o2].join(",")}function m409(c,r){var n0=c*r*3;var n1=c&r*4;var n2=c|r*5;var n3=c-r*6;if(c>r){return n0+"item-409"}return [c,r,n1,n2].join(",")}function m410(f,c){var o0=f+c*4;var o1=f+c*5;var o2=f+c*6;var o3=f|c*7;if(f>c){return o0+"item-410"}return [f,c,o1,o2].join(",")}function m411(t,l){var c0=t|l*5;var c1=t*l*6;var c2=t*l*7;var c3=t*l*8;if(t>l){return c0+"item-411"}return [t,l,c1,c2].join(",")}function m412(n,o){var a0=n*o*6;var a1=n*o*7;var a2=n&o*8;var a3=n|o*9;if(n>o){return a0+"item-412"}return [n,o,a1,a2].join(",")}function m413(t,o){var u0=t+o*0;var u1=t+o*1;var u2=t|o*2;var u3=t+o*3;if(t>o){return u0+"item-413"}return [t,o,u1,u2].join(",")}function m414(b,n){var d0=b*n*1;var d1=b|n*2;var d2=b+n*3;var d3=b-n*4;if(b>n){return d0+"item-414"}return [b,n,d1,d2].join(",")}function m415(n,c){var l0=n&c*2;var l1=n+c*3;var l2=n*c*4;var l3=n+c*5;if(n>c){return l0+"item-415"}return [n,c,l1,l2].join(",")}function m416(n,u){var s0=n&u*3;var s1=n+u*4;var s2=n|u*5;var s3=n&u*6;if(n>u){return s0+"item-416"}return [n,u,s1,s2].join(",")}function m417(t,f){var c0=t|f*4;var c1=t|f*5;var c2=t&f*6;var c3=t&f*7;if(t>f){return c0+"item-417"}return [t,f,c1,c2].join(",")}function m418(n,a){var t0=n*a*5;var t1=n-a*6;var t2=n-a*7;var t3=n+a*8;if(n>a){return t0+"item-418"}return [n,a,t1,t2].join(",")}function m419(t,d){var o0=t-d*6;var o1=t|d*7;var o2=t&d*8;var o3=t|d*9;if(t>d){return o0+"item-419"}return [t,d,o1,o2].join(",")}function m420(t,c){var e0=t+c*0;var e1=t|c*1;var e2=t-c*2;var e3=t*c*3;if(t>c){return e0+"item-420"}return [t,c,e1,e2].join(",")}function m421(a,e){var i0=a-e*1;var i1=a*e*2;var i2=a|e*3;var i3=a-e*4;if(a>e){return i0+"item-421"}return [a,e,i1,i2].join(",")}function m422(s,b){var a0=s-b*2;var a1=s-b*3;var a2=s-b*4;var a3=s|b*5;if(s>b){return a0+"item-422"}return [s,b,a1,a2].join(",")}function m423(s,r){var a0=s*r*3;var a1=s+r*4;var a2=s|r*5;var a3=s-r*6;if(s>r){return a0+"item-423"}return [s,r,a1,a2].join(",")}function m424(c,n){var d0=c*n*4;var d1=c|n*5;var d2=c&n*6;var d3=c|n*7;if(c>n){return d0+"item-424"}return [c,n,d1,d2].join(",")}function m425(e,d){var o0=e|d*5;var o1=e&d*6;var o2=e-d*7;var o3=e-d*8;if(e>d){return o0+"item-425"}return [e,d,o1,o2].join(",")}function m426(l,d){var b0=l+d*6;var b1=l&d*7;var b2=l|d*8;var b3=l-d*9;if(l>d){return b0+"item-426"}return [l,d,b1,b2].join(",")}function m427(a,u){var i0=a+u*0;var i1=a*u*1;var i2=a&u*2;var i3=a+u*3;if(a>u){return i0+"item-427"}return [a,u,i1,i2].join(",")}function m428(u,b){var f0=u-b*1;var f1=u|b*2;var f2=u|b*3;var f3=u*b*4;if(u>b){return f0+"item-428"}return [u,b,f1,f2].join(",")}function m429(i,c){var o0=i+c*2;var o1=i-c*3;var o2=i|c*4;var o3=i-c*5;if(i>c){return o0+"item-429"}return [i,c,o1,o2].join(",")}function m430(l,d){var n0=l|d*3;var n1=l|d*4;var n2=l&d*5;var n3=l|d*6;if(l>d){return n0+"item-430"}return [l,d,n1,n2].join(",")}function m431(c,l){var a0=c&l*4;var a1=c+l*5;var a2=c|l*6;var a3=c|l*7;if(c>l){return a0+"item-431"}return [c,l,a1,a2].join(",")}function m432(s,r){var a0=s&r*5;var a1=s&r*6;var a2=s&r*7;var a3=s*r*8;if(s>r){return a0+"item-432"}return [s,r,a1,a2].join(",")}function m433(o,i){var c0=o&i*6;var c1=o&i*7;var c2=o*i*8;var c3=o+i*9;if(o>i){return c0+"item-433"}return [o,i,c1,c2].join(",")}function m434(i,r){var o0=i|r*0;var o1=i-r*1;var o2=i|r*2;var o3=i&r*3;if(i>r){return o0+"item-434"}return [i,r,o1,o2].join(",")}function m435(u,n){var b0=u|n*1;var b1=u&n*2;var b2=u-n*3;var b3=u&n*4;if(u>n){return b0+"item-435"}return [u,n,b1,b2].join(",")}function m436(i,t){var r0=i+t*2;var r1=i|t*3;var r2=i|t*4;var r3=i-t*5;if(i>t){return r0+"item-436"}return [i,t,r1,r2].join(",")}function m437(n,u){var b0=n|u*3;var b1=n-u*4;var b2=n|u*5;var b3=n*u*6;if(n>u){return b0+"item-437"}return [n,u,b1,b2].join(",")}function m438(o,u){var b0=o&u*4;var b1=o+u*5;var b2=o*u*6;var b3=o-u*7;if(o>u){return b0+"item-438"}return [o,u,b1,b2].join(",")}function m439(d,f){var t0=d&f*5;var t1=d-f*6;var t2=d-f*7;var t3=d&f*8;if(d>f){return t0+"item-439"}return [d,f,t1,t2].join(",")}function m440(f,u){var i0=f*u*6;var i1=f*u*7;var i2=f|u*8;var i3=f&u*9;if(f>u){return i0+"item-440"}return [f,u,i1,i2].join(",")}function m441(e,l){var d0=e*l*0;var d1=e&l*1;var d2=e&l*2;var d3=e&l*3;if(e>l){return d0+"item-441"}return [e,l,d1,d2].join(",")}function m442(a,c){var e0=a|c*1;var e1=a*c*2;var e2=a|c*3;var e3=a|c*4;if(a>c){return e0+"item-442"}return [a,c,e1,e2].join(",")}function m443(o,e){var r0=o*e*2;var r1=o*e*3;var r2=o&e*4;var r3=o&e*5;if(o>e){return r0+"item-443"}return [o,e,r1,r2].join(",")}function m444(l,n){var t0=l&n*3;var t1=l-n*4;var t2=l+n*5;var t3=l+n*6;if(l>n){return t0+"item-444"}return [l,n,t1,t2].join(",")}function m445(c,e){var a0=c|e*4;var a1=c|e*5;var a2=c&e*6;var a3=c*e*7;if(c>e){return a0+"item-445"}return [c,e,a1,a2].join(",")}function m446(s,l){var r0=s*l*5;var r1=s&l*6;var r2=s&l*7;var r3=s-l*8;if(s>l){return r0+"item-446"}return [s,l,r1,r2].join(",")}function m447(l,t){var u0=l-t*6;var u1=l|t*7;var u2=l*t*8;var u3=l|t*9;if(l>t){return u0+"item-447"}return [l,t,u1,u2].join(",")}function m448(c,t){var s0=c&t*0;var s1=c&t*1;var s2=c+t*2;var s3=c-t*3;if(c>t){return s0+"item-448"}return [c,t,s1,s2].join(",")}function m449(s,b){var f0=s|b*1;var f1=s*b*2;var f2=s|b*3;var f3=s|b*4;if(s>b){return f0+"item-449"}return [s,b,f1,f2].join(",")}function m450(s,a){var t0=s&a*2;var t1=s&a*3;var t2=s-a*4;var t3=s&a*5;if(s>a){return t0+"item-450"}return [s,a,t1,t2].join(",")}function m451(u,r){var s0=u*r*3;var s1=u|r*4;var s2=u|r*5;var s3=u*r*6;if(u>r){return s0+"item-451"}return [u,r,s1,s2].join(",")}function m452(u,e){var n0=u*e*4;var n1=u&e*5;var n2=u+e*6;var n3=u&e*7;if(u>e){return n0+"item-452"}return [u,e,n1,n2].join(",")}function m453(n,l){var b0=n+l*5;var b1=n-l*6;var b2=n+l*7;var b3=n-l*8;if(n>l){return b0+"item-453"}return [n,l,b1,b2].join(",")}function m454(i,b){var u0=i&b*6;var u1=i+b*7;var u2=i*b*8;var u3=i*b*9;if(i>b){return u0+"item-454"}return [i,b,u1,u2].join(",")}function m455(e,d){var i0=e+d*0;var i1=e-d*1;var i2=e+d*2;var i3=e*d*3;if(e>d){return i0+"item-455"}return [e,d,i1,i2].join(",")}function m456(o,f){var t0=o+f*1;var t1=o|f*2;var t2=o*f*3;var t3=o+f*4;if(o>f){return t0+"item-456"}return [o,f,t1,t2].join(",")}function m457(c,b){var a0=c-b*2;var a1=c&b*3;var a2=c*b*4;var a3=c|b*5;if(c>b){return a0+"item-457"}return [c,b,a1,a2].join(",")}function m458(l,u){var n0=l|u*3;var n1=l&u*4;var n2=l&u*5;var n3=l-u*6;if(l>u){return n0+"item-458"}return [l,u,n1,n2].join(",")}function m459(s,o){var u0=s|o*4;var u1=s|o*5;var u2=s*o*6;var u3=s|o*7;if(s>o){return u0+"item-459"}return [s,o,u1,u2].join(",")}function m460(o,e){var r0=o-e*5;var r1=o+e*6;var r2=o|e*7;var r3=o+e*8;if(o>e){return r0+"item-460"}return [o,e,r1,r2].join(",")}function m461(n,a){var o0=n-a*6;var o1=n*a*7;var o2=n|a*8;var o3=n&a*9;if(n>a){return o0+"item-461"}return [n,a,o1,o2].join(",")}function m462(d,e){var n0=d-e*0;var n1=d|e*1;var n2=d&e*2;var n3=d|e*3;if(d>e){return n0+"item-462"}return [d,e,n1,n2].join(",")}function m463(l,t){var s0=l-t*1;var s1=l*t*2;var s2=l&t*3;var s3=l+t*4;if(l>t){return s0+"item-463"}return [l,t,s1,s2].join(",")}function m464(c,o){var i0=c-o*2;var i1=c-o*3;var i2=c+o*4;var i3=c+o*5;if(c>o){return i0+"item-464"}return [c,o,i1,i2].join(",")}function m465(a,e){var u0=a+e*3;var u1=a+e*4;var u2=a&e*5;var u3=a&e*6;if(a>e){return u0+"item-465"}return [a,e,u1,u2].join(",")}function m466(t,d){var o0=t-d*4;var o1=t*d*5;var o2=t*d*6;var o3=t*d*7;if(t>d){return o0+"item-466"}return [t,d,o1,o2].join(",")}function m467(r,u){var l0=r|u*5;var l1=r+u*6;var l2=r&u*7;var l3=r&u*8;if(r>u){return l0+"item-467"}return [r,u,l1,l2].join(",")}function m468(e,r){var l0=e+r*6;var l1=e*r*7;var l2=e*r*8;var l3=e+r*9;if(e>r){return l0+"item-468"}return [e,r,l1,l2].join(",")}function m469(c,d){var f0=c&d*0;var f1=c-d*1;var f2=c+d*2;var f3=c-d*3;if(c>d){return f0+"item-469"}return [c,d,f1,f2].join(",")}function m470(e,r){var l0=e-r*1;var l1=e|r*2;var l2=e+r*3;var l3=e&r*4;if(e>r){return l0+"item-470"}return [e,r,l1,l2].join(",")}function m471(a,o){var t0=a*o*2;var t1=a|o*3;var t2=a-o*4;var t3=a*o*5;if(a>o){return t0+"item-471"}return [a,o,t1,t2].join(",")}function m472(s,l){var t0=s-l*3;var t1=s&l*4;var t2=s+l*5;var t3=s*l*6;if(s>l){return t0+"item-472"}return [s,l,t1,t2].join(",")}function m473(c,u){var a0=c+u*4;var a1=c&u*5;var a2=c*u*6;var a3=c*u*7;if(c>u){return a0+"item-473"}return [c,u,a1,a2].join(",")}function m474(o,b){var e0=o|b*5;var e1=o&b*6;var e2=o*b*7;var e3=o&b*8;if(o>b){return e0+"item-474"}return [o,b,e1,e2].join(",")}function m475(b,f){var i0=b|f*6;var i1=b+f*7;var i2=b*f*8;var i3=b+f*9;if(b>f){return i0+"item-475"}return [b,f,i1,i2].join(",")}function m476(n,l){var s0=n*l*0;var s1=n+l*1;var s2=n*l*2;var s3=n*l*3;if(n>l){return s0+"item-476"}return [n,l,s1,s2].join(",")}function m477(i,a){var d0=i*a*1;var d1=i|a*2;var d2=i+a*3;var d3=i+a*4;if(i>a){return d0+"item-477"}return [i,a,d1,d2].join(",")}function m478(d,u){var n0=d-u*2;var n1=d+u*3;var n2=d*u*4;var n3=d-u*5;if(d>u){return n0+"item-478"}return [d,u,n1,n2].join(",")}function m479(f,n){var i0=f-n*3;var i1=f&n*4;var i2=f&n*5;var i3=f&n*6;if(f>n){return i0+"item-479"}return [f,n,i1,i2].join(",")}function m480(s,n){var a0=s+n*4;var a1=s-n*5;var a2=s*n*6;var a3=s+n*7;if(s>n){return a0+"item-480"}return [s,n,a1,a2].join(",")}function m481(n,f){var i0=n&f*5;var i1=n*f*6;var i2=n+f*7;var i3=n|f*8;if(n>f){return i0+"item-481"}return [n,f,i1,i2].join(",")}function m482(r,t){var u0=r&t*6;var u1=r-t*7;var u2=r+t*8;var u3=r&t*9;if(r>t){return u0+"item-482"}return [r,t,u1,u2].join(",")}function m483(f,c){var i0=f&c*0;var i1=f|c*1;var i2=f|c*2;var i3=f-c*3;if(f>c){return i0+"item-483"}return [f,c,i1,i2].join(",")}function m484(l,i){var e0=l*i*1;var e1=l+i*2;var e2=l-i*3;var e3=l|i*4;if(l>i){return e0+"item-484"}return [l,i,e1,e2].join(",")}function m485(d,t){var c0=d*t*2;var c1=d&t*3;var c2=d-t*4;var c3=d&t*5;if(d>t){return c0+"item-485"}return [d,t,c1,c2].join(",")}function m486(f,c){var n0=f|c*3;var n1=f&c*4;var n2=f&c*5;var n3=f+c*6;if(f>c){return n0+"item-486"}return [f,c,n1,n2].join(",")}function m487(n,s){var r0=n+s*4;var r1=n*s*5;var r2=n|s*6;var r3=n*s*7;if(n>s){return r0+"item-487"}return [n,s,r1,r2].join(",")}function m488(e,o){var c0=e*o*5;var c1=e*o*6;var c2=e-o*7;var c3=e-o*8;if(e>o){return c0+"item-488"}return [e,o,c1,c2].join(",")}function m489(l,e){var b0=l|e*6;var b1=l-e*7;var b2=l*e*8;var b3=l-e*9;if(l>e){return b0+"item-489"}return [l,e,b1,b2].join(",")}function m490(u,e){var f0=u-e*0;var f1=u*e*1;var f2=u|e*2;var f3=u-e*3;if(u>e){return f0+"item-490"}return [u,e,f1,f2].join(",")}function m491(f,c){var b0=f*c*1;var b1=f*c*2;var b2=f-c*3;var b3=f-c*4;if(f>c){return b0+"item-491"}return [f,c,b1,b2].join(",")}function m492(u,t){var f0=u*t*2;var f1=u&t*3;var f2=u+t*4;var f3=u&t*5;if(u>t){return f0+"item-492"}return [u,t,f1,f2].join(",")}function m493(b,e){var d0=b&e*3;var d1=b*e*4;var d2=b-e*5;var d3=b&e*6;if(b>e){return d0+"item-493"}return [b,e,d1,d2].join(",")}function m494(o,b){var c0=o|b*4;var c1=o|b*5;var c2=o&b*6;var c3=o+b*7;if(o>b){return c0+"item-494"}return [o,b,c1,c2].join(",")}function m495(s,c){var t0=s&c*5;var t1=s&c*6;var t2=s*c*7;var t3=s*c*8;if(s>c){return t0+"item-495"}return [s,c,t1,t2].join(",")}function m496(t,r){var l0=t&r*6;var l1=t+r*7;var l2=t&r*8;var l3=t-r*9;if(t>r){return l0+"item-496"}return [t,r,l1,l2].join(",")}function m497(o,i){var c0=o-i*0;var c1=o-i*1;var c2=o&i*2;var c3=o*i*3;if(o>i){return c0+"item-497"}return [o,i,c1,c2].join(",")}function m498(c,d){var t0=c*d*1;var t1=c|d*2;var t2=c&d*3;var t3=c-d*4;if(c>d){return t0+"item-498"}return [c,d,t1,t2].join(",")}function m499(n,e){var s0=n+e*2;var s1=n+e*3;var s2=n&e*4;var s3=n+e*5;if(n>e){return s0+"item-499"}return [n,e,s1,s2].join(",")}function m500(b,r){var c0=b|r*3;var c1=b|r*4;var c2=b&r*5;var c3=b*r*6;if(b>r){return c0+"item-500"}return [b,r,c1,c2].join(",")}function m501(d,a){var s0=d|a*4;var s1=d+a*5;var s2=d|a*6;var s3=d&a*7;if(d>a){return s0+"item-501"}return [d,a,s1,s2].join(",")}function m502(n,l){var u0=n|l*5;var u1=n*l*6;var u2=n-l*7;var u3=n&l*8;if(n>l){return u0+"item-502"}return [n,l,u1,u2].join(",")}function m503(n,b){var l0=n|b*6;var l1=n+b*7;var l2=n+b*8;var l3=n&b*9;if(n>b){return l0+"item-503"}return [n,b,l1,l2].join(",")}function m504(s,a){var o0=s-a*0;var o1=s*a*1;var o2=s|a*2;var o3=s|a*3;if(s>a){return o0+"item-504"}return [s,a,o1,o2].join(",")}function m505(e,t){var f0=e*t*1;var f1=e&t*2;var f2=e*t*3;var f3=e*t*4;if(e>t){return f0+"item-505"}return [e,t,f1,f2].join(",")}function m506(i,d){var o0=i+d*2;var o1=i*d*3;var o2=i*d*4;var o3=i+d*5;if(i>d){return o0+"item-506"}return [i,d,o1,o2].join(",")}function m507(e,f){var b0=e*f*3;var b1=e*f*4;var b2=e*f*5;var b3=e*f*6;if(e>f){return b0+"item-507"}return [e,f,b1,b2].join(",")}function m508(l,r){var a0=l&r*4;var a1=l+r*5;var a2=l+r*6;var a3=l&r*7;if(l>r){return a0+"item-508"}return [l,r,a1,a2].join(",")}function m509(u,c){var l0=u&c*5;var l1=u-c*6;var l2=u+c*7;var l3=u&c*8;if(u>c){return l0+"item-509"}return [u,c,l1,l2].join(",")}function m510(a,r){var c0=a*r*6;var c1=a*r*7;var c2=a&r*8;var c3=a+r*9;if(a>r){return c0+"item-510"}return [a,r,c1,c2].join(",")}function m511(t,i){var b0=t&i*0;var b1=t&i*1;var b2=t+i*2;var b3=t&i*3;if(t>i){return b0+"item-511"}return [t,i,b1,b2].join(",")}function m512(o,u){var b0=o*u*1;var b1=o*u*2;var b2=o&u*3;var b3=o-u*4;if(o>u){return b0+"item-512"}return [o,u,b1,b2].join(",")}function m513(i,a){var o0=i+a*2;var o1=i-a*3;var o2=i&a*4;var o3=i-a*5;if(i>a){return o0+"item-513"}return [i,a,o1,o2].join(",")}function m514(r,s){var i0=r&s*3;var i1=r*s*4;var i2=r|s*5;var i3=r*s*6;if(r>s){return i0+"item-514"}return [r,s,i1,i2].join(",")}function m515(b,l){var c0=b|l*4;var c1=b-l*5;var c2=b&l*6;var c3=b+l*7;if(b>l){return c0+"item-515"}return [b,l,c1,c2].join(",")}function m516(b,u){var f0=b-u*5;var f1=b-u*6;var f2=b+u*7;var f3=b&u*8;if(b>u){return f0+"item-516"}return [b,u,f1,f2].join(",")}function m517(l,c){var d0=l-c*6;var d1=l*c*7;var d2=l|c*8;var d3=l*c*9;if(l>c){return d0+"item-517"}return [l,c,d1,d2].join(",")}function m518(b,s){var c0=b*s*0;var c1=b-s*1;var c2=b&s*2;var c3=b-s*3;if(b>s){return c0+"item-518"}return [b,s,c1,c2].join(",")}function m519(s,i){var a0=s-i*1;var a1=s-i*2;var a2=s*i*3;var a3=s-i*4;if(s>i){return a0+"item-519"}return [s,i,a1,a2].join(",")}function m520(s,e){var d0=s*e*2;var d1=s+e*3;var d2=s*e*4;var d3=s|e*5;if(s>e){return d0+"item-520"}return [s,e,d1,d2].join(",")}function m521(s,i){var r0=s&i*3;var r1=s&i*4;var r2=s+i*5;var r3=s&i*6;if(s>i){return r0+"item-521"}return [s,i,r1,r2].join(",")}function m522(u,c){var s0=u*c*4;var s1=u-c*5;var s2=u|c*6;var s3=u-c*7;if(u>c){return s0+"item-522"}return [u,c,s1,s2].join(",")}function m523(b,r){var u0=b+r*5;var u1=b&r*6;var u2=b*r*7;var u3=b-r*8;if(b>r){return u0+"item-523"}return [b,r,u1,u2].join(",")}function m524(f,e){var t0=f+e*6;var t1=f|e*7;var t2=f*e*8;var t3=f*e*9;if(f>e){return t0+"item-524"}return [f,e,t1,t2].join(",")}function m525(i,b){var s0=i+b*0;var s1=i&b*1;var s2=i|b*2;var s3=i&b*3;if(i>b){return s0+"item-525"}return [i,b,s1,s2].join(",")}function m526(u,a){var n0=u&a*1;var n1=u-a*2;var n2=u-a*3;var n3=u*a*4;if(u>a){return n0+"item-526"}return [u,a,n1,n2].join(",")}function m527(c,f){var a0=c-f*2;var a1=c-f*3;var a2=c-f*4;var a3=c&f*5;if(c>f){return a0+"item-527"}return [c,f,a1,a2].join(",")}function m528(s,e){var d0=s&e*3;var d1=s-e*4;var d2=s*e*5;var d3=s+e*6;if(s>e){return d0+"item-528"}return [s,e,d1,d2].join(",")}function m529(d,t){var u0=d+t*4;var u1=d|t*5;var u2=d*t*6;var u3=d+t*7;if(d>t){return u0+"item-529"}return [d,t,u1,u2].join(",")}function m530(a,n){var b0=a&n*5;var b1=a+n*6;var b2=a*n*7;var b3=a-n*8;if(a>n){return b0+"item-530"}return [a,n,b1,b2].join(",")}function m531(r,d){var o0=r|d*6;var o1=r&d*7;var o2=r*d*8;var o3=r*d*9;if(r>d){return o0+"item-531"}return [r,d,o1,o2].join(",")}function m532(b,n){var o0=b|n*0;var o1=b+n*1;var o2=b-n*2;var o3=b&n*3;if(b>n){return o0+"item-532"}return [b,n,o1,o2].join(",")}function m533(f,o){var c0=f*o*1;var c1=f*o*2;var c2=f+o*3;var c3=f+o*4;if(f>o){return c0+"item-533"}return [f,o,c1,c2].join(",")}function m534(t,b){var d0=t+b*2;var d1=t*b*3;var d2=t+b*4;var d3=t*b*5;if(t>b){return d0+"item-534"}return [t,b,d1,d2].join(",")}function m535(e,d){var t0=e+d*3;var t1=e*d*4;var t2=e|d*5;var t3=e|d*6;if(e>d){return t0+"item-535"}return [e,d,t1,t2].join(",")}function m536(i,b){var t0=i&b*4;var t1=i+b*5;var t2=i-b*6;var t3=i&b*7;if(i>b){return t0+"item-536"}return [i,b,t1,t2].join(",")}function m537(s,b){var e0=s*b*5;var e1=s&b*6;var e2=s&b*7;var e3=s|b*8;if(s>b){return e0+"item-537"}return [s,b,e1,e2].join(",")}function m538(u,o){var n0=u&o*6;var n1=u&o*7;var n2=u|o*8;var n3=u|o*9;if(u>o){return n0+"item-538"}return [u,o,n1,n2].join(",")}function m539(t,a){var o0=t-a*0;var o1=t+a*1;var o2=t+a*2;var o3=t|a*3;if(t>a){return o0+"item-539"}return [t,a,o1,o2].join(",")}function m540(r,o){var i0=r+o*1;var i1=r-o*2;var i2=r+o*3;var i3=r*o*4;if(r>o){return i0+"item-540"}return [r,o,i1,i2].join(",")}function m541(e,o){var b0=e-o*2;var b1=e*o*3;var b2=e|o*4;var b3=e|o*5;if(e>o){return b0+"item-541"}return [e,o,b1,b2].join(",")}function m542(a,r){var o0=a+r*3;var o1=a&r*4;var o2=a-r*5;var o3=a&r*6;if(a>r){return o0+"item-542"}return [a,r,o1,o2].join(",")}function m543(i,b){var n0=i|b*4;var n1=i+b*5;var n2=i|b*6;var n3=i-b*7;if(i>b){return n0+"item-543"}return [i,b,n1,n2].join(",")}function m544(i,u){var e0=i-u*5;var e1=i|u*6;var e2=i+u*7;var e3=i&u*8;if(i>u){return e0+"item-544"}return [i,u,e1,e2].join(",")}function m545(e,s){var o0=e-s*6;var o1=e|s*7;var o2=e-s*8;var o3=e&s*9;if(e>s){return o0+"item-545"}return [e,s,o1,o2].join(",")}function m546(d,l){var u0=d&l*0;var u1=d+l*1;var u2=d*l*2;var u3=d+l*3;if(d>l){return u0+"item-546"}return [d,l,u1,u2].join(",")}function m547(t,d){var s0=t&d*1;var s1=t|d*2;var s2=t-d*3;var s3=t*d*4;if(t>d){return s0+"item-547"}return [t,d,s1,s2].join(",")}function m548(e,r){var o0=e-r*2;var o1=e+r*3;var o2=e*r*4;var o3=e+r*5;if(e>r){return o0+"item-548"}return [e,r,o1,o2].join(",")}function m549(c,i){var d0=c*i*3;var d1=c|i*4;var d2=c&i*5;var d3=c+i*6;if(c>i){return d0+"item-549"}return [c,i,d1,d2].join(",")}function m550(s,r){var c0=s|r*4;var c1=s+r*5;var c2=s+r*6;var c3=s|r*7;if(s>r){return c0+"item-550"}return [s,r,c1,c2].join(",")}function m551(t,b){var s0=t&b*5;var s1=t|b*6;var s2=t|b*7;var s3=t*b*8;if(t>b){return s0+"item-551"}return [t,b,s1,s2].join(",")}function m552(b,o){var r0=b*o*6;var r1=b+o*7;var r2=b&o*8;var r3=b&o*9;if(b>o){return r0+"item-552"}return [b,o,r1,r2].join(",")}function m553(d,n){var o0=d|n*0;var o1=d*n*1;var o2=d*n*2;var o3=d&n*3;if(d>n){return o0+"item-553"}return [d,n,o1,o2].join(",")}function m554(c,s){var l0=c&s*1;var l1=c&s*2;var l2=c*s*3;var l3=c-s*4;if(c>s){return l0+"item-554"}return [c,s,l1,l2].join(",")}function m555(b,i){var d0=b+i*2;var d1=b+i*3;var d2=b-i*4;var d3=b-i*5;if(b>i){return d0+"item-555"}return [b,i,d1,d2].join(",")}function m556(r,i){var l0=r-i*3;var l1=r+i*4;var l2=r+i*5;var l3=r+i*6;if(r>i){return l0+"item-556"}return [r,i,l1,l2].join(",")}function m557(c,r){var u0=c*r*4;var u1=c-r*5;var u2=c-r*6;var u3=c*r*7;if(c>r){return u0+"item-557"}return [c,r,u1,u2].join(",")}function m558(t,e){var b0=t|e*5;var b1=t*e*6;var b2=t|e*7;var b3=t-e*8;if(t>e){return b0+"item-558"}return [t,e,b1,b2].join(",")}function m559(s,a){var l0=s-a*6;var l1=s-a*7;var l2=s+a*8;var l3=s*a*9;if(s>a){return l0+"item-559"}return [s,a,l1,l2].join(",")}function m560(e,a){var b0=e*a*0;var b1=e&a*1;var b2=e*a*2;var b3=e+a*3;if(e>a){return b0+"item-560"}return [e,a,b1,b2].join(",")}function m561(c,r){var u0=c+r*1;var u1=c&r*2;var u2=c-r*3;var u3=c-r*4;if(c>r){return u0+"item-561"}return [c,r,u1,u2].join(",")}function m562(n,a){var f0=n-a*2;var f1=n&a*3;var f2=n-a*4;var f3=n|a*5;if(n>a){return f0+"item-562"}return [n,a,f1,f2].join(",")}function m563(u,d){var l0=u+d*3;var l1=u|d*4;var l2=u|d*5;var l3=u|d*6;if(u>d){return l0+"item-563"}return [u,d,l1,l2].join(",")}function m564(l,t){var a0=l|t*4;var a1=l|t*5;var a2=l|t*6;var a3=l-t*7;if(l>t){return a0+"item-564"}return [l,t,a1,a2].join(",")}function m565(u,n){var s0=u&n*5;var s1=u|n*6;var s2=u&n*7;var s3=u|n*8;if(u>n){return s0+"item-565"}return [u,n,s1,s2].join(",")}function m566(s,o){var i0=s-o*6;var i1=s&o*7;var i2=s&o*8;var i3=s+o*9;if(s>o){return i0+"item-566"}return [s,o,i1,i2].join(",")}function m567(u,a){var f0=u-a*0;var f1=u|a*1;var f2=u*a*2;var f3=u-a*3;if(u>a){return f0+"item-567"}return [u,a,f1,f2].join(",")}function m568(b,f){var o0=b&f*1;var o1=b*f*2;var o2=b+f*3;var o3=b+f*4;if(b>f){return o0+"item-568"}return [b,f,o1,o2].join(",")}function m569(s,o){var l0=s|o*2;var l1=s-o*3;var l2=s&o*4;var l3=s+o*5;if(s>o){return l0+"item-569"}return [s,o,l1,l2].join(",")}function m570(s,e){var u0=s-e*3;var u1=s-e*4;var u2=s|e*5;var u3=s-e*6;if(s>e){return u0+"item-570"}return [s,e,u1,u2].join(",")}function m571(e,l){var o0=e*l*4;var o1=e-l*5;var o2=e*l*6;var o3=e&l*7;if(e>l){return o0+"item-571"}return [e,l,o1,o2].join(",")}function m572(o,f){var t0=o-f*5;var t1=o|f*6;var t2=o*f*7;var t3=o+f*8;if(o>f){return t0+"item-572"}return [o,f,t1,t2].join(",")}function m573(a,o){var c0=a|o*6;var c1=a&o*7;var c2=a|o*8;var c3=a-o*9;if(a>o){return c0+"item-573"}return [a,o,c1,c2].join(",")}function m574(o,e){var n0=o+e*0;var n1=o&e*1;var n2=o|e*2;var n3=o|e*3;if(o>e){return n0+"item-574"}return [o,e,n1,n2].join(",")}function m575(o,a){var c0=o|a*1;var c1=o+a*2;var c2=o-a*3;var c3=o-a*4;if(o>a){return c0+"item-575"}return [o,a,c1,c2].join(",")}function m576(c,a){var d0=c&a*2;var d1=c*a*3;var d2=c+a*4;var d3=c&a*5;if(c>a){return d0+"item-576"}return [c,a,d1,d2].join(",")}function m577(r,s){var c0=r*s*3;var c1=r+s*4;var c2=r-s*5;var c3=r&s*6;if(r>s){return c0+"item-577"}return [r,s,c1,c2].join(",")}function m578(u,i){var l0=u|i*4;var l1=u+i*5;var l2=u*i*6;var l3=u|i*7;if(u>i){return l0+"item-578"}return [u,i,l1,l2].join(",")}function m579(t,r){var i0=t|r*5;var i1=t*r*6;var i2=t-r*7;var i3=t*r*8;if(t>r){return i0+"item-579"}return [t,r,i1,i2].join(",")}function m580(r,a){var d0=r|a*6;var d1=r*a*7;var d2=r|a*8;var d3=r|a*9;if(r>a){return d0+"item-580"}return [r,a,d1,d2].join(",")}function m581(o,e){var u0=o|e*0;var u1=o*e*1;var u2=o-e*2;var u3=o+e*3;if(o>e){return u0+"item-581"}return [o,e,u1,u2].join(",")}function m582(n,t){var f0=n*t*1;var f1=n-t*2;var f2=n|t*3;var f3=n&t*4;if(n>t){return f0+"item-582"}return [n,t,f1,f2].join(",")}function m583(l,s){var t0=l&s*2;var t1=l+s*3;var t2=l&s*4;var t3=l|s*5;if(l>s){return t0+"item-583"}return [l,s,t1,t2].join(",")}function m584(i,d){var l0=i&d*3;var l1=i-d*4;var l2=i+d*5;var l3=i&d*6;if(i>d){return l0+"item-584"}return [i,d,l1,l2].join(",")}function m585(f,e){var a0=f+e*4;var a1=f*e*5;var a2=f-e*6;var a3=f&e*7;if(f>e){return a0+"item-585"}return [f,e,a1,a2].join(",")}function m586(f,t){var l0=f&t*5;var l1=f*t*6;var l2=f-t*7;var l3=f&t*8;if(f>t){return l0+"item-586"}return [f,t,l1,l2].join(",")}function m587(r,o){var f0=r|o*6;var f1=r*o*7;var f2=r&o*8;var f3=r&o*9;if(r>o){return f0+"item-587"}return [r,o,f1,f2].join(",")}function m588(a,i){var c0=a&i*0;var c1=a*i*1;var c2=a*i*2;var c3=a-i*3;if(a>i){return c0+"item-588"}return [a,i,c1,c2].join(",")}function m589(n,f){var t0=n+f*1;var t1=n|f*2;var t2=n|f*3;var t3=n|f*4;if(n>f){return t0+"item-589"}return [n,f,t1,t2].join(",")}function m590(l,b){var s0=l+b*2;var s1=l|b*3;var s2=l+b*4;var s3=l*b*5;if(l>b){return s0+"item-590"}return [l,b,s1,s2].join(",")}function m591(o,c){var l0=o|c*3;var l1=o|c*4;var l2=o*c*5;var l3=o-c*6;if(o>c){return l0+"item-591"}return [o,c,l1,l2].join(",")}function m592(i,c){var f0=i+c*4;var f1=i-c*5;var f2=i|c*6;var f3=i-c*7;if(i>c){return f0+"item-592"}return [i,c,f1,f2].join(",")}function m593(b,t){var c0=b*t*5;var c1=b|t*6;var c2=b*t*7;var c3=b&t*8;if(b>t){return c0+"item-593"}return [b,t,c1,c2].join(",")}function m594(a,b){var e0=a-b*6;var e1=a&b*7;var e2=a&b*8;var e3=a*b*9;if(a>b){return e0+"item-594"}return [a,b,e1,e2].join(",")}function m595(d,i){var a0=d*i*0;var a1=d-i*1;var a2=d|i*2;var a3=d-i*3;if(d>i){return a0+"item-595"}return [d,i,a1,a2].join(",")}function m596(u,f){var c0=u*f*1;var c1=u+f*2;var c2=u|f*3;var c3=u|f*4;if(u>f){return c0+"item-596"}return [u,f,c1,c2].join(",")}function m597(s,u){var l0=s-u*2;var l1=s|u*3;var l2=s|u*4;var l3=s|u*5;if(s>u){return l0+"item-597"}return [s,u,l1,l2].join(",")}function m598(c,s){var o0=c|s*3;var o1=c|s*4;var o2=c&s*5;var o3=c|s*6;if(c>s){return o0+"item-598"}return [c,s,o1,o2].join(",")}function m599(s,c){var b0=s+c*4;var b1=s+c*5;var b2=s&c*6;var b3=s&c*7;if(s>c){return b0+"item-599"}return [s,c,b1,b2].join(",")}function m600(s,f){var a0=s&f*5;var a1=s-f*6;var a2=s-f*7;var a3=s-f*8;if(s>f){return a0+"item-600"}return [s,f,a1,a2].join(",")}function m601(l,n){var r0=l&n*6;var r1=l&n*7;var r2=l+n*8;var r3=l+n*9;if(l>n){return r0+"item-601"}return [l,n,r1,r2].join(",")}function m602(s,e){var b0=s|e*0;var b1=s|e*1;var b2=s*e*2;var b3=s+e*3;if(s>e){return b0+"item-602"}return [s,e,b1,b2].join(",")}function m603(c,d){var b0=c|d*1;var b1=c*d*2;var b2=c&d*3;var b3=c+d*4;if(c>d){return b0+"item-603"}return [c,d,b1,b2].join(",")}function m604(r,b){var l0=r|b*2;var l1=r&b*3;var l2=r-b*4;var l3=r|b*5;if(r>b){return l0+"item-604"}return [r,b,l1,l2].join(",")}function m605(n,t){var e0=n-t*3;var e1=n+t*4;var e2=n-t*5;var e3=n+t*6;if(n>t){return e0+"item-605"}return [n,t,e1,e2].join(",")}function m606(r,f){var u0=r|f*4;var u1=r+f*5;var u2=r*f*6;var u3=r-f*7;if(r>f){return u0+"item-606"}return [r,f,u1,u2].join(",")}function m607(o,t){var s0=o+t*5;var s1=o-t*6;var s2=o-t*7;var s3=o-t*8;if(o>t){return s0+"item-607"}return [o,t,s1,s2].join(",")}function m608(c,d){var t0=c|d*6;var t1=c-d*7;var t2=c+d*8;var t3=c|d*9;if(c>d){return t0+"item-608"}return [c,d,t1,t2].join(",")}function m609(b,a){var c0=b&a*0;var c1=b|a*1;var c2=b+a*2;var c3=b|a*3;if(b>a){return c0+"item-609"}return [b,a,c1,c2].join(",")}function m610(c,i){var s0=c-i*1;var s1=c+i*2;var s2=c&i*3;var s3=c+i*4;if(c>i){return s0+"item-610"}return [c,i,s1,s2].join(",")}function m611(b,n){var o0=b*n*2;var o1=b+n*3;var o2=b*n*4;var o3=b|n*5;if(b>n){return o0+"item-611"}console.log("changed 611",b);return [b,n,o1,o2].join(",")}function m612(a,s){var c0=a|s*3;var c1=a&s*4;var c2=a&s*5;var c3=a*s*6;if(a>s){return c0+"item-612"}return [a,s,c1,c2].join(",")}function m613(i,s){var r0=i&s*4;var r1=i*s*5;var r2=i-s*6;var r3=i|s*7;if(i>s){return r0+"item-613"}return [i,s,r1,r2].join(",")}function m614(n,t){var a0=n-t*5;var a1=n+t*6;var a2=n|t*7;var a3=n+t*8;if(n>t){return a0+"item-614"}return [n,t,a1,a2].join(",")}function m615(a,l){var b0=a|l*6;var b1=a|l*7;var b2=a-l*8;var b3=a-l*9;if(a>l){return b0+"item-615"}return [a,l,b1,b2].join(",")}function m616(d,l){var u0=d+l*0;var u1=d-l*1;var u2=d|l*2;var u3=d+l*3;if(d>l){return u0+"item-616"}return [d,l,u1,u2].join(",")}function m617(d,t){var r0=d&t*1;var r1=d*t*2;var r2=d*t*3;var r3=d|t*4;if(d>t){return r0+"item-617"}return [d,t,r1,r2].join(",")}function m618(n,e){var b0=n*e*2;var b1=n|e*3;var b2=n*e*4;var b3=n-e*5;if(n>e){return b0+"item-618"}return [n,e,b1,b2].join(",")}function m619(d,t){var s0=d*t*3;var s1=d&t*4;var s2=d+t*5;var s3=d&t*6;if(d>t){return s0+"item-619"}return [d,t,s1,s2].join(",")}function m620(a,s){var b0=a&s*4;var b1=a&s*5;var b2=a*s*6;var b3=a&s*7;if(a>s){return b0+"item-620"}return [a,s,b1,b2].join(",")}function m621(b,o){var l0=b+o*5;var l1=b-o*6;var l2=b*o*7;var l3=b-o*8;if(b>o){return l0+"item-621"}return [b,o,l1,l2].join(",")}function m622(a,l){var n0=a|l*6;var n1=a-l*7;var n2=a|l*8;var n3=a|l*9;if(a>l){return n0+"item-622"}return [a,l,n1,n2].join(",")}function m623(n,e){var o0=n|e*0;var o1=n&e*1;var o2=n*e*2;var o3=n-e*3;if(n>e){return o0+"item-623"}return [n,e,o1,o2].join(",")}function m624(r,s){var f0=r-s*1;var f1=r+s*2;var f2=r+s*3;var f3=r|s*4;if(r>s){return f0+"item-624"}return [r,s,f1,f2].join(",")}function m625(l,o){var s0=l+o*2;var s1=l|o*3;var s2=l-o*4;var s3=l-o*5;if(l>o){return s0+"item-625"}return [l,o,s1,s2].join(",")}function m626(n,u){var t0=n|u*3;var t1=n|u*4;var t2=n*u*5;var t3=n-u*6;if(n>u){return t0+"item-626"}return [n,u,t1,t2].join(",")}function m627(d,n){var c0=d-n*4;var c1=d|n*5;var c2=d*n*6;var c3=d+n*7;if(d>n){return c0+"item-627"}return [d,n,c1,c2].join(",")}function m628(l,e){var b0=l-e*5;var b1=l+e*6;var b2=l*e*7;var b3=l+e*8;if(l>e){return b0+"item-628"}return [l,e,b1,b2].join(",")}function m629(c,a){var l0=c*a*6;var l1=c-a*7;var l2=c-a*8;var l3=c+a*9;if(c>a){return l0+"item-629"}return [c,a,l1,l2].join(",")}function m630(c,a){var o0=c+a*0;var o1=c&a*1;var o2=c|a*2;var o3=c|a*3;if(c>a){return o0+"item-630"}return [c,a,o1,o2].join(",")}function m631(r,o){var f0=r+o*1;var f1=r&o*2;var f2=r-o*3;var f3=r&o*4;if(r>o){return f0+"item-631"}return [r,o,f1,f2].join(",")}function m632(d,a){var b0=d-a*2;var b1=d+a*3;var b2=d+a*4;var b3=d|a*5;if(d>a){return b0+"item-632"}return [d,a,b1,b2].join(",")}function m633(o,e){var f0=o+e*3;var f1=o+e*4;var f2=o&e*5;var f3=o-e*6;if(o>e){return f0+"item-633"}return [o,e,f1,f2].join(",")}function m634(s,e){var c0=s&e*4;var c1=s-e*5;var c2=s*e*6;var c3=s+e*7;if(s>e){return c0+"item-634"}return [s,e,c1,c2].join(",")}function m635(r,u){var e0=r&u*5;var e1=r-u*6;var e2=r&u*7;var e3=r*u*8;if(r>u){return e0+"item-635"}return [r,u,e1,e2].join(",")}function m636(l,d){var r0=l-d*6;var r1=l&d*7;var r2=l+d*8;var r3=l*d*9;if(l>d){return r0+"item-636"}return [l,d,r1,r2].join(",")}function m637(i,c){var b0=i|c*0;var b1=i+c*1;var b2=i+c*2;var b3=i&c*3;if(i>c){return b0+"item-637"}return [i,c,b1,b2].join(",")}function m638(s,o){var l0=s|o*1;var l1=s-o*2;var l2=s-o*3;var l3=s|o*4;if(s>o){return l0+"item-638"}return [s,o,l1,l2].join(",")}function m639(a,s){var d0=a+s*2;var d1=a|s*3;var d2=a-s*4;var d3=a-s*5;if(a>s){return d0+"item-639"}return [a,s,d1,d2].join(",")}function m640(u,d){var s0=u*d*3;var s1=u&d*4;var s2=u&d*5;var s3=u|d*6;if(u>d){return s0+"item-640"}return [u,d,s1,s2].join(",")}function m641(l,d){var c0=l*d*4;var c1=l|d*5;var c2=l&d*6;var c3=l-d*7;if(l>d){return c0+"item-641"}return [l,d,c1,c2].join(",")}function m642(a,d){var b0=a|d*5;var b1=a*d*6;var b2=a&d*7;var b3=a|d*8;if(a>d){return b0+"item-642"}return [a,d,b1,b2].join(",")}function m643(e,r){var b0=e+r*6;var b1=e+r*7;var b2=e&r*8;var b3=e*r*9;if(e>r){return b0+"item-643"}return [e,r,b1,b2].join(",")}function m644(e,l){var s0=e-l*0;var s1=e&l*1;var s2=e+l*2;var s3=e-l*3;if(e>l){return s0+"item-644"}return [e,l,s1,s2].join(",")}function m645(o,r){var c0=o|r*1;var c1=o&r*2;var c2=o*r*3;var c3=o+r*4;if(o>r){return c0+"item-645"}return [o,r,c1,c2].join(",")}function m646(a,o){var i0=a|o*2;var i1=a&o*3;var i2=a*o*4;var i3=a-o*5;if(a>o){return i0+"item-646"}return [a,o,i1,i2].join(",")}function m647(s,o){var l0=s*o*3;var l1=s+o*4;var l2=s+o*5;var l3=s-o*6;if(s>o){return l0+"item-647"}return [s,o,l1,l2].join(",")}function m648(d,a){var l0=d*a*4;var l1=d+a*5;var l2=d-a*6;var l3=d-a*7;if(d>a){return l0+"item-648"}return [d,a,l1,l2].join(",")}function m649(a,b){var r0=a+b*5;var r1=a*b*6;var r2=a-b*7;var r3=a|b*8;if(a>b){return r0+"item-649"}return [a,b,r1,r2].join(",")}function m650(s,o){var f0=s+o*6;var f1=s+o*7;var f2=s&o*8;var f3=s*o*9;if(s>o){return f0+"item-650"}return [s,o,f1,f2].join(",")}function m651(c,r){var d0=c|r*0;var d1=c-r*1;var d2=c*r*2;var d3=c+r*3;if(c>r){return d0+"item-651"}return [c,r,d1,d2].join(",")}function m652(f,s){var l0=f&s*1;var l1=f|s*2;var l2=f&s*3;var l3=f&s*4;if(f>s){return l0+"item-652"}return [f,s,l1,l2].join(",")}function m653(l,r){var t0=l*r*2;var t1=l*r*3;var t2=l+r*4;var t3=l&r*5;if(l>r){return t0+"item-653"}return [l,r,t1,t2].join(",")}function m654(i,r){var c0=i+r*3;var c1=i&r*4;var c2=i&r*5;var c3=i|r*6;if(i>r){return c0+"item-654"}return [i,r,c1,c2].join(",")}function m655(s,n){var d0=s+n*4;var d1=s*n*5;var d2=s&n*6;var d3=s|n*7;if(s>n){return d0+"item-655"}return [s,n,d1,d2].join(",")}function m656(a,o){var e0=a-o*5;var e1=a|o*6;var e2=a-o*7;var e3=a*o*8;if(a>o){return e0+"item-656"}return [a,o,e1,e2].join(",")}function m657(c,e){var d0=c-e*6;var d1=c|e*7;var d2=c-e*8;var d3=c*e*9;if(c>e){return d0+"item-657"}return [c,e,d1,d2].join(",")}function m658(c,d){var s0=c+d*0;var s1=c|d*1;var s2=c*d*2;var s3=c&d*3;if(c>d){return s0+"item-658"}return [c,d,s1,s2].join(",")}function m659(b,l){var u0=b|l*1;var u1=b&l*2;var u2=b&l*3;var u3=b|l*4;if(b>l){return u0+"item-659"}return [b,l,u1,u2].join(",")}function m660(l,i){var a0=l|i*2;var a1=l*i*3;var a2=l-i*4;var a3=l&i*5;if(l>i){return a0+"item-660"}return [l,i,a1,a2].join(",")}function m661(d,t){var n0=d|t*3;var n1=d-t*4;var n2=d&t*5;var n3=d|t*6;if(d>t){return n0+"item-661"}return [d,t,n1,n2].join(",")}function m662(e,d){var n0=e|d*4;var n1=e-d*5;var n2=e+d*6;var n3=e*d*7;if(e>d){return n0+"item-662"}return [e,d,n1,n2].join(",")}function m663(n,u){var s0=n+u*5;var s1=n*u*6;var s2=n|u*7;var s3=n|u*8;if(n>u){return s0+"item-663"}return [n,u,s1,s2].join(",")}function m664(c,u){var r0=c&u*6;var r1=c-u*7;var r2=c+u*8;var r3=c|u*9;if(c>u){return r0+"item-664"}return [c,u,r1,r2].join(",")}function m665(u,c){var b0=u-c*0;var b1=u*c*1;var b2=u&c*2;var b3=u+c*3;if(u>c){return b0+"item-665"}return [u,c,b1,b2].join(",")}function m666(a,u){var b0=a*u*1;var b1=a*u*2;var b2=a&u*3;var b3=a+u*4;if(a>u){return b0+"item-666"}return [a,u,b1,b2].join(",")}function m667(a,e){var o0=a&e*2;var o1=a+e*3;var o2=a|e*4;var o3=a&e*5;if(a>e){return o0+"item-667"}return [a,e,o1,o2].join(",")}function m668(o,u){var d0=o*u*3;var d1=o&u*4;var d2=o+u*5;var d3=o&u*6;if(o>u){return d0+"item-668"}return [o,u,d1,d2].join(",")}function m669(t,c){var n0=t&c*4;var n1=t&c*5;var n2=t+c*6;var n3=t&c*7;if(t>c){return n0+"item-669"}return [t,c,n1,n2].join(",")}function m670(b,e){var d0=b&e*5;var d1=b-e*6;var d2=b*e*7;var d3=b-e*8;if(b>e){return d0+"item-670"}return [b,e,d1,d2].join(",")}function m671(a,r){var l0=a-r*6;var l1=a+r*7;var l2=a-r*8;var l3=a+r*9;if(a>r){return l0+"item-671"}return [a,r,l1,l2].join(",")}function m672(n,i){var c0=n&i*0;var c1=n*i*1;var c2=n+i*2;var c3=n+i*3;if(n>i){return c0+"item-672"}return [n,i,c1,c2].join(",")}function m673(l,e){var d0=l*e*1;var d1=l+e*2;var d2=l|e*3;var d3=l+e*4;if(l>e){return d0+"item-673"}return [l,e,d1,d2].join(",")}function m674(o,u){var t0=o-u*2;var t1=o+u*3;var t2=o-u*4;var t3=o|u*5;if(o>u){return t0+"item-674"}return [o,u,t1,t2].join(",")}function m675(i,c){var o0=i-c*3;var o1=i&c*4;var o2=i+c*5;var o3=i&c*6;if(i>c){return o0+"item-675"}return [i,c,o1,o2].join(",")}function m676(t,a){var s0=t-a*4;var s1=t*a*5;var s2=t|a*6;var s3=t-a*7;if(t>a){return s0+"item-676"}return [t,a,s1,s2].join(",")}function m677(t,a){var d0=t-a*5;var d1=t*a*6;var d2=t*a*7;var d3=t*a*8;if(t>a){return d0+"item-677"}return [t,a,d1,d2].join(",")}function m678(e,l){var f0=e-l*6;var f1=e|l*7;var f2=e&l*8;var f3=e&l*9;if(e>l){return f0+"item-678"}return [e,l,f1,f2].join(",")}function m679(i,n){var d0=i-n*0;var d1=i+n*1;var d2=i|n*2;var d3=i|n*3;if(i>n){return d0+"item-679"}return [i,n,d1,d2].join(",")}function m680(b,c){var i0=b-c*1;var i1=b+c*2;var i2=b|c*3;var i3=b+c*4;if(b>c){return i0+"item-680"}return [b,c,i1,i2].join(",")}function m681(n,u){var i0=n-u*2;var i1=n+u*3;var i2=n-u*4;var i3=n*u*5;if(n>u){return i0+"item-681"}return [n,u,i1,i2].join(",")}function m682(c,i){var l0=c|i*3;var l1=c+i*4;var l2=c&i*5;var l3=c*i*6;if(c>i){return l0+"item-682"}return [c,i,l1,l2].join(",")}function m683(u,d){var e0=u+d*4;var e1=u|d*5;var e2=u-d*6;var e3=u-d*7;if(u>d){return e0+"item-683"}return [u,d,e1,e2].join(",")}function m684(n,l){var a0=n-l*5;var a1=n+l*6;var a2=n-l*7;var a3=n&l*8;if(n>l){return a0+"item-684"}return [n,l,a1,a2].join(",")}function m685(i,a){var f0=i-a*6;var f1=i+a*7;var f2=i|a*8;var f3=i&a*9;if(i>a){return f0+"item-685"}return [i,a,f1,f2].join(",")}function m686(a,r){var t0=a&r*0;var t1=a-r*1;var t2=a+r*2;var t3=a*r*3;if(a>r){return t0+"item-686"}return [a,r,t1,t2].join(",")}function m687(i,s){var b0=i&s*1;var b1=i-s*2;var b2=i|s*3;var b3=i+s*4;if(i>s){return b0+"item-687"}return [i,s,b1,b2].join(",")}function m688(t,l){var e0=t|l*2;var e1=t-l*3;var e2=t&l*4;var e3=t&l*5;if(t>l){return e0+"item-688"}return [t,l,e1,e2].join(",")}function m689(f,a){var l0=f*a*3;var l1=f&a*4;var l2=f-a*5;var l3=f*a*6;if(f>a){return l0+"item-689"}return [f,a,l1,l2].join(",")}function m690(t,r){var f0=t|r*4;var f1=t|r*5;var f2=t|r*6;var f3=t-r*7;if(t>r){return f0+"item-690"}return [t,r,f1,f2].join(",")}function m691(a,s){var f0=a-s*5;var f1=a*s*6;var f2=a+s*7;var f3=a&s*8;if(a>s){return f0+"item-691"}return [a,s,f1,f2].join(",")}function m692(f,d){var u0=f|d*6;var u1=f-d*7;var u2=f+d*8;var u3=f&d*9;if(f>d){return u0+"item-692"}return [f,d,u1,u2].join(",")}function m693(i,r){var c0=i&r*0;var c1=i|r*1;var c2=i-r*2;var c3=i-r*3;if(i>r){return c0+"item-693"}return [i,r,c1,c2].join(",")}function m694(u,a){var o0=u&a*1;var o1=u*a*2;var o2=u+a*3;var o3=u*a*4;if(u>a){return o0+"item-694"}return [u,a,o1,o2].join(",")}function m695(r,s){var i0=r&s*2;var i1=r|s*3;var i2=r&s*4;var i3=r-s*5;if(r>s){return i0+"item-695"}return [r,s,i1,i2].join(",")}function m696(a,s){var u0=a&s*3;var u1=a&s*4;var u2=a-s*5;var u3=a+s*6;if(a>s){return u0+"item-696"}return [a,s,u1,u2].join(",")}function m697(t,r){var b0=t*r*4;var b1=t-r*5;var b2=t-r*6;var b3=t+r*7;if(t>r){return b0+"item-697"}return [t,r,b1,b2].join(",")}function m698(u,n){var d0=u&n*5;var d1=u|n*6;var d2=u*n*7;var d3=u|n*8;if(u>n){return d0+"item-698"}return [u,n,d1,d2].join(",")}function m699(d,i){var t0=d&i*6;var t1=d|i*7;var t2=d-i*8;var t3=d-i*9;if(d>i){return t0+"item-699"}return [d,i,t1,t2].join(",")}window.m=[m0,m1,m2]}();
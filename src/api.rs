@@ -0,0 +1,683 @@
+//! The library API: diff two strings and get the changes on each
+//! line, the same data as `--display=json`.
+
+use std::{
+    borrow::Cow,
+    collections::{HashMap, HashSet},
+    path::Path,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use line_numbers::LineNumber;
+
+use crate::{
+    cli,
+    display::{
+        self,
+        context::{all_matched_lines_filled, opposite_positions},
+        hunks::{
+            matched_lines_indexes_for_hunk, matched_pos_to_hunks, merge_adjacent, merge_close,
+            split_large,
+        },
+        json,
+        side_by_side::lines_with_novel,
+    },
+    lines::MaxLine,
+    options::{self, DisplayMode, DisplayOptions},
+    parse::{
+        guess_language::{
+            guess, language_from_attribute, language_globs, language_name,
+            language_override_from_name, Language, LanguageOverride,
+        },
+        syntax::{self, MatchedPos, StringKind},
+    },
+    summary::FileFormat,
+};
+
+/// Options for [diff_files].
+///
+/// Start from [DiffOptions::default] and set the fields you need, as
+/// new options may be added.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct DiffOptions {
+    /// Use a text diff if the structural diff would need more than
+    /// this many graph vertices, like `--graph-limit`.
+    pub graph_limit: usize,
+    /// Use a text diff if either side is larger than this many
+    /// bytes, like `--byte-limit`.
+    pub byte_limit: usize,
+    /// Use a text diff if there are more than this many parse
+    /// errors, like `--parse-error-limit`.
+    pub parse_error_limit: usize,
+    /// Don't report changes in comments, like `--ignore-comments`.
+    pub ignore_comments: bool,
+    /// Report code that moved within the file as
+    /// [ChangeKind::Moved], like `--detect-moves`.
+    pub detect_moves: bool,
+    /// Use a text diff if the structural diff takes longer than
+    /// this, like `--timeout`.
+    pub timeout: Option<Duration>,
+    /// Stop diffing early when this token is cancelled, see
+    /// [FileDiff::cancelled].
+    pub cancel: Option<CancellationToken>,
+}
+
+impl Default for DiffOptions {
+    fn default() -> Self {
+        let defaults = options::DiffOptions::default();
+        Self {
+            graph_limit: defaults.graph_limit,
+            byte_limit: defaults.byte_limit,
+            parse_error_limit: defaults.parse_error_limit,
+            ignore_comments: defaults.ignore_comments,
+            detect_moves: defaults.detect_moves,
+            timeout: defaults.timeout,
+            cancel: defaults.cancel,
+        }
+    }
+}
+
+impl DiffOptions {
+    fn to_cli_options(&self) -> options::DiffOptions {
+        options::DiffOptions {
+            graph_limit: self.graph_limit,
+            byte_limit: self.byte_limit,
+            parse_error_limit: self.parse_error_limit,
+            ignore_comments: self.ignore_comments,
+            detect_moves: self.detect_moves,
+            timeout: self.timeout,
+            cancel: self.cancel.clone(),
+            ..options::DiffOptions::default()
+        }
+    }
+}
+
+/// A flag to stop diffs that are in progress, e.g. when an editor
+/// no longer needs the result. Clones share the same flag, so keep
+/// one and pass another in [DiffOptions::cancel].
+///
+/// Parsing and the structural diff check the flag regularly, and stop
+/// soon after it's set.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<AtomicUsize>);
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Stop every diff using this token. There's no way to undo
+    /// this, so use a new token for the next diff.
+    pub fn cancel(&self) {
+        self.0.store(1, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed) != 0
+    }
+
+    /// The flag, in the form that tree-sitter's parser checks.
+    pub(crate) fn flag(&self) -> &AtomicUsize {
+        &self.0
+    }
+}
+
+/// Tokens are equal if they share a flag.
+impl PartialEq for CancellationToken {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for CancellationToken {}
+
+/// The result of [diff_files].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde-types", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct FileDiff {
+    /// The language passed to [diff_files], or `None` for a text
+    /// diff.
+    pub language: Option<Language>,
+    /// If the files were diffed as text despite having a language,
+    /// why, e.g. because they exceeded [DiffOptions::byte_limit].
+    pub text_fallback_reason: Option<String>,
+    /// The changed regions, in order. This is empty if the files
+    /// have no changes, ignoring whitespace and other formatting
+    /// that doesn't change the syntax tree.
+    pub hunks: Vec<Hunk>,
+    /// Whether [DiffOptions::cancel] stopped the diff before it
+    /// finished, in which case there are no hunks.
+    pub cancelled: bool,
+}
+
+impl FileDiff {
+    /// This diff as JSON, with `"language"` (the name, or `null` for a
+    /// text diff), `"text_fallback_reason"` if set, `"cancelled"` if
+    /// set, and `"chunks"`, the hunks in the format of
+    /// `--display=json`.
+    pub fn to_json(&self) -> String {
+        json::file_diff_json(self)
+    }
+}
+
+/// A group of changed lines that are close together.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde-types", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct Hunk {
+    /// The lines with changes, in order. Lines that are aligned
+    /// with each other are reported together.
+    pub lines: Vec<Line>,
+}
+
+/// A line on the left, a line on the right, or two lines that are
+/// aligned with each other.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde-types", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct Line {
+    /// The line on the left, if any.
+    pub lhs: Option<LineChanges>,
+    /// The line on the right, if any.
+    pub rhs: Option<LineChanges>,
+}
+
+/// The changes on one line of one side.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde-types", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct LineChanges {
+    /// The zero-based line number.
+    pub line_number: u32,
+    /// The changed parts of the line, which may be empty if only
+    /// the other side of an aligned line changed.
+    pub changes: Vec<Change>,
+}
+
+/// A changed part of a line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde-types", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct Change {
+    /// The zero-based byte offset of the start of the change in the
+    /// line.
+    pub start: u32,
+    /// The byte offset of the end of the change, exclusive.
+    pub end: u32,
+    /// The changed text.
+    pub content: String,
+    /// How `difft` would highlight the change.
+    pub highlight: Highlight,
+    pub kind: ChangeKind,
+}
+
+/// The syntax highlighting class of a change.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde-types", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde-types", serde(rename_all = "snake_case"))]
+#[non_exhaustive]
+pub enum Highlight {
+    Delimiter,
+    Normal,
+    String,
+    Type,
+    Comment,
+    Keyword,
+    /// Text that the parser couldn't make sense of.
+    TreeSitterError,
+}
+
+/// How a change differs from the other side.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde-types", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde-types", serde(rename_all = "snake_case"))]
+#[non_exhaustive]
+pub enum ChangeKind {
+    /// Added on the right, or removed from the left.
+    Novel,
+    /// This content also occurs elsewhere on the other side, see
+    /// [DiffOptions::detect_moves].
+    Moved,
+    /// This content is part of code copied from `from_line` on the
+    /// left, and is the same as in the original.
+    Copied {
+        #[cfg_attr(feature = "serde-types", serde(rename = "copied_from"))]
+        from_line: u32,
+    },
+}
+
+impl Language {
+    /// The name of the language, e.g. "Rust" or "C++".
+    pub fn name(self) -> &'static str {
+        language_name(self)
+    }
+
+    /// The language called `name`, comparing case insensitively, e.g.
+    /// "rust" or "Objective-C". Short names that git and GitHub use,
+    /// such as "py" and "cpp", work too.
+    pub fn from_name(name: &str) -> Option<Language> {
+        match language_override_from_name(name) {
+            Some(LanguageOverride::Language(language)) => Some(language),
+            _ => language_from_attribute(name),
+        }
+    }
+
+    /// The file extensions of the language, without the dot and
+    /// sorted, e.g. `["rs"]`. Some languages are also detected by
+    /// file name or content, see [guess_language].
+    pub fn extensions(self) -> Vec<String> {
+        let mut extensions: Vec<String> = language_globs(self)
+            .iter()
+            .filter_map(|glob| glob.as_str().strip_prefix("*."))
+            .filter(|extension| !extension.contains(['*', '?', '[']))
+            .map(|extension| extension.to_owned())
+            .collect();
+        extensions.sort();
+        extensions
+    }
+}
+
+/// Guess the language of a file from its path, such as its extension,
+/// and its content, such as a shebang. This is the detection that
+/// `difft` uses, without `--override` or `.gitattributes`.
+///
+/// `contents` is the start of the file or all of it. It's read as
+/// UTF-8, replacing invalid bytes, so check separately whether the
+/// file is binary.
+pub fn guess_language(path: &Path, contents: &[u8]) -> Option<Language> {
+    guess(path, &String::from_utf8_lossy(contents), &[])
+}
+
+/// Diff `lhs` and `rhs` structurally as `language`, or as text if
+/// it's `None`. See [guess_language] to detect the language.
+///
+/// If the files can't be diffed structurally, e.g. due to parse
+/// errors, this falls back to a text diff and sets
+/// [FileDiff::text_fallback_reason].
+pub fn diff_files(
+    lhs: &str,
+    rhs: &str,
+    language: Option<Language>,
+    opts: &DiffOptions,
+) -> FileDiff {
+    let file_hunks = diff_files_iter(lhs, rhs, language, opts);
+    FileDiff {
+        language: file_hunks.language(),
+        text_fallback_reason: file_hunks.text_fallback_reason().map(|r| r.to_owned()),
+        cancelled: file_hunks.cancelled(),
+        hunks: file_hunks.collect(),
+    }
+}
+
+/// Like [diff_files], but returns the hunks one at a time, which
+/// uses less memory on files with many changes.
+pub fn diff_files_iter<'a>(
+    lhs: &'a str,
+    rhs: &'a str,
+    language: Option<Language>,
+    opts: &DiffOptions,
+) -> FileHunks<'a> {
+    // The display options only affect the hunks, and these are the
+    // settings of --display=json.
+    let display_options = DisplayOptions {
+        display_mode: DisplayMode::Json,
+        ..DisplayOptions::default()
+    };
+    let summary = cli::diff_text_content(
+        "",
+        None,
+        lhs,
+        rhs,
+        language,
+        &display_options,
+        &opts.to_cli_options(),
+    );
+
+    // The JSON display settings never truncate or split lines, so
+    // the positions are in `lhs` and `rhs`.
+    let mut file_hunks = FileHunks::new(
+        lhs,
+        rhs,
+        Cow::Owned(summary.lhs_positions),
+        Cow::Owned(summary.rhs_positions),
+        &display_options,
+    );
+    file_hunks.language = language;
+    match summary.file_format {
+        FileFormat::TextFallback { reason } if reason == cli::CANCELLED_REASON => {
+            file_hunks.cancelled = true;
+        }
+        FileFormat::TextFallback { reason } => file_hunks.text_fallback_reason = Some(reason),
+        _ => {}
+    }
+    file_hunks
+}
+
+/// The hunks of a diff, from [diff_files_iter]. Each [Hunk] is only
+/// built when the iterator reaches it, so consumers that handle one
+/// hunk at a time don't need the changes of the whole file in memory.
+pub struct FileHunks<'a> {
+    language: Option<Language>,
+    text_fallback_reason: Option<String>,
+    cancelled: bool,
+    lhs_lines: Vec<&'a str>,
+    rhs_lines: Vec<&'a str>,
+    lhs_positions: Cow<'a, [MatchedPos]>,
+    rhs_positions: Cow<'a, [MatchedPos]>,
+    lhs_lines_with_novel: HashSet<LineNumber>,
+    rhs_lines_with_novel: HashSet<LineNumber>,
+    matched_lines: Vec<(Option<LineNumber>, Option<LineNumber>)>,
+    /// The index in `matched_lines` to search for the next hunk from.
+    matched_lines_start: usize,
+    hunks: std::vec::IntoIter<display::hunks::Hunk>,
+}
+
+impl<'a> FileHunks<'a> {
+    /// The hunks in a diff of `lhs_src` and `rhs_src`, merged and
+    /// split as requested in `display_options`. `--display=json`
+    /// uses this too.
+    pub(crate) fn new(
+        lhs_src: &'a str,
+        rhs_src: &'a str,
+        lhs_positions: Cow<'a, [MatchedPos]>,
+        rhs_positions: Cow<'a, [MatchedPos]>,
+        display_options: &DisplayOptions,
+    ) -> Self {
+        let opposite_to_lhs = opposite_positions(&lhs_positions);
+        let opposite_to_rhs = opposite_positions(&rhs_positions);
+
+        let hunks = matched_pos_to_hunks(&lhs_positions, &rhs_positions);
+        let hunks = merge_adjacent(
+            &hunks,
+            &opposite_to_lhs,
+            &opposite_to_rhs,
+            lhs_src.max_line(),
+            rhs_src.max_line(),
+            0,
+            0,
+        );
+        let mut hunks = merge_close(hunks, display_options.merge_hunk_gap);
+        if let Some(max_hunk_lines) = display_options.max_hunk_lines {
+            hunks = split_large(hunks, max_hunk_lines);
+        }
+
+        // Files without changes don't need the lines.
+        let (lhs_lines, rhs_lines, lhs_lines_with_novel, rhs_lines_with_novel, matched_lines) =
+            if hunks.is_empty() {
+                (vec![], vec![], HashSet::new(), HashSet::new(), vec![])
+            } else {
+                let lhs_lines: Vec<&str> = lhs_src.split('\n').collect();
+                let rhs_lines: Vec<&str> = rhs_src.split('\n').collect();
+                let (lhs_lines_with_novel, rhs_lines_with_novel) =
+                    lines_with_novel(&lhs_positions, &rhs_positions);
+                let matched_lines = all_matched_lines_filled(
+                    &lhs_positions,
+                    &rhs_positions,
+                    &lhs_lines,
+                    &rhs_lines,
+                );
+                (
+                    lhs_lines,
+                    rhs_lines,
+                    lhs_lines_with_novel,
+                    rhs_lines_with_novel,
+                    matched_lines,
+                )
+            };
+
+        Self {
+            language: None,
+            text_fallback_reason: None,
+            cancelled: false,
+            lhs_lines,
+            rhs_lines,
+            lhs_positions,
+            rhs_positions,
+            lhs_lines_with_novel,
+            rhs_lines_with_novel,
+            matched_lines,
+            matched_lines_start: 0,
+            hunks: hunks.into_iter(),
+        }
+    }
+
+    /// The language passed to [diff_files_iter], or `None` for a
+    /// text diff.
+    pub fn language(&self) -> Option<Language> {
+        self.language
+    }
+
+    /// If the files were diffed as text despite having a language,
+    /// why, see [FileDiff::text_fallback_reason].
+    pub fn text_fallback_reason(&self) -> Option<&str> {
+        self.text_fallback_reason.as_deref()
+    }
+
+    /// Whether the diff was cancelled, see [FileDiff::cancelled].
+    pub fn cancelled(&self) -> bool {
+        self.cancelled
+    }
+
+    fn changed_hunk(&mut self, hunk: &display::hunks::Hunk) -> Hunk {
+        let mut lines: Vec<Line> = vec![];
+        // The index in `lines` of each pair of line numbers, as a
+        // line can be aligned more than once.
+        let mut line_indexes = HashMap::with_capacity(hunk.lines.len());
+
+        let matched_lines = &self.matched_lines[self.matched_lines_start..];
+        let (start_i, end_i) = matched_lines_indexes_for_hunk(matched_lines, hunk, 0, 0);
+        let aligned_lines = &matched_lines[start_i..end_i];
+        self.matched_lines_start += start_i;
+
+        for (lhs_line_num, rhs_line_num) in aligned_lines {
+            if !self
+                .lhs_lines_with_novel
+                .contains(&lhs_line_num.unwrap_or(LineNumber(0)))
+                && !self
+                    .rhs_lines_with_novel
+                    .contains(&rhs_line_num.unwrap_or(LineNumber(0)))
+            {
+                continue;
+            }
+
+            let key = (lhs_line_num.map(|l| l.0), rhs_line_num.map(|l| l.0));
+            let i = *line_indexes.entry(key).or_insert_with(|| {
+                lines.push(Line {
+                    lhs: key.0.map(LineChanges::new),
+                    rhs: key.1.map(LineChanges::new),
+                });
+                lines.len() - 1
+            });
+            let line = &mut lines[i];
+
+            if let (Some(line_num), Some(side)) = (lhs_line_num, &mut line.lhs) {
+                side.add_changes(*line_num, &self.lhs_lines, &self.lhs_positions);
+            }
+            if let (Some(line_num), Some(side)) = (rhs_line_num, &mut line.rhs) {
+                side.add_changes(*line_num, &self.rhs_lines, &self.rhs_positions);
+            }
+        }
+
+        Hunk { lines }
+    }
+}
+
+impl<'a> Iterator for FileHunks<'a> {
+    type Item = Hunk;
+
+    fn next(&mut self) -> Option<Hunk> {
+        let hunk = self.hunks.next()?;
+        Some(self.changed_hunk(&hunk))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.hunks.size_hint()
+    }
+}
+
+impl<'a> ExactSizeIterator for FileHunks<'a> {}
+
+impl LineChanges {
+    fn new(line_number: u32) -> Self {
+        Self {
+            line_number,
+            changes: vec![],
+        }
+    }
+
+    fn add_changes(
+        &mut self,
+        line_num: LineNumber,
+        src_lines: &[&str],
+        all_matches: &[MatchedPos],
+    ) {
+        let src_line = src_lines[line_num.0 as usize];
+
+        let matches = all_matches
+            .iter()
+            .filter(|m| m.pos.line == line_num)
+            .filter(|m| m.kind.is_novel());
+        for m in matches {
+            self.changes.push(Change {
+                start: m.pos.start_col,
+                end: m.pos.end_col,
+                content: src_line[(m.pos.start_col as usize)..(m.pos.end_col as usize)].to_owned(),
+                highlight: Highlight::from_match(&m.kind),
+                kind: match m.kind {
+                    syntax::MatchKind::Moved { .. } => ChangeKind::Moved,
+                    syntax::MatchKind::Copied { source_line, .. } => ChangeKind::Copied {
+                        from_line: source_line.0,
+                    },
+                    _ => ChangeKind::Novel,
+                },
+            })
+        }
+    }
+}
+
+impl Highlight {
+    fn from_match(kind: &syntax::MatchKind) -> Self {
+        use syntax::{AtomKind, MatchKind, TokenKind};
+
+        let highlight = match kind {
+            MatchKind::Ignored { highlight, .. } => highlight,
+            MatchKind::UnchangedToken { highlight, .. } => highlight,
+            MatchKind::Novel { highlight, .. } => highlight,
+            MatchKind::NovelWord { highlight, .. } => highlight,
+            MatchKind::NovelLinePart { highlight, .. } => highlight,
+            MatchKind::Moved { highlight, .. } => highlight,
+            MatchKind::Copied { highlight, .. } => highlight,
+        };
+
+        match highlight {
+            TokenKind::Delimiter => Highlight::Delimiter,
+            TokenKind::Atom(atom) => match atom {
+                AtomKind::String(StringKind::StringLiteral) => Highlight::String,
+                AtomKind::String(StringKind::Text) => Highlight::Normal,
+                AtomKind::Keyword => Highlight::Keyword,
+                AtomKind::Comment => Highlight::Comment,
+                AtomKind::Type => Highlight::Type,
+                AtomKind::Normal => Highlight::Normal,
+                AtomKind::TreeSitterError => Highlight::TreeSitterError,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_diff_files() {
+        let diff = diff_files(
+            "fn foo() {\n    bar();\n}\n",
+            "fn foo() {\n    bar(1);\n}\n",
+            Some(Language::Rust),
+            &DiffOptions::default(),
+        );
+        assert_eq!(diff.language, Some(Language::Rust));
+        assert_eq!(diff.text_fallback_reason, None);
+        assert_eq!(diff.hunks.len(), 1);
+
+        let lines = &diff.hunks[0].lines;
+        assert_eq!(lines.len(), 1);
+        let rhs = lines[0].rhs.as_ref().unwrap();
+        assert_eq!(rhs.line_number, 1);
+        assert_eq!(
+            rhs.changes,
+            vec![Change {
+                start: 8,
+                end: 9,
+                content: "1".to_owned(),
+                // Integer literals are @constant.builtin in the Rust
+                // highlights query.
+                highlight: Highlight::Keyword,
+                kind: ChangeKind::Novel,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_diff_files_iter() {
+        let lhs = "a = 1\nb = 2\n\n\n\n\n\n\n\nc = 3\n";
+        let rhs = "a = 10\nb = 2\n\n\n\n\n\n\n\nc = 30\n";
+        let opts = DiffOptions::default();
+
+        let mut file_hunks = diff_files_iter(lhs, rhs, Some(Language::Python), &opts);
+        assert_eq!(file_hunks.len(), 2);
+        let first = file_hunks.next().unwrap();
+        assert_eq!(file_hunks.len(), 1);
+
+        let diff = diff_files(lhs, rhs, Some(Language::Python), &opts);
+        assert_eq!(diff.hunks.len(), 2);
+        assert_eq!(first, diff.hunks[0]);
+    }
+
+    #[test]
+    fn test_diff_files_formatting_only() {
+        let diff = diff_files(
+            "fn foo() { bar(); }\n",
+            "fn foo() {\n    bar();\n}\n",
+            Some(Language::Rust),
+            &DiffOptions::default(),
+        );
+        assert_eq!(diff.hunks, vec![]);
+    }
+
+    #[test]
+    fn test_language_from_name() {
+        assert_eq!(Language::from_name("rust"), Some(Language::Rust));
+        assert_eq!(Language::from_name("Objective-C"), Some(Language::ObjC));
+        assert_eq!(Language::from_name("py"), Some(Language::Python));
+        assert_eq!(Language::from_name("text"), None);
+    }
+
+    #[test]
+    fn test_guess_language() {
+        assert_eq!(
+            guess_language(Path::new("main.rs"), b""),
+            Some(Language::Rust)
+        );
+        assert_eq!(
+            guess_language(Path::new("build"), b"#!/usr/bin/env python3\n"),
+            Some(Language::Python)
+        );
+        assert_eq!(guess_language(Path::new("notes.txt"), b"hello"), None);
+    }
+
+    #[test]
+    fn test_language_extensions() {
+        assert_eq!(Language::Rust.name(), "Rust");
+        assert_eq!(Language::Rust.extensions(), vec!["rs"]);
+        assert!(Language::Make.extensions().contains(&"mk".to_owned()));
+    }
+}
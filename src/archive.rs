@@ -0,0 +1,232 @@
+//! Diff archives as if they were directories.
+//!
+//! When both arguments are zip files, or both are tarballs (plain,
+//! gzipped or zstd compressed), we read the files in each archive
+//! into memory and diff the entries with the same path. Archives can
+//! expand to far more than their size on disk, so we stop reading
+//! after [MAX_ENTRIES] files or [MAX_EXTRACTED_BYTES] bytes, and the
+//! archives are diffed as binary files instead.
+
+use std::{
+    collections::BTreeMap,
+    fs::File,
+    io::{BufReader, Read, Seek},
+    path::Path,
+};
+
+use flate2::read::GzDecoder;
+use humansize::{format_size, BINARY};
+
+/// The most files we read from one archive.
+const MAX_ENTRIES: usize = 100_000;
+
+/// The most bytes we decompress from one archive.
+const MAX_EXTRACTED_BYTES: u64 = 512 * 1024 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ArchiveKind {
+    Zip,
+    Tar,
+    TarGz,
+    TarZst,
+}
+
+impl ArchiveKind {
+    /// The kind of archive that `path` is, from its extension.
+    fn from_path(path: &Path) -> Option<Self> {
+        let name = path.file_name()?.to_string_lossy().to_lowercase();
+        if name.ends_with(".zip") {
+            Some(ArchiveKind::Zip)
+        } else if name.ends_with(".tar") {
+            Some(ArchiveKind::Tar)
+        } else if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+            Some(ArchiveKind::TarGz)
+        } else if name.ends_with(".tar.zst") || name.ends_with(".tzst") {
+            Some(ArchiveKind::TarZst)
+        } else {
+            None
+        }
+    }
+
+    /// Whether the entries of the two kinds can be compared. Tarballs
+    /// can be compared regardless of their compression.
+    fn same_family(self, other: Self) -> bool {
+        (self == ArchiveKind::Zip) == (other == ArchiveKind::Zip)
+    }
+}
+
+/// Whether `path` has the extension of an archive we can read.
+pub(crate) fn is_archive_path(path: &Path) -> bool {
+    ArchiveKind::from_path(path).is_some()
+}
+
+/// If `lhs` and `rhs` are archive files of the same family, the kind
+/// of each.
+pub(crate) fn archive_kinds(lhs: &Path, rhs: &Path) -> Option<(ArchiveKind, ArchiveKind)> {
+    let lhs_kind = ArchiveKind::from_path(lhs)?;
+    let rhs_kind = ArchiveKind::from_path(rhs)?;
+    (lhs_kind.same_family(rhs_kind) && lhs.is_file() && rhs.is_file())
+        .then_some((lhs_kind, rhs_kind))
+}
+
+/// The content of each file in an archive, by its path in the
+/// archive.
+pub(crate) type Entries = BTreeMap<String, Vec<u8>>;
+
+/// How much we're willing to read from an archive.
+struct Budget {
+    max_entries: usize,
+    max_bytes: u64,
+    num_entries: usize,
+    num_bytes: u64,
+}
+
+impl Budget {
+    fn new(max_entries: usize, max_bytes: u64) -> Self {
+        Self {
+            max_entries,
+            max_bytes,
+            num_entries: 0,
+            num_bytes: 0,
+        }
+    }
+
+    /// Read one entry, failing rather than reading past the budget,
+    /// whatever size the archive claims the entry has.
+    fn read_entry(&mut self, reader: impl Read) -> Result<Vec<u8>, String> {
+        if self.num_entries == self.max_entries {
+            return Err(format!("it has more than {} files", self.max_entries));
+        }
+        self.num_entries += 1;
+
+        let bytes_left = self.max_bytes - self.num_bytes;
+        let mut bytes = vec![];
+        reader
+            .take(bytes_left + 1)
+            .read_to_end(&mut bytes)
+            .map_err(|e| e.to_string())?;
+        if bytes.len() as u64 > bytes_left {
+            return Err(format!(
+                "it expands to more than {}",
+                format_size(self.max_bytes, BINARY)
+            ));
+        }
+        self.num_bytes += bytes.len() as u64;
+        Ok(bytes)
+    }
+}
+
+/// Tarballs often store paths as `./foo`, so remove that to pair
+/// entries with zips or tarballs created differently.
+fn normalize_path(path: &str) -> String {
+    path.trim_start_matches("./")
+        .trim_start_matches('/')
+        .to_owned()
+}
+
+fn read_zip(reader: impl Read + Seek, budget: &mut Budget) -> Result<Entries, String> {
+    let mut archive = zip::ZipArchive::new(reader).map_err(|e| e.to_string())?;
+    let mut entries = Entries::new();
+    for i in 0..archive.len() {
+        let mut file = archive.by_index(i).map_err(|e| e.to_string())?;
+        if file.is_dir() {
+            continue;
+        }
+        let path = normalize_path(file.name());
+        let bytes = budget.read_entry(&mut file)?;
+        entries.insert(path, bytes);
+    }
+    Ok(entries)
+}
+
+/// Read the regular files in a tarball. Directories, links and other
+/// special entries are skipped. If a path occurs more than once, the
+/// last entry wins, as it would when extracting.
+fn read_tar(reader: impl Read, budget: &mut Budget) -> Result<Entries, String> {
+    let mut archive = tar::Archive::new(reader);
+    let mut entries = Entries::new();
+    for entry in archive.entries().map_err(|e| e.to_string())? {
+        let mut entry = entry.map_err(|e| e.to_string())?;
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+        let path = normalize_path(&entry.path().map_err(|e| e.to_string())?.to_string_lossy());
+        let bytes = budget.read_entry(&mut entry)?;
+        entries.insert(path, bytes);
+    }
+    Ok(entries)
+}
+
+/// Read every file in the archive at `path`, or a description of why
+/// we couldn't.
+pub(crate) fn read_entries(path: &Path, kind: ArchiveKind) -> Result<Entries, String> {
+    let file = File::open(path).map_err(|e| e.to_string())?;
+    let reader = BufReader::new(file);
+    let mut budget = Budget::new(MAX_ENTRIES, MAX_EXTRACTED_BYTES);
+    match kind {
+        ArchiveKind::Zip => read_zip(reader, &mut budget),
+        ArchiveKind::Tar => read_tar(reader, &mut budget),
+        ArchiveKind::TarGz => read_tar(GzDecoder::new(reader), &mut budget),
+        ArchiveKind::TarZst => {
+            let decoder = zstd::Decoder::with_buffer(reader).map_err(|e| e.to_string())?;
+            read_tar(decoder, &mut budget)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn tarball(files: &[(&str, &str)]) -> Vec<u8> {
+        let mut builder = tar::Builder::new(vec![]);
+        for (path, content) in files {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(content.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder
+                .append_data(&mut header, path, content.as_bytes())
+                .unwrap();
+        }
+        builder.into_inner().unwrap()
+    }
+
+    #[test]
+    fn test_archive_kinds() {
+        assert_eq!(
+            ArchiveKind::from_path(Path::new("bundle.TGZ")),
+            Some(ArchiveKind::TarGz)
+        );
+        assert_eq!(ArchiveKind::from_path(Path::new("notes.txt")), None);
+        assert!(ArchiveKind::Tar.same_family(ArchiveKind::TarZst));
+        assert!(!ArchiveKind::Zip.same_family(ArchiveKind::TarGz));
+    }
+
+    #[test]
+    fn test_read_tar() {
+        let bytes = tarball(&[
+            ("./conf/app.yaml", "port: 80\n"),
+            ("README", "old"),
+            ("README", "new"),
+        ]);
+        let entries = read_tar(&bytes[..], &mut Budget::new(10, 100)).unwrap();
+        assert_eq!(
+            entries.into_iter().collect::<Vec<_>>(),
+            vec![
+                ("README".to_owned(), b"new".to_vec()),
+                ("conf/app.yaml".to_owned(), b"port: 80\n".to_vec()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_read_tar_over_budget() {
+        let bytes = tarball(&[("a", "12345"), ("b", "67890")]);
+        assert!(read_tar(&bytes[..], &mut Budget::new(10, 8)).is_err());
+        assert!(read_tar(&bytes[..], &mut Budget::new(1, 100)).is_err());
+        assert!(read_tar(&bytes[..], &mut Budget::new(2, 10)).is_ok());
+    }
+}
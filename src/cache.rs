@@ -0,0 +1,531 @@
+//! An on-disk cache of diff results, enabled with `--cache-dir`.
+//!
+//! Workflows like `git log -p` diff the same pairs of files
+//! repeatedly. Cache entries are keyed by a hash of both files, the
+//! language and the options that affect the diff, and store the
+//! matched positions. A cache hit skips parsing and diffing. Hunks
+//! are recomputed from the positions, so display options aren't part
+//! of the key.
+//!
+//! The cache is best-effort: entries that can't be read, are
+//! corrupt, or were written by another version are treated as misses.
+
+use std::{
+    fs,
+    hash::Hasher,
+    io,
+    path::{Path, PathBuf},
+};
+
+use line_numbers::{LineNumber, SingleLineSpan};
+use log::info;
+use rustc_hash::FxHasher;
+
+use crate::{
+    options::DiffOptions,
+    parse::{
+        guess_language::{language_name, Language},
+        syntax::{AtomKind, MatchKind, MatchedPos, StringKind, TokenKind},
+    },
+    summary::FileFormat,
+};
+
+/// Increment this whenever the format of cache entries changes.
+const FORMAT_VERSION: u32 = 1;
+const MAGIC: &[u8; 4] = b"DFTC";
+const EXTENSION: &str = "dftc";
+
+/// The result of diffing two files, before computing hunks.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) struct CacheEntry {
+    pub(crate) file_format: FileFormat,
+    pub(crate) lhs_positions: Vec<MatchedPos>,
+    pub(crate) rhs_positions: Vec<MatchedPos>,
+    /// RHS lines that exceeded the graph limit, see
+    /// `fallback::mark_syntax_by_subtree`.
+    pub(crate) token_diffed_lines: Vec<(LineNumber, LineNumber)>,
+}
+
+/// The key for diffing `lhs_src` against `rhs_src`.
+pub(crate) fn cache_key(
+    lhs_src: &str,
+    rhs_src: &str,
+    language: Option<Language>,
+    diff_options: &DiffOptions,
+) -> String {
+    let hash_with_seed = |seed: u64| {
+        let mut hasher = FxHasher::default();
+        hasher.write_u64(seed);
+        hasher.write(env!("CARGO_PKG_VERSION").as_bytes());
+        hasher.write_usize(lhs_src.len());
+        hasher.write(lhs_src.as_bytes());
+        hasher.write_usize(rhs_src.len());
+        hasher.write(rhs_src.as_bytes());
+        hasher.write(language.map_or("", language_name).as_bytes());
+
+        hasher.write_usize(diff_options.graph_limit);
+        hasher.write_usize(diff_options.byte_limit);
+        for (pattern, byte_limit) in &diff_options.byte_limit_overrides {
+            hasher.write(pattern.as_str().as_bytes());
+            hasher.write_usize(*byte_limit);
+        }
+        hasher.write_u8(diff_options.byte_limit_action as u8);
+        hasher.write_usize(diff_options.parse_error_limit);
+        hasher.write_u8(diff_options.ignore_comments as u8);
+        hasher.write_u8(diff_options.ignore_punctuation as u8);
+        hasher.write_u8(diff_options.whitespace as u8);
+        hasher.write_u8(diff_options.prose_granularity as u8);
+        hasher.write(
+            diff_options
+                .word_regex
+                .as_ref()
+                .map_or("", |regex| regex.as_str())
+                .as_bytes(),
+        );
+        hasher.write_u8(diff_options.strip_cr as u8);
+        hasher.write_u8(diff_options.detect_moves as u8);
+        hasher.write_u8(diff_options.detect_copies as u8);
+        hasher.write_usize(diff_options.move_min_tokens);
+        hasher.write_u8(diff_options.unchanged_heuristic as u8);
+        hasher.write_u32(diff_options.unchanged_min_run);
+        hasher.write_usize(diff_options.unchanged_ngram);
+        hasher.write_u8(diff_options.anchors as u8);
+        hasher.finish()
+    };
+
+    // FxHasher is fast but only 64 bits, so combine two hashes to
+    // make collisions vanishingly unlikely.
+    format!(
+        "{:016x}{:016x}",
+        hash_with_seed(0),
+        hash_with_seed(0x9e37_79b9_7f4a_7c15)
+    )
+}
+
+fn entry_path(cache_dir: &Path, key: &str) -> PathBuf {
+    cache_dir.join(format!("{}.{}", key, EXTENSION))
+}
+
+fn token_kind_code(highlight: TokenKind) -> u8 {
+    match highlight {
+        TokenKind::Delimiter => 0,
+        TokenKind::Atom(AtomKind::Normal) => 1,
+        TokenKind::Atom(AtomKind::String(StringKind::StringLiteral)) => 2,
+        TokenKind::Atom(AtomKind::String(StringKind::Text)) => 3,
+        TokenKind::Atom(AtomKind::Type) => 4,
+        TokenKind::Atom(AtomKind::Comment) => 5,
+        TokenKind::Atom(AtomKind::Keyword) => 6,
+        TokenKind::Atom(AtomKind::TreeSitterError) => 7,
+    }
+}
+
+fn token_kind_from_code(code: u8) -> Option<TokenKind> {
+    Some(match code {
+        0 => TokenKind::Delimiter,
+        1 => TokenKind::Atom(AtomKind::Normal),
+        2 => TokenKind::Atom(AtomKind::String(StringKind::StringLiteral)),
+        3 => TokenKind::Atom(AtomKind::String(StringKind::Text)),
+        4 => TokenKind::Atom(AtomKind::Type),
+        5 => TokenKind::Atom(AtomKind::Comment),
+        6 => TokenKind::Atom(AtomKind::Keyword),
+        7 => TokenKind::Atom(AtomKind::TreeSitterError),
+        _ => return None,
+    })
+}
+
+fn write_u32(buf: &mut Vec<u8>, n: u32) {
+    buf.extend_from_slice(&n.to_le_bytes());
+}
+
+fn write_len(buf: &mut Vec<u8>, len: usize) {
+    write_u32(buf, len as u32);
+}
+
+fn write_span(buf: &mut Vec<u8>, span: &SingleLineSpan) {
+    write_u32(buf, span.line.0);
+    write_u32(buf, span.start_col);
+    write_u32(buf, span.end_col);
+}
+
+fn write_spans(buf: &mut Vec<u8>, spans: &[SingleLineSpan]) {
+    write_len(buf, spans.len());
+    for span in spans {
+        write_span(buf, span);
+    }
+}
+
+fn write_positions(buf: &mut Vec<u8>, positions: &[MatchedPos]) {
+    write_len(buf, positions.len());
+    for mp in positions {
+        write_span(buf, &mp.pos);
+        match &mp.kind {
+            MatchKind::UnchangedToken {
+                highlight,
+                self_pos,
+                opposite_pos,
+            } => {
+                buf.extend_from_slice(&[0, token_kind_code(*highlight)]);
+                write_spans(buf, self_pos);
+                write_spans(buf, opposite_pos);
+            }
+            MatchKind::Novel {
+                highlight,
+                punctuation,
+            } => {
+                buf.extend_from_slice(&[1, token_kind_code(*highlight), *punctuation as u8]);
+            }
+            MatchKind::NovelLinePart {
+                highlight,
+                self_pos,
+                opposite_pos,
+            } => {
+                buf.extend_from_slice(&[2, token_kind_code(*highlight)]);
+                write_span(buf, self_pos);
+                write_spans(buf, opposite_pos);
+            }
+            MatchKind::NovelWord { highlight } => {
+                buf.extend_from_slice(&[3, token_kind_code(*highlight)]);
+            }
+            MatchKind::Ignored { highlight } => {
+                buf.extend_from_slice(&[4, token_kind_code(*highlight)]);
+            }
+            MatchKind::Moved {
+                highlight,
+                opposite_line,
+            } => {
+                buf.extend_from_slice(&[5, token_kind_code(*highlight)]);
+                write_u32(buf, opposite_line.0);
+            }
+            MatchKind::Copied {
+                highlight,
+                source_line,
+            } => {
+                buf.extend_from_slice(&[6, token_kind_code(*highlight)]);
+                write_u32(buf, source_line.0);
+            }
+        }
+    }
+}
+
+fn encode(entry: &CacheEntry) -> Vec<u8> {
+    let mut buf = vec![];
+    buf.extend_from_slice(MAGIC);
+    write_u32(&mut buf, FORMAT_VERSION);
+
+    match &entry.file_format {
+        FileFormat::SupportedLanguage(_) => buf.push(0),
+        FileFormat::PlainText => buf.push(1),
+        FileFormat::TextFallback { reason } => {
+            buf.push(2);
+            write_len(&mut buf, reason.len());
+            buf.extend_from_slice(reason.as_bytes());
+        }
+        FileFormat::Generated => unreachable!("Generated files are not cached"),
+        FileFormat::Binary => unreachable!("Binary files are not cached"),
+        FileFormat::Directory => unreachable!("Directories are not cached"),
+    }
+
+    write_positions(&mut buf, &entry.lhs_positions);
+    write_positions(&mut buf, &entry.rhs_positions);
+
+    write_len(&mut buf, entry.token_diffed_lines.len());
+    for (start, end) in &entry.token_diffed_lines {
+        write_u32(&mut buf, start.0);
+        write_u32(&mut buf, end.0);
+    }
+
+    buf
+}
+
+/// Reads values from an encoded cache entry. Every method returns
+/// `None` if the entry is truncated or invalid.
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: usize) -> Option<&'a [u8]> {
+        if self.bytes.len() < n {
+            return None;
+        }
+        let (taken, rest) = self.bytes.split_at(n);
+        self.bytes = rest;
+        Some(taken)
+    }
+
+    fn u8(&mut self) -> Option<u8> {
+        Some(self.take(1)?[0])
+    }
+
+    fn u32(&mut self) -> Option<u32> {
+        let bytes = self.take(4)?;
+        Some(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    fn len(&mut self) -> Option<usize> {
+        Some(self.u32()? as usize)
+    }
+
+    fn token_kind(&mut self) -> Option<TokenKind> {
+        token_kind_from_code(self.u8()?)
+    }
+
+    fn span(&mut self) -> Option<SingleLineSpan> {
+        Some(SingleLineSpan {
+            line: self.u32()?.into(),
+            start_col: self.u32()?,
+            end_col: self.u32()?,
+        })
+    }
+
+    fn spans(&mut self) -> Option<Vec<SingleLineSpan>> {
+        let len = self.len()?;
+        let mut spans = vec![];
+        for _ in 0..len {
+            spans.push(self.span()?);
+        }
+        Some(spans)
+    }
+
+    fn positions(&mut self) -> Option<Vec<MatchedPos>> {
+        let len = self.len()?;
+        let mut positions = vec![];
+        for _ in 0..len {
+            let pos = self.span()?;
+            let kind = match self.u8()? {
+                0 => MatchKind::UnchangedToken {
+                    highlight: self.token_kind()?,
+                    self_pos: self.spans()?,
+                    opposite_pos: self.spans()?,
+                },
+                1 => MatchKind::Novel {
+                    highlight: self.token_kind()?,
+                    punctuation: self.u8()? != 0,
+                },
+                2 => MatchKind::NovelLinePart {
+                    highlight: self.token_kind()?,
+                    self_pos: self.span()?,
+                    opposite_pos: self.spans()?,
+                },
+                3 => MatchKind::NovelWord {
+                    highlight: self.token_kind()?,
+                },
+                4 => MatchKind::Ignored {
+                    highlight: self.token_kind()?,
+                },
+                5 => MatchKind::Moved {
+                    highlight: self.token_kind()?,
+                    opposite_line: self.u32()?.into(),
+                },
+                6 => MatchKind::Copied {
+                    highlight: self.token_kind()?,
+                    source_line: self.u32()?.into(),
+                },
+                _ => return None,
+            };
+            positions.push(MatchedPos { kind, pos });
+        }
+        Some(positions)
+    }
+}
+
+/// Decode a cache entry for a file in `language`. Returns `None` if
+/// the entry is invalid, or was written by another version.
+fn decode(bytes: &[u8], language: Option<Language>) -> Option<CacheEntry> {
+    let mut reader = Reader { bytes };
+    if reader.take(MAGIC.len())? != MAGIC || reader.u32()? != FORMAT_VERSION {
+        return None;
+    }
+
+    let file_format = match reader.u8()? {
+        0 => FileFormat::SupportedLanguage(language?),
+        1 => FileFormat::PlainText,
+        2 => {
+            let len = reader.len()?;
+            let reason = std::str::from_utf8(reader.take(len)?).ok()?;
+            FileFormat::TextFallback {
+                reason: reason.to_owned(),
+            }
+        }
+        _ => return None,
+    };
+
+    let lhs_positions = reader.positions()?;
+    let rhs_positions = reader.positions()?;
+
+    let num_token_diffed = reader.len()?;
+    let mut token_diffed_lines = vec![];
+    for _ in 0..num_token_diffed {
+        token_diffed_lines.push((reader.u32()?.into(), reader.u32()?.into()));
+    }
+
+    if !reader.bytes.is_empty() {
+        return None;
+    }
+
+    Some(CacheEntry {
+        file_format,
+        lhs_positions,
+        rhs_positions,
+        token_diffed_lines,
+    })
+}
+
+/// Look up `key` in the cache. Any problem reading the entry is
+/// treated as a cache miss.
+pub(crate) fn load(cache_dir: &Path, key: &str, language: Option<Language>) -> Option<CacheEntry> {
+    let bytes = fs::read(entry_path(cache_dir, key)).ok()?;
+    let entry = decode(&bytes, language);
+    if entry.is_none() {
+        info!("Ignoring invalid cache entry {}", key);
+    }
+    entry
+}
+
+/// Write `entry` to the cache. Failing to write isn't an error, as
+/// the cache is only an optimisation.
+pub(crate) fn store(cache_dir: &Path, key: &str, entry: &CacheEntry) {
+    if matches!(entry.file_format, FileFormat::Binary) {
+        return;
+    }
+
+    // Write to a temporary file and rename, so concurrent difftastic
+    // processes never see a partially written entry.
+    let tmp_path = cache_dir.join(format!("{}.{}.tmp", key, std::process::id()));
+    let res = fs::create_dir_all(cache_dir)
+        .and_then(|()| fs::write(&tmp_path, encode(entry)))
+        .and_then(|()| fs::rename(&tmp_path, entry_path(cache_dir, key)));
+    if let Err(e) = res {
+        info!("Could not write cache entry {}: {}", key, e);
+        let _ = fs::remove_file(&tmp_path);
+    }
+}
+
+/// Remove all the entries in `cache_dir`, returning how many were
+/// removed. Other files in the directory are left alone.
+pub(crate) fn clear(cache_dir: &Path) -> io::Result<usize> {
+    let read_dir = match fs::read_dir(cache_dir) {
+        Ok(read_dir) => read_dir,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(0),
+        Err(e) => return Err(e),
+    };
+
+    let mut num_removed = 0;
+    for dir_entry in read_dir {
+        let path = dir_entry?.path();
+        if path.extension().map_or(false, |ext| ext == EXTENSION) {
+            fs::remove_file(&path)?;
+            num_removed += 1;
+        }
+    }
+    Ok(num_removed)
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::options::ByteLimitAction;
+
+    fn sample_entry() -> CacheEntry {
+        let span = SingleLineSpan {
+            line: 3.into(),
+            start_col: 4,
+            end_col: 7,
+        };
+        CacheEntry {
+            file_format: FileFormat::TextFallback {
+                reason: "exceeded DFT_PARSE_ERROR_LIMIT".to_owned(),
+            },
+            lhs_positions: vec![
+                MatchedPos {
+                    kind: MatchKind::UnchangedToken {
+                        highlight: TokenKind::Atom(AtomKind::Keyword),
+                        self_pos: vec![span],
+                        opposite_pos: vec![span, span],
+                    },
+                    pos: span,
+                },
+                MatchedPos {
+                    kind: MatchKind::Moved {
+                        highlight: TokenKind::Delimiter,
+                        opposite_line: 10.into(),
+                    },
+                    pos: span,
+                },
+            ],
+            rhs_positions: vec![MatchedPos {
+                kind: MatchKind::Novel {
+                    highlight: TokenKind::Atom(AtomKind::String(StringKind::Text)),
+                    punctuation: true,
+                },
+                pos: span,
+            }],
+            token_diffed_lines: vec![(1.into(), 5.into())],
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let entry = sample_entry();
+        assert_eq!(decode(&encode(&entry), None), Some(entry));
+    }
+
+    #[test]
+    fn test_decode_invalid() {
+        let mut bytes = encode(&sample_entry());
+
+        assert_eq!(decode(&bytes[..bytes.len() - 1], None), None);
+
+        // A different format version.
+        bytes[4] += 1;
+        assert_eq!(decode(&bytes, None), None);
+    }
+
+    #[test]
+    fn test_cache_key() {
+        let diff_options = DiffOptions::default();
+        let key = cache_key("a", "b", None, &diff_options);
+
+        assert_eq!(key.len(), 32);
+        assert_eq!(key, cache_key("a", "b", None, &diff_options));
+        assert_ne!(key, cache_key("b", "a", None, &diff_options));
+        assert_ne!(
+            key,
+            cache_key(
+                "a",
+                "b",
+                None,
+                &DiffOptions {
+                    ignore_comments: true,
+                    ..DiffOptions::default()
+                }
+            )
+        );
+        assert_ne!(
+            key,
+            cache_key(
+                "a",
+                "b",
+                None,
+                &DiffOptions {
+                    word_regex: Some(regex::Regex::new("[^,]+").unwrap()),
+                    ..DiffOptions::default()
+                }
+            )
+        );
+        assert_ne!(
+            key,
+            cache_key(
+                "a",
+                "b",
+                None,
+                &DiffOptions {
+                    byte_limit_action: ByteLimitAction::Truncate,
+                    ..DiffOptions::default()
+                }
+            )
+        );
+    }
+}
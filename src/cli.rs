@@ -0,0 +1,3688 @@
+//! The `difft` command line interface. The binary only calls
+//! [run], so the CLI and the library share all the diffing code.
+
+use crossterm::tty::IsTty;
+use log::info;
+use notify::Watcher;
+
+use crate::conflicts::apply_conflict_markers;
+use crate::conflicts::START_LHS_MARKER;
+use crate::constants::Side;
+use crate::diff::changes::{insert_deep_unchanged, ChangeMap};
+use crate::diff::dijkstra::{GraphSearchError, Interrupt};
+use crate::diff::{anchors, fallback, moves, unchanged};
+use crate::display::context::opposite_positions;
+use crate::display::hunks::{
+    in_line_ranges, matched_pos_to_hunks, merge_adjacent, merge_close, merge_same_declaration,
+    set_enclosing_declarations, split_large,
+};
+use crate::display::invisible;
+use crate::display::long_lines::{self, LineMap};
+use crate::exit_codes::EXIT_BAD_ARGUMENTS;
+use crate::exit_codes::{self, EXIT_BROKEN_PIPE, EXIT_INTERRUPTED};
+use crate::files::{
+    decompress, dir_hash, escape_path, force_text, guess_content, non_utf8_path_bytes,
+    pair_paths_ignoring_case, read_file_or_die, read_files_or_die, read_or_die,
+    relative_paths_in_either, resolve_symlink, same_content, special_file_at, symlink_target,
+    without_compression_extension, Compression, ProbableFileKind,
+};
+use crate::logging::Level;
+use crate::parse::guess_language::language_globs;
+use crate::parse::guess_language::{
+    guess, guess_with_attribute, language_name, Language, LanguageOverride,
+};
+use crate::parse::syntax::{self, MatchedPos};
+use crate::profile::Phase;
+use crate::prose::ProseGranularity;
+use crate::reformat;
+
+use std::borrow::Cow;
+use std::cmp::{max, Reverse};
+use std::collections::BTreeMap;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{mpsc, Mutex};
+use std::time::{Duration, Instant, SystemTime};
+use std::{env, thread};
+
+use humansize::{format_size, BINARY};
+use owo_colors::OwoColorize;
+use rayon::prelude::*;
+use serde_json::json;
+use strum::IntoEnumIterator;
+use typed_arena::Arena;
+
+use crate::diff::sliders::fix_all_sliders;
+use crate::display::style::print_warning;
+use crate::options::FilePermissions;
+use crate::options::USAGE;
+use crate::options::{
+    is_git_dir_diff, ByteLimitAction, DiffOptions, DisplayMode, DisplayOptions, FileArgument,
+    MergeInput, Mode, Output, SortOrder, DEFAULT_UNCHANGED_MIN_RUN, DEFAULT_UNCHANGED_NGRAM,
+};
+use crate::pager::Pager;
+use crate::parse::syntax::init_next_prev;
+use crate::summary::{BinaryInfo, Degradation, DiffResult, FileContent, FileFormat, RunSummary};
+use crate::textconv::GitSide;
+use crate::{
+    archive, cache, diff, display, from_patch, generated, git, gitattributes, interactive,
+    line_parser, logging, options, pager, profile, prose, resource_usage, textconv, version,
+};
+use crate::{
+    diff::dijkstra::mark_syntax, lines::MaxLine, parse::syntax::init_all_info,
+    parse::tree_sitter_parser as tsp,
+};
+
+extern crate pretty_env_logger;
+
+/// Terminate the process if we get SIGPIPE.
+#[cfg(unix)]
+fn reset_sigpipe() {
+    unsafe {
+        libc::signal(libc::SIGPIPE, libc::SIG_DFL);
+    }
+}
+
+#[cfg(not(unix))]
+fn reset_sigpipe() {
+    // Do nothing.
+}
+
+/// Limit the number of threads used for diffing, if requested with
+/// `--jobs`.
+fn set_num_threads(jobs: Option<usize>) {
+    if let Some(jobs) = jobs {
+        rayon::ThreadPoolBuilder::new()
+            .num_threads(jobs)
+            .build_global()
+            .expect("The global thread pool should not be initialized yet");
+    }
+}
+
+/// The entrypoint of `difft`.
+pub(crate) fn run() {
+    pretty_env_logger::try_init_timed_custom_env("DFT_LOG")
+        .expect("The logger has not been previously initialized");
+    reset_sigpipe();
+
+    match options::parse_args() {
+        Mode::DumpTreeSitter {
+            path,
+            language_overrides,
+        } => {
+            let path = Path::new(&path);
+            let bytes = read_or_die(path);
+            let src = String::from_utf8_lossy(&bytes).to_string();
+
+            let language = guess(path, &src, &language_overrides);
+            match language {
+                Some(lang) => {
+                    let ts_lang = tsp::from_language(lang);
+                    let tree = tsp::to_tree(&src, &ts_lang);
+                    tsp::print_tree(&src, &tree);
+                }
+                None => {
+                    eprintln!("No tree-sitter parser for file: {:?}", path);
+                }
+            }
+        }
+        Mode::GuessLanguage {
+            path,
+            language_overrides,
+        } => {
+            let bytes = read_or_die(Path::new(&path));
+            let name = match guess_content(&bytes) {
+                ProbableFileKind::Binary => "Binary",
+                ProbableFileKind::Text(src) => {
+                    let language = guess_with_attribute(
+                        Path::new(&path),
+                        &src,
+                        &language_overrides,
+                        gitattributes::language(&path),
+                    );
+                    language.map_or("Text", language_name)
+                }
+            };
+            println!("{}", name);
+        }
+        Mode::DumpSyntax {
+            path,
+            ignore_comments,
+            language_overrides,
+        } => {
+            let path = Path::new(&path);
+            let bytes = read_or_die(path);
+            let src = String::from_utf8_lossy(&bytes).to_string();
+
+            let language = guess(path, &src, &language_overrides);
+            match language {
+                Some(lang) => {
+                    let ts_lang = tsp::from_language(lang);
+                    let arena = Arena::new();
+                    let ast = tsp::parse(&arena, &src, &ts_lang, ignore_comments);
+                    init_all_info(&ast, &[]);
+                    println!("{:#?}", ast);
+                }
+                None => {
+                    eprintln!("No tree-sitter parser for file: {:?}", path);
+                }
+            }
+        }
+        Mode::DumpGraph {
+            lhs_path,
+            rhs_path,
+            ignore_comments,
+            graph_limit,
+            max_depth,
+            language_overrides,
+        } => {
+            let lhs_path = Path::new(&lhs_path);
+            let rhs_path = Path::new(&rhs_path);
+            let lhs_src = String::from_utf8_lossy(&read_or_die(lhs_path)).to_string();
+            let rhs_src = String::from_utf8_lossy(&read_or_die(rhs_path)).to_string();
+
+            let language = guess(rhs_path, &rhs_src, &language_overrides);
+            match language {
+                Some(lang) => {
+                    let ts_lang = tsp::from_language(lang);
+                    let arena = Arena::new();
+                    let lhs = tsp::parse(&arena, &lhs_src, &ts_lang, ignore_comments);
+                    let rhs = tsp::parse(&arena, &rhs_src, &ts_lang, ignore_comments);
+                    init_all_info(&lhs, &rhs);
+
+                    let mut change_map = ChangeMap::default();
+                    for (lhs_section_nodes, rhs_section_nodes) in unchanged::mark_unchanged(
+                        &lhs,
+                        &rhs,
+                        &mut change_map,
+                        DEFAULT_UNCHANGED_MIN_RUN,
+                        DEFAULT_UNCHANGED_NGRAM,
+                        &Interrupt::default(),
+                    ) {
+                        init_next_prev(&lhs_section_nodes);
+                        init_next_prev(&rhs_section_nodes);
+
+                        if mark_syntax(
+                            lhs_section_nodes.first().copied(),
+                            rhs_section_nodes.first().copied(),
+                            &mut change_map,
+                            graph_limit,
+                            &Interrupt::default(),
+                        )
+                        .is_err()
+                        {
+                            eprintln!("error: Exceeded DFT_GRAPH_LIMIT while diffing.");
+                            std::process::exit(EXIT_BAD_ARGUMENTS);
+                        }
+                    }
+
+                    fix_all_sliders(lang, &lhs, &mut change_map);
+                    fix_all_sliders(lang, &rhs, &mut change_map);
+
+                    print!(
+                        "{}",
+                        diff::graphviz::to_dot(&lhs, &rhs, &change_map, max_depth)
+                    );
+                }
+                None => {
+                    eprintln!("No tree-sitter parser for file: {:?}", rhs_path);
+                }
+            }
+        }
+        Mode::ClearCache { cache_dir } => match cache::clear(&cache_dir) {
+            Ok(num_removed) => {
+                println!(
+                    "Removed {} cached diff{} from {}",
+                    num_removed,
+                    if num_removed == 1 { "" } else { "s" },
+                    cache_dir.display()
+                );
+            }
+            Err(e) => {
+                eprintln!("error: Could not clear {}: {}", cache_dir.display(), e);
+                std::process::exit(EXIT_BAD_ARGUMENTS);
+            }
+        },
+        Mode::Merge {
+            diff_options,
+            display_options,
+            language_overrides,
+            input,
+            display_path,
+            output,
+        } => {
+            set_num_threads(diff_options.jobs);
+
+            let mut out = open_output_or_die(&output);
+            write_or_die(diff_merge(
+                &mut out,
+                &display_path,
+                &input,
+                &display_options,
+                &diff_options,
+                &language_overrides,
+            ));
+            finish_output(out);
+        }
+        Mode::Watch {
+            diff_options,
+            display_options,
+            language_overrides,
+            lhs_path,
+            rhs_path,
+            display_path,
+        } => {
+            set_num_threads(diff_options.jobs);
+            watch_files(
+                &display_path,
+                &lhs_path,
+                &rhs_path,
+                &display_options,
+                &diff_options,
+                &language_overrides,
+            );
+        }
+        Mode::Version { verbose } => {
+            if verbose {
+                print!("{}", version::build_info());
+            } else {
+                println!("Difftastic {}", *version::VERSION);
+            }
+        }
+        Mode::ListLanguages {
+            use_color,
+            language_overrides,
+            json,
+        } => {
+            if json {
+                let mut out = BufWriter::new(std::io::stdout().lock());
+                write_or_die(display::json::print_languages(&mut out));
+                write_or_die(out.flush());
+                return;
+            }
+
+            let mut out = BufWriter::new(std::io::stdout().lock());
+            write_or_die(print_language_list(
+                &mut out,
+                &language_overrides,
+                use_color,
+            ));
+            write_or_die(out.flush());
+        }
+        Mode::GenerateCompletions { shell } => {
+            clap_complete::generate(
+                shell,
+                &mut options::completion_app(),
+                bin_name!(),
+                &mut std::io::stdout(),
+            );
+        }
+        Mode::DiffFromConflicts {
+            display_path,
+            path,
+            diff_options,
+            display_options,
+            set_exit_code,
+            fail_on_fallback,
+            quiet,
+            language_overrides,
+            output,
+        } => {
+            set_num_threads(diff_options.jobs);
+
+            let diff_result = diff_conflicts_file(
+                &display_path,
+                &path,
+                &display_options,
+                &diff_options,
+                &language_overrides,
+            );
+
+            exit_codes::record_file_format(&diff_result.file_format);
+            let has_reportable_change = diff_result.has_reportable_change();
+            if !quiet {
+                let mut out = open_output_or_die(&output);
+                write_or_die(match display_options.display_mode {
+                    DisplayMode::Html
+                    | DisplayMode::Stat
+                    | DisplayMode::Sarif
+                    | DisplayMode::Markdown
+                    | DisplayMode::Symbols
+                    | DisplayMode::Check => {
+                        print_combined(&mut out, &[diff_result], &display_options)
+                    }
+                    _ => print_diff_result(&mut out, &display_options, &diff_result),
+                });
+                finish_output(out);
+            }
+
+            finish_run(
+                &diff_options,
+                &display_options,
+                set_exit_code,
+                fail_on_fallback,
+                has_reportable_change,
+            );
+        }
+        Mode::DiffPairs {
+            diff_options,
+            display_options,
+            set_exit_code,
+            fail_on_fallback,
+            quiet,
+            language_overrides,
+            pairs,
+            output,
+        } => {
+            set_num_threads(diff_options.jobs);
+
+            let (pairs, skipped_pairs): (Vec<_>, Vec<_>) =
+                pairs.into_iter().partition(|(display_path, _, _)| {
+                    diff_options.path_filter.allows(Path::new(display_path))
+                });
+            let diff_iter =
+                diff_pairs(&pairs, &display_options, &diff_options, &language_overrides);
+            let encountered_changes =
+                print_results_or_die(diff_iter, &output, &display_options, quiet, false);
+            print_filter_summary(skipped_pairs.len());
+
+            finish_run(
+                &diff_options,
+                &display_options,
+                set_exit_code,
+                fail_on_fallback,
+                encountered_changes,
+            );
+        }
+        Mode::FromPatch {
+            diff_options,
+            display_options,
+            set_exit_code,
+            fail_on_fallback,
+            quiet,
+            language_overrides,
+            patch,
+            output,
+        } => {
+            set_num_threads(diff_options.jobs);
+
+            let patch_src = String::from_utf8_lossy(&read_file_or_die(&patch)).into_owned();
+            let patch_files = match from_patch::parse(&patch_src) {
+                Ok(patch_files) => patch_files,
+                Err(e) => {
+                    eprintln!("error: Could not parse patch {}: {}", patch, e);
+                    std::process::exit(EXIT_BAD_ARGUMENTS);
+                }
+            };
+
+            let diff_iter = diff_patch_files(
+                &patch_files,
+                &display_options,
+                &diff_options,
+                &language_overrides,
+            );
+            let encountered_changes =
+                print_results_or_die(diff_iter, &output, &display_options, quiet, false);
+
+            finish_run(
+                &diff_options,
+                &display_options,
+                set_exit_code,
+                fail_on_fallback,
+                encountered_changes,
+            );
+        }
+        Mode::GitRevisions {
+            diff_options,
+            display_options,
+            set_exit_code,
+            fail_on_fallback,
+            quiet,
+            language_overrides,
+            lhs,
+            rhs,
+            paths,
+            output,
+        } => {
+            set_num_threads(diff_options.jobs);
+
+            let toplevel = match git::toplevel() {
+                Ok(toplevel) => toplevel,
+                Err(e) => {
+                    eprintln!(
+                        "error: --git, --git-staged and --git-worktree must be used inside a git repository.\n{}",
+                        e
+                    );
+                    std::process::exit(EXIT_BAD_ARGUMENTS);
+                }
+            };
+            let changed_files = match git::changed_files(&lhs, &rhs, &paths) {
+                Ok(changed_files) => changed_files,
+                Err(e) => {
+                    eprintln!("{}", e);
+                    std::process::exit(EXIT_BAD_ARGUMENTS);
+                }
+            };
+
+            let diff_iter = diff_git_revisions(
+                &changed_files,
+                &lhs,
+                &rhs,
+                &toplevel,
+                &display_options,
+                &diff_options,
+                &language_overrides,
+            );
+            let encountered_changes =
+                print_results_or_die(diff_iter, &output, &display_options, quiet, false);
+
+            finish_run(
+                &diff_options,
+                &display_options,
+                set_exit_code,
+                fail_on_fallback,
+                encountered_changes,
+            );
+        }
+        Mode::Diff {
+            diff_options,
+            mut display_options,
+            set_exit_code,
+            fail_on_fallback,
+            quiet,
+            language_overrides,
+            lhs_path,
+            rhs_path,
+            lhs_permissions,
+            rhs_permissions,
+            display_path,
+            renamed_from,
+            extra_info,
+            textconv,
+            output,
+        } => {
+            set_num_threads(diff_options.jobs);
+
+            if lhs_path == rhs_path {
+                let is_dir = match &lhs_path {
+                    FileArgument::NamedPath(path) => path.is_dir(),
+                    _ => false,
+                };
+
+                print_warning(
+                    &format!(
+                        "You've specified the same {} twice.",
+                        if is_dir { "directory" } else { "file" }
+                    ),
+                    &display_options,
+                );
+            }
+
+            if matches!(display_options.display_mode, DisplayMode::JsonPatch) {
+                let ops = json_patch_or_die(&lhs_path, &rhs_path);
+                if !quiet {
+                    let mut out = open_output_or_die(&output);
+                    write_or_die(display::json_patch::print(&mut out, &ops));
+                    finish_output(out);
+                }
+
+                std::process::exit(exit_codes::exit_code(
+                    set_exit_code,
+                    fail_on_fallback,
+                    !ops.is_empty(),
+                ));
+            }
+
+            let archives = match (&lhs_path, &rhs_path) {
+                (FileArgument::NamedPath(lhs_path), FileArgument::NamedPath(rhs_path))
+                    if diff_options.diff_archives =>
+                {
+                    read_archives(lhs_path, rhs_path)
+                }
+                _ => None,
+            };
+
+            let mut encountered_changes = false;
+            match (&lhs_path, &rhs_path) {
+                (
+                    options::FileArgument::NamedPath(lhs_path),
+                    options::FileArgument::NamedPath(rhs_path),
+                ) if lhs_path.is_dir() && rhs_path.is_dir() => {
+                    display_options.hunk_selection_per_file = true;
+                    // Diffs in parallel when iterating this iterator.
+                    let (num_skipped, diff_iter) = diff_directories(
+                        lhs_path,
+                        rhs_path,
+                        extra_info,
+                        &display_options,
+                        &diff_options,
+                        &language_overrides,
+                    );
+
+                    encountered_changes = print_results_or_die(
+                        diff_iter,
+                        &output,
+                        &display_options,
+                        quiet,
+                        !display_options.new_file,
+                    );
+                    print_filter_summary(num_skipped);
+                    if !quiet {
+                        options::check_any_hunk_selected(&display_options);
+                    }
+                }
+                _ if archives.is_some() => {
+                    display_options.hunk_selection_per_file = true;
+                    let (lhs_entries, rhs_entries) = archives.expect("Checked by the guard");
+                    let (num_skipped, diff_iter) = diff_archives(
+                        &display_path,
+                        lhs_entries,
+                        rhs_entries,
+                        extra_info,
+                        &display_options,
+                        &diff_options,
+                        &language_overrides,
+                    );
+                    encountered_changes = print_results_or_die(
+                        diff_iter,
+                        &output,
+                        &display_options,
+                        quiet,
+                        !display_options.new_file,
+                    );
+                    print_filter_summary(num_skipped);
+                    if !quiet {
+                        options::check_any_hunk_selected(&display_options);
+                    }
+                }
+                _ => {
+                    let mut diff_result = match &textconv {
+                        Some(git_sides) => diff_file_textconv(
+                            &display_path,
+                            renamed_from,
+                            &lhs_path,
+                            &rhs_path,
+                            lhs_permissions.as_ref(),
+                            rhs_permissions.as_ref(),
+                            git_sides,
+                            &display_options,
+                            &diff_options,
+                            &language_overrides,
+                        ),
+                        None => diff_file(
+                            &display_path,
+                            renamed_from,
+                            &lhs_path,
+                            &rhs_path,
+                            lhs_permissions.as_ref(),
+                            rhs_permissions.as_ref(),
+                            &display_options,
+                            &diff_options,
+                            false,
+                            &language_overrides,
+                        ),
+                    };
+                    if let Some(extra_info) = &extra_info {
+                        diff_result.add_extra_info(extra_info);
+                    }
+                    let skipped = is_skipped(&display_options, &diff_result);
+                    if !skipped {
+                        exit_codes::record_file_format(&diff_result.file_format);
+                        encountered_changes = diff_result.has_reportable_change();
+                    }
+
+                    if output == Output::Interactive {
+                        if !skipped {
+                            write_or_die(print_interactive(&[diff_result], &display_options));
+                        }
+                    } else {
+                        let mut out = open_output_or_die(&output);
+                        write_or_die(match display_options.display_mode {
+                            _ if quiet || skipped => Ok(()),
+                            DisplayMode::Inline
+                            | DisplayMode::SideBySide
+                            | DisplayMode::SideBySideShowBoth
+                            | DisplayMode::Patch => {
+                                print_diff_result(&mut out, &display_options, &diff_result)
+                            }
+                            DisplayMode::Json => {
+                                display::json::print(&mut out, &diff_result, &display_options)
+                            }
+                            DisplayMode::JsonPatch => {
+                                unreachable!("JSON Patch output doesn't use the diff result")
+                            }
+                            DisplayMode::Html
+                            | DisplayMode::Stat
+                            | DisplayMode::Sarif
+                            | DisplayMode::Markdown
+                            | DisplayMode::Symbols
+                            | DisplayMode::Check => {
+                                print_combined(&mut out, &[diff_result], &display_options)
+                            }
+                        });
+                        finish_output(out);
+                    }
+                }
+            }
+
+            finish_run(
+                &diff_options,
+                &display_options,
+                set_exit_code,
+                fail_on_fallback,
+                encountered_changes,
+            );
+        }
+    };
+}
+
+/// Print the summary of the run and the `--profile` table to stderr,
+/// then exit with the exit code for `encountered_changes`.
+fn finish_run(
+    diff_options: &DiffOptions,
+    display_options: &DisplayOptions,
+    set_exit_code: bool,
+    fail_on_fallback: bool,
+    encountered_changes: bool,
+) -> ! {
+    // Each line of the summary has its own level, the lowest of
+    // which is shown with `--verbose`.
+    if logging::enabled(Level::Info) {
+        resource_usage::print_summary();
+    }
+    // JSON output includes the profile of each file instead.
+    if diff_options.profile && !matches!(display_options.display_mode, DisplayMode::Json) {
+        profile::print_table();
+    }
+
+    std::process::exit(exit_codes::exit_code(
+        set_exit_code,
+        fail_on_fallback,
+        encountered_changes,
+    ));
+}
+
+/// Print the results of `diff_iter` to `output`, in the order of the
+/// iterator unless `--sort-paths` asks otherwise, and return whether
+/// any file had changes. See [print_diff_results] for
+/// `list_one_sided`. Unless `--no-summary` or `--quiet` was given,
+/// the number of files of each kind is printed to stderr afterwards.
+fn print_results_or_die(
+    diff_iter: impl IndexedParallelIterator<Item = DiffResult>,
+    output: &Output,
+    display_options: &DisplayOptions,
+    quiet: bool,
+    list_one_sided: bool,
+) -> bool {
+    let run_summary = Mutex::new(RunSummary::default());
+    let diff_iter = diff_iter.inspect(|diff_result| {
+        let mut run_summary = run_summary.lock().expect("No other thread should panic");
+        if is_skipped(display_options, diff_result) {
+            run_summary.add_skipped();
+        } else {
+            exit_codes::record_file_format(&diff_result.file_format);
+            run_summary.add(diff_result);
+        }
+    });
+    let encountered_changes = if *output == Output::Interactive {
+        let mut results: Vec<_> = diff_iter.collect();
+        results.retain(|diff_result| !is_skipped(display_options, diff_result));
+        sort_results(&mut results, display_options.sort_order);
+        write_or_die(print_interactive(&results, display_options));
+        results
+            .iter()
+            .any(|diff_result| diff_result.has_reportable_change())
+    } else {
+        let mut out = open_output_or_die(output);
+        let encountered_changes = write_or_die(print_diff_results(
+            &mut out,
+            diff_iter,
+            display_options,
+            quiet,
+            list_one_sided,
+        ));
+        finish_output(out);
+        encountered_changes
+    };
+
+    // --quiet stops at the first changed file, so the counts would
+    // be incomplete.
+    if display_options.run_summary && !quiet {
+        print_run_summary(
+            &run_summary
+                .into_inner()
+                .expect("No other thread should panic"),
+        );
+    }
+    encountered_changes
+}
+
+/// Render each hunk of `diffs` separately, and show them one at a
+/// time for `--interactive`.
+fn print_interactive(
+    diffs: &[DiffResult],
+    display_options: &DisplayOptions,
+) -> std::io::Result<()> {
+    let mut files = vec![];
+    for diff in diffs {
+        let mut pages = vec![];
+        // Generated files only show a summary of the changes, not
+        // the hunks.
+        if diff.hunks.len() > 1
+            && diff.has_syntactic_changes
+            && diff.file_format != FileFormat::Generated
+        {
+            for hunk_num in 1..=diff.hunks.len() {
+                let mut hunk_display_options = display_options.clone();
+                hunk_display_options.hunk_selection = vec![(hunk_num, hunk_num)];
+
+                let mut page = vec![];
+                print_diff_result(&mut page, &hunk_display_options, diff)?;
+                pages.push(page);
+            }
+        } else {
+            let mut page = vec![];
+            print_diff_result(&mut page, display_options, diff)?;
+            if !page.is_empty() {
+                pages.push(page);
+            }
+        }
+
+        if !pages.is_empty() {
+            files.push(interactive::FilePages {
+                display_path: diff.display_path.clone(),
+                pages,
+            });
+        }
+    }
+
+    interactive::run(&files)
+}
+
+/// Sort `results` for `--sort-paths size` or `--sort-paths changes`.
+/// The sort is stable, so files that tie stay in the order they were
+/// diffed, which is by path for directories.
+fn sort_results(results: &mut [DiffResult], sort_order: SortOrder) {
+    match sort_order {
+        SortOrder::Path => {}
+        SortOrder::Size => results.sort_by_cached_key(|diff_result| {
+            let text_len = |content: &FileContent| match content {
+                FileContent::Text(src) => src.len(),
+                FileContent::Binary => 0,
+            };
+            Reverse(max(
+                text_len(&diff_result.lhs_src),
+                text_len(&diff_result.rhs_src),
+            ))
+        }),
+        SortOrder::Changes => results.sort_by_cached_key(|diff_result| {
+            let (insertions, deletions) = display::stat::line_changes(&diff_result.hunks);
+            Reverse(insertions + deletions)
+        }),
+    }
+}
+
+/// Print the results of diffing several files, such as the files in
+/// two directories. If `list_one_sided` is set, files that only exist
+/// on one side are listed rather than shown in full. Returns whether
+/// any file has changes.
+fn print_diff_results(
+    out: &mut dyn Write,
+    diff_iter: impl IndexedParallelIterator<Item = DiffResult>,
+    display_options: &DisplayOptions,
+    quiet: bool,
+    list_one_sided: bool,
+) -> std::io::Result<bool> {
+    if quiet {
+        // Stop diffing as soon as any file has changed.
+        return Ok(diff_iter.any(|diff_result| {
+            !is_skipped(display_options, &diff_result) && diff_result.has_reportable_change()
+        }));
+    }
+
+    if matches!(display_options.display_mode, DisplayMode::Json) {
+        let mut results: Vec<_> = diff_iter.collect();
+        if pager::INTERRUPT.is_cancelled() {
+            return Ok(false);
+        }
+        results.retain(|diff_result| !is_skipped(display_options, diff_result));
+        sort_results(&mut results, display_options.sort_order);
+        let encountered_changes = results
+            .iter()
+            .any(|diff_result| diff_result.has_reportable_change());
+        display::json::print_directory(out, results, display_options)?;
+        return Ok(encountered_changes);
+    }
+
+    if matches!(
+        display_options.display_mode,
+        DisplayMode::Html
+            | DisplayMode::Stat
+            | DisplayMode::Sarif
+            | DisplayMode::Markdown
+            | DisplayMode::Symbols
+            | DisplayMode::Check
+    ) {
+        let mut results: Vec<_> = diff_iter.collect();
+        if pager::INTERRUPT.is_cancelled() {
+            return Ok(false);
+        }
+        results.retain(|diff_result| !is_skipped(display_options, diff_result));
+        sort_results(&mut results, display_options.sort_order);
+        print_combined(out, &results, display_options)?;
+        return Ok(results
+            .iter()
+            .any(|diff_result| diff_result.has_reportable_change()));
+    }
+
+    let mut encountered_changes = false;
+    if display_options.sort_order != SortOrder::Path {
+        let mut results: Vec<DiffResult> = diff_iter.collect();
+        if pager::INTERRUPT.is_cancelled() {
+            return Ok(false);
+        }
+        results.retain(|diff_result| !is_skipped(display_options, diff_result));
+        sort_results(&mut results, display_options.sort_order);
+        for diff_result in results {
+            print_listing_or_diff_result(out, display_options, &diff_result, list_one_sided)?;
+            out.flush()?;
+
+            if diff_result.has_reportable_change() {
+                encountered_changes = true;
+            }
+        }
+    } else {
+        // We want to diff files in the directory in parallel, but
+        // print the results serially (to prevent display
+        // interleaving). Results that finish early wait until the
+        // results before them are printed, so the output is in the
+        // same order however the work is scheduled.
+        // https://github.com/rayon-rs/rayon/issues/210#issuecomment-551319338
+        thread::scope(|s| -> std::io::Result<()> {
+            let (send, recv) = std::sync::mpsc::sync_channel(1);
+
+            s.spawn(move || {
+                // The receiver is only disconnected if we stopped
+                // printing because of a write error.
+                let _ = diff_iter
+                    .enumerate()
+                    .try_for_each_with(send, |s, indexed_result| s.send(indexed_result));
+            });
+
+            let mut pending: BTreeMap<usize, DiffResult> = BTreeMap::new();
+            let mut next_index = 0;
+            for (index, diff_result) in recv.into_iter() {
+                pending.insert(index, diff_result);
+
+                while let Some(diff_result) = pending.remove(&next_index) {
+                    // Don't show the incomplete results of files
+                    // diffed after Ctrl-C.
+                    if pager::INTERRUPT.is_cancelled() {
+                        return Ok(());
+                    }
+                    next_index += 1;
+                    if is_skipped(display_options, &diff_result) {
+                        continue;
+                    }
+
+                    print_listing_or_diff_result(
+                        out,
+                        display_options,
+                        &diff_result,
+                        list_one_sided,
+                    )?;
+                    // Show each file as soon as it's ready, rather
+                    // than when the buffer fills.
+                    out.flush()?;
+
+                    if diff_result.has_reportable_change() {
+                        encountered_changes = true;
+                    }
+                }
+            }
+            Ok(())
+        })?;
+    }
+
+    Ok(encountered_changes)
+}
+
+/// Whether `--skip-binary` hides `diff_result`, so it isn't shown
+/// and doesn't affect the exit code.
+fn is_skipped(display_options: &DisplayOptions, diff_result: &DiffResult) -> bool {
+    display_options.skip_binary && diff_result.is_binary()
+}
+
+/// Print the counts in `summary` to stderr, after the diffs of
+/// several files.
+fn print_run_summary(summary: &RunSummary) {
+    if summary.num_files == 0 && summary.num_directories == 0 {
+        return;
+    }
+    print_degraded_recap(summary);
+
+    let message = summary.to_string();
+    if logging::json_format() {
+        logging::log(
+            Level::Warning,
+            "run_summary",
+            &message,
+            json!({
+                "files": summary.num_files,
+                "changed": summary.num_changed,
+                "unchanged": summary.num_unchanged,
+                "binary_changed": summary.num_binary_changed,
+                "skipped": summary.num_skipped,
+                "directories": summary.num_directories,
+                "directories_changed": summary.num_directories_changed,
+                "text_fallback": summary.text_fallbacks,
+            }),
+        );
+    } else {
+        eprintln!("{}", message);
+    }
+}
+
+/// Group the files that weren't diffed structurally by reason, so
+/// the warnings aren't lost among the diffs that scrolled past.
+fn print_degraded_recap(summary: &RunSummary) {
+    let recap = summary.recap();
+    if logging::json_format() {
+        for ((degradation, paths), message) in summary.degraded_paths.iter().zip(&recap) {
+            logging::log(
+                Level::Warning,
+                "degraded",
+                message,
+                json!({
+                    "reason": degradation,
+                    "count": paths.len(),
+                    "paths": paths,
+                }),
+            );
+        }
+    } else {
+        for message in recap {
+            eprintln!("{}", message);
+        }
+    }
+}
+
+/// Say how many files `--include` and `--exclude` left out, so a
+/// glob that matches more than intended is noticeable.
+fn print_filter_summary(num_skipped: usize) {
+    if num_skipped == 0 {
+        return;
+    }
+    let message = format!(
+        "Skipped {} {} not selected by --include or --exclude.",
+        num_skipped,
+        if num_skipped == 1 { "file" } else { "files" }
+    );
+    if logging::json_format() {
+        logging::log(
+            Level::Warning,
+            "skipped_files",
+            &message,
+            json!({ "count": num_skipped }),
+        );
+    } else {
+        eprintln!("{}", message);
+    }
+}
+
+/// Print a diff between two files.
+fn diff_file(
+    display_path: &str,
+    renamed_from: Option<String>,
+    lhs_path: &FileArgument,
+    rhs_path: &FileArgument,
+    lhs_permissions: Option<&FilePermissions>,
+    rhs_permissions: Option<&FilePermissions>,
+    display_options: &DisplayOptions,
+    diff_options: &DiffOptions,
+    missing_as_empty: bool,
+    overrides: &[(LanguageOverride, Vec<glob::Pattern>)],
+) -> DiffResult {
+    let (lhs_bytes, rhs_bytes) = read_files_or_die(lhs_path, rhs_path, missing_as_empty);
+    diff_file_bytes(
+        display_path,
+        renamed_from,
+        lhs_path,
+        rhs_path,
+        &lhs_bytes,
+        &rhs_bytes,
+        lhs_permissions,
+        rhs_permissions,
+        display_options,
+        diff_options,
+        overrides,
+    )
+}
+
+/// Diff two files passed by git, converting them with their textconv
+/// filters first, see `--textconv`. If a filter fails, we diff the
+/// files as they are.
+fn diff_file_textconv(
+    display_path: &str,
+    renamed_from: Option<String>,
+    lhs_path: &FileArgument,
+    rhs_path: &FileArgument,
+    lhs_permissions: Option<&FilePermissions>,
+    rhs_permissions: Option<&FilePermissions>,
+    git_sides: &(GitSide, GitSide),
+    display_options: &DisplayOptions,
+    diff_options: &DiffOptions,
+    overrides: &[(LanguageOverride, Vec<glob::Pattern>)],
+) -> DiffResult {
+    let (lhs_bytes, rhs_bytes) = read_files_or_die(lhs_path, rhs_path, false);
+
+    let convert = |git_side: &GitSide, path: &FileArgument| match path {
+        FileArgument::NamedPath(path) => {
+            textconv::convert(git_side, path, diff_options.cache_dir.as_deref())
+        }
+        FileArgument::Stdin | FileArgument::DevNull => Ok(None),
+    };
+    let (lhs_converted, rhs_converted) = match (
+        convert(&git_sides.0, lhs_path),
+        convert(&git_sides.1, rhs_path),
+    ) {
+        (Ok(lhs_converted), Ok(rhs_converted)) => (lhs_converted, rhs_converted),
+        (Err(e), _) | (_, Err(e)) => {
+            logging::log(
+                Level::Warning,
+                "textconv",
+                &format!(
+                    "{}: textconv {}, diffing the files as they are",
+                    display_path, e
+                ),
+                json!({ "path": display_path, "error": e }),
+            );
+            (None, None)
+        }
+    };
+
+    let driver = lhs_converted
+        .as_ref()
+        .or(rhs_converted.as_ref())
+        .map(|converted| converted.driver.clone());
+    let mut diff_result = diff_file_bytes(
+        display_path,
+        renamed_from,
+        lhs_path,
+        rhs_path,
+        lhs_converted.as_ref().map_or(&lhs_bytes, |c| &c.bytes),
+        rhs_converted.as_ref().map_or(&rhs_bytes, |c| &c.bytes),
+        lhs_permissions,
+        rhs_permissions,
+        display_options,
+        diff_options,
+        overrides,
+    );
+    if let Some(driver) = driver {
+        diff_result.add_extra_info(&format!("Converted with the textconv for '{}'.", driver));
+    }
+    diff_result
+}
+
+/// Decompress `bytes` if they're compressed, see [decompress]. If we
+/// can't, we warn and return the bytes as they are.
+///
+/// With --no-archive, compressed tarballs are left as they are, so
+/// they're diffed as binary files.
+fn decompress_or_warn<'a>(
+    display_path: &str,
+    bytes: &'a [u8],
+    diff_options: &DiffOptions,
+) -> (Option<Compression>, Cow<'a, [u8]>) {
+    if !diff_options.diff_archives && archive::is_archive_path(Path::new(display_path)) {
+        return (None, Cow::Borrowed(bytes));
+    }
+    match decompress(bytes, diff_options.byte_limit_for(Path::new(display_path))) {
+        Ok(Some((compression, decompressed))) => (Some(compression), Cow::Owned(decompressed)),
+        Ok(None) => (None, Cow::Borrowed(bytes)),
+        Err(e) => {
+            logging::log(
+                Level::Warning,
+                "decompress",
+                &format!("{}: Diffing the compressed bytes, as {}", display_path, e),
+                json!({ "path": display_path, "error": e }),
+            );
+            (None, Cow::Borrowed(bytes))
+        }
+    }
+}
+
+/// Diff the content of two files that we've already read. The paths
+/// are only used to detect the language. Compressed files are diffed
+/// by their decompressed content, even if the other side isn't
+/// compressed.
+fn diff_file_bytes(
+    display_path: &str,
+    renamed_from: Option<String>,
+    lhs_path: &FileArgument,
+    rhs_path: &FileArgument,
+    lhs_bytes: &[u8],
+    rhs_bytes: &[u8],
+    lhs_permissions: Option<&FilePermissions>,
+    rhs_permissions: Option<&FilePermissions>,
+    display_options: &DisplayOptions,
+    diff_options: &DiffOptions,
+    overrides: &[(LanguageOverride, Vec<glob::Pattern>)],
+) -> DiffResult {
+    let (lhs_compression, lhs_bytes) = decompress_or_warn(display_path, lhs_bytes, diff_options);
+    let (rhs_compression, rhs_bytes) = decompress_or_warn(display_path, rhs_bytes, diff_options);
+    let (lhs_bytes, rhs_bytes) = (lhs_bytes.as_ref(), rhs_bytes.as_ref());
+
+    let guess = |bytes: &[u8]| match guess_content(bytes) {
+        ProbableFileKind::Binary if diff_options.force_text => {
+            ProbableFileKind::Text(force_text(bytes))
+        }
+        kind => kind,
+    };
+    let mut diff_result = match (guess(lhs_bytes), guess(rhs_bytes)) {
+        (ProbableFileKind::Binary, _) | (_, ProbableFileKind::Binary) => DiffResult {
+            extra_info: None,
+            display_path: display_path.to_owned(),
+            path_bytes: None,
+            renamed_from: None,
+            missing_side: None,
+            mode_change: None,
+            mtime_change: None,
+            binary_info: Some((BinaryInfo::new(lhs_bytes), BinaryInfo::new(rhs_bytes))),
+            file_format: FileFormat::Binary,
+            lhs_src: FileContent::Binary,
+            rhs_src: FileContent::Binary,
+            lhs_positions: vec![],
+            rhs_positions: vec![],
+            lhs_line_map: LineMap::default(),
+            rhs_line_map: LineMap::default(),
+            hunks: vec![],
+            degradation: None,
+            has_byte_changes: lhs_bytes != rhs_bytes,
+            has_syntactic_changes: false,
+        },
+        (ProbableFileKind::Text(mut lhs_src), ProbableFileKind::Text(mut rhs_src)) => {
+            if diff_options.strip_cr {
+                lhs_src.retain(|c| c != '\r');
+                rhs_src.retain(|c| c != '\r');
+            }
+
+            diff_file_content(
+                display_path,
+                None,
+                lhs_path,
+                rhs_path,
+                &lhs_src,
+                &rhs_src,
+                display_options,
+                diff_options,
+                overrides,
+            )
+        }
+    };
+
+    diff_result.renamed_from = renamed_from;
+    // Git passes /dev/null for the missing side of an added or
+    // deleted file.
+    diff_result.missing_side = match (lhs_path, rhs_path) {
+        (FileArgument::DevNull, FileArgument::DevNull) => None,
+        (FileArgument::DevNull, _) => Some(Side::Left),
+        (_, FileArgument::DevNull) => Some(Side::Right),
+        _ => None,
+    };
+    if let (Some(lhs_perms), Some(rhs_perms)) = (lhs_permissions, rhs_permissions) {
+        diff_result.set_modes(&lhs_perms.to_string(), &rhs_perms.to_string());
+    }
+    match (lhs_compression, rhs_compression) {
+        (None, None) => {}
+        (Some(lhs_compression), Some(rhs_compression)) if lhs_compression == rhs_compression => {
+            diff_result.add_extra_info(&format!("Decompressed from {}.", lhs_compression.name()));
+        }
+        _ => {
+            for (compression, side) in [(lhs_compression, "left"), (rhs_compression, "right")] {
+                if let Some(compression) = compression {
+                    diff_result.add_extra_info(&format!(
+                        "Decompressed the {} file from {}.",
+                        side,
+                        compression.name()
+                    ));
+                }
+            }
+        }
+    }
+    diff_result
+}
+
+/// How long `--watch` waits for writes to stop before diffing, so
+/// an editor writing a file in several steps only causes one diff.
+const WATCH_DEBOUNCE: Duration = Duration::from_millis(100);
+
+/// The modification time and size of `path`, or `None` if we can't
+/// read its metadata. Editors that save by renaming can briefly leave
+/// the path missing.
+fn file_version(path: &Path) -> Option<(SystemTime, u64)> {
+    let metadata = std::fs::metadata(path).ok()?;
+    Some((metadata.modified().ok()?, metadata.len()))
+}
+
+/// The absolute path of `path`, resolving symlinks in its directory
+/// but not in the file name, so it matches the paths in events from
+/// watching the directory.
+fn watched_path(path: &Path) -> std::io::Result<PathBuf> {
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    let file_name = path.file_name().unwrap_or_default();
+    Ok(std::fs::canonicalize(dir)?.join(file_name))
+}
+
+/// Print a diff of `lhs_path` and `rhs_path`, then print a new diff
+/// every time either file changes. Runs until interrupted.
+fn watch_files(
+    display_path: &str,
+    lhs_path: &Path,
+    rhs_path: &Path,
+    display_options: &DisplayOptions,
+    diff_options: &DiffOptions,
+    overrides: &[(LanguageOverride, Vec<glob::Pattern>)],
+) {
+    // Usually only one side changes between diffs, so we don't need
+    // to parse the other side again.
+    tsp::reuse_trees_between_diffs();
+
+    let lhs_arg = FileArgument::NamedPath(lhs_path.to_owned());
+    let rhs_arg = FileArgument::NamedPath(rhs_path.to_owned());
+
+    let watched_paths: Vec<PathBuf> = match [lhs_path, rhs_path]
+        .iter()
+        .map(|path| watched_path(path))
+        .collect()
+    {
+        Ok(paths) => paths,
+        Err(e) => {
+            eprintln!("error: Could not watch files: {}", e);
+            std::process::exit(EXIT_BAD_ARGUMENTS);
+        }
+    };
+
+    // Watch the directories containing the files, rather than the
+    // files themselves. Editors that save atomically replace the file,
+    // and a watch on the old file wouldn't see any later changes.
+    let (sender, receiver) = mpsc::channel();
+    let watcher = notify::recommended_watcher(sender).and_then(|mut watcher| {
+        for path in &watched_paths {
+            if let Some(dir) = path.parent() {
+                watcher.watch(dir, notify::RecursiveMode::NonRecursive)?;
+            }
+        }
+        Ok(watcher)
+    });
+    // Keep the watcher alive until we return.
+    let _watcher = match watcher {
+        Ok(watcher) => watcher,
+        Err(e) => {
+            eprintln!("error: Could not watch files: {}", e);
+            std::process::exit(EXIT_BAD_ARGUMENTS);
+        }
+    };
+
+    let is_tty = std::io::stdout().is_tty();
+    let mut previous_versions = None;
+    loop {
+        if let (Some(lhs_version), Some(rhs_version)) =
+            (file_version(lhs_path), file_version(rhs_path))
+        {
+            let versions = Some((lhs_version, rhs_version));
+            if versions != previous_versions {
+                let mut out = stdout_output();
+                if is_tty {
+                    let _ = crossterm::execute!(
+                        out,
+                        crossterm::terminal::Clear(crossterm::terminal::ClearType::All),
+                        crossterm::cursor::MoveTo(0, 0)
+                    );
+                } else if previous_versions.is_some() {
+                    write_or_die(writeln!(
+                        out,
+                        "{}\n",
+                        "-".repeat(display_options.terminal_width)
+                    ));
+                }
+                previous_versions = versions;
+
+                let diff_result = diff_file(
+                    display_path,
+                    None,
+                    &lhs_arg,
+                    &rhs_arg,
+                    None,
+                    None,
+                    display_options,
+                    diff_options,
+                    false,
+                    overrides,
+                );
+                write_or_die(print_diff_result(&mut out, display_options, &diff_result));
+                finish_output(out);
+            }
+        }
+
+        // Wait for an event for one of our files. If a file is
+        // missing, it's being saved, so we'll get another event when
+        // it's back.
+        loop {
+            match receiver.recv() {
+                Ok(Ok(event)) if event.paths.iter().any(|path| watched_paths.contains(path)) => {
+                    break
+                }
+                Ok(_) => {}
+                Err(_) => return,
+            }
+        }
+        while receiver.recv_timeout(WATCH_DEBOUNCE).is_ok() {}
+    }
+}
+
+fn diff_conflicts_file(
+    display_path: &str,
+    path: &FileArgument,
+    display_options: &DisplayOptions,
+    diff_options: &DiffOptions,
+    overrides: &[(LanguageOverride, Vec<glob::Pattern>)],
+) -> DiffResult {
+    let bytes = read_file_or_die(path);
+    let mut src = match guess_content(&bytes) {
+        ProbableFileKind::Text(src) => src,
+        ProbableFileKind::Binary => {
+            eprintln!("error: Expected a text file with conflict markers, got a binary file.");
+            std::process::exit(EXIT_BAD_ARGUMENTS);
+        }
+    };
+
+    if diff_options.strip_cr {
+        src.retain(|c| c != '\r');
+    }
+
+    let conflict_files = match apply_conflict_markers(&src) {
+        Ok(cf) => cf,
+        Err(msg) => {
+            eprintln!("error: {}", msg);
+            std::process::exit(EXIT_BAD_ARGUMENTS);
+        }
+    };
+
+    if conflict_files.num_conflicts == 0 {
+        eprintln!(
+            "error: Difftastic requires two paths, or a single file with conflict markers {}.\n",
+            START_LHS_MARKER,
+        );
+
+        eprintln!("USAGE:\n\n    {}\n", USAGE);
+        eprintln!("For more information try --help");
+        std::process::exit(EXIT_BAD_ARGUMENTS);
+    }
+
+    let lhs_name = match conflict_files.lhs_name {
+        Some(name) => format!("'{}'", name),
+        None => "the left file".to_owned(),
+    };
+    let rhs_name = match conflict_files.rhs_name {
+        Some(name) => format!("'{}'", name),
+        None => "the right file".to_owned(),
+    };
+
+    let extra_info = format!(
+        "Showing the result of replacing every conflict in {} with {}.",
+        lhs_name, rhs_name
+    );
+
+    diff_file_content(
+        display_path,
+        Some(extra_info),
+        path,
+        path,
+        &conflict_files.lhs_content,
+        &conflict_files.rhs_content,
+        display_options,
+        diff_options,
+        overrides,
+    )
+}
+
+/// Read `path` as text for `--merge`, or exit if it's binary.
+fn read_text_or_die(path: &FileArgument, diff_options: &DiffOptions) -> String {
+    let bytes = read_file_or_die(path);
+    let mut src = match guess_content(&bytes) {
+        ProbableFileKind::Text(src) => src,
+        ProbableFileKind::Binary => {
+            eprintln!(
+                "error: --merge requires text files, but {} is binary.",
+                path
+            );
+            std::process::exit(EXIT_BAD_ARGUMENTS);
+        }
+    };
+
+    if diff_options.strip_cr {
+        src.retain(|c| c != '\r');
+    }
+    src
+}
+
+/// Diff both sides of a merge against the base, and print them
+/// aligned.
+fn diff_merge(
+    out: &mut dyn Write,
+    display_path: &str,
+    input: &MergeInput,
+    display_options: &DisplayOptions,
+    diff_options: &DiffOptions,
+    overrides: &[(LanguageOverride, Vec<glob::Pattern>)],
+) -> std::io::Result<()> {
+    if let MergeInput::Combined {
+        parent1,
+        parent2,
+        merged,
+    } = input
+    {
+        return diff_combined(
+            out,
+            display_path,
+            parent1,
+            parent2,
+            merged,
+            display_options,
+            diff_options,
+            overrides,
+        );
+    }
+
+    let (base_path, ours_path, theirs_path, base_src, ours_src, theirs_src, extra_info) =
+        match input {
+            MergeInput::Files { base, ours, theirs } => (
+                base,
+                ours,
+                theirs,
+                read_text_or_die(base, diff_options),
+                read_text_or_die(ours, diff_options),
+                read_text_or_die(theirs, diff_options),
+                format!(
+                    "Showing changes from {} in {} (left) and {} (right).",
+                    base, ours, theirs
+                ),
+            ),
+            MergeInput::Conflicts(path) => {
+                let src = read_text_or_die(path, diff_options);
+                let conflict_files = match apply_conflict_markers(&src) {
+                    Ok(cf) => cf,
+                    Err(msg) => {
+                        eprintln!("error: {}", msg);
+                        std::process::exit(EXIT_BAD_ARGUMENTS);
+                    }
+                };
+                if conflict_files.num_conflicts == 0 {
+                    eprintln!("error: --merge requires three paths, or a single file with conflict markers {}.", START_LHS_MARKER);
+                    std::process::exit(EXIT_BAD_ARGUMENTS);
+                }
+                let base_src = match conflict_files.base_content {
+                    Some(base_src) => base_src,
+                    None => {
+                        eprintln!("error: --merge requires conflict markers that include the base version. Use `git config merge.conflictStyle diff3`.");
+                        std::process::exit(EXIT_BAD_ARGUMENTS);
+                    }
+                };
+
+                let name = |name: Option<String>, fallback: &str| match name {
+                    Some(name) => format!("'{}'", name),
+                    None => fallback.to_owned(),
+                };
+                let extra_info = format!(
+                    "Showing changes from the base in {} (left) and {} (right).",
+                    name(conflict_files.lhs_name, "our version"),
+                    name(conflict_files.rhs_name, "their version"),
+                );
+                (
+                    path,
+                    path,
+                    path,
+                    base_src,
+                    conflict_files.lhs_content,
+                    conflict_files.rhs_content,
+                    extra_info,
+                )
+            }
+            MergeInput::Combined { .. } => unreachable!("Handled above"),
+        };
+
+    let diff_side = |side_path: &FileArgument, side_src: &str| {
+        diff_file_content(
+            display_path,
+            None,
+            base_path,
+            side_path,
+            &base_src,
+            side_src,
+            display_options,
+            diff_options,
+            overrides,
+        )
+    };
+    let (ours_diff, theirs_diff) = rayon::join(
+        || diff_side(ours_path, &ours_src),
+        || diff_side(theirs_path, &theirs_src),
+    );
+
+    display::merge::print(
+        out,
+        display_path,
+        Some(&extra_info),
+        &base_src,
+        &ours_src,
+        &theirs_src,
+        &ours_diff,
+        &theirs_diff,
+        display_options,
+    )
+}
+
+/// Show what a merge commit did, by diffing the merged result against
+/// each parent.
+fn diff_combined(
+    out: &mut dyn Write,
+    display_path: &str,
+    parent1_path: &FileArgument,
+    parent2_path: &FileArgument,
+    merged_path: &FileArgument,
+    display_options: &DisplayOptions,
+    diff_options: &DiffOptions,
+    overrides: &[(LanguageOverride, Vec<glob::Pattern>)],
+) -> std::io::Result<()> {
+    let parent1_src = read_text_or_die(parent1_path, diff_options);
+    let parent2_src = read_text_or_die(parent2_path, diff_options);
+    let merged_src = read_text_or_die(merged_path, diff_options);
+    let extra_info = format!(
+        "Showing how {} (parent 1) and {} (parent 2) were merged.",
+        parent1_path, parent2_path
+    );
+
+    let diff_parent = |parent_path: &FileArgument, parent_src: &str| {
+        diff_file_content(
+            display_path,
+            None,
+            merged_path,
+            parent_path,
+            &merged_src,
+            parent_src,
+            display_options,
+            diff_options,
+            overrides,
+        )
+    };
+    let (parent1_diff, parent2_diff) = rayon::join(
+        || diff_parent(parent1_path, &parent1_src),
+        || diff_parent(parent2_path, &parent2_src),
+    );
+
+    display::merge::print_combined(
+        out,
+        display_path,
+        Some(&extra_info),
+        &merged_src,
+        &parent1_src,
+        &parent2_src,
+        &parent1_diff,
+        &parent2_diff,
+        display_options,
+    )
+}
+
+/// The result of `--check-only` for a text diff, where we only know
+/// whether the files changed.
+fn check_only_text(
+    file_format: &FileFormat,
+    display_path: &str,
+    extra_info: Option<String>,
+    lhs_src: &str,
+    rhs_src: &str,
+    has_changes: bool,
+) -> DiffResult {
+    DiffResult {
+        display_path: display_path.to_owned(),
+        path_bytes: None,
+        renamed_from: None,
+        missing_side: None,
+        mode_change: None,
+        mtime_change: None,
+        binary_info: None,
+        extra_info,
+        file_format: file_format.clone(),
+        lhs_src: FileContent::Text(lhs_src.into()),
+        rhs_src: FileContent::Text(rhs_src.into()),
+        lhs_positions: vec![],
+        rhs_positions: vec![],
+        lhs_line_map: LineMap::default(),
+        rhs_line_map: LineMap::default(),
+        hunks: vec![],
+        degradation: Degradation::from_file_format(file_format),
+        has_byte_changes: has_changes,
+        has_syntactic_changes: has_changes,
+    }
+}
+
+/// The reason in the [FileFormat::TextFallback] of a diff that was
+/// cancelled, see [DiffOptions::cancel].
+pub(crate) const CANCELLED_REASON: &str = "cancelled";
+
+/// The result of a diff that was cancelled before it finished. It
+/// only says whether the bytes changed.
+fn cancelled(
+    display_path: &str,
+    extra_info: Option<String>,
+    lhs_src: &str,
+    rhs_src: &str,
+) -> DiffResult {
+    let file_format = FileFormat::TextFallback {
+        reason: CANCELLED_REASON.to_owned(),
+    };
+    let has_changes = lhs_src != rhs_src;
+    check_only_text(
+        &file_format,
+        display_path,
+        extra_info,
+        lhs_src,
+        rhs_src,
+        has_changes,
+    )
+}
+
+fn log_language(display_path: &str, language: Option<Language>) {
+    let message = match language {
+        Some(language) => format!("{}: Detected {}", display_path, language_name(language)),
+        None => format!("{}: No language detected, using a text diff", display_path),
+    };
+    logging::log(
+        Level::Info,
+        "language",
+        &message,
+        json!({
+            "path": display_path,
+            "language": language.map(language_name),
+        }),
+    );
+}
+
+/// The start of `src`, up to the end of the last line that fits in
+/// `max_len` bytes. If the first line doesn't fit, it's cut at a
+/// character boundary instead.
+fn truncate_at_line(src: &str, max_len: usize) -> &str {
+    if src.len() <= max_len {
+        return src;
+    }
+    let mut end = max_len;
+    while !src.is_char_boundary(end) {
+        end -= 1;
+    }
+    match src[..end].rfind('\n') {
+        Some(newline) => &src[..newline + 1],
+        None => &src[..end],
+    }
+}
+
+/// The format of a file that fell back to a text diff, logging the
+/// reason with `--verbose`.
+fn text_fallback(display_path: &str, reason: String) -> FileFormat {
+    logging::log(
+        Level::Info,
+        "fallback",
+        &format!("{}: Using a text diff, {}", display_path, reason),
+        json!({ "path": display_path, "reason": reason }),
+    );
+    FileFormat::TextFallback { reason }
+}
+
+/// The positions of the changes on each side of a text diff.
+fn text_change_positions(
+    lhs_src: &str,
+    rhs_src: &str,
+    diff_options: &DiffOptions,
+) -> (Vec<MatchedPos>, Vec<MatchedPos>) {
+    match diff_options.prose_granularity {
+        ProseGranularity::Line => (
+            line_parser::change_positions(
+                lhs_src,
+                rhs_src,
+                diff_options.whitespace,
+                diff_options.word_regex.as_ref(),
+            ),
+            line_parser::change_positions(
+                rhs_src,
+                lhs_src,
+                diff_options.whitespace,
+                diff_options.word_regex.as_ref(),
+            ),
+        ),
+        granularity => (
+            prose::change_positions(
+                lhs_src,
+                rhs_src,
+                granularity,
+                diff_options.word_regex.as_ref(),
+            ),
+            prose::change_positions(
+                rhs_src,
+                lhs_src,
+                granularity,
+                diff_options.word_regex.as_ref(),
+            ),
+        ),
+    }
+}
+
+/// Whether a text diff would find any changes, for `--check-only`.
+fn has_text_changes(
+    lhs_src: &str,
+    rhs_src: &str,
+    diff_options: &DiffOptions,
+    display_options: &DisplayOptions,
+) -> bool {
+    if diff_options.prose_granularity != ProseGranularity::Line {
+        prose::has_changes(lhs_src, rhs_src, diff_options.word_regex.as_ref())
+    } else {
+        line_parser::has_text_changes(
+            lhs_src,
+            rhs_src,
+            diff_options.whitespace,
+            display_options.ignore_blank_lines,
+        )
+    }
+}
+
+fn diff_file_content(
+    display_path: &str,
+    extra_info: Option<String>,
+    lhs_path: &FileArgument,
+    rhs_path: &FileArgument,
+    lhs_src: &str,
+    rhs_src: &str,
+    display_options: &DisplayOptions,
+    diff_options: &DiffOptions,
+    overrides: &[(LanguageOverride, Vec<glob::Pattern>)],
+) -> DiffResult {
+    let _usage_report = logging::enabled(Level::Debug)
+        .then(|| resource_usage::UsageReport::start(display_path, diff_options.profile));
+    // --verbose --verbose reports the graph statistics from the
+    // profile.
+    let _profiler = (diff_options.profile || logging::enabled(Level::Debug))
+        .then(|| profile::Profiler::start(display_path));
+
+    // Apply any `--byte-limit GLOB=LIMIT` for this file.
+    let byte_limit = diff_options.byte_limit_for(Path::new(display_path));
+    let file_diff_options = if byte_limit == diff_options.byte_limit {
+        Cow::Borrowed(diff_options)
+    } else {
+        Cow::Owned(DiffOptions {
+            byte_limit,
+            ..diff_options.clone()
+        })
+    };
+    let diff_options: &DiffOptions = &file_diff_options;
+
+    let (guess_src, guess_path) = match rhs_path {
+        FileArgument::NamedPath(path) => (&rhs_src, Path::new(path)),
+        // Use the other file's name, as the display path may be a
+        // label.
+        FileArgument::Stdin => match lhs_path {
+            FileArgument::NamedPath(path) => (&rhs_src, Path::new(path)),
+            _ => (&rhs_src, Path::new(&display_path)),
+        },
+        FileArgument::DevNull => (&lhs_src, Path::new(&display_path)),
+    };
+    // Decompressed files, see [diff_file_bytes], are in the language
+    // of the name without the compression extension.
+    let stripped_path = without_compression_extension(guess_path);
+    let guess_path = stripped_path.as_deref().unwrap_or(guess_path);
+
+    let language = guess_with_attribute(
+        guess_path,
+        guess_src,
+        overrides,
+        gitattributes::language(display_path),
+    );
+    log_language(display_path, language);
+
+    // Pretty-print minified JSON, so each change gets its own line.
+    if let Some((lhs_src, rhs_src)) =
+        reformat::reformat_both(lhs_src, rhs_src, language, diff_options.reformat_json)
+    {
+        let note = format!(
+            "Reformatted {}: line numbers refer to the reformatted text.",
+            language.map_or("text", language_name)
+        );
+        let extra_info = match extra_info {
+            Some(extra_info) => format!("{}\n{}", extra_info, note),
+            None => note,
+        };
+        return diff_text_content(
+            display_path,
+            Some(extra_info),
+            &lhs_src,
+            &rhs_src,
+            language,
+            display_options,
+            diff_options,
+        );
+    }
+
+    diff_text_content(
+        display_path,
+        extra_info,
+        lhs_src,
+        rhs_src,
+        language,
+        display_options,
+        diff_options,
+    )
+}
+
+/// Diff `lhs_src` and `rhs_src` structurally as `language`, or as
+/// text if it's `None`. This is also [crate::diff_files], so the
+/// library and the CLI can't diverge.
+pub(crate) fn diff_text_content(
+    display_path: &str,
+    extra_info: Option<String>,
+    lhs_src: &str,
+    rhs_src: &str,
+    language: Option<Language>,
+    display_options: &DisplayOptions,
+    diff_options: &DiffOptions,
+) -> DiffResult {
+    let byte_limit = diff_options.byte_limit;
+    let lang_config = language.map(|lang| (lang, tsp::from_language(lang)));
+
+    if lhs_src == rhs_src {
+        let file_format = match language {
+            Some(language) => FileFormat::SupportedLanguage(language),
+            None => FileFormat::PlainText,
+        };
+
+        // If the two files are byte-for-byte identical, return early
+        // rather than doing any more work.
+        return DiffResult {
+            extra_info,
+            display_path: display_path.to_owned(),
+            path_bytes: None,
+            renamed_from: None,
+            missing_side: None,
+            mode_change: None,
+            mtime_change: None,
+            binary_info: None,
+            file_format,
+            lhs_src: FileContent::Text("".into()),
+            rhs_src: FileContent::Text("".into()),
+            lhs_positions: vec![],
+            rhs_positions: vec![],
+            lhs_line_map: LineMap::default(),
+            rhs_line_map: LineMap::default(),
+            hunks: vec![],
+            degradation: None,
+            has_byte_changes: false,
+            has_syntactic_changes: false,
+        };
+    }
+
+    // Only files that we'd parse are truncated, as text diffs don't
+    // have a byte limit.
+    let (lhs_src, rhs_src, extra_info) = if language.is_some()
+        && diff_options.byte_limit_action == ByteLimitAction::Truncate
+        && (lhs_src.len() > byte_limit || rhs_src.len() > byte_limit)
+    {
+        let lhs_truncated = truncate_at_line(lhs_src, byte_limit);
+        let rhs_truncated = truncate_at_line(rhs_src, byte_limit);
+        resource_usage::record_truncated();
+
+        let omitted: Vec<String> = [
+            (lhs_src.len() - lhs_truncated.len(), "left"),
+            (rhs_src.len() - rhs_truncated.len(), "right"),
+        ]
+        .into_iter()
+        .filter(|(num_bytes, _)| *num_bytes > 0)
+        .map(|(num_bytes, side)| format!("{} on the {}", format_size(num_bytes, BINARY), side))
+        .collect();
+        let note = format!(
+            "Truncated to DFT_BYTE_LIMIT ({}), omitting {}.",
+            format_size(byte_limit, BINARY),
+            omitted.join(" and ")
+        );
+        let extra_info = match extra_info {
+            Some(extra_info) => format!("{}\n{}", extra_info, note),
+            None => note,
+        };
+        (lhs_truncated, rhs_truncated, Some(extra_info))
+    } else {
+        (lhs_src, rhs_src, extra_info)
+    };
+
+    if let Some(markers) = &diff_options.generated_markers {
+        if generated::is_generated(lhs_src, markers) || generated::is_generated(rhs_src, markers) {
+            // We only show the number of changed lines in generated
+            // files, so a line diff is enough.
+            let entry = cache::CacheEntry {
+                file_format: FileFormat::Generated,
+                lhs_positions: line_parser::change_positions(
+                    lhs_src,
+                    rhs_src,
+                    diff_options.whitespace,
+                    diff_options.word_regex.as_ref(),
+                ),
+                rhs_positions: line_parser::change_positions(
+                    rhs_src,
+                    lhs_src,
+                    diff_options.whitespace,
+                    diff_options.word_regex.as_ref(),
+                ),
+                token_diffed_lines: vec![],
+            };
+            return diff_result_from_entry(
+                display_path,
+                extra_info,
+                lhs_src,
+                rhs_src,
+                entry,
+                None,
+                display_options,
+            );
+        }
+    }
+
+    // Check this before looking in the cache, so an oversized file is
+    // an error even if it was diffed before with another action.
+    if language.is_some()
+        && diff_options.byte_limit_action == ByteLimitAction::Error
+        && (lhs_src.len() > byte_limit || rhs_src.len() > byte_limit)
+    {
+        eprintln!(
+            "error: {}: {} exceeded DFT_BYTE_LIMIT.",
+            display_path,
+            &format_size(max(lhs_src.len(), rhs_src.len()), BINARY)
+        );
+        std::process::exit(EXIT_BAD_ARGUMENTS);
+    }
+
+    let cache_key = match &diff_options.cache_dir {
+        Some(cache_dir) if !diff_options.check_only => {
+            let key = cache::cache_key(lhs_src, rhs_src, language, diff_options);
+            if let Some(entry) = cache::load(cache_dir, &key, language) {
+                info!("Using cached diff for {}", display_path);
+                return diff_result_from_entry(
+                    display_path,
+                    extra_info,
+                    lhs_src,
+                    rhs_src,
+                    entry,
+                    None,
+                    display_options,
+                );
+            }
+            Some(key)
+        }
+        _ => None,
+    };
+
+    // RHS lines of subtrees that were too large for a structural diff.
+    let mut token_diffed_lines = vec![];
+    // The declarations on each side, from the syntax trees we parse.
+    let mut declarations = None;
+    // Whether the structural diff took longer than `--timeout`.
+    let mut timed_out = false;
+    let interrupt = Interrupt {
+        deadline: diff_options.timeout.map(|timeout| Instant::now() + timeout),
+        cancel: diff_options.cancel.clone(),
+    };
+
+    let (file_format, lhs_positions, rhs_positions) = match lang_config {
+        None => {
+            let file_format = FileFormat::PlainText;
+            if diff_options.check_only {
+                let has_changes = has_text_changes(lhs_src, rhs_src, diff_options, display_options);
+                return check_only_text(
+                    &file_format,
+                    display_path,
+                    extra_info,
+                    lhs_src,
+                    rhs_src,
+                    has_changes,
+                );
+            }
+
+            let (lhs_positions, rhs_positions) =
+                text_change_positions(lhs_src, rhs_src, diff_options);
+            (file_format, lhs_positions, rhs_positions)
+        }
+        Some((language, lang_config)) => {
+            let lhs_arena = Arena::new();
+            let rhs_arena = Arena::new();
+            let parse_start = Instant::now();
+            match tsp::to_tree_with_limit(diff_options, &lang_config, lhs_src, rhs_src) {
+                Ok((lhs_tree, rhs_tree)) => {
+                    match tsp::to_syntax_with_limit(
+                        lhs_src,
+                        rhs_src,
+                        &lhs_tree,
+                        &rhs_tree,
+                        &lhs_arena,
+                        &rhs_arena,
+                        &lang_config,
+                        diff_options,
+                    ) {
+                        Ok((lhs, rhs)) => {
+                            profile::record_phase(Phase::Parse, parse_start);
+                            profile::record_nodes_parsed(&lhs, &rhs);
+
+                            if diff_options.check_only {
+                                let has_syntactic_changes = lhs != rhs;
+                                return DiffResult {
+                                    extra_info,
+                                    display_path: display_path.to_owned(),
+                                    path_bytes: None,
+                                    renamed_from: None,
+                                    missing_side: None,
+                                    mode_change: None,
+                                    mtime_change: None,
+                                    binary_info: None,
+                                    file_format: FileFormat::SupportedLanguage(language),
+                                    lhs_src: FileContent::Text(lhs_src.to_owned()),
+                                    rhs_src: FileContent::Text(rhs_src.to_owned()),
+                                    lhs_positions: vec![],
+                                    rhs_positions: vec![],
+                                    lhs_line_map: LineMap::default(),
+                                    rhs_line_map: LineMap::default(),
+                                    hunks: vec![],
+                                    degradation: None,
+                                    has_byte_changes: true,
+                                    has_syntactic_changes,
+                                };
+                            }
+
+                            let mut change_map = ChangeMap::default();
+                            let unchanged_start = Instant::now();
+                            let possibly_changed = if lhs == rhs {
+                                // The trees have the same content,
+                                // ignoring positions, so this is a
+                                // formatting-only change. Skip the
+                                // graph search entirely.
+                                for (lhs_node, rhs_node) in lhs.iter().zip(&rhs) {
+                                    insert_deep_unchanged(lhs_node, rhs_node, &mut change_map);
+                                    insert_deep_unchanged(rhs_node, lhs_node, &mut change_map);
+                                }
+                                resource_usage::record_identical_tokens();
+                                vec![]
+                            } else if !diff_options.unchanged_heuristic
+                                || env::var("DFT_DBG_KEEP_UNCHANGED").is_ok()
+                            {
+                                vec![(lhs.clone(), rhs.clone())]
+                            } else {
+                                unchanged::mark_unchanged(
+                                    &lhs,
+                                    &rhs,
+                                    &mut change_map,
+                                    diff_options.unchanged_min_run,
+                                    diff_options.unchanged_ngram,
+                                    &interrupt,
+                                )
+                            };
+                            let possibly_changed = if diff_options.anchors {
+                                let mut anchored = vec![];
+                                for (lhs_section_nodes, rhs_section_nodes) in possibly_changed {
+                                    anchored.extend(anchors::split_on_anchors(
+                                        &lhs_section_nodes,
+                                        &rhs_section_nodes,
+                                        &mut change_map,
+                                    ));
+                                }
+                                anchored
+                            } else {
+                                possibly_changed
+                            };
+                            profile::record_phase(Phase::Unchanged, unchanged_start);
+
+                            let graph_start = Instant::now();
+                            for (lhs_section_nodes, rhs_section_nodes) in possibly_changed {
+                                init_next_prev(&lhs_section_nodes);
+                                init_next_prev(&rhs_section_nodes);
+
+                                let res = match mark_syntax(
+                                    lhs_section_nodes.first().copied(),
+                                    rhs_section_nodes.first().copied(),
+                                    &mut change_map,
+                                    diff_options.graph_limit,
+                                    &interrupt,
+                                ) {
+                                    Err(GraphSearchError::ExceededGraphLimit) => {
+                                        logging::log(
+                                            Level::Info,
+                                            "graph_limit",
+                                            &format!(
+                                                "{}: Exceeded DFT_GRAPH_LIMIT, diffing the subtrees of a section separately",
+                                                display_path
+                                            ),
+                                            json!({ "path": display_path }),
+                                        );
+                                        // Diff the subtrees of this section
+                                        // separately, so only the subtrees
+                                        // that are still too large lose
+                                        // structural accuracy.
+                                        fallback::mark_syntax_by_subtree(
+                                            &lhs_section_nodes,
+                                            &rhs_section_nodes,
+                                            &mut change_map,
+                                            diff_options.graph_limit,
+                                            &interrupt,
+                                        )
+                                        .map(|lines| token_diffed_lines.extend(lines))
+                                    }
+                                    res => res,
+                                };
+
+                                match res {
+                                    Err(GraphSearchError::TimedOut) => {
+                                        timed_out = true;
+                                        break;
+                                    }
+                                    Err(GraphSearchError::Cancelled) => break,
+                                    _ => {}
+                                }
+                            }
+                            profile::record_phase(Phase::GraphSearch, graph_start);
+
+                            if interrupt.is_cancelled() {
+                                return cancelled(display_path, extra_info, lhs_src, rhs_src);
+                            }
+
+                            if timed_out {
+                                let file_format = text_fallback(
+                                    display_path,
+                                    format!(
+                                        "timed out after {:?}, exceeded DFT_TIMEOUT",
+                                        diff_options.timeout.unwrap_or_default()
+                                    ),
+                                );
+                                token_diffed_lines.clear();
+
+                                let (lhs_positions, rhs_positions) =
+                                    text_change_positions(lhs_src, rhs_src, diff_options);
+                                (file_format, lhs_positions, rhs_positions)
+                            } else {
+                                declarations = Some((
+                                    tsp::tree_declarations(&lhs_tree, lhs_src, language),
+                                    tsp::tree_declarations(&rhs_tree, rhs_src, language),
+                                ));
+                                fix_all_sliders(language, &lhs, &mut change_map);
+                                fix_all_sliders(language, &rhs, &mut change_map);
+
+                                let mut lhs_positions = syntax::change_positions(
+                                    &lhs,
+                                    &change_map,
+                                    diff_options.word_regex.as_ref(),
+                                );
+                                let mut rhs_positions = syntax::change_positions(
+                                    &rhs,
+                                    &change_map,
+                                    diff_options.word_regex.as_ref(),
+                                );
+
+                                let rhs_moved = if diff_options.detect_moves {
+                                    let (lhs_moved, rhs_moved) = moves::find_moves(
+                                        &lhs,
+                                        &rhs,
+                                        &change_map,
+                                        diff_options.move_min_tokens,
+                                    );
+                                    moves::mark_moved(&mut lhs_positions, &lhs_moved);
+                                    moves::mark_moved(&mut rhs_positions, &rhs_moved);
+                                    rhs_moved
+                                } else {
+                                    moves::MovedPositions::default()
+                                };
+
+                                if diff_options.detect_copies {
+                                    let rhs_copied = moves::find_copies(
+                                        &lhs,
+                                        &rhs,
+                                        &change_map,
+                                        diff_options.move_min_tokens,
+                                        &rhs_moved,
+                                    );
+                                    moves::mark_copied(&mut rhs_positions, &rhs_copied);
+                                }
+
+                                if diff_options.ignore_comments {
+                                    let lhs_comments =
+                                        tsp::comment_positions(&lhs_tree, lhs_src, &lang_config);
+                                    lhs_positions.extend(lhs_comments);
+
+                                    let rhs_comments =
+                                        tsp::comment_positions(&rhs_tree, rhs_src, &lang_config);
+                                    rhs_positions.extend(rhs_comments);
+                                }
+
+                                if diff_options.ignore_punctuation {
+                                    lhs_positions = syntax::ignore_punctuation(&lhs_positions);
+                                    rhs_positions = syntax::ignore_punctuation(&rhs_positions);
+                                }
+
+                                (
+                                    FileFormat::SupportedLanguage(language),
+                                    lhs_positions,
+                                    rhs_positions,
+                                )
+                            }
+                        }
+                        Err(tsp::ExceededParseErrorLimit(error_count)) => {
+                            let file_format = text_fallback(
+                                display_path,
+                                format!(
+                                    "{} {} parse error{}, exceeded DFT_PARSE_ERROR_LIMIT",
+                                    error_count,
+                                    language_name(language),
+                                    if error_count == 1 { "" } else { "s" }
+                                ),
+                            );
+
+                            if diff_options.check_only {
+                                let has_changes = has_text_changes(
+                                    lhs_src,
+                                    rhs_src,
+                                    diff_options,
+                                    display_options,
+                                );
+                                return check_only_text(
+                                    &file_format,
+                                    display_path,
+                                    extra_info,
+                                    lhs_src,
+                                    rhs_src,
+                                    has_changes,
+                                );
+                            }
+
+                            let (lhs_positions, rhs_positions) =
+                                text_change_positions(lhs_src, rhs_src, diff_options);
+                            (file_format, lhs_positions, rhs_positions)
+                        }
+                    }
+                }
+                Err(tsp::TreeError::Cancelled) => {
+                    return cancelled(display_path, extra_info, lhs_src, rhs_src);
+                }
+                Err(tsp::TreeError::ExceededByteLimit(num_bytes)) => {
+                    resource_usage::record_byte_limit_fallback();
+                    let file_format = text_fallback(
+                        display_path,
+                        format!(
+                            "{} exceeded DFT_BYTE_LIMIT",
+                            &format_size(num_bytes, BINARY)
+                        ),
+                    );
+
+                    if diff_options.check_only {
+                        let has_changes =
+                            has_text_changes(lhs_src, rhs_src, diff_options, display_options);
+                        return check_only_text(
+                            &file_format,
+                            display_path,
+                            extra_info,
+                            lhs_src,
+                            rhs_src,
+                            has_changes,
+                        );
+                    }
+
+                    let (lhs_positions, rhs_positions) =
+                        text_change_positions(lhs_src, rhs_src, diff_options);
+                    (file_format, lhs_positions, rhs_positions)
+                }
+            }
+        }
+    };
+
+    let entry = cache::CacheEntry {
+        file_format,
+        lhs_positions,
+        rhs_positions,
+        token_diffed_lines,
+    };
+    // Don't cache timeouts, as the structural diff may finish next
+    // time.
+    if let (Some(cache_dir), Some(key), false) = (&diff_options.cache_dir, cache_key, timed_out) {
+        cache::store(cache_dir, &key, &entry);
+    }
+
+    diff_result_from_entry(
+        display_path,
+        extra_info,
+        lhs_src,
+        rhs_src,
+        entry,
+        declarations,
+        display_options,
+    )
+}
+
+/// Compute the hunks for the diff in `entry`, either freshly computed
+/// or from the cache. `declarations` are the declarations on each
+/// side, if we've parsed the files.
+fn diff_result_from_entry(
+    display_path: &str,
+    extra_info: Option<String>,
+    lhs_src: &str,
+    rhs_src: &str,
+    entry: cache::CacheEntry,
+    declarations: Option<(Vec<tsp::Declaration>, Vec<tsp::Declaration>)>,
+    display_options: &DisplayOptions,
+) -> DiffResult {
+    let (lhs_source, rhs_source) = (lhs_src, rhs_src);
+    let cache::CacheEntry {
+        file_format,
+        mut lhs_positions,
+        mut rhs_positions,
+        token_diffed_lines,
+    } = entry;
+
+    if display_options.ignore_blank_lines {
+        lhs_positions = syntax::ignore_blank_lines(&lhs_positions, lhs_src);
+        rhs_positions = syntax::ignore_blank_lines(&rhs_positions, rhs_src);
+    }
+
+    // Hunks are made of whole lines, so split very long lines (e.g.
+    // minified code) into shorter display lines. Other display modes
+    // must show the file exactly as it is.
+    let split_lines = if matches!(
+        display_options.display_mode,
+        DisplayMode::Inline | DisplayMode::SideBySide | DisplayMode::SideBySideShowBoth
+    ) {
+        long_lines::split_long_lines(lhs_src, rhs_src, &lhs_positions, &rhs_positions)
+    } else {
+        None
+    };
+    let (lhs_src, rhs_src, lhs_positions, rhs_positions, lhs_line_map, rhs_line_map, split_note) =
+        match split_lines {
+            Some(split_lines) => (
+                Cow::Owned(split_lines.lhs_src),
+                Cow::Owned(split_lines.rhs_src),
+                split_lines.lhs_positions,
+                split_lines.rhs_positions,
+                split_lines.lhs_line_map,
+                split_lines.rhs_line_map,
+                Some(format!(
+                    "Lines longer than {} bytes have been split for display.",
+                    long_lines::LONG_LINE_THRESHOLD
+                )),
+            ),
+            None => (
+                Cow::Borrowed(lhs_src),
+                Cow::Borrowed(rhs_src),
+                lhs_positions,
+                rhs_positions,
+                LineMap::default(),
+                LineMap::default(),
+                None,
+            ),
+        };
+
+    // Likewise, show characters that are hard to see with visible
+    // escapes, so a change that looks like nothing is explained.
+    let escaped = if matches!(
+        display_options.display_mode,
+        DisplayMode::Inline | DisplayMode::SideBySide | DisplayMode::SideBySideShowBoth
+    ) {
+        invisible::escape_invisible(
+            &lhs_src,
+            &rhs_src,
+            &lhs_positions,
+            &rhs_positions,
+            display_options.show_invisible,
+            display_options.for_file(&file_format).tab_width,
+        )
+    } else {
+        None
+    };
+    let (lhs_src, rhs_src, lhs_positions, rhs_positions) = match escaped {
+        Some(escaped) => (
+            Cow::Owned(escaped.lhs_src),
+            Cow::Owned(escaped.rhs_src),
+            escaped.lhs_positions,
+            escaped.rhs_positions,
+        ),
+        None => (lhs_src, rhs_src, lhs_positions, rhs_positions),
+    };
+
+    // Without styling, highlighting novel trailing whitespace shows
+    // nothing, so replace it with visible glyphs instead.
+    let escaped = if matches!(
+        display_options.display_mode,
+        DisplayMode::Inline | DisplayMode::SideBySide | DisplayMode::SideBySideShowBoth
+    ) && !display_options.style_content()
+    {
+        invisible::escape_trailing_whitespace(
+            &lhs_src,
+            &rhs_src,
+            &lhs_positions,
+            &rhs_positions,
+            display_options.trailing_whitespace,
+            display_options.for_file(&file_format).tab_width,
+        )
+    } else {
+        None
+    };
+    let (lhs_src, rhs_src, lhs_positions, rhs_positions) = match escaped {
+        Some(escaped) => (
+            Cow::Owned(escaped.lhs_src),
+            Cow::Owned(escaped.rhs_src),
+            escaped.lhs_positions,
+            escaped.rhs_positions,
+        ),
+        None => (lhs_src, rhs_src, lhs_positions, rhs_positions),
+    };
+
+    let opposite_to_lhs = opposite_positions(&lhs_positions);
+    let opposite_to_rhs = opposite_positions(&rhs_positions);
+
+    let hunks = matched_pos_to_hunks(&lhs_positions, &rhs_positions);
+    let hunks = merge_adjacent(
+        &hunks,
+        &opposite_to_lhs,
+        &opposite_to_rhs,
+        lhs_src.max_line(),
+        rhs_src.max_line(),
+        display_options.before_context_lines as usize,
+        display_options.after_context_lines as usize,
+    );
+    let mut hunks = merge_close(hunks, display_options.merge_hunk_gap);
+    match file_format {
+        FileFormat::SupportedLanguage(language) if !hunks.is_empty() => {
+            // Cached diffs don't have syntax trees, so parse the files.
+            let (lhs_declarations, rhs_declarations) = declarations.unwrap_or_else(|| {
+                (
+                    tsp::declarations(lhs_source, language),
+                    tsp::declarations(rhs_source, language),
+                )
+            });
+            set_enclosing_declarations(
+                &mut hunks,
+                &lhs_declarations,
+                &rhs_declarations,
+                &lhs_line_map,
+                &rhs_line_map,
+            );
+        }
+        _ => {}
+    }
+    if display_options.function_context {
+        hunks = merge_same_declaration(hunks);
+    }
+    if let Some(max_hunk_lines) = display_options.max_hunk_lines {
+        hunks = split_large(hunks, max_hunk_lines);
+    }
+    if let Some(line_ranges) = &display_options.line_ranges {
+        hunks = in_line_ranges(hunks, line_ranges, &lhs_line_map, &rhs_line_map);
+    }
+    let has_syntactic_changes = !hunks.is_empty();
+    let degradation = if token_diffed_lines.is_empty() {
+        Degradation::from_file_format(&file_format)
+    } else {
+        Some(Degradation::GraphLimit)
+    };
+
+    let mut extra_info = extra_info;
+    for note in [fallback::fallback_note(&token_diffed_lines), split_note]
+        .into_iter()
+        .flatten()
+    {
+        extra_info = match extra_info {
+            Some(extra_info) => Some(format!("{}\n{}", extra_info, note)),
+            None => Some(note),
+        };
+    }
+
+    DiffResult {
+        extra_info,
+        display_path: display_path.to_owned(),
+        path_bytes: None,
+        renamed_from: None,
+        missing_side: None,
+        mode_change: None,
+        mtime_change: None,
+        binary_info: None,
+        file_format,
+        lhs_src: FileContent::Text(lhs_src.into_owned()),
+        rhs_src: FileContent::Text(rhs_src.into_owned()),
+        lhs_positions,
+        rhs_positions,
+        lhs_line_map,
+        rhs_line_map,
+        hunks,
+        degradation,
+        has_byte_changes: true,
+        has_syntactic_changes,
+    }
+}
+
+/// Given two directories that contain the files, compare them
+/// pairwise. Returns the number of files left out by `--include` and
+/// `--exclude`, and an iterator, so we can print results
+/// incrementally. The iterator is sorted by path, comparing bytes,
+/// so the order doesn't depend on the locale or the platform.
+///
+/// When more than one file is modified, the hg extdiff extension passes directory
+/// paths with the all the modified files.
+fn diff_directories<'a>(
+    lhs_dir: &'a Path,
+    rhs_dir: &'a Path,
+    extra_info: Option<String>,
+    display_options: &DisplayOptions,
+    diff_options: &DiffOptions,
+    overrides: &[(LanguageOverride, Vec<glob::Pattern>)],
+) -> (usize, impl IndexedParallelIterator<Item = DiffResult> + 'a) {
+    let diff_options = diff_options.clone();
+    let display_options = display_options.clone();
+    let overrides: Vec<_> = overrides.into();
+
+    // We greedily list all files in the directory, and then diff them
+    // in parallel. This is assuming that diffing is slower than
+    // enumerating files, so it benefits more from parallelism.
+    let (paths, subdirs) = relative_paths_in_either(lhs_dir, rhs_dir, &diff_options);
+    let (paths, skipped_paths): (Vec<_>, Vec<_>) = paths
+        .into_iter()
+        .partition(|rel_path| diff_options.path_filter.allows(rel_path));
+    let (subdirs, skipped_subdirs): (Vec<_>, Vec<_>) = subdirs
+        .into_iter()
+        .partition(|rel_path| !diff_options.path_filter.excludes(rel_path));
+    let git_dir_diff = is_git_dir_diff(lhs_dir, rhs_dir);
+
+    let rel_paths: Vec<String> = paths.iter().map(|p| escape_path(p)).collect();
+    gitattributes::load(&rel_paths.iter().map(|p| p.as_str()).collect::<Vec<_>>());
+
+    let num_skipped = skipped_paths.len() + skipped_subdirs.len();
+    // The path on each side, which only differ with
+    // --ignore-path-case.
+    let path_pairs: Vec<(PathBuf, PathBuf)> = if diff_options.ignore_path_case {
+        pair_paths_ignoring_case(lhs_dir, rhs_dir, paths)
+    } else {
+        paths
+            .into_iter()
+            .map(|rel_path| (rel_path.clone(), rel_path))
+            .collect()
+    };
+    let mut entries: Vec<((PathBuf, PathBuf), bool)> = path_pairs
+        .into_iter()
+        .map(|path_pair| (path_pair, false))
+        .chain(
+            subdirs
+                .into_iter()
+                .map(|rel_path| ((rel_path.clone(), rel_path), true)),
+        )
+        .collect();
+    // Compare the bytes of the right-hand paths, which sorts UTF-8
+    // paths like strings, without losing the bytes of other paths.
+    entries.sort_by(|((_, lhs_path), _), ((_, rhs_path), _)| {
+        lhs_path.as_os_str().cmp(rhs_path.as_os_str())
+    });
+
+    let diff_iter = entries.into_par_iter().map(move |(path_pair, is_subdir)| {
+        let (lhs_rel_path, rel_path) = path_pair;
+        if is_subdir {
+            return diff_subdirectory(
+                &rel_path,
+                &lhs_dir.join(&rel_path),
+                &rhs_dir.join(&rel_path),
+                &diff_options,
+            );
+        }
+        info!("Relative path is {:?} inside {:?}", rel_path, lhs_dir);
+
+        let lhs_path = Path::new(lhs_dir).join(&lhs_rel_path);
+        let mut rhs_path = Path::new(rhs_dir).join(&rel_path);
+        if git_dir_diff {
+            // `git difftool --dir-diff` links files in the right
+            // tree to the working tree, so read them from there.
+            rhs_path = resolve_symlink(&rhs_path);
+        }
+
+        // Dangling symlinks still exist. A FIFO or device was
+        // skipped when listing its side, so treat it as missing
+        // rather than blocking on reading it.
+        let lhs_special = special_file_at(&lhs_path, diff_options.follow_symlinks).is_some();
+        let rhs_special = special_file_at(&rhs_path, diff_options.follow_symlinks).is_some();
+        let missing_side = if lhs_special || std::fs::symlink_metadata(&lhs_path).is_err() {
+            Some(Side::Left)
+        } else if rhs_special || std::fs::symlink_metadata(&rhs_path).is_err() {
+            Some(Side::Right)
+        } else {
+            None
+        };
+
+        let lhs_target = symlink_target(&lhs_path, diff_options.follow_symlinks);
+        let rhs_target = symlink_target(&rhs_path, diff_options.follow_symlinks);
+        // Most files in similar trees are identical, so check that
+        // before reading and parsing them.
+        let identical = missing_side.is_none()
+            && lhs_target.is_none()
+            && rhs_target.is_none()
+            && same_content(&lhs_path, &rhs_path);
+
+        let as_file_argument = |path: PathBuf, special: bool| {
+            if special {
+                FileArgument::DevNull
+            } else {
+                FileArgument::NamedPath(path)
+            }
+        };
+        let lhs_path = as_file_argument(lhs_path, lhs_special);
+        let rhs_path = as_file_argument(rhs_path, rhs_special);
+
+        // The relative path is the same in both trees, so it's also
+        // the path in the repository for `git difftool --dir-diff`.
+        let display_path = escape_path(&rel_path);
+        let permissions = |path: &FileArgument| {
+            if diff_options.compare_metadata {
+                path.permissions()
+            } else {
+                None
+            }
+        };
+        let mut diff_result = if identical {
+            resource_usage::record_identical_content();
+            diff_identical_files(
+                &display_path,
+                &lhs_dir.join(&lhs_rel_path),
+                permissions(&lhs_path).as_ref(),
+                permissions(&rhs_path).as_ref(),
+                &diff_options,
+                &overrides,
+            )
+        } else if lhs_target.is_none() && rhs_target.is_none() {
+            diff_file(
+                &display_path,
+                None,
+                &lhs_path,
+                &rhs_path,
+                permissions(&lhs_path).as_ref(),
+                permissions(&rhs_path).as_ref(),
+                &display_options,
+                &diff_options,
+                true,
+                &overrides,
+            )
+        } else {
+            diff_symlinks(
+                &display_path,
+                &lhs_path,
+                &rhs_path,
+                lhs_target,
+                rhs_target,
+                &display_options,
+                &diff_options,
+            )
+        };
+        if let Some(extra_info) = &extra_info {
+            diff_result.add_extra_info(extra_info);
+        }
+        if diff_options.compare_mtime && missing_side.is_none() {
+            let mtime = |path: &FileArgument| match path {
+                FileArgument::NamedPath(path) => std::fs::metadata(path)
+                    .and_then(|metadata| metadata.modified())
+                    .ok(),
+                _ => None,
+            };
+            if let (Some(lhs_mtime), Some(rhs_mtime)) = (mtime(&lhs_path), mtime(&rhs_path)) {
+                diff_result.set_mtimes(lhs_mtime, rhs_mtime);
+            }
+        }
+        diff_result.missing_side = missing_side;
+        diff_result.path_bytes = non_utf8_path_bytes(&rel_path);
+        // With --ignore-path-case, show a change of case like a
+        // rename.
+        if lhs_rel_path != rel_path {
+            diff_result.renamed_from = Some(escape_path(&lhs_rel_path));
+        }
+        diff_result
+    });
+    (num_skipped, diff_iter)
+}
+
+/// Read the files in `lhs_path` and `rhs_path` if they're archives
+/// of the same family, see [archive::archive_kinds]. If either
+/// archive can't be read, we warn and return `None`, so they're
+/// diffed as files instead.
+fn read_archives(lhs_path: &Path, rhs_path: &Path) -> Option<(archive::Entries, archive::Entries)> {
+    let (lhs_kind, rhs_kind) = archive::archive_kinds(lhs_path, rhs_path)?;
+    let read = |path: &Path, kind| match archive::read_entries(path, kind) {
+        Ok(entries) => Some(entries),
+        Err(e) => {
+            logging::log(
+                Level::Warning,
+                "archive",
+                &format!(
+                    "{}: Diffing as a binary file, as the archive can't be read: {}",
+                    path.display(),
+                    e
+                ),
+                json!({ "path": path.display().to_string(), "error": e }),
+            );
+            None
+        }
+    };
+    Some((read(lhs_path, lhs_kind)?, read(rhs_path, rhs_kind)?))
+}
+
+/// Diff the files with the same path in two archives, like
+/// [diff_directories]. Each file is shown as `ARCHIVE!/PATH`.
+fn diff_archives(
+    display_path: &str,
+    lhs_entries: archive::Entries,
+    rhs_entries: archive::Entries,
+    extra_info: Option<String>,
+    display_options: &DisplayOptions,
+    diff_options: &DiffOptions,
+    overrides: &[(LanguageOverride, Vec<glob::Pattern>)],
+) -> (usize, impl IndexedParallelIterator<Item = DiffResult>) {
+    let diff_options = diff_options.clone();
+    let display_options = display_options.clone();
+    let overrides: Vec<_> = overrides.into();
+
+    let mut rel_paths: Vec<String> = lhs_entries
+        .keys()
+        .chain(rhs_entries.keys())
+        .cloned()
+        .collect();
+    rel_paths.sort();
+    rel_paths.dedup();
+    let (rel_paths, skipped_paths): (Vec<_>, Vec<_>) = rel_paths
+        .into_iter()
+        .partition(|rel_path| diff_options.path_filter.allows(Path::new(rel_path)));
+
+    let archive_path = display_path.to_owned();
+    let display_paths: Vec<String> = rel_paths
+        .iter()
+        .map(|rel_path| format!("{}!/{}", archive_path, rel_path))
+        .collect();
+    // Look up all the paths at once, so we don't run git for each
+    // entry.
+    gitattributes::load(&display_paths.iter().map(|p| p.as_str()).collect::<Vec<_>>());
+
+    let diff_iter =
+        rel_paths
+            .into_par_iter()
+            .zip(display_paths)
+            .map(move |(rel_path, display_path)| {
+                let lhs_bytes = lhs_entries.get(&rel_path);
+                let rhs_bytes = rhs_entries.get(&rel_path);
+                // The path in the archive is only used to detect the
+                // language.
+                let as_file_argument = |bytes: Option<&Vec<u8>>| match bytes {
+                    Some(_) => FileArgument::NamedPath(PathBuf::from(&rel_path)),
+                    None => FileArgument::DevNull,
+                };
+                let mut diff_result = diff_file_bytes(
+                    &display_path,
+                    None,
+                    &as_file_argument(lhs_bytes),
+                    &as_file_argument(rhs_bytes),
+                    lhs_bytes.map_or(&[], |bytes| bytes.as_slice()),
+                    rhs_bytes.map_or(&[], |bytes| bytes.as_slice()),
+                    None,
+                    None,
+                    &display_options,
+                    &diff_options,
+                    &overrides,
+                );
+                if let Some(extra_info) = &extra_info {
+                    diff_result.add_extra_info(extra_info);
+                }
+                diff_result
+            });
+    (skipped_paths.len(), diff_iter)
+}
+
+/// The result for two files with identical content, without diffing
+/// them. We still read one side, so we can tell whether it's binary
+/// in the same way as a diff would, detect the language for the
+/// header and show the hash of binary files.
+fn diff_identical_files(
+    display_path: &str,
+    path: &Path,
+    lhs_permissions: Option<&FilePermissions>,
+    rhs_permissions: Option<&FilePermissions>,
+    diff_options: &DiffOptions,
+    overrides: &[(LanguageOverride, Vec<glob::Pattern>)],
+) -> DiffResult {
+    // A binary file can start with text, so we need the whole file
+    // to tell.
+    let bytes = read_or_die(path);
+    let kind = match guess_content(&bytes) {
+        ProbableFileKind::Binary if diff_options.force_text => {
+            ProbableFileKind::Text(force_text(&bytes))
+        }
+        kind => kind,
+    };
+    let (file_format, binary_info) = match kind {
+        ProbableFileKind::Binary => {
+            let info = BinaryInfo::new(&bytes);
+            (FileFormat::Binary, Some((info, info)))
+        }
+        ProbableFileKind::Text(src) => {
+            let language =
+                guess_with_attribute(path, &src, overrides, gitattributes::language(display_path));
+            log_language(display_path, language);
+            let file_format = match language {
+                Some(language) => FileFormat::SupportedLanguage(language),
+                None => FileFormat::PlainText,
+            };
+            (file_format, None)
+        }
+    };
+    let content = || {
+        if binary_info.is_some() {
+            FileContent::Binary
+        } else {
+            FileContent::Text("".into())
+        }
+    };
+
+    let mut diff_result = DiffResult {
+        extra_info: None,
+        display_path: display_path.to_owned(),
+        path_bytes: None,
+        renamed_from: None,
+        missing_side: None,
+        mode_change: None,
+        mtime_change: None,
+        binary_info,
+        file_format,
+        lhs_src: content(),
+        rhs_src: content(),
+        lhs_positions: vec![],
+        rhs_positions: vec![],
+        lhs_line_map: LineMap::default(),
+        rhs_line_map: LineMap::default(),
+        hunks: vec![],
+        degradation: None,
+        has_byte_changes: false,
+        has_syntactic_changes: false,
+    };
+    if let (Some(lhs_perms), Some(rhs_perms)) = (lhs_permissions, rhs_permissions) {
+        diff_result.set_modes(&lhs_perms.to_string(), &rhs_perms.to_string());
+    }
+    diff_result
+}
+
+/// Compare a subdirectory beyond `--max-depth` by hashing the files in
+/// each side, rather than diffing them.
+fn diff_subdirectory(
+    rel_path: &Path,
+    lhs_dir: &Path,
+    rhs_dir: &Path,
+    diff_options: &DiffOptions,
+) -> DiffResult {
+    let missing_side = if !lhs_dir.is_dir() {
+        Some(Side::Left)
+    } else if !rhs_dir.is_dir() {
+        Some(Side::Right)
+    } else {
+        None
+    };
+    let has_byte_changes = missing_side.is_some()
+        || dir_hash(lhs_dir, diff_options) != dir_hash(rhs_dir, diff_options);
+
+    DiffResult {
+        extra_info: None,
+        display_path: format!("{}/", escape_path(rel_path)),
+        path_bytes: None,
+        renamed_from: None,
+        missing_side,
+        mode_change: None,
+        mtime_change: None,
+        binary_info: None,
+        file_format: FileFormat::Directory,
+        lhs_src: FileContent::Binary,
+        rhs_src: FileContent::Binary,
+        lhs_positions: vec![],
+        rhs_positions: vec![],
+        lhs_line_map: LineMap::default(),
+        rhs_line_map: LineMap::default(),
+        hunks: vec![],
+        degradation: None,
+        has_byte_changes,
+        has_syntactic_changes: false,
+    }
+}
+
+/// Diff a file in a directory diff where at least one side is a
+/// symlink that we compare by its target, from [symlink_target], as
+/// the text `symlink → TARGET`. The other side may be a regular file,
+/// which is shown as a change of mode.
+fn diff_symlinks(
+    display_path: &str,
+    lhs_path: &FileArgument,
+    rhs_path: &FileArgument,
+    lhs_target: Option<(String, bool)>,
+    rhs_target: Option<(String, bool)>,
+    display_options: &DisplayOptions,
+    diff_options: &DiffOptions,
+) -> DiffResult {
+    let side_content = |path: &FileArgument, target: &Option<(String, bool)>| match target {
+        Some((target, _)) => (
+            format!("symlink → {}", target).into_bytes(),
+            Some(FilePermissions::symlink()),
+        ),
+        None => match path {
+            FileArgument::NamedPath(p) if p.exists() => {
+                (read_file_or_die(path), path.permissions())
+            }
+            _ => (vec![], None),
+        },
+    };
+    let (lhs_bytes, lhs_permissions) = side_content(lhs_path, &lhs_target);
+    let (rhs_bytes, rhs_permissions) = side_content(rhs_path, &rhs_target);
+
+    // The link targets aren't in the language of the file name.
+    let text_override = [(
+        LanguageOverride::PlainText,
+        vec![glob::Pattern::new("*").expect("Valid glob")],
+    )];
+    let mut diff_result = diff_file_bytes(
+        display_path,
+        None,
+        lhs_path,
+        rhs_path,
+        &lhs_bytes,
+        &rhs_bytes,
+        lhs_permissions.as_ref(),
+        rhs_permissions.as_ref(),
+        display_options,
+        diff_options,
+        &text_override,
+    );
+
+    for (target, side) in [(&lhs_target, "left"), (&rhs_target, "right")] {
+        if let Some((target, true)) = target {
+            diff_result.add_extra_info(&format!(
+                "Dangling symlink on the {}: {} doesn't exist.",
+                side, target
+            ));
+        }
+    }
+    diff_result
+}
+
+/// Diff each pair of files from an argument file. Like
+/// [diff_directories], the pairs are diffed in parallel.
+fn diff_pairs<'a>(
+    pairs: &'a [(String, FileArgument, FileArgument)],
+    display_options: &DisplayOptions,
+    diff_options: &DiffOptions,
+    overrides: &[(LanguageOverride, Vec<glob::Pattern>)],
+) -> impl IndexedParallelIterator<Item = DiffResult> + 'a {
+    let diff_options = diff_options.clone();
+    let display_options = display_options.clone();
+    let overrides: Vec<_> = overrides.into();
+
+    pairs
+        .par_iter()
+        .map(move |(display_path, lhs_path, rhs_path)| {
+            diff_file(
+                display_path,
+                None,
+                lhs_path,
+                rhs_path,
+                lhs_path.permissions().as_ref(),
+                rhs_path.permissions().as_ref(),
+                &display_options,
+                &diff_options,
+                false,
+                &overrides,
+            )
+        })
+}
+
+/// Diff each file that changed between two git revisions, or a
+/// revision and the working tree if `rhs_rev` is `None`. Like
+/// [diff_pairs], the files are diffed in parallel.
+fn diff_git_revisions<'a>(
+    changed_files: &'a [git::ChangedFile],
+    lhs: &'a git::Source,
+    rhs: &'a git::Source,
+    toplevel: &'a Path,
+    display_options: &DisplayOptions,
+    diff_options: &DiffOptions,
+    overrides: &[(LanguageOverride, Vec<glob::Pattern>)],
+) -> impl IndexedParallelIterator<Item = DiffResult> + 'a {
+    let diff_options = diff_options.clone();
+    let display_options = display_options.clone();
+    let overrides: Vec<_> = overrides.into();
+
+    changed_files.par_iter().map(move |changed_file| {
+        let path = &changed_file.path;
+        let old_path = changed_file.old_path.as_ref().unwrap_or(path);
+        let read_source = |source: &git::Source, path: &str| {
+            let blob = match source {
+                git::Source::Revision(rev) => git::read_blob(rev, path),
+                git::Source::Index => git::read_blob("", path),
+                git::Source::Worktree => return read_or_die(&toplevel.join(path)),
+            };
+            blob.unwrap_or_else(|e| {
+                eprintln!("{}", e);
+                std::process::exit(EXIT_BAD_ARGUMENTS);
+            })
+        };
+        let lhs_bytes = match changed_file.status {
+            git::Status::Added => vec![],
+            _ => read_source(lhs, old_path),
+        };
+        let rhs_bytes = match changed_file.status {
+            git::Status::Deleted => vec![],
+            _ => read_source(rhs, path),
+        };
+
+        // The paths are only used to detect the language.
+        let file_arg = FileArgument::NamedPath(PathBuf::from(path));
+        let mut diff_result = diff_file_bytes(
+            path,
+            changed_file.old_path.clone(),
+            &file_arg,
+            &file_arg,
+            &lhs_bytes,
+            &rhs_bytes,
+            None,
+            None,
+            &display_options,
+            &diff_options,
+            &overrides,
+        );
+        diff_result.add_extra_info(&format!("{} @ {} → {} @ {}", old_path, lhs, path, rhs));
+        match changed_file.status {
+            git::Status::Added => diff_result.missing_side = Some(Side::Left),
+            git::Status::Deleted => diff_result.missing_side = Some(Side::Right),
+            git::Status::Modified => {}
+        }
+        diff_result
+    })
+}
+
+/// Diff each file reconstructed from a patch. Like [diff_pairs], the
+/// files are diffed in parallel.
+fn diff_patch_files<'a>(
+    patch_files: &'a [from_patch::PatchFile],
+    display_options: &DisplayOptions,
+    diff_options: &DiffOptions,
+    overrides: &[(LanguageOverride, Vec<glob::Pattern>)],
+) -> impl IndexedParallelIterator<Item = DiffResult> + 'a {
+    let diff_options = diff_options.clone();
+    let display_options = display_options.clone();
+    let overrides: Vec<_> = overrides.into();
+
+    patch_files.par_iter().map(move |patch_file| {
+        let display_path = patch_file.display_path();
+        // The files don't exist, but their names are still used to
+        // detect the language.
+        let file_arg = |path: &Option<String>| match path {
+            Some(path) => FileArgument::NamedPath(PathBuf::from(path)),
+            None => FileArgument::DevNull,
+        };
+        let lhs_path = file_arg(&patch_file.old_path);
+        let rhs_path = file_arg(&patch_file.new_path);
+
+        let mut diff_result = if patch_file.binary {
+            DiffResult {
+                extra_info: None,
+                display_path: display_path.to_owned(),
+                path_bytes: None,
+                renamed_from: None,
+                missing_side: None,
+                mode_change: None,
+                mtime_change: None,
+                binary_info: None,
+                file_format: FileFormat::Binary,
+                lhs_src: FileContent::Binary,
+                rhs_src: FileContent::Binary,
+                lhs_positions: vec![],
+                rhs_positions: vec![],
+                lhs_line_map: LineMap::default(),
+                rhs_line_map: LineMap::default(),
+                hunks: vec![],
+                degradation: None,
+                has_byte_changes: true,
+                has_syntactic_changes: false,
+            }
+        } else {
+            diff_file_content(
+                display_path,
+                None,
+                &lhs_path,
+                &rhs_path,
+                &patch_file.lhs_src,
+                &patch_file.rhs_src,
+                &display_options,
+                &diff_options,
+                &overrides,
+            )
+        };
+
+        match (&patch_file.old_path, &patch_file.new_path) {
+            (Some(old_path), Some(new_path)) if old_path != new_path => {
+                diff_result.renamed_from = Some(old_path.clone());
+            }
+            (None, Some(_)) => diff_result.missing_side = Some(Side::Left),
+            (Some(_), None) => diff_result.missing_side = Some(Side::Right),
+            _ => {}
+        }
+        if let (Some(old_mode), Some(new_mode)) = (&patch_file.old_mode, &patch_file.new_mode) {
+            diff_result.set_modes(old_mode, new_mode);
+        }
+        if patch_file.partial {
+            diff_result.add_extra_info("Partial file: only the lines in the patch are shown.");
+        }
+        diff_result
+    })
+}
+
+/// Parse both files as JSON and compute the JSON Patch between them.
+/// Exits if either file is not JSON.
+fn json_patch_or_die(lhs_path: &FileArgument, rhs_path: &FileArgument) -> Vec<serde_json::Value> {
+    let is_dir =
+        |path: &FileArgument| matches!(path, FileArgument::NamedPath(path) if path.is_dir());
+    if is_dir(lhs_path) || is_dir(rhs_path) {
+        eprintln!("error: --display=json-patch requires two files, not directories.");
+        std::process::exit(EXIT_BAD_ARGUMENTS);
+    }
+
+    let (lhs_bytes, rhs_bytes) = read_files_or_die(lhs_path, rhs_path, false);
+    let parse_or_die = |path: &FileArgument, bytes: &[u8]| -> serde_json::Value {
+        match serde_json::from_slice(bytes) {
+            Ok(value) => value,
+            Err(e) => {
+                eprintln!("error: {} is not valid JSON: {}", path, e);
+                std::process::exit(EXIT_BAD_ARGUMENTS);
+            }
+        }
+    };
+    let lhs = parse_or_die(lhs_path, &lhs_bytes);
+    let rhs = parse_or_die(rhs_path, &rhs_bytes);
+
+    display::json_patch::json_patch(&lhs, &rhs)
+}
+
+/// Open the destination for the diff. Call [finish_output] when
+/// done, as output may be buffered.
+fn open_output_or_die(output: &Output) -> Box<dyn Write> {
+    match output {
+        // Modes that don't support --interactive print as usual.
+        Output::Stdout | Output::Interactive => stdout_output(),
+        Output::Pager(command) => match Pager::spawn(command) {
+            Ok(pager) => Box::new(pager),
+            Err(e) => {
+                logging::log(
+                    Level::Warning,
+                    "pager",
+                    &format!("Could not run pager '{}': {}", command, e),
+                    json!({ "command": command }),
+                );
+                stdout_output()
+            }
+        },
+        Output::File(output_file) => match std::fs::File::create(output_file) {
+            Ok(file) => Box::new(BufWriter::new(file)),
+            Err(e) => {
+                eprintln!("error: Could not write to {}: {}", output_file.display(), e);
+                std::process::exit(EXIT_BAD_ARGUMENTS);
+            }
+        },
+    }
+}
+
+/// Stdout, locked once rather than for every write, and buffered.
+fn stdout_output() -> Box<dyn Write> {
+    Box::new(BufWriter::new(std::io::stdout().lock()))
+}
+
+/// Flush the output, and wait for the user to quit the pager if
+/// there is one.
+fn finish_output(mut out: Box<dyn Write>) {
+    write_or_die(out.flush());
+    drop(out);
+
+    if pager::INTERRUPT.is_cancelled() {
+        eprintln!("Interrupted.");
+        std::process::exit(EXIT_INTERRUPTED);
+    }
+}
+
+/// Print each language and its globs for `--list-languages`, starting
+/// with any `--override` globs.
+fn print_language_list(
+    out: &mut dyn Write,
+    language_overrides: &[(LanguageOverride, Vec<glob::Pattern>)],
+    use_color: bool,
+) -> std::io::Result<()> {
+    for (lang_override, globs) in language_overrides {
+        let mut name = match lang_override {
+            LanguageOverride::Language(lang) => language_name(*lang),
+            LanguageOverride::PlainText => "Text",
+        }
+        .to_owned();
+        if use_color {
+            name = name.bold().to_string();
+        }
+        writeln!(out, "{} (from override)", name)?;
+        for glob in globs {
+            write!(out, " {}", glob.as_str())?;
+        }
+        writeln!(out)?;
+    }
+
+    for language in Language::iter() {
+        let mut name = language_name(language).to_owned();
+        if use_color {
+            name = name.bold().to_string();
+        }
+        writeln!(out, "{}", name)?;
+
+        for glob in language_globs(language) {
+            write!(out, " {}", glob.as_str())?;
+        }
+        writeln!(out)?;
+    }
+    Ok(())
+}
+
+/// Exit if writing the output failed, e.g. the disk is full. If the
+/// output was closed early, exit quietly, as the reader doesn't want
+/// any more.
+fn write_or_die<T>(res: std::io::Result<T>) -> T {
+    match res {
+        Ok(value) => value,
+        Err(e) if e.kind() == std::io::ErrorKind::BrokenPipe => {
+            std::process::exit(EXIT_BROKEN_PIPE);
+        }
+        Err(e) => {
+            eprintln!("error: Could not write output: {}", e);
+            std::process::exit(EXIT_BAD_ARGUMENTS);
+        }
+    }
+}
+
+/// Print `diff_results` in a display mode that describes all the files
+/// together, rather than one file at a time.
+fn print_combined(
+    out: &mut dyn Write,
+    diff_results: &[DiffResult],
+    display_options: &DisplayOptions,
+) -> std::io::Result<()> {
+    match display_options.display_mode {
+        DisplayMode::Html => write!(
+            out,
+            "{}",
+            display::html::render_document(diff_results, display_options)
+        ),
+        DisplayMode::Stat => display::stat::print(out, diff_results, display_options),
+        DisplayMode::Sarif => display::sarif::print(out, diff_results),
+        DisplayMode::Markdown => display::markdown::print(out, diff_results, display_options),
+        DisplayMode::Symbols => display::symbols::print(out, diff_results, display_options),
+        DisplayMode::Check => display::check::print(out, diff_results),
+        DisplayMode::Inline
+        | DisplayMode::SideBySide
+        | DisplayMode::SideBySideShowBoth
+        | DisplayMode::Json
+        | DisplayMode::JsonPatch
+        | DisplayMode::Patch => unreachable!(),
+    }
+}
+
+/// Print `summary`, unless it's a text file that only exists on one
+/// side and `list_one_sided` is set. Then we only say how many lines
+/// it has, so a large new file doesn't bury the other changes.
+fn print_listing_or_diff_result(
+    out: &mut dyn Write,
+    display_options: &DisplayOptions,
+    summary: &DiffResult,
+    list_one_sided: bool,
+) -> std::io::Result<()> {
+    let list_one_sided =
+        list_one_sided && !matches!(display_options.display_mode, DisplayMode::Patch);
+    let (src, description) = match (summary.missing_side, &summary.lhs_src, &summary.rhs_src) {
+        (Some(Side::Left), _, FileContent::Text(src)) if list_one_sided => (src, "New file"),
+        (Some(Side::Right), FileContent::Text(src), _) if list_one_sided => (src, "Deleted file"),
+        _ => return print_diff_result(out, display_options, summary),
+    };
+
+    writeln!(
+        out,
+        "{}",
+        display::style::header(
+            &summary.header_path(),
+            summary.extra_info.as_ref(),
+            1,
+            1,
+            &summary.file_format,
+            None,
+            display_options
+        )
+    )?;
+    let num_lines = src.lines().count();
+    writeln!(
+        out,
+        "{} with {} line{}. Use --new-file to show its content.\n",
+        description,
+        num_lines,
+        if num_lines == 1 { "" } else { "s" }
+    )?;
+    Ok(())
+}
+
+fn print_diff_result(
+    out: &mut dyn Write,
+    display_options: &DisplayOptions,
+    summary: &DiffResult,
+) -> std::io::Result<()> {
+    let _display_timer = profile::DisplayTimer::start(summary);
+    let file_display_options = display_options.for_file(&summary.file_format);
+    let display_options: &DisplayOptions = &file_display_options;
+    let header_path = summary.header_path();
+
+    // A rename is a change even if the content is the same, so show
+    // it with --skip-unchanged too.
+    if summary.renamed_from.is_some() && !summary.has_byte_changes && !summary.has_metadata_change()
+    {
+        writeln!(
+            out,
+            "{}\n",
+            display::style::pure_rename_header(&header_path, display_options)
+        )?;
+        return Ok(());
+    }
+
+    if matches!(display_options.display_mode, DisplayMode::Patch) {
+        return display::patch::print(
+            out,
+            summary,
+            display_options.before_context_lines as usize,
+            display_options.after_context_lines as usize,
+        );
+    }
+
+    match (&summary.lhs_src, &summary.rhs_src) {
+        (FileContent::Text(lhs_src), FileContent::Text(rhs_src)) => {
+            let hunks = &summary.hunks;
+
+            if !summary.has_syntactic_changes {
+                // A mode or modification time change is a change even
+                // if the content is the same.
+                if display_options.print_unchanged || summary.has_metadata_change() {
+                    writeln!(
+                        out,
+                        "{}",
+                        display::style::header(
+                            &header_path,
+                            summary.extra_info.as_ref(),
+                            1,
+                            1,
+                            &summary.file_format,
+                            None,
+                            display_options
+                        )
+                    )?;
+                    match summary.file_format {
+                        _ if summary.has_metadata_change()
+                            && summary.lhs_src == summary.rhs_src =>
+                        {
+                            writeln!(out, "No content changes.\n")?;
+                        }
+                        _ if summary.lhs_src == summary.rhs_src => {
+                            writeln!(out, "No changes.\n")?;
+                        }
+                        // The file changed, but not in the lines
+                        // selected with --lines.
+                        _ if display_options.line_ranges.is_some() => {
+                            writeln!(out, "No changes in the selected lines.\n")?;
+                        }
+                        FileFormat::SupportedLanguage(_) => {
+                            writeln!(out, "No syntactic changes.\n")?;
+                        }
+                        _ => {
+                            writeln!(out, "No changes.\n")?;
+                        }
+                    }
+                }
+                return Ok(());
+            }
+
+            if summary.file_format == FileFormat::Generated {
+                writeln!(
+                    out,
+                    "{}",
+                    display::style::header(
+                        &header_path,
+                        summary.extra_info.as_ref(),
+                        1,
+                        1,
+                        &summary.file_format,
+                        None,
+                        display_options
+                    )
+                )?;
+                let (insertions, deletions) = display::stat::line_changes(hunks);
+                writeln!(
+                    out,
+                    "Generated file changed, +{}/-{} lines.\n",
+                    insertions, deletions
+                )?;
+                return Ok(());
+            }
+
+            if summary.has_syntactic_changes && hunks.is_empty() {
+                writeln!(
+                    out,
+                    "{}",
+                    display::style::header(
+                        &header_path,
+                        summary.extra_info.as_ref(),
+                        1,
+                        1,
+                        &summary.file_format,
+                        None,
+                        display_options
+                    )
+                )?;
+                match summary.file_format {
+                    FileFormat::SupportedLanguage(_) => {
+                        writeln!(out, "Has syntactic changes.\n")?;
+                    }
+                    _ => {
+                        writeln!(out, "Has changes.\n")?;
+                    }
+                }
+
+                return Ok(());
+            }
+
+            if !display_options.check_hunk_selection(&summary.display_path, hunks.len()) {
+                return Ok(());
+            }
+
+            match display_options.display_mode {
+                DisplayMode::Inline => {
+                    display::inline::print(
+                        out,
+                        lhs_src,
+                        rhs_src,
+                        display_options,
+                        &summary.lhs_positions,
+                        &summary.rhs_positions,
+                        hunks,
+                        &header_path,
+                        &summary.extra_info,
+                        &summary.file_format,
+                        &summary.lhs_line_map,
+                        &summary.rhs_line_map,
+                    )?;
+                }
+                DisplayMode::SideBySide | DisplayMode::SideBySideShowBoth => {
+                    display::side_by_side::print(
+                        out,
+                        hunks,
+                        display_options,
+                        &header_path,
+                        summary.extra_info.as_ref(),
+                        &summary.file_format,
+                        lhs_src,
+                        rhs_src,
+                        &summary.lhs_positions,
+                        &summary.rhs_positions,
+                        &summary.lhs_line_map,
+                        &summary.rhs_line_map,
+                    )?;
+                }
+                DisplayMode::Json
+                | DisplayMode::JsonPatch
+                | DisplayMode::Html
+                | DisplayMode::Patch
+                | DisplayMode::Stat
+                | DisplayMode::Sarif
+                | DisplayMode::Markdown
+                | DisplayMode::Symbols
+                | DisplayMode::Check => {
+                    unreachable!()
+                }
+            }
+        }
+        (FileContent::Binary, FileContent::Binary) => {
+            if display_options.print_unchanged
+                || summary.has_byte_changes
+                || summary.has_metadata_change()
+            {
+                writeln!(
+                    out,
+                    "{}",
+                    display::style::header(
+                        &header_path,
+                        summary.extra_info.as_ref(),
+                        1,
+                        1,
+                        &summary.file_format,
+                        None,
+                        display_options
+                    )
+                )?;
+                if summary.file_format == FileFormat::Directory {
+                    if summary.has_byte_changes {
+                        writeln!(out, "Subdirectory differs.\n")?;
+                    } else {
+                        writeln!(out, "Subdirectory identical.\n")?;
+                    }
+                } else {
+                    writeln!(out, "{}\n", summary.binary_message())?;
+                }
+            }
+        }
+        (FileContent::Text(_), FileContent::Binary)
+        | (FileContent::Binary, FileContent::Text(_)) => {
+            // We're diffing a binary file against a text file.
+            writeln!(
+                out,
+                "{}",
+                display::style::header(
+                    &header_path,
+                    summary.extra_info.as_ref(),
+                    1,
+                    1,
+                    &FileFormat::Binary,
+                    None,
+                    display_options
+                )
+            )?;
+            writeln!(out, "Binary contents changed.\n")?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ffi::OsStr;
+
+    use super::*;
+
+    #[test]
+    fn test_diff_identical_content() {
+        let s = "foo";
+        let res = diff_file_content(
+            "foo.el",
+            None,
+            &FileArgument::from_path_argument(OsStr::new("foo.el")),
+            &FileArgument::from_path_argument(OsStr::new("foo.el")),
+            s,
+            s,
+            &DisplayOptions::default(),
+            &DiffOptions::default(),
+            &[],
+        );
+
+        assert_eq!(res.lhs_positions, vec![]);
+        assert_eq!(res.rhs_positions, vec![]);
+    }
+
+    #[test]
+    fn test_trailing_commas_are_punctuation() {
+        let items: Vec<String> = (0..50).map(|i| format!("[{}]", i)).collect();
+        let lhs = format!("x = [{}];\n", items.join(",\n"));
+        let items: Vec<String> = (0..50).map(|i| format!("[{},]", i)).collect();
+        let rhs = format!("x = [{}];\n", items.join(",\n"));
+        let path = FileArgument::from_path_argument(OsStr::new("foo.js"));
+
+        let res = diff_file_content(
+            "foo.js",
+            None,
+            &path,
+            &path,
+            &lhs,
+            &rhs,
+            &DisplayOptions::default(),
+            &DiffOptions::default(),
+            &[],
+        );
+
+        // Every change is a comma, so it can be shown dimmed.
+        let novel: Vec<_> = res
+            .rhs_positions
+            .iter()
+            .filter(|mp| mp.kind.is_novel())
+            .collect();
+        assert_eq!(novel.len(), 50);
+        assert!(novel.iter().all(|mp| mp.kind.is_novel_punctuation()));
+
+        let res = diff_file_content(
+            "foo.js",
+            None,
+            &path,
+            &path,
+            &lhs,
+            &rhs,
+            &DisplayOptions::default(),
+            &DiffOptions {
+                ignore_punctuation: true,
+                ..DiffOptions::default()
+            },
+            &[],
+        );
+        assert!(!res.has_syntactic_changes);
+    }
+
+    #[test]
+    fn test_truncate_at_line() {
+        assert_eq!(truncate_at_line("foo\nbar\nbaz\n", 9), "foo\nbar\n");
+        assert_eq!(truncate_at_line("foo\nbar\n", 100), "foo\nbar\n");
+        // The first line doesn't fit, so cut it at a character.
+        assert_eq!(truncate_at_line("aéb\n", 2), "a");
+    }
+}
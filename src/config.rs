@@ -0,0 +1,238 @@
+//! Read default values for options from a config file, see
+//! `--config`.
+//!
+//! The config file is TOML. Each key is the long name of an option,
+//! and the value is a string, number, boolean or an array of strings
+//! and numbers. `[language.NAME]` tables hold settings that only
+//! apply to one language.
+
+use std::{
+    collections::BTreeMap,
+    env,
+    path::{Path, PathBuf},
+};
+
+use serde::Deserialize;
+use toml::Spanned;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Value {
+    /// A string or a number, passed to the option as written.
+    Scalar(String),
+    Boolean(bool),
+    Array(Vec<String>),
+}
+
+/// A `key = value` line in the config file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Entry {
+    pub(crate) key: String,
+    pub(crate) value: Value,
+    pub(crate) line_num: usize,
+}
+
+#[derive(Debug, Default, PartialEq, Eq)]
+pub(crate) struct Config {
+    /// The entries before the first table.
+    pub(crate) options: Vec<Entry>,
+    /// The entries in each `[language.NAME]` table.
+    pub(crate) languages: Vec<(String, Vec<Entry>)>,
+}
+
+/// The config file used when `--config` isn't specified,
+/// `$XDG_CONFIG_HOME/difftastic/config.toml`.
+pub(crate) fn default_path() -> Option<PathBuf> {
+    let config_home = match env::var_os("XDG_CONFIG_HOME") {
+        Some(config_home) if !config_home.is_empty() => PathBuf::from(config_home),
+        _ => PathBuf::from(env::var_os("HOME")?).join(".config"),
+    };
+    Some(config_home.join("difftastic").join("config.toml"))
+}
+
+/// Read the config file at `path`. If `must_exist` is false, a
+/// missing file is treated as an empty config.
+pub(crate) fn read(path: &Path, must_exist: bool) -> Result<Option<Config>, String> {
+    let src = match std::fs::read_to_string(path) {
+        Ok(src) => src,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound && !must_exist => return Ok(None),
+        Err(e) => return Err(format!("Could not read {}: {}", path.display(), e)),
+    };
+
+    parse(&src)
+        .map(Some)
+        .map_err(|e| format!("Invalid config file {}: {}", path.display(), e))
+}
+
+/// The values in a TOML table, with their positions so we can report
+/// the line of each entry.
+type Table = BTreeMap<String, Spanned<toml::Value>>;
+
+#[derive(Deserialize)]
+struct LanguageTables {
+    language: BTreeMap<String, Table>,
+}
+
+/// The 1-based line number of the byte at `offset` in `src`.
+fn line_num(src: &str, offset: usize) -> usize {
+    src[..offset].matches('\n').count() + 1
+}
+
+/// A string or a number, as it would be written on the command line.
+fn scalar(value: toml::Value) -> Option<String> {
+    match value {
+        toml::Value::String(s) => Some(s),
+        toml::Value::Integer(i) => Some(i.to_string()),
+        toml::Value::Float(f) => Some(f.to_string()),
+        _ => None,
+    }
+}
+
+/// The entries of `table`, in the order they appear in `src`.
+fn entries(src: &str, table: Table) -> Result<Vec<Entry>, String> {
+    let mut entries = vec![];
+    for (key, value) in table {
+        let line_num = line_num(src, value.start());
+        let value = match value.into_inner() {
+            toml::Value::Boolean(b) => Some(Value::Boolean(b)),
+            toml::Value::Array(items) => items
+                .into_iter()
+                .map(scalar)
+                .collect::<Option<Vec<_>>>()
+                .map(Value::Array),
+            toml::Value::Table(_) => {
+                return Err(format!(
+                    "line {}: unknown table `[{}]`, expected `[language.NAME]`",
+                    line_num, key
+                ))
+            }
+            value => scalar(value).map(Value::Scalar),
+        };
+        let value = value.ok_or_else(|| {
+            format!(
+                "line {}: `{}` must be a string, number, boolean or array of strings and numbers",
+                line_num, key
+            )
+        })?;
+
+        entries.push(Entry {
+            key,
+            value,
+            line_num,
+        });
+    }
+
+    entries.sort_by_key(|entry| entry.line_num);
+    Ok(entries)
+}
+
+pub(crate) fn parse(src: &str) -> Result<Config, String> {
+    let mut options: Table = toml::from_str(src).map_err(|e| e.to_string())?;
+
+    // `language` is also an option, so it's only the language tables
+    // if it's a table.
+    let mut languages = vec![];
+    if matches!(
+        options.get("language").map(|value| value.get_ref()),
+        Some(toml::Value::Table(_))
+    ) {
+        options.remove("language");
+
+        let tables: LanguageTables = toml::from_str(src).map_err(|e| e.to_string())?;
+        for (name, table) in tables.language {
+            languages.push((name, entries(src, table)?));
+        }
+        // Keep the tables in the order they appear in `src`.
+        languages.sort_by_key(|(_, entries)| entries.first().map(|entry| entry.line_num));
+    }
+
+    Ok(Config {
+        options: entries(src, options)?,
+        languages,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn entry(key: &str, value: Value, line_num: usize) -> Entry {
+        Entry {
+            key: key.to_owned(),
+            value,
+            line_num,
+        }
+    }
+
+    #[test]
+    fn test_parse() {
+        let src = r#"
+# Options for every file.
+display = "side-by-side-show-both"
+tab-width = 2  # Two spaces.
+syntax-highlight = true
+override = ['*.h:C++', "*.inc:C"]
+
+[language."C++"]
+tab-width = 4
+"#;
+
+        assert_eq!(
+            parse(src).unwrap(),
+            Config {
+                options: vec![
+                    entry(
+                        "display",
+                        Value::Scalar("side-by-side-show-both".to_owned()),
+                        3
+                    ),
+                    entry("tab-width", Value::Scalar("2".to_owned()), 4),
+                    entry("syntax-highlight", Value::Boolean(true), 5),
+                    entry(
+                        "override",
+                        Value::Array(vec!["*.h:C++".to_owned(), "*.inc:C".to_owned()]),
+                        6
+                    ),
+                ],
+                languages: vec![(
+                    "C++".to_owned(),
+                    vec![entry("tab-width", Value::Scalar("4".to_owned()), 9)]
+                )],
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_string_escapes() {
+        let value = |src: &str| parse(src).unwrap().options[0].value.clone();
+        assert_eq!(
+            value(r#"a = "a\"b # c""#),
+            Value::Scalar("a\"b # c".to_owned())
+        );
+        assert_eq!(
+            value(r#"a = 'C:\temp'"#),
+            Value::Scalar("C:\\temp".to_owned())
+        );
+    }
+
+    #[test]
+    fn test_parse_language_option() {
+        assert_eq!(
+            parse("language = \"Rust\"").unwrap(),
+            Config {
+                options: vec![entry("language", Value::Scalar("Rust".to_owned()), 1)],
+                languages: vec![],
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(parse("display = side-by-side").is_err());
+        assert!(parse("tab-width").is_err());
+        assert!(parse("[colors]").is_err());
+        assert!(parse("display = \"inline").is_err());
+        assert!(parse("override = [[\"*.h:C++\"]]").is_err());
+    }
+}
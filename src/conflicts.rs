@@ -22,6 +22,9 @@ pub(crate) struct ConflictFiles {
     pub(crate) lhs_content: String,
     pub(crate) rhs_name: Option<String>,
     pub(crate) rhs_content: String,
+    /// The common ancestor, if every conflict included it (the diff3
+    /// conflict style).
+    pub(crate) base_content: Option<String>,
     pub(crate) num_conflicts: usize,
 }
 
@@ -33,7 +36,9 @@ pub(crate) fn apply_conflict_markers(s: &str) -> Result<ConflictFiles, String> {
 
     let mut lhs_content = String::with_capacity(s.len());
     let mut rhs_content = String::with_capacity(s.len());
+    let mut base_content = String::with_capacity(s.len());
     let mut num_conflicts = 0;
+    let mut num_base_sections = 0;
 
     let mut state = NoConflict;
     let mut conflict_start_line = None;
@@ -58,6 +63,7 @@ pub(crate) fn apply_conflict_markers(s: &str) -> Result<ConflictFiles, String> {
         }
         if line.starts_with(START_BASE_MARKER) {
             state = Base;
+            num_base_sections += 1;
             continue;
         }
         if line.starts_with(START_RHS_MARKER) {
@@ -84,6 +90,7 @@ pub(crate) fn apply_conflict_markers(s: &str) -> Result<ConflictFiles, String> {
             NoConflict => {
                 lhs_content.push_str(line);
                 rhs_content.push_str(line);
+                base_content.push_str(line);
             }
             Left => {
                 lhs_content.push_str(line);
@@ -91,7 +98,9 @@ pub(crate) fn apply_conflict_markers(s: &str) -> Result<ConflictFiles, String> {
             Right => {
                 rhs_content.push_str(line);
             }
-            Base => {}
+            Base => {
+                base_content.push_str(line);
+            }
         }
     }
 
@@ -101,6 +110,11 @@ pub(crate) fn apply_conflict_markers(s: &str) -> Result<ConflictFiles, String> {
             lhs_content,
             rhs_name,
             rhs_content,
+            base_content: if num_base_sections == num_conflicts {
+                Some(base_content)
+            } else {
+                None
+            },
             num_conflicts,
         })
     } else {
@@ -128,6 +142,10 @@ mod tests {
         let conflict_files = apply_conflict_markers(s).unwrap();
         assert_eq!(conflict_files.lhs_content, "before\nnew in left\nafter");
         assert_eq!(conflict_files.rhs_content, "before\nnew in right\nafter");
+        assert_eq!(
+            conflict_files.base_content.as_deref(),
+            Some("before\noriginal\nafter")
+        );
 
         assert_eq!(conflict_files.lhs_name.unwrap(), "Temporary merge branch 1");
         assert_eq!(conflict_files.rhs_name.unwrap(), "Temporary merge branch 2");
@@ -142,6 +160,7 @@ mod tests {
         let conflict_files = apply_conflict_markers(s).unwrap();
         assert_eq!(conflict_files.lhs_content, "before\nnew in left\nafter");
         assert_eq!(conflict_files.rhs_content, "before\nnew in right\nafter");
+        assert_eq!(conflict_files.base_content, None);
 
         assert_eq!(conflict_files.lhs_name.unwrap(), "Temporary merge branch 1");
         assert_eq!(conflict_files.rhs_name.unwrap(), "Temporary merge branch 2");
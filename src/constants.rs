@@ -1,4 +1,6 @@
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde-types", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde-types", serde(rename_all = "lowercase"))]
 pub(crate) enum Side {
     /// The left-hand side, or the 'before' file. Often abbreviated to
     /// LHS.
@@ -0,0 +1,252 @@
+//! Split the input to the tree diff at unique nodes that occur on
+//! both sides.
+//!
+//! This is patience diff applied to syntax: a node whose content
+//! occurs exactly once on each side is almost certainly the same
+//! node, so we treat it as a fixed match and only run the tree diff
+//! on the regions between these anchors. This keeps the graph small
+//! on large files with many repeated tokens, where the search can't
+//! otherwise commit to obvious correspondences.
+
+use crate::{
+    diff::changes::{insert_deep_unchanged, ChangeKind, ChangeMap},
+    hash::DftHashMap,
+    parse::syntax::Syntax,
+};
+
+/// Find pairs of sibling nodes that are unique on their side and
+/// have the same content on both sides.
+///
+/// The pairs are in order on both sides, so anchors never cross.
+fn find_anchors(lhs_nodes: &[&Syntax], rhs_nodes: &[&Syntax]) -> Vec<(usize, usize)> {
+    let rhs_unique: DftHashMap<u32, usize> = rhs_nodes
+        .iter()
+        .enumerate()
+        .filter(|(_, node)| node.content_is_unique())
+        .map(|(j, node)| (node.content_id(), j))
+        .collect();
+
+    let candidates: Vec<(usize, usize)> = lhs_nodes
+        .iter()
+        .enumerate()
+        .filter(|(_, node)| node.content_is_unique())
+        .filter_map(|(i, node)| rhs_unique.get(&node.content_id()).map(|j| (i, *j)))
+        .collect();
+
+    longest_increasing_subsequence(&candidates)
+}
+
+/// Given pairs sorted by their first item, return the longest
+/// subsequence whose second items are also increasing.
+fn longest_increasing_subsequence(pairs: &[(usize, usize)]) -> Vec<(usize, usize)> {
+    // The index in `pairs` of the last item of the best subsequence
+    // of each length, patience sorting style.
+    let mut pile_tops: Vec<usize> = vec![];
+    // The index in `pairs` of the previous item in the subsequence.
+    let mut predecessors: Vec<Option<usize>> = Vec::with_capacity(pairs.len());
+
+    for (i, (_, j)) in pairs.iter().enumerate() {
+        let pile = pile_tops.partition_point(|top| pairs[*top].1 < *j);
+        predecessors.push(if pile == 0 {
+            None
+        } else {
+            Some(pile_tops[pile - 1])
+        });
+
+        if pile == pile_tops.len() {
+            pile_tops.push(i);
+        } else {
+            pile_tops[pile] = i;
+        }
+    }
+
+    let mut res = vec![];
+    let mut current = pile_tops.last().copied();
+    while let Some(i) = current {
+        res.push(pairs[i]);
+        current = predecessors[i];
+    }
+
+    res.reverse();
+    res
+}
+
+/// If `lhs` and `rhs` are both lists with the same delimiters,
+/// return their children.
+fn same_delimiter_children<'a>(
+    lhs: &'a Syntax<'a>,
+    rhs: &'a Syntax<'a>,
+) -> Option<(&'a [&'a Syntax<'a>], &'a [&'a Syntax<'a>])> {
+    match (lhs, rhs) {
+        (
+            Syntax::List {
+                open_content: lhs_open,
+                children: lhs_children,
+                close_content: lhs_close,
+                ..
+            },
+            Syntax::List {
+                open_content: rhs_open,
+                children: rhs_children,
+                close_content: rhs_close,
+                ..
+            },
+        ) if lhs_open == rhs_open && lhs_close == rhs_close => {
+            Some((&lhs_children[..], &rhs_children[..]))
+        }
+        _ => None,
+    }
+}
+
+fn split_section<'a>(
+    lhs_nodes: &[&'a Syntax<'a>],
+    rhs_nodes: &[&'a Syntax<'a>],
+    change_map: &mut ChangeMap<'a>,
+    res: &mut Vec<(Vec<&'a Syntax<'a>>, Vec<&'a Syntax<'a>>)>,
+) {
+    if lhs_nodes.is_empty() && rhs_nodes.is_empty() {
+        return;
+    }
+
+    // If this section is a single list on both sides, look for
+    // anchors inside it. We can only split the children if we mark
+    // the delimiters as unchanged.
+    if let (&[lhs_node], &[rhs_node]) = (lhs_nodes, rhs_nodes) {
+        if let Some((lhs_children, rhs_children)) = same_delimiter_children(lhs_node, rhs_node) {
+            if !find_anchors(lhs_children, rhs_children).is_empty() {
+                change_map.insert(lhs_node, ChangeKind::Unchanged(rhs_node));
+                change_map.insert(rhs_node, ChangeKind::Unchanged(lhs_node));
+                split_anchored(lhs_children, rhs_children, change_map, res);
+                return;
+            }
+        }
+    }
+
+    res.push((lhs_nodes.to_vec(), rhs_nodes.to_vec()));
+}
+
+fn split_anchored<'a>(
+    lhs_nodes: &[&'a Syntax<'a>],
+    rhs_nodes: &[&'a Syntax<'a>],
+    change_map: &mut ChangeMap<'a>,
+    res: &mut Vec<(Vec<&'a Syntax<'a>>, Vec<&'a Syntax<'a>>)>,
+) {
+    let mut lhs_start = 0;
+    let mut rhs_start = 0;
+    for (i, j) in find_anchors(lhs_nodes, rhs_nodes) {
+        split_section(
+            &lhs_nodes[lhs_start..i],
+            &rhs_nodes[rhs_start..j],
+            change_map,
+            res,
+        );
+
+        insert_deep_unchanged(lhs_nodes[i], rhs_nodes[j], change_map);
+        insert_deep_unchanged(rhs_nodes[j], lhs_nodes[i], change_map);
+
+        lhs_start = i + 1;
+        rhs_start = j + 1;
+    }
+
+    split_section(
+        &lhs_nodes[lhs_start..],
+        &rhs_nodes[rhs_start..],
+        change_map,
+        res,
+    );
+}
+
+/// Mark nodes that are unique on both sides as unchanged, and return
+/// the regions between them that need proper diffing.
+pub(crate) fn split_on_anchors<'a>(
+    lhs_nodes: &[&'a Syntax<'a>],
+    rhs_nodes: &[&'a Syntax<'a>],
+    change_map: &mut ChangeMap<'a>,
+) -> Vec<(Vec<&'a Syntax<'a>>, Vec<&'a Syntax<'a>>)> {
+    let mut res = vec![];
+    split_anchored(lhs_nodes, rhs_nodes, change_map, &mut res);
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use typed_arena::Arena;
+
+    use super::*;
+    use crate::{
+        parse::guess_language,
+        parse::tree_sitter_parser::{from_language, parse},
+        syntax::init_all_info,
+    };
+
+    #[test]
+    fn test_longest_increasing_subsequence() {
+        assert_eq!(
+            longest_increasing_subsequence(&[(0, 3), (1, 0), (2, 1), (3, 4), (4, 2)]),
+            vec![(1, 0), (2, 1), (4, 2)]
+        );
+        assert_eq!(longest_increasing_subsequence(&[]), vec![]);
+    }
+
+    #[test]
+    fn test_find_anchors_no_crossing() {
+        let arena = Arena::new();
+        let config = from_language(guess_language::Language::EmacsLisp);
+
+        let lhs_nodes = parse(&arena, "a b x c", &config, false);
+        let rhs_nodes = parse(&arena, "b a y c", &config, false);
+        init_all_info(&lhs_nodes, &rhs_nodes);
+
+        let anchors = find_anchors(&lhs_nodes, &rhs_nodes);
+        assert_eq!(anchors.len(), 2);
+        assert_eq!(anchors[1], (3, 3));
+        for pair in anchors.windows(2) {
+            assert!(pair[0].0 < pair[1].0 && pair[0].1 < pair[1].1);
+        }
+    }
+
+    #[test]
+    fn test_split_on_anchors() {
+        let arena = Arena::new();
+        let config = from_language(guess_language::Language::EmacsLisp);
+
+        let lhs_nodes = parse(&arena, "(x x unique x)", &config, false);
+        let rhs_nodes = parse(&arena, "(x unique x y)", &config, false);
+        init_all_info(&lhs_nodes, &rhs_nodes);
+
+        let mut change_map = ChangeMap::default();
+        let res = split_on_anchors(&lhs_nodes, &rhs_nodes, &mut change_map);
+
+        // The outer list and `unique` are anchored, leaving the
+        // regions before and after `unique`.
+        assert_eq!(
+            change_map.get(lhs_nodes[0]),
+            Some(ChangeKind::Unchanged(rhs_nodes[0]))
+        );
+        assert_eq!(res.len(), 2);
+        assert_eq!((res[0].0.len(), res[0].1.len()), (2, 1));
+        assert_eq!((res[1].0.len(), res[1].1.len()), (1, 2));
+    }
+
+    #[test]
+    fn test_split_on_anchors_top_level() {
+        let arena = Arena::new();
+        let config = from_language(guess_language::Language::EmacsLisp);
+
+        let lhs_nodes = parse(&arena, "x x unique x", &config, false);
+        let rhs_nodes = parse(&arena, "x unique x y", &config, false);
+        init_all_info(&lhs_nodes, &rhs_nodes);
+
+        let mut change_map = ChangeMap::default();
+        let res = split_on_anchors(&lhs_nodes, &rhs_nodes, &mut change_map);
+
+        assert_eq!(
+            change_map.get(lhs_nodes[2]),
+            Some(ChangeKind::Unchanged(rhs_nodes[1]))
+        );
+        assert_eq!(res.len(), 2);
+        assert_eq!((res[0].0.len(), res[0].1.len()), (2, 1));
+        assert_eq!((res[1].0.len(), res[1].1.len()), (1, 2));
+    }
+}
@@ -0,0 +1,126 @@
+//! A priority queue for graph searches where every edge has a small
+//! integer cost.
+//!
+//! This is Dial's algorithm. Dijkstra's algorithm always pops
+//! vertices in increasing distance order, and a vertex is only ever
+//! pushed with the distance of the vertex being visited plus the
+//! cost of one edge. Every distance in the queue is therefore within
+//! `max_cost` of the last distance popped, so we can store a bucket
+//! per distance in a circular buffer.
+
+/// A min-priority queue where items must be pushed with a priority
+/// between the last priority popped and that plus `max_cost`.
+pub(crate) struct BucketQueue<T> {
+    buckets: Vec<Vec<T>>,
+    /// The priority of the bucket that we're currently popping from.
+    current: u32,
+    len: usize,
+}
+
+impl<T> BucketQueue<T> {
+    pub(crate) fn new(max_cost: u32) -> Self {
+        Self {
+            buckets: (0..=max_cost).map(|_| vec![]).collect(),
+            current: 0,
+            len: 0,
+        }
+    }
+
+    pub(crate) fn push(&mut self, priority: u32, item: T) {
+        debug_assert!(
+            priority >= self.current && priority - self.current < self.buckets.len() as u32,
+            "Priority {} is outside the range of the queue (currently at {})",
+            priority,
+            self.current
+        );
+
+        let num_buckets = self.buckets.len();
+        self.buckets[priority as usize % num_buckets].push(item);
+        self.len += 1;
+    }
+
+    /// Remove an item with the lowest priority. Items with the same
+    /// priority are popped in last-in first-out order.
+    ///
+    /// A radix heap orders items with the same priority differently,
+    /// so the graph search may find a different route of the same
+    /// cost when vertices tie.
+    pub(crate) fn pop(&mut self) -> Option<(u32, T)> {
+        if self.len == 0 {
+            return None;
+        }
+
+        let num_buckets = self.buckets.len();
+        loop {
+            if let Some(item) = self.buckets[self.current as usize % num_buckets].pop() {
+                self.len -= 1;
+                return Some((self.current, item));
+            }
+            self.current += 1;
+        }
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.len
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_pop_in_priority_order() {
+        let mut queue = BucketQueue::new(10);
+        queue.push(3, "c");
+        queue.push(0, "a");
+        queue.push(10, "d");
+        queue.push(1, "b");
+
+        assert_eq!(queue.len(), 4);
+        assert_eq!(queue.pop(), Some((0, "a")));
+        assert_eq!(queue.pop(), Some((1, "b")));
+        assert_eq!(queue.pop(), Some((3, "c")));
+        assert_eq!(queue.pop(), Some((10, "d")));
+        assert_eq!(queue.pop(), None);
+    }
+
+    #[test]
+    fn test_same_priority_last_in_first_out() {
+        let mut queue = BucketQueue::new(10);
+        queue.push(2, "a");
+        queue.push(2, "b");
+        queue.push(1, "c");
+        queue.push(2, "d");
+
+        assert_eq!(queue.pop(), Some((1, "c")));
+        assert_eq!(queue.pop(), Some((2, "d")));
+        assert_eq!(queue.pop(), Some((2, "b")));
+        assert_eq!(queue.pop(), Some((2, "a")));
+    }
+
+    #[test]
+    fn test_wraps_around() {
+        let mut queue = BucketQueue::new(2);
+        queue.push(0, 0);
+
+        // Each pop allows pushing up to 2 further along, so we go
+        // around the buckets several times.
+        let mut popped = vec![];
+        while let Some((priority, item)) = queue.pop() {
+            popped.push((priority, item));
+            if priority < 10 {
+                queue.push(priority + 2, item + 1);
+                queue.push(priority + 1, item + 1);
+            }
+            if popped.len() > 5 {
+                break;
+            }
+        }
+
+        let priorities: Vec<u32> = popped.iter().map(|(priority, _)| *priority).collect();
+        assert_eq!(priorities, vec![0, 1, 2, 2, 3, 3]);
+    }
+}
@@ -1,21 +1,116 @@
 //! Implements Dijkstra's algorithm for shortest path, to find an
 //! optimal and readable diff between two ASTs.
 
-use std::{cmp::Reverse, env};
+#[cfg(any(test, feature = "radix-heap"))]
+use std::cmp::Reverse;
+use std::{env, time::Instant};
 
 use bumpalo::Bump;
 use itertools::Itertools;
+#[cfg(any(test, feature = "radix-heap"))]
 use radix_heap::RadixHeapMap;
 
 use crate::{
+    api::CancellationToken,
+    diff::bucket_queue::BucketQueue,
     diff::changes::ChangeMap,
-    diff::graph::{populate_change_map, set_neighbours, Edge, Vertex},
+    diff::graph::{populate_change_map, set_neighbours, Edge, Vertex, MAX_EDGE_COST},
     hash::DftHashMap,
     parse::syntax::Syntax,
+    profile,
 };
 
-#[derive(Debug)]
-pub(crate) struct ExceededGraphLimit {}
+/// How often to check for an interrupt, in vertices visited.
+/// Checking the time on every vertex would slow down the search.
+const INTERRUPT_CHECK_INTERVAL: usize = 1024;
+
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum GraphSearchError {
+    /// The graph exceeded the graph limit.
+    ExceededGraphLimit,
+    /// The search didn't finish before the deadline.
+    TimedOut,
+    /// The diff was cancelled, see [CancellationToken].
+    Cancelled,
+}
+
+/// The reasons to give up on a search before it finishes.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Interrupt {
+    /// When `--timeout` runs out.
+    pub(crate) deadline: Option<Instant>,
+    pub(crate) cancel: Option<CancellationToken>,
+}
+
+impl Interrupt {
+    pub(crate) fn is_cancelled(&self) -> bool {
+        self.cancel
+            .as_ref()
+            .map_or(false, |cancel| cancel.is_cancelled())
+    }
+
+    /// Whether the search should stop now, and why.
+    pub(crate) fn check(&self) -> Result<(), GraphSearchError> {
+        if self.is_cancelled() {
+            return Err(GraphSearchError::Cancelled);
+        }
+        match self.deadline {
+            Some(deadline) if Instant::now() >= deadline => Err(GraphSearchError::TimedOut),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// The priority queue operations used by the search, so we can
+/// compare different queue implementations.
+trait SearchQueue<T> {
+    /// Add `item`, which is `distance` from the start vertex.
+    fn push(&mut self, distance: u32, item: T);
+    /// Remove an item with the shortest distance.
+    fn pop(&mut self) -> Option<(u32, T)>;
+    fn len(&self) -> usize;
+}
+
+impl<T> SearchQueue<T> for BucketQueue<T> {
+    fn push(&mut self, distance: u32, item: T) {
+        BucketQueue::push(self, distance, item);
+    }
+
+    fn pop(&mut self) -> Option<(u32, T)> {
+        BucketQueue::pop(self)
+    }
+
+    fn len(&self) -> usize {
+        BucketQueue::len(self)
+    }
+}
+
+// RadixHeapMap is a max-heap, so wrap distances with Reverse to flip
+// comparisons.
+#[cfg(any(test, feature = "radix-heap"))]
+impl<T> SearchQueue<T> for RadixHeapMap<Reverse<u32>, T> {
+    fn push(&mut self, distance: u32, item: T) {
+        RadixHeapMap::push(self, Reverse(distance), item);
+    }
+
+    fn pop(&mut self) -> Option<(u32, T)> {
+        RadixHeapMap::pop(self).map(|(Reverse(distance), item)| (distance, item))
+    }
+
+    fn len(&self) -> usize {
+        RadixHeapMap::len(self)
+    }
+}
+
+#[cfg(not(feature = "radix-heap"))]
+fn new_queue<T>() -> BucketQueue<T> {
+    BucketQueue::new(MAX_EDGE_COST)
+}
+
+#[cfg(feature = "radix-heap")]
+fn new_queue<T>() -> RadixHeapMap<Reverse<u32>, T> {
+    RadixHeapMap::new()
+}
 
 /// Return the shortest route from `start` to the end vertex.
 fn shortest_vertex_path<'s, 'b>(
@@ -23,24 +118,45 @@ fn shortest_vertex_path<'s, 'b>(
     vertex_arena: &'b Bump,
     size_hint: usize,
     graph_limit: usize,
-) -> Result<Vec<&'b Vertex<'s, 'b>>, ExceededGraphLimit> {
-    // We want to visit nodes with the shortest distance first, but
-    // RadixHeapMap is a max-heap. Ensure nodes are wrapped with
-    // Reverse to flip comparisons.
-    let mut heap: RadixHeapMap<Reverse<_>, &'b Vertex<'s, 'b>> = RadixHeapMap::new();
-
-    heap.push(Reverse(0), start);
+    interrupt: &Interrupt,
+    mut heap: impl SearchQueue<&'b Vertex<'s, 'b>>,
+) -> Result<Vec<&'b Vertex<'s, 'b>>, GraphSearchError> {
+    heap.push(0, start);
 
     let mut seen = DftHashMap::default();
     seen.reserve(size_hint);
 
+    let mut num_visited: usize = 0;
     let end: &'b Vertex<'s, 'b> = loop {
         match heap.pop() {
-            Some((Reverse(distance), current)) => {
+            Some((distance, current)) => {
                 if current.is_end() {
                     break current;
                 }
 
+                // We don't update the distance of vertices already on
+                // the heap when we find a shorter route, we push them
+                // again. Skip the entries for the longer routes.
+                //
+                // This only changes the number of vertices visited,
+                // not when we reach the graph limit: that counts the
+                // vertices in `seen`, and visiting a vertex again adds
+                // nothing, as its neighbours are already set.
+                if let Some((shortest_distance, _)) = current.predecessor.get() {
+                    if distance > shortest_distance {
+                        continue;
+                    }
+                }
+
+                if num_visited % INTERRUPT_CHECK_INTERVAL == 0 {
+                    if let Err(e) = interrupt.check() {
+                        info!("Stopped after visiting {} vertices: {:?}", num_visited, e);
+                        profile::record_vertices_explored(num_visited);
+                        return Err(e);
+                    }
+                }
+                num_visited += 1;
+
                 set_neighbours(current, vertex_arena, &mut seen);
                 for neighbour in *current.neighbours.borrow().as_ref().unwrap() {
                     let (edge, next) = neighbour;
@@ -53,7 +169,7 @@ fn shortest_vertex_path<'s, 'b>(
 
                     if found_shorter_route {
                         next.predecessor.replace(Some((distance_to_next, current)));
-                        heap.push(Reverse(distance_to_next), next);
+                        heap.push(distance_to_next, next);
                     }
                 }
 
@@ -62,7 +178,8 @@ fn shortest_vertex_path<'s, 'b>(
                         "Reached graph limit, arena consumed {}",
                         humansize::format_size(vertex_arena.allocated_bytes(), humansize::BINARY),
                     );
-                    return Err(ExceededGraphLimit {});
+                    profile::record_vertices_explored(num_visited);
+                    return Err(GraphSearchError::ExceededGraphLimit);
                 }
             }
             None => panic!("Ran out of graph nodes before reaching end"),
@@ -85,6 +202,9 @@ fn shortest_vertex_path<'s, 'b>(
     }
 
     vertex_route.reverse();
+
+    profile::record_vertices_explored(num_visited);
+    profile::record_route_length(vertex_route.len());
     Ok(vertex_route)
 }
 
@@ -117,9 +237,17 @@ fn shortest_path<'s, 'b>(
     vertex_arena: &'b Bump,
     size_hint: usize,
     graph_limit: usize,
-) -> Result<Vec<(Edge, &'b Vertex<'s, 'b>)>, ExceededGraphLimit> {
+    interrupt: &Interrupt,
+) -> Result<Vec<(Edge, &'b Vertex<'s, 'b>)>, GraphSearchError> {
     let start: &'b Vertex<'s, 'b> = vertex_arena.alloc(start);
-    let vertex_path = shortest_vertex_path(start, vertex_arena, size_hint, graph_limit)?;
+    let vertex_path = shortest_vertex_path(
+        start,
+        vertex_arena,
+        size_hint,
+        graph_limit,
+        interrupt,
+        new_queue(),
+    )?;
     Ok(shortest_path_with_edges(&vertex_path))
 }
 
@@ -191,7 +319,8 @@ pub(crate) fn mark_syntax<'a>(
     rhs_syntax: Option<&'a Syntax<'a>>,
     change_map: &mut ChangeMap<'a>,
     graph_limit: usize,
-) -> Result<(), ExceededGraphLimit> {
+    interrupt: &Interrupt,
+) -> Result<(), GraphSearchError> {
     let lhs_node_count = node_count(lhs_syntax) as usize;
     let rhs_node_count = node_count(rhs_syntax) as usize;
     info!(
@@ -216,7 +345,7 @@ pub(crate) fn mark_syntax<'a>(
     let start = Vertex::new(lhs_syntax, rhs_syntax);
     let vertex_arena = Bump::new();
 
-    let route = shortest_path(start, &vertex_arena, size_hint, graph_limit)?;
+    let route = shortest_path(start, &vertex_arena, size_hint, graph_limit, interrupt)?;
 
     let print_length = if env::var("DFT_VERBOSE").is_ok() {
         50
@@ -249,6 +378,13 @@ pub(crate) fn mark_syntax<'a>(
 
 #[cfg(test)]
 mod tests {
+    use std::{
+        cell::RefCell,
+        collections::HashSet,
+        fs,
+        path::{Path, PathBuf},
+    };
+
     use itertools::Itertools;
     use line_numbers::SingleLineSpan;
     use typed_arena::Arena;
@@ -258,6 +394,8 @@ mod tests {
         diff::changes::ChangeKind,
         diff::graph::Edge::*,
         options::DEFAULT_GRAPH_LIMIT,
+        parse::guess_language,
+        parse::tree_sitter_parser::{from_language, parse},
         syntax::{init_all_info, AtomKind},
     };
 
@@ -280,7 +418,14 @@ mod tests {
 
         let start = Vertex::new(Some(lhs), Some(rhs));
         let vertex_arena = Bump::new();
-        let route = shortest_path(start, &vertex_arena, 0, DEFAULT_GRAPH_LIMIT).unwrap();
+        let route = shortest_path(
+            start,
+            &vertex_arena,
+            0,
+            DEFAULT_GRAPH_LIMIT,
+            &Interrupt::default(),
+        )
+        .unwrap();
 
         let actions = route.iter().map(|(action, _)| *action).collect_vec();
         assert_eq!(
@@ -322,7 +467,14 @@ mod tests {
 
         let start = Vertex::new(lhs.get(0).copied(), rhs.get(0).copied());
         let vertex_arena = Bump::new();
-        let route = shortest_path(start, &vertex_arena, 0, DEFAULT_GRAPH_LIMIT).unwrap();
+        let route = shortest_path(
+            start,
+            &vertex_arena,
+            0,
+            DEFAULT_GRAPH_LIMIT,
+            &Interrupt::default(),
+        )
+        .unwrap();
 
         let actions = route.iter().map(|(action, _)| *action).collect_vec();
         assert_eq!(
@@ -364,7 +516,14 @@ mod tests {
 
         let start = Vertex::new(lhs.get(0).copied(), rhs.get(0).copied());
         let vertex_arena = Bump::new();
-        let route = shortest_path(start, &vertex_arena, 0, DEFAULT_GRAPH_LIMIT).unwrap();
+        let route = shortest_path(
+            start,
+            &vertex_arena,
+            0,
+            DEFAULT_GRAPH_LIMIT,
+            &Interrupt::default(),
+        )
+        .unwrap();
 
         let actions = route.iter().map(|(action, _)| *action).collect_vec();
         assert_eq!(
@@ -410,7 +569,14 @@ mod tests {
 
         let start = Vertex::new(lhs.get(0).copied(), rhs.get(0).copied());
         let vertex_arena = Bump::new();
-        let route = shortest_path(start, &vertex_arena, 0, DEFAULT_GRAPH_LIMIT).unwrap();
+        let route = shortest_path(
+            start,
+            &vertex_arena,
+            0,
+            DEFAULT_GRAPH_LIMIT,
+            &Interrupt::default(),
+        )
+        .unwrap();
 
         let actions = route.iter().map(|(action, _)| *action).collect_vec();
         assert_eq!(
@@ -451,7 +617,14 @@ mod tests {
 
         let start = Vertex::new(lhs.get(0).copied(), rhs.get(0).copied());
         let vertex_arena = Bump::new();
-        let route = shortest_path(start, &vertex_arena, 0, DEFAULT_GRAPH_LIMIT).unwrap();
+        let route = shortest_path(
+            start,
+            &vertex_arena,
+            0,
+            DEFAULT_GRAPH_LIMIT,
+            &Interrupt::default(),
+        )
+        .unwrap();
 
         let actions = route.iter().map(|(action, _)| *action).collect_vec();
         assert_eq!(
@@ -462,6 +635,47 @@ mod tests {
         );
     }
 
+    #[test]
+    fn replace_comment_indentation_only() {
+        let arena = Arena::new();
+
+        let lhs = vec![Syntax::new_atom(
+            &arena,
+            pos_helper(1),
+            "/* foo\n   bar */",
+            AtomKind::Comment,
+        )];
+
+        let rhs = vec![Syntax::new_atom(
+            &arena,
+            pos_helper(1),
+            "/* foo\n bar */",
+            AtomKind::Comment,
+        )];
+        init_all_info(&lhs, &rhs);
+
+        // The comments only differ in indentation, so they have the
+        // same content ID, but replacing one with the other is still
+        // a change.
+        let vertex_arena = Bump::new();
+        let start = vertex_arena.alloc(Vertex::new(lhs.get(0).copied(), rhs.get(0).copied()));
+        set_neighbours(start, &vertex_arena, &mut DftHashMap::default());
+
+        let edges = start
+            .neighbours
+            .borrow()
+            .unwrap()
+            .iter()
+            .map(|(edge, _)| *edge)
+            .collect_vec();
+        assert!(edges
+            .iter()
+            .any(|edge| matches!(edge, UnchangedNode { .. })));
+        assert!(edges
+            .iter()
+            .any(|edge| matches!(edge, ReplacedComment { .. })));
+    }
+
     #[test]
     fn replace_very_different_comment() {
         let arena = Arena::new();
@@ -483,7 +697,14 @@ mod tests {
 
         let start = Vertex::new(lhs.get(0).copied(), rhs.get(0).copied());
         let vertex_arena = Bump::new();
-        let route = shortest_path(start, &vertex_arena, 0, DEFAULT_GRAPH_LIMIT).unwrap();
+        let route = shortest_path(
+            start,
+            &vertex_arena,
+            0,
+            DEFAULT_GRAPH_LIMIT,
+            &Interrupt::default(),
+        )
+        .unwrap();
 
         let actions = route.iter().map(|(action, _)| *action).collect_vec();
         assert_eq!(
@@ -523,7 +744,14 @@ mod tests {
 
         let start = Vertex::new(lhs.get(0).copied(), rhs.get(0).copied());
         let vertex_arena = Bump::new();
-        let route = shortest_path(start, &vertex_arena, 0, DEFAULT_GRAPH_LIMIT).unwrap();
+        let route = shortest_path(
+            start,
+            &vertex_arena,
+            0,
+            DEFAULT_GRAPH_LIMIT,
+            &Interrupt::default(),
+        )
+        .unwrap();
 
         let actions = route.iter().map(|(action, _)| *action).collect_vec();
         assert_eq!(
@@ -545,7 +773,14 @@ mod tests {
         init_all_info(&[lhs], &[rhs]);
 
         let mut change_map = ChangeMap::default();
-        mark_syntax(Some(lhs), Some(rhs), &mut change_map, DEFAULT_GRAPH_LIMIT).unwrap();
+        mark_syntax(
+            Some(lhs),
+            Some(rhs),
+            &mut change_map,
+            DEFAULT_GRAPH_LIMIT,
+            &Interrupt::default(),
+        )
+        .unwrap();
 
         assert_eq!(change_map.get(lhs), Some(ChangeKind::Unchanged(rhs)));
         assert_eq!(change_map.get(rhs), Some(ChangeKind::Unchanged(lhs)));
@@ -559,8 +794,316 @@ mod tests {
         init_all_info(&[lhs], &[rhs]);
 
         let mut change_map = ChangeMap::default();
-        mark_syntax(Some(lhs), Some(rhs), &mut change_map, DEFAULT_GRAPH_LIMIT).unwrap();
+        mark_syntax(
+            Some(lhs),
+            Some(rhs),
+            &mut change_map,
+            DEFAULT_GRAPH_LIMIT,
+            &Interrupt::default(),
+        )
+        .unwrap();
         assert_eq!(change_map.get(lhs), Some(ChangeKind::Novel));
         assert_eq!(change_map.get(rhs), Some(ChangeKind::Novel));
     }
+
+    #[test]
+    fn mark_syntax_past_deadline() {
+        let arena = Arena::new();
+        let lhs = Syntax::new_atom(&arena, pos_helper(1), "foo", AtomKind::Normal);
+        let rhs = Syntax::new_atom(&arena, pos_helper(1), "bar", AtomKind::Normal);
+        init_all_info(&[lhs], &[rhs]);
+
+        let mut change_map = ChangeMap::default();
+        let res = mark_syntax(
+            Some(lhs),
+            Some(rhs),
+            &mut change_map,
+            DEFAULT_GRAPH_LIMIT,
+            &Interrupt {
+                deadline: Some(Instant::now()),
+                cancel: None,
+            },
+        );
+        assert_eq!(res, Err(GraphSearchError::TimedOut));
+    }
+
+    #[test]
+    fn mark_syntax_cancelled() {
+        let arena = Arena::new();
+        let lhs = Syntax::new_atom(&arena, pos_helper(1), "foo", AtomKind::Normal);
+        let rhs = Syntax::new_atom(&arena, pos_helper(1), "bar", AtomKind::Normal);
+        init_all_info(&[lhs], &[rhs]);
+
+        let cancel = CancellationToken::new();
+        cancel.cancel();
+        let mut change_map = ChangeMap::default();
+        let res = mark_syntax(
+            Some(lhs),
+            Some(rhs),
+            &mut change_map,
+            DEFAULT_GRAPH_LIMIT,
+            &Interrupt {
+                deadline: Some(Instant::now()),
+                cancel: Some(cancel),
+            },
+        );
+        assert_eq!(res, Err(GraphSearchError::Cancelled));
+    }
+
+    #[test]
+    fn max_edge_cost() {
+        // The most expensive edge of each kind.
+        let edges = [
+            UnchangedNode {
+                depth_difference: u32::MAX - 1,
+                probably_punctuation: true,
+            },
+            EnterUnchangedDelimiter {
+                depth_difference: u32::MAX - 1,
+            },
+            ReplacedComment { levenshtein_pct: 0 },
+            ReplacedString { levenshtein_pct: 0 },
+            NovelAtomLHS {},
+            NovelAtomRHS {},
+            EnterNovelDelimiterLHS {},
+            EnterNovelDelimiterRHS {},
+        ];
+
+        // The queue has a bucket for each cost up to MAX_EDGE_COST,
+        // so it shouldn't be any larger than needed either.
+        assert_eq!(
+            edges.iter().map(|edge| edge.cost()).max(),
+            Some(MAX_EDGE_COST)
+        );
+    }
+
+    /// A bucket queue that records every item popped.
+    struct RecordingQueue<'a, T> {
+        queue: BucketQueue<T>,
+        popped: &'a RefCell<Vec<T>>,
+    }
+
+    impl<'a, T: Copy> SearchQueue<T> for RecordingQueue<'a, T> {
+        fn push(&mut self, distance: u32, item: T) {
+            self.queue.push(distance, item);
+        }
+
+        fn pop(&mut self) -> Option<(u32, T)> {
+            let res = self.queue.pop();
+            if let Some((_, item)) = res {
+                self.popped.borrow_mut().push(item);
+            }
+            res
+        }
+
+        fn len(&self) -> usize {
+            self.queue.len()
+        }
+    }
+
+    /// The result of searching from `lhs` and `rhs` with a
+    /// [RecordingQueue], with the `--verbose --verbose` graph
+    /// statistics, the number of vertices seen, the number of items
+    /// popped and the number of distinct vertices popped.
+    fn recorded_search<'a>(
+        lhs: Option<&'a Syntax<'a>>,
+        rhs: Option<&'a Syntax<'a>>,
+        graph_limit: usize,
+    ) -> (Result<(), GraphSearchError>, String, usize, usize, usize) {
+        let vertex_arena = Bump::new();
+        let start = vertex_arena.alloc(Vertex::new(lhs, rhs));
+        let popped = RefCell::new(vec![]);
+        let queue = RecordingQueue {
+            queue: BucketQueue::new(MAX_EDGE_COST),
+            popped: &popped,
+        };
+
+        let profiler = profile::Profiler::start("recorded_search");
+        let res = shortest_vertex_path(
+            start,
+            &vertex_arena,
+            0,
+            graph_limit,
+            &Interrupt::default(),
+            queue,
+        )
+        .map(|_| ());
+        drop(profiler);
+        let stats = profile::take_file_profile("recorded_search")
+            .unwrap()
+            .format_stats();
+
+        let popped = popped.borrow();
+        // Every neighbour of a vertex we visited is in `seen`.
+        let num_seen = popped
+            .iter()
+            .flat_map(|v| (*v.neighbours.borrow()).unwrap_or(&[]).iter())
+            .map(|(_, neighbour)| *neighbour)
+            .collect::<HashSet<&Vertex>>()
+            .len();
+        let num_distinct = popped
+            .iter()
+            .map(|v| *v as *const Vertex)
+            .collect::<HashSet<_>>()
+            .len();
+        (res, stats, num_seen, popped.len(), num_distinct)
+    }
+
+    #[test]
+    fn stale_queue_entries() {
+        let arena = Arena::new();
+        let config = from_language(guess_language::Language::JavaScript);
+
+        // From sample_files/simple_1.js.
+        let lhs = parse(
+            &arena,
+            "const React = require('react');\nconsole.log('hello world');\n",
+            &config,
+            false,
+        );
+        let rhs = parse(
+            &arena,
+            "import React, {useState} from 'react';\nconsole.log('hello world');\n",
+            &config,
+            false,
+        );
+        init_all_info(&lhs, &rhs);
+        let (lhs, rhs) = (lhs.first().copied(), rhs.first().copied());
+
+        let (res, stats, num_seen, num_popped, num_distinct) =
+            recorded_search(lhs, rhs, DEFAULT_GRAPH_LIMIT);
+        assert_eq!(res, Ok(()));
+        // Some vertices were pushed again with a shorter distance.
+        assert!(num_popped > num_distinct);
+        // Each vertex is only visited once. We stop at the end
+        // vertex, so it isn't counted.
+        assert!(stats.contains(&format!("{} vertices explored", num_distinct - 1)));
+
+        // The graph limit is the number of vertices seen, regardless
+        // of how often they were pushed.
+        let (res, ..) = recorded_search(lhs, rhs, num_seen);
+        assert_eq!(res, Ok(()));
+        let (res, ..) = recorded_search(lhs, rhs, num_seen - 1);
+        assert_eq!(res, Err(GraphSearchError::ExceededGraphLimit));
+    }
+
+    /// The total cost of the shortest route between `lhs` and `rhs`,
+    /// using either a radix heap or a bucket queue for the search.
+    fn route_cost<'a>(
+        lhs: Option<&'a Syntax<'a>>,
+        rhs: Option<&'a Syntax<'a>>,
+        use_radix_heap: bool,
+    ) -> Result<u32, GraphSearchError> {
+        let vertex_arena = Bump::new();
+        let start = vertex_arena.alloc(Vertex::new(lhs, rhs));
+
+        let route = if use_radix_heap {
+            shortest_vertex_path(
+                start,
+                &vertex_arena,
+                0,
+                DEFAULT_GRAPH_LIMIT,
+                &Interrupt::default(),
+                RadixHeapMap::new(),
+            )?
+        } else {
+            shortest_vertex_path(
+                start,
+                &vertex_arena,
+                0,
+                DEFAULT_GRAPH_LIMIT,
+                &Interrupt::default(),
+                BucketQueue::new(MAX_EDGE_COST),
+            )?
+        };
+
+        Ok(shortest_path_with_edges(&route)
+            .iter()
+            .map(|(edge, _)| edge.cost())
+            .sum())
+    }
+
+    #[test]
+    fn bucket_queue_same_cost_as_radix_heap() {
+        let arena = Arena::new();
+        let config = from_language(guess_language::Language::EmacsLisp);
+
+        let lhs = parse(&arena, "(a b (c d) \"x\" ; foo\n e)", &config, false);
+        let rhs = parse(&arena, "(a (b c) d \"y\" ; bar\n f e)", &config, false);
+        init_all_info(&lhs, &rhs);
+
+        assert_eq!(
+            route_cost(lhs.first().copied(), rhs.first().copied(), false),
+            route_cost(lhs.first().copied(), rhs.first().copied(), true)
+        );
+    }
+
+    /// Check that the bucket queue finds routes with the same cost as
+    /// the radix heap, for `lhs_path` and the corresponding `_2`
+    /// file. Returns false if they aren't a pair of files in a
+    /// language we know.
+    fn check_same_cost_as_radix_heap(lhs_path: &Path) -> bool {
+        let file_name = lhs_path.file_name().unwrap().to_string_lossy();
+        let rhs_path = lhs_path.with_file_name(file_name.replacen("_1", "_2", 1));
+
+        let (lhs_src, rhs_src) = match (fs::read_to_string(lhs_path), fs::read_to_string(&rhs_path))
+        {
+            (Ok(lhs_src), Ok(rhs_src)) => (lhs_src, rhs_src),
+            _ => return false,
+        };
+        let language = match guess_language::guess(lhs_path, &lhs_src, &[]) {
+            Some(language) => language,
+            None => return false,
+        };
+        let config = from_language(language);
+
+        let arena = Arena::new();
+        let lhs = parse(&arena, &lhs_src, &config, false);
+        let rhs = parse(&arena, &rhs_src, &config, false);
+        init_all_info(&lhs, &rhs);
+
+        assert_eq!(
+            route_cost(lhs.first().copied(), rhs.first().copied(), false),
+            route_cost(lhs.first().copied(), rhs.first().copied(), true),
+            "Different route costs for {}",
+            lhs_path.display()
+        );
+        true
+    }
+
+    #[test]
+    fn bucket_queue_same_cost_as_radix_heap_small_sample_files() {
+        // Small files with replacements, sliders and comments.
+        for name in [
+            "comma_and_comment_1.js",
+            "comments_1.rs",
+            "contiguous_1.js",
+            "nested_slider_1.rs",
+            "prefer_outer_1.el",
+            "string_subwords_1.el",
+        ] {
+            let lhs_path = Path::new("sample_files").join(name);
+            assert!(check_same_cost_as_radix_heap(&lhs_path), "{}", name);
+        }
+    }
+
+    #[test]
+    // Diffing every sample file is slow in debug builds, so make it
+    // opt-in. Run with sample_files/bench_dijkstra.sh.
+    #[ignore]
+    fn bucket_queue_same_cost_as_radix_heap_sample_files() {
+        let mut lhs_paths: Vec<PathBuf> = fs::read_dir("sample_files")
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| {
+                path.file_name()
+                    .map_or(false, |name| name.to_string_lossy().contains("_1."))
+            })
+            .collect();
+        lhs_paths.sort();
+
+        for lhs_path in lhs_paths {
+            check_same_cost_as_radix_heap(&lhs_path);
+        }
+    }
 }
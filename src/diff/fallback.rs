@@ -0,0 +1,456 @@
+//! Diffing when the structural graph is too large.
+//!
+//! If the Dijkstra search for a section exceeds the graph limit, we
+//! pair up the top-level nodes of the section and diff each pair
+//! separately. Pairs that are still too large are split again when
+//! they have matching delimiters, and otherwise fall back to a diff of
+//! their tokens. This only loses structural accuracy for the subtrees
+//! that are too large, rather than for the whole file.
+
+use line_numbers::LineNumber;
+
+use crate::{
+    diff::{
+        changes::{insert_deep_novel, ChangeKind, ChangeMap},
+        dijkstra::{mark_syntax, GraphSearchError, Interrupt},
+        myers_diff,
+    },
+    hash::DftHashMap,
+    parse::syntax::{init_next_prev, Syntax, SyntaxId},
+};
+
+/// The number of atoms at the start of a node that we use to decide
+/// whether two top-level nodes correspond, e.g. `fn` and `foo`.
+const KEY_ATOMS: usize = 2;
+
+fn push_leading_atoms<'a>(node: &'a Syntax<'a>, atoms: &mut Vec<&'a str>) {
+    if atoms.len() >= KEY_ATOMS {
+        return;
+    }
+
+    match node {
+        Syntax::List { children, .. } => {
+            for child in children {
+                push_leading_atoms(child, atoms);
+            }
+        }
+        Syntax::Atom { content, .. } => atoms.push(content),
+    }
+}
+
+/// A key for `node` that's equal for nodes that probably correspond
+/// to each other, such as two versions of the same function.
+fn node_key<'a>(node: &'a Syntax<'a>) -> (&'a str, Vec<&'a str>) {
+    let mut atoms = vec![];
+    push_leading_atoms(node, &mut atoms);
+
+    match node {
+        Syntax::List { open_content, .. } => (open_content, atoms),
+        Syntax::Atom { .. } => ("", atoms),
+    }
+}
+
+/// Pair up `lhs` and `rhs` nodes. Nodes with the same key are paired,
+/// and the remaining nodes are paired by position between each pair
+/// with the same key.
+fn pair_nodes<'a>(
+    lhs: &[&'a Syntax<'a>],
+    rhs: &[&'a Syntax<'a>],
+) -> Vec<(Option<&'a Syntax<'a>>, Option<&'a Syntax<'a>>)> {
+    let lhs_keys: Vec<_> = lhs.iter().map(|node| node_key(node)).collect();
+    let rhs_keys: Vec<_> = rhs.iter().map(|node| node_key(node)).collect();
+
+    let mut pairs = vec![];
+    let mut lhs_unpaired = vec![];
+    let mut rhs_unpaired = vec![];
+    let flush =
+        |lhs_unpaired: &mut Vec<&'a Syntax<'a>>,
+         rhs_unpaired: &mut Vec<&'a Syntax<'a>>,
+         pairs: &mut Vec<(Option<&'a Syntax<'a>>, Option<&'a Syntax<'a>>)>| {
+            let num_paired = std::cmp::min(lhs_unpaired.len(), rhs_unpaired.len());
+            for i in 0..std::cmp::max(lhs_unpaired.len(), rhs_unpaired.len()) {
+                if i < num_paired {
+                    pairs.push((Some(lhs_unpaired[i]), Some(rhs_unpaired[i])));
+                } else {
+                    pairs.push((lhs_unpaired.get(i).copied(), rhs_unpaired.get(i).copied()));
+                }
+            }
+            lhs_unpaired.clear();
+            rhs_unpaired.clear();
+        };
+
+    let mut lhs_i = 0;
+    let mut rhs_i = 0;
+    for diff_res in myers_diff::slice(&lhs_keys, &rhs_keys) {
+        match diff_res {
+            myers_diff::DiffResult::Left(_) => {
+                lhs_unpaired.push(lhs[lhs_i]);
+                lhs_i += 1;
+            }
+            myers_diff::DiffResult::Right(_) => {
+                rhs_unpaired.push(rhs[rhs_i]);
+                rhs_i += 1;
+            }
+            myers_diff::DiffResult::Both(_, _) => {
+                flush(&mut lhs_unpaired, &mut rhs_unpaired, &mut pairs);
+                pairs.push((Some(lhs[lhs_i]), Some(rhs[rhs_i])));
+                lhs_i += 1;
+                rhs_i += 1;
+            }
+        }
+    }
+    flush(&mut lhs_unpaired, &mut rhs_unpaired, &mut pairs);
+
+    pairs
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TokenPart {
+    Atom,
+    Open,
+    Close,
+}
+
+/// A token in a subtree that's diffed without its structure.
+#[derive(Debug, Clone)]
+struct Token<'a> {
+    node: &'a Syntax<'a>,
+    content: &'a str,
+    part: TokenPart,
+}
+
+impl<'a> PartialEq for Token<'a> {
+    fn eq(&self, other: &Self) -> bool {
+        self.part == other.part && self.content == other.content
+    }
+}
+
+fn push_tokens<'a>(node: &'a Syntax<'a>, tokens: &mut Vec<Token<'a>>) {
+    match node {
+        Syntax::List {
+            open_content,
+            children,
+            close_content,
+            ..
+        } => {
+            tokens.push(Token {
+                node,
+                content: open_content,
+                part: TokenPart::Open,
+            });
+            for child in children {
+                push_tokens(child, tokens);
+            }
+            tokens.push(Token {
+                node,
+                content: close_content,
+                part: TokenPart::Close,
+            });
+        }
+        Syntax::Atom { content, .. } => tokens.push(Token {
+            node,
+            content,
+            part: TokenPart::Atom,
+        }),
+    }
+}
+
+/// Mark `lhs` and `rhs` by diffing their tokens as flat sequences.
+/// This is much cheaper than a structural diff, but can't tell when a
+/// delimiter has been added around existing code.
+fn mark_by_tokens<'a>(lhs: &'a Syntax<'a>, rhs: &'a Syntax<'a>, change_map: &mut ChangeMap<'a>) {
+    insert_deep_novel(lhs, change_map);
+    insert_deep_novel(rhs, change_map);
+
+    let mut lhs_tokens = vec![];
+    push_tokens(lhs, &mut lhs_tokens);
+    let mut rhs_tokens = vec![];
+    push_tokens(rhs, &mut rhs_tokens);
+
+    // The opposite list for each list whose delimiters matched.
+    let mut open_matches: DftHashMap<SyntaxId, &'a Syntax<'a>> = DftHashMap::default();
+    let mut close_matches: Vec<(&'a Syntax<'a>, &'a Syntax<'a>)> = vec![];
+
+    for diff_res in myers_diff::slice(&lhs_tokens, &rhs_tokens) {
+        if let myers_diff::DiffResult::Both(lhs_token, rhs_token) = diff_res {
+            match lhs_token.part {
+                TokenPart::Atom => {
+                    change_map.insert(lhs_token.node, ChangeKind::Unchanged(rhs_token.node));
+                    change_map.insert(rhs_token.node, ChangeKind::Unchanged(lhs_token.node));
+                }
+                TokenPart::Open => {
+                    open_matches.insert(lhs_token.node.id(), rhs_token.node);
+                }
+                TokenPart::Close => close_matches.push((lhs_token.node, rhs_token.node)),
+            }
+        }
+    }
+
+    // Only treat a list as unchanged if both its delimiters matched
+    // the same list.
+    for (lhs_list, rhs_list) in close_matches {
+        if open_matches
+            .get(&lhs_list.id())
+            .map_or(false, |open_match| open_match.id() == rhs_list.id())
+        {
+            change_map.insert(lhs_list, ChangeKind::Unchanged(rhs_list));
+            change_map.insert(rhs_list, ChangeKind::Unchanged(lhs_list));
+        }
+    }
+}
+
+/// The first and last line of `node`.
+fn line_range(node: &Syntax) -> Option<(LineNumber, LineNumber)> {
+    let (first, last) = match node {
+        Syntax::List {
+            open_position,
+            close_position,
+            ..
+        } => (open_position.first(), close_position.last()),
+        Syntax::Atom { position, .. } => (position.first(), position.last()),
+    };
+    Some((first?.line, last?.line))
+}
+
+/// Mark `lhs` and `rhs` by structurally diffing each pair of
+/// corresponding nodes separately. Returns the RHS line ranges that
+/// were too large, so used a token diff.
+///
+/// Stops early if `interrupt` says so, leaving the remaining nodes
+/// unmarked.
+pub(crate) fn mark_syntax_by_subtree<'a>(
+    lhs: &[&'a Syntax<'a>],
+    rhs: &[&'a Syntax<'a>],
+    change_map: &mut ChangeMap<'a>,
+    graph_limit: usize,
+    interrupt: &Interrupt,
+) -> Result<Vec<(LineNumber, LineNumber)>, GraphSearchError> {
+    let mut token_diffed = vec![];
+    mark_pairs(
+        lhs,
+        rhs,
+        change_map,
+        graph_limit,
+        interrupt,
+        &mut token_diffed,
+    )?;
+    Ok(token_diffed)
+}
+
+fn mark_pairs<'a>(
+    lhs: &[&'a Syntax<'a>],
+    rhs: &[&'a Syntax<'a>],
+    change_map: &mut ChangeMap<'a>,
+    graph_limit: usize,
+    interrupt: &Interrupt,
+    token_diffed: &mut Vec<(LineNumber, LineNumber)>,
+) -> Result<(), GraphSearchError> {
+    for (lhs_node, rhs_node) in pair_nodes(lhs, rhs) {
+        let (lhs_node, rhs_node) = match (lhs_node, rhs_node) {
+            (Some(lhs_node), Some(rhs_node)) => (lhs_node, rhs_node),
+            (Some(node), None) | (None, Some(node)) => {
+                insert_deep_novel(node, change_map);
+                continue;
+            }
+            (None, None) => continue,
+        };
+
+        // Diff this pair on its own, rather than continuing to the
+        // following siblings.
+        init_next_prev(&[lhs_node]);
+        init_next_prev(&[rhs_node]);
+        match mark_syntax(
+            Some(lhs_node),
+            Some(rhs_node),
+            change_map,
+            graph_limit,
+            interrupt,
+        ) {
+            Ok(()) => continue,
+            Err(GraphSearchError::ExceededGraphLimit) => {}
+            Err(e) => return Err(e),
+        }
+
+        match (lhs_node, rhs_node) {
+            (
+                Syntax::List {
+                    open_content: lhs_open,
+                    children: lhs_children,
+                    close_content: lhs_close,
+                    ..
+                },
+                Syntax::List {
+                    open_content: rhs_open,
+                    children: rhs_children,
+                    close_content: rhs_close,
+                    ..
+                },
+            ) if lhs_open == rhs_open && lhs_close == rhs_close => {
+                change_map.insert(lhs_node, ChangeKind::Unchanged(rhs_node));
+                change_map.insert(rhs_node, ChangeKind::Unchanged(lhs_node));
+                mark_pairs(
+                    lhs_children,
+                    rhs_children,
+                    change_map,
+                    graph_limit,
+                    interrupt,
+                    token_diffed,
+                )?;
+            }
+            _ => {
+                mark_by_tokens(lhs_node, rhs_node, change_map);
+                if let Some(range) = line_range(rhs_node) {
+                    token_diffed.push(range);
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// A note for the file header describing which lines used a token
+/// diff, e.g. "lines 10-200 exceeded DFT_GRAPH_LIMIT, using a token
+/// diff".
+pub(crate) fn fallback_note(token_diffed: &[(LineNumber, LineNumber)]) -> Option<String> {
+    if token_diffed.is_empty() {
+        return None;
+    }
+
+    let ranges: Vec<String> = token_diffed
+        .iter()
+        .map(|(start, end)| {
+            if start == end {
+                start.display()
+            } else {
+                format!("{}-{}", start.display(), end.display())
+            }
+        })
+        .collect();
+    Some(format!(
+        "Line{} {} exceeded DFT_GRAPH_LIMIT, using a token diff.",
+        if token_diffed.len() == 1 && token_diffed[0].0 == token_diffed[0].1 {
+            ""
+        } else {
+            "s"
+        },
+        ranges.join(", ")
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use line_numbers::SingleLineSpan;
+    use pretty_assertions::assert_eq;
+    use typed_arena::Arena;
+
+    use super::*;
+    use crate::parse::syntax::{init_all_info, AtomKind};
+
+    fn atom<'a>(arena: &'a Arena<Syntax<'a>>, line: u32, content: &'a str) -> &'a Syntax<'a> {
+        Syntax::new_atom(
+            arena,
+            vec![SingleLineSpan {
+                line: line.into(),
+                start_col: 0,
+                end_col: content.len() as u32,
+            }],
+            content,
+            AtomKind::Normal,
+        )
+    }
+
+    fn list<'a>(
+        arena: &'a Arena<Syntax<'a>>,
+        line: u32,
+        children: Vec<&'a Syntax<'a>>,
+    ) -> &'a Syntax<'a> {
+        let pos = vec![SingleLineSpan {
+            line: line.into(),
+            start_col: 0,
+            end_col: 1,
+        }];
+        Syntax::new_list(arena, "(", pos.clone(), children, ")", pos)
+    }
+
+    #[test]
+    fn test_pair_nodes() {
+        let arena = Arena::new();
+        let lhs = vec![
+            list(
+                &arena,
+                0,
+                vec![atom(&arena, 0, "fn"), atom(&arena, 0, "foo")],
+            ),
+            list(
+                &arena,
+                1,
+                vec![atom(&arena, 1, "fn"), atom(&arena, 1, "bar")],
+            ),
+        ];
+        let rhs = vec![
+            list(
+                &arena,
+                0,
+                vec![atom(&arena, 0, "fn"), atom(&arena, 0, "new")],
+            ),
+            list(
+                &arena,
+                1,
+                vec![atom(&arena, 1, "fn"), atom(&arena, 1, "bar")],
+            ),
+        ];
+        init_all_info(&lhs, &rhs);
+
+        let pairs = pair_nodes(&lhs, &rhs);
+        assert_eq!(pairs.len(), 2);
+        assert_eq!(pairs[0].0.map(|node| node.id()), Some(lhs[0].id()));
+        assert_eq!(pairs[0].1.map(|node| node.id()), Some(rhs[0].id()));
+        assert_eq!(pairs[1].0.map(|node| node.id()), Some(lhs[1].id()));
+        assert_eq!(pairs[1].1.map(|node| node.id()), Some(rhs[1].id()));
+    }
+
+    #[test]
+    fn test_mark_by_tokens() {
+        let arena = Arena::new();
+        let lhs_a = atom(&arena, 0, "a");
+        let lhs_b = atom(&arena, 0, "b");
+        let rhs_a = atom(&arena, 0, "a");
+        let rhs_c = atom(&arena, 0, "c");
+        let lhs = list(&arena, 0, vec![lhs_a, lhs_b]);
+        let rhs = list(&arena, 0, vec![rhs_a, rhs_c]);
+        init_all_info(&[lhs], &[rhs]);
+
+        let mut change_map = ChangeMap::default();
+        mark_by_tokens(lhs, rhs, &mut change_map);
+
+        assert_eq!(change_map.get(lhs), Some(ChangeKind::Unchanged(rhs)));
+        assert_eq!(change_map.get(lhs_a), Some(ChangeKind::Unchanged(rhs_a)));
+        assert_eq!(change_map.get(lhs_b), Some(ChangeKind::Novel));
+        assert_eq!(change_map.get(rhs_c), Some(ChangeKind::Novel));
+    }
+
+    #[test]
+    fn test_mark_syntax_by_subtree_tiny_limit() {
+        let arena = Arena::new();
+        let lhs = vec![list(&arena, 0, vec![atom(&arena, 0, "a")])];
+        let rhs = vec![list(&arena, 3, vec![atom(&arena, 3, "b")])];
+        init_all_info(&lhs, &rhs);
+
+        let mut change_map = ChangeMap::default();
+        let token_diffed =
+            mark_syntax_by_subtree(&lhs, &rhs, &mut change_map, 0, &Interrupt::default()).unwrap();
+
+        assert_eq!(token_diffed, vec![(3.into(), 3.into())]);
+        assert_eq!(change_map.get(lhs[0]), Some(ChangeKind::Unchanged(rhs[0])));
+    }
+
+    #[test]
+    fn test_fallback_note() {
+        assert_eq!(fallback_note(&[]), None);
+        assert_eq!(
+            fallback_note(&[(9.into(), 199.into())]),
+            Some("Lines 10-200 exceeded DFT_GRAPH_LIMIT, using a token diff.".to_owned())
+        );
+    }
+}
@@ -315,9 +315,14 @@ pub(crate) enum Edge {
     EnterNovelDelimiterRHS {},
 }
 
+/// The highest cost of any edge, see `Edge::cost`. This is the
+/// cost of a `ReplacedComment` or `ReplacedString` with nothing in
+/// common.
+pub(crate) const MAX_EDGE_COST: u32 = 600;
+
 impl Edge {
     pub(crate) fn cost(self) -> u32 {
-        match self {
+        let cost = match self {
             // Matching nodes is always best.
             UnchangedNode {
                 depth_difference,
@@ -359,7 +364,14 @@ impl Edge {
             ReplacedComment { levenshtein_pct } | ReplacedString { levenshtein_pct } => {
                 500 + u32::from(100 - levenshtein_pct)
             }
-        }
+        };
+        // The bucket queue in the graph search relies on this.
+        debug_assert!(
+            cost <= MAX_EDGE_COST,
+            "{:?} costs more than MAX_EDGE_COST",
+            self
+        );
+        cost
     }
 }
 
@@ -418,7 +430,7 @@ fn allocate_if_new<'s, 'b>(
 /// accurately recognise punctuation in a language-agnostic way.
 fn looks_like_punctuation(node: &Syntax) -> bool {
     match node {
-        Syntax::Atom { content, .. } => content == "," || content == ";" || content == ".",
+        Syntax::Atom { content, .. } => *content == "," || *content == ";" || *content == ".",
         _ => false,
     }
 }
@@ -616,8 +628,11 @@ pub(crate) fn set_neighbours<'s, 'b>(
         ) = (lhs_syntax, rhs_syntax)
         {
             // Both sides are comments/both sides are strings and
-            // their content is reasonably similar.
-            if lhs_kind == rhs_kind && lhs_content != rhs_content {
+            // their content is reasonably similar. Compare interned
+            // atom IDs rather than strings. The content ID isn't
+            // suitable here, because it ignores the indentation of
+            // comments.
+            if lhs_kind == rhs_kind && lhs_syntax.atom_id() != rhs_syntax.atom_id() {
                 let levenshtein_pct =
                     (normalized_levenshtein(lhs_content, rhs_content) * 100.0).round() as u8;
                 let edge = if lhs_kind == &AtomKind::Comment {
@@ -0,0 +1,196 @@
+//! Graphviz output of the syntax trees after diffing, for debugging
+//! why difftastic matched (or didn't match) nodes.
+
+use std::collections::HashSet;
+
+use crate::{
+    diff::changes::{ChangeKind, ChangeMap},
+    parse::syntax::{Syntax, SyntaxId},
+};
+
+/// Labels longer than this are truncated, so huge string literals
+/// don't dominate the graph.
+const MAX_LABEL_CHARS: usize = 40;
+
+/// Escape `s` for use inside a double-quoted DOT string.
+fn escape_dot(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => {}
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn truncate_label(s: &str) -> String {
+    if s.chars().count() <= MAX_LABEL_CHARS {
+        return s.to_owned();
+    }
+
+    let mut truncated: String = s.chars().take(MAX_LABEL_CHARS).collect();
+    truncated.push_str("...");
+    truncated
+}
+
+fn node_label(node: &Syntax) -> String {
+    let label = match node {
+        Syntax::List {
+            open_content,
+            close_content,
+            ..
+        } => format!("{}...{}", open_content, close_content),
+        Syntax::Atom { content, .. } => content.to_string(),
+    };
+    escape_dot(&truncate_label(&label))
+}
+
+/// A DOT identifier for this node. Syntax IDs are unique across both
+/// sides, and are assigned in a deterministic order, so these are
+/// stable between runs.
+fn node_id(id: SyntaxId) -> String {
+    format!("n{}", id)
+}
+
+/// Write DOT nodes for `nodes` and their descendants, up to
+/// `max_depth`, and record the IDs of the nodes we wrote.
+fn write_nodes<'a>(
+    out: &mut String,
+    nodes: &[&'a Syntax<'a>],
+    change_map: &ChangeMap<'a>,
+    depth: usize,
+    max_depth: Option<usize>,
+    seen: &mut HashSet<SyntaxId>,
+) {
+    if let Some(max_depth) = max_depth {
+        if depth > max_depth {
+            return;
+        }
+    }
+
+    for node in nodes {
+        let style = match change_map.get(node) {
+            Some(ChangeKind::Novel) => ", color=red, penwidth=2",
+            Some(_) => "",
+            None => ", style=dashed",
+        };
+        let shape = match node {
+            Syntax::List { .. } => "ellipse",
+            Syntax::Atom { .. } => "box",
+        };
+
+        out.push_str(&format!(
+            "    {} [label=\"{}\", shape={}{}];\n",
+            node_id(node.id()),
+            node_label(node),
+            shape,
+            style
+        ));
+        seen.insert(node.id());
+
+        if let Syntax::List { children, .. } = node {
+            if max_depth.map_or(true, |max_depth| depth < max_depth) {
+                for child in children {
+                    out.push_str(&format!(
+                        "    {} -> {};\n",
+                        node_id(node.id()),
+                        node_id(child.id())
+                    ));
+                }
+            }
+            write_nodes(out, children, change_map, depth + 1, max_depth, seen);
+        }
+    }
+}
+
+/// Write edges from LHS nodes to the RHS nodes they were matched
+/// with, coloured by the kind of match.
+fn write_match_edges<'a>(
+    out: &mut String,
+    nodes: &[&'a Syntax<'a>],
+    change_map: &ChangeMap<'a>,
+    seen: &HashSet<SyntaxId>,
+) {
+    for node in nodes {
+        if !seen.contains(&node.id()) {
+            // Beyond the depth limit.
+            continue;
+        }
+
+        let opposite_and_color = match change_map.get(node) {
+            Some(ChangeKind::Unchanged(opposite)) => Some((opposite, "darkgreen")),
+            Some(ChangeKind::ReplacedComment(_, opposite)) => Some((opposite, "blue")),
+            Some(ChangeKind::ReplacedString(_, opposite)) => Some((opposite, "purple")),
+            Some(ChangeKind::Novel) | None => None,
+        };
+        if let Some((opposite, color)) = opposite_and_color {
+            if seen.contains(&opposite.id()) {
+                out.push_str(&format!(
+                    "  {} -> {} [color={}, style=dotted, constraint=false];\n",
+                    node_id(node.id()),
+                    node_id(opposite.id()),
+                    color
+                ));
+            }
+        }
+
+        if let Syntax::List { children, .. } = node {
+            write_match_edges(out, children, change_map, seen);
+        }
+    }
+}
+
+/// Render both syntax trees as a Graphviz digraph, with edges between
+/// matched nodes and novel nodes outlined in red.
+pub(crate) fn to_dot<'a>(
+    lhs_nodes: &[&'a Syntax<'a>],
+    rhs_nodes: &[&'a Syntax<'a>],
+    change_map: &ChangeMap<'a>,
+    max_depth: Option<usize>,
+) -> String {
+    let mut seen = HashSet::new();
+
+    let mut out = String::new();
+    out.push_str("digraph difftastic {\n");
+    out.push_str("  node [fontname=\"monospace\"];\n");
+
+    out.push_str("  subgraph cluster_lhs {\n");
+    out.push_str("    label=\"LHS\";\n");
+    write_nodes(&mut out, lhs_nodes, change_map, 0, max_depth, &mut seen);
+    out.push_str("  }\n");
+
+    out.push_str("  subgraph cluster_rhs {\n");
+    out.push_str("    label=\"RHS\";\n");
+    write_nodes(&mut out, rhs_nodes, change_map, 0, max_depth, &mut seen);
+    out.push_str("  }\n");
+
+    write_match_edges(&mut out, lhs_nodes, change_map, &seen);
+
+    out.push_str("}\n");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_escape_dot() {
+        assert_eq!(escape_dot("\"a\\b\"\n"), "\\\"a\\\\b\\\"\\n");
+    }
+
+    #[test]
+    fn test_truncate_label() {
+        assert_eq!(truncate_label("foo"), "foo");
+        assert_eq!(
+            truncate_label(&"x".repeat(50)),
+            format!("{}...", "x".repeat(MAX_LABEL_CHARS))
+        );
+    }
+}
@@ -1,6 +1,12 @@
+pub(crate) mod anchors;
+#[cfg_attr(feature = "radix-heap", allow(dead_code))]
+mod bucket_queue;
 pub(crate) mod changes;
 pub(crate) mod dijkstra;
+pub(crate) mod fallback;
 mod graph;
+pub(crate) mod graphviz;
+pub(crate) mod moves;
 pub(crate) mod myers_diff;
 pub(crate) mod sliders;
 mod stack;
@@ -0,0 +1,612 @@
+//! Detect code that moved or was copied, rather than changed.
+//!
+//! When a block of code moves within a file, the structural diff
+//! sees a novel region on each side. We pair up novel regions whose
+//! tokens are the same, or almost the same, and mark the matching
+//! tokens as moved.
+//!
+//! When a block of code is copied, the structural diff sees a novel
+//! region on the RHS that is almost the same as existing code. We
+//! mark the tokens that match the existing code as copied, so only
+//! the edits to the copy are highlighted.
+
+use std::collections::HashSet;
+
+use line_numbers::{LineNumber, SingleLineSpan};
+
+use crate::{
+    diff::{
+        changes::{ChangeKind, ChangeMap},
+        myers_diff,
+    },
+    hash::DftHashMap,
+    parse::syntax::{MatchKind, MatchedPos, Syntax},
+};
+
+/// The fraction of tokens that must match for two novel regions to
+/// be considered a move.
+const MIN_SIMILARITY: f64 = 0.9;
+
+/// Moved tokens on one side, keyed by their line and start column,
+/// with the line they moved to or from on the other side.
+pub(crate) type MovedPositions = DftHashMap<(LineNumber, u32), LineNumber>;
+
+/// Copied tokens on the RHS, keyed by their line and start column,
+/// with the first line of the LHS code they were copied from.
+pub(crate) type CopiedPositions = DftHashMap<(LineNumber, u32), LineNumber>;
+
+/// A token in a novel region. Tokens are equal if they have the same
+/// content, regardless of where they occur.
+#[derive(Debug, Clone)]
+struct Token<'a> {
+    content: &'a str,
+    position: &'a [SingleLineSpan],
+}
+
+impl<'a> PartialEq for Token<'a> {
+    fn eq(&self, other: &Self) -> bool {
+        self.content == other.content
+    }
+}
+
+fn push_token<'a>(tokens: &mut Vec<Token<'a>>, content: &'a str, position: &'a [SingleLineSpan]) {
+    // Lists without delimiters have empty open and close content.
+    if !content.is_empty() {
+        tokens.push(Token { content, position });
+    }
+}
+
+fn push_tokens<'a>(node: &'a Syntax<'a>, tokens: &mut Vec<Token<'a>>) {
+    match node {
+        Syntax::List {
+            open_content,
+            open_position,
+            children,
+            close_content,
+            close_position,
+            ..
+        } => {
+            push_token(tokens, open_content, open_position);
+            for child in children {
+                push_tokens(child, tokens);
+            }
+            push_token(tokens, close_content, close_position);
+        }
+        Syntax::Atom {
+            content, position, ..
+        } => push_token(tokens, content, position),
+    }
+}
+
+fn region_tokens<'a>(nodes: &[&'a Syntax<'a>]) -> Vec<Token<'a>> {
+    let mut tokens = vec![];
+    for node in nodes {
+        push_tokens(node, &mut tokens);
+    }
+    tokens
+}
+
+/// Add runs of adjacent sibling nodes that are entirely novel to
+/// `regions`. Returns true if every node in `nodes` is entirely
+/// novel, in which case the caller is responsible for adding them,
+/// so they're part of the largest possible region.
+fn find_regions<'a>(
+    nodes: &[&'a Syntax<'a>],
+    change_map: &ChangeMap<'a>,
+    regions: &mut Vec<Vec<Token<'a>>>,
+) -> bool {
+    let fully_novel: Vec<bool> = nodes
+        .iter()
+        .map(|node| {
+            let node_novel = change_map.get(node) == Some(ChangeKind::Novel);
+            match node {
+                Syntax::List { children, .. } => {
+                    let children_novel = find_regions(children, change_map, regions);
+                    if children_novel && !node_novel && !children.is_empty() {
+                        regions.push(region_tokens(children));
+                    }
+                    children_novel && node_novel
+                }
+                Syntax::Atom { .. } => node_novel,
+            }
+        })
+        .collect();
+
+    if fully_novel.iter().all(|novel| *novel) {
+        return true;
+    }
+
+    let mut run_start = None;
+    for (i, novel) in fully_novel
+        .iter()
+        .chain(std::iter::once(&false))
+        .enumerate()
+    {
+        match (novel, run_start) {
+            (true, None) => run_start = Some(i),
+            (false, Some(start)) => {
+                regions.push(region_tokens(&nodes[start..i]));
+                run_start = None;
+            }
+            _ => {}
+        }
+    }
+    false
+}
+
+fn novel_regions<'a>(nodes: &[&'a Syntax<'a>], change_map: &ChangeMap<'a>) -> Vec<Vec<Token<'a>>> {
+    let mut regions = vec![];
+    if find_regions(nodes, change_map, &mut regions) && !nodes.is_empty() {
+        regions.push(region_tokens(nodes));
+    }
+    regions
+}
+
+/// Regions of very different sizes can't be similar.
+fn similar_sizes(lhs_len: usize, rhs_len: usize) -> bool {
+    let (shorter, longer) = if lhs_len < rhs_len {
+        (lhs_len, rhs_len)
+    } else {
+        (rhs_len, lhs_len)
+    };
+    shorter as f64 >= longer as f64 * MIN_SIMILARITY
+}
+
+/// If `lhs` and `rhs` are similar enough to be a move, return the
+/// pairs of tokens that match.
+fn matching_tokens<'a, 'b>(
+    lhs: &'b [Token<'a>],
+    rhs: &'b [Token<'a>],
+) -> Option<Vec<(&'b Token<'a>, &'b Token<'a>)>> {
+    // Cheap check before diffing.
+    if !similar_sizes(lhs.len(), rhs.len()) {
+        return None;
+    }
+
+    let matched: Vec<_> = myers_diff::slice(lhs, rhs)
+        .into_iter()
+        .filter_map(|diff_res| match diff_res {
+            myers_diff::DiffResult::Both(lhs_token, rhs_token) => Some((lhs_token, rhs_token)),
+            _ => None,
+        })
+        .collect();
+
+    let similarity = 2.0 * matched.len() as f64 / (lhs.len() + rhs.len()) as f64;
+    if similarity >= MIN_SIMILARITY {
+        Some(matched)
+    } else {
+        None
+    }
+}
+
+/// Find novel regions of at least `min_tokens` tokens that occur on
+/// both sides, so were moved rather than removed and added.
+pub(crate) fn find_moves<'a>(
+    lhs_nodes: &[&'a Syntax<'a>],
+    rhs_nodes: &[&'a Syntax<'a>],
+    change_map: &ChangeMap<'a>,
+    min_tokens: usize,
+) -> (MovedPositions, MovedPositions) {
+    let is_large = |region: &Vec<Token>| region.len() >= std::cmp::max(min_tokens, 1);
+    let lhs_regions: Vec<_> = novel_regions(lhs_nodes, change_map)
+        .into_iter()
+        .filter(is_large)
+        .collect();
+    let mut rhs_regions: Vec<Option<Vec<Token>>> = novel_regions(rhs_nodes, change_map)
+        .into_iter()
+        .filter(is_large)
+        .map(Some)
+        .collect();
+
+    let mut lhs_moved = MovedPositions::default();
+    let mut rhs_moved = MovedPositions::default();
+    for lhs_region in &lhs_regions {
+        for rhs_region in rhs_regions.iter_mut() {
+            let matched = match rhs_region {
+                Some(rhs_region) => matching_tokens(lhs_region, rhs_region),
+                None => continue,
+            };
+            let matched = match matched {
+                Some(matched) => matched,
+                None => continue,
+            };
+
+            for (lhs_token, rhs_token) in matched {
+                if let (Some(lhs_first), Some(rhs_first)) =
+                    (lhs_token.position.first(), rhs_token.position.first())
+                {
+                    for span in lhs_token.position {
+                        lhs_moved.insert((span.line, span.start_col), rhs_first.line);
+                    }
+                    for span in rhs_token.position {
+                        rhs_moved.insert((span.line, span.start_col), lhs_first.line);
+                    }
+                }
+            }
+
+            // Each region can only be moved once.
+            *rhs_region = None;
+            break;
+        }
+    }
+
+    (lhs_moved, rhs_moved)
+}
+
+/// Add the lists in `nodes` that aren't novel to `sources`, with
+/// their number of tokens. Returns the number of tokens in `nodes`.
+fn find_sources<'a>(
+    nodes: &[&'a Syntax<'a>],
+    change_map: &ChangeMap<'a>,
+    sources: &mut Vec<(&'a Syntax<'a>, usize)>,
+) -> usize {
+    let mut num_tokens = 0;
+    for node in nodes {
+        match node {
+            Syntax::List {
+                open_content,
+                children,
+                close_content,
+                ..
+            } => {
+                let node_tokens = find_sources(children, change_map, sources)
+                    + !open_content.is_empty() as usize
+                    + !close_content.is_empty() as usize;
+                if change_map.get(node) != Some(ChangeKind::Novel) {
+                    sources.push((*node, node_tokens));
+                }
+                num_tokens += node_tokens;
+            }
+            Syntax::Atom { content, .. } => {
+                if !content.is_empty() {
+                    num_tokens += 1;
+                }
+            }
+        }
+    }
+    num_tokens
+}
+
+fn token_counts<'a>(tokens: &[Token<'a>]) -> DftHashMap<&'a str, usize> {
+    let mut counts = DftHashMap::default();
+    for token in tokens {
+        *counts.entry(token.content).or_insert(0) += 1;
+    }
+    counts
+}
+
+/// An upper bound on the similarity that `matching_tokens` would
+/// find, which is much cheaper than diffing the tokens.
+fn max_similarity(
+    lhs_counts: &DftHashMap<&str, usize>,
+    rhs_counts: &DftHashMap<&str, usize>,
+    lhs_len: usize,
+    rhs_len: usize,
+) -> f64 {
+    let common: usize = lhs_counts
+        .iter()
+        .map(|(content, count)| std::cmp::min(*count, *rhs_counts.get(content).unwrap_or(&0)))
+        .sum();
+    2.0 * common as f64 / (lhs_len + rhs_len) as f64
+}
+
+/// Find novel regions on the RHS of at least `min_tokens` tokens that
+/// are almost the same as a list on the LHS that still exists, so
+/// were copied from it. Regions that were moved are ignored.
+pub(crate) fn find_copies<'a>(
+    lhs_nodes: &[&'a Syntax<'a>],
+    rhs_nodes: &[&'a Syntax<'a>],
+    change_map: &ChangeMap<'a>,
+    min_tokens: usize,
+    rhs_moved: &MovedPositions,
+) -> CopiedPositions {
+    let min_tokens = std::cmp::max(min_tokens, 1);
+    let mut sources = vec![];
+    find_sources(lhs_nodes, change_map, &mut sources);
+    sources.retain(|(_, num_tokens)| *num_tokens >= min_tokens);
+
+    let mut copied = CopiedPositions::default();
+    for region in novel_regions(rhs_nodes, change_map) {
+        if region.len() < min_tokens {
+            continue;
+        }
+        let is_moved = region.iter().any(|token| {
+            token.position.first().map_or(false, |span| {
+                rhs_moved.contains_key(&(span.line, span.start_col))
+            })
+        });
+        if is_moved {
+            continue;
+        }
+
+        let region_counts = token_counts(&region);
+        for (source, num_tokens) in &sources {
+            if !similar_sizes(*num_tokens, region.len()) {
+                continue;
+            }
+
+            let source_tokens = region_tokens(&[*source]);
+            let source_counts = token_counts(&source_tokens);
+            if max_similarity(
+                &source_counts,
+                &region_counts,
+                source_tokens.len(),
+                region.len(),
+            ) < MIN_SIMILARITY
+            {
+                continue;
+            }
+
+            let matched = match matching_tokens(&source_tokens, &region) {
+                Some(matched) => matched,
+                None => continue,
+            };
+            let source_line = match source_tokens[0].position.first() {
+                Some(span) => span.line,
+                None => continue,
+            };
+            for (_, rhs_token) in matched {
+                for span in rhs_token.position {
+                    copied.insert((span.line, span.start_col), source_line);
+                }
+            }
+            break;
+        }
+    }
+
+    copied
+}
+
+/// Mark the novel positions in `positions` that were copied.
+pub(crate) fn mark_copied(positions: &mut [MatchedPos], copied: &CopiedPositions) {
+    for mp in positions {
+        if let MatchKind::Novel { highlight, .. } = mp.kind {
+            if let Some(source_line) = copied.get(&(mp.pos.line, mp.pos.start_col)) {
+                mp.kind = MatchKind::Copied {
+                    highlight,
+                    source_line: *source_line,
+                };
+            }
+        }
+    }
+}
+
+/// The first line of each block of copied lines in `mps`, with the
+/// line the block was copied from and the number of lines in the
+/// block that were changed after copying.
+pub(crate) fn copied_block_starts(
+    mps: &[MatchedPos],
+) -> DftHashMap<LineNumber, (LineNumber, usize)> {
+    let mut copied_lines: DftHashMap<LineNumber, LineNumber> = DftHashMap::default();
+    let mut changed_lines: HashSet<LineNumber> = HashSet::new();
+    for mp in mps {
+        match mp.kind {
+            MatchKind::Copied { source_line, .. } => {
+                copied_lines.entry(mp.pos.line).or_insert(source_line);
+            }
+            _ if mp.kind.is_novel() => {
+                changed_lines.insert(mp.pos.line);
+            }
+            _ => {}
+        }
+    }
+
+    let mut starts = DftHashMap::default();
+    for (line, source_line) in &copied_lines {
+        let continues_block =
+            line.0 > 0 && copied_lines.get(&LineNumber(line.0 - 1)) == Some(source_line);
+        if continues_block {
+            continue;
+        }
+
+        let mut num_changed = 0;
+        let mut block_line = *line;
+        while copied_lines.get(&block_line) == Some(source_line) {
+            if changed_lines.contains(&block_line) {
+                num_changed += 1;
+            }
+            block_line = LineNumber(block_line.0 + 1);
+        }
+        starts.insert(*line, (*source_line, num_changed));
+    }
+
+    starts
+}
+
+/// Mark the novel positions in `positions` that were moved.
+pub(crate) fn mark_moved(positions: &mut [MatchedPos], moved: &MovedPositions) {
+    for mp in positions {
+        if let MatchKind::Novel { highlight, .. } = mp.kind {
+            if let Some(opposite_line) = moved.get(&(mp.pos.line, mp.pos.start_col)) {
+                mp.kind = MatchKind::Moved {
+                    highlight,
+                    opposite_line: *opposite_line,
+                };
+            }
+        }
+    }
+}
+
+/// The first line of each block of moved lines in `mps`, with the
+/// line on the other side that it moved to or from.
+pub(crate) fn moved_block_starts(mps: &[MatchedPos]) -> DftHashMap<LineNumber, LineNumber> {
+    let mut moved_lines: DftHashMap<LineNumber, LineNumber> = DftHashMap::default();
+    for mp in mps {
+        if let MatchKind::Moved { opposite_line, .. } = mp.kind {
+            moved_lines.entry(mp.pos.line).or_insert(opposite_line);
+        }
+    }
+
+    moved_lines
+        .iter()
+        .filter(|(line, _)| line.0 == 0 || !moved_lines.contains_key(&LineNumber(line.0 - 1)))
+        .map(|(line, opposite_line)| (*line, *opposite_line))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use typed_arena::Arena;
+
+    use super::*;
+    use crate::{
+        diff::changes::{insert_deep_novel, insert_deep_unchanged},
+        parse::syntax::{init_all_info, AtomKind, TokenKind},
+    };
+
+    fn atoms<'a>(
+        arena: &'a Arena<Syntax<'a>>,
+        line: u32,
+        contents: &[&'a str],
+    ) -> Vec<&'a Syntax<'a>> {
+        contents
+            .iter()
+            .enumerate()
+            .map(|(i, content)| {
+                Syntax::new_atom(
+                    arena,
+                    vec![SingleLineSpan {
+                        line: line.into(),
+                        start_col: i as u32 * 2,
+                        end_col: i as u32 * 2 + 1,
+                    }],
+                    content,
+                    AtomKind::Normal,
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_find_moves() {
+        let arena = Arena::new();
+        let lhs = atoms(&arena, 0, &["a", "b", "c", "d"]);
+        let rhs = atoms(&arena, 5, &["a", "b", "c", "d"]);
+        init_all_info(&lhs, &rhs);
+
+        let mut change_map = ChangeMap::default();
+        for node in lhs.iter().chain(rhs.iter()) {
+            insert_deep_novel(node, &mut change_map);
+        }
+
+        let (lhs_moved, rhs_moved) = find_moves(&lhs, &rhs, &change_map, 4);
+        assert_eq!(lhs_moved.get(&(0.into(), 2)), Some(&5.into()));
+        assert_eq!(rhs_moved.get(&(5.into(), 6)), Some(&0.into()));
+
+        let (lhs_moved, _) = find_moves(&lhs, &rhs, &change_map, 5);
+        assert!(lhs_moved.is_empty());
+    }
+
+    #[test]
+    fn test_find_moves_dissimilar() {
+        let arena = Arena::new();
+        let lhs = atoms(&arena, 0, &["a", "b", "c", "d"]);
+        let rhs = atoms(&arena, 5, &["a", "x", "y", "d"]);
+        init_all_info(&lhs, &rhs);
+
+        let mut change_map = ChangeMap::default();
+        for node in lhs.iter().chain(rhs.iter()) {
+            insert_deep_novel(node, &mut change_map);
+        }
+
+        let (lhs_moved, rhs_moved) = find_moves(&lhs, &rhs, &change_map, 1);
+        assert!(lhs_moved.is_empty());
+        assert!(rhs_moved.is_empty());
+    }
+
+    #[test]
+    fn test_moved_block_starts() {
+        let moved = |line: u32, opposite_line: u32| MatchedPos {
+            kind: MatchKind::Moved {
+                highlight: TokenKind::Atom(AtomKind::Normal),
+                opposite_line: opposite_line.into(),
+            },
+            pos: SingleLineSpan {
+                line: line.into(),
+                start_col: 0,
+                end_col: 1,
+            },
+        };
+        let mps = vec![moved(3, 10), moved(4, 11), moved(8, 1)];
+
+        let starts = moved_block_starts(&mps);
+        assert_eq!(starts.len(), 2);
+        assert_eq!(starts.get(&LineNumber::from(3)), Some(&10.into()));
+        assert_eq!(starts.get(&LineNumber::from(8)), Some(&1.into()));
+    }
+
+    fn list<'a>(arena: &'a Arena<Syntax<'a>>, line: u32, contents: &[&'a str]) -> &'a Syntax<'a> {
+        let delimiter_span = |col| SingleLineSpan {
+            line: line.into(),
+            start_col: col,
+            end_col: col + 1,
+        };
+        Syntax::new_list(
+            arena,
+            "(",
+            vec![delimiter_span(100)],
+            atoms(arena, line, contents),
+            ")",
+            vec![delimiter_span(101)],
+        )
+    }
+
+    #[test]
+    fn test_find_copies() {
+        let arena = Arena::new();
+        let contents = ["a", "b", "c", "d", "e", "f", "g", "h", "i", "j"];
+        let lhs = vec![list(&arena, 0, &contents)];
+        let rhs = vec![
+            list(&arena, 0, &contents),
+            list(
+                &arena,
+                2,
+                &["a", "b", "c", "d", "e", "f", "g", "h", "i", "x"],
+            ),
+        ];
+        init_all_info(&lhs, &rhs);
+
+        let mut change_map = ChangeMap::default();
+        insert_deep_unchanged(lhs[0], rhs[0], &mut change_map);
+        insert_deep_unchanged(rhs[0], lhs[0], &mut change_map);
+        insert_deep_novel(rhs[1], &mut change_map);
+
+        let copied = find_copies(&lhs, &rhs, &change_map, 5, &MovedPositions::default());
+        assert_eq!(copied.get(&(2.into(), 0)), Some(&0.into()));
+        assert_eq!(copied.get(&(2.into(), 18)), None);
+
+        let copied = find_copies(&lhs, &rhs, &change_map, 20, &MovedPositions::default());
+        assert!(copied.is_empty());
+    }
+
+    #[test]
+    fn test_copied_block_starts() {
+        let pos = |line: u32, kind: MatchKind| MatchedPos {
+            kind,
+            pos: SingleLineSpan {
+                line: line.into(),
+                start_col: 0,
+                end_col: 1,
+            },
+        };
+        let copied = |source_line: u32| MatchKind::Copied {
+            highlight: TokenKind::Atom(AtomKind::Normal),
+            source_line: source_line.into(),
+        };
+        let novel = MatchKind::Novel {
+            highlight: TokenKind::Atom(AtomKind::Normal),
+            punctuation: false,
+        };
+        let mps = vec![
+            pos(3, copied(10)),
+            pos(4, copied(10)),
+            pos(4, novel),
+            pos(5, copied(10)),
+        ];
+
+        let starts = copied_block_starts(&mps);
+        assert_eq!(starts.len(), 1);
+        assert_eq!(starts.get(&LineNumber::from(3)), Some(&(10.into(), 1)));
+    }
+}
@@ -49,7 +49,6 @@ pub(crate) fn slice_by_hash<'a, T: Eq + Hash>(
     // This is the decorate-sort-undecorate pattern, or Schwartzian
     // transform, for diffing.
     let mut value_ids: DftHashMap<&T, u32> = DftHashMap::default();
-    let mut id_values: DftHashMap<u32, &T> = DftHashMap::default();
 
     let mut lhs_ids = Vec::with_capacity(lhs.len());
     for value in lhs {
@@ -58,7 +57,6 @@ pub(crate) fn slice_by_hash<'a, T: Eq + Hash>(
             None => {
                 let new_id = value_ids.len() as u32;
                 value_ids.insert(value, new_id);
-                id_values.insert(new_id, value);
                 new_id
             }
         };
@@ -72,22 +70,33 @@ pub(crate) fn slice_by_hash<'a, T: Eq + Hash>(
             None => {
                 let new_id = value_ids.len() as u32;
                 value_ids.insert(value, new_id);
-                id_values.insert(new_id, value);
                 new_id
             }
         };
         rhs_ids.push(id);
     }
 
+    // Return the items at each position, rather than the first item
+    // with each ID. Items that are equal may still differ, such as
+    // lines that only differ in ignored whitespace.
+    let mut lhs_i = 0;
+    let mut rhs_i = 0;
     slice(&lhs_ids[..], &rhs_ids[..])
         .into_iter()
         .map(|result| match result {
-            DiffResult::Left(id) => DiffResult::Left(*id_values.get(id).unwrap()),
-            DiffResult::Both(lhs_id, rhs_id) => DiffResult::Both(
-                *id_values.get(lhs_id).unwrap(),
-                *id_values.get(rhs_id).unwrap(),
-            ),
-            DiffResult::Right(id) => DiffResult::Right(*id_values.get(id).unwrap()),
+            DiffResult::Left(_) => {
+                lhs_i += 1;
+                DiffResult::Left(&lhs[lhs_i - 1])
+            }
+            DiffResult::Both(_, _) => {
+                lhs_i += 1;
+                rhs_i += 1;
+                DiffResult::Both(&lhs[lhs_i - 1], &rhs[rhs_i - 1])
+            }
+            DiffResult::Right(_) => {
+                rhs_i += 1;
+                DiffResult::Right(&rhs[rhs_i - 1])
+            }
         })
         .collect::<Vec<_>>()
 }
@@ -237,6 +246,20 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_slice_by_hash_equal_items_from_each_side() {
+        let lhs = ["a".to_owned()];
+        let rhs = ["a".to_owned()];
+        let diff_items = slice_by_hash(&lhs, &rhs);
+        match diff_items[..] {
+            [DiffResult::Both(lhs_item, rhs_item)] => {
+                assert!(std::ptr::eq(lhs_item, &lhs[0]));
+                assert!(std::ptr::eq(rhs_item, &rhs[0]));
+            }
+            _ => panic!("Expected a single unchanged item: {:?}", diff_items),
+        }
+    }
+
     #[test]
     fn test_slice_unique_same_items() {
         let diff_items = slice_unique_by_hash(&["a", "b"], &["a", "b"]);
@@ -45,6 +45,7 @@ pub(crate) fn fix_all_sliders<'a>(
     // TODO: fix sliders that require more than two steps.
     fix_all_sliders_one_step(nodes, change_map);
     fix_all_sliders_one_step(nodes, change_map);
+    fix_all_append_sliders(nodes, change_map);
 
     fix_all_nested_sliders(language, nodes, change_map);
 }
@@ -81,6 +82,88 @@ fn fix_all_sliders_one_step<'a>(nodes: &[&'a Syntax<'a>], change_map: &mut Chang
     fix_sliders(nodes, change_map);
 }
 
+fn fix_all_append_sliders<'a>(nodes: &[&'a Syntax<'a>], change_map: &mut ChangeMap<'a>) {
+    for node in nodes {
+        if let List {
+            open_position,
+            children,
+            ..
+        } = node
+        {
+            fix_all_append_sliders(children, change_map);
+            fix_append_slider(open_position, children, change_map);
+        }
+    }
+}
+
+/// Correct sliders when appending an element that's identical to
+/// the last element of a list.
+///
+/// ```text
+/// // Before
+/// (let [x 1]
+///   (foo x))
+/// // After
+/// (let [x 1]
+///   (foo x)
+///   (foo x))
+/// ```
+///
+/// Either `(foo x)` could be novel. If it's the first one, the
+/// closing delimiters at the end of the list are shown as unchanged,
+/// and the old line is shown as novel. Prefer the last one, so the
+/// novel delimiters are on the same line as the novel content.
+///
+/// The one step slider correction doesn't handle this, because the
+/// novel region may be a single node, and the last element is
+/// usually on a different line to the novel region.
+fn fix_append_slider<'a>(
+    open_position: &[SingleLineSpan],
+    children: &[&'a Syntax<'a>],
+    change_map: &mut ChangeMap<'a>,
+) {
+    let last_node = match children.last() {
+        Some(node) => *node,
+        None => return,
+    };
+    let opposite = match change_map.get(last_node) {
+        Some(Unchanged(n)) if n.content_id() == last_node.content_id() => n,
+        _ => return,
+    };
+
+    // Find the novel region immediately before the last node.
+    let last_idx = children.len() - 1;
+    let mut start_idx = last_idx;
+    while start_idx > 0 && is_novel_deep(children[start_idx - 1], change_map) {
+        start_idx -= 1;
+    }
+    if start_idx == last_idx {
+        return;
+    }
+
+    let start_node = children[start_idx];
+    if start_node.content_id() != last_node.content_id() {
+        return;
+    }
+
+    // Only slide when the region starts on its own line. Otherwise
+    // we prefer keeping novel nodes on the same line together, see
+    // `slide_to_prev_node`.
+    let before_start = if start_idx > 0 {
+        children[start_idx - 1].last_line_span()
+    } else {
+        open_position.last().copied()
+    };
+    match (before_start, start_node.first_line_span()) {
+        (Some(before_start), Some(start)) if before_start.line != start.line => {}
+        _ => return,
+    }
+
+    insert_deep_unchanged(start_node, opposite, change_map);
+    insert_deep_unchanged(opposite, start_node, change_map);
+    insert_deep_novel(last_node, change_map);
+}
+
 /// Correct sliders in middle insertions.
 ///
 /// Consider the code:
@@ -201,7 +284,7 @@ fn unchanged_descendants<'a>(
 /// Nested sliders require a single unchanged descendant whose
 /// delimiters we can slide.
 ///
-/// ```
+/// ```text
 /// (old-1 (novel (old-2)))
 /// ```
 ///
@@ -803,6 +886,94 @@ mod tests {
         assert_eq!(change_map.get(rhs[3]), Some(Unchanged(rhs[0])));
     }
 
+    fn children<'a>(node: &'a Syntax<'a>) -> &'a [&'a Syntax<'a>] {
+        match node {
+            List { children, .. } => children,
+            Atom { .. } => unreachable!(),
+        }
+    }
+
+    /// Find the first list in `nodes` with this open delimiter,
+    /// searching depth first.
+    fn find_list<'a>(nodes: &[&'a Syntax<'a>], open: &str) -> Option<&'a Syntax<'a>> {
+        for node in nodes {
+            if let List {
+                open_content,
+                children,
+                ..
+            } = node
+            {
+                if *open_content == open {
+                    return Some(node);
+                }
+                if let Some(found) = find_list(children, open) {
+                    return Some(found);
+                }
+            }
+        }
+        None
+    }
+
+    #[test]
+    fn test_slider_append_clojure_let() {
+        let arena = Arena::new();
+        let config = from_language(guess_language::Language::Clojure);
+
+        let lhs = parse(&arena, "(let [a 1]\n  (foo a))", &config, false);
+        let rhs = parse(&arena, "(let [a 1]\n  (foo a)\n  (foo a))", &config, false);
+        init_all_info(&lhs, &rhs);
+
+        let lhs_foo = children(lhs[0])[2];
+        let rhs_foo_1 = children(rhs[0])[2];
+        let rhs_foo_2 = children(rhs[0])[3];
+
+        // The first `(foo a)` is novel.
+        let mut change_map = ChangeMap::default();
+        insert_deep_unchanged(lhs[0], rhs[0], &mut change_map);
+        insert_deep_unchanged(rhs[0], lhs[0], &mut change_map);
+        insert_deep_novel(rhs_foo_1, &mut change_map);
+        insert_deep_unchanged(rhs_foo_2, lhs_foo, &mut change_map);
+        insert_deep_unchanged(lhs_foo, rhs_foo_2, &mut change_map);
+
+        fix_all_sliders(guess_language::Language::Clojure, &rhs, &mut change_map);
+        assert_eq!(change_map.get(rhs_foo_1), Some(Unchanged(lhs_foo)));
+        assert_eq!(change_map.get(rhs_foo_2), Some(Novel));
+        assert_eq!(change_map.get(lhs_foo), Some(Unchanged(rhs_foo_1)));
+    }
+
+    #[test]
+    fn test_slider_append_nested_json() {
+        let arena = Arena::new();
+        let config = from_language(guess_language::Language::Json);
+
+        let lhs = parse(&arena, "{\"items\": [\n  {\"id\": 1}\n]}", &config, false);
+        let rhs = parse(
+            &arena,
+            "{\"items\": [\n  {\"id\": 1},\n  {\"id\": 1}\n]}",
+            &config,
+            false,
+        );
+        init_all_info(&lhs, &rhs);
+
+        let lhs_item = children(find_list(&lhs, "[").unwrap())[0];
+        let rhs_items = children(find_list(&rhs, "[").unwrap());
+        assert_eq!(rhs_items.len(), 3);
+
+        // The first item and the comma are novel.
+        let mut change_map = ChangeMap::default();
+        insert_deep_unchanged(lhs[0], rhs[0], &mut change_map);
+        insert_deep_unchanged(rhs[0], lhs[0], &mut change_map);
+        insert_deep_novel(rhs_items[0], &mut change_map);
+        insert_deep_novel(rhs_items[1], &mut change_map);
+        insert_deep_unchanged(rhs_items[2], lhs_item, &mut change_map);
+        insert_deep_unchanged(lhs_item, rhs_items[2], &mut change_map);
+
+        fix_all_sliders(guess_language::Language::Json, &rhs, &mut change_map);
+        assert_eq!(change_map.get(rhs_items[0]), Some(Unchanged(lhs_item)));
+        assert_eq!(change_map.get(rhs_items[1]), Some(Novel));
+        assert_eq!(change_map.get(rhs_items[2]), Some(Novel));
+    }
+
     /// If a list is partially unchanged but contains some novel
     /// children, we should not slide it.
     #[test]
@@ -5,26 +5,40 @@ use std::collections::HashSet;
 use std::hash::Hash;
 
 use crate::diff::changes::{insert_deep_unchanged, ChangeKind, ChangeMap};
+use crate::diff::dijkstra::Interrupt;
 use crate::diff::myers_diff;
 use crate::parse::syntax::Syntax;
 
-const TINY_TREE_THRESHOLD: u32 = 10;
-const MOSTLY_UNCHANGED_MIN_COMMON_CHILDREN: usize = 4;
-
 /// Set [`ChangeKind`] on nodes that have exactly the same structure
 /// on both sides, and return a vec of pairs that need proper diffing.
+///
+/// Unchanged nodes in the middle of the file are only marked if they
+/// have at least `min_run` descendants. Top-level lists are diffed
+/// separately if they share at least `min_common_subtrees` unique
+/// subtrees.
+///
+/// If the diff is cancelled, this stops early, so callers should
+/// check `interrupt` before using the result.
 pub(crate) fn mark_unchanged<'a>(
     lhs_nodes: &[&'a Syntax<'a>],
     rhs_nodes: &[&'a Syntax<'a>],
     change_map: &mut ChangeMap<'a>,
+    min_run: u32,
+    min_common_subtrees: usize,
+    interrupt: &Interrupt,
 ) -> Vec<(Vec<&'a Syntax<'a>>, Vec<&'a Syntax<'a>>)> {
     let (_, lhs_nodes, rhs_nodes) = shrink_unchanged_at_ends(lhs_nodes, rhs_nodes, change_map);
 
     let mut nodes_to_diff = vec![];
-    for (lhs_nodes, rhs_nodes) in split_mostly_unchanged_toplevel(&lhs_nodes, &rhs_nodes) {
+    for (lhs_nodes, rhs_nodes) in
+        split_mostly_unchanged_toplevel(&lhs_nodes, &rhs_nodes, min_common_subtrees)
+    {
+        if interrupt.is_cancelled() {
+            break;
+        }
         let (_, lhs_nodes, rhs_nodes) =
             shrink_unchanged_at_ends(&lhs_nodes, &rhs_nodes, change_map);
-        nodes_to_diff.extend(split_unchanged(&lhs_nodes, &rhs_nodes, change_map));
+        nodes_to_diff.extend(split_unchanged(&lhs_nodes, &rhs_nodes, change_map, min_run));
     }
 
     nodes_to_diff
@@ -41,16 +55,8 @@ fn split_unchanged<'a>(
     lhs_nodes: &[&'a Syntax<'a>],
     rhs_nodes: &[&'a Syntax<'a>],
     change_map: &mut ChangeMap<'a>,
+    size_threshold: u32,
 ) -> Vec<(Vec<&'a Syntax<'a>>, Vec<&'a Syntax<'a>>)> {
-    let size_threshold = if let Ok(env_threshold) = std::env::var("DFT_TINY_THRESHOLD") {
-        env_threshold
-            .parse::<u32>()
-            .ok()
-            .unwrap_or(TINY_TREE_THRESHOLD)
-    } else {
-        TINY_TREE_THRESHOLD
-    };
-
     let mut res: Vec<(Vec<&'a Syntax<'a>>, Vec<&'a Syntax<'a>>)> = vec![];
     for (cs, lhs_section_nodes, rhs_section_nodes) in
         split_unchanged_toplevel(lhs_nodes, rhs_nodes, size_threshold)
@@ -168,10 +174,10 @@ fn count_common_unique(lhs: &Syntax, rhs: &Syntax) -> usize {
 
 /// Return true if both nodes are lists with same delimiters and have
 /// the same start and end children.
-fn is_mostly_unchanged_list(lhs: &Syntax, rhs: &Syntax) -> bool {
+fn is_mostly_unchanged_list(lhs: &Syntax, rhs: &Syntax, min_common_subtrees: usize) -> bool {
     match (lhs, rhs) {
         (Syntax::List { .. }, Syntax::List { .. }) => {
-            count_common_unique(lhs, rhs) >= MOSTLY_UNCHANGED_MIN_COMMON_CHILDREN
+            count_common_unique(lhs, rhs) >= min_common_subtrees
         }
         _ => false,
     }
@@ -182,7 +188,7 @@ fn is_mostly_unchanged_list(lhs: &Syntax, rhs: &Syntax) -> bool {
 /// This is important in cases where we have two adjacent lists that
 /// have a small number of changes.
 ///
-/// ```
+/// ```text
 /// ; old
 /// (1 2 3 4) (a b c d)
 ///
@@ -195,13 +201,14 @@ fn is_mostly_unchanged_list(lhs: &Syntax, rhs: &Syntax) -> bool {
 fn split_mostly_unchanged_toplevel<'a>(
     lhs_nodes: &[&'a Syntax<'a>],
     rhs_nodes: &[&'a Syntax<'a>],
+    min_common_subtrees: usize,
 ) -> Vec<(Vec<&'a Syntax<'a>>, Vec<&'a Syntax<'a>>)> {
     let mut lhs_nodes = lhs_nodes;
     let mut rhs_nodes = rhs_nodes;
 
     let mut leading: Vec<(Vec<&'a Syntax<'a>>, Vec<&'a Syntax<'a>>)> = vec![];
     while let (Some(lhs), Some(rhs)) = (lhs_nodes.first(), rhs_nodes.first()) {
-        if is_mostly_unchanged_list(lhs, rhs) {
+        if is_mostly_unchanged_list(lhs, rhs, min_common_subtrees) {
             leading.push((vec![lhs], vec![rhs]));
 
             lhs_nodes = &lhs_nodes[1..];
@@ -213,7 +220,7 @@ fn split_mostly_unchanged_toplevel<'a>(
 
     let mut trailing: Vec<(Vec<&'a Syntax<'a>>, Vec<&'a Syntax<'a>>)> = vec![];
     while let (Some(lhs), Some(rhs)) = (lhs_nodes.last(), rhs_nodes.last()) {
-        if is_mostly_unchanged_list(lhs, rhs) {
+        if is_mostly_unchanged_list(lhs, rhs, min_common_subtrees) {
             trailing.push((vec![lhs], vec![rhs]));
 
             lhs_nodes = &lhs_nodes[..lhs_nodes.len() - 1];
@@ -420,7 +427,7 @@ fn shrink_unchanged_at_ends<'a>(
 
     let mut changed = false;
     while let (Some(lhs_node), Some(rhs_node)) = (lhs_nodes.first(), rhs_nodes.first()) {
-        // We don't consider TINY_TREE_THRESHOLD here because we are
+        // We don't consider the size threshold here because we are
         // only considering equal nodes at the beginning or end of the
         // file. There's no risk we split unrelated regions with a
         // trivial unchanged node in the middle.
@@ -464,6 +471,7 @@ mod tests {
 
     use super::*;
     use crate::{
+        options::{DEFAULT_UNCHANGED_MIN_RUN, DEFAULT_UNCHANGED_NGRAM},
         parse::guess_language,
         parse::tree_sitter_parser::{from_language, parse},
         syntax::init_all_info,
@@ -561,7 +569,7 @@ mod tests {
         let arena = Arena::new();
         let config = from_language(guess_language::Language::EmacsLisp);
 
-        // Make sure that the initial unchanged node exceeds TINY_TREE_THRESHOLD.
+        // Make sure that the initial unchanged node exceeds DEFAULT_UNCHANGED_MIN_RUN.
         let lhs_nodes = parse(
             &arena,
             "(unchanged (1 2 3 4 5 6 7 8 9 10)) A B",
@@ -577,7 +585,12 @@ mod tests {
         init_all_info(&lhs_nodes, &rhs_nodes);
 
         let mut change_map = ChangeMap::default();
-        let res = split_unchanged(&lhs_nodes, &rhs_nodes, &mut change_map);
+        let res = split_unchanged(
+            &lhs_nodes,
+            &rhs_nodes,
+            &mut change_map,
+            DEFAULT_UNCHANGED_MIN_RUN,
+        );
 
         assert_eq!(res.len(), 1);
         let (lhs_after_skip, rhs_after_skip) = &res[0];
@@ -615,7 +628,12 @@ mod tests {
         init_all_info(&lhs_nodes, &rhs_nodes);
 
         let mut change_map = ChangeMap::default();
-        let res = split_unchanged(&lhs_nodes, &rhs_nodes, &mut change_map);
+        let res = split_unchanged(
+            &lhs_nodes,
+            &rhs_nodes,
+            &mut change_map,
+            DEFAULT_UNCHANGED_MIN_RUN,
+        );
 
         assert_eq!(res.len(), 1);
         let (lhs_after_skip, rhs_after_skip) = &res[0];
@@ -643,7 +661,12 @@ mod tests {
         init_all_info(&lhs_nodes, &rhs_nodes);
 
         let mut change_map = ChangeMap::default();
-        let res = split_unchanged(&lhs_nodes, &rhs_nodes, &mut change_map);
+        let res = split_unchanged(
+            &lhs_nodes,
+            &rhs_nodes,
+            &mut change_map,
+            DEFAULT_UNCHANGED_MIN_RUN,
+        );
 
         assert_eq!(res.len(), 1);
         let (lhs_after_skip, rhs_after_skip) = &res[0];
@@ -680,7 +703,12 @@ mod tests {
         init_all_info(&lhs_nodes, &rhs_nodes);
 
         let mut change_map = ChangeMap::default();
-        let res = split_unchanged(&lhs_nodes, &rhs_nodes, &mut change_map);
+        let res = split_unchanged(
+            &lhs_nodes,
+            &rhs_nodes,
+            &mut change_map,
+            DEFAULT_UNCHANGED_MIN_RUN,
+        );
         assert_eq!(
             res,
             vec![
@@ -717,7 +745,12 @@ mod tests {
         init_all_info(&lhs_nodes, &rhs_nodes);
 
         let mut change_map = ChangeMap::default();
-        let res = split_unchanged(&lhs_nodes, &rhs_nodes, &mut change_map);
+        let res = split_unchanged(
+            &lhs_nodes,
+            &rhs_nodes,
+            &mut change_map,
+            DEFAULT_UNCHANGED_MIN_RUN,
+        );
         assert_eq!(
             res,
             vec![
@@ -747,7 +780,12 @@ mod tests {
         init_all_info(&lhs_nodes, &rhs_nodes);
 
         let mut change_map = ChangeMap::default();
-        let res = split_unchanged(&lhs_nodes, &rhs_nodes, &mut change_map);
+        let res = split_unchanged(
+            &lhs_nodes,
+            &rhs_nodes,
+            &mut change_map,
+            DEFAULT_UNCHANGED_MIN_RUN,
+        );
         assert_eq!(res.len(), 2);
 
         assert_eq!(
@@ -775,8 +813,13 @@ mod tests {
         );
         init_all_info(&lhs_nodes, &rhs_nodes);
 
-        let split = split_mostly_unchanged_toplevel(&lhs_nodes, &rhs_nodes);
+        let split =
+            split_mostly_unchanged_toplevel(&lhs_nodes, &rhs_nodes, DEFAULT_UNCHANGED_NGRAM);
         assert_eq!(split.len(), 2);
+
+        // Each list only has 10 unique children in common.
+        let split = split_mostly_unchanged_toplevel(&lhs_nodes, &rhs_nodes, 11);
+        assert_eq!(split.len(), 1);
     }
 
     #[test]
@@ -816,7 +859,7 @@ mod tests {
         init_all_info(&lhs_nodes, &rhs_nodes);
 
         assert_eq!(
-            split_mostly_unchanged_toplevel(&lhs_nodes, &rhs_nodes).len(),
+            split_mostly_unchanged_toplevel(&lhs_nodes, &rhs_nodes, DEFAULT_UNCHANGED_NGRAM).len(),
             2
         );
     }
@@ -830,7 +873,7 @@ mod tests {
         init_all_info(&lhs_nodes, &rhs_nodes);
 
         assert_eq!(
-            split_mostly_unchanged_toplevel(&lhs_nodes, &rhs_nodes).len(),
+            split_mostly_unchanged_toplevel(&lhs_nodes, &rhs_nodes, DEFAULT_UNCHANGED_NGRAM).len(),
             2
         );
     }
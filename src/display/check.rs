@@ -0,0 +1,16 @@
+//! The paths of the changed files, one per line, for `--check`.
+
+use std::io::Write;
+
+use crate::summary::DiffResult;
+
+/// Print the path of each file in `diffs` that has changes, in the
+/// same order as the other display modes.
+pub(crate) fn print(out: &mut dyn Write, diffs: &[DiffResult]) -> std::io::Result<()> {
+    for diff in diffs {
+        if diff.has_reportable_change() {
+            writeln!(out, "{}", diff.display_path)?;
+        }
+    }
+    Ok(())
+}
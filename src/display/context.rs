@@ -7,6 +7,8 @@ use line_numbers::LineNumber;
 use rustc_hash::FxHashSet;
 
 use crate::{
+    constants::Side,
+    display::hunks::Hunk,
     hash::DftHashMap,
     parse::syntax::{zip_repeat_shorter, MatchKind, MatchedPos},
 };
@@ -153,9 +155,11 @@ fn matched_lines_from_unchanged(
                     opposite_pos.first().map(|p| p.line)
                 }
             }
-            MatchKind::Novel { .. } | MatchKind::NovelWord { .. } | MatchKind::Ignored { .. } => {
-                None
-            }
+            MatchKind::Novel { .. }
+            | MatchKind::NovelWord { .. }
+            | MatchKind::Ignored { .. }
+            | MatchKind::Moved { .. }
+            | MatchKind::Copied { .. } => None,
         };
 
         let should_insert = match highest_line {
@@ -354,7 +358,11 @@ pub(crate) fn opposite_positions(
                     opposite_lines.insert(opposite_span.line);
                 }
             }
-            MatchKind::Novel { .. } | MatchKind::NovelWord { .. } | MatchKind::Ignored { .. } => {}
+            MatchKind::Novel { .. }
+            | MatchKind::NovelWord { .. }
+            | MatchKind::Ignored { .. }
+            | MatchKind::Moved { .. }
+            | MatchKind::Copied { .. } => {}
         }
     }
 
@@ -454,6 +462,52 @@ fn pad_after(ln: LineNumber, max_line: LineNumber, num_context_lines: usize) ->
     line_nums
 }
 
+fn indentation(line: &str) -> usize {
+    line.len() - line.trim_start().len()
+}
+
+/// Whether the line at `start` is a good first line of context for a
+/// hunk starting at `end`. Lines that close a delimiter (e.g. `} else
+/// {`) or that are more indented than the lines below them start in
+/// the middle of a construct, so the reader can't see what they
+/// belong to.
+fn is_structural_start(lines: &[&str], start: usize, end: usize) -> bool {
+    let line = lines.get(start).copied().unwrap_or("");
+    let trimmed = line.trim_start();
+    if trimmed.is_empty() || trimmed.starts_with(|c| matches!(c, '}' | ')' | ']')) {
+        return false;
+    }
+
+    let indent = indentation(line);
+    (start + 1..=end)
+        .filter_map(|i| lines.get(i))
+        .filter(|line| !line.trim().is_empty())
+        .all(|line| indentation(line) >= indent)
+}
+
+/// The number of lines of context to show before `first_line`,
+/// extending `before_context_lines` upwards (up to twice as many
+/// lines) so the context starts at the beginning of a construct,
+/// such as an enclosing `if (...) {` line.
+pub(crate) fn structural_before_context(
+    lines: &[&str],
+    first_line: LineNumber,
+    before_context_lines: usize,
+) -> usize {
+    let first_line = first_line.as_usize();
+    let max_lines = std::cmp::min(2 * before_context_lines, first_line);
+
+    let mut num_lines = std::cmp::min(before_context_lines, first_line);
+    if num_lines == 0 {
+        return 0;
+    }
+
+    while num_lines < max_lines && !is_structural_start(lines, first_line - num_lines, first_line) {
+        num_lines += 1;
+    }
+    num_lines
+}
+
 pub(crate) fn flip_tuple<Tx: Copy, Ty: Copy>(pair: (Tx, Ty)) -> (Ty, Tx) {
     let (x, y) = pair;
     (y, x)
@@ -519,16 +573,16 @@ pub(crate) fn calculate_before_context(
     lines: &[(Option<LineNumber>, Option<LineNumber>)],
     opposite_to_lhs: &DftHashMap<LineNumber, HashSet<LineNumber>>,
     opposite_to_rhs: &DftHashMap<LineNumber, HashSet<LineNumber>>,
-    num_context_lines: usize,
+    before_context_lines: usize,
 ) -> Vec<(Option<LineNumber>, Option<LineNumber>)> {
     match lines.first() {
         Some(first_line) => match *first_line {
             (Some(lhs_line), _) => {
-                let padded_lines = pad_before(lhs_line, num_context_lines);
+                let padded_lines = pad_before(lhs_line, before_context_lines);
                 before_with_opposites(&padded_lines, opposite_to_lhs)
             }
             (_, Some(rhs_line)) => {
-                let padded_lines = pad_before(rhs_line, num_context_lines);
+                let padded_lines = pad_before(rhs_line, before_context_lines);
                 flip_tuples(&before_with_opposites(&padded_lines, opposite_to_rhs))
             }
             (None, None) => vec![],
@@ -543,7 +597,7 @@ pub(crate) fn calculate_after_context(
     opposite_to_rhs: &DftHashMap<LineNumber, HashSet<LineNumber>>,
     max_lhs_src_line: LineNumber,
     max_rhs_src_line: LineNumber,
-    num_context_lines: usize,
+    after_context_lines: usize,
 ) -> Vec<(Option<LineNumber>, Option<LineNumber>)> {
     match lines.last() {
         Some(last_line) => match *last_line {
@@ -557,7 +611,7 @@ pub(crate) fn calculate_after_context(
                     }
                 }
 
-                let padded_lines = pad_after(lhs_line, max_lhs_src_line, num_context_lines);
+                let padded_lines = pad_after(lhs_line, max_lhs_src_line, after_context_lines);
                 after_with_opposites(
                     &padded_lines,
                     opposite_to_lhs,
@@ -573,7 +627,7 @@ pub(crate) fn calculate_after_context(
                     }
                 }
 
-                let padded_lines = pad_after(rhs_line, max_rhs_src_line, num_context_lines);
+                let padded_lines = pad_after(rhs_line, max_rhs_src_line, after_context_lines);
                 flip_tuples(&after_with_opposites(
                     &padded_lines,
                     opposite_to_rhs,
@@ -593,17 +647,22 @@ pub(crate) fn add_context(
     opposite_to_rhs: &DftHashMap<LineNumber, HashSet<LineNumber>>,
     max_lhs_src_line: LineNumber,
     max_rhs_src_line: LineNumber,
-    num_context_lines: usize,
+    before_context_lines: usize,
+    after_context_lines: usize,
 ) -> Vec<(Option<LineNumber>, Option<LineNumber>)> {
-    let before_lines =
-        calculate_before_context(lines, opposite_to_lhs, opposite_to_rhs, num_context_lines);
+    let before_lines = calculate_before_context(
+        lines,
+        opposite_to_lhs,
+        opposite_to_rhs,
+        before_context_lines,
+    );
     let after_lines = calculate_after_context(
         &[&before_lines, lines].concat(),
         opposite_to_lhs,
         opposite_to_rhs,
         max_lhs_src_line,
         max_rhs_src_line,
-        num_context_lines,
+        after_context_lines,
     );
 
     before_lines
@@ -727,6 +786,59 @@ fn compact_gaps(
     res
 }
 
+/// The lines on `side` that might be displayed for `hunk`, including
+/// context, as a zero-indexed inclusive range. This is an
+/// overestimate, so callers can skip work on lines that definitely
+/// won't be shown.
+pub(crate) fn displayed_line_range(
+    hunk: &Hunk,
+    side: Side,
+    opposite_to_lhs: &DftHashMap<LineNumber, HashSet<LineNumber>>,
+    opposite_to_rhs: &DftHashMap<LineNumber, HashSet<LineNumber>>,
+    before_context_lines: usize,
+    after_context_lines: usize,
+) -> Option<(usize, usize)> {
+    let (self_lines, other_lines, opposite_to_other): (Vec<_>, Vec<_>, _) = match side {
+        Side::Left => (
+            hunk.lines.iter().filter_map(|(lhs, _)| *lhs).collect(),
+            hunk.lines.iter().filter_map(|(_, rhs)| *rhs).collect(),
+            opposite_to_rhs,
+        ),
+        Side::Right => (
+            hunk.lines.iter().filter_map(|(_, rhs)| *rhs).collect(),
+            hunk.lines.iter().filter_map(|(lhs, _)| *lhs).collect(),
+            opposite_to_lhs,
+        ),
+    };
+
+    let with_context = |lines: &[LineNumber]| -> Option<(usize, usize)> {
+        let min = lines.iter().min()?.as_usize();
+        let max = lines.iter().max()?.as_usize();
+        Some((
+            min.saturating_sub(before_context_lines),
+            max + after_context_lines,
+        ))
+    };
+
+    let mut lines: Vec<usize> = vec![];
+    if let Some((start, end)) = with_context(&self_lines) {
+        lines.push(start);
+        lines.push(end);
+    }
+    // If this side has no lines in the hunk (e.g. a pure insertion),
+    // its context lines are the ones opposite the other side's
+    // context.
+    if let Some((start, end)) = with_context(&other_lines) {
+        for line in start..=end {
+            if let Some(opposite_lines) = opposite_to_other.get(&LineNumber::from(line as u32)) {
+                lines.extend(opposite_lines.iter().map(|line| line.as_usize()));
+            }
+        }
+    }
+
+    Some((*lines.iter().min()?, *lines.iter().max()?))
+}
+
 #[cfg(test)]
 mod tests {
     use std::iter::FromIterator;
@@ -768,6 +880,7 @@ mod tests {
             MatchedPos {
                 kind: MatchKind::Novel {
                     highlight: TokenKind::Delimiter,
+                    punctuation: false,
                 },
                 pos: SingleLineSpan {
                     line: 0.into(),
@@ -810,6 +923,7 @@ mod tests {
             MatchedPos {
                 kind: MatchKind::Novel {
                     highlight: TokenKind::Delimiter,
+                    punctuation: false,
                 },
                 pos: SingleLineSpan {
                     line: 1.into(),
@@ -879,6 +993,7 @@ mod tests {
             MatchedPos {
                 kind: MatchKind::Novel {
                     highlight: TokenKind::Delimiter,
+                    punctuation: false,
                 },
                 pos: SingleLineSpan {
                     line: 2.into(),
@@ -1044,4 +1159,90 @@ mod tests {
         let res = pad_after(2.into(), 1.into(), 5);
         assert_eq!(res, vec![]);
     }
+
+    #[test]
+    fn test_displayed_line_range_insertion() {
+        // RHS line 5 is inserted, so the LHS has no lines in the hunk.
+        let hunk = Hunk {
+            novel_lhs: HashSet::new(),
+            novel_rhs: HashSet::from_iter([5.into()]),
+            lines: vec![(None, Some(5.into()))],
+            enclosing_declaration: None,
+        };
+        let opposite_to_rhs: DftHashMap<LineNumber, HashSet<LineNumber>> = (0..10)
+            .filter(|i| *i != 5)
+            .map(|i: u32| {
+                let lhs_line = if i < 5 { i } else { i - 1 };
+                (i.into(), HashSet::from_iter([lhs_line.into()]))
+            })
+            .collect();
+
+        assert_eq!(
+            displayed_line_range(
+                &hunk,
+                Side::Right,
+                &DftHashMap::default(),
+                &opposite_to_rhs,
+                2,
+                2
+            ),
+            Some((3, 7))
+        );
+        assert_eq!(
+            displayed_line_range(
+                &hunk,
+                Side::Left,
+                &DftHashMap::default(),
+                &opposite_to_rhs,
+                2,
+                2
+            ),
+            Some((3, 6))
+        );
+    }
+
+    #[test]
+    fn test_displayed_line_range_asymmetric_context() {
+        let hunk = Hunk {
+            novel_lhs: HashSet::from_iter([5.into()]),
+            novel_rhs: HashSet::from_iter([5.into()]),
+            lines: vec![(Some(5.into()), Some(5.into()))],
+            enclosing_declaration: None,
+        };
+
+        assert_eq!(
+            displayed_line_range(
+                &hunk,
+                Side::Left,
+                &DftHashMap::default(),
+                &DftHashMap::default(),
+                1,
+                3
+            ),
+            Some((4, 8))
+        );
+    }
+
+    #[test]
+    fn test_structural_before_context() {
+        let lines = vec![
+            "fn foo() {",
+            "    if x {",
+            "        a();",
+            "        b();",
+            "    } else {",
+            "        c();",
+            "    }",
+            "}",
+        ];
+
+        // The `if` line is already a good start.
+        assert_eq!(structural_before_context(&lines, 2.into(), 1), 1);
+        // Rather than starting inside the `if` body, show the `if`.
+        assert_eq!(structural_before_context(&lines, 5.into(), 3), 4);
+        // Don't exceed twice the requested context.
+        assert_eq!(structural_before_context(&lines, 5.into(), 1), 2);
+        // No context was requested.
+        assert_eq!(structural_before_context(&lines, 5.into(), 0), 0);
+    }
 }
@@ -0,0 +1,585 @@
+//! HTML display of diffs, for publishing results as static pages.
+//!
+//! The layout mirrors the side-by-side terminal display: a table per
+//! hunk with line numbers and source on each side. Styling is inlined
+//! so the output is a single self-contained file.
+
+use line_numbers::LineNumber;
+
+use crate::{
+    display::context::all_matched_lines_filled,
+    display::hunks::{hunk_context, matched_lines_indexes_for_hunk},
+    display::side_by_side::lines_with_novel,
+    display::style::{self, replace_tabs},
+    hash::DftHashMap,
+    options::DisplayOptions,
+    parse::syntax::{AtomKind, MatchKind, MatchedPos, StringKind, TokenKind},
+    summary::{DiffResult, FileContent, FileFormat},
+};
+
+const LIGHT_CSS: &str = "
+body { font-family: sans-serif; background: #ffffff; color: #24292e; }
+nav.index ul { font-family: monospace; }
+section.file { margin-bottom: 2em; }
+h2, h3 { font-family: monospace; font-weight: normal; }
+h2 { font-size: 1.1em; }
+h3 { font-size: 0.9em; color: #6a737d; }
+.extra-info { font-family: monospace; color: #6a737d; white-space: pre-wrap; }
+table.hunk { border-collapse: collapse; width: 100%; table-layout: fixed; font-family: monospace; }
+table.hunk td { padding: 0 0.5em; white-space: pre-wrap; word-break: break-all; vertical-align: top; }
+td.line-num { width: 4em; text-align: right; color: #959da5; user-select: none; }
+td.line-num.novel { font-weight: bold; }
+td.lhs-line-num.novel { color: #b31d28; }
+td.rhs-line-num.novel { color: #22863a; }
+td.missing { background: #fafbfc; }
+.keyword, .type { font-weight: bold; }
+.string { color: #a626a4; }
+.comment { color: #005cc5; font-style: italic; }
+.error { color: #6f42c1; }
+td.lhs .novel { color: #b31d28; background: #ffeef0; }
+td.rhs .novel { color: #22863a; background: #e6ffed; }
+td.lhs .novel-word { background: #fdb8c0; }
+td.rhs .novel-word { background: #acf2bd; }
+.novel-word { font-weight: bold; }
+.moved, .copied { opacity: 0.6; }
+";
+
+const DARK_CSS: &str = "
+body { font-family: sans-serif; background: #0d1117; color: #c9d1d9; }
+nav.index ul { font-family: monospace; }
+nav.index a { color: #58a6ff; }
+section.file { margin-bottom: 2em; }
+h2, h3 { font-family: monospace; font-weight: normal; }
+h2 { font-size: 1.1em; }
+h3 { font-size: 0.9em; color: #8b949e; }
+.extra-info { font-family: monospace; color: #8b949e; white-space: pre-wrap; }
+table.hunk { border-collapse: collapse; width: 100%; table-layout: fixed; font-family: monospace; }
+table.hunk td { padding: 0 0.5em; white-space: pre-wrap; word-break: break-all; vertical-align: top; }
+td.line-num { width: 4em; text-align: right; color: #6e7681; user-select: none; }
+td.line-num.novel { font-weight: bold; }
+td.lhs-line-num.novel { color: #ff7b72; }
+td.rhs-line-num.novel { color: #7ee787; }
+td.missing { background: #161b22; }
+.keyword, .type { font-weight: bold; }
+.string { color: #d2a8ff; }
+.comment { color: #79c0ff; font-style: italic; }
+.error { color: #bc8cff; }
+td.lhs .novel { color: #ff7b72; background: #2d1215; }
+td.rhs .novel { color: #7ee787; background: #12261e; }
+td.lhs .novel-word { background: #67060c; }
+td.rhs .novel-word { background: #033a16; }
+.novel-word { font-weight: bold; }
+.moved, .copied { opacity: 0.6; }
+";
+
+/// Escape `s` so it can be used as HTML text or an attribute value.
+pub(crate) fn escape_html(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// The CSS classes to apply to the span for a `MatchedPos` of this
+/// kind.
+fn span_classes(kind: &MatchKind, syntax_highlight: bool) -> Vec<&'static str> {
+    let mut classes = vec![];
+
+    let highlight = match kind {
+        MatchKind::UnchangedToken { highlight, .. } => {
+            classes.push("unchanged");
+            highlight
+        }
+        MatchKind::Ignored { highlight } => {
+            classes.push("ignored");
+            highlight
+        }
+        MatchKind::Novel { highlight, .. } | MatchKind::NovelLinePart { highlight, .. } => {
+            classes.push("novel");
+            highlight
+        }
+        MatchKind::NovelWord { highlight } => {
+            classes.push("novel");
+            classes.push("novel-word");
+            highlight
+        }
+        MatchKind::Moved { highlight, .. } => {
+            classes.push("novel");
+            classes.push("moved");
+            highlight
+        }
+        MatchKind::Copied { highlight, .. } => {
+            classes.push("novel");
+            classes.push("copied");
+            highlight
+        }
+    };
+
+    // Always mark comments, so they're distinguishable even when
+    // syntax highlighting is off. This matches the italics used in
+    // the terminal display.
+    if !syntax_highlight && !matches!(highlight, TokenKind::Atom(AtomKind::Comment)) {
+        return classes;
+    }
+
+    match highlight {
+        TokenKind::Delimiter => classes.push("delimiter"),
+        TokenKind::Atom(AtomKind::Keyword) => classes.push("keyword"),
+        TokenKind::Atom(AtomKind::Type) => classes.push("type"),
+        TokenKind::Atom(AtomKind::String(StringKind::StringLiteral)) => classes.push("string"),
+        TokenKind::Atom(AtomKind::Comment) => classes.push("comment"),
+        TokenKind::Atom(AtomKind::TreeSitterError) => classes.push("error"),
+        TokenKind::Atom(AtomKind::String(StringKind::Text) | AtomKind::Normal) => {}
+    }
+
+    classes
+}
+
+fn group_by_line(mps: &[MatchedPos]) -> DftHashMap<LineNumber, Vec<&MatchedPos>> {
+    let mut mps_by_line: DftHashMap<LineNumber, Vec<&MatchedPos>> = DftHashMap::default();
+    for mp in mps {
+        mps_by_line
+            .entry(mp.pos.line)
+            .or_insert_with(Vec::new)
+            .push(mp);
+    }
+
+    for line_mps in mps_by_line.values_mut() {
+        line_mps.sort_unstable_by_key(|mp| mp.pos.start_col);
+    }
+
+    mps_by_line
+}
+
+/// Render `line` as escaped HTML, wrapping every position in `mps` in a
+/// span with the appropriate classes.
+///
+/// Tolerant against positions that extend beyond the end of the line.
+fn render_line(
+    line: &str,
+    mps: &[&MatchedPos],
+    syntax_highlight: bool,
+    tab_width: usize,
+) -> String {
+    let line_bytes = line.len();
+    let mut res = String::with_capacity(line.len());
+    let mut i = 0;
+
+    for mp in mps {
+        let start_col = mp.pos.start_col as usize;
+        let end_col = std::cmp::min(mp.pos.end_col as usize, line_bytes);
+        if start_col >= line_bytes || start_col < i {
+            continue;
+        }
+
+        // Text before this span.
+        if i < start_col {
+            res.push_str(&escape_html(&replace_tabs(&line[i..start_col], tab_width)));
+        }
+
+        let classes = span_classes(&mp.kind, syntax_highlight);
+        let content = escape_html(&replace_tabs(&line[start_col..end_col], tab_width));
+        if classes.is_empty() {
+            res.push_str(&content);
+        } else {
+            res.push_str(&format!(
+                "<span class=\"{}\">{}</span>",
+                classes.join(" "),
+                content
+            ));
+        }
+
+        i = end_col;
+    }
+
+    // Text after the last span.
+    if i < line_bytes {
+        res.push_str(&escape_html(&replace_tabs(&line[i..], tab_width)));
+    }
+
+    res
+}
+
+fn render_cells(
+    line_num: Option<LineNumber>,
+    lines: &[&str],
+    mps_by_line: &DftHashMap<LineNumber, Vec<&MatchedPos>>,
+    lines_with_novel: &std::collections::HashSet<LineNumber>,
+    side: &str,
+    display_options: &DisplayOptions,
+) -> String {
+    match line_num {
+        Some(line_num) => {
+            let novel_class = if lines_with_novel.contains(&line_num) {
+                " novel"
+            } else {
+                ""
+            };
+            let content = match lines.get(line_num.as_usize()) {
+                Some(line) => render_line(
+                    line,
+                    mps_by_line.get(&line_num).map(|v| &v[..]).unwrap_or(&[]),
+                    display_options.syntax_highlight,
+                    display_options.tab_width,
+                ),
+                None => "".to_owned(),
+            };
+
+            format!(
+                "<td class=\"line-num {side}-line-num{novel_class}\">{}</td><td class=\"{side}\">{}</td>",
+                line_num.display(),
+                content,
+                side = side,
+                novel_class = novel_class,
+            )
+        }
+        None => format!(
+            "<td class=\"line-num {side}-line-num missing\"></td><td class=\"{side} missing\"></td>",
+            side = side
+        ),
+    }
+}
+
+/// The header text for this hunk, without any terminal styling.
+fn header_text(
+    summary: &DiffResult,
+    hunk_num: usize,
+    hunk_total: usize,
+    file_format: &FileFormat,
+    display_options: &DisplayOptions,
+) -> String {
+    let plain_options = DisplayOptions {
+        use_color: false,
+        ..display_options.clone()
+    };
+    // Extra info is rendered separately, so it's not inside the heading.
+    style::header(
+        &summary.display_path,
+        None,
+        hunk_num,
+        hunk_total,
+        file_format,
+        None,
+        &plain_options,
+    )
+}
+
+/// The file heading, followed by any extra information about the file
+/// (e.g. renames or permission changes).
+fn render_header(
+    summary: &DiffResult,
+    file_format: &FileFormat,
+    display_options: &DisplayOptions,
+) -> String {
+    let mut res = format!(
+        "<h2>{}</h2>\n",
+        escape_html(&header_text(summary, 1, 1, file_format, display_options))
+    );
+    if let Some(extra_info) = &summary.extra_info {
+        res.push_str(&format!(
+            "<p class=\"extra-info\">{}</p>\n",
+            escape_html(extra_info)
+        ));
+    }
+    res
+}
+
+fn render_message(
+    summary: &DiffResult,
+    file_format: &FileFormat,
+    display_options: &DisplayOptions,
+    message: &str,
+) -> String {
+    let mut res = render_header(summary, file_format, display_options);
+    res.push_str(&format!("<p>{}</p>\n", escape_html(message)));
+    res
+}
+
+fn render_hunks(
+    summary: &DiffResult,
+    lhs_src: &str,
+    rhs_src: &str,
+    display_options: &DisplayOptions,
+) -> String {
+    let mut res = render_header(summary, &summary.file_format, display_options);
+
+    let lhs_lines = lhs_src.lines().collect::<Vec<_>>();
+    let rhs_lines = rhs_src.lines().collect::<Vec<_>>();
+
+    let lhs_mps_by_line = group_by_line(&summary.lhs_positions);
+    let rhs_mps_by_line = group_by_line(&summary.rhs_positions);
+    let (lhs_lines_with_novel, rhs_lines_with_novel) =
+        lines_with_novel(&summary.lhs_positions, &summary.rhs_positions);
+
+    let matched_lines = all_matched_lines_filled(
+        &summary.lhs_positions,
+        &summary.rhs_positions,
+        &lhs_lines,
+        &rhs_lines,
+    );
+    let mut matched_lines_to_print = &matched_lines[..];
+
+    let hunks = &summary.hunks;
+    for (i, hunk) in hunks.iter().enumerate() {
+        if hunks.len() > 1 {
+            res.push_str(&format!(
+                "<h3>{}</h3>\n",
+                escape_html(&header_text(
+                    summary,
+                    i + 1,
+                    hunks.len(),
+                    &summary.file_format,
+                    display_options
+                ))
+            ));
+        }
+
+        let context = hunk_context(hunk, &lhs_lines, &rhs_lines, display_options);
+        let (start_i, end_i) = matched_lines_indexes_for_hunk(
+            matched_lines_to_print,
+            hunk,
+            context.before,
+            context.after,
+        );
+        let aligned_lines = &matched_lines_to_print[start_i..end_i];
+        matched_lines_to_print = &matched_lines_to_print[start_i..];
+
+        res.push_str("<table class=\"hunk\">\n");
+        for (lhs_line_num, rhs_line_num) in aligned_lines {
+            res.push_str("<tr>");
+            res.push_str(&render_cells(
+                *lhs_line_num,
+                &lhs_lines,
+                &lhs_mps_by_line,
+                &lhs_lines_with_novel,
+                "lhs",
+                display_options,
+            ));
+            res.push_str(&render_cells(
+                *rhs_line_num,
+                &rhs_lines,
+                &rhs_mps_by_line,
+                &rhs_lines_with_novel,
+                "rhs",
+                display_options,
+            ));
+            res.push_str("</tr>\n");
+        }
+        res.push_str("</table>\n");
+    }
+
+    res
+}
+
+/// Render the body of the section for a single file.
+fn render_file(summary: &DiffResult, display_options: &DisplayOptions) -> String {
+    let file_display_options = display_options.for_file(&summary.file_format);
+    let display_options: &DisplayOptions = &file_display_options;
+    match (&summary.lhs_src, &summary.rhs_src) {
+        (FileContent::Text(lhs_src), FileContent::Text(rhs_src)) => {
+            if !summary.has_syntactic_changes {
+                let message = match summary.file_format {
+                    _ if summary.lhs_src == summary.rhs_src => "No changes.",
+                    FileFormat::SupportedLanguage(_) => "No syntactic changes.",
+                    _ => "No changes.",
+                };
+                return render_message(summary, &summary.file_format, display_options, message);
+            }
+
+            if summary.hunks.is_empty() {
+                let message = match summary.file_format {
+                    FileFormat::SupportedLanguage(_) => "Has syntactic changes.",
+                    _ => "Has changes.",
+                };
+                return render_message(summary, &summary.file_format, display_options, message);
+            }
+
+            render_hunks(summary, lhs_src, rhs_src, display_options)
+        }
+        (FileContent::Binary, FileContent::Binary) => render_message(
+            summary,
+            &FileFormat::Binary,
+            display_options,
+            &summary.binary_message(),
+        ),
+        (FileContent::Text(_), FileContent::Binary)
+        | (FileContent::Binary, FileContent::Text(_)) => render_message(
+            summary,
+            &FileFormat::Binary,
+            display_options,
+            "Binary contents changed.",
+        ),
+    }
+}
+
+/// Render `diffs` as a standalone HTML document. When there's more
+/// than one file, the document starts with an index linking to each
+/// file.
+pub(crate) fn render_document(diffs: &[DiffResult], display_options: &DisplayOptions) -> String {
+    let diffs: Vec<&DiffResult> = diffs
+        .iter()
+        .filter(|diff| display_options.print_unchanged || diff.has_reportable_change())
+        .collect();
+
+    let css = if display_options.background_color.is_dark() {
+        DARK_CSS
+    } else {
+        LIGHT_CSS
+    };
+
+    let title = match diffs.as_slice() {
+        [diff] => diff.display_path.clone(),
+        _ => "Difftastic".to_owned(),
+    };
+
+    let mut res = String::new();
+    res.push_str("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n");
+    res.push_str(&format!("<title>{}</title>\n", escape_html(&title)));
+    res.push_str(&format!("<style>{}</style>\n", css));
+    res.push_str("</head>\n<body>\n");
+
+    if diffs.len() > 1 {
+        res.push_str("<nav class=\"index\">\n<ul>\n");
+        for (i, diff) in diffs.iter().enumerate() {
+            res.push_str(&format!(
+                "<li><a href=\"#file-{}\">{}</a></li>\n",
+                i,
+                escape_html(&diff.display_path)
+            ));
+        }
+        res.push_str("</ul>\n</nav>\n");
+    }
+
+    for (i, diff) in diffs.iter().enumerate() {
+        res.push_str(&format!("<section class=\"file\" id=\"file-{}\">\n", i));
+        res.push_str(&render_file(diff, display_options));
+        res.push_str("</section>\n");
+    }
+
+    res.push_str("</body>\n</html>\n");
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use line_numbers::SingleLineSpan;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::{
+        display::{hunks::Hunk, long_lines::LineMap},
+        parse::guess_language::Language,
+    };
+
+    #[test]
+    fn test_escape_html() {
+        assert_eq!(
+            escape_html("<a href=\"x\">&'"),
+            "&lt;a href=&quot;x&quot;&gt;&amp;&#39;"
+        );
+    }
+
+    #[test]
+    fn test_render_line_spans() {
+        let mp = MatchedPos {
+            kind: MatchKind::Novel {
+                highlight: TokenKind::Atom(AtomKind::Keyword),
+                punctuation: false,
+            },
+            pos: SingleLineSpan {
+                line: 0.into(),
+                start_col: 0,
+                end_col: 2,
+            },
+        };
+
+        assert_eq!(
+            render_line("if x < 1", &[&mp], true, 4),
+            "<span class=\"novel keyword\">if</span> x &lt; 1"
+        );
+    }
+
+    #[test]
+    fn test_render_line_span_beyond_end() {
+        let mp = MatchedPos {
+            kind: MatchKind::Novel {
+                highlight: TokenKind::Atom(AtomKind::Normal),
+                punctuation: false,
+            },
+            pos: SingleLineSpan {
+                line: 0.into(),
+                start_col: 1,
+                end_col: 10,
+            },
+        };
+
+        assert_eq!(
+            render_line("foo", &[&mp], true, 4),
+            "f<span class=\"novel\">oo</span>"
+        );
+    }
+
+    #[test]
+    fn test_render_document() {
+        let novel_mp = MatchedPos {
+            kind: MatchKind::Novel {
+                highlight: TokenKind::Atom(AtomKind::Normal),
+                punctuation: false,
+            },
+            pos: SingleLineSpan {
+                line: 0.into(),
+                start_col: 0,
+                end_col: 3,
+            },
+        };
+
+        let mut novel_lhs = HashSet::new();
+        novel_lhs.insert(0.into());
+        let mut novel_rhs = HashSet::new();
+        novel_rhs.insert(0.into());
+
+        let diff = DiffResult {
+            display_path: "foo.el".to_owned(),
+            path_bytes: None,
+            renamed_from: None,
+            missing_side: None,
+            mode_change: None,
+            mtime_change: None,
+            binary_info: None,
+            extra_info: None,
+            file_format: FileFormat::SupportedLanguage(Language::EmacsLisp),
+            lhs_src: FileContent::Text("foo".to_owned()),
+            rhs_src: FileContent::Text("bar".to_owned()),
+            hunks: vec![Hunk {
+                novel_lhs,
+                novel_rhs,
+                lines: vec![(Some(0.into()), Some(0.into()))],
+                enclosing_declaration: None,
+            }],
+            lhs_positions: vec![novel_mp.clone()],
+            rhs_positions: vec![novel_mp],
+            lhs_line_map: LineMap::default(),
+            rhs_line_map: LineMap::default(),
+            degradation: None,
+            has_byte_changes: true,
+            has_syntactic_changes: true,
+        };
+
+        let html = render_document(&[diff], &DisplayOptions::default());
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("<td class=\"lhs\"><span class=\"novel\">foo</span></td>"));
+        assert!(html.contains("<td class=\"rhs\"><span class=\"novel\">bar</span></td>"));
+        // No index for a single file.
+        assert!(!html.contains("<nav"));
+    }
+}
@@ -5,29 +5,67 @@
 /// If we exceed this, the lines are stored in separate hunks.
 const MAX_DISTANCE: u32 = 4;
 
+/// The maximum number of lines in a declaration that we show in full
+/// with `--context function`. Longer declarations are truncated.
+const MAX_FUNCTION_CONTEXT_LINES: usize = 400;
+
 use std::collections::HashSet;
 
 use line_numbers::LineNumber;
 
 use crate::{
     constants::Side,
-    display::context::{add_context, opposite_positions},
+    display::context::{add_context, opposite_positions, structural_before_context},
+    display::long_lines::LineMap,
     display::side_by_side::lines_with_novel,
+    display::symbols::{enclosing_declaration, symbol},
     hash::DftHashMap,
+    options::{DisplayOptions, LineRanges},
     parse::syntax::{zip_pad_shorter, MatchKind, MatchedPos},
+    parse::tree_sitter_parser::Declaration,
 };
 
 /// A hunk represents a series of modified lines that are displayed
 /// together.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde-types", derive(serde::Serialize, serde::Deserialize))]
 pub(crate) struct Hunk {
     /// The LHS line numbers that contain novel content.
+    #[cfg_attr(
+        feature = "serde-types",
+        serde(with = "crate::serialize::line_number_set")
+    )]
     pub(crate) novel_lhs: HashSet<LineNumber>,
     /// The RHS line numbers that contain novel content.
+    #[cfg_attr(
+        feature = "serde-types",
+        serde(with = "crate::serialize::line_number_set")
+    )]
     pub(crate) novel_rhs: HashSet<LineNumber>,
     /// Line pairs that contain modified lines. This does not include
     /// padding, so at least one of the two lines has novel content.
+    #[cfg_attr(
+        feature = "serde-types",
+        serde(with = "crate::serialize::line_number_pairs")
+    )]
     pub(crate) lines: Vec<(Option<LineNumber>, Option<LineNumber>)>,
+    /// The innermost named declaration containing this hunk, if
+    /// known.
+    pub(crate) enclosing_declaration: Option<EnclosingDeclaration>,
+}
+
+/// A declaration, such as a function, that contains a hunk.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde-types", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct EnclosingDeclaration {
+    /// How to describe the declaration to the user, e.g. `fn foo`.
+    pub(crate) description: String,
+    /// The side that the line numbers refer to.
+    pub(crate) side: Side,
+    #[cfg_attr(feature = "serde-types", serde(with = "crate::serialize::line_number"))]
+    pub(crate) start_line: LineNumber,
+    #[cfg_attr(feature = "serde-types", serde(with = "crate::serialize::line_number"))]
+    pub(crate) end_line: LineNumber,
 }
 
 impl Hunk {
@@ -70,6 +108,9 @@ impl Hunk {
             novel_lhs: self.novel_lhs.union(&other.novel_lhs).copied().collect(),
             novel_rhs: self.novel_rhs.union(&other.novel_rhs).copied().collect(),
             lines: deduped_lines,
+            enclosing_declaration: self
+                .enclosing_declaration
+                .or_else(|| other.enclosing_declaration.clone()),
         }
     }
 }
@@ -136,7 +177,8 @@ pub(crate) fn merge_adjacent(
     opposite_to_rhs: &DftHashMap<LineNumber, HashSet<LineNumber>>,
     max_lhs_src_line: LineNumber,
     max_rhs_src_line: LineNumber,
-    num_context_lines: usize,
+    before_context_lines: usize,
+    after_context_lines: usize,
 ) -> Vec<Hunk> {
     let mut merged_hunks: Vec<Hunk> = vec![];
     let mut prev_hunk: Option<Hunk> = None;
@@ -155,7 +197,8 @@ pub(crate) fn merge_adjacent(
             opposite_to_rhs,
             max_lhs_src_line,
             max_rhs_src_line,
-            num_context_lines,
+            before_context_lines,
+            after_context_lines,
         );
         for (lhs_line, rhs_line) in contextual_lines {
             if let Some(lhs_line) = lhs_line {
@@ -199,6 +242,320 @@ pub(crate) fn merge_adjacent(
     merged_hunks
 }
 
+/// Whether there are at most `max_gap` unchanged lines between the
+/// end of `prev` and the start of `next`, on every side that both
+/// hunks have lines on.
+fn within_gap(prev: &Hunk, next: &Hunk, max_gap: u32) -> bool {
+    let sides = [
+        (
+            prev.lines.iter().filter_map(|(lhs, _)| *lhs).max(),
+            next.lines.iter().filter_map(|(lhs, _)| *lhs).min(),
+        ),
+        (
+            prev.lines.iter().filter_map(|(_, rhs)| *rhs).max(),
+            next.lines.iter().filter_map(|(_, rhs)| *rhs).min(),
+        ),
+    ];
+
+    let mut compared_any = false;
+    for (prev_max, next_min) in sides {
+        if let (Some(prev_max), Some(next_min)) = (prev_max, next_min) {
+            if next_min.0 > prev_max.0 + max_gap + 1 {
+                return false;
+            }
+            compared_any = true;
+        }
+    }
+
+    compared_any
+}
+
+/// Merge consecutive hunks that are separated by at most `max_gap`
+/// unchanged lines, so they're displayed under a single header.
+pub(crate) fn merge_close(hunks: Vec<Hunk>, max_gap: u32) -> Vec<Hunk> {
+    let mut merged_hunks: Vec<Hunk> = vec![];
+
+    for hunk in hunks {
+        match merged_hunks.pop() {
+            Some(prev_hunk) if within_gap(&prev_hunk, &hunk, max_gap) => {
+                merged_hunks.push(prev_hunk.merge(&hunk));
+            }
+            Some(prev_hunk) => {
+                merged_hunks.push(prev_hunk);
+                merged_hunks.push(hunk);
+            }
+            None => merged_hunks.push(hunk),
+        }
+    }
+
+    merged_hunks
+}
+
+/// The declaration containing the first changed line of `hunk`. We
+/// use the RHS, or the LHS for hunks that only remove lines.
+fn hunk_declaration(
+    hunk: &Hunk,
+    lhs_declarations: &[Declaration],
+    rhs_declarations: &[Declaration],
+) -> Option<EnclosingDeclaration> {
+    let (side, declaration) = match hunk.novel_rhs.iter().min() {
+        Some(rhs_line) => (
+            Side::Right,
+            enclosing_declaration(rhs_declarations, *rhs_line)?,
+        ),
+        None => (
+            Side::Left,
+            enclosing_declaration(lhs_declarations, *hunk.novel_lhs.iter().min()?)?,
+        ),
+    };
+
+    Some(EnclosingDeclaration {
+        description: symbol(declaration),
+        side,
+        start_line: (declaration.start_line as u32).into(),
+        end_line: (declaration.end_line as u32).into(),
+    })
+}
+
+/// `declarations` with display lines rather than source lines, which
+/// differ if we split long lines.
+fn display_declarations(declarations: &[Declaration], line_map: &LineMap) -> Vec<Declaration> {
+    declarations
+        .iter()
+        .map(|d| Declaration {
+            start_line: line_map
+                .display_line((d.start_line as u32).into(), d.start_col)
+                .as_usize(),
+            end_line: line_map
+                .display_line((d.end_line as u32).into(), d.end_col.saturating_sub(1))
+                .as_usize(),
+            ..d.clone()
+        })
+        .collect()
+}
+
+/// Record the enclosing declaration of each hunk, so it can be shown
+/// in the hunk header. The declarations are from the source files,
+/// see [display_declarations].
+pub(crate) fn set_enclosing_declarations(
+    hunks: &mut [Hunk],
+    lhs_declarations: &[Declaration],
+    rhs_declarations: &[Declaration],
+    lhs_line_map: &LineMap,
+    rhs_line_map: &LineMap,
+) {
+    let lhs_declarations = display_declarations(lhs_declarations, lhs_line_map);
+    let rhs_declarations = display_declarations(rhs_declarations, rhs_line_map);
+
+    for hunk in hunks {
+        hunk.enclosing_declaration = hunk_declaration(hunk, &lhs_declarations, &rhs_declarations);
+    }
+}
+
+/// Whether `line` immediately follows `prev` on either side, so
+/// they're part of the same contiguous change.
+fn is_contiguous(
+    prev: (Option<LineNumber>, Option<LineNumber>),
+    line: (Option<LineNumber>, Option<LineNumber>),
+) -> bool {
+    let follows = |prev: Option<LineNumber>, line: Option<LineNumber>| match (prev, line) {
+        (Some(prev), Some(line)) => line.0 <= prev.0 + 1,
+        _ => false,
+    };
+    follows(prev.0, line.0) || follows(prev.1, line.1)
+}
+
+/// The number of lines needed to display `lines`, from the first
+/// line to the last line on the longer side.
+fn displayed_len(lines: &[(Option<LineNumber>, Option<LineNumber>)]) -> usize {
+    let side_len = |side_lines: Vec<LineNumber>| match (side_lines.first(), side_lines.last()) {
+        (Some(first), Some(last)) => (last.0 - first.0) as usize + 1,
+        _ => 0,
+    };
+
+    std::cmp::max(
+        side_len(lines.iter().filter_map(|(lhs, _)| *lhs).collect()),
+        side_len(lines.iter().filter_map(|(_, rhs)| *rhs).collect()),
+    )
+}
+
+/// Split `hunk` into hunks that each span at most `max_lines`
+/// lines. We only split between contiguous changes, so a single
+/// change longer than `max_lines` stays in one hunk.
+fn split_hunk(hunk: Hunk, max_lines: usize) -> Vec<Hunk> {
+    if displayed_len(&hunk.lines) <= max_lines {
+        return vec![hunk];
+    }
+
+    let mut regions: Vec<Vec<(Option<LineNumber>, Option<LineNumber>)>> = vec![];
+    for line in &hunk.lines {
+        match regions.last_mut() {
+            Some(region) if is_contiguous(*region.last().unwrap(), *line) => region.push(*line),
+            _ => regions.push(vec![*line]),
+        }
+    }
+
+    let mut split_lines: Vec<Vec<(Option<LineNumber>, Option<LineNumber>)>> = vec![];
+    for region in regions {
+        match split_lines.last_mut() {
+            Some(current) if displayed_len(&[&current[..], &region[..]].concat()) <= max_lines => {
+                current.extend(region);
+            }
+            _ => split_lines.push(region),
+        }
+    }
+
+    split_lines
+        .into_iter()
+        .map(|lines| {
+            let (novel_lhs, novel_rhs) = find_novel_lines(&lines, &hunk.novel_lhs, &hunk.novel_rhs);
+            Hunk {
+                novel_lhs,
+                novel_rhs,
+                lines,
+                enclosing_declaration: hunk.enclosing_declaration.clone(),
+            }
+        })
+        .collect()
+}
+
+/// Split hunks that span more than `max_lines` lines, so very large
+/// changes are shown as several smaller hunks.
+pub(crate) fn split_large(hunks: Vec<Hunk>, max_lines: usize) -> Vec<Hunk> {
+    hunks
+        .into_iter()
+        .flat_map(|hunk| split_hunk(hunk, max_lines))
+        .collect()
+}
+
+/// Drop hunks that don't change any lines in `line_ranges`, for
+/// `--lines`. The ranges are lines in the original files, so we map
+/// hunk lines with `lhs_line_map` and `rhs_line_map` if long lines
+/// were split for display.
+pub(crate) fn in_line_ranges(
+    hunks: Vec<Hunk>,
+    line_ranges: &LineRanges,
+    lhs_line_map: &LineMap,
+    rhs_line_map: &LineMap,
+) -> Vec<Hunk> {
+    let in_range = |line: Option<LineNumber>, range: Option<(usize, usize)>| match (line, range) {
+        (Some(line), Some((start, end))) => (start..=end).contains(&(line.as_usize() + 1)),
+        _ => false,
+    };
+
+    hunks
+        .into_iter()
+        .filter(|hunk| {
+            hunk.lines.iter().any(|(lhs_line, rhs_line)| {
+                in_range(
+                    lhs_line.map(|line| lhs_line_map.source_line(line)),
+                    line_ranges.lhs,
+                ) || in_range(
+                    rhs_line.map(|line| rhs_line_map.source_line(line)),
+                    Some(line_ranges.rhs),
+                )
+            })
+        })
+        .collect()
+}
+
+/// Merge consecutive hunks that are inside the same declaration, as
+/// `--context function` shows them together anyway.
+pub(crate) fn merge_same_declaration(hunks: Vec<Hunk>) -> Vec<Hunk> {
+    let mut merged_hunks: Vec<Hunk> = vec![];
+
+    for hunk in hunks {
+        match merged_hunks.pop() {
+            Some(prev_hunk)
+                if prev_hunk.enclosing_declaration.is_some()
+                    && prev_hunk.enclosing_declaration == hunk.enclosing_declaration =>
+            {
+                merged_hunks.push(prev_hunk.merge(&hunk));
+            }
+            Some(prev_hunk) => {
+                merged_hunks.push(prev_hunk);
+                merged_hunks.push(hunk);
+            }
+            None => merged_hunks.push(hunk),
+        }
+    }
+
+    merged_hunks
+}
+
+/// The number of context lines to show around a hunk.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct HunkContext {
+    pub(crate) before: usize,
+    pub(crate) after: usize,
+    /// Whether part of the enclosing declaration before the hunk
+    /// isn't shown, because the declaration is too long.
+    pub(crate) elided_before: bool,
+    /// Whether part of the enclosing declaration after the hunk
+    /// isn't shown.
+    pub(crate) elided_after: bool,
+}
+
+/// The context to show around `hunk`. This is the number of lines
+/// requested with `--before-context` and `--after-context`, or enough
+/// to cover the enclosing declaration with `--context function`.
+pub(crate) fn hunk_context(
+    hunk: &Hunk,
+    lhs_lines: &[&str],
+    rhs_lines: &[&str],
+    display_options: &DisplayOptions,
+) -> HunkContext {
+    let mut before = display_options.before_context_lines as usize;
+    if display_options.structural_context {
+        // Context is calculated from the first line of the hunk,
+        // preferring the LHS, so look at that line.
+        before = match hunk.lines.first() {
+            Some((Some(lhs_line), _)) => structural_before_context(lhs_lines, *lhs_line, before),
+            Some((_, Some(rhs_line))) => structural_before_context(rhs_lines, *rhs_line, before),
+            _ => before,
+        };
+    }
+
+    let mut context = HunkContext {
+        before,
+        after: display_options.after_context_lines as usize,
+        elided_before: false,
+        elided_after: false,
+    };
+    if !display_options.function_context {
+        return context;
+    }
+
+    let declaration = match &hunk.enclosing_declaration {
+        Some(declaration) => declaration,
+        None => return context,
+    };
+    let novel_lines = match declaration.side {
+        Side::Left => &hunk.novel_lhs,
+        Side::Right => &hunk.novel_rhs,
+    };
+    let (first_novel, last_novel) = match (novel_lines.iter().min(), novel_lines.iter().max()) {
+        (Some(first_novel), Some(last_novel)) => (first_novel.as_usize(), last_novel.as_usize()),
+        _ => return context,
+    };
+
+    let mut before = first_novel.saturating_sub(declaration.start_line.as_usize());
+    let mut after = declaration.end_line.as_usize().saturating_sub(last_novel);
+
+    let declaration_len = declaration.end_line.as_usize() - declaration.start_line.as_usize() + 1;
+    if declaration_len > MAX_FUNCTION_CONTEXT_LINES {
+        let max_each_side = MAX_FUNCTION_CONTEXT_LINES / 2;
+        context.elided_before = before > max_each_side;
+        context.elided_after = after > max_each_side;
+        before = std::cmp::min(before, max_each_side);
+        after = std::cmp::min(after, max_each_side);
+    }
+
+    context.before = std::cmp::max(context.before, before);
+    context.after = std::cmp::max(context.after, after);
+    context
+}
+
 fn lines_are_close(
     max_lhs: Option<LineNumber>,
     max_rhs: Option<LineNumber>,
@@ -320,6 +677,7 @@ fn lines_to_hunks(
                 novel_lhs,
                 novel_rhs,
                 lines: current_hunk_lines,
+                enclosing_declaration: None,
             });
             current_hunk_lines = vec![line];
         }
@@ -339,6 +697,7 @@ fn lines_to_hunks(
             novel_lhs,
             novel_rhs,
             lines: current_hunk_lines,
+            enclosing_declaration: None,
         });
     }
 
@@ -626,7 +985,8 @@ fn either_side_equal(
 pub(crate) fn matched_lines_indexes_for_hunk(
     matched_lines: &[(Option<LineNumber>, Option<LineNumber>)],
     hunk: &Hunk,
-    num_context_lines: usize,
+    before_context_lines: usize,
+    after_context_lines: usize,
 ) -> (usize, usize) {
     let mut hunk_lhs_novel = hunk.novel_lhs.iter().copied().collect::<Vec<_>>();
     hunk_lhs_novel.sort();
@@ -662,14 +1022,14 @@ pub(crate) fn matched_lines_indexes_for_hunk(
 
     let mut start_i = start_i.expect("Hunk lines should be present in matched lines");
     let mut end_i = end_i.expect("Hunk lines should be present in matched lines");
-    if start_i >= num_context_lines {
-        start_i -= num_context_lines;
+    if start_i >= before_context_lines {
+        start_i -= before_context_lines;
     } else {
         start_i = 0;
     }
 
-    if end_i + num_context_lines < matched_lines.len() {
-        end_i += num_context_lines
+    if end_i + after_context_lines < matched_lines.len() {
+        end_i += after_context_lines
     } else {
         end_i = matched_lines.len();
     }
@@ -695,6 +1055,7 @@ mod tests {
         let novel_mp = MatchedPos {
             kind: MatchKind::Novel {
                 highlight: TokenKind::Delimiter,
+                punctuation: false,
             },
             pos: SingleLineSpan {
                 line: 0.into(),
@@ -746,6 +1107,7 @@ mod tests {
             MatchedPos {
                 kind: MatchKind::Novel {
                     highlight: TokenKind::Delimiter,
+                    punctuation: false,
                 },
                 pos: SingleLineSpan {
                     line: 0.into(),
@@ -767,6 +1129,7 @@ mod tests {
             MatchedPos {
                 kind: MatchKind::Novel {
                     highlight: TokenKind::Delimiter,
+                    punctuation: false,
                 },
                 pos: SingleLineSpan {
                     line: 0.into(),
@@ -793,7 +1156,7 @@ mod tests {
         matched_lines: &'a [(Option<LineNumber>, Option<LineNumber>)],
         hunk: &Hunk,
     ) -> &'a [(Option<LineNumber>, Option<LineNumber>)] {
-        let (start_i, end_i) = matched_lines_indexes_for_hunk(matched_lines, hunk, 3);
+        let (start_i, end_i) = matched_lines_indexes_for_hunk(matched_lines, hunk, 3, 3);
         &matched_lines[start_i..end_i]
     }
 
@@ -811,6 +1174,7 @@ mod tests {
             novel_lhs,
             novel_rhs,
             lines: vec![(Some(1.into()), Some(1.into()))],
+            enclosing_declaration: None,
         };
 
         let res = matched_lines_for_hunk(matched_lines, &hunk);
@@ -842,6 +1206,7 @@ mod tests {
             novel_rhs,
             // LHS and RHS are misaligned
             lines: vec![(Some(1.into()), Some(2.into()))],
+            enclosing_declaration: None,
         };
 
         let res = matched_lines_for_hunk(matched_lines, &hunk);
@@ -860,4 +1225,302 @@ mod tests {
             ]
         );
     }
+
+    /// Two hunks, on line 2 and line 8, with unchanged lines between
+    /// them.
+    fn separated_hunks() -> (Vec<Hunk>, DftHashMap<LineNumber, HashSet<LineNumber>>) {
+        let hunks: Vec<Hunk> = [2, 8]
+            .iter()
+            .map(|line: &u32| Hunk {
+                novel_lhs: HashSet::from_iter([(*line).into()]),
+                novel_rhs: HashSet::from_iter([(*line).into()]),
+                lines: vec![(Some((*line).into()), Some((*line).into()))],
+                enclosing_declaration: None,
+            })
+            .collect();
+        let opposite: DftHashMap<LineNumber, HashSet<LineNumber>> = (0..12)
+            .filter(|i| *i != 2 && *i != 8)
+            .map(|i: u32| (i.into(), HashSet::from_iter([i.into()])))
+            .collect();
+
+        (hunks, opposite)
+    }
+
+    #[test]
+    fn test_merge_adjacent_asymmetric_context() {
+        let (hunks, opposite) = separated_hunks();
+        let merge = |before_context_lines, after_context_lines| {
+            merge_adjacent(
+                &hunks,
+                &opposite,
+                &opposite,
+                11.into(),
+                11.into(),
+                before_context_lines,
+                after_context_lines,
+            )
+            .len()
+        };
+
+        // The hunks are merged when the after context of the first
+        // hunk and the before context of the second hunk overlap.
+        assert_eq!(merge(0, 3), 2);
+        assert_eq!(merge(3, 0), 2);
+        assert_eq!(merge(1, 3), 1);
+        assert_eq!(merge(4, 0), 1);
+        assert_eq!(merge(2, 2), 1);
+    }
+
+    #[test]
+    fn test_in_line_ranges() {
+        let (hunks, _) = separated_hunks();
+        let num_hunks = |lhs, rhs| {
+            in_line_ranges(
+                hunks.clone(),
+                &LineRanges { lhs, rhs },
+                &LineMap::default(),
+                &LineMap::default(),
+            )
+            .len()
+        };
+
+        // The hunks change lines 3 and 9, counting from 1.
+        assert_eq!(num_hunks(None, (1, 5)), 1);
+        assert_eq!(num_hunks(None, (4, 8)), 0);
+        assert_eq!(num_hunks(Some((9, 9)), (1, 3)), 2);
+        assert_eq!(num_hunks(None, (100, 200)), 0);
+    }
+
+    #[test]
+    fn test_matched_lines_for_hunk_asymmetric_context() {
+        let matched_lines: Vec<(Option<LineNumber>, Option<LineNumber>)> = (0..10)
+            .map(|i: u32| (Some(i.into()), Some(i.into())))
+            .collect();
+        let hunk = Hunk {
+            novel_lhs: HashSet::from_iter([5.into()]),
+            novel_rhs: HashSet::from_iter([5.into()]),
+            lines: vec![(Some(5.into()), Some(5.into()))],
+            enclosing_declaration: None,
+        };
+
+        assert_eq!(
+            matched_lines_indexes_for_hunk(&matched_lines, &hunk, 1, 3),
+            (4, 9)
+        );
+        assert_eq!(
+            matched_lines_indexes_for_hunk(&matched_lines, &hunk, 0, 0),
+            (5, 6)
+        );
+    }
+
+    #[test]
+    fn test_merge_close() {
+        let hunk = |lhs: u32, rhs: u32| Hunk {
+            novel_lhs: HashSet::from_iter([lhs.into()]),
+            novel_rhs: HashSet::from_iter([rhs.into()]),
+            lines: vec![(Some(lhs.into()), Some(rhs.into()))],
+            enclosing_declaration: None,
+        };
+
+        // Three unchanged lines between the hunks on both sides.
+        assert_eq!(merge_close(vec![hunk(2, 2), hunk(6, 6)], 2).len(), 2);
+
+        let merged = merge_close(vec![hunk(2, 2), hunk(6, 6)], 3);
+        assert_eq!(merged.len(), 1);
+        assert_eq!(
+            merged[0].lines,
+            vec![
+                (Some(2.into()), Some(2.into())),
+                (Some(6.into()), Some(6.into()))
+            ]
+        );
+        assert_eq!(
+            merged[0].novel_lhs,
+            HashSet::from_iter([2.into(), 6.into()])
+        );
+    }
+
+    #[test]
+    fn test_merge_close_requires_both_sides() {
+        let prev = Hunk {
+            novel_lhs: HashSet::from_iter([2.into()]),
+            novel_rhs: HashSet::from_iter([2.into()]),
+            lines: vec![(Some(2.into()), Some(2.into()))],
+            enclosing_declaration: None,
+        };
+        // Close on the LHS, but far away on the RHS.
+        let next = Hunk {
+            novel_lhs: HashSet::from_iter([4.into()]),
+            novel_rhs: HashSet::from_iter([20.into()]),
+            lines: vec![(Some(4.into()), Some(20.into()))],
+            enclosing_declaration: None,
+        };
+
+        assert_eq!(merge_close(vec![prev, next], 3).len(), 2);
+    }
+
+    #[test]
+    fn test_merge_close_one_sided() {
+        // A removal followed by an insertion.
+        let prev = Hunk {
+            novel_lhs: HashSet::from_iter([2.into()]),
+            novel_rhs: HashSet::new(),
+            lines: vec![(Some(2.into()), None)],
+            enclosing_declaration: None,
+        };
+        let next = Hunk {
+            novel_lhs: HashSet::new(),
+            novel_rhs: HashSet::from_iter([3.into()]),
+            lines: vec![(None, Some(3.into()))],
+            enclosing_declaration: None,
+        };
+
+        // No side has lines in both hunks, so there's nothing to
+        // measure the gap with.
+        assert_eq!(merge_close(vec![prev, next], 10).len(), 2);
+    }
+
+    #[test]
+    fn test_hunk_declaration() {
+        let declaration = |name: &str, start_line, end_line| Declaration {
+            label: "fn",
+            name: name.to_owned(),
+            start_line,
+            end_line,
+            start_col: 0,
+            end_col: 0,
+        };
+        let lhs_declarations = vec![declaration("old", 0, 5)];
+        let rhs_declarations = vec![declaration("outer", 0, 10), declaration("inner", 2, 4)];
+
+        let mut hunk = Hunk {
+            novel_lhs: HashSet::from_iter([3.into()]),
+            novel_rhs: HashSet::from_iter([3.into(), 8.into()]),
+            lines: vec![],
+            enclosing_declaration: None,
+        };
+        assert_eq!(
+            hunk_declaration(&hunk, &lhs_declarations, &rhs_declarations),
+            Some(EnclosingDeclaration {
+                description: "fn inner".to_owned(),
+                side: Side::Right,
+                start_line: 2.into(),
+                end_line: 4.into(),
+            })
+        );
+
+        // Removals use the LHS.
+        hunk.novel_rhs = HashSet::new();
+        assert_eq!(
+            hunk_declaration(&hunk, &lhs_declarations, &rhs_declarations)
+                .map(|declaration| declaration.description),
+            Some("fn old".to_owned())
+        );
+
+        // Changes outside declarations have no name.
+        hunk.novel_lhs = HashSet::from_iter([7.into()]);
+        assert_eq!(
+            hunk_declaration(&hunk, &lhs_declarations, &rhs_declarations),
+            None
+        );
+    }
+
+    fn hunk_in_declaration(line: u32, start_line: u32, end_line: u32) -> Hunk {
+        Hunk {
+            novel_lhs: HashSet::new(),
+            novel_rhs: HashSet::from_iter([line.into()]),
+            lines: vec![(None, Some(line.into()))],
+            enclosing_declaration: Some(EnclosingDeclaration {
+                description: "fn foo".to_owned(),
+                side: Side::Right,
+                start_line: start_line.into(),
+                end_line: end_line.into(),
+            }),
+        }
+    }
+
+    fn hunk_context_for(hunk: &Hunk, display_options: &DisplayOptions) -> HunkContext {
+        hunk_context(hunk, &[], &[], display_options)
+    }
+
+    #[test]
+    fn test_hunk_context_function() {
+        let display_options = DisplayOptions {
+            function_context: true,
+            ..DisplayOptions::default()
+        };
+
+        assert_eq!(
+            hunk_context_for(&hunk_in_declaration(20, 10, 50), &display_options),
+            HunkContext {
+                before: 10,
+                after: 30,
+                elided_before: false,
+                elided_after: false,
+            }
+        );
+        // We still show the usual context near the start of a
+        // declaration.
+        assert_eq!(
+            hunk_context_for(&hunk_in_declaration(11, 10, 11), &display_options).before,
+            3
+        );
+    }
+
+    #[test]
+    fn test_hunk_context_function_too_long() {
+        let display_options = DisplayOptions {
+            function_context: true,
+            ..DisplayOptions::default()
+        };
+
+        assert_eq!(
+            hunk_context_for(&hunk_in_declaration(1000, 0, 1010), &display_options),
+            HunkContext {
+                before: MAX_FUNCTION_CONTEXT_LINES / 2,
+                after: 10,
+                elided_before: true,
+                elided_after: false,
+            }
+        );
+    }
+
+    #[test]
+    fn test_merge_same_declaration() {
+        let hunks = vec![
+            hunk_in_declaration(2, 0, 20),
+            hunk_in_declaration(15, 0, 20),
+            hunk_in_declaration(25, 22, 30),
+        ];
+
+        assert_eq!(merge_same_declaration(hunks).len(), 2);
+    }
+
+    #[test]
+    fn test_split_large() {
+        // Two contiguous changes, on lines 0-2 and 10-11.
+        let lines: Vec<(Option<LineNumber>, Option<LineNumber>)> = [0, 1, 2, 10, 11]
+            .iter()
+            .map(|i: &u32| (Some((*i).into()), Some((*i).into())))
+            .collect();
+        let hunk = Hunk {
+            novel_lhs: lines.iter().filter_map(|(lhs, _)| *lhs).collect(),
+            novel_rhs: lines.iter().filter_map(|(_, rhs)| *rhs).collect(),
+            lines,
+            enclosing_declaration: None,
+        };
+
+        assert_eq!(split_large(vec![hunk.clone()], 20).len(), 1);
+
+        let split = split_large(vec![hunk.clone()], 5);
+        assert_eq!(split.len(), 2);
+        assert_eq!(split[0].lines.len(), 3);
+        assert_eq!(
+            split[1].novel_lhs,
+            HashSet::from_iter([10.into(), 11.into()])
+        );
+
+        // A contiguous change is never split, even if it's too long.
+        assert_eq!(split_large(vec![hunk], 1).len(), 2);
+    }
 }
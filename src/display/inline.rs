@@ -1,17 +1,39 @@
 //! Inline, or "unified" diff display.
 
+use std::io::Write;
+
+use line_numbers::LineNumber;
+
 use crate::{
     constants::Side,
+    diff::moves::{copied_block_starts, moved_block_starts},
     display::context::{calculate_after_context, calculate_before_context, opposite_positions},
-    display::hunks::Hunk,
-    display::style::{self, apply_colors, apply_line_number_color},
-    lines::{format_line_num, MaxLine},
+    display::hunks::{hunk_context, Hunk},
+    display::long_lines::LineMap,
+    display::style::{
+        self, apply_colors, apply_line_number_color, novel_line_marker, SyntaxHighlights,
+    },
+    lines::{missing_final_newline, MaxLine},
     options::DisplayOptions,
     parse::syntax::MatchedPos,
     summary::FileFormat,
 };
 
+/// The line number and colored line for `line_num`, if the file has
+/// that line. Context can extend past the end of a short file, e.g.
+/// an empty file has no lines at all, so we show nothing.
+fn colored_line(
+    colored_lines: &[String],
+    line_num: Option<LineNumber>,
+) -> Option<(LineNumber, &str)> {
+    let line_num = line_num?;
+    colored_lines
+        .get(line_num.as_usize())
+        .map(|line| (line_num, line.as_str()))
+}
+
 pub(crate) fn print(
+    out: &mut dyn Write,
     lhs_src: &str,
     rhs_src: &str,
     display_options: &DisplayOptions,
@@ -21,23 +43,53 @@ pub(crate) fn print(
     display_path: &str,
     extra_info: &Option<String>,
     file_format: &FileFormat,
-) {
-    let (lhs_colored_lines, rhs_colored_lines) = if display_options.use_color {
+    lhs_line_map: &LineMap,
+    rhs_line_map: &LineMap,
+) -> std::io::Result<()> {
+    let opposite_to_lhs = opposite_positions(lhs_positions);
+    let opposite_to_rhs = opposite_positions(rhs_positions);
+
+    let (lhs_colored_lines, rhs_colored_lines) = if display_options.style_content() {
+        let lhs_syntax_highlights = SyntaxHighlights::new(
+            lhs_src,
+            Side::Left,
+            file_format,
+            hunks,
+            &opposite_to_lhs,
+            &opposite_to_rhs,
+            display_options,
+        );
+        let rhs_syntax_highlights = SyntaxHighlights::new(
+            rhs_src,
+            Side::Right,
+            file_format,
+            hunks,
+            &opposite_to_lhs,
+            &opposite_to_rhs,
+            display_options,
+        );
+
         (
             apply_colors(
                 lhs_src,
                 Side::Left,
                 display_options.syntax_highlight,
+                display_options.dim_punctuation,
+                display_options.trailing_whitespace,
                 file_format,
-                display_options.background_color,
+                &display_options.palette,
+                &lhs_syntax_highlights,
                 lhs_positions,
             ),
             apply_colors(
                 rhs_src,
                 Side::Right,
                 display_options.syntax_highlight,
+                display_options.dim_punctuation,
+                display_options.trailing_whitespace,
                 file_format,
-                display_options.background_color,
+                &display_options.palette,
+                &rhs_syntax_highlights,
                 rhs_positions,
             ),
         )
@@ -57,11 +109,39 @@ pub(crate) fn print(
         .map(|line| style::replace_tabs(&line, display_options.tab_width))
         .collect();
 
-    let opposite_to_lhs = opposite_positions(lhs_positions);
-    let opposite_to_rhs = opposite_positions(rhs_positions);
+    let lhs_lines: Vec<&str> = lhs_src.lines().collect();
+    let rhs_lines: Vec<&str> = rhs_src.lines().collect();
+    // Each hunk's after context stops at the end of the file, so find
+    // the last line once rather than scanning the source per hunk.
+    let lhs_max_line = lhs_src.max_line();
+    let rhs_max_line = rhs_src.max_line();
+    let lhs_missing_newline = missing_final_newline(lhs_src, rhs_src);
+    let rhs_missing_newline = missing_final_newline(rhs_src, lhs_src);
+
+    let mut lhs_moves = moved_block_starts(lhs_positions);
+    let mut rhs_moves = moved_block_starts(rhs_positions);
+    let mut rhs_copies = copied_block_starts(rhs_positions);
+    // Annotations name lines in the opposite file, so show the line
+    // numbers from the original file.
+    for opposite_line in lhs_moves.values_mut() {
+        *opposite_line = rhs_line_map.source_line(*opposite_line);
+    }
+    for opposite_line in rhs_moves.values_mut() {
+        *opposite_line = lhs_line_map.source_line(*opposite_line);
+    }
+    for (source_line, _) in rhs_copies.values_mut() {
+        *source_line = lhs_line_map.source_line(*source_line);
+    }
 
     for (i, hunk) in hunks.iter().enumerate() {
-        println!(
+        // Skip hunks that weren't selected with --hunk, but keep the
+        // numbering so headers still count all the hunks.
+        if !display_options.shows_hunk(i + 1) {
+            continue;
+        }
+
+        writeln!(
+            out,
             "{}",
             style::header(
                 display_path,
@@ -69,82 +149,302 @@ pub(crate) fn print(
                 i + 1,
                 hunks.len(),
                 file_format,
+                hunk.enclosing_declaration
+                    .as_ref()
+                    .map(|declaration| declaration.description.as_str()),
                 display_options
             )
-        );
+        )?;
 
         let hunk_lines = hunk.lines.clone();
+        let context = hunk_context(hunk, &lhs_lines, &rhs_lines, display_options);
 
-        let before_lines =
-            calculate_before_context(&hunk_lines, &opposite_to_lhs, &opposite_to_rhs, 3);
+        let before_lines = calculate_before_context(
+            &hunk_lines,
+            &opposite_to_lhs,
+            &opposite_to_rhs,
+            context.before,
+        );
         let after_lines = calculate_after_context(
             &[&before_lines[..], &hunk_lines[..]].concat(),
             &opposite_to_lhs,
             &opposite_to_rhs,
-            // TODO: repeatedly calculating the maximum is wasteful.
-            lhs_src.max_line(),
-            rhs_src.max_line(),
-            display_options.num_context_lines as usize,
+            lhs_max_line,
+            rhs_max_line,
+            context.after,
         );
 
+        if display_options.hunk_ranges {
+            writeln!(
+                out,
+                "{}",
+                style::hunk_range_marker(
+                    &[&before_lines[..], &hunk_lines[..], &after_lines[..]].concat(),
+                    lhs_line_map,
+                    rhs_line_map,
+                    i + 1,
+                    display_options
+                )
+            )?;
+        }
+        if context.elided_before {
+            writeln!(out, "{}", style::elision_marker(display_options))?;
+        }
+        // The last line shown on each side, so a split long line
+        // only shows its line number on its first part.
+        let mut prev_lhs_line = None;
+        let mut prev_rhs_line = None;
         for (lhs_line, _) in before_lines {
-            if let Some(lhs_line) = lhs_line {
-                print!(
-                    "{}   {}",
+            if let Some((lhs_line, colored)) = colored_line(&lhs_colored_lines, lhs_line) {
+                write!(
+                    out,
+                    "{}{}   {}",
+                    novel_line_marker(false, Side::Left, display_options),
                     apply_line_number_color(
-                        &format_line_num(lhs_line),
+                        &format!("{} ", lhs_line_map.display(lhs_line, prev_lhs_line)),
                         false,
                         Side::Left,
                         display_options,
                     ),
-                    lhs_colored_lines[lhs_line.as_usize()]
-                );
+                    colored
+                )?;
+                if Some(lhs_line) == lhs_missing_newline {
+                    writeln!(out, "{}", style::no_newline_marker(None, display_options))?;
+                }
+                prev_lhs_line = Some(lhs_line);
             }
         }
 
         for (lhs_line, _) in &hunk_lines {
-            if let Some(lhs_line) = lhs_line {
-                print!(
-                    "{}   {}",
+            if let Some((lhs_line, colored)) = colored_line(&lhs_colored_lines, *lhs_line) {
+                if let Some(annotation) = style::move_annotation(
+                    Some(lhs_line),
+                    None,
+                    &lhs_moves,
+                    &rhs_moves,
+                    display_options,
+                ) {
+                    writeln!(out, "{}", annotation)?;
+                }
+                write!(
+                    out,
+                    "{}{}   {}",
+                    novel_line_marker(true, Side::Left, display_options),
                     apply_line_number_color(
-                        &format_line_num(*lhs_line),
+                        &format!("{} ", lhs_line_map.display(lhs_line, prev_lhs_line)),
                         true,
                         Side::Left,
                         display_options,
                     ),
-                    lhs_colored_lines[lhs_line.as_usize()]
-                );
+                    colored
+                )?;
+                if Some(lhs_line) == lhs_missing_newline {
+                    writeln!(out, "{}", style::no_newline_marker(None, display_options))?;
+                }
+                prev_lhs_line = Some(lhs_line);
             }
         }
         for (_, rhs_line) in &hunk_lines {
-            if let Some(rhs_line) = rhs_line {
-                print!(
-                    "   {}{}",
+            if let Some((rhs_line, colored)) = colored_line(&rhs_colored_lines, *rhs_line) {
+                if let Some(annotation) = style::move_annotation(
+                    None,
+                    Some(rhs_line),
+                    &lhs_moves,
+                    &rhs_moves,
+                    display_options,
+                ) {
+                    writeln!(out, "{}", annotation)?;
+                }
+                if let Some(annotation) =
+                    style::copy_annotation(Some(rhs_line), &rhs_copies, display_options)
+                {
+                    writeln!(out, "{}", annotation)?;
+                }
+                write!(
+                    out,
+                    "   {}{}{}",
+                    novel_line_marker(true, Side::Right, display_options),
                     apply_line_number_color(
-                        &format_line_num(*rhs_line),
+                        &format!("{} ", rhs_line_map.display(rhs_line, prev_rhs_line)),
                         true,
                         Side::Right,
                         display_options,
                     ),
-                    rhs_colored_lines[rhs_line.as_usize()]
-                );
+                    colored
+                )?;
+                if Some(rhs_line) == rhs_missing_newline {
+                    writeln!(out, "{}", style::no_newline_marker(None, display_options))?;
+                }
+                prev_rhs_line = Some(rhs_line);
             }
         }
 
         for (_, rhs_line) in &after_lines {
-            if let Some(rhs_line) = rhs_line {
-                print!(
-                    "   {}{}",
+            if let Some((rhs_line, colored)) = colored_line(&rhs_colored_lines, *rhs_line) {
+                write!(
+                    out,
+                    "   {}{}{}",
+                    novel_line_marker(false, Side::Right, display_options),
                     apply_line_number_color(
-                        &format_line_num(*rhs_line),
+                        &format!("{} ", rhs_line_map.display(rhs_line, prev_rhs_line)),
                         false,
                         Side::Right,
                         display_options,
                     ),
-                    rhs_colored_lines[rhs_line.as_usize()]
-                );
+                    colored
+                )?;
+                if Some(rhs_line) == rhs_missing_newline {
+                    writeln!(out, "{}", style::no_newline_marker(None, display_options))?;
+                }
+                prev_rhs_line = Some(rhs_line);
             }
         }
-        println!();
+        if context.elided_after {
+            writeln!(out, "{}", style::elision_marker(display_options))?;
+        }
+        writeln!(out)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::{
+        display::hunks::matched_pos_to_hunks,
+        line_parser::{self, Whitespace},
+        parse::syntax,
+    };
+
+    /// Display a text diff of `lhs` and `rhs` without color, as lines.
+    fn display_lines(lhs: &str, rhs: &str) -> Vec<String> {
+        display_lines_with(lhs, rhs, &DisplayOptions::default())
+    }
+
+    fn display_lines_with(lhs: &str, rhs: &str, display_options: &DisplayOptions) -> Vec<String> {
+        let lhs_positions = line_parser::change_positions(lhs, rhs, Whitespace::Exact, None);
+        let rhs_positions = line_parser::change_positions(rhs, lhs, Whitespace::Exact, None);
+        let (lhs_positions, rhs_positions) = if display_options.ignore_blank_lines {
+            (
+                syntax::ignore_blank_lines(&lhs_positions, lhs),
+                syntax::ignore_blank_lines(&rhs_positions, rhs),
+            )
+        } else {
+            (lhs_positions, rhs_positions)
+        };
+        let hunks = matched_pos_to_hunks(&lhs_positions, &rhs_positions);
+
+        let mut out = vec![];
+        print(
+            &mut out,
+            lhs,
+            rhs,
+            display_options,
+            &lhs_positions,
+            &rhs_positions,
+            &hunks,
+            "foo.txt",
+            &None,
+            &FileFormat::PlainText,
+            &LineMap::default(),
+            &LineMap::default(),
+        )
+        .unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.ends_with('\n'));
+        out.lines().map(|line| line.to_owned()).collect()
+    }
+
+    /// The line after the line that ends with `text`.
+    fn line_after<'a>(lines: &'a [String], text: &str) -> Option<&'a str> {
+        let i = lines.iter().position(|line| line.ends_with(text))?;
+        lines.get(i + 1).map(|line| line.as_str())
+    }
+
+    #[test]
+    fn test_missing_final_newline_lhs() {
+        let lines = display_lines("foo\nbar", "foo\nbaz\n");
+        assert_eq!(
+            line_after(&lines, "bar"),
+            Some("\\ No newline at end of file")
+        );
+        assert_ne!(
+            line_after(&lines, "baz"),
+            Some("\\ No newline at end of file")
+        );
+    }
+
+    #[test]
+    fn test_missing_final_newline_rhs() {
+        let lines = display_lines("foo\nbar\n", "foo\nbaz");
+        assert_ne!(
+            line_after(&lines, "bar"),
+            Some("\\ No newline at end of file")
+        );
+        assert_eq!(
+            line_after(&lines, "baz"),
+            Some("\\ No newline at end of file")
+        );
+    }
+
+    #[test]
+    fn test_missing_final_newline_both() {
+        // Both lines are still shown on their own rows, and there's
+        // no difference in newlines to mark.
+        let lines = display_lines("foo\nbar", "foo\nbaz");
+        assert!(lines.iter().any(|line| line.ends_with("bar")));
+        assert!(lines.iter().any(|line| line.ends_with("baz")));
+        assert!(!lines.iter().any(|line| line.contains("No newline")));
+    }
+
+    #[test]
+    fn test_ignored_blank_line_not_marked() {
+        let display_options = DisplayOptions {
+            ignore_blank_lines: true,
+            novel_line_marker: Some('+'),
+            ..DisplayOptions::default()
+        };
+        let lines = display_lines_with("foo\nbar\n", "foo\n  \nbaz\n", &display_options);
+        let marked: Vec<&String> = lines.iter().filter(|line| line.contains('+')).collect();
+        assert_eq!(marked.len(), 2);
+        assert!(marked
+            .iter()
+            .all(|line| line.contains("bar") || line.contains("baz")));
+    }
+
+    #[test]
+    fn test_colored_line_out_of_range() {
+        let lines = vec!["foo\n".to_owned()];
+        assert_eq!(
+            colored_line(&lines, Some(0.into())),
+            Some((0.into(), "foo\n"))
+        );
+        assert_eq!(colored_line(&lines, Some(1.into())), None);
+        assert_eq!(colored_line(&[], Some(0.into())), None);
+        assert_eq!(colored_line(&lines, None), None);
+    }
+
+    #[test]
+    fn test_empty_lhs() {
+        let lines = display_lines("", "foo\n");
+        assert!(lines.iter().any(|line| line.ends_with("foo")));
+    }
+
+    #[test]
+    fn test_huge_context() {
+        let display_options = DisplayOptions {
+            before_context_lines: 1_000_000,
+            after_context_lines: 1_000_000,
+            ..DisplayOptions::default()
+        };
+        let lines = display_lines_with("foo\nbar\n", "baz\n", &display_options);
+        assert!(lines.iter().any(|line| line.ends_with("bar")));
+        assert!(lines.iter().any(|line| line.ends_with("baz")));
+
+        let lines = display_lines_with("", "baz\n", &display_options);
+        assert!(lines.iter().any(|line| line.ends_with("baz")));
     }
 }
@@ -0,0 +1,516 @@
+//! Show characters that are hard to see in changed text, see
+//! `--show-invisible`.
+//!
+//! A line that only changes a non-breaking space, a zero-width space
+//! or a curly quote looks unchanged, which is baffling. Before
+//! display, we replace these characters in novel text with visible
+//! escapes, and move the positions to match.
+//!
+//! Without colors, novel trailing whitespace can't be highlighted
+//! either, so we also replace it with visible glyphs.
+
+use std::collections::HashSet;
+
+use line_numbers::{LineNumber, SingleLineSpan};
+
+use crate::{
+    display::style::TrailingWhitespace,
+    hash::DftHashMap,
+    parse::syntax::{MatchKind, MatchedPos},
+};
+
+/// When to show escapes for characters that are hard to see.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum ShowInvisible {
+    /// Only on changed lines that look the same as a changed line on
+    /// the other side, see [normalize]. Spaces and tabs are shown too,
+    /// so mixed indentation is visible.
+    Auto,
+    /// In all novel text, as well as on the lines that `Auto` would
+    /// show.
+    Always,
+    Never,
+}
+
+/// Whether `c` is invisible, such as a zero-width space or a
+/// direction override, or looks like an ASCII character.
+pub(crate) fn is_hard_to_see(c: char) -> bool {
+    matches!(
+        c,
+        '\u{a0}'
+            | '\u{ad}'
+            | '\u{2000}'..='\u{200f}'
+            | '\u{2018}'
+            | '\u{2019}'
+            | '\u{201c}'
+            | '\u{201d}'
+            | '\u{2028}'..='\u{202f}'
+            | '\u{205f}'..='\u{206f}'
+            | '\u{3000}'
+            | '\u{feff}'
+    )
+}
+
+/// `line` as it looks, more or less: without invisible characters,
+/// with ASCII quotes and with each run of whitespace as one space.
+fn normalize(line: &str) -> String {
+    let mut res = String::with_capacity(line.len());
+    for c in line.chars() {
+        let c = match c {
+            '\u{2018}' | '\u{2019}' => '\'',
+            '\u{201c}' | '\u{201d}' => '"',
+            c if c.is_whitespace() => ' ',
+            c if is_hard_to_see(c) => continue,
+            c => c,
+        };
+        if c != ' ' || !res.ends_with(' ') {
+            res.push(c);
+        }
+    }
+    res.trim().to_owned()
+}
+
+fn is_strictly_novel(mp: &MatchedPos) -> bool {
+    matches!(
+        mp.kind,
+        MatchKind::Novel { .. } | MatchKind::NovelWord { .. }
+    )
+}
+
+/// The lines in `lines` with novel text, by their normalized text.
+fn normalized_novel_lines<'a>(
+    lines: &[&'a str],
+    positions: &[MatchedPos],
+) -> DftHashMap<String, HashSet<&'a str>> {
+    let mut res: DftHashMap<String, HashSet<&'a str>> = DftHashMap::default();
+    for line in positions
+        .iter()
+        .filter(|mp| is_strictly_novel(mp))
+        .filter_map(|mp| lines.get(mp.pos.line.as_usize()))
+    {
+        res.entry(normalize(line)).or_default().insert(line);
+    }
+    res
+}
+
+/// The visible form of `c`, if it needs one. Spaces and tabs are
+/// only escaped if `whitespace` is set.
+fn escape(c: char, tab_width: usize, whitespace: bool) -> Option<String> {
+    match c {
+        // Keep the width of the tab, so indentation still lines up.
+        '\t' if whitespace => Some(format!("→{}", " ".repeat(tab_width.saturating_sub(1)))),
+        ' ' if whitespace => Some("␣".to_owned()),
+        '\u{a0}' => Some("⍽".to_owned()),
+        c if is_hard_to_see(c) => Some(format!("\\u{{{:X}}}", c as u32)),
+        _ => None,
+    }
+}
+
+/// The source and positions of both sides, after escaping.
+pub(crate) struct EscapedLines {
+    pub(crate) lhs_src: String,
+    pub(crate) rhs_src: String,
+    pub(crate) lhs_positions: Vec<MatchedPos>,
+    pub(crate) rhs_positions: Vec<MatchedPos>,
+}
+
+/// The escapes on one side.
+struct Escapes {
+    /// For each line with escapes, the byte offset of each escaped
+    /// character, its length in bytes and its escape, in order.
+    by_line: DftHashMap<LineNumber, Vec<(u32, usize, String)>>,
+}
+
+impl Escapes {
+    /// Escape the novel text in `lines`. `opposite_novel_lines` are
+    /// the lines with novel text on the other side, by their
+    /// normalized text.
+    fn new(
+        lines: &[&str],
+        positions: &[MatchedPos],
+        opposite_novel_lines: &DftHashMap<String, HashSet<&str>>,
+        show_invisible: ShowInvisible,
+        tab_width: usize,
+    ) -> Self {
+        let span_text = |span: &SingleLineSpan| {
+            lines
+                .get(span.line.as_usize())
+                .and_then(|line| line.get(span.start_col as usize..span.end_col as usize))
+                .unwrap_or("")
+        };
+
+        // Whether each line with novel text looks like nothing
+        // changed.
+        let mut looks_unchanged: DftHashMap<LineNumber, bool> = DftHashMap::default();
+        let mut by_line: DftHashMap<LineNumber, Vec<(u32, usize, String)>> = DftHashMap::default();
+        for mp in positions.iter().filter(|mp| is_strictly_novel(mp)) {
+            let whitespace = *looks_unchanged.entry(mp.pos.line).or_insert_with(|| {
+                let line = lines.get(mp.pos.line.as_usize()).copied().unwrap_or("");
+                // A line that has moved, rather than changed, looks
+                // different enough already.
+                opposite_novel_lines
+                    .get(&normalize(line))
+                    .map_or(false, |opposite_lines| {
+                        opposite_lines.iter().any(|opposite| *opposite != line)
+                    })
+            });
+            if show_invisible == ShowInvisible::Auto && !whitespace {
+                continue;
+            }
+
+            for (offset, c) in span_text(&mp.pos).char_indices() {
+                if let Some(escape) = escape(c, tab_width, whitespace) {
+                    by_line.entry(mp.pos.line).or_default().push((
+                        mp.pos.start_col + offset as u32,
+                        c.len_utf8(),
+                        escape,
+                    ));
+                }
+            }
+        }
+        for escapes in by_line.values_mut() {
+            escapes.sort_by_key(|(start, _, _)| *start);
+            escapes.dedup_by_key(|(start, _, _)| *start);
+        }
+
+        Self { by_line }
+    }
+
+    /// Replace the novel whitespace at the end of lines in `lines`
+    /// with `·` and `⇥`, on the lines that `trailing_whitespace`
+    /// says to highlight, see
+    /// [crate::display::style::highlight_trailing_whitespace].
+    fn trailing_whitespace(
+        lines: &[&str],
+        positions: &[MatchedPos],
+        trailing_whitespace: TrailingWhitespace,
+        tab_width: usize,
+    ) -> Self {
+        let span_text = |span: &SingleLineSpan| {
+            lines
+                .get(span.line.as_usize())
+                .and_then(|line| line.get(span.start_col as usize..span.end_col as usize))
+                .unwrap_or("")
+        };
+
+        // Lines where every novel span is whitespace.
+        let mut whitespace_only: DftHashMap<LineNumber, bool> = DftHashMap::default();
+        for mp in positions.iter().filter(|mp| is_strictly_novel(mp)) {
+            let is_whitespace = span_text(&mp.pos).trim().is_empty();
+            *whitespace_only.entry(mp.pos.line).or_insert(true) &= is_whitespace;
+        }
+
+        let mut by_line: DftHashMap<LineNumber, Vec<(u32, usize, String)>> = DftHashMap::default();
+        for mp in positions.iter().filter(|mp| is_strictly_novel(mp)) {
+            if trailing_whitespace == TrailingWhitespace::Auto
+                && whitespace_only.get(&mp.pos.line) != Some(&true)
+            {
+                continue;
+            }
+
+            let line = lines.get(mp.pos.line.as_usize()).copied().unwrap_or("");
+            let trailing_start = line.trim_end().len() as u32;
+            for (offset, c) in span_text(&mp.pos).char_indices() {
+                let start = mp.pos.start_col + offset as u32;
+                let escape = match c {
+                    // Keep the width of the tab, so the line is as
+                    // wide as before.
+                    '\t' => format!("⇥{}", " ".repeat(tab_width.saturating_sub(1))),
+                    ' ' => "·".to_owned(),
+                    _ => continue,
+                };
+                if start >= trailing_start {
+                    by_line
+                        .entry(mp.pos.line)
+                        .or_default()
+                        .push((start, c.len_utf8(), escape));
+                }
+            }
+        }
+        for escapes in by_line.values_mut() {
+            escapes.sort_by_key(|(start, _, _)| *start);
+            escapes.dedup_by_key(|(start, _, _)| *start);
+        }
+
+        Self { by_line }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.by_line.is_empty()
+    }
+
+    /// The column of `col` on `line` after escaping.
+    fn col(&self, line: LineNumber, col: u32) -> u32 {
+        let Some(escapes) = self.by_line.get(&line) else {
+            return col;
+        };
+        let extra: usize = escapes
+            .iter()
+            .take_while(|(start, _, _)| *start < col)
+            .map(|(_, len, escape)| escape.len() - len)
+            .sum();
+        col + extra as u32
+    }
+
+    fn span(&self, span: &SingleLineSpan) -> SingleLineSpan {
+        SingleLineSpan {
+            line: span.line,
+            start_col: self.col(span.line, span.start_col),
+            end_col: self.col(span.line, span.end_col),
+        }
+    }
+
+    fn spans(&self, spans: &[SingleLineSpan]) -> Vec<SingleLineSpan> {
+        spans.iter().map(|span| self.span(span)).collect()
+    }
+
+    fn escape_src(&self, src: &str) -> String {
+        let mut res = String::with_capacity(src.len());
+        for (i, line) in src.split_inclusive('\n').enumerate() {
+            let Some(escapes) = self.by_line.get(&LineNumber::from(i as u32)) else {
+                res.push_str(line);
+                continue;
+            };
+
+            let mut offset = 0;
+            for (start, len, escape) in escapes {
+                let start = *start as usize;
+                res.push_str(&line[offset..start]);
+                res.push_str(escape);
+                offset = start + len;
+            }
+            res.push_str(&line[offset..]);
+        }
+        res
+    }
+}
+
+fn escape_positions(
+    positions: &[MatchedPos],
+    escapes: &Escapes,
+    opposite_escapes: &Escapes,
+) -> Vec<MatchedPos> {
+    positions
+        .iter()
+        .map(|mp| {
+            let kind = match &mp.kind {
+                MatchKind::UnchangedToken {
+                    highlight,
+                    self_pos,
+                    opposite_pos,
+                } => MatchKind::UnchangedToken {
+                    highlight: *highlight,
+                    self_pos: escapes.spans(self_pos),
+                    opposite_pos: opposite_escapes.spans(opposite_pos),
+                },
+                MatchKind::NovelLinePart {
+                    highlight,
+                    self_pos,
+                    opposite_pos,
+                } => MatchKind::NovelLinePart {
+                    highlight: *highlight,
+                    self_pos: escapes.span(self_pos),
+                    opposite_pos: opposite_escapes.spans(opposite_pos),
+                },
+                kind => kind.clone(),
+            };
+
+            MatchedPos {
+                kind,
+                pos: escapes.span(&mp.pos),
+            }
+        })
+        .collect()
+}
+
+/// If either side has novel text that's hard to see, escape it and
+/// update the positions to match.
+pub(crate) fn escape_invisible(
+    lhs_src: &str,
+    rhs_src: &str,
+    lhs_positions: &[MatchedPos],
+    rhs_positions: &[MatchedPos],
+    show_invisible: ShowInvisible,
+    tab_width: usize,
+) -> Option<EscapedLines> {
+    if show_invisible == ShowInvisible::Never {
+        return None;
+    }
+
+    let lhs_lines: Vec<&str> = lhs_src.lines().collect();
+    let rhs_lines: Vec<&str> = rhs_src.lines().collect();
+    let lhs_novel_lines = normalized_novel_lines(&lhs_lines, lhs_positions);
+    let rhs_novel_lines = normalized_novel_lines(&rhs_lines, rhs_positions);
+
+    let lhs_escapes = Escapes::new(
+        &lhs_lines,
+        lhs_positions,
+        &rhs_novel_lines,
+        show_invisible,
+        tab_width,
+    );
+    let rhs_escapes = Escapes::new(
+        &rhs_lines,
+        rhs_positions,
+        &lhs_novel_lines,
+        show_invisible,
+        tab_width,
+    );
+    if lhs_escapes.is_empty() && rhs_escapes.is_empty() {
+        return None;
+    }
+
+    Some(EscapedLines {
+        lhs_src: lhs_escapes.escape_src(lhs_src),
+        rhs_src: rhs_escapes.escape_src(rhs_src),
+        lhs_positions: escape_positions(lhs_positions, &lhs_escapes, &rhs_escapes),
+        rhs_positions: escape_positions(rhs_positions, &rhs_escapes, &lhs_escapes),
+    })
+}
+
+/// If either side has novel trailing whitespace that
+/// `trailing_whitespace` says to highlight, replace it with visible
+/// glyphs and update the positions to match. This is for display
+/// without colors, where highlighting whitespace shows nothing.
+pub(crate) fn escape_trailing_whitespace(
+    lhs_src: &str,
+    rhs_src: &str,
+    lhs_positions: &[MatchedPos],
+    rhs_positions: &[MatchedPos],
+    trailing_whitespace: TrailingWhitespace,
+    tab_width: usize,
+) -> Option<EscapedLines> {
+    if trailing_whitespace == TrailingWhitespace::Never {
+        return None;
+    }
+
+    let lhs_lines: Vec<&str> = lhs_src.lines().collect();
+    let rhs_lines: Vec<&str> = rhs_src.lines().collect();
+    let lhs_escapes =
+        Escapes::trailing_whitespace(&lhs_lines, lhs_positions, trailing_whitespace, tab_width);
+    let rhs_escapes =
+        Escapes::trailing_whitespace(&rhs_lines, rhs_positions, trailing_whitespace, tab_width);
+    if lhs_escapes.is_empty() && rhs_escapes.is_empty() {
+        return None;
+    }
+
+    Some(EscapedLines {
+        lhs_src: lhs_escapes.escape_src(lhs_src),
+        rhs_src: rhs_escapes.escape_src(rhs_src),
+        lhs_positions: escape_positions(lhs_positions, &lhs_escapes, &rhs_escapes),
+        rhs_positions: escape_positions(rhs_positions, &rhs_escapes, &lhs_escapes),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::{line_parser, line_parser::Whitespace};
+
+    fn escaped(lhs: &str, rhs: &str, show_invisible: ShowInvisible) -> Option<(String, String)> {
+        let lhs_positions = line_parser::change_positions(lhs, rhs, Whitespace::Exact, None);
+        let rhs_positions = line_parser::change_positions(rhs, lhs, Whitespace::Exact, None);
+        escape_invisible(lhs, rhs, &lhs_positions, &rhs_positions, show_invisible, 4)
+            .map(|escaped| (escaped.lhs_src, escaped.rhs_src))
+    }
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(normalize("\t\u{201c}a\u{200b}\u{a0} b\u{201d} "), "\"a b\"");
+    }
+
+    #[test]
+    fn test_escape_non_breaking_space() {
+        assert_eq!(
+            escaped("foo bar\n", "foo\u{a0}bar\n", ShowInvisible::Auto),
+            Some(("foo␣bar\n".to_owned(), "foo⍽bar\n".to_owned()))
+        );
+    }
+
+    #[test]
+    fn test_escape_zero_width_space() {
+        assert_eq!(
+            escaped("foobar\n", "foo\u{200b}bar\n", ShowInvisible::Auto),
+            Some(("foobar\n".to_owned(), "foo\\u{200B}bar\n".to_owned()))
+        );
+    }
+
+    #[test]
+    fn test_escape_tab() {
+        assert_eq!(
+            escaped("    x\n", "\tx\n", ShowInvisible::Auto),
+            Some(("␣␣␣␣x\n".to_owned(), "→   x\n".to_owned()))
+        );
+    }
+
+    #[test]
+    fn test_escape_moved_line() {
+        assert_eq!(escaped("a b\nx\n", "x\na b\n", ShowInvisible::Auto), None);
+    }
+
+    #[test]
+    fn test_escape_visible_change() {
+        // The change is obvious, so only escape with `Always`, and
+        // leave ordinary spaces alone.
+        let lhs = "foo bar\n";
+        let rhs = "foo\u{a0}baz\n";
+        assert_eq!(escaped(lhs, rhs, ShowInvisible::Auto), None);
+        assert_eq!(
+            escaped(lhs, rhs, ShowInvisible::Always),
+            Some((lhs.to_owned(), "foo⍽baz\n".to_owned()))
+        );
+        assert_eq!(escaped(lhs, "foo\u{a0}bar\n", ShowInvisible::Never), None);
+    }
+
+    #[test]
+    fn test_escape_trailing_whitespace() {
+        let lhs = "foo\n";
+        let rhs = "foo \t\n";
+        let lhs_positions = line_parser::change_positions(lhs, rhs, Whitespace::Exact, None);
+        let rhs_positions = line_parser::change_positions(rhs, lhs, Whitespace::Exact, None);
+        let escaped = |trailing_whitespace| {
+            escape_trailing_whitespace(
+                lhs,
+                rhs,
+                &lhs_positions,
+                &rhs_positions,
+                trailing_whitespace,
+                4,
+            )
+            .map(|escaped| escaped.rhs_src)
+        };
+
+        assert_eq!(
+            escaped(TrailingWhitespace::Auto),
+            Some("foo·⇥   \n".to_owned())
+        );
+        assert_eq!(escaped(TrailingWhitespace::Never), None);
+    }
+
+    #[test]
+    fn test_escape_positions() {
+        let lhs = "a\u{200b}b c\n";
+        let rhs = "ab c\n";
+        let lhs_positions = line_parser::change_positions(lhs, rhs, Whitespace::Exact, None);
+        let rhs_positions = line_parser::change_positions(rhs, lhs, Whitespace::Exact, None);
+        let escaped = escape_invisible(
+            lhs,
+            rhs,
+            &lhs_positions,
+            &rhs_positions,
+            ShowInvisible::Auto,
+            4,
+        )
+        .unwrap();
+
+        let line = escaped.lhs_src.lines().next().unwrap();
+        let texts: Vec<&str> = escaped
+            .lhs_positions
+            .iter()
+            .map(|mp| &line[mp.pos.start_col as usize..mp.pos.end_col as usize])
+            .collect();
+        assert_eq!(texts, vec!["a", "\\u{200B}", "b", " ", "c"]);
+    }
+}
@@ -1,17 +1,19 @@
-use std::collections::HashMap;
+use std::{borrow::Cow, io::Write};
 
-use line_numbers::LineNumber;
 use serde::{ser::SerializeStruct, Serialize, Serializer};
+use serde_json::json;
+use strum::IntoEnumIterator;
 
 use crate::{
-    display::{
-        context::{all_matched_lines_filled, opposite_positions},
-        hunks::{matched_lines_indexes_for_hunk, matched_pos_to_hunks, merge_adjacent},
-        side_by_side::lines_with_novel,
+    api, constants,
+    display::invisible,
+    options::DisplayOptions,
+    parse::{
+        guess_language::{language_globs, language_name, Language},
+        tree_sitter_parser::has_highlight_query,
     },
-    lines::MaxLine,
-    parse::syntax::{self, MatchedPos, StringKind},
-    summary::{DiffResult, FileContent, FileFormat},
+    profile,
+    summary::{BinaryInfo, DiffResult, FileContent, FileFormat},
 };
 
 #[derive(Debug, Serialize, PartialEq)]
@@ -19,6 +21,9 @@ use crate::{
 enum Status {
     Unchanged,
     Changed,
+    /// The content of a binary file changed, so there are no chunks.
+    #[serde(rename = "binary_changed")]
+    BinaryChanged,
     Created,
     Deleted,
 }
@@ -27,19 +32,30 @@ enum Status {
 struct File<'f> {
     language: &'f FileFormat,
     path: &'f str,
-    chunks: Vec<Vec<Line<'f>>>,
+    /// The bytes of the path, if it isn't valid UTF-8.
+    path_bytes: Option<&'f [u8]>,
+    /// The previous path, if the file was renamed.
+    old_path: Option<&'f str>,
+    /// The old and new mode, if the mode changed.
+    mode_change: Option<&'f (String, String)>,
+    /// The old and new modification time, with `--compare-mtime`.
+    mtime_change: Option<&'f (String, String)>,
+    /// The old and new size and hash, for binary files.
+    binary_info: Option<&'f (BinaryInfo, BinaryInfo)>,
+    chunks: Vec<api::Hunk>,
     status: Status,
 }
 
 impl<'f> File<'f> {
-    fn with_sections(
-        language: &'f FileFormat,
-        path: &'f str,
-        chunks: Vec<Vec<Line<'f>>>,
-    ) -> File<'f> {
+    fn with_sections(language: &'f FileFormat, path: &'f str, chunks: Vec<api::Hunk>) -> File<'f> {
         File {
             language,
             path,
+            path_bytes: None,
+            old_path: None,
+            mode_change: None,
+            mtime_change: None,
+            binary_info: None,
             chunks,
             status: Status::Changed,
         }
@@ -49,31 +65,59 @@ impl<'f> File<'f> {
         File {
             language,
             path,
+            path_bytes: None,
+            old_path: None,
+            mode_change: None,
+            mtime_change: None,
+            binary_info: None,
             chunks: Vec::new(),
             status,
         }
     }
 }
 
-impl<'f> From<&'f DiffResult> for File<'f> {
-    fn from(summary: &'f DiffResult) -> Self {
+impl<'f> File<'f> {
+    fn from_diff_result(summary: &'f DiffResult, display_options: &DisplayOptions) -> Self {
+        let file = match summary.missing_side {
+            Some(constants::Side::Left) => {
+                File::with_status(&summary.file_format, &summary.display_path, Status::Created)
+            }
+            Some(constants::Side::Right) => {
+                File::with_status(&summary.file_format, &summary.display_path, Status::Deleted)
+            }
+            None => Self::from_hunks(summary, display_options),
+        };
+        // A mode or modification time change is a change even if the
+        // content is the same.
+        let status = match file.status {
+            Status::Unchanged if summary.has_metadata_change() => Status::Changed,
+            status => status,
+        };
+        File {
+            path_bytes: summary.path_bytes.as_deref(),
+            old_path: summary.renamed_from.as_deref(),
+            mode_change: summary.mode_change.as_ref(),
+            mtime_change: summary.mtime_change.as_ref(),
+            binary_info: summary.binary_info.as_ref(),
+            status,
+            ..file
+        }
+    }
+
+    /// The JSON representation of `summary`, merging and splitting
+    /// hunks as requested in `display_options`.
+    fn from_hunks(summary: &'f DiffResult, display_options: &DisplayOptions) -> Self {
         match (&summary.lhs_src, &summary.rhs_src) {
             (FileContent::Text(lhs_src), FileContent::Text(rhs_src)) => {
-                // TODO: move into function as it is effectively duplicates lines 365-375 of main::print_diff_result
-                let opposite_to_lhs = opposite_positions(&summary.lhs_positions);
-                let opposite_to_rhs = opposite_positions(&summary.rhs_positions);
-
-                let hunks = matched_pos_to_hunks(&summary.lhs_positions, &summary.rhs_positions);
-                let hunks = merge_adjacent(
-                    &hunks,
-                    &opposite_to_lhs,
-                    &opposite_to_rhs,
-                    lhs_src.max_line(),
-                    rhs_src.max_line(),
-                    0,
+                let hunks = api::FileHunks::new(
+                    lhs_src,
+                    rhs_src,
+                    Cow::Borrowed(&summary.lhs_positions),
+                    Cow::Borrowed(&summary.rhs_positions),
+                    display_options,
                 );
-
-                if hunks.is_empty() {
+                let num_hunks = hunks.len();
+                if num_hunks == 0 {
                     return File::with_status(
                         &summary.file_format,
                         &summary.display_path,
@@ -96,76 +140,33 @@ impl<'f> From<&'f DiffResult> for File<'f> {
                     );
                 }
 
-                let lhs_lines = lhs_src.split('\n').collect::<Vec<&str>>();
-                let rhs_lines = rhs_src.split('\n').collect::<Vec<&str>>();
-
-                let (lhs_lines_with_novel, rhs_lines_with_novel) =
-                    lines_with_novel(&summary.lhs_positions, &summary.rhs_positions);
-
-                let matched_lines = all_matched_lines_filled(
-                    &summary.lhs_positions,
-                    &summary.rhs_positions,
-                    &lhs_lines,
-                    &rhs_lines,
-                );
-                let mut matched_lines = &matched_lines[..];
-
-                let mut chunks = Vec::with_capacity(hunks.len());
-                for hunk in &hunks {
-                    let mut lines = HashMap::with_capacity(hunk.lines.len());
-
-                    let (start_i, end_i) = matched_lines_indexes_for_hunk(matched_lines, hunk, 0);
-                    let aligned_lines = &matched_lines[start_i..end_i];
-                    matched_lines = &matched_lines[start_i..];
-
-                    for (lhs_line_num, rhs_line_num) in aligned_lines {
-                        if !lhs_lines_with_novel.contains(&lhs_line_num.unwrap_or(LineNumber(0)))
-                            && !rhs_lines_with_novel
-                                .contains(&rhs_line_num.unwrap_or(LineNumber(0)))
-                        {
-                            continue;
-                        }
-
-                        let line = lines
-                            .entry((lhs_line_num.map(|l| l.0), rhs_line_num.map(|l| l.0)))
-                            .or_insert_with(|| {
-                                Line::new(lhs_line_num.map(|l| l.0), rhs_line_num.map(|l| l.0))
-                            });
-
-                        if let Some(line_num) = lhs_line_num {
-                            add_changes_to_side(
-                                line.lhs.as_mut().unwrap(),
-                                *line_num,
-                                &lhs_lines,
-                                &summary.lhs_positions,
-                            );
-                        }
-                        if let Some(line_num) = rhs_line_num {
-                            add_changes_to_side(
-                                line.rhs.as_mut().unwrap(),
-                                *line_num,
-                                &rhs_lines,
-                                &summary.rhs_positions,
-                            );
-                        }
-                    }
-
-                    chunks.push(lines.into_values().collect());
-                }
+                let chunks =
+                    if display_options.check_hunk_selection(&summary.display_path, num_hunks) {
+                        hunks
+                            .into_iter()
+                            .enumerate()
+                            .filter(|(i, _)| display_options.shows_hunk(i + 1))
+                            .map(|(_, hunk)| hunk)
+                            .collect()
+                    } else {
+                        vec![]
+                    };
 
                 File::with_sections(&summary.file_format, &summary.display_path, chunks)
             }
             (FileContent::Binary, FileContent::Binary) => {
                 let status = if summary.has_byte_changes {
-                    Status::Changed
+                    Status::BinaryChanged
                 } else {
                     Status::Unchanged
                 };
-                File::with_status(&FileFormat::Binary, &summary.display_path, status)
-            }
-            (_, FileContent::Binary) | (FileContent::Binary, _) => {
-                File::with_status(&FileFormat::Binary, &summary.display_path, Status::Changed)
+                File::with_status(&summary.file_format, &summary.display_path, status)
             }
+            (_, FileContent::Binary) | (FileContent::Binary, _) => File::with_status(
+                &FileFormat::Binary,
+                &summary.display_path,
+                Status::BinaryChanged,
+            ),
         }
     }
 }
@@ -180,18 +181,89 @@ impl<'f> Serialize for File<'f> {
             serializer.serialize_struct("File", 3)?
         } else {
             let mut file = serializer.serialize_struct("File", 4)?;
-            file.serialize_field("chunks", &self.chunks)?;
+            let chunks: Vec<Vec<Line>> = self
+                .chunks
+                .iter()
+                .map(|hunk| hunk.lines.iter().map(Line::from).collect())
+                .collect();
+            file.serialize_field("chunks", &chunks)?;
             file
         };
 
         file.serialize_field("language", &format!("{}", self.language))?;
         file.serialize_field("path", &self.path)?;
+        // `path` escapes the bytes that aren't valid UTF-8.
+        if let Some(path_bytes) = self.path_bytes {
+            file.serialize_field("path_bytes", &base64(path_bytes))?;
+        }
+        if let Some(old_path) = self.old_path {
+            file.serialize_field("old_path", old_path)?;
+        }
+        if let Some((old_mode, new_mode)) = self.mode_change {
+            file.serialize_field("old_mode", old_mode)?;
+            file.serialize_field("new_mode", new_mode)?;
+        }
+        if let Some((old_mtime, new_mtime)) = self.mtime_change {
+            file.serialize_field("old_mtime", old_mtime)?;
+            file.serialize_field("new_mtime", new_mtime)?;
+        }
+        if let Some((old_info, new_info)) = self.binary_info {
+            file.serialize_field("old_size", &old_info.len)?;
+            file.serialize_field("new_size", &new_info.len)?;
+            file.serialize_field("old_hash", &old_info.short_hash())?;
+            file.serialize_field("new_hash", &new_info.short_hash())?;
+        }
         file.serialize_field("status", &self.status)?;
+        // Only recorded with --profile.
+        if let Some(profile) = profile::file_profile(self.path) {
+            file.serialize_field("profile", &profile)?;
+        }
 
         file.end()
     }
 }
 
+/// The JSON for a diff from the library, see [api::FileDiff::to_json].
+pub(crate) fn file_diff_json(diff: &api::FileDiff) -> String {
+    let chunks: Vec<Vec<Line>> = diff
+        .hunks
+        .iter()
+        .map(|hunk| hunk.lines.iter().map(Line::from).collect())
+        .collect();
+    let mut value = json!({
+        "language": diff.language.map(language_name),
+        "chunks": chunks,
+    });
+    if let Some(reason) = &diff.text_fallback_reason {
+        value["text_fallback_reason"] = json!(reason);
+    }
+    if diff.cancelled {
+        value["cancelled"] = json!(true);
+    }
+    value.to_string()
+}
+
+/// Encode `bytes` as standard base64, with padding.
+fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    let mut encoded = String::with_capacity((bytes.len() + 2) / 3 * 4);
+    for chunk in bytes.chunks(3) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0_u32, |n, (i, byte)| n | ((*byte as u32) << (16 - 8 * i)));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(ALPHABET[((n >> (18 - 6 * i)) & 0x3f) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
 #[derive(Debug, Serialize)]
 struct Line<'l> {
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -200,11 +272,11 @@ struct Line<'l> {
     rhs: Option<Side<'l>>,
 }
 
-impl<'l> Line<'l> {
-    fn new(lhs_number: Option<u32>, rhs_number: Option<u32>) -> Line<'l> {
+impl<'l> From<&'l api::Line> for Line<'l> {
+    fn from(line: &'l api::Line) -> Self {
         Line {
-            lhs: lhs_number.map(Side::new),
-            rhs: rhs_number.map(Side::new),
+            lhs: line.lhs.as_ref().map(Side::from),
+            rhs: line.rhs.as_ref().map(Side::from),
         }
     }
 }
@@ -215,11 +287,11 @@ struct Side<'s> {
     changes: Vec<Change<'s>>,
 }
 
-impl<'s> Side<'s> {
-    fn new(line_number: u32) -> Side<'s> {
+impl<'s> From<&'s api::LineChanges> for Side<'s> {
+    fn from(side: &'s api::LineChanges) -> Self {
         Side {
-            line_number,
-            changes: Vec::new(),
+            line_number: side.line_number,
+            changes: side.changes.iter().map(Change::from).collect(),
         }
     }
 }
@@ -230,92 +302,197 @@ struct Change<'c> {
     end: u32,
     content: &'c str,
     highlight: Highlight,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    kind: Option<ChangeKind>,
+    /// For copied content, the line on the LHS that the code was
+    /// copied from.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    copied_from: Option<u32>,
+    /// Characters in the content that are hard to see, such as
+    /// `"U+00A0"` for a non-breaking space.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    codepoints: Vec<String>,
+}
+
+impl<'c> From<&'c api::Change> for Change<'c> {
+    fn from(change: &'c api::Change) -> Self {
+        let (kind, copied_from) = match change.kind {
+            api::ChangeKind::Moved => (Some(ChangeKind::Moved), None),
+            api::ChangeKind::Copied { from_line } => (Some(ChangeKind::Copied), Some(from_line)),
+            api::ChangeKind::Novel => (None, None),
+        };
+        Change {
+            start: change.start,
+            end: change.end,
+            content: &change.content,
+            highlight: Highlight(change.highlight),
+            kind,
+            copied_from,
+            codepoints: change
+                .content
+                .chars()
+                .filter(|c| invisible::is_hard_to_see(*c))
+                .map(|c| format!("U+{:04X}", c as u32))
+                .collect(),
+        }
+    }
 }
 
+/// How a change differs from an ordinary novel token.
 #[derive(Debug, Serialize)]
 #[serde(rename_all = "snake_case")]
-// TODO: use syntax::TokenKind and syntax::AtomKind instead of this merged enum,
-// blocked by https://github.com/serde-rs/serde/issues/1402
-enum Highlight {
-    Delimiter,
-    Normal,
-    String,
-    Type,
-    Comment,
-    Keyword,
-    TreeSitterError,
+enum ChangeKind {
+    /// This content also occurs elsewhere on the other side, see
+    /// `--detect-moves`.
+    Moved,
+    /// This content is part of code copied from the LHS, and is the
+    /// same as in the original. See `--detect-copies`.
+    Copied,
 }
 
-impl Highlight {
-    fn from_match(kind: &syntax::MatchKind) -> Self {
-        use syntax::{AtomKind, MatchKind, TokenKind};
-
-        let highlight = match kind {
-            MatchKind::Ignored { highlight, .. } => highlight,
-            MatchKind::UnchangedToken { highlight, .. } => highlight,
-            MatchKind::Novel { highlight, .. } => highlight,
-            MatchKind::NovelWord { highlight, .. } => highlight,
-            MatchKind::NovelLinePart { highlight, .. } => highlight,
-        };
+/// The highlight of a change, serialized in snake case, e.g.
+/// `tree_sitter_error`.
+#[derive(Debug)]
+struct Highlight(api::Highlight);
 
-        match highlight {
-            TokenKind::Delimiter => Highlight::Delimiter,
-            TokenKind::Atom(atom) => match atom {
-                AtomKind::String(StringKind::StringLiteral) => Highlight::String,
-                AtomKind::String(StringKind::Text) => Highlight::Normal,
-                AtomKind::Keyword => Highlight::Keyword,
-                AtomKind::Comment => Highlight::Comment,
-                AtomKind::Type => Highlight::Type,
-                AtomKind::Normal => Highlight::Normal,
-                AtomKind::TreeSitterError => Highlight::TreeSitterError,
-            },
-        }
+impl Serialize for Highlight {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(match self.0 {
+            api::Highlight::Delimiter => "delimiter",
+            api::Highlight::Normal => "normal",
+            api::Highlight::String => "string",
+            api::Highlight::Type => "type",
+            api::Highlight::Comment => "comment",
+            api::Highlight::Keyword => "keyword",
+            api::Highlight::TreeSitterError => "tree_sitter_error",
+        })
     }
 }
 
-pub(crate) fn print_directory(diffs: Vec<DiffResult>, print_unchanged: bool) {
+pub(crate) fn print_directory(
+    out: &mut dyn Write,
+    diffs: Vec<DiffResult>,
+    display_options: &DisplayOptions,
+) -> std::io::Result<()> {
     let files = diffs
         .iter()
-        .map(File::from)
-        .filter(|f| print_unchanged || f.status != Status::Unchanged)
+        .map(|diff| File::from_diff_result(diff, display_options))
+        .filter(|f| display_options.print_unchanged || f.status != Status::Unchanged)
         .collect::<Vec<File>>();
-    println!(
+    writeln!(
+        out,
         "{}",
         serde_json::to_string(&files).expect("failed to serialize files")
-    );
+    )
 }
 
-pub(crate) fn print(diff: &DiffResult) {
-    let file = File::from(diff);
-    println!(
+pub(crate) fn print(
+    out: &mut dyn Write,
+    diff: &DiffResult,
+    display_options: &DisplayOptions,
+) -> std::io::Result<()> {
+    let file = File::from_diff_result(diff, display_options);
+    writeln!(
+        out,
         "{}",
         serde_json::to_string(&file).expect("failed to serialize file")
     )
 }
 
-fn add_changes_to_side<'s>(
-    side: &mut Side<'s>,
-    line_num: LineNumber,
-    src_lines: &[&'s str],
-    all_matches: &[MatchedPos],
-) {
-    let src_line = src_lines[line_num.0 as usize];
-
-    let matches = matches_for_line(all_matches, line_num);
-    for m in matches {
-        side.changes.push(Change {
-            start: m.pos.start_col,
-            end: m.pos.end_col,
-            content: &src_line[(m.pos.start_col as usize)..(m.pos.end_col as usize)],
-            highlight: Highlight::from_match(&m.kind),
-        })
+/// A language in the output of `--list-languages --display=json`.
+#[derive(Debug, Serialize, PartialEq)]
+struct LanguageInfo {
+    name: &'static str,
+    /// File extensions, without the leading `.`, e.g. `rs` or
+    /// `sh.in`.
+    extensions: Vec<String>,
+    /// Literal file names, e.g. `Makefile`.
+    filenames: Vec<String>,
+    /// Any other globs matched against the file name.
+    globs: Vec<String>,
+    highlighting: bool,
+}
+
+impl LanguageInfo {
+    fn new(language: Language) -> Self {
+        let mut extensions = vec![];
+        let mut filenames = vec![];
+        let mut globs = vec![];
+        for glob in language_globs(language) {
+            let glob = glob.as_str();
+            let is_literal = |s: &str| !s.contains(['*', '?', '[']);
+            match glob.strip_prefix("*.") {
+                Some(extension) if is_literal(extension) => extensions.push(extension.to_owned()),
+                _ if is_literal(glob) => filenames.push(glob.to_owned()),
+                _ => globs.push(glob.to_owned()),
+            }
+        }
+        extensions.sort();
+        filenames.sort();
+        globs.sort();
+
+        Self {
+            name: language_name(language),
+            extensions,
+            filenames,
+            globs,
+            highlighting: has_highlight_query(language),
+        }
     }
 }
 
-fn matches_for_line(matches: &[MatchedPos], line_num: LineNumber) -> Vec<&MatchedPos> {
-    matches
-        .iter()
-        .filter(|m| m.pos.line == line_num)
-        .filter(|m| m.kind.is_novel())
-        .collect()
+/// Print every language that difftastic supports, sorted by name.
+pub(crate) fn print_languages(out: &mut dyn Write) -> std::io::Result<()> {
+    let mut languages: Vec<LanguageInfo> = Language::iter().map(LanguageInfo::new).collect();
+    languages.sort_by_key(|language| language.name);
+    writeln!(
+        out,
+        "{}",
+        serde_json::to_string(&languages).expect("failed to serialize languages")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_base64() {
+        assert_eq!(base64(b""), "");
+        assert_eq!(base64(b"f"), "Zg==");
+        assert_eq!(base64(b"fo"), "Zm8=");
+        assert_eq!(base64(b"foo"), "Zm9v");
+        assert_eq!(base64(b"caf\xe9.txt"), "Y2Fm6S50eHQ=");
+    }
+
+    #[test]
+    fn test_change_codepoints() {
+        let change = api::Change {
+            start: 0,
+            end: 8,
+            content: "a\u{a0}b\u{200b}".to_owned(),
+            highlight: api::Highlight::Normal,
+            kind: api::ChangeKind::Novel,
+        };
+        assert_eq!(
+            serde_json::to_value(Change::from(&change)).unwrap()["codepoints"],
+            json!(["U+00A0", "U+200B"])
+        );
+    }
+
+    #[test]
+    fn test_print_languages() {
+        let mut out = vec![];
+        print_languages(&mut out).unwrap();
+
+        let languages: serde_json::Value = serde_json::from_slice(&out).unwrap();
+        let languages = languages.as_array().unwrap();
+        assert_eq!(languages.len(), Language::iter().count());
+        assert!(languages.iter().any(|language| language["name"] == "Rust"));
+    }
 }
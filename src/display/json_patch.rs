@@ -0,0 +1,226 @@
+//! RFC 6902 JSON Patch output, for diffing two JSON documents.
+//!
+//! Applying the operations in order to the LHS document produces the
+//! RHS document.
+
+use std::io::Write;
+
+use serde_json::{json, Map, Value};
+
+use crate::diff::myers_diff;
+
+/// Escape a key for use in a JSON Pointer, as described in RFC 6901.
+fn escape_pointer_token(token: &str) -> String {
+    token.replace('~', "~0").replace('/', "~1")
+}
+
+fn child_pointer(pointer: &str, token: &str) -> String {
+    format!("{}/{}", pointer, escape_pointer_token(token))
+}
+
+fn diff_objects(
+    pointer: &str,
+    lhs: &Map<String, Value>,
+    rhs: &Map<String, Value>,
+    ops: &mut Vec<Value>,
+) {
+    for (key, lhs_value) in lhs {
+        let key_pointer = child_pointer(pointer, key);
+        match rhs.get(key) {
+            Some(rhs_value) => diff_values(&key_pointer, lhs_value, rhs_value, ops),
+            None => ops.push(json!({ "op": "remove", "path": key_pointer })),
+        }
+    }
+
+    for (key, rhs_value) in rhs {
+        if !lhs.contains_key(key) {
+            ops.push(json!({
+                "op": "add",
+                "path": child_pointer(pointer, key),
+                "value": rhs_value,
+            }));
+        }
+    }
+}
+
+fn diff_arrays(pointer: &str, lhs: &[Value], rhs: &[Value], ops: &mut Vec<Value>) {
+    // Elements deleted and inserted since the last element that is
+    // present on both sides.
+    let mut removed: Vec<&Value> = vec![];
+    let mut added: Vec<&Value> = vec![];
+    // The index in the array as it will be after applying all the
+    // operations so far.
+    let mut i = 0;
+
+    let mut flush = |removed: &mut Vec<&Value>, added: &mut Vec<&Value>, i: &mut usize| {
+        // Where elements were removed and added at the same position,
+        // diff them, so a small change inside an element is a small
+        // operation.
+        let num_paired = std::cmp::min(removed.len(), added.len());
+        for (lhs_value, rhs_value) in removed.iter().zip(added.iter()) {
+            diff_values(
+                &child_pointer(pointer, &i.to_string()),
+                lhs_value,
+                rhs_value,
+                ops,
+            );
+            *i += 1;
+        }
+
+        for _ in num_paired..removed.len() {
+            ops.push(json!({
+                "op": "remove",
+                "path": child_pointer(pointer, &i.to_string()),
+            }));
+        }
+        for rhs_value in &added[num_paired..] {
+            ops.push(json!({
+                "op": "add",
+                "path": child_pointer(pointer, &i.to_string()),
+                "value": rhs_value,
+            }));
+            *i += 1;
+        }
+
+        removed.clear();
+        added.clear();
+    };
+
+    for diff_res in myers_diff::slice(lhs, rhs) {
+        match diff_res {
+            myers_diff::DiffResult::Left(lhs_value) => removed.push(lhs_value),
+            myers_diff::DiffResult::Right(rhs_value) => added.push(rhs_value),
+            myers_diff::DiffResult::Both(_, _) => {
+                flush(&mut removed, &mut added, &mut i);
+                i += 1;
+            }
+        }
+    }
+    flush(&mut removed, &mut added, &mut i);
+}
+
+fn diff_values(pointer: &str, lhs: &Value, rhs: &Value, ops: &mut Vec<Value>) {
+    if lhs == rhs {
+        return;
+    }
+
+    match (lhs, rhs) {
+        (Value::Object(lhs), Value::Object(rhs)) => diff_objects(pointer, lhs, rhs, ops),
+        (Value::Array(lhs), Value::Array(rhs)) => diff_arrays(pointer, lhs, rhs, ops),
+        _ => ops.push(json!({ "op": "replace", "path": pointer, "value": rhs })),
+    }
+}
+
+/// Compute the JSON Patch operations that transform `lhs` into `rhs`.
+pub(crate) fn json_patch(lhs: &Value, rhs: &Value) -> Vec<Value> {
+    let mut ops = vec![];
+    diff_values("", lhs, rhs, &mut ops);
+    ops
+}
+
+pub(crate) fn print(out: &mut dyn Write, ops: &[Value]) -> std::io::Result<()> {
+    writeln!(
+        out,
+        "{}",
+        serde_json::to_string_pretty(ops).expect("failed to serialize JSON Patch")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn unescape_pointer_token(token: &str) -> String {
+        token.replace("~1", "/").replace("~0", "~")
+    }
+
+    /// A minimal JSON Patch implementation, supporting the operations
+    /// that we generate.
+    fn apply(doc: &Value, ops: &[Value]) -> Value {
+        let mut doc = doc.clone();
+        for op in ops {
+            let path = op["path"].as_str().unwrap();
+            if path.is_empty() {
+                assert_eq!(op["op"], "replace");
+                doc = op["value"].clone();
+                continue;
+            }
+
+            let (parent_path, token) = path.rsplit_once('/').unwrap();
+            let token = unescape_pointer_token(token);
+            let parent = doc.pointer_mut(parent_path).unwrap();
+
+            match (op["op"].as_str().unwrap(), parent) {
+                ("add", Value::Object(map)) | ("replace", Value::Object(map)) => {
+                    map.insert(token, op["value"].clone());
+                }
+                ("remove", Value::Object(map)) => {
+                    map.remove(&token);
+                }
+                ("add", Value::Array(items)) => {
+                    items.insert(token.parse().unwrap(), op["value"].clone());
+                }
+                ("replace", Value::Array(items)) => {
+                    items[token.parse::<usize>().unwrap()] = op["value"].clone();
+                }
+                ("remove", Value::Array(items)) => {
+                    items.remove(token.parse().unwrap());
+                }
+                _ => unreachable!(),
+            }
+        }
+        doc
+    }
+
+    fn assert_round_trips(lhs: Value, rhs: Value) {
+        let ops = json_patch(&lhs, &rhs);
+        assert_eq!(apply(&lhs, &ops), rhs);
+    }
+
+    #[test]
+    fn test_identical() {
+        assert!(json_patch(&json!({"a": [1, 2]}), &json!({"a": [1, 2]})).is_empty());
+    }
+
+    #[test]
+    fn test_replace_root() {
+        assert_eq!(
+            json_patch(&json!(1), &json!("x")),
+            vec![json!({"op": "replace", "path": "", "value": "x"})]
+        );
+    }
+
+    #[test]
+    fn test_escape_keys() {
+        assert_eq!(
+            json_patch(&json!({}), &json!({"a/b~c": 1})),
+            vec![json!({"op": "add", "path": "/a~1b~0c", "value": 1})]
+        );
+    }
+
+    #[test]
+    fn test_nested_change_in_array() {
+        assert_eq!(
+            json_patch(&json!([{"a": 1}]), &json!([{"a": 2}])),
+            vec![json!({"op": "replace", "path": "/0/a", "value": 2})]
+        );
+    }
+
+    #[test]
+    fn test_round_trip_arrays() {
+        assert_round_trips(json!([1, 2, 3, 4, 5]), json!([0, 2, 4, 5, 6, 7]));
+        assert_round_trips(json!([1, 2, 3]), json!([]));
+        assert_round_trips(json!([]), json!([1, [2, 3]]));
+        assert_round_trips(json!([[1, 2], 3]), json!([[1, 3], 3, 4]));
+    }
+
+    #[test]
+    fn test_round_trip_objects() {
+        assert_round_trips(
+            json!({"name": "x", "tags": ["a", "b"], "~/": {"n": null}}),
+            json!({"name": "y", "tags": ["b", "c"], "~/": {"n": true}, "new": 1}),
+        );
+    }
+}
@@ -0,0 +1,512 @@
+//! Split very long lines, such as minified JavaScript or single-line
+//! JSON, so they can be displayed.
+//!
+//! Hunks and context are whole lines, so a file that is a single
+//! 500 KiB line would be shown as one enormous hunk. Instead, we
+//! split long lines into shorter display lines at token boundaries.
+
+use line_numbers::{LineNumber, SingleLineSpan};
+
+use crate::parse::syntax::{MatchKind, MatchedPos};
+
+/// Lines longer than this many bytes are split for display.
+pub(crate) const LONG_LINE_THRESHOLD: usize = 1000;
+
+/// The length in bytes that we aim for when splitting a long line.
+/// Lines are only split between tokens, so a long token can make a
+/// display line longer than this.
+const SPLIT_LINE_LEN: usize = 80;
+
+/// The source and positions of both sides, after splitting long
+/// lines.
+pub(crate) struct SplitLines {
+    pub(crate) lhs_src: String,
+    pub(crate) rhs_src: String,
+    pub(crate) lhs_positions: Vec<MatchedPos>,
+    pub(crate) rhs_positions: Vec<MatchedPos>,
+    pub(crate) lhs_line_map: LineMap,
+    pub(crate) rhs_line_map: LineMap,
+}
+
+/// The source line of each display line on one side, so we can
+/// show and select lines by their number in the original file.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde-types", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct LineMap {
+    /// The source line of each display line. Empty if no lines were
+    /// split, so every display line is its own source line.
+    source_lines: Vec<u32>,
+    /// The byte offset in its source line where each display line
+    /// starts.
+    start_cols: Vec<u32>,
+}
+
+impl LineMap {
+    /// The line in the original file that display line `line` is
+    /// part of.
+    pub(crate) fn source_line(&self, line: LineNumber) -> LineNumber {
+        match (
+            self.source_lines.get(line.as_usize()),
+            self.source_lines.last(),
+        ) {
+            (Some(source_line), _) => (*source_line).into(),
+            // Lines after the end, e.g. when counting lines, are
+            // never split.
+            (None, Some(last)) => (last + 1 + line.0 - self.source_lines.len() as u32).into(),
+            (None, None) => line,
+        }
+    }
+
+    /// The display line that shows byte `col` of source line `line`.
+    pub(crate) fn display_line(&self, line: LineNumber, col: usize) -> LineNumber {
+        let first = self.source_lines.partition_point(|l| *l < line.0);
+        let end = self.source_lines.partition_point(|l| *l <= line.0);
+        match self.source_lines.last() {
+            Some(_) if end > first => {
+                let part = self.start_cols[first + 1..end].partition_point(|c| *c as usize <= col);
+                ((first + part) as u32).into()
+            }
+            // As in `source_line`, lines after the end aren't split.
+            Some(last) => (self.source_lines.len() as u32 + line.0 - last - 1).into(),
+            None => line,
+        }
+    }
+
+    /// Whether display line `line` continues the source line of the
+    /// display line before it, so it has no line number of its own.
+    pub(crate) fn is_continuation(&self, line: LineNumber) -> bool {
+        match (
+            line.as_usize().checked_sub(1),
+            self.source_lines.get(line.as_usize()),
+        ) {
+            (Some(prev), Some(source_line)) => self.source_lines.get(prev) == Some(source_line),
+            _ => false,
+        }
+    }
+
+    /// The line number shown in the gutter for display line `line`,
+    /// e.g. "12". If `line` continues a split line that was shown
+    /// just before it, at `prev_line`, we show dots instead, like
+    /// lines wrapped side by side.
+    pub(crate) fn display(&self, line: LineNumber, prev_line: Option<LineNumber>) -> String {
+        let source_line = self.source_line(line).display();
+        if self.is_continuation(line) && prev_line.map(|prev| prev.0 + 1) == Some(line.0) {
+            ".".repeat(source_line.len())
+        } else {
+            source_line
+        }
+    }
+}
+
+/// Where we split the lines of one side.
+struct LineSplits {
+    /// For each original line, the offsets where a new display line
+    /// starts. Empty for lines that aren't split.
+    breaks: Vec<Vec<usize>>,
+    /// For each original line, its length in bytes.
+    lens: Vec<usize>,
+    /// For each original line, the display line of its first part.
+    first_display_line: Vec<u32>,
+}
+
+impl LineSplits {
+    fn new(src: &str, positions: &[MatchedPos]) -> Self {
+        let lines: Vec<&str> = src.split('\n').collect();
+
+        let mut spans_by_line: Vec<Vec<(usize, usize)>> = vec![vec![]; lines.len()];
+        for mp in positions {
+            if let Some(spans) = spans_by_line.get_mut(mp.pos.line.0 as usize) {
+                spans.push((mp.pos.start_col as usize, mp.pos.end_col as usize));
+            }
+        }
+
+        let mut breaks = Vec::with_capacity(lines.len());
+        let mut first_display_line = Vec::with_capacity(lines.len());
+        let mut display_line = 0;
+        for (line, spans) in lines.iter().zip(spans_by_line) {
+            let line_breaks = if line.len() > LONG_LINE_THRESHOLD {
+                find_breaks(line, &merge_overlapping(spans))
+            } else {
+                vec![]
+            };
+
+            first_display_line.push(display_line);
+            display_line += line_breaks.len() as u32 + 1;
+            breaks.push(line_breaks);
+        }
+
+        Self {
+            breaks,
+            lens: lines.iter().map(|line| line.len()).collect(),
+            first_display_line,
+        }
+    }
+
+    fn has_splits(&self) -> bool {
+        self.breaks
+            .iter()
+            .any(|line_breaks| !line_breaks.is_empty())
+    }
+
+    fn split_src(&self, src: &str) -> String {
+        let mut res = String::with_capacity(src.len() + self.breaks.len());
+        for (i, (line, line_breaks)) in src.split('\n').zip(&self.breaks).enumerate() {
+            if i > 0 {
+                res.push('\n');
+            }
+
+            let mut part_start = 0;
+            for line_break in line_breaks {
+                res.push_str(&line[part_start..*line_break]);
+                res.push('\n');
+                part_start = *line_break;
+            }
+            res.push_str(&line[part_start..]);
+        }
+        res
+    }
+
+    fn line_map(&self) -> LineMap {
+        let mut source_lines = vec![];
+        let mut start_cols = vec![];
+        for (line, line_breaks) in self.breaks.iter().enumerate() {
+            source_lines.push(line as u32);
+            start_cols.push(0);
+            for line_break in line_breaks {
+                source_lines.push(line as u32);
+                start_cols.push(*line_break as u32);
+            }
+        }
+        LineMap {
+            source_lines,
+            start_cols,
+        }
+    }
+
+    fn split_line(&self, line: LineNumber) -> LineNumber {
+        match self.first_display_line.get(line.0 as usize) {
+            Some(display_line) => (*display_line).into(),
+            None => line,
+        }
+    }
+
+    fn split_span(&self, span: &SingleLineSpan) -> SingleLineSpan {
+        let line = span.line.0 as usize;
+        let line_breaks = match self.breaks.get(line) {
+            Some(line_breaks) => line_breaks,
+            None => return *span,
+        };
+
+        let part = line_breaks.partition_point(|b| *b <= span.start_col as usize);
+        let part_start = if part == 0 { 0 } else { line_breaks[part - 1] };
+        let part_end = line_breaks.get(part).copied().unwrap_or(self.lens[line]);
+
+        SingleLineSpan {
+            line: (self.first_display_line[line] + part as u32).into(),
+            start_col: span.start_col - part_start as u32,
+            end_col: (std::cmp::min(span.end_col as usize, part_end) - part_start) as u32,
+        }
+    }
+
+    fn split_spans(&self, spans: &[SingleLineSpan]) -> Vec<SingleLineSpan> {
+        spans.iter().map(|span| self.split_span(span)).collect()
+    }
+}
+
+/// Merge overlapping spans, so we can check whether an offset is
+/// inside a token. Spans that only touch aren't merged, as the
+/// boundary between them is somewhere we can split.
+fn merge_overlapping(mut spans: Vec<(usize, usize)>) -> Vec<(usize, usize)> {
+    spans.sort_unstable();
+
+    let mut res: Vec<(usize, usize)> = vec![];
+    for (start, end) in spans {
+        match res.last_mut() {
+            Some((_, prev_end)) if start < *prev_end => {
+                *prev_end = std::cmp::max(*prev_end, end);
+            }
+            _ => res.push((start, end)),
+        }
+    }
+    res
+}
+
+/// The merged span that `offset` is strictly inside, if any.
+fn containing_span(spans: &[(usize, usize)], offset: usize) -> Option<(usize, usize)> {
+    let i = spans.partition_point(|(start, _)| *start < offset);
+    if i == 0 {
+        return None;
+    }
+
+    let (start, end) = spans[i - 1];
+    if offset < end {
+        Some((start, end))
+    } else {
+        None
+    }
+}
+
+/// Whether `offset` in `line` is between two word characters, so
+/// splitting there would split a word.
+fn inside_word(line: &str, offset: usize) -> bool {
+    let before = line[..offset].chars().next_back();
+    let after = line[offset..].chars().next();
+    match (before, after) {
+        (Some(before), Some(after)) => is_word_char(before) && is_word_char(after),
+        _ => false,
+    }
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+/// Find the last offset after `part_start` and at most `limit` where
+/// we can split `line`.
+fn last_break_before(
+    line: &str,
+    spans: &[(usize, usize)],
+    part_start: usize,
+    limit: usize,
+    allow_inside_word: bool,
+) -> Option<usize> {
+    let mut offset = limit;
+    while offset > part_start {
+        if !line.is_char_boundary(offset) {
+            offset -= 1;
+        } else if let Some((start, _)) = containing_span(spans, offset) {
+            offset = start;
+        } else if !allow_inside_word && inside_word(line, offset) {
+            offset -= 1;
+        } else {
+            return Some(offset);
+        }
+    }
+    None
+}
+
+/// Find the first offset after `limit` where we can split `line`.
+fn first_break_after(line: &str, spans: &[(usize, usize)], limit: usize) -> Option<usize> {
+    let mut offset = limit + 1;
+    while offset < line.len() {
+        if !line.is_char_boundary(offset) {
+            offset += 1;
+        } else if let Some((_, end)) = containing_span(spans, offset) {
+            offset = end;
+        } else {
+            return Some(offset);
+        }
+    }
+    None
+}
+
+/// Find the offsets where we should start a new display line, so
+/// each part of `line` is about `SPLIT_LINE_LEN` bytes long.
+///
+/// We never split inside a span in `spans`, so every token stays on
+/// a single display line. Outside spans, we avoid splitting words
+/// where possible.
+fn find_breaks(line: &str, spans: &[(usize, usize)]) -> Vec<usize> {
+    let mut breaks = vec![];
+    let mut part_start = 0;
+    while line.len() - part_start > SPLIT_LINE_LEN {
+        let limit = part_start + SPLIT_LINE_LEN;
+        let line_break = last_break_before(line, spans, part_start, limit, false)
+            .or_else(|| last_break_before(line, spans, part_start, limit, true))
+            .or_else(|| first_break_after(line, spans, limit));
+
+        match line_break {
+            Some(line_break) => {
+                breaks.push(line_break);
+                part_start = line_break;
+            }
+            None => break,
+        }
+    }
+    breaks
+}
+
+fn split_positions(
+    positions: &[MatchedPos],
+    splits: &LineSplits,
+    opposite_splits: &LineSplits,
+) -> Vec<MatchedPos> {
+    positions
+        .iter()
+        .map(|mp| {
+            let kind = match &mp.kind {
+                MatchKind::UnchangedToken {
+                    highlight,
+                    self_pos,
+                    opposite_pos,
+                } => MatchKind::UnchangedToken {
+                    highlight: *highlight,
+                    self_pos: splits.split_spans(self_pos),
+                    opposite_pos: opposite_splits.split_spans(opposite_pos),
+                },
+                MatchKind::NovelLinePart {
+                    highlight,
+                    self_pos,
+                    opposite_pos,
+                } => MatchKind::NovelLinePart {
+                    highlight: *highlight,
+                    self_pos: splits.split_span(self_pos),
+                    opposite_pos: opposite_splits.split_spans(opposite_pos),
+                },
+                MatchKind::Moved {
+                    highlight,
+                    opposite_line,
+                } => MatchKind::Moved {
+                    highlight: *highlight,
+                    opposite_line: opposite_splits.split_line(*opposite_line),
+                },
+                MatchKind::Copied {
+                    highlight,
+                    source_line,
+                } => MatchKind::Copied {
+                    highlight: *highlight,
+                    source_line: opposite_splits.split_line(*source_line),
+                },
+                kind => kind.clone(),
+            };
+
+            MatchedPos {
+                kind,
+                pos: splits.split_span(&mp.pos),
+            }
+        })
+        .collect()
+}
+
+/// If either side has lines longer than `LONG_LINE_THRESHOLD`, split
+/// them into shorter lines and update the positions to match.
+pub(crate) fn split_long_lines(
+    lhs_src: &str,
+    rhs_src: &str,
+    lhs_positions: &[MatchedPos],
+    rhs_positions: &[MatchedPos],
+) -> Option<SplitLines> {
+    let lhs_splits = LineSplits::new(lhs_src, lhs_positions);
+    let rhs_splits = LineSplits::new(rhs_src, rhs_positions);
+    if !lhs_splits.has_splits() && !rhs_splits.has_splits() {
+        return None;
+    }
+
+    Some(SplitLines {
+        lhs_src: lhs_splits.split_src(lhs_src),
+        rhs_src: rhs_splits.split_src(rhs_src),
+        lhs_positions: split_positions(lhs_positions, &lhs_splits, &rhs_splits),
+        rhs_positions: split_positions(rhs_positions, &rhs_splits, &lhs_splits),
+        lhs_line_map: lhs_splits.line_map(),
+        rhs_line_map: rhs_splits.line_map(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::parse::syntax::TokenKind;
+
+    #[test]
+    fn test_find_breaks_between_words() {
+        let line = "foo ".repeat(30);
+        let breaks = find_breaks(&line, &[]);
+
+        assert_eq!(breaks, vec![80]);
+    }
+
+    #[test]
+    fn test_find_breaks_not_inside_span() {
+        let line = "x".repeat(200);
+        // A single token covering most of the line.
+        let breaks = find_breaks(&line, &[(10, 190)]);
+
+        assert_eq!(breaks, vec![10, 190]);
+    }
+
+    #[test]
+    fn test_find_breaks_not_inside_char() {
+        let line = "é".repeat(100);
+        for line_break in find_breaks(&line, &[]) {
+            assert!(line.is_char_boundary(line_break));
+        }
+    }
+
+    #[test]
+    fn test_short_lines_unchanged() {
+        let positions = vec![MatchedPos {
+            kind: MatchKind::Novel {
+                highlight: TokenKind::Delimiter,
+                punctuation: true,
+            },
+            pos: SingleLineSpan {
+                line: 0.into(),
+                start_col: 0,
+                end_col: 1,
+            },
+        }];
+
+        assert!(split_long_lines("a\nb\n", "a\n", &positions, &[]).is_none());
+    }
+
+    #[test]
+    fn test_split_long_lines() {
+        let line = "ab ".repeat(500);
+        let src = format!("start\n{}\nend", line);
+        let positions = vec![MatchedPos {
+            kind: MatchKind::Novel {
+                highlight: TokenKind::Delimiter,
+                punctuation: false,
+            },
+            pos: SingleLineSpan {
+                line: 2.into(),
+                start_col: 0,
+                end_col: 3,
+            },
+        }];
+
+        let split = split_long_lines(&src, "", &positions, &[]).unwrap();
+        assert_eq!(split.lhs_src.replace('\n', ""), src.replace('\n', ""));
+        assert!(split
+            .lhs_src
+            .lines()
+            .all(|line| line.len() <= SPLIT_LINE_LEN));
+
+        // The span on the last line moves to the last display line.
+        let num_display_lines = split.lhs_src.lines().count() as u32;
+        assert_eq!(
+            split.lhs_positions[0].pos,
+            SingleLineSpan {
+                line: (num_display_lines - 1).into(),
+                start_col: 0,
+                end_col: 3,
+            }
+        );
+
+        // Every display line of the long line keeps its line number.
+        let line_map = &split.lhs_line_map;
+        assert_eq!(line_map.source_line(1.into()), 1.into());
+        assert_eq!(line_map.source_line(2.into()), 1.into());
+        assert!(!line_map.is_continuation(1.into()));
+        assert!(line_map.is_continuation(2.into()));
+        assert_eq!(line_map.display(2.into(), Some(1.into())), ".");
+        // The first part shown in a hunk has the line number.
+        assert_eq!(line_map.display(2.into(), None), "2");
+        assert_eq!(
+            line_map.source_line((num_display_lines - 1).into()),
+            2.into()
+        );
+        assert!(!line_map.is_continuation((num_display_lines - 1).into()));
+
+        assert_eq!(line_map.display_line(0.into(), 0), 0.into());
+        assert_eq!(line_map.display_line(1.into(), 0), 1.into());
+        assert_eq!(line_map.display_line(1.into(), SPLIT_LINE_LEN), 2.into());
+        assert_eq!(
+            line_map.display_line(1.into(), line.len()),
+            (num_display_lines - 2).into()
+        );
+        assert_eq!(line_map.display_line(3.into(), 0), num_display_lines.into());
+    }
+}
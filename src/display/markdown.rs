@@ -0,0 +1,405 @@
+//! GitHub-flavored Markdown output, for posting diffs in pull request
+//! comments.
+//!
+//! Each file is a collapsible `<details>` section, with a fenced
+//! `diff` code block per hunk.
+
+use std::io::Write;
+
+use line_numbers::LineNumber;
+
+use crate::{
+    constants::Side,
+    display::{
+        context::{calculate_after_context, calculate_before_context, opposite_positions},
+        html::escape_html,
+        hunks::{hunk_context, Hunk},
+        style::{self, replace_tabs},
+    },
+    lines::MaxLine,
+    options::DisplayOptions,
+    summary::{DiffResult, FileContent, FileFormat},
+};
+
+const TRUNCATED_NOTE: &str = "\n*Output truncated: exceeded --markdown-max-bytes.*\n";
+
+/// A code fence that doesn't conflict with any backticks in `content`.
+fn fence_for(content: &str) -> String {
+    let mut longest_run = 0;
+    let mut current_run = 0;
+    for c in content.chars() {
+        if c == '`' {
+            current_run += 1;
+            longest_run = std::cmp::max(longest_run, current_run);
+        } else {
+            current_run = 0;
+        }
+    }
+
+    "`".repeat(std::cmp::max(3, longest_run + 1))
+}
+
+/// The header text for a file, without any terminal styling.
+fn plain_header(
+    summary: &DiffResult,
+    hunk_num: usize,
+    hunk_total: usize,
+    file_format: &FileFormat,
+    display_options: &DisplayOptions,
+) -> String {
+    let plain_options = DisplayOptions {
+        use_color: false,
+        ..display_options.clone()
+    };
+    style::header(
+        &summary.display_path,
+        None,
+        hunk_num,
+        hunk_total,
+        file_format,
+        None,
+        &plain_options,
+    )
+}
+
+/// The start of the `<details>` section for a file.
+fn details_start(
+    summary: &DiffResult,
+    file_format: &FileFormat,
+    display_options: &DisplayOptions,
+) -> String {
+    let mut res = format!(
+        "<details>\n<summary>{}</summary>\n\n",
+        escape_html(&plain_header(summary, 1, 1, file_format, display_options))
+    );
+    if let Some(extra_info) = &summary.extra_info {
+        res.push_str(&format!("{}\n\n", escape_html(extra_info)));
+    }
+    res
+}
+
+const DETAILS_END: &str = "</details>\n\n";
+
+fn diff_line(
+    prefix: char,
+    line_num: LineNumber,
+    num_width: usize,
+    line: &str,
+    tab_width: usize,
+) -> String {
+    format!(
+        "{}{:>width$} {}\n",
+        prefix,
+        line_num.display(),
+        replace_tabs(line, tab_width),
+        width = num_width,
+    )
+}
+
+/// The lines of `hunk` in order, with their prefix and the side
+/// they're from, like a unified diff. Novel lines are `-` or `+`,
+/// with each run of removed lines before the added lines, and the
+/// unchanged lines between the runs are context.
+fn hunk_rows(hunk: &Hunk) -> Vec<(char, Side, LineNumber)> {
+    let line_range = |lines: Vec<LineNumber>| match (lines.iter().min(), lines.iter().max()) {
+        (Some(start), Some(end)) => start.0..end.0 + 1,
+        _ => 0..0,
+    };
+    let lhs_range = line_range(hunk.lines.iter().filter_map(|(lhs, _)| *lhs).collect());
+    let rhs_range = line_range(hunk.lines.iter().filter_map(|(_, rhs)| *rhs).collect());
+
+    let mut rows = vec![];
+    let mut lhs_i = lhs_range.start;
+    let mut rhs_i = rhs_range.start;
+    while lhs_i < lhs_range.end || rhs_i < rhs_range.end {
+        while lhs_i < lhs_range.end && hunk.novel_lhs.contains(&lhs_i.into()) {
+            rows.push(('-', Side::Left, lhs_i.into()));
+            lhs_i += 1;
+        }
+        while rhs_i < rhs_range.end && hunk.novel_rhs.contains(&rhs_i.into()) {
+            rows.push(('+', Side::Right, rhs_i.into()));
+            rhs_i += 1;
+        }
+
+        // Both sides are now at an unchanged line, or at the end of
+        // the hunk.
+        match (lhs_i < lhs_range.end, rhs_i < rhs_range.end) {
+            (true, true) => {
+                rows.push((' ', Side::Left, lhs_i.into()));
+                lhs_i += 1;
+                rhs_i += 1;
+            }
+            (true, false) => {
+                rows.push((' ', Side::Left, lhs_i.into()));
+                lhs_i += 1;
+            }
+            (false, true) => {
+                rows.push((' ', Side::Right, rhs_i.into()));
+                rhs_i += 1;
+            }
+            (false, false) => {}
+        }
+    }
+    rows
+}
+
+/// Render each hunk of this file as a fenced code block.
+fn hunk_blocks(
+    summary: &DiffResult,
+    lhs_src: &str,
+    rhs_src: &str,
+    display_options: &DisplayOptions,
+) -> Vec<String> {
+    let lhs_lines: Vec<&str> = lhs_src.lines().collect();
+    let rhs_lines: Vec<&str> = rhs_src.lines().collect();
+    let line_at = |lines: &[&str], line_num: LineNumber| -> String {
+        lines
+            .get(line_num.as_usize())
+            .copied()
+            .unwrap_or("")
+            .to_owned()
+    };
+
+    let opposite_to_lhs = opposite_positions(&summary.lhs_positions);
+    let opposite_to_rhs = opposite_positions(&summary.rhs_positions);
+    let lhs_max_line = lhs_src.max_line();
+    let rhs_max_line = rhs_src.max_line();
+
+    let num_width = std::cmp::max(lhs_max_line.display().len(), rhs_max_line.display().len());
+    let tab_width = display_options.for_file(&summary.file_format).tab_width;
+
+    let mut blocks = vec![];
+    for hunk in &summary.hunks {
+        let hunk_lines = hunk.lines.clone();
+        let context = hunk_context(hunk, &lhs_lines, &rhs_lines, display_options);
+
+        let before_lines = calculate_before_context(
+            &hunk_lines,
+            &opposite_to_lhs,
+            &opposite_to_rhs,
+            context.before,
+        );
+        let after_lines = calculate_after_context(
+            &[&before_lines[..], &hunk_lines[..]].concat(),
+            &opposite_to_lhs,
+            &opposite_to_rhs,
+            lhs_max_line,
+            rhs_max_line,
+            context.after,
+        );
+
+        let mut content = String::new();
+        for (lhs_line, _) in &before_lines {
+            if let Some(lhs_line) = lhs_line {
+                let line = line_at(&lhs_lines, *lhs_line);
+                content.push_str(&diff_line(' ', *lhs_line, num_width, &line, tab_width));
+            }
+        }
+        for (prefix, side, line_num) in hunk_rows(hunk) {
+            let lines = match side {
+                Side::Left => &lhs_lines,
+                Side::Right => &rhs_lines,
+            };
+            let line = line_at(lines, line_num);
+            content.push_str(&diff_line(prefix, line_num, num_width, &line, tab_width));
+        }
+        for (_, rhs_line) in &after_lines {
+            if let Some(rhs_line) = rhs_line {
+                let line = line_at(&rhs_lines, *rhs_line);
+                content.push_str(&diff_line(' ', *rhs_line, num_width, &line, tab_width));
+            }
+        }
+
+        let fence = fence_for(&content);
+        blocks.push(format!("{}diff\n{}{}\n\n", fence, content, fence));
+    }
+
+    blocks
+}
+
+/// The opening of the `<details>` section for this file, and the
+/// blocks in its body. Returns `None` if the file shouldn't be shown.
+fn file_parts(
+    summary: &DiffResult,
+    display_options: &DisplayOptions,
+) -> Option<(String, Vec<String>)> {
+    if !display_options.print_unchanged && !summary.has_reportable_change() {
+        return None;
+    }
+
+    match (&summary.lhs_src, &summary.rhs_src) {
+        (FileContent::Text(lhs_src), FileContent::Text(rhs_src)) => {
+            let start = details_start(summary, &summary.file_format, display_options);
+
+            if !summary.has_syntactic_changes {
+                let message = match summary.file_format {
+                    _ if summary.lhs_src == summary.rhs_src => "No changes.",
+                    FileFormat::SupportedLanguage(_) => "No syntactic changes.",
+                    _ => "No changes.",
+                };
+                return Some((start, vec![format!("{}\n\n", message)]));
+            }
+
+            if summary.hunks.is_empty() {
+                let message = match summary.file_format {
+                    FileFormat::SupportedLanguage(_) => "Has syntactic changes.",
+                    _ => "Has changes.",
+                };
+                return Some((start, vec![format!("{}\n\n", message)]));
+            }
+
+            Some((
+                start,
+                hunk_blocks(summary, lhs_src, rhs_src, display_options),
+            ))
+        }
+        _ => {
+            let start = details_start(summary, &FileFormat::Binary, display_options);
+            Some((start, vec![format!("{}\n\n", summary.binary_message())]))
+        }
+    }
+}
+
+/// Render `diffs` as Markdown. If `max_bytes` is set, stop before
+/// exceeding it and add a note saying the output was truncated.
+fn render(
+    diffs: &[DiffResult],
+    display_options: &DisplayOptions,
+    max_bytes: Option<usize>,
+) -> String {
+    // Leave room for closing the current section and the note.
+    let budget = max_bytes
+        .map(|max_bytes| max_bytes.saturating_sub(DETAILS_END.len() + TRUNCATED_NOTE.len()));
+    let fits = |res: &str, part: &str| match budget {
+        Some(budget) => res.len() + part.len() <= budget,
+        None => true,
+    };
+
+    let mut res = String::new();
+    for summary in diffs {
+        let (start, blocks) = match file_parts(summary, display_options) {
+            Some(parts) => parts,
+            None => continue,
+        };
+
+        if !fits(&res, &start) {
+            res.push_str(TRUNCATED_NOTE);
+            return res;
+        }
+        res.push_str(&start);
+
+        for block in blocks {
+            if !fits(&res, &block) {
+                res.push_str(DETAILS_END);
+                res.push_str(TRUNCATED_NOTE);
+                return res;
+            }
+            res.push_str(&block);
+        }
+
+        res.push_str(DETAILS_END);
+    }
+
+    res
+}
+
+pub(crate) fn print(
+    out: &mut dyn Write,
+    diffs: &[DiffResult],
+    display_options: &DisplayOptions,
+) -> std::io::Result<()> {
+    write!(
+        out,
+        "{}",
+        render(diffs, display_options, display_options.markdown_max_bytes)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::display::long_lines::LineMap;
+
+    fn binary_diff(path: &str) -> DiffResult {
+        DiffResult {
+            display_path: path.to_owned(),
+            path_bytes: None,
+            renamed_from: None,
+            missing_side: None,
+            mode_change: None,
+            mtime_change: None,
+            binary_info: None,
+            extra_info: None,
+            file_format: FileFormat::Binary,
+            lhs_src: FileContent::Binary,
+            rhs_src: FileContent::Binary,
+            hunks: vec![],
+            lhs_positions: vec![],
+            rhs_positions: vec![],
+            lhs_line_map: LineMap::default(),
+            rhs_line_map: LineMap::default(),
+            degradation: None,
+            has_byte_changes: true,
+            has_syntactic_changes: false,
+        }
+    }
+
+    #[test]
+    fn test_fence_for() {
+        assert_eq!(fence_for("foo"), "```");
+        assert_eq!(fence_for("a ```` b"), "`````");
+    }
+
+    #[test]
+    fn test_diff_line() {
+        assert_eq!(diff_line('+', 9.into(), 3, "\tfoo", 2), "+ 10   foo\n");
+    }
+
+    #[test]
+    fn test_hunk_rows() {
+        // Line 1 is unchanged between two changes.
+        let hunk = Hunk {
+            novel_lhs: HashSet::from([0.into(), 2.into()]),
+            novel_rhs: HashSet::from([0.into(), 2.into()]),
+            lines: vec![
+                (Some(0.into()), Some(0.into())),
+                (Some(2.into()), Some(2.into())),
+            ],
+            enclosing_declaration: None,
+        };
+        assert_eq!(
+            hunk_rows(&hunk),
+            vec![
+                ('-', Side::Left, 0.into()),
+                ('+', Side::Right, 0.into()),
+                (' ', Side::Left, 1.into()),
+                ('-', Side::Left, 2.into()),
+                ('+', Side::Right, 2.into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_render_binary() {
+        assert_eq!(
+            render(&[binary_diff("a.png")], &DisplayOptions::default(), None),
+            "<details>\n<summary>a.png --- Binary</summary>\n\nBinary contents changed.\n\n</details>\n\n"
+        );
+    }
+
+    #[test]
+    fn test_render_truncated() {
+        let diffs = vec![binary_diff("a.png"), binary_diff("b.png")];
+        let max_bytes = 150;
+        let res = render(&diffs, &DisplayOptions::default(), Some(max_bytes));
+
+        assert!(res.len() <= max_bytes);
+        assert!(res.contains("a.png"));
+        assert!(!res.contains("b.png"));
+        assert!(res.ends_with(TRUNCATED_NOTE));
+    }
+}
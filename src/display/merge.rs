@@ -0,0 +1,829 @@
+//! Three-way display of a merge, for `--merge`.
+//!
+//! We diff the base against each side, then align the two diffs on
+//! the base lines. Our version is shown on the left and their version
+//! on the right, with the base line numbers in a shared gutter.
+//!
+//! `--combined` shows what a merge commit did instead. We diff the
+//! merged result against each parent, align the two diffs on the
+//! merged lines in the same way, and show the merged file with each
+//! change marked by where it came from.
+
+use std::{
+    cmp::{max, Ordering},
+    collections::HashSet,
+    io::Write,
+    ops::Range,
+};
+
+use line_numbers::{LineNumber, SingleLineSpan};
+use owo_colors::{OwoColorize, Style};
+
+use crate::{
+    constants::Side,
+    display::{
+        context::all_matched_lines_filled,
+        side_by_side::lines_with_novel,
+        style::{self, color_positions, novel_style, split_and_apply, SyntaxHighlights},
+    },
+    hash::DftHashMap,
+    options::DisplayOptions,
+    parse::syntax::zip_pad_shorter,
+    summary::DiffResult,
+};
+
+const SPACER: &str = " ";
+
+/// A displayed line: a line from each version, any of which may be
+/// missing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Row {
+    ours: Option<LineNumber>,
+    base: Option<LineNumber>,
+    theirs: Option<LineNumber>,
+}
+
+/// Whether each side of a row differs from the base.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+struct RowChanges {
+    ours: bool,
+    theirs: bool,
+    /// Both sides changed the same code, so this row is a conflict.
+    conflict: bool,
+}
+
+impl RowChanges {
+    fn any(self) -> bool {
+        self.ours || self.theirs
+    }
+}
+
+/// Every line in the base and `side`, aligned.
+fn side_alignment(
+    diff: &DiffResult,
+    base_lines: &[&str],
+    side_lines: &[&str],
+) -> Vec<(Option<LineNumber>, Option<LineNumber>)> {
+    if !diff.has_byte_changes {
+        // Identical files don't have any positions, so every line
+        // matches the line at the same index.
+        return (0..base_lines.len() as u32)
+            .map(|i| (Some(i.into()), Some(i.into())))
+            .collect();
+    }
+
+    all_matched_lines_filled(
+        &diff.lhs_positions,
+        &diff.rhs_positions,
+        base_lines,
+        side_lines,
+    )
+}
+
+/// Combine the alignments of base with ours and base with theirs into
+/// rows, matching up the base lines. Lines that were added on both
+/// sides at the same point share rows.
+fn align_rows(
+    ours: &[(Option<LineNumber>, Option<LineNumber>)],
+    theirs: &[(Option<LineNumber>, Option<LineNumber>)],
+) -> Vec<Row> {
+    let mut rows = vec![];
+    let (mut i, mut j) = (0, 0);
+    loop {
+        let row = match (ours.get(i), theirs.get(j)) {
+            (Some((None, ours_line)), Some((None, theirs_line))) => {
+                i += 1;
+                j += 1;
+                Row {
+                    ours: *ours_line,
+                    base: None,
+                    theirs: *theirs_line,
+                }
+            }
+            (Some((None, ours_line)), _) => {
+                i += 1;
+                Row {
+                    ours: *ours_line,
+                    base: None,
+                    theirs: None,
+                }
+            }
+            (_, Some((None, theirs_line))) => {
+                j += 1;
+                Row {
+                    ours: None,
+                    base: None,
+                    theirs: *theirs_line,
+                }
+            }
+            (Some((Some(ours_base), ours_line)), Some((Some(theirs_base), theirs_line))) => {
+                match ours_base.cmp(theirs_base) {
+                    Ordering::Equal => {
+                        i += 1;
+                        j += 1;
+                        Row {
+                            ours: *ours_line,
+                            base: Some(*ours_base),
+                            theirs: *theirs_line,
+                        }
+                    }
+                    Ordering::Less => {
+                        i += 1;
+                        Row {
+                            ours: *ours_line,
+                            base: Some(*ours_base),
+                            theirs: None,
+                        }
+                    }
+                    Ordering::Greater => {
+                        j += 1;
+                        Row {
+                            ours: None,
+                            base: Some(*theirs_base),
+                            theirs: *theirs_line,
+                        }
+                    }
+                }
+            }
+            (Some((Some(base_line), ours_line)), None) => {
+                i += 1;
+                Row {
+                    ours: *ours_line,
+                    base: Some(*base_line),
+                    theirs: None,
+                }
+            }
+            (None, Some((Some(base_line), theirs_line))) => {
+                j += 1;
+                Row {
+                    ours: None,
+                    base: Some(*base_line),
+                    theirs: *theirs_line,
+                }
+            }
+            (None, None) => break,
+        };
+        rows.push(row);
+    }
+
+    rows
+}
+
+/// The base lines containing a token that was changed on both sides.
+fn conflict_base_lines(ours_diff: &DiffResult, theirs_diff: &DiffResult) -> HashSet<LineNumber> {
+    let novel_in_ours: HashSet<(LineNumber, u32)> = ours_diff
+        .lhs_positions
+        .iter()
+        .filter(|mp| mp.kind.is_novel())
+        .map(|mp| (mp.pos.line, mp.pos.start_col))
+        .collect();
+
+    theirs_diff
+        .lhs_positions
+        .iter()
+        .filter(|mp| mp.kind.is_novel() && novel_in_ours.contains(&(mp.pos.line, mp.pos.start_col)))
+        .map(|mp| mp.pos.line)
+        .collect()
+}
+
+fn row_changes(rows: &[Row], ours_diff: &DiffResult, theirs_diff: &DiffResult) -> Vec<RowChanges> {
+    let (base_novel_in_ours, ours_novel) =
+        lines_with_novel(&ours_diff.lhs_positions, &ours_diff.rhs_positions);
+    let (base_novel_in_theirs, theirs_novel) =
+        lines_with_novel(&theirs_diff.lhs_positions, &theirs_diff.rhs_positions);
+    let conflict_lines = conflict_base_lines(ours_diff, theirs_diff);
+
+    let is_novel = |line: Option<LineNumber>, novel_lines: &HashSet<LineNumber>| {
+        line.map_or(false, |line| novel_lines.contains(&line))
+    };
+
+    rows.iter()
+        .map(|row| {
+            let ours = is_novel(row.ours, &ours_novel) || is_novel(row.base, &base_novel_in_ours);
+            let theirs =
+                is_novel(row.theirs, &theirs_novel) || is_novel(row.base, &base_novel_in_theirs);
+            let conflict = match row.base {
+                Some(base_line) => conflict_lines.contains(&base_line),
+                // Both sides added lines at the same point.
+                None => ours && theirs,
+            };
+            RowChanges {
+                ours,
+                theirs,
+                conflict,
+            }
+        })
+        .collect()
+}
+
+/// The ranges of rows to display: the changed rows, with
+/// `before_context` and `after_context` unchanged rows around them.
+fn regions(
+    changes: &[RowChanges],
+    before_context: usize,
+    after_context: usize,
+) -> Vec<Range<usize>> {
+    let mut regions: Vec<Range<usize>> = vec![];
+    for (i, _) in changes.iter().enumerate().filter(|(_, c)| c.any()) {
+        let start = i.saturating_sub(before_context);
+        let end = std::cmp::min(i + after_context + 1, changes.len());
+
+        match regions.last_mut() {
+            Some(prev) if start <= prev.end => prev.end = max(prev.end, end),
+            _ => regions.push(start..end),
+        }
+    }
+    regions
+}
+
+/// Styles for the lines on the RHS of `diff`, with novel tokens shown
+/// as being on `side`.
+fn styles_by_line(
+    diff: &DiffResult,
+    side: Side,
+    display_options: &DisplayOptions,
+) -> DftHashMap<LineNumber, Vec<(SingleLineSpan, Style)>> {
+    let mut styles: DftHashMap<LineNumber, Vec<(SingleLineSpan, Style)>> = DftHashMap::default();
+    if !display_options.style_content() {
+        return styles;
+    }
+
+    for (span, style) in color_positions(
+        side,
+        &display_options.palette,
+        display_options.syntax_highlight,
+        display_options.dim_punctuation,
+        &SyntaxHighlights::default(),
+        &diff.file_format,
+        &diff.rhs_positions,
+    ) {
+        styles
+            .entry(span.line)
+            .or_insert_with(Vec::new)
+            .push((span, style));
+    }
+    styles
+}
+
+fn format_line_num(
+    line_num: Option<LineNumber>,
+    width: usize,
+    is_novel: bool,
+    display_options: &DisplayOptions,
+) -> String {
+    let s = match line_num {
+        Some(line_num) => format!("{:>width$}", line_num.display(), width = width),
+        None => " ".repeat(width),
+    };
+
+    if !display_options.use_color {
+        s
+    } else if is_novel {
+        s.style(novel_style(
+            Style::new(),
+            Side::Right,
+            &display_options.palette,
+        ))
+        .to_string()
+    } else {
+        s.dimmed().to_string()
+    }
+}
+
+/// The markers either side of the base line number, showing which
+/// sides changed.
+fn gutter_markers(changes: RowChanges, display_options: &DisplayOptions) -> (String, String) {
+    let (lhs_marker, rhs_marker) = if changes.conflict {
+        ("!", "!")
+    } else {
+        (
+            if changes.ours { "<" } else { " " },
+            if changes.theirs { ">" } else { " " },
+        )
+    };
+
+    if display_options.use_color && changes.conflict {
+        (
+            lhs_marker.red().bold().to_string(),
+            rhs_marker.red().bold().to_string(),
+        )
+    } else {
+        (lhs_marker.to_owned(), rhs_marker.to_owned())
+    }
+}
+
+fn conflict_note(display_options: &DisplayOptions) -> String {
+    let note = "Conflict: both sides changed the same code.";
+    if display_options.use_color {
+        note.red().bold().to_string()
+    } else {
+        note.to_owned()
+    }
+}
+
+pub(crate) fn print(
+    out: &mut dyn Write,
+    display_path: &str,
+    extra_info: Option<&String>,
+    base_src: &str,
+    ours_src: &str,
+    theirs_src: &str,
+    ours_diff: &DiffResult,
+    theirs_diff: &DiffResult,
+    display_options: &DisplayOptions,
+) -> std::io::Result<()> {
+    let base_lines: Vec<&str> = base_src.lines().collect();
+    let ours_lines: Vec<&str> = ours_src.lines().collect();
+    let theirs_lines: Vec<&str> = theirs_src.lines().collect();
+
+    let rows = align_rows(
+        &side_alignment(ours_diff, &base_lines, &ours_lines),
+        &side_alignment(theirs_diff, &base_lines, &theirs_lines),
+    );
+    let changes = row_changes(&rows, ours_diff, theirs_diff);
+    let regions = regions(
+        &changes,
+        display_options.before_context_lines as usize,
+        display_options.after_context_lines as usize,
+    );
+
+    if regions.is_empty() {
+        writeln!(
+            out,
+            "{}",
+            style::header(
+                display_path,
+                extra_info,
+                1,
+                1,
+                &ours_diff.file_format,
+                None,
+                display_options
+            )
+        )?;
+        writeln!(out, "No changes from the base.\n")?;
+        return Ok(());
+    }
+
+    let num_width = [&base_lines, &ours_lines, &theirs_lines]
+        .iter()
+        .map(|lines| LineNumber::from(lines.len() as u32).display().len())
+        .max()
+        .unwrap_or(1);
+    // Each row is two columns of content, three line numbers, the
+    // gutter markers and the spacing between them.
+    let gutters_width = 3 * num_width + 2 + 4 * SPACER.len();
+    let content_width = max(
+        display_options.terminal_width.saturating_sub(gutters_width) / 2,
+        display_options.tab_width + 1,
+    );
+
+    // Both sides are additions relative to the base.
+    let ours_styles = styles_by_line(ours_diff, Side::Right, display_options);
+    let theirs_styles = styles_by_line(theirs_diff, Side::Right, display_options);
+    let no_styles = vec![];
+
+    for (i, region) in regions.iter().enumerate() {
+        writeln!(
+            out,
+            "{}",
+            style::header(
+                display_path,
+                extra_info,
+                i + 1,
+                regions.len(),
+                &ours_diff.file_format,
+                None,
+                display_options
+            )
+        )?;
+        if changes[region.clone()].iter().any(|c| c.conflict) {
+            writeln!(out, "{}", conflict_note(display_options))?;
+        }
+
+        for (row, row_changes) in rows[region.clone()].iter().zip(&changes[region.clone()]) {
+            let ours_parts = match row.ours {
+                Some(line_num) => split_and_apply(
+                    ours_lines.get(line_num.as_usize()).copied().unwrap_or(""),
+                    content_width,
+                    display_options.tab_width,
+                    ours_styles.get(&line_num).unwrap_or(&no_styles),
+                    Side::Left,
+                ),
+                None => vec![" ".repeat(content_width)],
+            };
+            let theirs_parts = match row.theirs {
+                Some(line_num) => split_and_apply(
+                    theirs_lines.get(line_num.as_usize()).copied().unwrap_or(""),
+                    content_width,
+                    display_options.tab_width,
+                    theirs_styles.get(&line_num).unwrap_or(&no_styles),
+                    Side::Right,
+                ),
+                None => vec!["".to_owned()],
+            };
+
+            let (lhs_marker, rhs_marker) = gutter_markers(*row_changes, display_options);
+            for (part_i, (ours_part, theirs_part)) in zip_pad_shorter(&ours_parts, &theirs_parts)
+                .into_iter()
+                .enumerate()
+            {
+                // Only show line numbers on the first part of a
+                // wrapped line.
+                let (ours_num, base_num, theirs_num) = if part_i == 0 {
+                    (row.ours, row.base, row.theirs)
+                } else {
+                    (None, None, None)
+                };
+
+                writeln!(
+                    out,
+                    "{}{}{}{}{}{}{}{}{}{}{}",
+                    format_line_num(ours_num, num_width, row_changes.ours, display_options),
+                    SPACER,
+                    ours_part.unwrap_or_else(|| " ".repeat(content_width)),
+                    SPACER,
+                    lhs_marker,
+                    format_line_num(base_num, num_width, false, display_options),
+                    rhs_marker,
+                    SPACER,
+                    format_line_num(theirs_num, num_width, row_changes.theirs, display_options),
+                    SPACER,
+                    theirs_part.unwrap_or_default(),
+                )?;
+            }
+        }
+        writeln!(out)?;
+    }
+    Ok(())
+}
+
+/// Where a row of a combined view came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Origin {
+    /// The merged line is in both parents.
+    Both,
+    /// The merged line matches parent 1 but not parent 2.
+    Parent1,
+    /// The merged line matches parent 2 but not parent 1.
+    Parent2,
+    /// The merged line isn't in either parent.
+    NewInMerge,
+    /// A parent line that isn't in the merged result.
+    Removed,
+}
+
+impl Origin {
+    fn label(self) -> &'static str {
+        match self {
+            Origin::Both => "",
+            Origin::Parent1 => "from parent 1",
+            Origin::Parent2 => "from parent 2",
+            Origin::NewInMerge => "new in merge",
+            Origin::Removed => "removed",
+        }
+    }
+
+    fn line_style(self, display_options: &DisplayOptions) -> Style {
+        match self {
+            Origin::Both => Style::new(),
+            Origin::Parent1 => Style::new().blue(),
+            Origin::Parent2 => Style::new().magenta(),
+            Origin::NewInMerge => novel_style(Style::new(), Side::Right, &display_options.palette),
+            Origin::Removed => novel_style(Style::new(), Side::Left, &display_options.palette),
+        }
+    }
+}
+
+/// The origin of each row, and whether it differs from each parent.
+/// In the rows, `base` is the merged line and `ours` and `theirs`
+/// are the lines of parent 1 and parent 2.
+fn combined_origins(
+    rows: &[Row],
+    parent1_diff: &DiffResult,
+    parent2_diff: &DiffResult,
+) -> Vec<(Origin, RowChanges)> {
+    let (merged_novel_in_parent1, _) =
+        lines_with_novel(&parent1_diff.lhs_positions, &parent1_diff.rhs_positions);
+    let (merged_novel_in_parent2, _) =
+        lines_with_novel(&parent2_diff.lhs_positions, &parent2_diff.rhs_positions);
+
+    rows.iter()
+        .map(|row| match row.base {
+            Some(merged_line) => {
+                let changes = RowChanges {
+                    ours: merged_novel_in_parent1.contains(&merged_line),
+                    theirs: merged_novel_in_parent2.contains(&merged_line),
+                    conflict: false,
+                };
+                let origin = match (changes.ours, changes.theirs) {
+                    (false, false) => Origin::Both,
+                    (false, true) => Origin::Parent1,
+                    (true, false) => Origin::Parent2,
+                    (true, true) => Origin::NewInMerge,
+                };
+                (origin, changes)
+            }
+            None => (
+                Origin::Removed,
+                RowChanges {
+                    ours: row.ours.is_some(),
+                    theirs: row.theirs.is_some(),
+                    conflict: false,
+                },
+            ),
+        })
+        .collect()
+}
+
+/// Styles for the novel tokens in the merged file, colored by which
+/// parent they came from.
+fn merged_styles_by_line(
+    parent1_diff: &DiffResult,
+    parent2_diff: &DiffResult,
+    display_options: &DisplayOptions,
+) -> DftHashMap<LineNumber, Vec<(SingleLineSpan, Style)>> {
+    let mut styles: DftHashMap<LineNumber, Vec<(SingleLineSpan, Style)>> = DftHashMap::default();
+    if !display_options.style_content() {
+        return styles;
+    }
+
+    // Both diffs tokenize the same merged file, so their positions
+    // are the same.
+    let novel_spans = |diff: &DiffResult| -> DftHashMap<(LineNumber, u32), SingleLineSpan> {
+        diff.lhs_positions
+            .iter()
+            .filter(|mp| mp.kind.is_novel())
+            .map(|mp| ((mp.pos.line, mp.pos.start_col), mp.pos))
+            .collect()
+    };
+    let novel_in_parent1 = novel_spans(parent1_diff);
+    let novel_in_parent2 = novel_spans(parent2_diff);
+
+    let mut spans: Vec<(&(LineNumber, u32), &SingleLineSpan)> =
+        novel_in_parent1.iter().chain(&novel_in_parent2).collect();
+    spans.sort_by_key(|(key, _)| **key);
+    spans.dedup_by_key(|(key, _)| **key);
+    for (key, span) in spans {
+        let origin = match (
+            novel_in_parent1.contains_key(key),
+            novel_in_parent2.contains_key(key),
+        ) {
+            (true, true) => Origin::NewInMerge,
+            (false, true) => Origin::Parent1,
+            _ => Origin::Parent2,
+        };
+        styles
+            .entry(span.line)
+            .or_insert_with(Vec::new)
+            .push((*span, origin.line_style(display_options)));
+    }
+    styles
+}
+
+/// The marker for a row relative to one parent, like `git diff --cc`:
+/// `+` for a merged line that isn't in the parent, `-` for a parent
+/// line that isn't in the merged result.
+fn combined_marker(row: &Row, changed: bool, parent_line: Option<LineNumber>) -> &'static str {
+    match (row.base, changed) {
+        (Some(_), true) => "+",
+        (None, _) if parent_line.is_some() => "-",
+        _ => " ",
+    }
+}
+
+/// Print the merged file of a merge commit, marking each change by
+/// which parent it came from.
+pub(crate) fn print_combined(
+    out: &mut dyn Write,
+    display_path: &str,
+    extra_info: Option<&String>,
+    merged_src: &str,
+    parent1_src: &str,
+    parent2_src: &str,
+    parent1_diff: &DiffResult,
+    parent2_diff: &DiffResult,
+    display_options: &DisplayOptions,
+) -> std::io::Result<()> {
+    let merged_lines: Vec<&str> = merged_src.lines().collect();
+    let parent1_lines: Vec<&str> = parent1_src.lines().collect();
+    let parent2_lines: Vec<&str> = parent2_src.lines().collect();
+
+    let rows = align_rows(
+        &side_alignment(parent1_diff, &merged_lines, &parent1_lines),
+        &side_alignment(parent2_diff, &merged_lines, &parent2_lines),
+    );
+    let origins = combined_origins(&rows, parent1_diff, parent2_diff);
+    let changes: Vec<RowChanges> = origins.iter().map(|(_, changes)| *changes).collect();
+    let regions = regions(
+        &changes,
+        display_options.before_context_lines as usize,
+        display_options.after_context_lines as usize,
+    );
+
+    if regions.is_empty() {
+        writeln!(
+            out,
+            "{}",
+            style::header(
+                display_path,
+                extra_info,
+                1,
+                1,
+                &parent1_diff.file_format,
+                None,
+                display_options
+            )
+        )?;
+        writeln!(out, "No changes from either parent.\n")?;
+        return Ok(());
+    }
+
+    let num_width = [&merged_lines, &parent1_lines, &parent2_lines]
+        .iter()
+        .map(|lines| LineNumber::from(lines.len() as u32).display().len())
+        .max()
+        .unwrap_or(1);
+    let label_width = Origin::Parent1.label().len();
+    // The line number, the two markers, the label and the spacing
+    // between them.
+    let gutters_width = num_width + 2 + label_width + 3 * SPACER.len();
+    let content_width = max(
+        display_options.terminal_width.saturating_sub(gutters_width),
+        display_options.tab_width + 1,
+    );
+
+    let merged_styles = merged_styles_by_line(parent1_diff, parent2_diff, display_options);
+    // Parent lines are only shown when they were removed.
+    let parent1_styles = styles_by_line(parent1_diff, Side::Left, display_options);
+    let parent2_styles = styles_by_line(parent2_diff, Side::Left, display_options);
+    let no_styles = vec![];
+
+    for (i, region) in regions.iter().enumerate() {
+        writeln!(
+            out,
+            "{}",
+            style::header(
+                display_path,
+                extra_info,
+                i + 1,
+                regions.len(),
+                &parent1_diff.file_format,
+                None,
+                display_options
+            )
+        )?;
+
+        let mut prev_origin = Origin::Both;
+        for (row, (origin, row_changes)) in
+            rows[region.clone()].iter().zip(&origins[region.clone()])
+        {
+            // Show the parent 1 version of a removed line if both
+            // parents have it.
+            let (line, styles, line_num) = match (row.base, row.ours, row.theirs) {
+                (Some(line_num), _, _) => (
+                    merged_lines.get(line_num.as_usize()),
+                    merged_styles.get(&line_num),
+                    Some(line_num),
+                ),
+                (None, Some(line_num), _) => (
+                    parent1_lines.get(line_num.as_usize()),
+                    parent1_styles.get(&line_num),
+                    None,
+                ),
+                (None, None, Some(line_num)) => (
+                    parent2_lines.get(line_num.as_usize()),
+                    parent2_styles.get(&line_num),
+                    None,
+                ),
+                (None, None, None) => continue,
+            };
+            let parts = split_and_apply(
+                line.copied().unwrap_or(""),
+                content_width,
+                display_options.tab_width,
+                styles.unwrap_or(&no_styles),
+                Side::Left,
+            );
+
+            let markers = format!(
+                "{}{}",
+                combined_marker(row, row_changes.ours, row.ours),
+                combined_marker(row, row_changes.theirs, row.theirs)
+            );
+            // Label the first line of each run of rows with the same
+            // origin.
+            let label = if *origin != prev_origin {
+                origin.label()
+            } else {
+                ""
+            };
+            prev_origin = *origin;
+
+            let (markers, label) = if display_options.use_color {
+                let style = origin.line_style(display_options);
+                (
+                    markers.style(style).to_string(),
+                    label.style(style).to_string(),
+                )
+            } else {
+                (markers, label.to_owned())
+            };
+
+            for (part_i, part) in parts.into_iter().enumerate() {
+                let (line_num, markers, label) = if part_i == 0 {
+                    (line_num, markers.as_str(), label.as_str())
+                } else {
+                    (None, "  ", "")
+                };
+                let line = format!(
+                    "{}{}{}{}{}{}{}",
+                    format_line_num(line_num, num_width, row_changes.any(), display_options),
+                    SPACER,
+                    markers,
+                    SPACER,
+                    part,
+                    SPACER,
+                    label,
+                );
+                writeln!(out, "{}", line.trim_end())?;
+            }
+        }
+        writeln!(out)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn line(n: u32) -> Option<LineNumber> {
+        Some(n.into())
+    }
+
+    #[test]
+    fn test_align_rows() {
+        // Ours inserted a line after base line 0, theirs removed base
+        // line 1.
+        let ours = vec![(line(0), line(0)), (None, line(1)), (line(1), line(2))];
+        let theirs = vec![(line(0), line(0)), (line(1), None)];
+
+        assert_eq!(
+            align_rows(&ours, &theirs),
+            vec![
+                Row {
+                    ours: line(0),
+                    base: line(0),
+                    theirs: line(0),
+                },
+                Row {
+                    ours: line(1),
+                    base: None,
+                    theirs: None,
+                },
+                Row {
+                    ours: line(2),
+                    base: line(1),
+                    theirs: None,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_regions() {
+        let unchanged = RowChanges::default();
+        let changed = RowChanges {
+            ours: true,
+            ..RowChanges::default()
+        };
+        let changes = vec![
+            changed, unchanged, unchanged, changed, unchanged, unchanged, unchanged, unchanged,
+            changed,
+        ];
+
+        assert_eq!(regions(&changes, 1, 1), vec![0..5, 7..9]);
+    }
+
+    #[test]
+    fn test_combined_marker() {
+        let changed = Row {
+            ours: line(0),
+            base: line(0),
+            theirs: None,
+        };
+        assert_eq!(combined_marker(&changed, true, changed.ours), "+");
+        assert_eq!(combined_marker(&changed, false, changed.ours), " ");
+
+        let removed = Row {
+            ours: line(1),
+            base: None,
+            theirs: None,
+        };
+        assert_eq!(combined_marker(&removed, true, removed.ours), "-");
+        assert_eq!(combined_marker(&removed, false, removed.theirs), " ");
+    }
+}
@@ -1,6 +1,17 @@
+pub(crate) mod check;
 pub(crate) mod context;
+pub(crate) mod html;
 pub(crate) mod hunks;
 pub(crate) mod inline;
+pub(crate) mod invisible;
 pub(crate) mod json;
+pub(crate) mod json_patch;
+pub(crate) mod long_lines;
+pub(crate) mod markdown;
+pub(crate) mod merge;
+pub(crate) mod patch;
+pub(crate) mod sarif;
 pub(crate) mod side_by_side;
+pub(crate) mod stat;
 pub(crate) mod style;
+pub(crate) mod symbols;
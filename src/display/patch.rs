@@ -0,0 +1,538 @@
+//! Unified diff output, suitable for `patch` or `git apply`.
+//!
+//! Structural hunks decide how the two files are aligned, but a patch
+//! must reproduce the RHS exactly. We therefore compute a line diff
+//! within each aligned region, so changes that difftastic considers
+//! insignificant (e.g. whitespace) are still included.
+
+use std::io::Write;
+
+use line_numbers::LineNumber;
+
+use crate::{
+    constants::Side,
+    diff::myers_diff,
+    display::hunks::Hunk,
+    summary::{DiffResult, FileContent},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LineOp {
+    /// The LHS line at this index is unchanged, and is the RHS line
+    /// at the second index.
+    Context(usize, usize),
+    /// The LHS line at this index is removed.
+    Removed(usize),
+    /// The RHS line at this index is added.
+    Added(usize),
+}
+
+/// Split `src` into lines, preserving the line terminators so the
+/// patch reproduces the file byte-for-byte.
+fn split_lines_inclusive(src: &str) -> Vec<&str> {
+    src.split_inclusive('\n').collect()
+}
+
+/// The (start, end) line numbers on each side of this hunk, ignoring
+/// sides that have no lines in the hunk.
+fn hunk_line_ranges(hunk: &Hunk) -> (Option<(usize, usize)>, Option<(usize, usize)>) {
+    let lhs_lines = hunk.lines.iter().filter_map(|(lhs, _)| *lhs);
+    let rhs_lines = hunk.lines.iter().filter_map(|(_, rhs)| *rhs);
+
+    fn range(lines: impl Iterator<Item = LineNumber>) -> Option<(usize, usize)> {
+        lines.fold(None, |acc, line| {
+            let line = line.as_usize();
+            match acc {
+                Some((start, end)) => Some((std::cmp::min(start, line), std::cmp::max(end, line))),
+                None => Some((line, line)),
+            }
+        })
+    }
+
+    (range(lhs_lines), range(rhs_lines))
+}
+
+/// Append a line diff of `lhs_lines[lhs_start..lhs_end]` against
+/// `rhs_lines[rhs_start..rhs_end]` to `ops`.
+fn push_line_diff(
+    ops: &mut Vec<LineOp>,
+    lhs_lines: &[&str],
+    rhs_lines: &[&str],
+    (lhs_start, lhs_end): (usize, usize),
+    (rhs_start, rhs_end): (usize, usize),
+) {
+    let lhs_region = &lhs_lines[lhs_start..lhs_end];
+    let rhs_region = &rhs_lines[rhs_start..rhs_end];
+
+    // Common case: regions between hunks are usually identical.
+    if lhs_region == rhs_region {
+        for i in 0..lhs_region.len() {
+            ops.push(LineOp::Context(lhs_start + i, rhs_start + i));
+        }
+        return;
+    }
+
+    let mut lhs_i = lhs_start;
+    let mut rhs_i = rhs_start;
+    for diff_res in myers_diff::slice_by_hash(lhs_region, rhs_region) {
+        match diff_res {
+            myers_diff::DiffResult::Left(_) => {
+                ops.push(LineOp::Removed(lhs_i));
+                lhs_i += 1;
+            }
+            myers_diff::DiffResult::Both(_, _) => {
+                ops.push(LineOp::Context(lhs_i, rhs_i));
+                lhs_i += 1;
+                rhs_i += 1;
+            }
+            myers_diff::DiffResult::Right(_) => {
+                ops.push(LineOp::Added(rhs_i));
+                rhs_i += 1;
+            }
+        }
+    }
+}
+
+/// Compute the line operations that transform the LHS into the RHS,
+/// using `hunks` to align the two files.
+fn line_ops(lhs_lines: &[&str], rhs_lines: &[&str], hunks: &[Hunk]) -> Vec<LineOp> {
+    let mut ops = vec![];
+    let mut lhs_pos = 0;
+    let mut rhs_pos = 0;
+
+    for hunk in hunks {
+        // Hunks that only have lines on one side don't tell us
+        // anything about alignment, so they're handled by the line
+        // diff of the surrounding region.
+        let ((lhs_start, lhs_end), (rhs_start, rhs_end)) = match hunk_line_ranges(hunk) {
+            (Some(lhs_range), Some(rhs_range)) => (lhs_range, rhs_range),
+            _ => continue,
+        };
+
+        // Hunks should be in order, but ensure we never go backwards
+        // or beyond the end of either file.
+        if lhs_start < lhs_pos
+            || rhs_start < rhs_pos
+            || lhs_end >= lhs_lines.len()
+            || rhs_end >= rhs_lines.len()
+        {
+            continue;
+        }
+
+        push_line_diff(
+            &mut ops,
+            lhs_lines,
+            rhs_lines,
+            (lhs_pos, lhs_start),
+            (rhs_pos, rhs_start),
+        );
+        push_line_diff(
+            &mut ops,
+            lhs_lines,
+            rhs_lines,
+            (lhs_start, lhs_end + 1),
+            (rhs_start, rhs_end + 1),
+        );
+
+        lhs_pos = lhs_end + 1;
+        rhs_pos = rhs_end + 1;
+    }
+
+    push_line_diff(
+        &mut ops,
+        lhs_lines,
+        rhs_lines,
+        (lhs_pos, lhs_lines.len()),
+        (rhs_pos, rhs_lines.len()),
+    );
+
+    ops
+}
+
+/// Format a hunk range in the `@@ -start,count +start,count @@`
+/// header. Line numbers are 1-indexed, and an empty range refers to
+/// the line before it.
+pub(crate) fn format_range(start: usize, count: usize) -> String {
+    match count {
+        0 => format!("{},0", start),
+        1 => format!("{}", start + 1),
+        _ => format!("{},{}", start + 1, count),
+    }
+}
+
+fn push_patch_line(res: &mut String, prefix: char, line: &str) {
+    res.push(prefix);
+    res.push_str(line);
+    if !line.ends_with('\n') {
+        res.push_str("\n\\ No newline at end of file\n");
+    }
+}
+
+/// Render the unified diff hunks for `ops`, with `before_context_lines`
+/// unchanged lines before each change and `after_context_lines` after.
+fn format_hunks(
+    ops: &[LineOp],
+    lhs_lines: &[&str],
+    rhs_lines: &[&str],
+    before_context_lines: usize,
+    after_context_lines: usize,
+) -> String {
+    let change_indexes: Vec<usize> = ops
+        .iter()
+        .enumerate()
+        .filter(|(_, op)| !matches!(op, LineOp::Context(_, _)))
+        .map(|(i, _)| i)
+        .collect();
+
+    // Group changes whose context would overlap or touch.
+    let mut groups: Vec<(usize, usize)> = vec![];
+    for i in change_indexes {
+        match groups.last_mut() {
+            Some((_, end)) if i - *end <= before_context_lines + after_context_lines + 1 => {
+                *end = i;
+            }
+            _ => groups.push((i, i)),
+        }
+    }
+
+    let mut res = String::new();
+    // The LHS and RHS line counts of `ops[..ops_seen]`.
+    let mut ops_seen = 0;
+    let mut lhs_start = 0;
+    let mut rhs_start = 0;
+    for (first_change, last_change) in groups {
+        let start = first_change.saturating_sub(before_context_lines);
+        let end = std::cmp::min(last_change + after_context_lines + 1, ops.len());
+        let hunk_ops = &ops[start..end];
+
+        // The position of this hunk is the position of its first
+        // line, or where the next line would be if it's empty on
+        // this side.
+        for op in &ops[ops_seen..start] {
+            match op {
+                LineOp::Context(_, _) => {
+                    lhs_start += 1;
+                    rhs_start += 1;
+                }
+                LineOp::Removed(_) => lhs_start += 1,
+                LineOp::Added(_) => rhs_start += 1,
+            }
+        }
+        ops_seen = start;
+
+        let lhs_count = hunk_ops
+            .iter()
+            .filter(|op| !matches!(op, LineOp::Added(_)))
+            .count();
+        let rhs_count = hunk_ops
+            .iter()
+            .filter(|op| !matches!(op, LineOp::Removed(_)))
+            .count();
+
+        res.push_str(&format!(
+            "@@ -{} +{} @@\n",
+            format_range(lhs_start, lhs_count),
+            format_range(rhs_start, rhs_count)
+        ));
+
+        for op in hunk_ops {
+            match op {
+                LineOp::Context(lhs_i, _) => push_patch_line(&mut res, ' ', lhs_lines[*lhs_i]),
+                LineOp::Removed(lhs_i) => push_patch_line(&mut res, '-', lhs_lines[*lhs_i]),
+                LineOp::Added(rhs_i) => push_patch_line(&mut res, '+', rhs_lines[*rhs_i]),
+            }
+        }
+    }
+
+    res
+}
+
+/// The mode git uses for a regular file that isn't executable.
+const REGULAR_FILE_MODE: &str = "100644";
+
+/// The header before the hunks of `summary`. A file that only exists
+/// on one side has `/dev/null` for the other side, and a git `new file
+/// mode` or `deleted file mode` line, so `git apply` and `patch`
+/// create or remove the file.
+fn file_header(summary: &DiffResult) -> String {
+    let display_path = &summary.display_path;
+    match summary.missing_side {
+        Some(Side::Left) => format!(
+            "diff --git a/{path} b/{path}\nnew file mode {}\n--- /dev/null\n+++ b/{path}\n",
+            file_mode(summary, Side::Right),
+            path = display_path
+        ),
+        Some(Side::Right) => format!(
+            "diff --git a/{path} b/{path}\ndeleted file mode {}\n--- a/{path}\n+++ /dev/null\n",
+            file_mode(summary, Side::Left),
+            path = display_path
+        ),
+        None => format!("--- a/{}\n+++ b/{}\n", display_path, display_path),
+    }
+}
+
+/// The mode of the file on `side`, if git reported it, or the mode of
+/// a regular file.
+fn file_mode(summary: &DiffResult, side: Side) -> &str {
+    match (&summary.mode_change, side) {
+        (Some((lhs_mode, _)), Side::Left) => lhs_mode,
+        (Some((_, rhs_mode)), Side::Right) => rhs_mode,
+        (None, _) => REGULAR_FILE_MODE,
+    }
+}
+
+/// Render `summary` as a unified diff. Returns an empty string if the
+/// files are identical.
+pub(crate) fn format_patch(
+    summary: &DiffResult,
+    before_context_lines: usize,
+    after_context_lines: usize,
+) -> String {
+    let display_path = &summary.display_path;
+
+    match (&summary.lhs_src, &summary.rhs_src) {
+        (FileContent::Text(lhs_src), FileContent::Text(rhs_src)) => {
+            if lhs_src == rhs_src {
+                return String::new();
+            }
+
+            let lhs_lines = split_lines_inclusive(lhs_src);
+            let rhs_lines = split_lines_inclusive(rhs_src);
+            let ops = line_ops(&lhs_lines, &rhs_lines, &summary.hunks);
+
+            let mut res = file_header(summary);
+            res.push_str(&format_hunks(
+                &ops,
+                &lhs_lines,
+                &rhs_lines,
+                before_context_lines,
+                after_context_lines,
+            ));
+            res
+        }
+        _ => {
+            if summary.has_byte_changes {
+                let (lhs_path, rhs_path) = match summary.missing_side {
+                    Some(Side::Left) => ("/dev/null".to_owned(), format!("b/{}", display_path)),
+                    Some(Side::Right) => (format!("a/{}", display_path), "/dev/null".to_owned()),
+                    None => (format!("a/{}", display_path), format!("b/{}", display_path)),
+                };
+                format!("Binary files {} and {} differ\n", lhs_path, rhs_path)
+            } else {
+                String::new()
+            }
+        }
+    }
+}
+
+/// Write `summary` as a unified diff.
+pub(crate) fn print(
+    out: &mut dyn Write,
+    summary: &DiffResult,
+    before_context_lines: usize,
+    after_context_lines: usize,
+) -> std::io::Result<()> {
+    write!(
+        out,
+        "{}",
+        format_patch(summary, before_context_lines, after_context_lines)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::{display::long_lines::LineMap, summary::FileFormat};
+
+    /// Apply a patch produced by `format_hunks`, so we can check that
+    /// it round-trips.
+    fn apply(lhs_src: &str, patch: &str) -> String {
+        let lhs_lines = split_lines_inclusive(lhs_src);
+        let mut res = String::new();
+        let mut lhs_i = 0;
+
+        let mut patch_lines = split_lines_inclusive(patch).into_iter().peekable();
+        while let Some(line) = patch_lines.next() {
+            if let Some(header) = line.strip_prefix("@@ -") {
+                let range = header.split(' ').next().unwrap();
+                let mut parts = range.split(',');
+                let start: usize = parts.next().unwrap().parse().unwrap();
+                let count: usize = parts.next().map(|c| c.parse().unwrap()).unwrap_or(1);
+                let start = if count == 0 { start } else { start - 1 };
+
+                for line in &lhs_lines[lhs_i..start] {
+                    res.push_str(line);
+                }
+                lhs_i = start;
+                continue;
+            }
+
+            let mut content = line[1..].to_owned();
+            if patch_lines.peek() == Some(&"\\ No newline at end of file\n") {
+                patch_lines.next();
+                content.pop();
+            }
+
+            match &line[..1] {
+                " " => {
+                    res.push_str(&content);
+                    lhs_i += 1;
+                }
+                "-" => lhs_i += 1,
+                "+" => res.push_str(&content),
+                _ => unreachable!(),
+            }
+        }
+
+        for line in &lhs_lines[lhs_i..] {
+            res.push_str(line);
+        }
+        res
+    }
+
+    fn assert_round_trips(lhs_src: &str, rhs_src: &str, hunks: &[Hunk]) {
+        let lhs_lines = split_lines_inclusive(lhs_src);
+        let rhs_lines = split_lines_inclusive(rhs_src);
+        let ops = line_ops(&lhs_lines, &rhs_lines, hunks);
+        let patch = format_hunks(&ops, &lhs_lines, &rhs_lines, 3, 3);
+
+        assert_eq!(apply(lhs_src, &patch), rhs_src);
+    }
+
+    #[test]
+    fn test_format_range() {
+        assert_eq!(format_range(0, 0), "0,0");
+        assert_eq!(format_range(0, 1), "1");
+        assert_eq!(format_range(4, 3), "5,3");
+    }
+
+    #[test]
+    fn test_simple_change() {
+        let lhs_lines = vec!["a\n", "b\n", "c\n"];
+        let rhs_lines = vec!["a\n", "x\n", "c\n"];
+        let ops = line_ops(&lhs_lines, &rhs_lines, &[]);
+
+        assert_eq!(
+            format_hunks(&ops, &lhs_lines, &rhs_lines, 3, 3),
+            "@@ -1,3 +1,3 @@\n a\n-b\n+x\n c\n"
+        );
+    }
+
+    #[test]
+    fn test_asymmetric_context() {
+        let lhs_lines = vec!["a\n", "b\n", "c\n", "d\n", "e\n"];
+        let rhs_lines = vec!["a\n", "b\n", "x\n", "d\n", "e\n"];
+        let ops = line_ops(&lhs_lines, &rhs_lines, &[]);
+
+        assert_eq!(
+            format_hunks(&ops, &lhs_lines, &rhs_lines, 1, 0),
+            "@@ -2,2 +2,2 @@\n b\n-c\n+x\n"
+        );
+    }
+
+    #[test]
+    fn test_insertion_at_start() {
+        let lhs_lines: Vec<&str> = vec![];
+        let rhs_lines = vec!["a\n"];
+        let ops = line_ops(&lhs_lines, &rhs_lines, &[]);
+
+        assert_eq!(
+            format_hunks(&ops, &lhs_lines, &rhs_lines, 3, 3),
+            "@@ -0,0 +1 @@\n+a\n"
+        );
+    }
+
+    #[test]
+    fn test_no_trailing_newline() {
+        let lhs_lines = vec!["a\n", "b"];
+        let rhs_lines = vec!["a\n", "b\n"];
+        let ops = line_ops(&lhs_lines, &rhs_lines, &[]);
+
+        assert_eq!(
+            format_hunks(&ops, &lhs_lines, &rhs_lines, 3, 3),
+            "@@ -1,2 +1,2 @@\n a\n-b\n\\ No newline at end of file\n+b\n"
+        );
+    }
+
+    #[test]
+    fn test_round_trip_crlf_and_missing_newline() {
+        assert_round_trips("a\r\nb\r\nc", "a\r\nB\r\nc\r\nd", &[]);
+    }
+
+    #[test]
+    fn test_round_trip_separate_hunks() {
+        let lhs_src = "1\n2\n3\n4\n5\n6\n7\n8\n9\n10\n11\n12\n";
+        let rhs_src = "one\n2\n3\n4\n5\n6\n7\n8\n9\n10\n11\ntwelve\n";
+
+        let hunks = vec![
+            Hunk {
+                novel_lhs: HashSet::from([0.into()]),
+                novel_rhs: HashSet::from([0.into()]),
+                lines: vec![(Some(0.into()), Some(0.into()))],
+                enclosing_declaration: None,
+            },
+            Hunk {
+                novel_lhs: HashSet::from([11.into()]),
+                novel_rhs: HashSet::from([11.into()]),
+                lines: vec![(Some(11.into()), Some(11.into()))],
+                enclosing_declaration: None,
+            },
+        ];
+        assert_round_trips(lhs_src, rhs_src, &hunks);
+    }
+
+    #[test]
+    fn test_round_trip_unaligned_whitespace_change() {
+        // A whitespace change that isn't in any structural hunk must
+        // still be in the patch.
+        let lhs_src = "foo(1)\nbar(2)\n";
+        let rhs_src = "foo(1)\nbar( 2)\n";
+        assert_round_trips(lhs_src, rhs_src, &[]);
+    }
+
+    fn text_result(lhs_src: &str, rhs_src: &str, missing_side: Option<Side>) -> DiffResult {
+        DiffResult {
+            display_path: "foo.txt".to_owned(),
+            path_bytes: None,
+            renamed_from: None,
+            missing_side,
+            mode_change: None,
+            mtime_change: None,
+            binary_info: None,
+            extra_info: None,
+            file_format: FileFormat::PlainText,
+            lhs_src: FileContent::Text(lhs_src.to_owned()),
+            rhs_src: FileContent::Text(rhs_src.to_owned()),
+            hunks: vec![],
+            lhs_positions: vec![],
+            rhs_positions: vec![],
+            lhs_line_map: LineMap::default(),
+            rhs_line_map: LineMap::default(),
+            degradation: None,
+            has_byte_changes: true,
+            has_syntactic_changes: true,
+        }
+    }
+
+    #[test]
+    fn test_added_file() {
+        let summary = text_result("", "a\nb\n", Some(Side::Left));
+        assert_eq!(
+            format_patch(&summary, 3, 3),
+            "diff --git a/foo.txt b/foo.txt\nnew file mode 100644\n--- /dev/null\n+++ b/foo.txt\n@@ -0,0 +1,2 @@\n+a\n+b\n"
+        );
+    }
+
+    #[test]
+    fn test_deleted_file() {
+        let summary = text_result("a\n", "", Some(Side::Right));
+        assert_eq!(
+            format_patch(&summary, 3, 3),
+            "diff --git a/foo.txt b/foo.txt\ndeleted file mode 100644\n--- a/foo.txt\n+++ /dev/null\n@@ -1 +0,0 @@\n-a\n"
+        );
+    }
+}
@@ -0,0 +1,382 @@
+//! SARIF output, so code review tools can annotate changes.
+//!
+//! This produces a SARIF 2.1.0 log with a result for each changed
+//! region. See
+//! <https://docs.oasis-open.org/sarif/sarif/v2.1.0/sarif-v2.1.0.html>.
+
+use std::{collections::HashSet, io::Write};
+
+use line_numbers::LineNumber;
+use serde_json::{json, Map, Value};
+
+use crate::{
+    display::{
+        context::opposite_positions,
+        hunks::{matched_pos_to_hunks, merge_adjacent, Hunk},
+    },
+    lines::MaxLine,
+    parse::syntax::MatchedPos,
+    summary::{DiffResult, FileContent},
+};
+
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+
+/// The rules that results can refer to, as (id, description). The
+/// index in this array is the `ruleIndex` of the result.
+const RULES: [(&str, &str); 3] = [
+    ("addition", "Content was added."),
+    ("removal", "Content was removed."),
+    ("modification", "Content was modified."),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ChangeKind {
+    Addition,
+    Removal,
+    Modification,
+}
+
+impl ChangeKind {
+    fn rule_index(self) -> usize {
+        match self {
+            ChangeKind::Addition => 0,
+            ChangeKind::Removal => 1,
+            ChangeKind::Modification => 2,
+        }
+    }
+}
+
+/// Which version of the file a location refers to. These are used as
+/// `uriBaseId`s.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Side {
+    Lhs,
+    Rhs,
+}
+
+impl Side {
+    fn base_id(self) -> &'static str {
+        match self {
+            Side::Lhs => "LHS",
+            Side::Rhs => "RHS",
+        }
+    }
+}
+
+/// A region in SARIF terms: 1-indexed lines and columns, where the end
+/// column is the column after the last character.
+#[derive(Debug, PartialEq, Eq)]
+struct Region {
+    start_line: usize,
+    start_column: usize,
+    end_line: usize,
+    end_column: usize,
+}
+
+/// Percent-encode `path` so it's a valid relative URI reference.
+fn path_to_uri(path: &str) -> String {
+    let mut uri = String::with_capacity(path.len());
+    for c in path.chars() {
+        match c {
+            'A'..='Z' | 'a'..='z' | '0'..='9' | '-' | '.' | '_' | '~' | '/' => uri.push(c),
+            '\\' => uri.push('/'),
+            _ => {
+                let mut buf = [0; 4];
+                for byte in c.encode_utf8(&mut buf).bytes() {
+                    uri.push_str(&format!("%{:02X}", byte));
+                }
+            }
+        }
+    }
+    uri
+}
+
+/// Convert a byte offset in `line` to a 1-indexed column, counting
+/// Unicode code points.
+fn column(line: &str, byte_offset: u32) -> usize {
+    let byte_offset = std::cmp::min(byte_offset as usize, line.len());
+    let prefix = match line.get(..byte_offset) {
+        Some(prefix) => prefix,
+        // Not on a character boundary, which shouldn't happen.
+        None => line,
+    };
+    prefix.chars().count() + 1
+}
+
+/// The region spanning all the novel positions in `novel_lines`.
+fn novel_region(
+    mps: &[MatchedPos],
+    novel_lines: &HashSet<LineNumber>,
+    lines: &[&str],
+) -> Option<Region> {
+    let mut novel_mps = mps
+        .iter()
+        .filter(|mp| mp.kind.is_novel() && novel_lines.contains(&mp.pos.line));
+
+    let first = novel_mps.next()?;
+    let mut start = (first.pos.line, first.pos.start_col);
+    let mut end = (first.pos.line, first.pos.end_col);
+    for mp in novel_mps {
+        start = std::cmp::min(start, (mp.pos.line, mp.pos.start_col));
+        end = std::cmp::max(end, (mp.pos.line, mp.pos.end_col));
+    }
+
+    let line_text = |line: LineNumber| lines.get(line.as_usize()).copied().unwrap_or("");
+    Some(Region {
+        start_line: start.0.as_usize() + 1,
+        start_column: column(line_text(start.0), start.1),
+        end_line: end.0.as_usize() + 1,
+        end_column: column(line_text(end.0), end.1),
+    })
+}
+
+fn location(path: &str, side: Side, artifact_index: usize, region: Option<&Region>) -> Value {
+    let mut physical_location = Map::new();
+    physical_location.insert(
+        "artifactLocation".to_owned(),
+        json!({
+            "uri": path_to_uri(path),
+            "uriBaseId": side.base_id(),
+            "index": artifact_index,
+        }),
+    );
+    if let Some(region) = region {
+        physical_location.insert(
+            "region".to_owned(),
+            json!({
+                "startLine": region.start_line,
+                "startColumn": region.start_column,
+                "endLine": region.end_line,
+                "endColumn": region.end_column,
+            }),
+        );
+    }
+
+    json!({ "physicalLocation": physical_location })
+}
+
+fn sarif_result(
+    kind: ChangeKind,
+    language: &str,
+    primary_location: Value,
+    related_location: Option<Value>,
+) -> Value {
+    let (rule_id, description) = RULES[kind.rule_index()];
+
+    let mut result = json!({
+        "ruleId": rule_id,
+        "ruleIndex": kind.rule_index(),
+        "level": "note",
+        "message": { "text": description },
+        "locations": [primary_location],
+        "properties": { "language": language },
+    });
+    if let Some(mut related_location) = related_location {
+        related_location["id"] = json!(0);
+        result["relatedLocations"] = json!([related_location]);
+    }
+    result
+}
+
+/// Hunks without context, so regions are as tight as possible.
+fn unpadded_hunks(summary: &DiffResult, lhs_src: &str, rhs_src: &str) -> Vec<Hunk> {
+    let opposite_to_lhs = opposite_positions(&summary.lhs_positions);
+    let opposite_to_rhs = opposite_positions(&summary.rhs_positions);
+
+    let hunks = matched_pos_to_hunks(&summary.lhs_positions, &summary.rhs_positions);
+    merge_adjacent(
+        &hunks,
+        &opposite_to_lhs,
+        &opposite_to_rhs,
+        lhs_src.max_line(),
+        rhs_src.max_line(),
+        0,
+        0,
+    )
+}
+
+/// The SARIF results for a single file. `lhs_index` and `rhs_index`
+/// are the indexes of the file in the run's `artifacts`.
+fn file_results(summary: &DiffResult, lhs_index: usize, rhs_index: usize) -> Vec<Value> {
+    let path = &summary.display_path;
+    let language = format!("{}", summary.file_format);
+
+    let (lhs_src, rhs_src) = match (&summary.lhs_src, &summary.rhs_src) {
+        (FileContent::Text(lhs_src), FileContent::Text(rhs_src)) => (lhs_src, rhs_src),
+        _ => {
+            // Binary files don't have meaningful regions.
+            return vec![sarif_result(
+                ChangeKind::Modification,
+                &language,
+                location(path, Side::Rhs, rhs_index, None),
+                None,
+            )];
+        }
+    };
+
+    let hunks = unpadded_hunks(summary, lhs_src, rhs_src);
+    if hunks.is_empty() {
+        // We know the file changed, e.g. with --check-only, but we
+        // don't know where.
+        return vec![sarif_result(
+            ChangeKind::Modification,
+            &language,
+            location(path, Side::Rhs, rhs_index, None),
+            None,
+        )];
+    }
+
+    let lhs_lines: Vec<&str> = lhs_src.lines().collect();
+    let rhs_lines: Vec<&str> = rhs_src.lines().collect();
+
+    let mut results = vec![];
+    for hunk in &hunks {
+        let lhs_region = novel_region(&summary.lhs_positions, &hunk.novel_lhs, &lhs_lines);
+        let rhs_region = novel_region(&summary.rhs_positions, &hunk.novel_rhs, &rhs_lines);
+
+        let hunk_result = match (lhs_region, rhs_region) {
+            (Some(lhs_region), Some(rhs_region)) => sarif_result(
+                ChangeKind::Modification,
+                &language,
+                location(path, Side::Rhs, rhs_index, Some(&rhs_region)),
+                Some(location(path, Side::Lhs, lhs_index, Some(&lhs_region))),
+            ),
+            (None, Some(rhs_region)) => sarif_result(
+                ChangeKind::Addition,
+                &language,
+                location(path, Side::Rhs, rhs_index, Some(&rhs_region)),
+                None,
+            ),
+            // Removals have no RHS location, so refer to the LHS.
+            (Some(lhs_region), None) => sarif_result(
+                ChangeKind::Removal,
+                &language,
+                location(path, Side::Lhs, lhs_index, Some(&lhs_region)),
+                None,
+            ),
+            (None, None) => continue,
+        };
+        results.push(hunk_result);
+    }
+
+    results
+}
+
+/// Build a SARIF log for the files in `diffs` that have changes.
+fn sarif_log(diffs: &[DiffResult]) -> Value {
+    let mut artifacts = vec![];
+    let mut results = vec![];
+
+    for diff in diffs.iter().filter(|diff| diff.has_reportable_change()) {
+        let uri = path_to_uri(&diff.display_path);
+        let lhs_index = artifacts.len();
+        artifacts.push(json!({
+            "location": { "uri": uri, "uriBaseId": Side::Lhs.base_id() },
+        }));
+        let rhs_index = artifacts.len();
+        artifacts.push(json!({
+            "location": { "uri": uri, "uriBaseId": Side::Rhs.base_id() },
+        }));
+
+        results.extend(file_results(diff, lhs_index, rhs_index));
+    }
+
+    let rules: Vec<Value> = RULES
+        .iter()
+        .map(|(id, description)| {
+            json!({
+                "id": id,
+                "shortDescription": { "text": description },
+            })
+        })
+        .collect();
+
+    json!({
+        "$schema": SARIF_SCHEMA,
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "difftastic",
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "informationUri": "https://difftastic.wilfred.me.uk/",
+                    "rules": rules,
+                },
+            },
+            "originalUriBaseIds": {
+                "LHS": { "description": { "text": "The old version of the files." } },
+                "RHS": { "description": { "text": "The new version of the files." } },
+            },
+            "columnKind": "unicodeCodePoints",
+            "artifacts": artifacts,
+            "results": results,
+        }],
+    })
+}
+
+pub(crate) fn print(out: &mut dyn Write, diffs: &[DiffResult]) -> std::io::Result<()> {
+    writeln!(
+        out,
+        "{}",
+        serde_json::to_string_pretty(&sarif_log(diffs)).expect("failed to serialize SARIF")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use line_numbers::SingleLineSpan;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::parse::syntax::{AtomKind, MatchKind, TokenKind};
+
+    #[test]
+    fn test_path_to_uri() {
+        assert_eq!(path_to_uri("src/main.rs"), "src/main.rs");
+        assert_eq!(path_to_uri("a b/ü.rs"), "a%20b/%C3%BC.rs");
+    }
+
+    #[test]
+    fn test_column_counts_code_points() {
+        assert_eq!(column("foo", 0), 1);
+        assert_eq!(column("ü = 1", 3), 3);
+        assert_eq!(column("foo", 100), 4);
+    }
+
+    #[test]
+    fn test_novel_region() {
+        let novel = |line: u32, start_col, end_col| MatchedPos {
+            kind: MatchKind::Novel {
+                highlight: TokenKind::Atom(AtomKind::Normal),
+                punctuation: false,
+            },
+            pos: SingleLineSpan {
+                line: line.into(),
+                start_col,
+                end_col,
+            },
+        };
+        let mps = vec![novel(1, 4, 7), novel(0, 2, 3)];
+
+        assert_eq!(
+            novel_region(
+                &mps,
+                &HashSet::from([0.into(), 1.into()]),
+                &["foo", "bar baz"]
+            ),
+            Some(Region {
+                start_line: 1,
+                start_column: 3,
+                end_line: 2,
+                end_column: 8,
+            })
+        );
+    }
+
+    #[test]
+    fn test_empty_log() {
+        let log = sarif_log(&[]);
+        assert_eq!(log["version"], "2.1.0");
+        assert_eq!(log["runs"][0]["results"], json!([]));
+    }
+}
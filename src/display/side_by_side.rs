@@ -3,6 +3,7 @@
 use std::{
     cmp::{max, min},
     collections::HashSet,
+    io::Write,
 };
 
 use line_numbers::LineNumber;
@@ -11,14 +12,17 @@ use owo_colors::{OwoColorize, Style};
 
 use crate::{
     constants::Side,
-    display::context::all_matched_lines_filled,
-    display::hunks::{matched_lines_indexes_for_hunk, Hunk},
+    diff::moves::{copied_block_starts, moved_block_starts},
+    display::context::{all_matched_lines_filled, opposite_positions},
+    display::hunks::{hunk_context, matched_lines_indexes_for_hunk, Hunk},
+    display::long_lines::LineMap,
     display::style::{
-        self, apply_colors, apply_line_number_color, color_positions, novel_style, replace_tabs,
-        split_and_apply, BackgroundColor,
+        self, apply_colors, apply_line_number_color, color_positions,
+        highlight_trailing_whitespace, novel_line_marker, novel_line_marker_width, novel_style,
+        replace_tabs, split_and_apply, SyntaxHighlights,
     },
     hash::DftHashMap,
-    lines::format_line_num,
+    lines::{format_line_num, missing_final_newline},
     options::{DisplayMode, DisplayOptions},
     parse::syntax::{zip_pad_shorter, MatchedPos},
     summary::FileFormat,
@@ -26,16 +30,22 @@ use crate::{
 
 const SPACER: &str = " ";
 
-fn format_line_num_padded(line_num: LineNumber, column_width: usize) -> String {
+fn format_line_num_padded(
+    line_num: LineNumber,
+    prev_line_num: Option<LineNumber>,
+    line_map: &LineMap,
+    column_width: usize,
+) -> String {
     format!(
-        "{:width$} ",
-        line_num.as_usize() + 1,
+        "{:>width$} ",
+        line_map.display(line_num, prev_line_num),
         width = column_width - 1
     )
 }
 
 fn format_missing_line_num(
     prev_num: LineNumber,
+    line_map: &LineMap,
     source_dims: &SourceDimensions,
     side: Side,
     use_color: bool,
@@ -55,7 +65,7 @@ fn format_missing_line_num(
         style = style.dimmed();
     }
 
-    let num_digits = prev_num.display().len();
+    let num_digits = line_map.source_line(prev_num).display().len();
     format!(
         "{:>width$} ",
         (if after_end { " " } else { "." }).repeat(num_digits),
@@ -71,10 +81,11 @@ fn display_single_column(
     old_path: Option<&String>,
     file_format: &FileFormat,
     src_lines: &[String],
+    line_map: &LineMap,
     side: Side,
     display_options: &DisplayOptions,
 ) -> Vec<String> {
-    let column_width = format_line_num((src_lines.len() as u32).into()).len();
+    let column_width = format_line_num(line_map.source_line((src_lines.len() as u32).into())).len();
 
     let mut formatted_lines = Vec::with_capacity(src_lines.len());
 
@@ -85,6 +96,7 @@ fn display_single_column(
         1,
         1,
         file_format,
+        None,
         display_options,
     ));
     header_line.push('\n');
@@ -92,15 +104,22 @@ fn display_single_column(
 
     let mut style = Style::new();
     if display_options.use_color {
-        style = novel_style(Style::new(), side, display_options.background_color);
+        style = novel_style(Style::new(), side, &display_options.palette);
     }
 
     for (i, line) in src_lines.iter().enumerate() {
         let mut formatted_line = String::with_capacity(line.len());
+        // Every line in a single column display is novel.
+        formatted_line.push_str(&novel_line_marker(true, side, display_options));
         formatted_line.push_str(
-            &format_line_num_padded((i as u32).into(), column_width)
-                .style(style)
-                .to_string(),
+            &format_line_num_padded(
+                (i as u32).into(),
+                i.checked_sub(1).map(|prev| (prev as u32).into()),
+                line_map,
+                column_width,
+            )
+            .style(style)
+            .to_string(),
         );
         formatted_line.push_str(line);
         formatted_lines.push(formatted_line);
@@ -112,6 +131,8 @@ fn display_single_column(
 fn display_line_nums(
     lhs_line_num: Option<LineNumber>,
     rhs_line_num: Option<LineNumber>,
+    lhs_line_map: &LineMap,
+    rhs_line_map: &LineMap,
     source_dims: &SourceDimensions,
     display_options: &DisplayOptions,
     lhs_has_novel: bool,
@@ -121,26 +142,54 @@ fn display_line_nums(
 ) -> (String, String) {
     let display_lhs_line_num: String = match lhs_line_num {
         Some(line_num) => {
-            let s = format_line_num_padded(line_num, source_dims.lhs_line_nums_width);
-            apply_line_number_color(&s, lhs_has_novel, Side::Left, display_options)
+            let s = format_line_num_padded(
+                line_num,
+                prev_lhs_line_num,
+                lhs_line_map,
+                source_dims.lhs_line_nums_width,
+            );
+            format!(
+                "{}{}",
+                novel_line_marker(lhs_has_novel, Side::Left, display_options),
+                apply_line_number_color(&s, lhs_has_novel, Side::Left, display_options)
+            )
         }
-        None => format_missing_line_num(
-            prev_lhs_line_num.unwrap_or_else(|| 1.into()),
-            source_dims,
-            Side::Left,
-            display_options.use_color,
+        None => format!(
+            "{}{}",
+            novel_line_marker(false, Side::Left, display_options),
+            format_missing_line_num(
+                prev_lhs_line_num.unwrap_or_else(|| 1.into()),
+                lhs_line_map,
+                source_dims,
+                Side::Left,
+                display_options.use_color,
+            )
         ),
     };
     let display_rhs_line_num: String = match rhs_line_num {
         Some(line_num) => {
-            let s = format_line_num_padded(line_num, source_dims.rhs_line_nums_width);
-            apply_line_number_color(&s, rhs_has_novel, Side::Right, display_options)
+            let s = format_line_num_padded(
+                line_num,
+                prev_rhs_line_num,
+                rhs_line_map,
+                source_dims.rhs_line_nums_width,
+            );
+            format!(
+                "{}{}",
+                novel_line_marker(rhs_has_novel, Side::Right, display_options),
+                apply_line_number_color(&s, rhs_has_novel, Side::Right, display_options)
+            )
         }
-        None => format_missing_line_num(
-            prev_rhs_line_num.unwrap_or_else(|| 1.into()),
-            source_dims,
-            Side::Right,
-            display_options.use_color,
+        None => format!(
+            "{}{}",
+            novel_line_marker(false, Side::Right, display_options),
+            format_missing_line_num(
+                prev_rhs_line_num.unwrap_or_else(|| 1.into()),
+                rhs_line_map,
+                source_dims,
+                Side::Right,
+                display_options.use_color,
+            )
         ),
     };
 
@@ -166,7 +215,17 @@ struct SourceDimensions {
 }
 
 impl SourceDimensions {
-    fn new(terminal_width: usize, line_nums: &[(Option<LineNumber>, Option<LineNumber>)]) -> Self {
+    /// `marker_width` is the width of the `--novel-line-marker`
+    /// gutter, which is shown before the line numbers on each side.
+    /// Line numbers are shown from `lhs_line_map` and `rhs_line_map`,
+    /// so their width depends on the lines in the original files.
+    fn new(
+        terminal_width: usize,
+        line_nums: &[(Option<LineNumber>, Option<LineNumber>)],
+        lhs_line_map: &LineMap,
+        rhs_line_map: &LineMap,
+        marker_width: usize,
+    ) -> Self {
         let mut lhs_max_line: LineNumber = 1.into();
         let mut rhs_max_line: LineNumber = 1.into();
 
@@ -179,8 +238,10 @@ impl SourceDimensions {
             }
         }
 
-        let lhs_line_nums_width = format_line_num(lhs_max_line).len();
-        let rhs_line_nums_width = format_line_num(rhs_max_line).len();
+        let lhs_line_nums_width = format_line_num(lhs_line_map.source_line(lhs_max_line)).len();
+        let rhs_line_nums_width = format_line_num(rhs_line_map.source_line(rhs_max_line)).len();
+        let lhs_gutter_width = lhs_line_nums_width + marker_width;
+        let rhs_gutter_width = rhs_line_nums_width + marker_width;
 
         assert!(
             terminal_width > SPACER.len(),
@@ -188,8 +249,8 @@ impl SourceDimensions {
         );
         let lhs_total_width = (terminal_width - SPACER.len()) / 2;
 
-        let lhs_content_width = if lhs_line_nums_width < lhs_total_width {
-            lhs_total_width - lhs_line_nums_width
+        let lhs_content_width = if lhs_gutter_width < lhs_total_width {
+            lhs_total_width - lhs_gutter_width
         } else {
             // The terminal is so narrow that even the column numbers
             // display doesn't fit. Ensure we show a non-zero number
@@ -202,7 +263,7 @@ impl SourceDimensions {
             terminal_width as isize
                 - lhs_total_width as isize
                 - SPACER.len() as isize
-                - rhs_line_nums_width as isize,
+                - rhs_gutter_width as isize,
         ) as usize;
 
         // We want the content width to be the same on both
@@ -241,9 +302,12 @@ pub(crate) fn lines_with_novel(
 /// Calculate positions of highlights on both sides. This includes
 /// both syntax highlighting and added/removed content highlighting.
 fn highlight_positions(
-    background: BackgroundColor,
-    syntax_highlight: bool,
+    display_options: &DisplayOptions,
     file_format: &FileFormat,
+    lhs_syntax_highlights: &SyntaxHighlights,
+    rhs_syntax_highlights: &SyntaxHighlights,
+    lhs_lines: &[&str],
+    rhs_lines: &[&str],
     lhs_mps: &[MatchedPos],
     rhs_mps: &[MatchedPos],
 ) -> (
@@ -252,11 +316,19 @@ fn highlight_positions(
 ) {
     let lhs_positions = color_positions(
         Side::Left,
-        background,
-        syntax_highlight,
+        &display_options.palette,
+        display_options.syntax_highlight,
+        display_options.dim_punctuation,
+        lhs_syntax_highlights,
         file_format,
         lhs_mps,
     );
+    let lhs_positions = highlight_trailing_whitespace(
+        lhs_lines,
+        lhs_mps,
+        lhs_positions,
+        display_options.trailing_whitespace,
+    );
     // Preallocate the hashmap assuming the average line will have 2 items on it.
     let mut lhs_styles: DftHashMap<LineNumber, Vec<(SingleLineSpan, Style)>> =
         DftHashMap::default();
@@ -267,11 +339,19 @@ fn highlight_positions(
 
     let rhs_positions = color_positions(
         Side::Right,
-        background,
-        syntax_highlight,
+        &display_options.palette,
+        display_options.syntax_highlight,
+        display_options.dim_punctuation,
+        rhs_syntax_highlights,
         file_format,
         rhs_mps,
     );
+    let rhs_positions = highlight_trailing_whitespace(
+        rhs_lines,
+        rhs_mps,
+        rhs_positions,
+        display_options.trailing_whitespace,
+    );
     let mut rhs_styles: DftHashMap<LineNumber, Vec<(SingleLineSpan, Style)>> =
         DftHashMap::default();
     for (span, style) in rhs_positions {
@@ -287,6 +367,7 @@ fn highlight_as_novel(
     lines: &[&str],
     opposite_line_num: Option<LineNumber>,
     lines_with_novel: &HashSet<LineNumber>,
+    ignore_blank_lines: bool,
 ) -> bool {
     if let Some(line_num) = line_num {
         // If this line contains any novel tokens, highlight it.
@@ -297,8 +378,8 @@ fn highlight_as_novel(
         let line_content = lines.get(line_num.as_usize()).map(|s| str::trim(s));
         // If this is a blank line without a corresponding line on the
         // other side, highlight it too. This helps highlight novel
-        // blank lines.
-        if line_content == Some("") && opposite_line_num.is_none() {
+        // blank lines, unless we're ignoring them.
+        if line_content == Some("") && opposite_line_num.is_none() && !ignore_blank_lines {
             return true;
         }
     }
@@ -307,6 +388,7 @@ fn highlight_as_novel(
 }
 
 pub(crate) fn print(
+    out: &mut dyn Write,
     hunks: &[Hunk],
     display_options: &DisplayOptions,
     display_path: &str,
@@ -316,23 +398,58 @@ pub(crate) fn print(
     rhs_src: &str,
     lhs_mps: &[MatchedPos],
     rhs_mps: &[MatchedPos],
-) {
-    let (lhs_colored_lines, rhs_colored_lines) = if display_options.use_color {
+    lhs_line_map: &LineMap,
+    rhs_line_map: &LineMap,
+) -> std::io::Result<()> {
+    let (lhs_syntax_highlights, rhs_syntax_highlights) = if display_options.use_color {
+        let opposite_to_lhs = opposite_positions(lhs_mps);
+        let opposite_to_rhs = opposite_positions(rhs_mps);
+        (
+            SyntaxHighlights::new(
+                lhs_src,
+                Side::Left,
+                file_format,
+                hunks,
+                &opposite_to_lhs,
+                &opposite_to_rhs,
+                display_options,
+            ),
+            SyntaxHighlights::new(
+                rhs_src,
+                Side::Right,
+                file_format,
+                hunks,
+                &opposite_to_lhs,
+                &opposite_to_rhs,
+                display_options,
+            ),
+        )
+    } else {
+        (SyntaxHighlights::default(), SyntaxHighlights::default())
+    };
+
+    let (lhs_colored_lines, rhs_colored_lines) = if display_options.style_content() {
         (
             apply_colors(
                 lhs_src,
                 Side::Left,
                 display_options.syntax_highlight,
+                display_options.dim_punctuation,
+                display_options.trailing_whitespace,
                 file_format,
-                display_options.background_color,
+                &display_options.palette,
+                &lhs_syntax_highlights,
                 lhs_mps,
             ),
             apply_colors(
                 rhs_src,
                 Side::Right,
                 display_options.syntax_highlight,
+                display_options.dim_punctuation,
+                display_options.trailing_whitespace,
                 file_format,
-                display_options.background_color,
+                &display_options.palette,
+                &rhs_syntax_highlights,
                 rhs_mps,
             ),
         )
@@ -360,13 +477,14 @@ pub(crate) fn print(
             old_path,
             file_format,
             &rhs_colored_lines,
+            rhs_line_map,
             Side::Right,
             display_options,
         ) {
-            print!("{}", line);
+            write!(out, "{}", line)?;
         }
-        println!();
-        return;
+        writeln!(out)?;
+        return Ok(());
     }
     if rhs_src.is_empty() {
         for line in display_single_column(
@@ -374,21 +492,28 @@ pub(crate) fn print(
             old_path,
             file_format,
             &lhs_colored_lines,
+            lhs_line_map,
             Side::Left,
             display_options,
         ) {
-            print!("{}", line);
+            write!(out, "{}", line)?;
         }
-        println!();
-        return;
+        writeln!(out)?;
+        return Ok(());
     }
 
+    let lhs_lines = lhs_src.lines().collect::<Vec<_>>();
+    let rhs_lines = rhs_src.lines().collect::<Vec<_>>();
+
     // TODO: this is largely duplicating the `apply_colors` logic.
-    let (lhs_highlights, rhs_highlights) = if display_options.use_color {
+    let (lhs_highlights, rhs_highlights) = if display_options.style_content() {
         highlight_positions(
-            display_options.background_color,
-            display_options.syntax_highlight,
+            display_options,
             file_format,
+            &lhs_syntax_highlights,
+            &rhs_syntax_highlights,
+            &lhs_lines,
+            &rhs_lines,
             lhs_mps,
             rhs_mps,
         )
@@ -397,17 +522,38 @@ pub(crate) fn print(
     };
 
     let (lhs_lines_with_novel, rhs_lines_with_novel) = lines_with_novel(lhs_mps, rhs_mps);
+    let mut lhs_moves = moved_block_starts(lhs_mps);
+    let mut rhs_moves = moved_block_starts(rhs_mps);
+    let mut rhs_copies = copied_block_starts(rhs_mps);
+    // Annotations name lines in the opposite file, so show the line
+    // numbers from the original file.
+    for opposite_line in lhs_moves.values_mut() {
+        *opposite_line = rhs_line_map.source_line(*opposite_line);
+    }
+    for opposite_line in rhs_moves.values_mut() {
+        *opposite_line = lhs_line_map.source_line(*opposite_line);
+    }
+    for (source_line, _) in rhs_copies.values_mut() {
+        *source_line = lhs_line_map.source_line(*source_line);
+    }
+    let lhs_missing_newline = missing_final_newline(lhs_src, rhs_src);
+    let rhs_missing_newline = missing_final_newline(rhs_src, lhs_src);
 
     let mut prev_lhs_line_num = None;
     let mut prev_rhs_line_num = None;
 
-    let lhs_lines = lhs_src.lines().collect::<Vec<_>>();
-    let rhs_lines = rhs_src.lines().collect::<Vec<_>>();
     let matched_lines = all_matched_lines_filled(lhs_mps, rhs_mps, &lhs_lines, &rhs_lines);
     let mut matched_lines_to_print = &matched_lines[..];
 
     for (i, hunk) in hunks.iter().enumerate() {
-        println!(
+        // Skip hunks that weren't selected with --hunk, but keep the
+        // numbering so headers still count all the hunks.
+        if !display_options.shows_hunk(i + 1) {
+            continue;
+        }
+
+        writeln!(
+            out,
             "{}",
             style::header(
                 display_path,
@@ -415,14 +561,19 @@ pub(crate) fn print(
                 i + 1,
                 hunks.len(),
                 file_format,
+                hunk.enclosing_declaration
+                    .as_ref()
+                    .map(|declaration| declaration.description.as_str()),
                 display_options
             )
-        );
+        )?;
 
+        let context = hunk_context(hunk, &lhs_lines, &rhs_lines, display_options);
         let (start_i, end_i) = matched_lines_indexes_for_hunk(
             matched_lines_to_print,
             hunk,
-            display_options.num_context_lines as usize,
+            context.before,
+            context.after,
         );
         let aligned_lines = &matched_lines_to_print[start_i..end_i];
         // We iterate through hunks in order, so we know the next hunk
@@ -432,28 +583,55 @@ pub(crate) fn print(
         // diffs.
         matched_lines_to_print = &matched_lines_to_print[start_i..];
 
+        if display_options.hunk_ranges {
+            writeln!(
+                out,
+                "{}",
+                style::hunk_range_marker(
+                    aligned_lines,
+                    lhs_line_map,
+                    rhs_line_map,
+                    i + 1,
+                    display_options
+                )
+            )?;
+        }
+
         let no_lhs_changes = hunk.novel_lhs.is_empty();
         let no_rhs_changes = hunk.novel_rhs.is_empty();
         let same_lines = aligned_lines.iter().all(|(l, r)| l == r);
 
-        let source_dims = SourceDimensions::new(display_options.terminal_width, aligned_lines);
+        let source_dims = SourceDimensions::new(
+            display_options.terminal_width,
+            aligned_lines,
+            lhs_line_map,
+            rhs_line_map,
+            novel_line_marker_width(display_options.novel_line_marker),
+        );
+        if context.elided_before {
+            writeln!(out, "{}", style::elision_marker(display_options))?;
+        }
         for (lhs_line_num, rhs_line_num) in aligned_lines {
             let lhs_line_novel = highlight_as_novel(
                 *lhs_line_num,
                 &lhs_lines,
                 *rhs_line_num,
                 &lhs_lines_with_novel,
+                display_options.ignore_blank_lines,
             );
             let rhs_line_novel = highlight_as_novel(
                 *rhs_line_num,
                 &rhs_lines,
                 *lhs_line_num,
                 &rhs_lines_with_novel,
+                display_options.ignore_blank_lines,
             );
 
             let (display_lhs_line_num, display_rhs_line_num) = display_line_nums(
                 *lhs_line_num,
                 *rhs_line_num,
+                lhs_line_map,
+                rhs_line_map,
                 &source_dims,
                 display_options,
                 lhs_line_novel,
@@ -462,51 +640,71 @@ pub(crate) fn print(
                 prev_rhs_line_num,
             );
 
+            if let Some(annotation) = style::move_annotation(
+                *lhs_line_num,
+                *rhs_line_num,
+                &lhs_moves,
+                &rhs_moves,
+                display_options,
+            ) {
+                writeln!(out, "{}", annotation)?;
+            }
+            if let Some(annotation) =
+                style::copy_annotation(*rhs_line_num, &rhs_copies, display_options)
+            {
+                writeln!(out, "{}", annotation)?;
+            }
+
             let show_both = matches!(
                 display_options.display_mode,
                 DisplayMode::SideBySideShowBoth
             );
             if no_lhs_changes && !show_both {
-                match rhs_line_num {
-                    Some(rhs_line_num) => {
-                        let rhs_line = &rhs_colored_lines[rhs_line_num.as_usize()];
+                // Context can extend past the end of a short file, so
+                // the line may not exist.
+                match rhs_line_num.and_then(|n| rhs_colored_lines.get(n.as_usize())) {
+                    Some(rhs_line) => {
                         if same_lines {
-                            print!("{}{}", display_rhs_line_num, rhs_line);
+                            write!(out, "{}{}", display_rhs_line_num, rhs_line)?;
                         } else {
-                            print!(
+                            write!(
+                                out,
                                 "{}{}{}",
                                 display_lhs_line_num, display_rhs_line_num, rhs_line
-                            );
+                            )?;
                         }
                     }
                     None => {
                         // We didn't have any changed RHS lines in the
                         // hunk, but we had some contextual lines that
                         // only occurred on the LHS (e.g. extra newlines).
-                        println!("{}{}", display_lhs_line_num, display_rhs_line_num);
+                        writeln!(out, "{}{}", display_lhs_line_num, display_rhs_line_num)?;
                     }
                 }
             } else if no_rhs_changes && !show_both {
-                match lhs_line_num {
-                    Some(lhs_line_num) => {
-                        let lhs_line = &lhs_colored_lines[lhs_line_num.as_usize()];
+                match lhs_line_num.and_then(|n| lhs_colored_lines.get(n.as_usize())) {
+                    Some(lhs_line) => {
                         if same_lines {
-                            print!("{}{}", display_lhs_line_num, lhs_line);
+                            write!(out, "{}{}", display_lhs_line_num, lhs_line)?;
                         } else {
-                            print!(
+                            write!(
+                                out,
                                 "{}{}{}",
                                 display_lhs_line_num, display_rhs_line_num, lhs_line
-                            );
+                            )?;
                         }
                     }
                     None => {
-                        println!("{}{}", display_lhs_line_num, display_rhs_line_num);
+                        writeln!(out, "{}{}", display_lhs_line_num, display_rhs_line_num)?;
                     }
                 }
             } else {
                 let lhs_line = match lhs_line_num {
                     Some(lhs_line_num) => split_and_apply(
-                        lhs_lines[lhs_line_num.as_usize()],
+                        lhs_lines
+                            .get(lhs_line_num.as_usize())
+                            .copied()
+                            .unwrap_or(""),
                         source_dims.content_width,
                         display_options.tab_width,
                         lhs_highlights.get(lhs_line_num).unwrap_or(&vec![]),
@@ -516,7 +714,10 @@ pub(crate) fn print(
                 };
                 let rhs_line = match rhs_line_num {
                     Some(rhs_line_num) => split_and_apply(
-                        rhs_lines[rhs_line_num.as_usize()],
+                        rhs_lines
+                            .get(rhs_line_num.as_usize())
+                            .copied()
+                            .unwrap_or(""),
                         source_dims.content_width,
                         display_options.tab_width,
                         rhs_highlights.get(rhs_line_num).unwrap_or(&vec![]),
@@ -538,19 +739,21 @@ pub(crate) fn print(
                         let mut s = format_missing_line_num(
                             lhs_line_num
                                 .unwrap_or_else(|| prev_lhs_line_num.unwrap_or_else(|| 10.into())),
+                            lhs_line_map,
                             &source_dims,
                             Side::Left,
                             display_options.use_color,
                         );
+                        let mut is_novel = false;
                         if let Some(line_num) = lhs_line_num {
-                            s = apply_line_number_color(
-                                &s,
-                                lhs_lines_with_novel.contains(line_num),
-                                Side::Left,
-                                display_options,
-                            );
+                            is_novel = lhs_lines_with_novel.contains(line_num);
+                            s = apply_line_number_color(&s, is_novel, Side::Left, display_options);
                         }
-                        s
+                        format!(
+                            "{}{}",
+                            novel_line_marker(is_novel, Side::Left, display_options),
+                            s
+                        )
                     };
                     let rhs_num: String = if i == 0 {
                         display_rhs_line_num.clone()
@@ -558,25 +761,50 @@ pub(crate) fn print(
                         let mut s = format_missing_line_num(
                             rhs_line_num
                                 .unwrap_or_else(|| prev_rhs_line_num.unwrap_or_else(|| 10.into())),
+                            rhs_line_map,
                             &source_dims,
                             Side::Right,
                             display_options.use_color,
                         );
+                        let mut is_novel = false;
                         if let Some(line_num) = rhs_line_num {
-                            s = apply_line_number_color(
-                                &s,
-                                rhs_lines_with_novel.contains(line_num),
-                                Side::Right,
-                                display_options,
-                            );
+                            is_novel = rhs_lines_with_novel.contains(line_num);
+                            s = apply_line_number_color(&s, is_novel, Side::Right, display_options);
                         }
-                        s
+                        format!(
+                            "{}{}",
+                            novel_line_marker(is_novel, Side::Right, display_options),
+                            s
+                        )
                     };
 
-                    println!("{}{}{}{}{}", lhs_num, lhs_line, SPACER, rhs_num, rhs_line);
+                    writeln!(
+                        out,
+                        "{}{}{}{}{}",
+                        lhs_num, lhs_line, SPACER, rhs_num, rhs_line
+                    )?;
                 }
             }
 
+            // Only mark lines on a side we've shown, not the opposite
+            // line numbers in a single column hunk.
+            let lhs_shown = show_both || !no_lhs_changes;
+            let rhs_shown = show_both || no_lhs_changes || !no_rhs_changes;
+            if lhs_shown && lhs_line_num.is_some() && *lhs_line_num == lhs_missing_newline {
+                writeln!(
+                    out,
+                    "{}",
+                    style::no_newline_marker(Some(Side::Left), display_options)
+                )?;
+            }
+            if rhs_shown && rhs_line_num.is_some() && *rhs_line_num == rhs_missing_newline {
+                writeln!(
+                    out,
+                    "{}",
+                    style::no_newline_marker(Some(Side::Right), display_options)
+                )?;
+            }
+
             if lhs_line_num.is_some() {
                 prev_lhs_line_num = *lhs_line_num;
             }
@@ -584,8 +812,12 @@ pub(crate) fn print(
                 prev_rhs_line_num = *rhs_line_num;
             }
         }
-        println!();
+        if context.elided_after {
+            writeln!(out, "{}", style::elision_marker(display_options))?;
+        }
+        writeln!(out)?;
     }
+    Ok(())
 }
 
 #[cfg(test)]
@@ -594,6 +826,8 @@ mod tests {
 
     use super::*;
     use crate::{
+        display::hunks::matched_pos_to_hunks,
+        line_parser::{self, Whitespace},
         options::DEFAULT_TERMINAL_WIDTH,
         parse::guess_language::Language,
         syntax::{AtomKind, MatchKind, TokenKind},
@@ -602,12 +836,39 @@ mod tests {
     #[test]
     fn test_width_calculations() {
         let line_nums = [(Some(1.into()), Some(10.into()))];
-        let source_dims = SourceDimensions::new(DEFAULT_TERMINAL_WIDTH, &line_nums);
+        let source_dims = SourceDimensions::new(
+            DEFAULT_TERMINAL_WIDTH,
+            &line_nums,
+            &LineMap::default(),
+            &LineMap::default(),
+            0,
+        );
 
         assert_eq!(source_dims.lhs_line_nums_width, 2);
         assert_eq!(source_dims.rhs_line_nums_width, 3);
     }
 
+    #[test]
+    fn test_width_calculations_with_marker() {
+        let line_nums = [(Some(1.into()), Some(10.into()))];
+        let without_marker = SourceDimensions::new(
+            DEFAULT_TERMINAL_WIDTH,
+            &line_nums,
+            &LineMap::default(),
+            &LineMap::default(),
+            0,
+        );
+        let with_marker = SourceDimensions::new(
+            DEFAULT_TERMINAL_WIDTH,
+            &line_nums,
+            &LineMap::default(),
+            &LineMap::default(),
+            1,
+        );
+
+        assert_eq!(with_marker.content_width + 1, without_marker.content_width);
+    }
+
     #[test]
     fn test_format_missing_line_num() {
         let source_dims = SourceDimensions::new(
@@ -616,14 +877,29 @@ mod tests {
                 (Some(0.into()), Some(0.into())),
                 (Some(1.into()), Some(1.into())),
             ],
+            &LineMap::default(),
+            &LineMap::default(),
+            0,
         );
 
         assert_eq!(
-            format_missing_line_num(0.into(), &source_dims, Side::Left, true),
+            format_missing_line_num(
+                0.into(),
+                &LineMap::default(),
+                &source_dims,
+                Side::Left,
+                true
+            ),
             ". ".dimmed().to_string()
         );
         assert_eq!(
-            format_missing_line_num(0.into(), &source_dims, Side::Left, false),
+            format_missing_line_num(
+                0.into(),
+                &LineMap::default(),
+                &source_dims,
+                Side::Left,
+                false
+            ),
             ". ".to_owned()
         );
     }
@@ -636,14 +912,29 @@ mod tests {
                 (Some(0.into()), Some(0.into())),
                 (Some(1.into()), Some(1.into())),
             ],
+            &LineMap::default(),
+            &LineMap::default(),
+            0,
         );
 
         assert_eq!(
-            format_missing_line_num(1.into(), &source_dims, Side::Left, true),
+            format_missing_line_num(
+                1.into(),
+                &LineMap::default(),
+                &source_dims,
+                Side::Left,
+                true
+            ),
             "  ".dimmed().to_string()
         );
         assert_eq!(
-            format_missing_line_num(1.into(), &source_dims, Side::Left, false),
+            format_missing_line_num(
+                1.into(),
+                &LineMap::default(),
+                &source_dims,
+                Side::Left,
+                false
+            ),
             "  ".to_owned()
         );
     }
@@ -656,6 +947,7 @@ mod tests {
             None,
             &FileFormat::SupportedLanguage(Language::Python),
             &["print(123)\n".to_owned()],
+            &LineMap::default(),
             Side::Right,
             &DisplayOptions::default(),
         );
@@ -675,6 +967,7 @@ mod tests {
         let lhs_mps = [MatchedPos {
             kind: MatchKind::Novel {
                 highlight: TokenKind::Atom(AtomKind::Normal),
+                punctuation: false,
             },
             pos: SingleLineSpan {
                 line: 0.into(),
@@ -686,6 +979,7 @@ mod tests {
         let rhs_mps = [MatchedPos {
             kind: MatchKind::Novel {
                 highlight: TokenKind::Atom(AtomKind::Normal),
+                punctuation: false,
             },
             pos: SingleLineSpan {
                 line: 0.into(),
@@ -703,10 +997,13 @@ mod tests {
             novel_lhs,
             novel_rhs,
             lines: vec![(Some(0.into()), Some(0.into()))],
+            enclosing_declaration: None,
         }];
 
         // Simple smoke test.
+        let mut out = vec![];
         print(
+            &mut out,
             &hunks,
             &DisplayOptions::default(),
             "foo-new.el",
@@ -716,6 +1013,81 @@ mod tests {
             "bar",
             &lhs_mps,
             &rhs_mps,
-        );
+            &LineMap::default(),
+            &LineMap::default(),
+        )
+        .unwrap();
+        assert!(!out.is_empty());
+    }
+
+    #[test]
+    fn test_display_missing_final_newline() {
+        let lhs = "foo\nbar";
+        let rhs = "foo\nbaz\n";
+        let lhs_mps = line_parser::change_positions(lhs, rhs, Whitespace::Exact, None);
+        let rhs_mps = line_parser::change_positions(rhs, lhs, Whitespace::Exact, None);
+        let hunks = matched_pos_to_hunks(&lhs_mps, &rhs_mps);
+
+        let mut out = vec![];
+        print(
+            &mut out,
+            &hunks,
+            &DisplayOptions::default(),
+            "foo.txt",
+            None,
+            &FileFormat::PlainText,
+            lhs,
+            rhs,
+            &lhs_mps,
+            &rhs_mps,
+            &LineMap::default(),
+            &LineMap::default(),
+        )
+        .unwrap();
+        let out = String::from_utf8(out).unwrap();
+
+        // The marker is on its own line, after the row with the last
+        // old line. The hunks aren't merged, so the new lines are in a
+        // separate single column hunk, which isn't marked.
+        let lines: Vec<&str> = out.lines().collect();
+        let i = lines.iter().position(|line| line.contains("bar")).unwrap();
+        assert_eq!(lines[i + 1], "\\ No newline at end of old file");
+        assert_eq!(out.matches("No newline").count(), 1);
+    }
+
+    #[test]
+    fn test_display_huge_context() {
+        // The context is far longer than either file, so must stop at
+        // the end of each.
+        let lhs = "foo\nbar\n";
+        let rhs = "baz\n";
+        let lhs_mps = line_parser::change_positions(lhs, rhs, Whitespace::Exact, None);
+        let rhs_mps = line_parser::change_positions(rhs, lhs, Whitespace::Exact, None);
+        let hunks = matched_pos_to_hunks(&lhs_mps, &rhs_mps);
+        let display_options = DisplayOptions {
+            before_context_lines: 1_000_000,
+            after_context_lines: 1_000_000,
+            ..DisplayOptions::default()
+        };
+
+        let mut out = vec![];
+        print(
+            &mut out,
+            &hunks,
+            &display_options,
+            "foo.txt",
+            None,
+            &FileFormat::PlainText,
+            lhs,
+            rhs,
+            &lhs_mps,
+            &rhs_mps,
+            &LineMap::default(),
+            &LineMap::default(),
+        )
+        .unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.contains("bar"));
+        assert!(out.contains("baz"));
     }
 }
@@ -0,0 +1,310 @@
+//! A compact summary of changes per file, similar to `git diff --stat`.
+
+use std::{collections::HashSet, io::Write};
+
+use line_numbers::LineNumber;
+use owo_colors::OwoColorize;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+use crate::{
+    display::hunks::Hunk,
+    options::DisplayOptions,
+    summary::{DiffResult, FileContent, FileFormat},
+};
+
+/// Always leave at least this many columns for the `+++---` bar, even
+/// if it means truncating paths.
+const MIN_BAR_WIDTH: usize = 10;
+
+#[derive(Debug, PartialEq, Eq)]
+enum FileStat {
+    Text {
+        insertions: usize,
+        deletions: usize,
+    },
+    /// A binary file, with the size of each side if we read them.
+    Binary(Option<(u64, u64)>),
+    /// A subdirectory beyond `--max-depth`.
+    Directory,
+}
+
+/// The number of inserted and deleted lines in `hunks`.
+pub(crate) fn line_changes(hunks: &[Hunk]) -> (usize, usize) {
+    let mut novel_lhs: HashSet<LineNumber> = HashSet::new();
+    let mut novel_rhs: HashSet<LineNumber> = HashSet::new();
+    for hunk in hunks {
+        novel_lhs.extend(hunk.novel_lhs.iter());
+        novel_rhs.extend(hunk.novel_rhs.iter());
+    }
+
+    (novel_rhs.len(), novel_lhs.len())
+}
+
+fn file_stat(diff: &DiffResult) -> FileStat {
+    if diff.file_format == FileFormat::Directory {
+        return FileStat::Directory;
+    }
+    match (&diff.lhs_src, &diff.rhs_src) {
+        (FileContent::Text(_), FileContent::Text(_)) => {
+            let (insertions, deletions) = line_changes(&diff.hunks);
+            FileStat::Text {
+                insertions,
+                deletions,
+            }
+        }
+        _ => FileStat::Binary(
+            diff.binary_info
+                .map(|(lhs_info, rhs_info)| (lhs_info.len, rhs_info.len)),
+        ),
+    }
+}
+
+/// Truncate `path` to fit in `width` columns, keeping the end of the
+/// path so the file name remains visible. Where possible, truncate
+/// at a directory boundary, e.g. `.../src/main.rs`.
+fn truncate_path(path: &str, width: usize) -> String {
+    if path.width() <= width {
+        return path.to_owned();
+    }
+
+    let ellipsis = "...";
+    let max_suffix_width = width.saturating_sub(ellipsis.len());
+
+    // Find the longest suffix of `path` that fits.
+    let mut suffix_start = path.len();
+    let mut suffix_width = 0;
+    for (i, c) in path.char_indices().rev() {
+        let c_width = c.width().unwrap_or(0);
+        if suffix_width + c_width > max_suffix_width {
+            break;
+        }
+        suffix_width += c_width;
+        suffix_start = i;
+    }
+
+    let mut suffix = &path[suffix_start..];
+    if let Some(slash_i) = suffix.find('/') {
+        // Don't show a partial directory name, unless that's all we
+        // have room for.
+        if slash_i < suffix.len() - 1 {
+            suffix = &suffix[slash_i..];
+        }
+    }
+
+    format!("{}{}", ellipsis, suffix)
+}
+
+/// Scale `count` so the largest change fits in `bar_width`, ensuring
+/// that any non-zero count is still shown.
+fn scale(count: usize, max_change: usize, bar_width: usize) -> usize {
+    if max_change <= bar_width || count == 0 {
+        return count;
+    }
+
+    std::cmp::max(1, count * bar_width / max_change)
+}
+
+fn pluralize(count: usize, singular: &str, plural: &str) -> String {
+    format!("{} {}", count, if count == 1 { singular } else { plural })
+}
+
+/// The final summary line, e.g. `2 files changed, 3 insertions(+), 1
+/// deletion(-)`. Subdirectories beyond `--max-depth` are counted
+/// separately from files.
+fn totals_line(
+    num_files: usize,
+    num_directories: usize,
+    insertions: usize,
+    deletions: usize,
+) -> String {
+    let mut res = format!(" {} changed", pluralize(num_files, "file", "files"));
+    if num_directories > 0 {
+        res.push_str(&format!(
+            ", {} changed",
+            pluralize(num_directories, "directory", "directories")
+        ));
+    }
+    if insertions > 0 || deletions == 0 {
+        res.push_str(&format!(
+            ", {}(+)",
+            pluralize(insertions, "insertion", "insertions")
+        ));
+    }
+    if deletions > 0 || insertions == 0 {
+        res.push_str(&format!(
+            ", {}(-)",
+            pluralize(deletions, "deletion", "deletions")
+        ));
+    }
+    res
+}
+
+/// Format the stat lines for the files in `diffs` that have changes.
+fn format_stat(diffs: &[DiffResult], display_options: &DisplayOptions) -> Vec<String> {
+    let stats: Vec<(&str, FileStat)> = diffs
+        .iter()
+        .filter(|diff| diff.has_reportable_change())
+        .map(|diff| (diff.display_path.as_str(), file_stat(diff)))
+        .collect();
+
+    let max_change = stats
+        .iter()
+        .map(|(_, stat)| match stat {
+            FileStat::Text {
+                insertions,
+                deletions,
+            } => insertions + deletions,
+            FileStat::Binary(_) | FileStat::Directory => 0,
+        })
+        .max()
+        .unwrap_or(0);
+
+    let count_width = std::cmp::max(max_change.to_string().len(), "Bin".len());
+    let max_path_width = stats
+        .iter()
+        .map(|(path, _)| path.width())
+        .max()
+        .unwrap_or(0);
+
+    // Layout: " {path} | {count} {bar}"
+    let fixed_width = 1 + " | ".len() + count_width + 1;
+    let path_width = std::cmp::min(
+        max_path_width,
+        display_options
+            .terminal_width
+            .saturating_sub(fixed_width + MIN_BAR_WIDTH),
+    );
+    let bar_width = display_options
+        .terminal_width
+        .saturating_sub(fixed_width + path_width)
+        .max(1);
+
+    let mut lines = vec![];
+    let mut total_insertions = 0;
+    let mut total_deletions = 0;
+    for (path, stat) in &stats {
+        let path = truncate_path(path, path_width);
+        let padding = " ".repeat(path_width.saturating_sub(path.width()));
+
+        let line = match stat {
+            FileStat::Text {
+                insertions,
+                deletions,
+            } => {
+                total_insertions += insertions;
+                total_deletions += deletions;
+
+                let mut plus = "+".repeat(scale(*insertions, max_change, bar_width));
+                let mut minus = "-".repeat(scale(*deletions, max_change, bar_width));
+                if display_options.use_color {
+                    plus = plus.green().to_string();
+                    minus = minus.red().to_string();
+                }
+
+                format!(
+                    " {}{} | {:>count_width$} {}{}",
+                    path,
+                    padding,
+                    insertions + deletions,
+                    plus,
+                    minus,
+                    count_width = count_width,
+                )
+            }
+            FileStat::Binary(sizes) => {
+                let mut line = format!(
+                    " {}{} | {:>count_width$}",
+                    path,
+                    padding,
+                    "Bin",
+                    count_width = count_width,
+                );
+                // Like `git diff --stat`.
+                if let Some((lhs_len, rhs_len)) = sizes {
+                    line.push_str(&format!(" {} → {} bytes", lhs_len, rhs_len));
+                }
+                line
+            }
+            FileStat::Directory => format!(
+                " {}{} | {:>count_width$}",
+                path,
+                padding,
+                "Dir",
+                count_width = count_width,
+            ),
+        };
+        lines.push(line.trim_end().to_owned());
+    }
+
+    let num_directories = stats
+        .iter()
+        .filter(|(_, stat)| *stat == FileStat::Directory)
+        .count();
+    lines.push(totals_line(
+        stats.len() - num_directories,
+        num_directories,
+        total_insertions,
+        total_deletions,
+    ));
+    lines
+}
+
+/// Print a summary of the changed lines in each file, followed by the
+/// totals.
+pub(crate) fn print(
+    out: &mut dyn Write,
+    diffs: &[DiffResult],
+    display_options: &DisplayOptions,
+) -> std::io::Result<()> {
+    for line in format_stat(diffs, display_options) {
+        writeln!(out, "{}", line)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_truncate_path_fits() {
+        assert_eq!(truncate_path("src/main.rs", 20), "src/main.rs");
+    }
+
+    #[test]
+    fn test_truncate_path_at_directory() {
+        assert_eq!(
+            truncate_path("src/display/side_by_side.rs", 20),
+            ".../side_by_side.rs"
+        );
+    }
+
+    #[test]
+    fn test_truncate_path_long_file_name() {
+        assert_eq!(truncate_path("a_very_long_name.rs", 10), "...name.rs");
+    }
+
+    #[test]
+    fn test_scale() {
+        assert_eq!(scale(5, 10, 20), 5);
+        assert_eq!(scale(50, 100, 10), 5);
+        assert_eq!(scale(1, 1000, 10), 1);
+        assert_eq!(scale(0, 1000, 10), 0);
+    }
+
+    #[test]
+    fn test_totals_line() {
+        assert_eq!(
+            totals_line(12, 0, 340, 77),
+            " 12 files changed, 340 insertions(+), 77 deletions(-)"
+        );
+        assert_eq!(totals_line(1, 0, 1, 0), " 1 file changed, 1 insertion(+)");
+        assert_eq!(totals_line(1, 0, 0, 2), " 1 file changed, 2 deletions(-)");
+        assert_eq!(
+            totals_line(1, 2, 1, 0),
+            " 1 file changed, 2 directories changed, 1 insertion(+)"
+        );
+    }
+}
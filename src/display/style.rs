@@ -1,19 +1,31 @@
 //! Apply colours and styling to strings.
 
 use std::cmp::{max, min};
+use std::collections::HashSet;
 
 use line_numbers::LineNumber;
 use line_numbers::SingleLineSpan;
-use owo_colors::{OwoColorize, Style};
+use owo_colors::{AnsiColors, DynColors, OwoColorize, Style, XtermColors};
+use serde_json::json;
 use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
 
 use crate::parse::syntax::StringKind;
 use crate::{
     constants::Side,
+    display::{
+        context::displayed_line_range,
+        hunks::{hunk_context, Hunk},
+        long_lines::LineMap,
+        patch::format_range,
+    },
     hash::DftHashMap,
     lines::byte_len,
+    logging::{self, Level},
     options::DisplayOptions,
-    parse::syntax::{AtomKind, MatchKind, MatchedPos, TokenKind},
+    parse::{
+        syntax::{AtomKind, MatchKind, MatchedPos, TokenKind},
+        tree_sitter_parser::{highlight_captures, HighlightKind},
+    },
     summary::FileFormat,
 };
 
@@ -29,6 +41,525 @@ impl BackgroundColor {
     }
 }
 
+/// How many colors the terminal supports.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum ColorDepth {
+    Ansi16,
+    Ansi256,
+    TrueColor,
+}
+
+/// When to make novel trailing whitespace visible.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum TrailingWhitespace {
+    /// Only on lines where the change is entirely whitespace.
+    Auto,
+    Always,
+    Never,
+}
+
+/// A terminal color.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Color {
+    Ansi(AnsiColors),
+    /// An index into the 256 color palette.
+    Xterm(u8),
+    Rgb(u8, u8, u8),
+}
+
+/// The 16 ANSI colors, with the RGB values that xterm uses by
+/// default. Terminals vary, but this is good enough for finding the
+/// nearest ANSI color.
+const ANSI_RGB: [(AnsiColors, (u8, u8, u8)); 16] = [
+    (AnsiColors::Black, (0, 0, 0)),
+    (AnsiColors::Red, (205, 0, 0)),
+    (AnsiColors::Green, (0, 205, 0)),
+    (AnsiColors::Yellow, (205, 205, 0)),
+    (AnsiColors::Blue, (0, 0, 238)),
+    (AnsiColors::Magenta, (205, 0, 205)),
+    (AnsiColors::Cyan, (0, 205, 205)),
+    (AnsiColors::White, (229, 229, 229)),
+    (AnsiColors::BrightBlack, (127, 127, 127)),
+    (AnsiColors::BrightRed, (255, 0, 0)),
+    (AnsiColors::BrightGreen, (0, 255, 0)),
+    (AnsiColors::BrightYellow, (255, 255, 0)),
+    (AnsiColors::BrightBlue, (92, 92, 255)),
+    (AnsiColors::BrightMagenta, (255, 0, 255)),
+    (AnsiColors::BrightCyan, (0, 255, 255)),
+    (AnsiColors::BrightWhite, (255, 255, 255)),
+];
+
+/// The intensities used for each component of the 6x6x6 color cube
+/// in the 256 color palette.
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+fn distance((r1, g1, b1): (u8, u8, u8), (r2, g2, b2): (u8, u8, u8)) -> u32 {
+    let d = |x: u8, y: u8| (x as i32 - y as i32).pow(2) as u32;
+    d(r1, r2) + d(g1, g2) + d(b1, b2)
+}
+
+/// The RGB value of an entry in the 256 color palette.
+fn xterm_to_rgb(index: u8) -> (u8, u8, u8) {
+    match index {
+        0..=15 => ANSI_RGB[index as usize].1,
+        16..=231 => {
+            let i = index - 16;
+            (
+                CUBE_LEVELS[(i / 36) as usize],
+                CUBE_LEVELS[(i / 6 % 6) as usize],
+                CUBE_LEVELS[(i % 6) as usize],
+            )
+        }
+        232..=255 => {
+            let level = 8 + 10 * (index - 232);
+            (level, level, level)
+        }
+    }
+}
+
+/// The nearest entry in the 256 color palette. We don't use the
+/// first 16 entries, as terminals often customise them.
+fn rgb_to_xterm(rgb: (u8, u8, u8)) -> u8 {
+    let nearest_level = |component: u8| -> u8 {
+        (0..CUBE_LEVELS.len() as u8)
+            .min_by_key(|i| (CUBE_LEVELS[*i as usize] as i32 - component as i32).abs())
+            .unwrap()
+    };
+    let cube_index =
+        16 + 36 * nearest_level(rgb.0) + 6 * nearest_level(rgb.1) + nearest_level(rgb.2);
+
+    let gray_index = (232..=255)
+        .min_by_key(|i| distance(xterm_to_rgb(*i), rgb))
+        .unwrap();
+
+    if distance(xterm_to_rgb(gray_index), rgb) < distance(xterm_to_rgb(cube_index), rgb) {
+        gray_index
+    } else {
+        cube_index
+    }
+}
+
+fn rgb_to_ansi(rgb: (u8, u8, u8)) -> AnsiColors {
+    ANSI_RGB
+        .iter()
+        .min_by_key(|(_, ansi_rgb)| distance(*ansi_rgb, rgb))
+        .unwrap()
+        .0
+}
+
+impl Color {
+    /// The nearest color that can be shown with `depth`.
+    fn quantize(self, depth: ColorDepth) -> Self {
+        match (self, depth) {
+            (Color::Rgb(r, g, b), ColorDepth::Ansi256) => Color::Xterm(rgb_to_xterm((r, g, b))),
+            (Color::Rgb(r, g, b), ColorDepth::Ansi16) => Color::Ansi(rgb_to_ansi((r, g, b))),
+            (Color::Xterm(index), ColorDepth::Ansi16) => {
+                Color::Ansi(rgb_to_ansi(xterm_to_rgb(index)))
+            }
+            _ => self,
+        }
+    }
+
+    fn to_dyn(self) -> DynColors {
+        match self {
+            Color::Ansi(color) => DynColors::Ansi(color),
+            Color::Xterm(index) => DynColors::Xterm(XtermColors::from(index)),
+            Color::Rgb(r, g, b) => DynColors::Rgb(r, g, b),
+        }
+    }
+}
+
+/// How to highlight content of a given role.
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum Highlight {
+    Color(Color),
+    // Text effects without a color, for terminals with broken color
+    // support.
+    Bold,
+    Underline,
+    Reverse,
+    Strikethrough,
+}
+
+impl Highlight {
+    fn apply(self, style: Style) -> Style {
+        match self {
+            Highlight::Color(color) => style.color(color.to_dyn()),
+            Highlight::Bold => style.bold(),
+            Highlight::Underline => style.underline(),
+            Highlight::Reverse => style.reversed(),
+            Highlight::Strikethrough => style.strikethrough(),
+        }
+    }
+}
+
+/// A built-in set of colors.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Theme {
+    /// The default for dark backgrounds.
+    Dark,
+    /// The default for light backgrounds.
+    Light,
+    SolarizedDark,
+    SolarizedLight,
+    Gruvbox,
+    Dracula,
+    /// No colors, only bold, underline and reverse video.
+    Monochrome,
+}
+
+impl Theme {
+    /// The theme to use if the user hasn't chosen one.
+    pub(crate) fn default_for(background: BackgroundColor) -> Self {
+        match background {
+            BackgroundColor::Dark => Theme::Dark,
+            BackgroundColor::Light => Theme::Light,
+        }
+    }
+
+    /// The background this theme is designed for.
+    pub(crate) fn background(self) -> BackgroundColor {
+        match self {
+            Theme::Light | Theme::SolarizedLight => BackgroundColor::Light,
+            Theme::Dark
+            | Theme::SolarizedDark
+            | Theme::Gruvbox
+            | Theme::Dracula
+            | Theme::Monochrome => BackgroundColor::Dark,
+        }
+    }
+}
+
+fn rgb(rgb: u32) -> Highlight {
+    Highlight::Color(Color::Rgb((rgb >> 16) as u8, (rgb >> 8) as u8, rgb as u8))
+}
+
+fn xterm(index: u8) -> Highlight {
+    Highlight::Color(Color::Xterm(index))
+}
+
+fn ansi(color: AnsiColors) -> Highlight {
+    Highlight::Color(Color::Ansi(color))
+}
+
+/// The highlighting used for each kind of content. Roles that are
+/// `None` use the default styling for that role.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Palette {
+    pub(crate) novel_lhs: Highlight,
+    pub(crate) novel_rhs: Highlight,
+    /// Novel words inside changed lines. Defaults to the novel
+    /// highlighting of that side.
+    pub(crate) novel_word: Option<Highlight>,
+    /// Line numbers of unchanged lines. Defaults to dimmed.
+    pub(crate) line_number: Option<Highlight>,
+    /// Line numbers of changed lines. Defaults to the novel
+    /// highlighting of that side.
+    pub(crate) novel_line_number: Option<Highlight>,
+    pub(crate) header: Highlight,
+    /// Comments. These are always italic.
+    pub(crate) comment: Option<Highlight>,
+    pub(crate) string: Option<Highlight>,
+    /// Keywords and types. These are always bold.
+    pub(crate) keyword: Option<Highlight>,
+    /// Types on unchanged lines, with `--syntax-highlight=full`.
+    pub(crate) type_name: Option<Highlight>,
+    /// Function names on unchanged lines, with
+    /// `--syntax-highlight=full`.
+    pub(crate) function: Option<Highlight>,
+    /// Constants and number literals on unchanged lines, with
+    /// `--syntax-highlight=full`.
+    pub(crate) constant: Option<Highlight>,
+    /// Whether to also mark novel content with text attributes, so
+    /// removals and additions can be told apart without color.
+    pub(crate) accessible: bool,
+}
+
+impl Palette {
+    /// The palette for `theme`. The default themes have RGB, 256 color
+    /// and ANSI variants, for the colors the terminal supports. Call
+    /// `quantize` to convert other themes to the terminal's colors.
+    pub(crate) fn new(theme: Theme, color_depth: ColorDepth) -> Self {
+        let defaults = Self {
+            novel_lhs: ansi(AnsiColors::BrightRed),
+            novel_rhs: ansi(AnsiColors::BrightGreen),
+            novel_word: None,
+            line_number: None,
+            novel_line_number: None,
+            header: ansi(AnsiColors::BrightYellow),
+            comment: Some(ansi(AnsiColors::BrightBlue)),
+            string: Some(ansi(AnsiColors::BrightMagenta)),
+            keyword: None,
+            type_name: Some(ansi(AnsiColors::BrightCyan)),
+            function: Some(ansi(AnsiColors::BrightYellow)),
+            constant: Some(ansi(AnsiColors::BrightMagenta)),
+            accessible: false,
+        };
+
+        match theme {
+            Theme::Dark if color_depth == ColorDepth::TrueColor => Self {
+                novel_lhs: rgb(0xff5f5f),
+                novel_rhs: rgb(0x5fd75f),
+                header: rgb(0xffd75f),
+                comment: Some(rgb(0x729fcf)),
+                string: Some(rgb(0xe17ee1)),
+                type_name: Some(rgb(0x56b6c2)),
+                function: Some(rgb(0xe5c07b)),
+                constant: Some(rgb(0xd19a66)),
+                ..defaults
+            },
+            Theme::Dark if color_depth == ColorDepth::Ansi256 => Self {
+                novel_lhs: xterm(203),
+                novel_rhs: xterm(77),
+                header: xterm(221),
+                comment: Some(xterm(74)),
+                string: Some(xterm(176)),
+                type_name: Some(xterm(73)),
+                function: Some(xterm(180)),
+                constant: Some(xterm(173)),
+                ..defaults
+            },
+            Theme::Dark => defaults,
+            Theme::Light if color_depth == ColorDepth::TrueColor => Self {
+                novel_lhs: rgb(0xc82828),
+                novel_rhs: rgb(0x2a8a2a),
+                header: rgb(0xa67c00),
+                comment: Some(rgb(0x3465a4)),
+                string: Some(rgb(0xa0409f)),
+                type_name: Some(rgb(0x0e7c86)),
+                function: Some(rgb(0x8a6a00)),
+                constant: Some(rgb(0xb35900)),
+                ..defaults
+            },
+            Theme::Light if color_depth == ColorDepth::Ansi256 => Self {
+                novel_lhs: xterm(160),
+                novel_rhs: xterm(28),
+                header: xterm(136),
+                comment: Some(xterm(25)),
+                string: Some(xterm(133)),
+                type_name: Some(xterm(30)),
+                function: Some(xterm(94)),
+                constant: Some(xterm(130)),
+                ..defaults
+            },
+            Theme::Light => Self {
+                novel_lhs: ansi(AnsiColors::Red),
+                novel_rhs: ansi(AnsiColors::Green),
+                header: ansi(AnsiColors::Yellow),
+                comment: Some(ansi(AnsiColors::Blue)),
+                string: Some(ansi(AnsiColors::Magenta)),
+                type_name: Some(ansi(AnsiColors::Cyan)),
+                function: Some(ansi(AnsiColors::Yellow)),
+                constant: Some(ansi(AnsiColors::Magenta)),
+                ..defaults
+            },
+            // Solarized uses the same accent colors on both
+            // backgrounds, but swaps its base tones: secondary content
+            // is base01 on dark and base1 on light, and emphasized
+            // content is base1 on dark and base01 on light.
+            Theme::SolarizedDark => Self {
+                novel_lhs: rgb(0xdc322f),
+                novel_rhs: rgb(0x859900),
+                header: rgb(0x93a1a1),
+                comment: Some(rgb(0x586e75)),
+                line_number: Some(rgb(0x586e75)),
+                string: Some(rgb(0x2aa198)),
+                keyword: Some(rgb(0x268bd2)),
+                type_name: Some(rgb(0xb58900)),
+                function: Some(rgb(0x6c71c4)),
+                constant: Some(rgb(0xcb4b16)),
+                ..defaults
+            },
+            Theme::SolarizedLight => Self {
+                novel_lhs: rgb(0xdc322f),
+                novel_rhs: rgb(0x859900),
+                header: rgb(0x586e75),
+                comment: Some(rgb(0x93a1a1)),
+                line_number: Some(rgb(0x93a1a1)),
+                string: Some(rgb(0x2aa198)),
+                keyword: Some(rgb(0x268bd2)),
+                type_name: Some(rgb(0xb58900)),
+                function: Some(rgb(0x6c71c4)),
+                constant: Some(rgb(0xcb4b16)),
+                ..defaults
+            },
+            Theme::Gruvbox => Self {
+                novel_lhs: rgb(0xfb4934),
+                novel_rhs: rgb(0xb8bb26),
+                header: rgb(0xfabd2f),
+                comment: Some(rgb(0x928374)),
+                line_number: Some(rgb(0x7c6f64)),
+                string: Some(rgb(0xd3869b)),
+                keyword: Some(rgb(0x83a598)),
+                type_name: Some(rgb(0xfabd2f)),
+                function: Some(rgb(0x8ec07c)),
+                constant: Some(rgb(0xfe8019)),
+                ..defaults
+            },
+            Theme::Dracula => Self {
+                novel_lhs: rgb(0xff5555),
+                novel_rhs: rgb(0x50fa7b),
+                header: rgb(0xbd93f9),
+                comment: Some(rgb(0x6272a4)),
+                line_number: Some(rgb(0x6272a4)),
+                string: Some(rgb(0xf1fa8c)),
+                keyword: Some(rgb(0xff79c6)),
+                type_name: Some(rgb(0x8be9fd)),
+                function: Some(rgb(0xffb86c)),
+                constant: Some(rgb(0xbd93f9)),
+                ..defaults
+            },
+            Theme::Monochrome => Self {
+                novel_lhs: Highlight::Reverse,
+                novel_rhs: Highlight::Underline,
+                header: Highlight::Bold,
+                comment: None,
+                string: None,
+                type_name: None,
+                function: None,
+                constant: None,
+                ..defaults
+            },
+        }
+    }
+
+    /// Convert every color in this palette to the nearest color that
+    /// can be shown with `depth`.
+    pub(crate) fn quantize(self, depth: ColorDepth) -> Self {
+        let quantize = |highlight: Highlight| match highlight {
+            Highlight::Color(color) => Highlight::Color(color.quantize(depth)),
+            _ => highlight,
+        };
+
+        Self {
+            novel_lhs: quantize(self.novel_lhs),
+            novel_rhs: quantize(self.novel_rhs),
+            novel_word: self.novel_word.map(quantize),
+            line_number: self.line_number.map(quantize),
+            novel_line_number: self.novel_line_number.map(quantize),
+            header: quantize(self.header),
+            comment: self.comment.map(quantize),
+            string: self.string.map(quantize),
+            keyword: self.keyword.map(quantize),
+            type_name: self.type_name.map(quantize),
+            function: self.function.map(quantize),
+            constant: self.constant.map(quantize),
+            accessible: self.accessible,
+        }
+    }
+
+    /// Strike through removed content and embolden added content,
+    /// in addition to this palette's colors.
+    pub(crate) fn accessible(self) -> Self {
+        Self {
+            accessible: true,
+            ..self
+        }
+    }
+
+    /// A palette without any colors, for `--accessibility` with
+    /// `--color=never`. Text attributes are the only way of
+    /// distinguishing changes.
+    pub(crate) fn attributes_only() -> Self {
+        Self {
+            novel_lhs: Highlight::Strikethrough,
+            novel_rhs: Highlight::Bold,
+            ..Self::new(Theme::Monochrome, ColorDepth::Ansi16)
+        }
+        .accessible()
+    }
+
+    fn syntax(&self, kind: HighlightKind) -> Option<Highlight> {
+        match kind {
+            HighlightKind::Keyword => self.keyword,
+            HighlightKind::Type => self.type_name,
+            HighlightKind::Function => self.function,
+            HighlightKind::Constant => self.constant,
+        }
+    }
+
+    fn novel(&self, side: Side) -> Highlight {
+        match side {
+            Side::Left => self.novel_lhs,
+            Side::Right => self.novel_rhs,
+        }
+    }
+}
+
+/// Highlighting from tree-sitter highlight queries, for
+/// `--syntax-highlight=full`.
+#[derive(Debug, Default)]
+pub(crate) struct SyntaxHighlights {
+    by_line: DftHashMap<LineNumber, Vec<(SingleLineSpan, HighlightKind)>>,
+}
+
+impl SyntaxHighlights {
+    /// Highlight the lines of `src` that might be displayed for
+    /// `hunks`. If full syntax highlighting isn't enabled, or this
+    /// isn't a supported language, there are no highlights.
+    pub(crate) fn new(
+        src: &str,
+        side: Side,
+        file_format: &FileFormat,
+        hunks: &[Hunk],
+        opposite_to_lhs: &DftHashMap<LineNumber, HashSet<LineNumber>>,
+        opposite_to_rhs: &DftHashMap<LineNumber, HashSet<LineNumber>>,
+        display_options: &DisplayOptions,
+    ) -> Self {
+        let mut by_line: DftHashMap<LineNumber, Vec<(SingleLineSpan, HighlightKind)>> =
+            DftHashMap::default();
+
+        if let FileFormat::SupportedLanguage(language) = file_format {
+            if display_options.use_color && display_options.full_syntax_highlight {
+                let line_ranges: Vec<(usize, usize)> = hunks
+                    .iter()
+                    .filter_map(|hunk| {
+                        let mut context = hunk_context(hunk, &[], &[], display_options);
+                        if display_options.structural_context {
+                            // We don't have the lines of both sides
+                            // here, so assume the largest structural
+                            // context.
+                            context.before = std::cmp::max(
+                                context.before,
+                                2 * display_options.before_context_lines as usize,
+                            );
+                        }
+                        displayed_line_range(
+                            hunk,
+                            side,
+                            opposite_to_lhs,
+                            opposite_to_rhs,
+                            context.before,
+                            context.after,
+                        )
+                    })
+                    .collect();
+
+                for (span, kind) in highlight_captures(src, *language, &line_ranges) {
+                    by_line
+                        .entry(span.line)
+                        .or_insert_with(Vec::new)
+                        .push((span, kind));
+                }
+            }
+        }
+
+        Self { by_line }
+    }
+
+    /// The highlighting of the narrowest capture containing `span`.
+    fn get(&self, span: &SingleLineSpan) -> Option<HighlightKind> {
+        self.by_line
+            .get(&span.line)?
+            .iter()
+            .filter(|(capture, _)| {
+                capture.start_col <= span.start_col && span.end_col <= capture.end_col
+            })
+            .min_by_key(|(capture, _)| capture.end_col - capture.start_col)
+            .map(|(_, kind)| *kind)
+    }
+}
+
 /// Find the largest byte offset in `s` that gives the longest
 /// starting substring whose display width does not exceed `width`.
 ///
@@ -84,7 +615,7 @@ fn width_respecting_tabs(s: &str, tab_width: usize) -> usize {
 /// specify the number of spaces required to pad the part to reach the
 /// desired width.
 ///
-/// ```
+/// ```text
 /// split_string_by_width("fooba", 3) // vec![("foo", 0), ("ba", 1)]
 /// ```
 fn split_string_by_width(s: &str, max_width: usize, tab_width: usize) -> Vec<(&str, usize)> {
@@ -160,11 +691,18 @@ pub(crate) fn split_and_apply(
 
     let mut styled_parts = vec![];
     let mut part_start = 0;
+    let mut first_style = 0;
 
     for (line_part, pad) in split_string_by_width(line, max_len, tab_width) {
+        // Skip the spans that end before this line_part, so wrapping
+        // a long line with many spans isn't quadratic.
+        while first_style < styles.len() && styles[first_style].0.end_col as usize <= part_start {
+            first_style += 1;
+        }
+
         let mut res = String::with_capacity(line_part.len() + pad);
         let mut prev_style_end = 0;
-        for (span, style) in styles {
+        for (span, style) in &styles[first_style..] {
             let start_col = span.start_col as usize;
             let end_col = span.end_col as usize;
 
@@ -297,24 +835,24 @@ fn style_lines(lines: &[&str], styles: &[(SingleLineSpan, Style)]) -> Vec<String
     styled_lines
 }
 
-pub(crate) fn novel_style(style: Style, side: Side, background: BackgroundColor) -> Style {
-    if background.is_dark() {
+pub(crate) fn novel_style(style: Style, side: Side, palette: &Palette) -> Style {
+    let style = palette.novel(side).apply(style);
+    if palette.accessible {
         match side {
-            Side::Left => style.bright_red(),
-            Side::Right => style.bright_green(),
+            Side::Left => style.strikethrough(),
+            Side::Right => style.bold(),
         }
     } else {
-        match side {
-            Side::Left => style.red(),
-            Side::Right => style.green(),
-        }
+        style
     }
 }
 
 pub(crate) fn color_positions(
     side: Side,
-    background: BackgroundColor,
+    palette: &Palette,
     syntax_highlight: bool,
+    dim_punctuation: bool,
+    syntax_highlights: &SyntaxHighlights,
     file_format: &FileFormat,
     positions: &[MatchedPos],
 ) -> Vec<(SingleLineSpan, Style)> {
@@ -327,32 +865,47 @@ pub(crate) fn color_positions(
                     if let TokenKind::Atom(atom_kind) = highlight {
                         match atom_kind {
                             AtomKind::String(StringKind::StringLiteral) => {
-                                style = if background.is_dark() {
-                                    style.bright_magenta()
-                                } else {
-                                    style.magenta()
-                                };
+                                if let Some(string) = palette.string {
+                                    style = string.apply(style);
+                                }
                             }
                             AtomKind::String(StringKind::Text) => {}
                             AtomKind::Comment => {
                                 style = style.italic();
-                                style = if background.is_dark() {
-                                    style.bright_blue()
-                                } else {
-                                    style.blue()
-                                };
+                                if let Some(comment) = palette.comment {
+                                    style = comment.apply(style);
+                                }
                             }
                             AtomKind::Keyword | AtomKind::Type => {
                                 style = style.bold();
+                                if let Some(keyword) = palette.keyword {
+                                    style = keyword.apply(style);
+                                }
                             }
                             AtomKind::TreeSitterError => style = style.purple(),
                             AtomKind::Normal => {}
                         }
+
+                        // Strings and comments are already highlighted.
+                        if matches!(
+                            atom_kind,
+                            AtomKind::Normal | AtomKind::Keyword | AtomKind::Type
+                        ) {
+                            if let Some(highlight) = syntax_highlights
+                                .get(&pos.pos)
+                                .and_then(|kind| palette.syntax(kind))
+                            {
+                                style = highlight.apply(style);
+                            }
+                        }
                     }
                 }
             }
+            MatchKind::Novel { .. } if dim_punctuation && pos.kind.is_novel_punctuation() => {
+                style = novel_style(style, side, palette).dimmed();
+            }
             MatchKind::Novel { highlight, .. } => {
-                style = novel_style(style, side, background);
+                style = novel_style(style, side, palette);
                 if syntax_highlight
                     && matches!(
                         highlight,
@@ -368,7 +921,10 @@ pub(crate) fn color_positions(
                 }
             }
             MatchKind::NovelWord { highlight } => {
-                style = novel_style(style, side, background).bold();
+                style = novel_style(style, side, palette).bold();
+                if let Some(novel_word) = palette.novel_word {
+                    style = novel_word.apply(style);
+                }
 
                 // Underline novel words inside comments in code, but
                 // don't apply it to every single line in plaintext.
@@ -381,7 +937,16 @@ pub(crate) fn color_positions(
                 }
             }
             MatchKind::NovelLinePart { highlight, .. } => {
-                style = novel_style(style, side, background);
+                style = novel_style(style, side, palette);
+                if syntax_highlight && matches!(highlight, TokenKind::Atom(AtomKind::Comment)) {
+                    style = style.italic();
+                }
+            }
+            MatchKind::Moved { highlight, .. } | MatchKind::Copied { highlight, .. } => {
+                // Moved code, and the unedited parts of copied code,
+                // don't need reviewing again, so make them less
+                // prominent than novel code.
+                style = novel_style(style, side, palette).dimmed();
                 if syntax_highlight && matches!(highlight, TokenKind::Atom(AtomKind::Comment)) {
                     style = style.italic();
                 }
@@ -392,32 +957,112 @@ pub(crate) fn color_positions(
     styles
 }
 
+/// Reverse the highlighting of novel whitespace at the end of lines,
+/// so it's shown as a block of the novel color. Otherwise the
+/// highlighting is invisible, and it's not clear why the line
+/// changed. This doesn't change the width of the line.
+///
+/// `styles` must be the result of `color_positions` for `positions`.
+pub(crate) fn highlight_trailing_whitespace(
+    lines: &[&str],
+    positions: &[MatchedPos],
+    styles: Vec<(SingleLineSpan, Style)>,
+    trailing_whitespace: TrailingWhitespace,
+) -> Vec<(SingleLineSpan, Style)> {
+    if trailing_whitespace == TrailingWhitespace::Never {
+        return styles;
+    }
+
+    let is_strictly_novel = |mp: &MatchedPos| {
+        matches!(
+            mp.kind,
+            MatchKind::Novel { .. } | MatchKind::NovelWord { .. }
+        )
+    };
+    let span_text = |span: &SingleLineSpan| {
+        lines
+            .get(span.line.as_usize())
+            .and_then(|line| line.get(span.start_col as usize..span.end_col as usize))
+            .unwrap_or("")
+    };
+
+    // Lines where every novel span is whitespace.
+    let mut whitespace_only: DftHashMap<LineNumber, bool> = DftHashMap::default();
+    for mp in positions.iter().filter(|mp| is_strictly_novel(mp)) {
+        let is_whitespace = span_text(&mp.pos).trim().is_empty();
+        *whitespace_only.entry(mp.pos.line).or_insert(true) &= is_whitespace;
+    }
+
+    let mut res = Vec::with_capacity(styles.len());
+    for (mp, (span, style)) in positions.iter().zip(styles) {
+        let line = lines.get(span.line.as_usize()).copied().unwrap_or("");
+        let trailing_start = line.trim_end().len() as u32;
+
+        let show = is_strictly_novel(mp)
+            && span.end_col > trailing_start
+            && (trailing_whitespace == TrailingWhitespace::Always
+                || whitespace_only.get(&span.line) == Some(&true));
+        if !show {
+            res.push((span, style));
+            continue;
+        }
+
+        if span.start_col < trailing_start {
+            res.push((
+                SingleLineSpan {
+                    end_col: trailing_start,
+                    ..span
+                },
+                style,
+            ));
+        }
+        res.push((
+            SingleLineSpan {
+                start_col: max(span.start_col, trailing_start),
+                ..span
+            },
+            style.reversed(),
+        ));
+    }
+    res
+}
+
 pub(crate) fn apply_colors(
     s: &str,
     side: Side,
     syntax_highlight: bool,
+    dim_punctuation: bool,
+    trailing_whitespace: TrailingWhitespace,
     file_format: &FileFormat,
-    background: BackgroundColor,
+    palette: &Palette,
+    syntax_highlights: &SyntaxHighlights,
     positions: &[MatchedPos],
 ) -> Vec<String> {
-    let styles = color_positions(side, background, syntax_highlight, file_format, positions);
+    let styles = color_positions(
+        side,
+        palette,
+        syntax_highlight,
+        dim_punctuation,
+        syntax_highlights,
+        file_format,
+        positions,
+    );
     let lines = s.lines().collect::<Vec<_>>();
+    let styles = highlight_trailing_whitespace(&lines, positions, styles, trailing_whitespace);
     style_lines(&lines, &styles)
 }
 
-fn apply_header_color(
-    s: &str,
-    use_color: bool,
-    background: BackgroundColor,
-    hunk_num: usize,
-) -> String {
+fn apply_header_color(s: &str, use_color: bool, palette: &Palette, hunk_num: usize) -> String {
     if use_color {
         if hunk_num != 1 {
             s.to_owned()
-        } else if background.is_dark() {
-            s.bright_yellow().to_string()
         } else {
-            s.yellow().to_string()
+            match palette.header {
+                // Only reset the color afterwards, as we did before
+                // themes, rather than every style.
+                Highlight::Color(color) => s.color(color.to_dyn()).to_string(),
+                highlight => s.style(highlight.apply(Style::new())).to_string(),
+            }
         }
         .bold()
         .to_string()
@@ -426,8 +1071,134 @@ fn apply_header_color(
     }
 }
 
+/// A unified diff style range for the lines displayed in a hunk,
+/// e.g. `@@ -120,15 +122,18 @@`, styled like the hunk header. The
+/// ranges are lines in the original files, even if long lines were
+/// split for display.
+pub(crate) fn hunk_range_marker(
+    lines: &[(Option<LineNumber>, Option<LineNumber>)],
+    lhs_line_map: &LineMap,
+    rhs_line_map: &LineMap,
+    hunk_num: usize,
+    display_options: &DisplayOptions,
+) -> String {
+    let range =
+        |side_lines: Vec<LineNumber>| match (side_lines.iter().min(), side_lines.iter().max()) {
+            (Some(first), Some(last)) => {
+                format_range(first.as_usize(), last.as_usize() - first.as_usize() + 1)
+            }
+            _ => format_range(0, 0),
+        };
+
+    let marker = format!(
+        "@@ -{} +{} @@",
+        range(
+            lines
+                .iter()
+                .filter_map(|(lhs, _)| lhs.map(|line| lhs_line_map.source_line(line)))
+                .collect()
+        ),
+        range(
+            lines
+                .iter()
+                .filter_map(|(_, rhs)| rhs.map(|line| rhs_line_map.source_line(line)))
+                .collect()
+        )
+    );
+    apply_header_color(
+        &marker,
+        display_options.use_color,
+        &display_options.palette,
+        hunk_num,
+    )
+}
+
+/// The line shown where part of a long declaration has been omitted
+/// with `--context function`.
+pub(crate) fn elision_marker(display_options: &DisplayOptions) -> String {
+    if display_options.use_color {
+        "...".dimmed().to_string()
+    } else {
+        "...".to_owned()
+    }
+}
+
+/// The line shown after the last line of a file that has no trailing
+/// newline, when the other file does, see
+/// [crate::lines::missing_final_newline]. Side by side, `side` says
+/// which file it is.
+pub(crate) fn no_newline_marker(side: Option<Side>, display_options: &DisplayOptions) -> String {
+    let marker = match side {
+        None => "\\ No newline at end of file",
+        Some(Side::Left) => "\\ No newline at end of old file",
+        Some(Side::Right) => "\\ No newline at end of new file",
+    };
+    if display_options.use_color {
+        marker.dimmed().to_string()
+    } else {
+        marker.to_owned()
+    }
+}
+
+/// The line shown before code that was moved with `--detect-moves`,
+/// e.g. "moved to line 40". `lhs_moves` and `rhs_moves` are the
+/// results of `moved_block_starts`.
+pub(crate) fn move_annotation(
+    lhs_line_num: Option<LineNumber>,
+    rhs_line_num: Option<LineNumber>,
+    lhs_moves: &DftHashMap<LineNumber, LineNumber>,
+    rhs_moves: &DftHashMap<LineNumber, LineNumber>,
+    display_options: &DisplayOptions,
+) -> Option<String> {
+    let mut annotations = vec![];
+    if let Some(opposite_line) = lhs_line_num.and_then(|line_num| lhs_moves.get(&line_num)) {
+        annotations.push(format!("moved to line {}", opposite_line.display()));
+    }
+    if let Some(opposite_line) = rhs_line_num.and_then(|line_num| rhs_moves.get(&line_num)) {
+        annotations.push(format!("moved from line {}", opposite_line.display()));
+    }
+    if annotations.is_empty() {
+        return None;
+    }
+
+    let text = annotations.join(", ");
+    Some(if display_options.use_color {
+        text.dimmed().italic().to_string()
+    } else {
+        text
+    })
+}
+
+/// The line shown before code that was copied with
+/// `--detect-copies`, e.g. "copied from line 12 (2 lines changed)".
+/// `rhs_copies` is the result of `copied_block_starts`.
+pub(crate) fn copy_annotation(
+    rhs_line_num: Option<LineNumber>,
+    rhs_copies: &DftHashMap<LineNumber, (LineNumber, usize)>,
+    display_options: &DisplayOptions,
+) -> Option<String> {
+    let (source_line, num_changed) = rhs_line_num.and_then(|line_num| rhs_copies.get(&line_num))?;
+
+    let text = format!(
+        "copied from line {} ({} line{} changed)",
+        source_line.display(),
+        num_changed,
+        if *num_changed == 1 { "" } else { "s" }
+    );
+    Some(if display_options.use_color {
+        text.dimmed().italic().to_string()
+    } else {
+        text
+    })
+}
+
 /// Style `s` as a warning and write to stderr.
 pub(crate) fn print_warning(s: &str, display_options: &DisplayOptions) {
+    if logging::json_format() {
+        logging::log(Level::Warning, "warning", s, json!({}));
+        return;
+    }
+
     let prefix = if display_options.use_color {
         if display_options.background_color.is_dark() {
             "warning: ".bright_yellow().to_string()
@@ -459,11 +1230,18 @@ pub(crate) fn apply_line_number_color(
             // For changed lines, show the line number as red/green
             // and bold. This works well for syntactic diffs, where
             // most content is not bold.
-            style = novel_style(style, side, display_options.background_color).bold();
+            style = match display_options.palette.novel_line_number {
+                Some(highlight) => highlight.apply(style),
+                None => novel_style(style, side, &display_options.palette),
+            }
+            .bold();
         } else {
             // For unchanged lines, dim the line numbers so it's
             // clearly separate from the content.
-            style = style.dimmed()
+            style = match display_options.palette.line_number {
+                Some(highlight) => highlight.apply(style),
+                None => style.dimmed(),
+            }
         }
 
         s.style(style).to_string()
@@ -472,12 +1250,42 @@ pub(crate) fn apply_line_number_color(
     }
 }
 
+/// The gutter marker for a line, with `--novel-line-marker`. Lines
+/// without changes get padding of the same width, so line numbers
+/// stay aligned.
+pub(crate) fn novel_line_marker(
+    is_novel: bool,
+    side: Side,
+    display_options: &DisplayOptions,
+) -> String {
+    match display_options.novel_line_marker {
+        Some(marker) if is_novel => {
+            let marker = marker.to_string();
+            if display_options.use_color {
+                marker
+                    .style(novel_style(Style::new(), side, &display_options.palette))
+                    .to_string()
+            } else {
+                marker
+            }
+        }
+        Some(marker) => " ".repeat(novel_line_marker_width(Some(marker))),
+        None => "".to_owned(),
+    }
+}
+
+/// The number of columns used by the `--novel-line-marker` gutter.
+pub(crate) fn novel_line_marker_width(marker: Option<char>) -> usize {
+    marker.map_or(0, |marker| marker.width().unwrap_or(1))
+}
+
 pub(crate) fn header(
     display_path: &str,
     extra_info: Option<&String>,
     hunk_num: usize,
     hunk_total: usize,
     file_format: &FileFormat,
+    enclosing_declaration: Option<&str>,
     display_options: &DisplayOptions,
 ) -> String {
     let divider = if hunk_total == 1 {
@@ -489,11 +1297,14 @@ pub(crate) fn header(
     let display_path_pretty = apply_header_color(
         display_path,
         display_options.use_color,
-        display_options.background_color,
+        &display_options.palette,
         hunk_num,
     );
 
     let mut trailer = format!(" --- {}{}", divider, file_format);
+    if let Some(enclosing_declaration) = enclosing_declaration {
+        trailer.push_str(&format!(" --- {}", enclosing_declaration));
+    }
     if display_options.use_color {
         trailer = trailer.dimmed().to_string();
     }
@@ -513,6 +1324,23 @@ pub(crate) fn header(
     }
 }
 
+/// The single line shown for a file that was renamed without
+/// changing its content.
+pub(crate) fn pure_rename_header(header_path: &str, display_options: &DisplayOptions) -> String {
+    let path = apply_header_color(
+        header_path,
+        display_options.use_color,
+        &display_options.palette,
+        1,
+    );
+
+    let mut trailer = " --- renamed, no content changes".to_owned();
+    if display_options.use_color {
+        trailer = trailer.dimmed().to_string();
+    }
+    format!("{}{}", path, trailer)
+}
+
 #[cfg(test)]
 mod tests {
     const TAB_WIDTH: usize = 2;
@@ -646,4 +1474,460 @@ mod tests {
         );
         assert_eq!(res, vec!["foobar", "      "])
     }
+
+    #[test]
+    fn test_xterm_to_rgb() {
+        assert_eq!(xterm_to_rgb(1), (205, 0, 0));
+        assert_eq!(xterm_to_rgb(196), (255, 0, 0));
+        assert_eq!(xterm_to_rgb(232), (8, 8, 8));
+    }
+
+    #[test]
+    fn test_quantize_to_256() {
+        assert_eq!(
+            Color::Rgb(0x00, 0xaf, 0xff).quantize(ColorDepth::Ansi256),
+            Color::Xterm(39)
+        );
+        assert_eq!(
+            Color::Rgb(0x80, 0x80, 0x80).quantize(ColorDepth::Ansi256),
+            Color::Xterm(244)
+        );
+    }
+
+    #[test]
+    fn test_quantize_to_16() {
+        assert_eq!(
+            Color::Rgb(0xdc, 0x32, 0x2f).quantize(ColorDepth::Ansi16),
+            Color::Ansi(AnsiColors::Red)
+        );
+        assert_eq!(
+            Color::Xterm(46).quantize(ColorDepth::Ansi16),
+            Color::Ansi(AnsiColors::BrightGreen)
+        );
+    }
+
+    #[test]
+    fn test_quantize_keeps_supported_colors() {
+        assert_eq!(
+            Color::Rgb(1, 2, 3).quantize(ColorDepth::TrueColor),
+            Color::Rgb(1, 2, 3)
+        );
+        assert_eq!(
+            Color::Xterm(100).quantize(ColorDepth::Ansi256),
+            Color::Xterm(100)
+        );
+    }
+
+    fn colored_line(palette: &Palette, side: Side) -> String {
+        // A novel string literal followed by an unchanged comment.
+        let positions = vec![
+            MatchedPos {
+                kind: MatchKind::Novel {
+                    highlight: TokenKind::Atom(AtomKind::String(StringKind::StringLiteral)),
+                    punctuation: false,
+                },
+                pos: SingleLineSpan {
+                    line: 0.into(),
+                    start_col: 0,
+                    end_col: 3,
+                },
+            },
+            MatchedPos {
+                kind: MatchKind::Ignored {
+                    highlight: TokenKind::Atom(AtomKind::Comment),
+                },
+                pos: SingleLineSpan {
+                    line: 0.into(),
+                    start_col: 4,
+                    end_col: 8,
+                },
+            },
+        ];
+
+        apply_colors(
+            "\"a\" // b",
+            side,
+            true,
+            false,
+            TrailingWhitespace::Never,
+            &FileFormat::PlainText,
+            palette,
+            &SyntaxHighlights::default(),
+            &positions,
+        )
+        .concat()
+    }
+
+    #[test]
+    fn test_theme_solarized_dark() {
+        let palette = Palette::new(Theme::SolarizedDark, ColorDepth::TrueColor);
+        assert_eq!(
+            colored_line(&palette, Side::Right),
+            format!(
+                "{} {}\n",
+                "\"a\"".style(Style::new().color(DynColors::Rgb(0x85, 0x99, 0x00))),
+                "// b".style(
+                    Style::new()
+                        .italic()
+                        .color(DynColors::Rgb(0x58, 0x6e, 0x75))
+                )
+            )
+        );
+    }
+
+    #[test]
+    fn test_theme_solarized_light_differs_from_dark() {
+        let dark = Palette::new(Theme::SolarizedDark, ColorDepth::TrueColor);
+        let light = Palette::new(Theme::SolarizedLight, ColorDepth::TrueColor);
+
+        assert_ne!(
+            apply_header_color("foo.rs", true, &dark, 1),
+            apply_header_color("foo.rs", true, &light, 1)
+        );
+        let line_number_style =
+            |palette: &Palette| palette.line_number.unwrap().apply(Style::new());
+        assert_ne!(line_number_style(&dark), line_number_style(&light));
+    }
+
+    #[test]
+    fn test_default_themes_use_color_depth() {
+        for theme in [Theme::Dark, Theme::Light] {
+            let ansi16 = Palette::new(theme, ColorDepth::Ansi16);
+            let ansi256 = Palette::new(theme, ColorDepth::Ansi256);
+            let true_color = Palette::new(theme, ColorDepth::TrueColor);
+
+            assert!(matches!(ansi16.novel_lhs, Highlight::Color(Color::Ansi(_))));
+            assert!(matches!(
+                ansi256.novel_lhs,
+                Highlight::Color(Color::Xterm(_))
+            ));
+            assert!(matches!(
+                true_color.novel_lhs,
+                Highlight::Color(Color::Rgb(..))
+            ));
+            assert_ne!(
+                colored_line(&ansi16, Side::Right),
+                colored_line(&ansi256, Side::Right)
+            );
+        }
+    }
+
+    #[test]
+    fn test_header_color_default_theme() {
+        let palette = Palette::new(Theme::Dark, ColorDepth::Ansi16);
+        assert_eq!(
+            apply_header_color("foo.rs", true, &palette, 1),
+            "foo.rs"
+                .color(DynColors::Ansi(AnsiColors::BrightYellow))
+                .to_string()
+                .bold()
+                .to_string()
+        );
+    }
+
+    #[test]
+    fn test_theme_monochrome() {
+        let palette = Palette::new(Theme::Monochrome, ColorDepth::TrueColor);
+        let lhs = colored_line(&palette, Side::Left);
+        let rhs = colored_line(&palette, Side::Right);
+
+        assert_eq!(
+            lhs,
+            format!(
+                "{} {}\n",
+                "\"a\"".style(Style::new().reversed()),
+                "// b".style(Style::new().italic())
+            )
+        );
+        assert_eq!(
+            rhs,
+            format!(
+                "{} {}\n",
+                "\"a\"".style(Style::new().underline()),
+                "// b".style(Style::new().italic())
+            )
+        );
+        // No foreground colors.
+        assert!(!lhs.contains("38;") && !rhs.contains("38;"));
+    }
+
+    #[test]
+    fn test_dim_punctuation() {
+        let palette = Palette::new(Theme::Dark, ColorDepth::Ansi16);
+        let positions = vec![MatchedPos {
+            kind: MatchKind::Novel {
+                highlight: TokenKind::Atom(AtomKind::Normal),
+                punctuation: true,
+            },
+            pos: SingleLineSpan {
+                line: 0.into(),
+                start_col: 1,
+                end_col: 2,
+            },
+        }];
+        let colored = |dim_punctuation| {
+            apply_colors(
+                "a,",
+                Side::Right,
+                true,
+                dim_punctuation,
+                TrailingWhitespace::Never,
+                &FileFormat::PlainText,
+                &palette,
+                &SyntaxHighlights::default(),
+                &positions,
+            )
+        };
+
+        assert_eq!(
+            colored(false),
+            vec![format!("a{}\n", ",".style(Style::new().bright_green()))]
+        );
+        assert_eq!(
+            colored(true),
+            vec![format!(
+                "a{}\n",
+                ",".style(Style::new().bright_green().dimmed())
+            )]
+        );
+    }
+
+    #[test]
+    fn test_highlight_trailing_whitespace() {
+        let palette = Palette::new(Theme::Dark, ColorDepth::Ansi16);
+        let novel_word = |start_col, end_col| MatchedPos {
+            kind: MatchKind::NovelWord {
+                highlight: TokenKind::Atom(AtomKind::Normal),
+            },
+            pos: SingleLineSpan {
+                line: 0.into(),
+                start_col,
+                end_col,
+            },
+        };
+        let colored = |src, positions: &[MatchedPos], trailing_whitespace| {
+            apply_colors(
+                src,
+                Side::Right,
+                true,
+                false,
+                trailing_whitespace,
+                &FileFormat::PlainText,
+                &palette,
+                &SyntaxHighlights::default(),
+                positions,
+            )
+        };
+        let novel = Style::new().bright_green().bold();
+
+        // Only whitespace changed, so it's made visible.
+        let positions = vec![novel_word(3, 4), novel_word(4, 5)];
+        assert_eq!(
+            colored("foo  ", &positions, TrailingWhitespace::Auto),
+            vec![format!(
+                "foo{}{}\n",
+                " ".style(novel.reversed()),
+                " ".style(novel.reversed())
+            )]
+        );
+
+        // A word changed too, so whitespace is only made visible
+        // with Always.
+        let positions = vec![novel_word(0, 5)];
+        assert_eq!(
+            colored("bar  ", &positions, TrailingWhitespace::Auto),
+            vec![format!("{}\n", "bar  ".style(novel))]
+        );
+        assert_eq!(
+            colored("bar  ", &positions, TrailingWhitespace::Always),
+            vec![format!(
+                "{}{}\n",
+                "bar".style(novel),
+                "  ".style(novel.reversed())
+            )]
+        );
+    }
+
+    #[test]
+    fn test_novel_line_marker() {
+        let display_options = DisplayOptions {
+            novel_line_marker: Some('▌'),
+            ..DisplayOptions::default()
+        };
+        assert_eq!(novel_line_marker(true, Side::Left, &display_options), "▌");
+        assert_eq!(novel_line_marker(false, Side::Left, &display_options), " ");
+
+        let display_options = DisplayOptions::default();
+        assert_eq!(novel_line_marker(true, Side::Left, &display_options), "");
+    }
+
+    #[test]
+    fn test_accessible() {
+        let palette = Palette::new(Theme::Dark, ColorDepth::Ansi16).accessible();
+
+        assert_eq!(
+            colored_line(&palette, Side::Left),
+            format!(
+                "{} {}\n",
+                "\"a\"".style(Style::new().bright_red().strikethrough()),
+                "// b".style(Style::new().italic().bright_blue())
+            )
+        );
+        assert_eq!(
+            colored_line(&palette, Side::Right),
+            format!(
+                "{} {}\n",
+                "\"a\"".style(Style::new().bright_green().bold()),
+                "// b".style(Style::new().italic().bright_blue())
+            )
+        );
+    }
+
+    #[test]
+    fn test_attributes_only() {
+        let palette = Palette::attributes_only();
+        let lhs = colored_line(&palette, Side::Left);
+
+        // The strikethrough is reset before the unchanged comment.
+        assert_eq!(
+            lhs,
+            format!(
+                "{} {}\n",
+                "\"a\"".style(Style::new().strikethrough()),
+                "// b".style(Style::new().italic())
+            )
+        );
+        assert!(!lhs.contains("38;"));
+    }
+
+    #[test]
+    fn test_header_enclosing_declaration() {
+        let display_options = DisplayOptions {
+            use_color: false,
+            ..DisplayOptions::default()
+        };
+
+        assert_eq!(
+            header(
+                "foo.txt",
+                None,
+                2,
+                3,
+                &FileFormat::PlainText,
+                Some("fn foo"),
+                &display_options
+            ),
+            "foo.txt --- 2/3 --- Text --- fn foo"
+        );
+        assert_eq!(
+            header(
+                "foo.txt",
+                None,
+                1,
+                1,
+                &FileFormat::PlainText,
+                None,
+                &display_options
+            ),
+            "foo.txt --- Text"
+        );
+    }
+
+    #[test]
+    fn test_pure_rename_header() {
+        let display_options = DisplayOptions {
+            use_color: false,
+            ..DisplayOptions::default()
+        };
+
+        assert_eq!(
+            pure_rename_header("old.rs → new.rs", &display_options),
+            "old.rs → new.rs --- renamed, no content changes"
+        );
+    }
+
+    #[test]
+    fn test_move_annotation() {
+        let lhs_moves: DftHashMap<LineNumber, LineNumber> =
+            DftHashMap::from_iter([(2.into(), 39.into())]);
+        let rhs_moves: DftHashMap<LineNumber, LineNumber> =
+            DftHashMap::from_iter([(5.into(), 0.into())]);
+        let display_options = DisplayOptions {
+            use_color: false,
+            ..DisplayOptions::default()
+        };
+
+        assert_eq!(
+            move_annotation(
+                Some(2.into()),
+                Some(5.into()),
+                &lhs_moves,
+                &rhs_moves,
+                &display_options
+            ),
+            Some("moved to line 40, moved from line 1".to_owned())
+        );
+        assert_eq!(
+            move_annotation(
+                Some(3.into()),
+                None,
+                &lhs_moves,
+                &rhs_moves,
+                &display_options
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn test_copy_annotation() {
+        let rhs_copies: DftHashMap<LineNumber, (LineNumber, usize)> =
+            DftHashMap::from_iter([(5.into(), (11.into(), 1))]);
+        let display_options = DisplayOptions {
+            use_color: false,
+            ..DisplayOptions::default()
+        };
+
+        assert_eq!(
+            copy_annotation(Some(5.into()), &rhs_copies, &display_options),
+            Some("copied from line 12 (1 line changed)".to_owned())
+        );
+        assert_eq!(
+            copy_annotation(Some(6.into()), &rhs_copies, &display_options),
+            None
+        );
+    }
+
+    #[test]
+    fn test_hunk_range_marker() {
+        let display_options = DisplayOptions {
+            use_color: false,
+            ..DisplayOptions::default()
+        };
+
+        assert_eq!(
+            hunk_range_marker(
+                &[
+                    (Some(119.into()), Some(121.into())),
+                    (None, Some(122.into())),
+                    (Some(133.into()), Some(139.into())),
+                ],
+                &LineMap::default(),
+                &LineMap::default(),
+                1,
+                &display_options
+            ),
+            "@@ -120,15 +122,19 @@"
+        );
+        assert_eq!(
+            hunk_range_marker(
+                &[(None, Some(0.into()))],
+                &LineMap::default(),
+                &LineMap::default(),
+                1,
+                &display_options
+            ),
+            "@@ -0,0 +1 @@"
+        );
+    }
 }
@@ -0,0 +1,268 @@
+//! A summary of which declarations (functions, classes etc) changed
+//! in each file, for triaging a diff without reading it.
+
+use std::{collections::HashSet, io::Write};
+
+use line_numbers::LineNumber;
+
+use crate::{
+    display::hunks::Hunk,
+    options::DisplayOptions,
+    parse::tree_sitter_parser::{declaration_kinds, declarations, Declaration},
+    summary::{DiffResult, FileContent, FileFormat},
+};
+
+/// Changes that aren't inside any declaration.
+const TOP_LEVEL: &str = "<top level>";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Status {
+    Added,
+    Removed,
+    Modified,
+}
+
+impl Status {
+    fn description(self) -> &'static str {
+        match self {
+            Status::Added => "added",
+            Status::Removed => "removed",
+            Status::Modified => "modified",
+        }
+    }
+}
+
+pub(crate) fn symbol(declaration: &Declaration) -> String {
+    format!("{} {}", declaration.label, declaration.name)
+}
+
+/// The innermost declaration that contains `line`.
+pub(crate) fn enclosing_declaration(
+    declarations: &[Declaration],
+    line: LineNumber,
+) -> Option<&Declaration> {
+    let line = line.as_usize();
+    // Declarations are outermost first, so search from the end to
+    // prefer the inner declaration when two have the same extent.
+    declarations
+        .iter()
+        .rev()
+        .filter(|d| d.start_line <= line && line <= d.end_line)
+        .min_by_key(|d| d.end_line - d.start_line)
+}
+
+fn sorted_lines(lines: &HashSet<LineNumber>) -> Vec<LineNumber> {
+    let mut lines: Vec<LineNumber> = lines.iter().copied().collect();
+    lines.sort();
+    lines
+}
+
+/// The declarations touched by `hunks`, in the order they first
+/// occur, with how they changed.
+fn changed_symbols(
+    hunks: &[Hunk],
+    lhs_declarations: &[Declaration],
+    rhs_declarations: &[Declaration],
+) -> Vec<(String, Status)> {
+    let lhs_symbols: HashSet<String> = lhs_declarations.iter().map(symbol).collect();
+    let rhs_symbols: HashSet<String> = rhs_declarations.iter().map(symbol).collect();
+
+    let mut changes: Vec<(String, Status)> = vec![];
+    let mut add_change = |name: String, status: Status| {
+        if !changes.iter().any(|(existing, _)| *existing == name) {
+            changes.push((name, status));
+        }
+    };
+
+    for hunk in hunks {
+        for line in sorted_lines(&hunk.novel_lhs) {
+            match enclosing_declaration(lhs_declarations, line) {
+                Some(declaration) => {
+                    let name = symbol(declaration);
+                    let status = if rhs_symbols.contains(&name) {
+                        Status::Modified
+                    } else {
+                        Status::Removed
+                    };
+                    add_change(name, status);
+                }
+                None => add_change(TOP_LEVEL.to_owned(), Status::Modified),
+            }
+        }
+        for line in sorted_lines(&hunk.novel_rhs) {
+            match enclosing_declaration(rhs_declarations, line) {
+                Some(declaration) => {
+                    let name = symbol(declaration);
+                    let status = if lhs_symbols.contains(&name) {
+                        Status::Modified
+                    } else {
+                        Status::Added
+                    };
+                    add_change(name, status);
+                }
+                None => add_change(TOP_LEVEL.to_owned(), Status::Modified),
+            }
+        }
+    }
+
+    changes
+}
+
+fn line_range(lines: &HashSet<LineNumber>) -> String {
+    let lines = sorted_lines(lines);
+    match (lines.first(), lines.last()) {
+        (Some(first), Some(last)) if first != last => {
+            format!("lines {}-{}", first.display(), last.display())
+        }
+        (Some(first), _) => format!("line {}", first.display()),
+        _ => unreachable!("Hunks always have novel lines"),
+    }
+}
+
+/// The changed line ranges in `hunks`. Additions and modifications
+/// use RHS line numbers, removals use LHS line numbers.
+fn changed_ranges(hunks: &[Hunk]) -> Vec<(String, Status)> {
+    hunks
+        .iter()
+        .filter_map(
+            |hunk| match (hunk.novel_lhs.is_empty(), hunk.novel_rhs.is_empty()) {
+                (false, false) => Some((line_range(&hunk.novel_rhs), Status::Modified)),
+                (true, false) => Some((line_range(&hunk.novel_rhs), Status::Added)),
+                (false, true) => Some((line_range(&hunk.novel_lhs), Status::Removed)),
+                (true, true) => None,
+            },
+        )
+        .collect()
+}
+
+fn format_changes(changes: &[(String, Status)]) -> String {
+    changes
+        .iter()
+        .map(|(name, status)| format!("{} ({})", name, status.description()))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// A one line summary of the changes in this file, or `None` if the
+/// file shouldn't be shown.
+fn file_summary(diff: &DiffResult, display_options: &DisplayOptions) -> Option<String> {
+    if !diff.has_reportable_change() {
+        if display_options.print_unchanged {
+            return Some(format!("{}: no changes", diff.display_path));
+        }
+        return None;
+    }
+
+    let description = match (&diff.lhs_src, &diff.rhs_src) {
+        (FileContent::Text(_), FileContent::Text(_)) if diff.hunks.is_empty() => {
+            // We know the file changed, e.g. with --check-only, but
+            // we don't know where.
+            "changed".to_owned()
+        }
+        (FileContent::Text(lhs_src), FileContent::Text(rhs_src)) => match diff.file_format {
+            FileFormat::SupportedLanguage(language) if !declaration_kinds(language).is_empty() => {
+                format_changes(&changed_symbols(
+                    &diff.hunks,
+                    &declarations(lhs_src, language),
+                    &declarations(rhs_src, language),
+                ))
+            }
+            _ => format_changes(&changed_ranges(&diff.hunks)),
+        },
+        _ => "binary contents changed".to_owned(),
+    };
+
+    Some(format!("{}: {}", diff.display_path, description))
+}
+
+/// Print the changed declarations in each file.
+pub(crate) fn print(
+    out: &mut dyn Write,
+    diffs: &[DiffResult],
+    display_options: &DisplayOptions,
+) -> std::io::Result<()> {
+    for diff in diffs {
+        if let Some(summary) = file_summary(diff, display_options) {
+            writeln!(out, "{}", summary)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn declaration(
+        label: &'static str,
+        name: &str,
+        start_line: usize,
+        end_line: usize,
+    ) -> Declaration {
+        Declaration {
+            label,
+            name: name.to_owned(),
+            start_line,
+            end_line,
+            start_col: 0,
+            end_col: 0,
+        }
+    }
+
+    fn hunk(novel_lhs: &[u32], novel_rhs: &[u32]) -> Hunk {
+        Hunk {
+            novel_lhs: novel_lhs.iter().map(|n| (*n).into()).collect(),
+            novel_rhs: novel_rhs.iter().map(|n| (*n).into()).collect(),
+            lines: vec![],
+            enclosing_declaration: None,
+        }
+    }
+
+    #[test]
+    fn test_enclosing_declaration_innermost() {
+        let declarations = vec![
+            declaration("impl", "Foo", 0, 10),
+            declaration("fn", "bar", 2, 4),
+        ];
+
+        assert_eq!(
+            enclosing_declaration(&declarations, 3.into()).map(symbol),
+            Some("fn bar".to_owned())
+        );
+        assert_eq!(
+            enclosing_declaration(&declarations, 6.into()).map(symbol),
+            Some("impl Foo".to_owned())
+        );
+        assert_eq!(enclosing_declaration(&declarations, 11.into()), None);
+    }
+
+    #[test]
+    fn test_changed_symbols() {
+        let lhs = vec![
+            declaration("fn", "parse_args", 0, 5),
+            declaration("fn", "old", 7, 8),
+        ];
+        let rhs = vec![
+            declaration("fn", "parse_args", 0, 6),
+            declaration("struct", "Options", 8, 10),
+        ];
+        let hunks = vec![hunk(&[2], &[2, 3]), hunk(&[7, 8], &[9]), hunk(&[], &[12])];
+
+        assert_eq!(
+            format_changes(&changed_symbols(&hunks, &lhs, &rhs)),
+            "fn parse_args (modified), fn old (removed), struct Options (added), <top level> (modified)"
+        );
+    }
+
+    #[test]
+    fn test_changed_ranges() {
+        let hunks = vec![hunk(&[1], &[1, 2, 3]), hunk(&[], &[9]), hunk(&[20], &[])];
+
+        assert_eq!(
+            format_changes(&changed_ranges(&hunks)),
+            "lines 2-4 (modified), line 10 (added), line 21 (removed)"
+        );
+    }
+}
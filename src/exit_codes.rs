@@ -1,3 +1,19 @@
+//! Exit codes follow diff(1): 0 if there are no changes, 1 if there
+//! are changes and >1 for errors. Changes only set the exit code with
+//! `--exit-code` or `--quiet`.
+//!
+//! When diffing directories, any changed, added or removed file sets
+//! the exit code to 1. Errors take precedence, so a file that we
+//! can't read exits with 2 even if other files have changed.
+//!
+//! With `--fail-on-fallback`, files that we couldn't diff
+//! structurally exit with 3 or 4 instead, so CI can notice when a
+//! parser starts failing.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::summary::FileFormat;
+
 /// Successfully ran a diff, found no syntactic changes in text files
 /// or byte changes in binary files.
 pub(crate) const EXIT_SUCCESS: i32 = 0;
@@ -10,3 +26,53 @@ pub(crate) const EXIT_FOUND_CHANGES: i32 = 1;
 /// (e.g. invalid numbers of arguments) or invalid paths (e.g. files
 /// we don't have permission to read).
 pub(crate) const EXIT_BAD_ARGUMENTS: i32 = 2;
+
+/// With `--fail-on-fallback`, at least one file fell back to a text
+/// diff, e.g. because it had too many parse errors or exceeded
+/// DFT_BYTE_LIMIT or DFT_GRAPH_LIMIT.
+pub(crate) const EXIT_TEXT_FALLBACK: i32 = 3;
+
+/// With `--fail-on-fallback`, at least one file was only compared
+/// byte by byte, because it was binary or a directory beyond
+/// `--max-depth`. This takes precedence over [EXIT_TEXT_FALLBACK].
+pub(crate) const EXIT_SKIPPED: i32 = 4;
+
+/// The user pressed Ctrl-C while difftastic was still diffing, like
+/// other programs killed by SIGINT.
+pub(crate) const EXIT_INTERRUPTED: i32 = 130;
+
+/// The output was closed early, e.g. by `difft | head` or quitting
+/// the pager, like other programs killed by SIGPIPE. On Unix we
+/// usually get SIGPIPE itself, see `reset_sigpipe`.
+pub(crate) const EXIT_BROKEN_PIPE: i32 = 141;
+
+static FELL_BACK_TO_TEXT: AtomicBool = AtomicBool::new(false);
+static SKIPPED: AtomicBool = AtomicBool::new(false);
+
+/// Note how a file was diffed, for `--fail-on-fallback`.
+pub(crate) fn record_file_format(file_format: &FileFormat) {
+    match file_format {
+        FileFormat::TextFallback { .. } => FELL_BACK_TO_TEXT.store(true, Ordering::Relaxed),
+        FileFormat::Binary | FileFormat::Directory => SKIPPED.store(true, Ordering::Relaxed),
+        FileFormat::SupportedLanguage(_) | FileFormat::PlainText | FileFormat::Generated => {}
+    }
+}
+
+/// The exit code after a successful diff. `encountered_changes` only
+/// matters with `--exit-code`, and the file formats recorded by
+/// [record_file_format] only matter with `--fail-on-fallback`.
+pub(crate) fn exit_code(
+    set_exit_code: bool,
+    fail_on_fallback: bool,
+    encountered_changes: bool,
+) -> i32 {
+    if fail_on_fallback && SKIPPED.load(Ordering::Relaxed) {
+        EXIT_SKIPPED
+    } else if fail_on_fallback && FELL_BACK_TO_TEXT.load(Ordering::Relaxed) {
+        EXIT_TEXT_FALLBACK
+    } else if set_exit_code && encountered_changes {
+        EXIT_FOUND_CHANGES
+    } else {
+        EXIT_SUCCESS
+    }
+}
@@ -1,5 +1,6 @@
 //! File reading utilities.
 
+use std::hash::{Hash, Hasher};
 use std::io::Read;
 use std::{
     fs,
@@ -8,10 +9,12 @@ use std::{
 };
 
 use ignore::WalkBuilder;
-use rustc_hash::FxHashSet;
+use rustc_hash::{FxHashMap, FxHashSet, FxHasher};
+use serde_json::json;
 
 use crate::exit_codes::EXIT_BAD_ARGUMENTS;
-use crate::options::FileArgument;
+use crate::logging::{self, Level};
+use crate::options::{DiffOptions, FileArgument};
 
 pub(crate) fn read_file_or_die(path: &FileArgument) -> Vec<u8> {
     match read_file_arg(path) {
@@ -105,6 +108,128 @@ pub(crate) fn read_or_die(path: &Path) -> Vec<u8> {
     }
 }
 
+/// Read as much of `buf` as `file` has left, returning the number of
+/// bytes read. Unlike a single `read`, this only stops early at the
+/// end of the file.
+fn read_chunk(file: &mut fs::File, buf: &mut [u8]) -> std::io::Result<usize> {
+    let mut len = 0;
+    while len < buf.len() {
+        match file.read(&mut buf[len..])? {
+            0 => break,
+            n => len += n,
+        }
+    }
+    Ok(len)
+}
+
+/// Whether `lhs_path` and `rhs_path` are regular files with the same
+/// content. This checks the sizes first, then compares the files a
+/// chunk at a time, which is much cheaper than reading and diffing
+/// them. Comparing the chunks costs the same as hashing them, and
+/// can't give false matches.
+pub(crate) fn same_content(lhs_path: &Path, rhs_path: &Path) -> bool {
+    let (Ok(lhs_metadata), Ok(rhs_metadata)) = (fs::metadata(lhs_path), fs::metadata(rhs_path))
+    else {
+        return false;
+    };
+    if !lhs_metadata.is_file()
+        || !rhs_metadata.is_file()
+        || lhs_metadata.len() != rhs_metadata.len()
+    {
+        return false;
+    }
+
+    let (Ok(mut lhs_file), Ok(mut rhs_file)) = (fs::File::open(lhs_path), fs::File::open(rhs_path))
+    else {
+        return false;
+    };
+    let mut lhs_buf = vec![0; 64 * 1024];
+    let mut rhs_buf = vec![0; 64 * 1024];
+    loop {
+        let (Ok(lhs_len), Ok(rhs_len)) = (
+            read_chunk(&mut lhs_file, &mut lhs_buf),
+            read_chunk(&mut rhs_file, &mut rhs_buf),
+        ) else {
+            return false;
+        };
+        if lhs_buf[..lhs_len] != rhs_buf[..rhs_len] {
+            return false;
+        }
+        if lhs_len < lhs_buf.len() {
+            return true;
+        }
+    }
+}
+
+/// Append `s` to `escaped`, escaping control characters so a file
+/// name can't move the cursor or change colours.
+fn push_escaped_str(escaped: &mut String, s: &str) {
+    for c in s.chars() {
+        if c.is_control() {
+            escaped.push_str(&format!("\\x{:02x}", c as u32));
+        } else {
+            escaped.push(c);
+        }
+    }
+}
+
+/// `bytes` as text, with each byte that isn't valid UTF-8 shown as
+/// `\xNN`.
+fn escape_bytes(bytes: &[u8]) -> String {
+    let mut escaped = String::new();
+    let mut rest = bytes;
+    loop {
+        match std::str::from_utf8(rest) {
+            Ok(valid) => {
+                push_escaped_str(&mut escaped, valid);
+                return escaped;
+            }
+            Err(e) => {
+                let (valid, after_valid) = rest.split_at(e.valid_up_to());
+                push_escaped_str(
+                    &mut escaped,
+                    std::str::from_utf8(valid).expect("Valid up to this index"),
+                );
+                let invalid_len = e.error_len().unwrap_or(after_valid.len());
+                for byte in &after_valid[..invalid_len] {
+                    escaped.push_str(&format!("\\x{:02x}", byte));
+                }
+                rest = &after_valid[invalid_len..];
+            }
+        }
+    }
+}
+
+/// `path` as text for display. Paths are arbitrary bytes on Unix, so
+/// unlike [Path::display], bytes that aren't valid UTF-8 are escaped
+/// rather than replaced, and so are control characters.
+pub(crate) fn escape_path(path: &Path) -> String {
+    #[cfg(unix)]
+    {
+        use std::os::unix::ffi::OsStrExt;
+        escape_bytes(path.as_os_str().as_bytes())
+    }
+    #[cfg(not(unix))]
+    {
+        escape_bytes(path.to_string_lossy().as_bytes())
+    }
+}
+
+/// The bytes of `path`, if it isn't valid UTF-8, so it can't be
+/// recovered from [escape_path].
+#[cfg(unix)]
+pub(crate) fn non_utf8_path_bytes(path: &Path) -> Option<Vec<u8>> {
+    use std::os::unix::ffi::OsStrExt;
+    path.to_str()
+        .is_none()
+        .then(|| path.as_os_str().as_bytes().to_vec())
+}
+
+#[cfg(not(unix))]
+pub(crate) fn non_utf8_path_bytes(_path: &Path) -> Option<Vec<u8>> {
+    None
+}
+
 fn has_utf16_byte_order_mark(bytes: &[u8]) -> bool {
     matches!(bytes, [0xfe, 0xff, ..] | [0xff, 0xfe, ..])
 }
@@ -131,6 +256,99 @@ fn u16_from_bytes(bytes: &[u8]) -> Vec<u16> {
         .collect()
 }
 
+/// Decode `bytes` as text, even though [`guess_content`] thinks
+/// they're binary, for `--text`. UTF-16 with a byte order mark is
+/// already detected, so try UTF-16 without one if at least a quarter
+/// of the bytes are NUL, as in mostly-ASCII UTF-16.
+pub(crate) fn force_text(bytes: &[u8]) -> String {
+    let num_nul = bytes.iter().filter(|b| **b == 0).count();
+    if num_nul * 4 >= bytes.len() {
+        if let Ok(utf16_string) = String::from_utf16(&u16_from_bytes(bytes)) {
+            return utf16_string;
+        }
+    }
+    String::from_utf8_lossy(bytes).into_owned()
+}
+
+/// A compression format for single files, which we decompress before
+/// diffing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Compression {
+    Gzip,
+    Zstd,
+    /// Only supported when built with the `xz` feature, as it
+    /// requires liblzma.
+    #[cfg(feature = "xz")]
+    Xz,
+}
+
+impl Compression {
+    /// The compression of `bytes`, from its magic number.
+    fn detect(bytes: &[u8]) -> Option<Self> {
+        match bytes {
+            [0x1f, 0x8b, ..] => Some(Compression::Gzip),
+            [0x28, 0xb5, 0x2f, 0xfd, ..] => Some(Compression::Zstd),
+            #[cfg(feature = "xz")]
+            [0xfd, b'7', b'z', b'X', b'Z', 0x00, ..] => Some(Compression::Xz),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            Compression::Gzip => "gzip",
+            Compression::Zstd => "zstd",
+            #[cfg(feature = "xz")]
+            Compression::Xz => "xz",
+        }
+    }
+}
+
+/// If `bytes` are compressed, the compression and the decompressed
+/// content. Fails if the content is corrupt or expands to more than
+/// `max_len` bytes, so a small file can't exhaust memory.
+pub(crate) fn decompress(
+    bytes: &[u8],
+    max_len: usize,
+) -> Result<Option<(Compression, Vec<u8>)>, String> {
+    let Some(compression) = Compression::detect(bytes) else {
+        return Ok(None);
+    };
+    let decoder: Box<dyn Read + '_> = match compression {
+        // gzip allows several members, which are concatenated, as
+        // produced by `cat a.gz b.gz`.
+        Compression::Gzip => Box::new(flate2::read::MultiGzDecoder::new(bytes)),
+        Compression::Zstd => {
+            Box::new(zstd::Decoder::with_buffer(bytes).map_err(|e| e.to_string())?)
+        }
+        #[cfg(feature = "xz")]
+        Compression::Xz => Box::new(xz2::read::XzDecoder::new(bytes)),
+    };
+
+    let mut decompressed = vec![];
+    decoder
+        .take(max_len as u64 + 1)
+        .read_to_end(&mut decompressed)
+        .map_err(|e| e.to_string())?;
+    if decompressed.len() > max_len {
+        return Err(format!(
+            "it expands to more than DFT_BYTE_LIMIT ({} bytes)",
+            max_len
+        ));
+    }
+    Ok(Some((compression, decompressed)))
+}
+
+/// `path` without its compression extension, such as `foo.json` for
+/// `foo.json.gz`, so we can detect the language of decompressed
+/// files.
+pub(crate) fn without_compression_extension(path: &Path) -> Option<PathBuf> {
+    match path.extension()?.to_str()? {
+        "gz" | "zst" | "xz" => Some(path.with_extension("")),
+        _ => None,
+    }
+}
+
 #[derive(Debug, Eq, PartialEq)]
 pub(crate) enum ProbableFileKind {
     Text(String),
@@ -226,25 +444,333 @@ pub(crate) fn guess_content(bytes: &[u8]) -> ProbableFileKind {
     ProbableFileKind::Binary
 }
 
-/// All the files in `dir`, including subdirectories.
-fn relative_file_paths_in_dir(dir: &Path) -> Vec<PathBuf> {
-    WalkBuilder::new(dir)
-        .hidden(false)
-        .build()
-        .filter_map(Result::ok)
-        .map(|entry| Path::new(entry.path()).to_owned())
-        .filter(|path| !path.is_dir())
-        .map(|path| path.strip_prefix(dir).unwrap().to_path_buf())
-        .collect()
+/// Directories that belong to a version control system, rather than
+/// the project.
+const VCS_DIRS: &[&str] = &[".git", ".hg", ".jj", ".svn"];
+
+/// Identifies a directory, however it was reached, so we can tell
+/// when following a symlink would loop.
+#[cfg(unix)]
+type DirId = (u64, u64);
+#[cfg(not(unix))]
+type DirId = PathBuf;
+
+#[cfg(unix)]
+fn dir_id(path: &Path) -> Option<DirId> {
+    use std::os::unix::fs::MetadataExt;
+    let metadata = fs::metadata(path).ok()?;
+    Some((metadata.dev(), metadata.ino()))
+}
+
+#[cfg(not(unix))]
+fn dir_id(path: &Path) -> Option<DirId> {
+    fs::canonicalize(path).ok()
+}
+
+/// What kind of file `file_type` is, if it's not a regular file,
+/// directory or symlink. Reading a FIFO or a device could block
+/// forever, so directory diffs skip them.
+fn special_file_kind(file_type: fs::FileType) -> Option<&'static str> {
+    if file_type.is_file() || file_type.is_dir() || file_type.is_symlink() {
+        return None;
+    }
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::FileTypeExt;
+        if file_type.is_fifo() {
+            return Some("FIFO");
+        }
+        if file_type.is_socket() {
+            return Some("socket");
+        }
+        if file_type.is_block_device() {
+            return Some("block device");
+        }
+        if file_type.is_char_device() {
+            return Some("character device");
+        }
+    }
+    Some("unknown type")
+}
+
+/// What kind of special file `path` is, see [`special_file_kind`].
+/// With `follow`, this is the kind of the file a symlink points to.
+pub(crate) fn special_file_at(path: &Path, follow: bool) -> Option<&'static str> {
+    let metadata = if follow {
+        fs::metadata(path)
+    } else {
+        fs::symlink_metadata(path)
+    };
+    special_file_kind(metadata.ok()?.file_type())
+}
+
+/// The files found by walking a directory, relative to it.
+#[derive(Debug, Default)]
+struct Listing {
+    files: Vec<PathBuf>,
+    /// Directories at `--max-depth`, which we don't walk.
+    subdirs: Vec<PathBuf>,
+}
+
+/// All the files in `dir`, including subdirectories up to
+/// `max_depth`.
+fn list_dir(dir: &Path, diff_options: &DiffOptions, max_depth: Option<usize>) -> Listing {
+    let mut listing = Listing::default();
+    walk_dir(
+        dir,
+        Path::new(""),
+        diff_options,
+        max_depth,
+        &mut vec![],
+        &mut listing,
+    );
+    listing
+}
+
+/// Add the files in `dir` to `listing`, as `rel_dir` joined with their
+/// path inside `dir`. Symlinks are listed as files, unless
+/// `--follow-symlinks` is set and they point to a directory. Special
+/// files, such as FIFOs, are skipped with a warning.
+/// `ancestors` are the directories we followed symlinks from, which
+/// we mustn't walk again.
+///
+/// With a `max_depth` of 0, only the files directly in `dir` are
+/// listed, and its subdirectories are added to `listing.subdirs`.
+fn walk_dir(
+    dir: &Path,
+    rel_dir: &Path,
+    diff_options: &DiffOptions,
+    max_depth: Option<usize>,
+    ancestors: &mut Vec<DirId>,
+    listing: &mut Listing,
+) {
+    let respect_ignore_files = diff_options.respect_ignore_files;
+    let walk = WalkBuilder::new(dir)
+        .hidden(!diff_options.include_hidden)
+        .ignore(respect_ignore_files)
+        .git_ignore(respect_ignore_files)
+        .git_global(respect_ignore_files)
+        .git_exclude(respect_ignore_files)
+        .parents(respect_ignore_files)
+        // Copies of a working tree, such as the directories from
+        // `git difftool --dir-diff`, don't have a .git directory.
+        .require_git(false)
+        // Walk one level further, so we see the subdirectories that
+        // we're not walking.
+        .max_depth(max_depth.map(|max_depth| max_depth + 1))
+        .filter_entry(|entry| {
+            let name = entry.file_name().to_string_lossy();
+            !VCS_DIRS.contains(&name.as_ref())
+        })
+        .build();
+
+    let pushed_ancestor = match dir_id(dir) {
+        Some(id) => {
+            ancestors.push(id);
+            true
+        }
+        None => false,
+    };
+    for entry in walk.filter_map(Result::ok) {
+        let path = entry.path();
+        let rel_path = rel_dir.join(path.strip_prefix(dir).unwrap());
+        let at_max_depth = max_depth.map_or(false, |max_depth| entry.depth() > max_depth);
+
+        // Without following symlinks, this is the type of the link
+        // itself.
+        let Some(file_type) = entry.file_type() else {
+            continue;
+        };
+        if file_type.is_dir() {
+            if at_max_depth {
+                listing.subdirs.push(rel_path);
+            }
+            continue;
+        }
+
+        if entry.path_is_symlink() && diff_options.follow_symlinks && path.is_dir() {
+            match dir_id(path) {
+                Some(id) if ancestors.contains(&id) => {
+                    logging::log(
+                        Level::Warning,
+                        "symlink_loop",
+                        &format!(
+                            "{}: Not following symlink to a parent directory",
+                            path.display()
+                        ),
+                        json!({ "path": path.display().to_string() }),
+                    );
+                }
+                _ if at_max_depth => listing.subdirs.push(rel_path),
+                // Walk the directory itself, so its entries are
+                // inside the path we're walking.
+                _ => match fs::canonicalize(path) {
+                    Ok(target) => walk_dir(
+                        &target,
+                        &rel_path,
+                        diff_options,
+                        max_depth.map(|max_depth| max_depth - entry.depth()),
+                        ancestors,
+                        listing,
+                    ),
+                    Err(_) => listing.files.push(rel_path),
+                },
+            }
+            continue;
+        }
+
+        let special_kind = if entry.path_is_symlink() {
+            special_file_at(path, diff_options.follow_symlinks)
+        } else {
+            special_file_kind(file_type)
+        };
+        if let Some(kind) = special_kind {
+            logging::log(
+                Level::Warning,
+                "special_file",
+                &format!("{}: Skipping special file ({})", path.display(), kind),
+                json!({ "path": path.display().to_string(), "kind": kind }),
+            );
+            continue;
+        }
+
+        listing.files.push(rel_path);
+    }
+    if pushed_ancestor {
+        ancestors.pop();
+    }
+}
+
+/// A hash of the names and contents of every file in `dir`, so we can
+/// tell whether two subdirectories beyond `--max-depth` differ
+/// without diffing them.
+pub(crate) fn dir_hash(dir: &Path, diff_options: &DiffOptions) -> u64 {
+    let mut files = list_dir(dir, diff_options, None).files;
+    files.sort();
+
+    let mut hasher = FxHasher::default();
+    for rel_path in files {
+        let path = dir.join(&rel_path);
+        let content = match symlink_target(&path, diff_options.follow_symlinks) {
+            Some((target, _)) => format!("symlink → {}", target).into_bytes(),
+            None => fs::read(&path).unwrap_or_default(),
+        };
+
+        rel_path.hash(&mut hasher);
+        hasher.write_usize(content.len());
+        hasher.write(&content);
+    }
+    hasher.finish()
+}
+
+/// If `path` is a symlink that we compare by its target, rather than
+/// the file it points to, the target and whether the symlink is
+/// dangling. Dangling symlinks are compared by their target even with
+/// `--follow-symlinks`.
+pub(crate) fn symlink_target(path: &Path, follow_symlinks: bool) -> Option<(String, bool)> {
+    let target = fs::read_link(path).ok()?;
+    let dangling = !path.exists();
+    if follow_symlinks && !dangling {
+        return None;
+    }
+    Some((escape_path(&target), dangling))
+}
+
+/// If `path` is a symlink, the path of the file it points to.
+/// Otherwise, `path` itself.
+pub(crate) fn resolve_symlink(path: &Path) -> PathBuf {
+    match fs::symlink_metadata(path) {
+        Ok(metadata) if metadata.file_type().is_symlink() => {
+            fs::canonicalize(path).unwrap_or_else(|_| path.to_owned())
+        }
+        _ => path.to_owned(),
+    }
+}
+
+/// The `--include` and `--exclude` globs, which select the files to
+/// diff in directories and argument files.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct PathFilter {
+    pub(crate) include: Vec<glob::Pattern>,
+    pub(crate) exclude: Vec<glob::Pattern>,
+}
+
+impl PathFilter {
+    /// Whether we should diff the file at `rel_path`. Excludes take
+    /// precedence over includes, and with no includes every file is
+    /// included.
+    ///
+    /// As in .gitignore, a glob without a `/` matches the name of the
+    /// file or of any directory containing it, and a glob with a `/`
+    /// matches the path from the root. Globs that match a directory
+    /// apply to everything inside it.
+    pub(crate) fn allows(&self, rel_path: &Path) -> bool {
+        if self.excludes(rel_path) {
+            return false;
+        }
+        self.include.is_empty()
+            || self
+                .include
+                .iter()
+                .any(|pattern| glob_matches_path(pattern, rel_path))
+    }
+
+    /// Whether `rel_path` matches one of the `--exclude` globs.
+    /// Subdirectories beyond `--max-depth` are only filtered by
+    /// excludes, as an include such as `*.rs` wouldn't match their
+    /// name.
+    pub(crate) fn excludes(&self, rel_path: &Path) -> bool {
+        self.exclude
+            .iter()
+            .any(|pattern| glob_matches_path(pattern, rel_path))
+    }
+}
+
+/// Whether `pattern` matches `rel_path`, as described in
+/// [PathFilter::allows].
+pub(crate) fn glob_matches_path(pattern: &glob::Pattern, rel_path: &Path) -> bool {
+    let options = glob::MatchOptions {
+        require_literal_separator: true,
+        ..glob::MatchOptions::new()
+    };
+    let anchored = pattern.as_str().contains('/');
+    let rel_path = rel_path.strip_prefix("./").unwrap_or(rel_path);
+
+    rel_path
+        .ancestors()
+        .filter(|ancestor| !ancestor.as_os_str().is_empty())
+        .any(|ancestor| {
+            if anchored {
+                pattern.matches_path_with(ancestor, options)
+            } else {
+                ancestor.file_name().map_or(false, |name| {
+                    pattern.matches_with(&name.to_string_lossy(), options)
+                })
+            }
+        })
 }
 
 /// Walk `lhs_dir` and `rhs_dir`, and return relative paths of files
-/// that occur in at least one directory.
+/// that occur in at least one directory, followed by the
+/// subdirectories beyond `--max-depth` in either directory.
+///
+/// Each directory is walked with its own ignore files, so a file that
+/// is only ignored on one side is still listed. The caller checks
+/// whether each path exists on both sides, so it's then diffed
+/// against the other side's file rather than shown as added or
+/// removed.
 ///
 /// Attempts to preserve the ordering of files in both directories.
-pub(crate) fn relative_paths_in_either(lhs_dir: &Path, rhs_dir: &Path) -> Vec<PathBuf> {
-    let lhs_paths = relative_file_paths_in_dir(lhs_dir);
-    let rhs_paths = relative_file_paths_in_dir(rhs_dir);
+pub(crate) fn relative_paths_in_either(
+    lhs_dir: &Path,
+    rhs_dir: &Path,
+    diff_options: &DiffOptions,
+) -> (Vec<PathBuf>, Vec<PathBuf>) {
+    let lhs_listing = list_dir(lhs_dir, diff_options, diff_options.max_depth);
+    let rhs_listing = list_dir(rhs_dir, diff_options, diff_options.max_depth);
+    let lhs_paths = lhs_listing.files;
+    let rhs_paths = rhs_listing.files;
 
     let mut seen = FxHashSet::default();
     let mut paths: Vec<PathBuf> = vec![];
@@ -298,13 +824,221 @@ pub(crate) fn relative_paths_in_either(lhs_dir: &Path, rhs_dir: &Path) -> Vec<Pa
             .cloned(),
     );
 
+    let mut subdirs = lhs_listing.subdirs;
+    for subdir in rhs_listing.subdirs {
+        if !subdirs.contains(&subdir) {
+            subdirs.push(subdir);
+        }
+    }
+
+    (paths, subdirs)
+}
+
+/// For `--ignore-path-case`, pair files that only exist on one side
+/// with a file on the other side whose path only differs by case.
+/// Returns the path on each side, in the order of `rel_paths`, which
+/// are from [relative_paths_in_either].
+///
+/// If both casings of a path exist on one side, we warn and pair
+/// those files by their exact paths.
+pub(crate) fn pair_paths_ignoring_case(
+    lhs_dir: &Path,
+    rhs_dir: &Path,
+    rel_paths: Vec<PathBuf>,
+) -> Vec<(PathBuf, PathBuf)> {
+    let exists = |path: PathBuf| fs::symlink_metadata(path).is_ok();
+    let case_key = |rel_path: &Path| rel_path.to_string_lossy().to_lowercase();
+
+    let mut lhs_by_key: FxHashMap<String, Vec<&Path>> = FxHashMap::default();
+    let mut rhs_by_key: FxHashMap<String, Vec<&Path>> = FxHashMap::default();
+    let mut lhs_only = FxHashSet::default();
+    let mut rhs_only = FxHashSet::default();
+    for rel_path in &rel_paths {
+        let in_lhs = exists(lhs_dir.join(rel_path));
+        let in_rhs = exists(rhs_dir.join(rel_path));
+        if in_lhs {
+            lhs_by_key
+                .entry(case_key(rel_path))
+                .or_default()
+                .push(rel_path);
+        }
+        if in_rhs {
+            rhs_by_key
+                .entry(case_key(rel_path))
+                .or_default()
+                .push(rel_path);
+        }
+        match (in_lhs, in_rhs) {
+            (true, false) => lhs_only.insert(rel_path.as_path()),
+            (false, true) => rhs_only.insert(rel_path.as_path()),
+            _ => false,
+        };
+    }
+
+    // Only warn when the conflict stops us pairing a file. On a
+    // case-insensitive file system, every casing exists on both sides.
+    let one_sided_keys: FxHashSet<String> = lhs_only
+        .iter()
+        .chain(rhs_only.iter())
+        .map(|rel_path| case_key(rel_path))
+        .collect();
+    for (dir, by_key) in [(lhs_dir, &lhs_by_key), (rhs_dir, &rhs_by_key)] {
+        for (_, rel_paths) in by_key
+            .iter()
+            .filter(|(key, rel_paths)| rel_paths.len() > 1 && one_sided_keys.contains(*key))
+        {
+            let names: Vec<String> = rel_paths.iter().map(|p| escape_path(p)).collect();
+            logging::log(
+                Level::Warning,
+                "path_case_conflict",
+                &format!(
+                    "{}: {} only differ by case, so they're compared by their exact paths",
+                    dir.display(),
+                    names.join(" and ")
+                ),
+                json!({ "path": dir.display().to_string(), "conflicts": names }),
+            );
+        }
+    }
+
+    // The right-hand path for each left-hand path that only differs
+    // by case.
+    let mut renames: FxHashMap<&Path, &Path> = FxHashMap::default();
+    for (key, lhs_paths) in &lhs_by_key {
+        if let (&[lhs_path], Some(&[rhs_path])) = (
+            lhs_paths.as_slice(),
+            rhs_by_key.get(key).map(|p| p.as_slice()),
+        ) {
+            if lhs_only.contains(lhs_path) && rhs_only.contains(rhs_path) {
+                renames.insert(lhs_path, rhs_path);
+            }
+        }
+    }
+    let renamed_rhs_paths: FxHashSet<&Path> = renames.values().copied().collect();
+
+    rel_paths
+        .iter()
+        .filter(|rel_path| !renamed_rhs_paths.contains(rel_path.as_path()))
+        .map(|rel_path| match renames.get(rel_path.as_path()) {
+            Some(rhs_path) => (rel_path.clone(), rhs_path.to_path_buf()),
+            None => (rel_path.clone(), rel_path.clone()),
+        })
+        .collect()
+}
+
+/// Read the pairs of files listed in an argument file, for
+/// `difft @PATH`.
+///
+/// Each line has two paths separated by a tab. If `null_separated`,
+/// every path is terminated by a NUL instead, and consecutive paths
+/// form a pair. Relative paths are relative to the directory
+/// containing the argument file.
+pub(crate) fn read_pairs_file(
+    path: &Path,
+    null_separated: bool,
+) -> Result<Vec<(PathBuf, PathBuf)>, String> {
+    let src = fs::read_to_string(path)
+        .map_err(|e| format!("Could not read {}: {}", path.display(), e))?;
+    let pairs = if null_separated {
+        parse_null_separated_pairs(&src)
+    } else {
+        parse_tab_separated_pairs(&src)
+    }
+    .map_err(|e| format!("Invalid argument file {}: {}", path.display(), e))?;
+
+    let base_dir = path.parent().unwrap_or_else(|| Path::new(""));
+    Ok(pairs
+        .into_iter()
+        .map(|(lhs, rhs)| (base_dir.join(lhs), base_dir.join(rhs)))
+        .collect())
+}
+
+fn parse_tab_separated_pairs(src: &str) -> Result<Vec<(&str, &str)>, String> {
+    let mut pairs = vec![];
+    for (i, line) in src.lines().enumerate() {
+        if line.is_empty() {
+            continue;
+        }
+
+        match line.split('\t').collect::<Vec<_>>()[..] {
+            [lhs, rhs] if !lhs.is_empty() && !rhs.is_empty() => pairs.push((lhs, rhs)),
+            _ => {
+                return Err(format!(
+                    "line {}: expected two paths separated by a tab, got `{}`",
+                    i + 1,
+                    line
+                ))
+            }
+        }
+    }
+    Ok(pairs)
+}
+
+fn parse_null_separated_pairs(src: &str) -> Result<Vec<(&str, &str)>, String> {
+    let mut paths: Vec<&str> = src.split('\0').collect();
+    // The last path is terminated too, like `find -print0`.
+    if paths.last() == Some(&"") {
+        paths.pop();
+    }
+
     paths
+        .chunks(2)
+        .enumerate()
+        .map(|(i, chunk)| match chunk {
+            [lhs, rhs] if !lhs.is_empty() && !rhs.is_empty() => Ok((*lhs, *rhs)),
+            _ => Err(format!("pair {}: expected two non-empty paths", i + 1)),
+        })
+        .collect()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn path_filter(include: &[&str], exclude: &[&str]) -> PathFilter {
+        let patterns = |globs: &[&str]| {
+            globs
+                .iter()
+                .map(|glob| glob::Pattern::new(glob).unwrap())
+                .collect()
+        };
+        PathFilter {
+            include: patterns(include),
+            exclude: patterns(exclude),
+        }
+    }
+
+    #[test]
+    fn test_path_filter_include() {
+        let filter = path_filter(&["*.rs"], &[]);
+        assert!(filter.allows(Path::new("src/main.rs")));
+        assert!(!filter.allows(Path::new("Cargo.lock")));
+    }
+
+    #[test]
+    fn test_path_filter_exclude_wins() {
+        let filter = path_filter(&["*.rs"], &["vendor/**", "*.lock"]);
+        assert!(!filter.allows(Path::new("vendor/foo/lib.rs")));
+        assert!(!filter.allows(Path::new("Cargo.lock")));
+        assert!(filter.allows(Path::new("src/vendor.rs")));
+    }
+
+    #[test]
+    fn test_path_filter_matches_directory_name() {
+        let filter = path_filter(&[], &["node_modules"]);
+        assert!(!filter.allows(Path::new("web/node_modules/a/index.js")));
+        assert!(filter.allows(Path::new("web/index.js")));
+    }
+
+    #[test]
+    fn test_path_filter_anchored() {
+        // `*` doesn't match `/`, unlike `**`.
+        let filter = path_filter(&["src/*.rs"], &[]);
+        assert!(filter.allows(Path::new("src/main.rs")));
+        assert!(!filter.allows(Path::new("src/parse/mod.rs")));
+        assert!(!filter.allows(Path::new("tests/src/main.rs")));
+    }
+
     #[test]
     fn test_plaintext_is_text() {
         let s = "hello world";
@@ -328,6 +1062,43 @@ mod tests {
         assert_eq!(guess_content(&bytes), ProbableFileKind::Binary);
     }
 
+    #[test]
+    fn test_force_text() {
+        assert_eq!(force_text(b"a\x00b\x00c\x00"), "abc");
+        assert_eq!(force_text(b"abc\xff"), "abc\u{fffd}");
+    }
+
+    #[test]
+    fn test_escape_bytes() {
+        assert_eq!(escape_bytes("café.txt".as_bytes()), "café.txt");
+        assert_eq!(escape_bytes(b"caf\xe9.txt"), "caf\\xe9.txt");
+        assert_eq!(escape_bytes(b"a\x1b[31mb"), "a\\x1b[31mb");
+        assert_eq!(escape_bytes(b"\xf0\x9f"), "\\xf0\\x9f");
+    }
+
+    #[test]
+    fn test_decompress() {
+        let mut encoder = flate2::write::GzEncoder::new(vec![], flate2::Compression::default());
+        std::io::Write::write_all(&mut encoder, b"{\"a\": 1}\n").unwrap();
+        let gzipped = encoder.finish().unwrap();
+
+        assert_eq!(
+            decompress(&gzipped, 100),
+            Ok(Some((Compression::Gzip, b"{\"a\": 1}\n".to_vec())))
+        );
+        assert!(decompress(&gzipped, 4).is_err());
+        assert_eq!(decompress(b"plain text", 100), Ok(None));
+    }
+
+    #[test]
+    fn test_without_compression_extension() {
+        assert_eq!(
+            without_compression_extension(Path::new("logs/config.json.gz")),
+            Some(PathBuf::from("logs/config.json"))
+        );
+        assert_eq!(without_compression_extension(Path::new("main.rs")), None);
+    }
+
     #[test]
     fn test_dex_is_binary() {
         let bytes = vec![
@@ -377,4 +1148,25 @@ mod tests {
         ];
         assert_eq!(guess_content(&bytes), ProbableFileKind::Binary);
     }
+
+    #[test]
+    fn test_parse_tab_separated_pairs() {
+        assert_eq!(
+            parse_tab_separated_pairs("a.rs\tb.rs\n\nc.rs\t/dev/null\r\n").unwrap(),
+            vec![("a.rs", "b.rs"), ("c.rs", "/dev/null")]
+        );
+
+        let err = parse_tab_separated_pairs("a.rs\tb.rs\nc.rs d.rs\n").unwrap_err();
+        assert!(err.starts_with("line 2:"), "{}", err);
+    }
+
+    #[test]
+    fn test_parse_null_separated_pairs() {
+        assert_eq!(
+            parse_null_separated_pairs("a.rs\0b\tc.rs\0").unwrap(),
+            vec![("a.rs", "b\tc.rs")]
+        );
+        assert_eq!(parse_null_separated_pairs("").unwrap(), vec![]);
+        assert!(parse_null_separated_pairs("a.rs\0b.rs\0c.rs\0").is_err());
+    }
 }
@@ -0,0 +1,397 @@
+//! Reconstruct the files in a unified diff, for `--from-patch`.
+//!
+//! A patch only contains the changed lines and their context, so the
+//! lines between hunks are unknown. We fill them with blank lines, so
+//! line numbers still match the original files, and mark the file as
+//! partial.
+
+/// One file in a patch, with the text of both sides as far as the
+/// patch shows it.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub(crate) struct PatchFile {
+    /// The path before the change, or `None` if the file was created.
+    pub(crate) old_path: Option<String>,
+    /// The path after the change, or `None` if the file was deleted.
+    pub(crate) new_path: Option<String>,
+    pub(crate) old_mode: Option<String>,
+    pub(crate) new_mode: Option<String>,
+    pub(crate) lhs_src: String,
+    pub(crate) rhs_src: String,
+    /// Whether some lines of the file weren't in the patch.
+    pub(crate) partial: bool,
+    /// Whether the patch only says that a binary file changed.
+    pub(crate) binary: bool,
+}
+
+impl PatchFile {
+    /// The path to show in headers.
+    pub(crate) fn display_path(&self) -> &str {
+        self.new_path
+            .as_deref()
+            .or(self.old_path.as_deref())
+            .unwrap_or("/dev/null")
+    }
+}
+
+/// The start and length of one side of a hunk header,
+/// e.g. `-12,5`.
+fn parse_range(s: &str) -> Option<(usize, usize)> {
+    match s.split_once(',') {
+        Some((start, len)) => Some((start.parse().ok()?, len.parse().ok()?)),
+        None => Some((s.parse().ok()?, 1)),
+    }
+}
+
+/// Parse `@@ -12,5 +12,6 @@ fn foo()` into the old and new ranges.
+fn parse_hunk_header(line: &str) -> Option<((usize, usize), (usize, usize))> {
+    let rest = line.strip_prefix("@@ -")?;
+    let (ranges, _) = rest.split_once(" @@")?;
+    let (old_range, new_range) = ranges.split_once(" +")?;
+    Some((parse_range(old_range)?, parse_range(new_range)?))
+}
+
+/// The path in a `---` or `+++` line, without the `a/` or `b/`
+/// prefix and any timestamp. `/dev/null` is `None`.
+fn parse_header_path(s: &str, prefix: &str) -> Option<String> {
+    let path = s.split('\t').next().unwrap_or(s).trim_end();
+    if path == "/dev/null" {
+        return None;
+    }
+    Some(path.strip_prefix(prefix).unwrap_or(path).to_owned())
+}
+
+/// One side of a file being reconstructed.
+struct Side {
+    src: String,
+    /// The number of the next line to add, counting from 1.
+    next_line: usize,
+}
+
+impl Side {
+    fn new() -> Self {
+        Self {
+            src: String::new(),
+            next_line: 1,
+        }
+    }
+
+    /// Fill the lines before `line_num` that aren't in the patch.
+    /// Returns whether any lines were missing.
+    fn skip_to(&mut self, line_num: usize) -> bool {
+        if line_num <= self.next_line {
+            return false;
+        }
+        for _ in self.next_line..line_num {
+            self.src.push('\n');
+        }
+        self.next_line = line_num;
+        true
+    }
+
+    fn push_line(&mut self, line: &str) {
+        self.src.push_str(line);
+        self.src.push('\n');
+        self.next_line += 1;
+    }
+
+    fn remove_final_newline(&mut self) {
+        if self.src.ends_with('\n') {
+            self.src.pop();
+        }
+    }
+}
+
+/// A file that we're still reading hunks for.
+struct FileBuilder {
+    file: PatchFile,
+    lhs: Side,
+    rhs: Side,
+    has_hunks: bool,
+}
+
+impl FileBuilder {
+    fn new() -> Self {
+        Self {
+            file: PatchFile::default(),
+            lhs: Side::new(),
+            rhs: Side::new(),
+            has_hunks: false,
+        }
+    }
+
+    fn finish(self) -> PatchFile {
+        PatchFile {
+            lhs_src: self.lhs.src,
+            rhs_src: self.rhs.src,
+            ..self.file
+        }
+    }
+}
+
+/// Parse a patch in unified diff format, as produced by `diff -u` or
+/// `git diff`. Text outside the file headers and hunks, such as commit
+/// messages, is ignored.
+pub(crate) fn parse(src: &str) -> Result<Vec<PatchFile>, String> {
+    let mut files = vec![];
+    let mut current: Option<FileBuilder> = None;
+
+    // Don't use `lines()`, as carriage returns are part of the
+    // content of each line.
+    let mut lines: Vec<&str> = src.split('\n').collect();
+    // The empty string after the final newline isn't a line, and
+    // mustn't be taken for a blank context line.
+    if lines.last() == Some(&"") {
+        lines.pop();
+    }
+    let mut i = 0;
+    while i < lines.len() {
+        let line = lines[i];
+        i += 1;
+
+        if let Some(paths) = line.strip_prefix("diff --git ") {
+            files.extend(current.take().map(FileBuilder::finish));
+            let mut builder = FileBuilder::new();
+            // The `---` and `+++` lines are more reliable, as paths
+            // may contain spaces, but they're missing for renames
+            // without changes and for binary files.
+            if let Some((old_path, new_path)) = paths.split_once(" b/") {
+                builder.file.old_path = Some(old_path.trim_start_matches("a/").to_owned());
+                builder.file.new_path = Some(new_path.to_owned());
+            }
+            current = Some(builder);
+            continue;
+        }
+
+        if let Some(old_path) = line.strip_prefix("--- ") {
+            let Some(new_path) = lines.get(i).and_then(|line| line.strip_prefix("+++ ")) else {
+                continue;
+            };
+            i += 1;
+
+            // A `---` line after hunks starts the next file of a
+            // plain `diff -u` patch.
+            if current.as_ref().map_or(true, |builder| builder.has_hunks) {
+                files.extend(current.take().map(FileBuilder::finish));
+                current = Some(FileBuilder::new());
+            }
+            let builder = current.as_mut().expect("Just set if missing");
+            builder.file.old_path = parse_header_path(old_path, "a/");
+            builder.file.new_path = parse_header_path(new_path, "b/");
+            continue;
+        }
+
+        let Some(builder) = current.as_mut() else {
+            continue;
+        };
+
+        if let Some(((old_start, old_len), (new_start, new_len))) = parse_hunk_header(line) {
+            builder.has_hunks = true;
+            // An empty range starts after the given line.
+            let old_first = if old_len == 0 {
+                old_start + 1
+            } else {
+                old_start
+            };
+            let new_first = if new_len == 0 {
+                new_start + 1
+            } else {
+                new_start
+            };
+            let lhs_skipped = builder.lhs.skip_to(old_first);
+            let rhs_skipped = builder.rhs.skip_to(new_first);
+            if lhs_skipped || rhs_skipped {
+                builder.file.partial = true;
+            }
+
+            let mut old_remaining = old_len;
+            let mut new_remaining = new_len;
+            let mut last_kind = ' ';
+            while i < lines.len()
+                && (old_remaining > 0 || new_remaining > 0 || lines[i].starts_with('\\'))
+            {
+                let hunk_line = lines[i];
+                i += 1;
+
+                // Some tools strip the trailing space from blank
+                // context lines.
+                let (kind, content) = match hunk_line.chars().next() {
+                    Some(kind) => (kind, &hunk_line[kind.len_utf8()..]),
+                    None => (' ', ""),
+                };
+                match kind {
+                    ' ' if old_remaining > 0 && new_remaining > 0 => {
+                        builder.lhs.push_line(content);
+                        builder.rhs.push_line(content);
+                        old_remaining -= 1;
+                        new_remaining -= 1;
+                    }
+                    '-' if old_remaining > 0 => {
+                        builder.lhs.push_line(content);
+                        old_remaining -= 1;
+                    }
+                    '+' if new_remaining > 0 => {
+                        builder.rhs.push_line(content);
+                        new_remaining -= 1;
+                    }
+                    // "\ No newline at end of file" applies to the
+                    // previous line.
+                    '\\' => {
+                        if last_kind != '+' {
+                            builder.lhs.remove_final_newline();
+                        }
+                        if last_kind != '-' {
+                            builder.rhs.remove_final_newline();
+                        }
+                    }
+                    _ => {
+                        return Err(format!(
+                            "line {}: unexpected `{}` in hunk for {}",
+                            i,
+                            hunk_line,
+                            builder.file.display_path()
+                        ));
+                    }
+                }
+                last_kind = kind;
+            }
+
+            if old_remaining > 0 || new_remaining > 0 {
+                return Err(format!(
+                    "hunk for {} ends early",
+                    builder.file.display_path()
+                ));
+            }
+            continue;
+        }
+
+        if builder.has_hunks {
+            continue;
+        }
+        if let Some(mode) = line
+            .strip_prefix("old mode ")
+            .or_else(|| line.strip_prefix("deleted file mode "))
+        {
+            builder.file.old_mode = Some(mode.to_owned());
+        }
+        if let Some(mode) = line
+            .strip_prefix("new mode ")
+            .or_else(|| line.strip_prefix("new file mode "))
+        {
+            builder.file.new_mode = Some(mode.to_owned());
+        }
+        if line.starts_with("new file mode ") {
+            builder.file.old_path = None;
+        }
+        if line.starts_with("deleted file mode ") {
+            builder.file.new_path = None;
+        }
+        if let Some(path) = line.strip_prefix("rename from ") {
+            builder.file.old_path = Some(path.to_owned());
+        }
+        if let Some(path) = line.strip_prefix("rename to ") {
+            builder.file.new_path = Some(path.to_owned());
+        }
+        if line.starts_with("Binary files ") || line == "GIT binary patch" {
+            builder.file.binary = true;
+        }
+    }
+    files.extend(current.take().map(FileBuilder::finish));
+
+    if files.is_empty() {
+        return Err("no files found in the patch".to_owned());
+    }
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_git_diff() {
+        let src = "\
+diff --git a/src/foo.rs b/src/foo.rs
+index 1234567..89abcde 100644
+--- a/src/foo.rs
++++ b/src/foo.rs
+@@ -1,3 +1,3 @@
+ fn foo() {
+-    1
++    2
+ }
+";
+        let files = parse(src).unwrap();
+        assert_eq!(
+            files,
+            vec![PatchFile {
+                old_path: Some("src/foo.rs".to_owned()),
+                new_path: Some("src/foo.rs".to_owned()),
+                lhs_src: "fn foo() {\n    1\n}\n".to_owned(),
+                rhs_src: "fn foo() {\n    2\n}\n".to_owned(),
+                ..PatchFile::default()
+            }]
+        );
+    }
+
+    #[test]
+    fn test_parse_partial() {
+        let src = "\
+--- old.txt\t2024-01-01 00:00:00
++++ new.txt\t2024-01-02 00:00:00
+@@ -3,2 +3,2 @@
+ a
+-b
++c
+";
+        let files = parse(src).unwrap();
+        assert_eq!(files[0].old_path.as_deref(), Some("old.txt"));
+        assert_eq!(files[0].lhs_src, "\n\na\nb\n");
+        assert_eq!(files[0].rhs_src, "\n\na\nc\n");
+        assert!(files[0].partial);
+    }
+
+    #[test]
+    fn test_parse_new_file_without_newline() {
+        let src = "\
+diff --git a/foo.sh b/foo.sh
+new file mode 100755
+--- /dev/null
++++ b/foo.sh
+@@ -0,0 +1,2 @@
++#!/bin/sh
++echo hi
+\\ No newline at end of file
+";
+        let files = parse(src).unwrap();
+        assert_eq!(
+            files,
+            vec![PatchFile {
+                old_path: None,
+                new_path: Some("foo.sh".to_owned()),
+                new_mode: Some("100755".to_owned()),
+                rhs_src: "#!/bin/sh\necho hi".to_owned(),
+                ..PatchFile::default()
+            }]
+        );
+    }
+
+    #[test]
+    fn test_parse_mode_change() {
+        let src = "\
+diff --git a/run.sh b/run.sh
+old mode 100644
+new mode 100755
+";
+        let files = parse(src).unwrap();
+        assert_eq!(files[0].old_mode.as_deref(), Some("100644"));
+        assert_eq!(files[0].new_mode.as_deref(), Some("100755"));
+        assert_eq!(files[0].lhs_src, files[0].rhs_src);
+    }
+
+    #[test]
+    fn test_parse_truncated_hunk() {
+        assert!(parse("--- a/x\n+++ b/x\n@@ -1,2 +1,2 @@\n a\n").is_err());
+    }
+}
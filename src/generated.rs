@@ -0,0 +1,65 @@
+//! Detect generated files from the markers that code generators
+//! write in their headers, see `--collapse-generated`.
+
+use regex::Regex;
+
+/// The markers used when `--generated-marker` isn't specified.
+pub(crate) const DEFAULT_GENERATED_MARKERS: &[&str] = &[
+    // The Go convention, also used by protoc-gen-go.
+    // https://pkg.go.dev/cmd/go#hdr-Generate_Go_files_by_processing_source
+    r"^// Code generated .* DO NOT EDIT\.$",
+    // The protobuf compiler for C++, Java, Python and others.
+    r"Generated by the protocol buffer compiler\.",
+    // Used by Facebook tools, Cargo.lock and others.
+    r"@generated",
+];
+
+/// Only look for markers in this many lines at the start of a file.
+const MARKER_LINES: usize = 5;
+
+pub(crate) fn parse_generated_markers(markers: &[&str]) -> Result<Vec<Regex>, regex::Error> {
+    markers.iter().map(|marker| Regex::new(marker)).collect()
+}
+
+/// Does one of the first few lines of `src` match one of `markers`?
+pub(crate) fn is_generated(src: &str, markers: &[Regex]) -> bool {
+    src.lines()
+        .take(MARKER_LINES)
+        .any(|line| markers.iter().any(|marker| marker.is_match(line)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn default_markers() -> Vec<Regex> {
+        parse_generated_markers(DEFAULT_GENERATED_MARKERS).unwrap()
+    }
+
+    #[test]
+    fn test_go_generated() {
+        let src = "// Code generated by protoc-gen-go. DO NOT EDIT.\n// source: foo.proto\n\npackage foo\n";
+        assert!(is_generated(src, &default_markers()));
+    }
+
+    #[test]
+    fn test_at_generated() {
+        let src = "# This file is automatically @generated by Cargo.\n[[package]]\n";
+        assert!(is_generated(src, &default_markers()));
+    }
+
+    #[test]
+    fn test_marker_after_header() {
+        let src = format!(
+            "{}// Code generated by foo. DO NOT EDIT.\n",
+            "\n".repeat(10)
+        );
+        assert!(!is_generated(&src, &default_markers()));
+    }
+
+    #[test]
+    fn test_not_generated() {
+        let src = "// This code is not generated.\nfn main() {}\n";
+        assert!(!is_generated(src, &default_markers()));
+    }
+}
@@ -0,0 +1,215 @@
+//! Compare files between git revisions without configuring git,
+//! `difft --git REV1 [REV2] -- PATH...`, or between the index and
+//! HEAD or the working tree with `--git-staged` and `--git-worktree`.
+//!
+//! We run the `git` command for everything, so errors show git's own
+//! message.
+
+use std::{
+    fmt::Display,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+/// A version of the files in a repository.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Source {
+    Revision(String),
+    /// The staged files.
+    Index,
+    Worktree,
+}
+
+impl Display for Source {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Source::Revision(rev) => write!(f, "{}", rev),
+            Source::Index => write!(f, "index"),
+            Source::Worktree => write!(f, "worktree"),
+        }
+    }
+}
+
+/// Run git with `args`, returning stdout, or git's error message.
+pub(crate) fn run_git(args: &[&str]) -> Result<Vec<u8>, String> {
+    let output = Command::new("git")
+        .args(args)
+        .output()
+        .map_err(|e| format!("Could not run git: {}", e))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(stderr.trim().to_owned());
+    }
+    Ok(output.stdout)
+}
+
+/// The root of the repository containing the current directory, or
+/// git's error if we're not in a repository.
+pub(crate) fn toplevel() -> Result<PathBuf, String> {
+    let stdout = run_git(&["rev-parse", "--show-toplevel"])?;
+    Ok(PathBuf::from(String::from_utf8_lossy(&stdout).trim_end()))
+}
+
+/// Check that `rev` is a revision that git knows about. `git log`
+/// gives a clearer error than `git rev-parse`, e.g. "bad revision".
+fn check_revision(rev: &str) -> Result<(), String> {
+    run_git(&["log", "-1", "--format=%H", rev, "--"]).map(|_| ())
+}
+
+/// Check that `path` exists in `source`.
+fn check_path(source: &Source, path: &Path) -> Result<(), String> {
+    match source {
+        // `REV:./PATH` is relative to the current directory, like
+        // other git paths.
+        Source::Revision(rev) => {
+            let object = format!("{}:./{}", rev, path.display());
+            run_git(&["rev-parse", &object]).map(|_| ())
+        }
+        // This also accepts directories, unlike `:./PATH`.
+        Source::Index => {
+            let path = path.display().to_string();
+            run_git(&["ls-files", "--error-unmatch", "--", &path]).map(|_| ())
+        }
+        Source::Worktree if path.exists() => Ok(()),
+        Source::Worktree => Err(format!(
+            "path '{}' does not exist in the working tree",
+            path.display()
+        )),
+    }
+}
+
+/// Whether a file is in each revision, from `git diff --name-status`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Status {
+    Added,
+    Deleted,
+    Modified,
+}
+
+/// A file that differs between the two sources.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) struct ChangedFile {
+    /// The path relative to the root of the repository.
+    pub(crate) path: String,
+    /// The previous path, if git detected a rename.
+    pub(crate) old_path: Option<String>,
+    pub(crate) status: Status,
+}
+
+/// Parse the output of `git diff --name-status -z`, which is a NUL
+/// after each status letter and each path. Renames and copies, such
+/// as `R087`, have the old path and then the new path.
+fn parse_name_status(stdout: &str) -> Vec<ChangedFile> {
+    let mut changed_files = vec![];
+    let mut fields = stdout.split('\0').filter(|field| !field.is_empty());
+    while let (Some(status), Some(path)) = (fields.next(), fields.next()) {
+        let (old_path, path) = if status.starts_with('R') || status.starts_with('C') {
+            match fields.next() {
+                Some(new_path) => (Some(path.to_owned()), new_path),
+                None => break,
+            }
+        } else {
+            (None, path)
+        };
+        let status = match status {
+            "A" => Status::Added,
+            "D" => Status::Deleted,
+            _ => Status::Modified,
+        };
+        changed_files.push(ChangedFile {
+            path: path.to_owned(),
+            old_path,
+            status,
+        });
+    }
+    changed_files
+}
+
+/// The arguments to `git diff` that compare `lhs` with `rhs`. Only
+/// the combinations that difftastic's options produce are supported.
+fn diff_source_args(lhs: &Source, rhs: &Source) -> Vec<String> {
+    match (lhs, rhs) {
+        (Source::Revision(lhs_rev), Source::Revision(rhs_rev)) => {
+            vec![lhs_rev.clone(), rhs_rev.clone()]
+        }
+        (Source::Revision(rev), Source::Worktree) => vec![rev.clone()],
+        (Source::Revision(rev), Source::Index) => vec!["--cached".to_owned(), rev.clone()],
+        (Source::Index, Source::Worktree) => vec![],
+        _ => unreachable!("Can't compare {} with {}", lhs, rhs),
+    }
+}
+
+/// The files under `paths` that differ between `lhs` and `rhs`. Every
+/// path must exist in at least one of them.
+pub(crate) fn changed_files(
+    lhs: &Source,
+    rhs: &Source,
+    paths: &[PathBuf],
+) -> Result<Vec<ChangedFile>, String> {
+    for source in [lhs, rhs] {
+        if let Source::Revision(rev) = source {
+            check_revision(rev)?;
+        }
+    }
+    for path in paths {
+        if let Err(e) = check_path(lhs, path) {
+            // A path that was added or deleted only exists on one
+            // side.
+            check_path(rhs, path).map_err(|_| e)?;
+        }
+    }
+
+    let source_args = diff_source_args(lhs, rhs);
+    let mut args = vec!["diff", "--name-status", "-z", "-M"];
+    args.extend(source_args.iter().map(|arg| arg.as_str()));
+    args.push("--");
+    let path_args: Vec<String> = paths.iter().map(|p| p.display().to_string()).collect();
+    args.extend(path_args.iter().map(|p| p.as_str()));
+
+    let stdout = run_git(&args)?;
+    Ok(parse_name_status(&String::from_utf8_lossy(&stdout)))
+}
+
+/// The content of `path`, relative to the root of the repository, in
+/// `rev`. An empty `rev` reads the index.
+pub(crate) fn read_blob(rev: &str, path: &str) -> Result<Vec<u8>, String> {
+    run_git(&["cat-file", "blob", &format!("{}:{}", rev, path)])
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_name_status() {
+        assert_eq!(
+            parse_name_status(
+                "M\0src/main.rs\0A\0new file.txt\0D\0old.txt\0R087\0foo.rs\0bar.rs\0"
+            ),
+            vec![
+                ChangedFile {
+                    path: "src/main.rs".to_owned(),
+                    old_path: None,
+                    status: Status::Modified,
+                },
+                ChangedFile {
+                    path: "new file.txt".to_owned(),
+                    old_path: None,
+                    status: Status::Added,
+                },
+                ChangedFile {
+                    path: "old.txt".to_owned(),
+                    old_path: None,
+                    status: Status::Deleted,
+                },
+                ChangedFile {
+                    path: "bar.rs".to_owned(),
+                    old_path: Some("foo.rs".to_owned()),
+                    status: Status::Modified,
+                },
+            ]
+        );
+    }
+}
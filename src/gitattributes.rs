@@ -0,0 +1,169 @@
+//! Language hints from `.gitattributes`.
+//!
+//! Repositories can set `linguist-language` to tell GitHub how to
+//! highlight a file, and `diff` to pick one of git's built-in diff
+//! drivers, such as `diff=python`. We use either of these to detect
+//! the language, unless the user passed `--override`.
+//!
+//! The lookup is best-effort: outside a repository, or without git
+//! installed, every path simply has no hint.
+
+use std::{
+    process::{Command, Stdio},
+    sync::Mutex,
+};
+
+use lazy_static::lazy_static;
+
+use crate::{
+    hash::DftHashMap,
+    parse::guess_language::{language_from_attribute, Language},
+};
+
+lazy_static! {
+    /// The language hint for each path we've asked git about.
+    static ref LANGUAGES: Mutex<DftHashMap<String, Option<Language>>> =
+        Mutex::new(DftHashMap::default());
+}
+
+/// Parse the output of `git check-attr -z`, which is
+/// `PATH\0ATTR\0VALUE\0` for each path and attribute. `linguist-language`
+/// takes precedence over `diff`.
+fn parse_check_attr(stdout: &str) -> DftHashMap<String, Option<Language>> {
+    let mut linguist: DftHashMap<String, Option<Language>> = DftHashMap::default();
+    let mut diff: DftHashMap<String, Option<Language>> = DftHashMap::default();
+
+    let fields: Vec<&str> = stdout.split('\0').collect();
+    for chunk in fields.chunks_exact(3) {
+        let (path, attr, value) = (chunk[0], chunk[1], chunk[2]);
+        let language = match value {
+            "set" | "unset" | "unspecified" => None,
+            value => language_from_attribute(value),
+        };
+        match attr {
+            "linguist-language" => linguist.insert(path.to_owned(), language),
+            _ => diff.insert(path.to_owned(), language),
+        };
+    }
+
+    for (path, language) in diff {
+        let entry = linguist.entry(path).or_insert(None);
+        if entry.is_none() {
+            *entry = language;
+        }
+    }
+    linguist
+}
+
+/// The most bytes of paths to pass to one `git check-attr`, so the
+/// command line stays well under the limit on Windows.
+const MAX_PATHS_LEN: usize = 16 * 1024;
+
+/// Split `paths` into batches with at most [MAX_PATHS_LEN] bytes of
+/// paths each, unless a single path is longer.
+fn batches<'a, 'b>(mut paths: &'a [&'b str]) -> Vec<&'a [&'b str]> {
+    let mut batches = vec![];
+    while !paths.is_empty() {
+        let mut len = 0;
+        let mut size = 0;
+        for path in paths {
+            if size > 0 && len + path.len() > MAX_PATHS_LEN {
+                break;
+            }
+            len += path.len() + 1;
+            size += 1;
+        }
+        let (batch, rest) = paths.split_at(size);
+        batches.push(batch);
+        paths = rest;
+    }
+    batches
+}
+
+/// Run `git check-attr` for all of `paths`, in batches so each
+/// command line is short enough.
+///
+/// We pass the paths as arguments rather than with `--stdin`, as git
+/// exits without reading stdin outside a repository, and writing to
+/// the closed pipe would kill us with SIGPIPE.
+fn check_attr(paths: &[&str]) -> Option<DftHashMap<String, Option<Language>>> {
+    let mut languages = DftHashMap::default();
+    for batch in batches(paths) {
+        let output = Command::new("git")
+            .args(["check-attr", "-z", "linguist-language", "diff", "--"])
+            .args(batch)
+            .stdin(Stdio::null())
+            .stderr(Stdio::null())
+            .output()
+            .ok()?;
+        if !output.status.success() {
+            return None;
+        }
+        languages.extend(parse_check_attr(&String::from_utf8_lossy(&output.stdout)));
+    }
+    Some(languages)
+}
+
+/// Look up the language hints for `paths` with as few git commands
+/// as possible, so later calls to [`language`] don't need to run git.
+pub(crate) fn load(paths: &[&str]) {
+    if paths.is_empty() {
+        return;
+    }
+    let found = check_attr(paths).unwrap_or_default();
+
+    let mut languages = LANGUAGES.lock().expect("No other thread should panic");
+    for path in paths {
+        let language = found.get(*path).copied().flatten();
+        languages.insert((*path).to_owned(), language);
+    }
+}
+
+/// The language that `.gitattributes` sets for `path`, relative to
+/// the current directory.
+pub(crate) fn language(path: &str) -> Option<Language> {
+    let cached = LANGUAGES
+        .lock()
+        .expect("No other thread should panic")
+        .get(path)
+        .copied();
+    match cached {
+        Some(language) => language,
+        None => {
+            load(&[path]);
+            language(path)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_check_attr() {
+        let languages = parse_check_attr(
+            "a.inc\0linguist-language\0PHP\0a.inc\0diff\0unspecified\0\
+             b.h\0linguist-language\0unspecified\0b.h\0diff\0cpp\0\
+             c.txt\0linguist-language\0unspecified\0c.txt\0diff\0unset\0",
+        );
+        assert_eq!(languages.get("a.inc"), Some(&Some(Language::Php)));
+        assert_eq!(languages.get("b.h"), Some(&Some(Language::CPlusPlus)));
+        assert_eq!(languages.get("c.txt"), Some(&None));
+    }
+
+    #[test]
+    fn test_batches() {
+        assert_eq!(batches(&[]), Vec::<&[&str]>::new());
+        assert_eq!(batches(&["a", "b"]), vec![&["a", "b"][..]]);
+
+        let long_path = "x".repeat(MAX_PATHS_LEN);
+        let paths = [long_path.as_str(), long_path.as_str(), "a", "b"];
+        assert_eq!(
+            batches(&paths),
+            vec![&paths[..1], &paths[1..2], &paths[2..]]
+        );
+    }
+}
@@ -1,4 +1,4 @@
-use std::hash::BuildHasherDefault;
+use std::hash::{BuildHasherDefault, Hasher};
 
 use rustc_hash::FxHasher;
 
@@ -10,3 +10,12 @@ use rustc_hash::FxHasher;
 /// little faster, and it also allows us to use the entry_ref API
 /// which is unavailable in stable Rust.
 pub(crate) type DftHashMap<K, V> = hashbrown::HashMap<K, V, BuildHasherDefault<FxHasher>>;
+
+/// A hash of the content of a file, so we can show whether two binary
+/// files differ. This isn't collision resistant, so it's only for
+/// display.
+pub(crate) fn content_hash(bytes: &[u8]) -> u64 {
+    let mut hasher = FxHasher::default();
+    hasher.write(bytes);
+    hasher.finish()
+}
@@ -0,0 +1,152 @@
+//! Support for Mercurial's extdiff extension, see `--hg`.
+//!
+//! extdiff copies each revision into a snapshot directory,
+//! "$TMPDIR/extdiff.AbCdEf/REPO.CHANGESET", and passes difftastic
+//! either the two snapshot directories or, when only one file changed,
+//! the file inside each snapshot. The working directory isn't copied
+//! when a single file changed, so the second path is then in the
+//! repository itself.
+
+use std::{
+    env,
+    path::{Path, PathBuf},
+};
+
+/// A path passed by extdiff.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) struct ExtdiffPath {
+    /// The changeset of the snapshot, or `None` for the working
+    /// directory.
+    pub(crate) changeset: Option<String>,
+    /// The path relative to the root of the repository. This is
+    /// empty when extdiff passed a whole snapshot directory.
+    pub(crate) rel_path: PathBuf,
+}
+
+/// The changeset in a snapshot directory name, such as
+/// "difftastic.1a2b3c4d5e6f". Snapshots of the working directory
+/// don't have a changeset.
+fn snapshot_changeset(snapshot_name: &str) -> Option<String> {
+    let (_, changeset) = snapshot_name.rsplit_once('.')?;
+    if changeset.len() >= 12 && changeset.chars().all(|c| c.is_ascii_hexdigit()) {
+        Some(changeset.to_owned())
+    } else {
+        None
+    }
+}
+
+/// If `path` is inside an extdiff snapshot in `tmp_dir`, the
+/// changeset of the snapshot and the path inside it.
+fn snapshot_path(path: &Path, tmp_dir: &Path) -> Option<ExtdiffPath> {
+    let rel_path = path.strip_prefix(tmp_dir).ok()?;
+    let mut components = rel_path.components();
+
+    let tmp_name = components.next()?.as_os_str().to_string_lossy();
+    if !tmp_name.starts_with("extdiff.") {
+        return None;
+    }
+    let snapshot_name = components.next()?.as_os_str().to_string_lossy();
+
+    Some(ExtdiffPath {
+        changeset: snapshot_changeset(&snapshot_name),
+        rel_path: components.as_path().to_owned(),
+    })
+}
+
+/// Does `path` look like a file or directory in an extdiff snapshot?
+pub(crate) fn is_snapshot_path(path: &Path) -> bool {
+    snapshot_path(path, &env::temp_dir()).is_some()
+}
+
+/// `path` relative to the root of the Mercurial repository that
+/// contains it, or `path` itself if it isn't in a repository.
+fn repo_relative(path: &Path) -> PathBuf {
+    let abs_path = path.canonicalize().unwrap_or_else(|_| path.to_owned());
+    for root in abs_path.ancestors().skip(1) {
+        if root.join(".hg").is_dir() {
+            if let Ok(rel_path) = abs_path.strip_prefix(root) {
+                return rel_path.to_owned();
+            }
+        }
+    }
+    path.to_owned()
+}
+
+/// Interpret a path passed by extdiff. Paths outside a snapshot are
+/// in the working directory.
+pub(crate) fn extdiff_path(path: &Path) -> ExtdiffPath {
+    match snapshot_path(path, &env::temp_dir()) {
+        Some(extdiff_path) => extdiff_path,
+        None => ExtdiffPath {
+            changeset: None,
+            rel_path: repo_relative(path),
+        },
+    }
+}
+
+/// A description of the two changesets being compared, shown in the
+/// header of each file. `HG_PARENT1` and `HG_NODE` are used when the
+/// changesets aren't in the snapshot names, as in hooks.
+pub(crate) fn changesets_info(lhs_changeset: Option<&str>, rhs_changeset: Option<&str>) -> String {
+    let lhs_env = env::var("HG_PARENT1").ok();
+    let rhs_env = env::var("HG_NODE").ok();
+    format_changesets(
+        lhs_changeset.or(lhs_env.as_deref()),
+        rhs_changeset.or(rhs_env.as_deref()),
+    )
+}
+
+fn format_changesets(lhs_changeset: Option<&str>, rhs_changeset: Option<&str>) -> String {
+    let describe = |changeset: Option<&str>| match changeset {
+        Some(changeset) => format!("changeset {}", changeset),
+        None => "working directory".to_owned(),
+    };
+    format!(
+        "Comparing {} to {}.",
+        describe(lhs_changeset),
+        describe(rhs_changeset)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_snapshot_path() {
+        assert_eq!(
+            snapshot_path(
+                Path::new("/tmp/extdiff.AbCdEf/difftastic.1a2b3c4d5e6f/src/main.rs"),
+                Path::new("/tmp")
+            ),
+            Some(ExtdiffPath {
+                changeset: Some("1a2b3c4d5e6f".to_owned()),
+                rel_path: PathBuf::from("src/main.rs"),
+            })
+        );
+        assert_eq!(
+            snapshot_path(
+                Path::new("/tmp/extdiff.AbCdEf/my.project"),
+                Path::new("/tmp")
+            ),
+            Some(ExtdiffPath {
+                changeset: None,
+                rel_path: PathBuf::new(),
+            })
+        );
+        assert_eq!(
+            snapshot_path(Path::new("/tmp/git-blob-abc/main.rs"), Path::new("/tmp")),
+            None
+        );
+    }
+
+    #[test]
+    fn test_format_changesets() {
+        assert_eq!(
+            format_changesets(Some("1a2b3c4d5e6f"), None),
+            "Comparing changeset 1a2b3c4d5e6f to working directory."
+        );
+    }
+}
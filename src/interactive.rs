@@ -0,0 +1,244 @@
+//! Show the diff one hunk at a time with `--interactive`, in the
+//! terminal's alternate screen.
+
+use std::io::{self, Write};
+
+use crossterm::{
+    cursor,
+    event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
+    execute, queue,
+    terminal::{self, ClearType, EnterAlternateScreen, LeaveAlternateScreen},
+};
+
+/// The rendered hunks of a file. Files without hunks, such as binary
+/// files, have a single page.
+pub(crate) struct FilePages {
+    pub(crate) display_path: String,
+    pub(crate) pages: Vec<Vec<u8>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Command {
+    NextHunk,
+    PreviousHunk,
+    NextFile,
+    PreviousFile,
+    Quit,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Position {
+    file: usize,
+    page: usize,
+}
+
+fn key_command(key: KeyEvent) -> Option<Command> {
+    if key.kind == KeyEventKind::Release {
+        return None;
+    }
+
+    match key.code {
+        // Raw mode doesn't turn Ctrl-C into SIGINT.
+        KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => Some(Command::Quit),
+        KeyCode::Char('n') | KeyCode::Down | KeyCode::Char(' ') => Some(Command::NextHunk),
+        KeyCode::Char('p') | KeyCode::Up => Some(Command::PreviousHunk),
+        KeyCode::Char('f') | KeyCode::Right => Some(Command::NextFile),
+        KeyCode::Char('b') | KeyCode::Left => Some(Command::PreviousFile),
+        KeyCode::Char('q') | KeyCode::Esc => Some(Command::Quit),
+        _ => None,
+    }
+}
+
+/// Where to move to after `command`, given the number of pages in
+/// each file. Moving past the first or last hunk moves to the
+/// neighbouring file. Returns `None` to quit.
+fn next_position(num_pages: &[usize], pos: Position, command: Command) -> Option<Position> {
+    let last_file = num_pages.len() - 1;
+    let new_pos = match command {
+        Command::NextHunk if pos.page + 1 < num_pages[pos.file] => Position {
+            file: pos.file,
+            page: pos.page + 1,
+        },
+        Command::NextHunk | Command::NextFile if pos.file < last_file => Position {
+            file: pos.file + 1,
+            page: 0,
+        },
+        Command::PreviousHunk if pos.page > 0 => Position {
+            file: pos.file,
+            page: pos.page - 1,
+        },
+        Command::PreviousHunk if pos.file > 0 => Position {
+            file: pos.file - 1,
+            page: num_pages[pos.file - 1] - 1,
+        },
+        Command::PreviousFile if pos.file > 0 => Position {
+            file: pos.file - 1,
+            page: 0,
+        },
+        Command::Quit => return None,
+        // Already at the first or last hunk.
+        _ => pos,
+    };
+    Some(new_pos)
+}
+
+fn format_status(files: &[FilePages], pos: Position) -> String {
+    let file = &files[pos.file];
+    let mut status = format!("{} ", file.display_path);
+    if files.len() > 1 {
+        status.push_str(&format!("(file {}/{}) ", pos.file + 1, files.len()));
+    }
+    if file.pages.len() > 1 {
+        status.push_str(&format!("hunk {}/{} ", pos.page + 1, file.pages.len()));
+    }
+    status.push_str("-- n/p: hunk, f/b: file, q: quit");
+    status
+}
+
+fn draw(out: &mut impl Write, files: &[FilePages], pos: Position) -> io::Result<()> {
+    let (_, height) = terminal::size()?;
+    queue!(out, terminal::Clear(ClearType::All), cursor::MoveTo(0, 0))?;
+
+    // Leave the last row for the status line. Raw mode doesn't move
+    // to the start of the line on \n, so write \r\n.
+    let page = &files[pos.file].pages[pos.page];
+    for line in page
+        .split(|b| *b == b'\n')
+        .take(height.saturating_sub(1) as usize)
+    {
+        out.write_all(line)?;
+        out.write_all(b"\r\n")?;
+    }
+
+    queue!(out, cursor::MoveTo(0, height.saturating_sub(1)))?;
+    write!(out, "{}", format_status(files, pos))?;
+    out.flush()
+}
+
+fn event_loop(out: &mut impl Write, files: &[FilePages]) -> io::Result<()> {
+    let num_pages: Vec<usize> = files.iter().map(|file| file.pages.len()).collect();
+    let mut pos = Position { file: 0, page: 0 };
+    loop {
+        draw(out, files, pos)?;
+
+        // Resize events redraw at the new height.
+        if let Event::Key(key) = event::read()? {
+            if let Some(command) = key_command(key) {
+                match next_position(&num_pages, pos, command) {
+                    Some(new_pos) => pos = new_pos,
+                    None => return Ok(()),
+                }
+            }
+        }
+    }
+}
+
+/// Show `files` until the user quits. Every file must have at least
+/// one page.
+pub(crate) fn run(files: &[FilePages]) -> io::Result<()> {
+    if files.is_empty() {
+        return Ok(());
+    }
+
+    let mut stdout = io::stdout();
+    terminal::enable_raw_mode()?;
+    execute!(stdout, EnterAlternateScreen, cursor::Hide)?;
+
+    let res = event_loop(&mut stdout, files);
+
+    // Always restore the terminal, even if drawing failed.
+    let _ = execute!(stdout, cursor::Show, LeaveAlternateScreen);
+    let _ = terminal::disable_raw_mode();
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn pos(file: usize, page: usize) -> Option<Position> {
+        Some(Position { file, page })
+    }
+
+    #[test]
+    fn test_next_position_hunks() {
+        let num_pages = [2, 3];
+        let start = Position { file: 0, page: 0 };
+
+        assert_eq!(
+            next_position(&num_pages, start, Command::NextHunk),
+            pos(0, 1)
+        );
+        assert_eq!(
+            next_position(&num_pages, Position { file: 0, page: 1 }, Command::NextHunk),
+            pos(1, 0)
+        );
+        assert_eq!(
+            next_position(
+                &num_pages,
+                Position { file: 1, page: 0 },
+                Command::PreviousHunk
+            ),
+            pos(0, 1)
+        );
+        assert_eq!(
+            next_position(&num_pages, start, Command::PreviousHunk),
+            pos(0, 0)
+        );
+        assert_eq!(
+            next_position(&num_pages, Position { file: 1, page: 2 }, Command::NextHunk),
+            pos(1, 2)
+        );
+    }
+
+    #[test]
+    fn test_next_position_files() {
+        let num_pages = [2, 3];
+
+        assert_eq!(
+            next_position(&num_pages, Position { file: 0, page: 1 }, Command::NextFile),
+            pos(1, 0)
+        );
+        assert_eq!(
+            next_position(
+                &num_pages,
+                Position { file: 1, page: 2 },
+                Command::PreviousFile
+            ),
+            pos(0, 0)
+        );
+        assert_eq!(
+            next_position(
+                &num_pages,
+                Position { file: 0, page: 1 },
+                Command::PreviousFile
+            ),
+            pos(0, 1)
+        );
+        assert_eq!(
+            next_position(&num_pages, Position { file: 0, page: 0 }, Command::Quit),
+            None
+        );
+    }
+
+    #[test]
+    fn test_format_status() {
+        let files = [
+            FilePages {
+                display_path: "foo.rs".to_owned(),
+                pages: vec![vec![], vec![]],
+            },
+            FilePages {
+                display_path: "bar.rs".to_owned(),
+                pages: vec![vec![]],
+            },
+        ];
+
+        assert_eq!(
+            format_status(&files, Position { file: 0, page: 1 }),
+            "foo.rs (file 1/2) hunk 2/2 -- n/p: hunk, f/b: file, q: quit"
+        );
+    }
+}
@@ -0,0 +1,122 @@
+//! Difftastic is a syntactic diff tool.
+//!
+//! For usage instructions and advice on contributing, see [the
+//! manual](http://difftastic.wilfred.me.uk/).
+//!
+//! This crate is also a library, for programs that want structural
+//! diffs without running `difft` and parsing its output:
+//!
+//! ```
+//! use difft::{diff_files, DiffOptions, Language};
+//!
+//! let diff = diff_files(
+//!     "fn foo() {}\n",
+//!     "fn foo() { bar(); }\n",
+//!     Some(Language::Rust),
+//!     &DiffOptions::default(),
+//! );
+//! assert_eq!(diff.language, Some(Language::Rust));
+//! assert_eq!(diff.hunks.len(), 1);
+//! ```
+//!
+//! # Stability
+//!
+//! The library API is the items documented here: [diff_files],
+//! [diff_files_iter], [guess_language] and the types they use. It follows semver,
+//! separately from the command line interface. Options structs and
+//! the types in [FileDiff] are `#[non_exhaustive]`, so adding an
+//! option, a field or a variant is not a breaking change. Anything
+//! marked `#[doc(hidden)]` exists for the `difft` binary and may
+//! change in any release.
+//!
+//! With the `serde-types` feature, [FileDiff] and the types in it
+//! implement serde's `Serialize` and `Deserialize`.
+
+// This tends to trigger on larger tuples of simple types, and naming
+// them would probably be worse for readability.
+#![allow(clippy::type_complexity)]
+// == "" is often clearer when dealing with strings.
+#![allow(clippy::comparison_to_empty)]
+// It's common to have pairs foo_lhs and foo_rhs, leading to double
+// the number of arguments and triggering this lint.
+#![allow(clippy::too_many_arguments)]
+// Has false positives on else if chains that sometimes have the same
+// body for readability.
+#![allow(clippy::if_same_then_else)]
+// Good practice in general, but a necessary evil for Syntax. Its Hash
+// implementation does not consider the mutable fields, so it is still
+// correct.
+#![allow(clippy::mutable_key_type)]
+// .to_owned() is more explicit on string references.
+#![warn(clippy::str_to_string)]
+// .to_string() on a String is clearer as .clone().
+#![warn(clippy::string_to_string)]
+// Debugging features shouldn't be in checked-in code.
+#![warn(clippy::todo)]
+#![warn(clippy::dbg_macro)]
+
+/// The name of the `difft` binary, for usage messages. Cargo only
+/// sets `CARGO_BIN_NAME` when compiling a binary, and the CLI is part
+/// of the library.
+macro_rules! bin_name {
+    () => {
+        "difft"
+    };
+}
+
+mod api;
+mod archive;
+mod cache;
+mod cli;
+mod config;
+mod conflicts;
+mod constants;
+mod diff;
+mod display;
+mod exit_codes;
+mod files;
+mod from_patch;
+mod generated;
+mod git;
+mod gitattributes;
+mod hash;
+mod hg;
+mod interactive;
+mod line_parser;
+mod lines;
+mod logging;
+mod options;
+mod p4;
+mod pager;
+mod parse;
+mod profile;
+mod prose;
+mod reformat;
+mod resource_usage;
+#[cfg(feature = "serde-types")]
+mod serialize;
+mod summary;
+mod terminal;
+mod textconv;
+mod version;
+mod words;
+
+#[macro_use]
+extern crate log;
+
+// Many tests use `crate::syntax`.
+#[cfg(test)]
+use crate::parse::syntax;
+
+pub use crate::api::{
+    diff_files, diff_files_iter, guess_language, CancellationToken, Change, ChangeKind,
+    DiffOptions, FileDiff, FileHunks, Highlight, Hunk, Line, LineChanges,
+};
+pub use crate::parse::guess_language::Language;
+
+/// Run `difft` with the arguments of this process. This is only
+/// for the binary, and isn't part of the stable API.
+#[doc(hidden)]
+pub fn run() {
+    cli::run()
+}
@@ -1,5 +1,10 @@
 //! A fallback "parser" for plain text.
 
+use std::{
+    borrow::Cow,
+    hash::{Hash, Hasher},
+};
+
 use lazy_static::lazy_static;
 use line_numbers::LinePositions;
 use regex::Regex;
@@ -31,6 +36,105 @@ fn split_lines_keep_newline(s: &str) -> Vec<&str> {
     lines
 }
 
+/// How to compare whitespace when diffing text, see
+/// `--ignore-space-change` and `--ignore-all-space`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Whitespace {
+    /// Whitespace must match exactly.
+    Exact,
+    /// Ignore whitespace at the end of lines, and treat all other
+    /// runs of whitespace as equal.
+    IgnoreChange,
+    /// Ignore all whitespace.
+    IgnoreAll,
+}
+
+/// `line` with its whitespace normalized, so lines that only differ
+/// in ignored whitespace are equal.
+fn normalize_whitespace(line: &str, whitespace: Whitespace) -> Cow<'_, str> {
+    match whitespace {
+        Whitespace::Exact => Cow::Borrowed(line),
+        Whitespace::IgnoreChange => {
+            let mut res = String::with_capacity(line.len());
+            let mut in_whitespace = false;
+            for c in line.trim_end().chars() {
+                if c.is_whitespace() {
+                    if !in_whitespace {
+                        res.push(' ');
+                    }
+                    in_whitespace = true;
+                } else {
+                    res.push(c);
+                    in_whitespace = false;
+                }
+            }
+            Cow::Owned(res)
+        }
+        Whitespace::IgnoreAll => Cow::Owned(line.chars().filter(|c| !c.is_whitespace()).collect()),
+    }
+}
+
+/// Whether `lhs_src` and `rhs_src` have different lines, ignoring
+/// whitespace as `whitespace` asks, and blank lines if
+/// `ignore_blank_lines` is set. This is much cheaper than
+/// [change_positions], for `--check-only`.
+pub(crate) fn has_text_changes(
+    lhs_src: &str,
+    rhs_src: &str,
+    whitespace: Whitespace,
+    ignore_blank_lines: bool,
+) -> bool {
+    fn lines(
+        src: &str,
+        whitespace: Whitespace,
+        ignore_blank_lines: bool,
+    ) -> impl Iterator<Item = Cow<'_, str>> {
+        src.lines()
+            .filter(move |line| !ignore_blank_lines || !line.trim().is_empty())
+            .map(move |line| normalize_whitespace(line, whitespace))
+    }
+
+    if lhs_src == rhs_src {
+        return false;
+    }
+    !lines(lhs_src, whitespace, ignore_blank_lines).eq(lines(
+        rhs_src,
+        whitespace,
+        ignore_blank_lines,
+    ))
+}
+
+/// A line of text that is compared by its normalized content, but
+/// keeps its original text for display.
+#[derive(Debug, Clone)]
+struct Line<'a> {
+    text: &'a str,
+    normalized: Cow<'a, str>,
+}
+
+impl<'a> Line<'a> {
+    fn new(text: &'a str, whitespace: Whitespace) -> Self {
+        Self {
+            text,
+            normalized: normalize_whitespace(text, whitespace),
+        }
+    }
+}
+
+impl<'a> PartialEq for Line<'a> {
+    fn eq(&self, other: &Self) -> bool {
+        self.normalized == other.normalized
+    }
+}
+
+impl<'a> Eq for Line<'a> {}
+
+impl<'a> Hash for Line<'a> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.normalized.hash(state);
+    }
+}
+
 #[derive(Debug)]
 enum TextChangeKind {
     Novel,
@@ -76,21 +180,32 @@ fn merge_novel<'a>(
 fn changed_parts<'a>(
     src: &'a str,
     opposite_src: &'a str,
+    whitespace: Whitespace,
 ) -> Vec<(TextChangeKind, Vec<&'a str>, Vec<&'a str>)> {
-    let src_lines = split_lines_keep_newline(src);
-    let opposite_src_lines = split_lines_keep_newline(opposite_src);
+    let to_lines = |src: &'a str| -> Vec<Line<'a>> {
+        split_lines_keep_newline(src)
+            .into_iter()
+            .map(|line| Line::new(line, whitespace))
+            .collect()
+    };
+    let src_lines = to_lines(src);
+    let opposite_src_lines = to_lines(opposite_src);
 
     let mut res: Vec<(TextChangeKind, Vec<&'a str>, Vec<&'a str>)> = vec![];
     for diff_res in myers_diff::slice_unique_by_hash(&src_lines, &opposite_src_lines) {
         match diff_res {
             myers_diff::DiffResult::Left(line) => {
-                res.push((TextChangeKind::Novel, vec![line], vec![]));
+                res.push((TextChangeKind::Novel, vec![line.text], vec![]));
             }
             myers_diff::DiffResult::Both(line, opposite_line) => {
-                res.push((TextChangeKind::Unchanged, vec![line], vec![opposite_line]));
+                res.push((
+                    TextChangeKind::Unchanged,
+                    vec![line.text],
+                    vec![opposite_line.text],
+                ));
             }
             myers_diff::DiffResult::Right(opposite_line) => {
-                res.push((TextChangeKind::Novel, vec![], vec![opposite_line]));
+                res.push((TextChangeKind::Novel, vec![], vec![opposite_line.text]));
             }
         }
     }
@@ -107,8 +222,97 @@ fn line_len_in_bytes(line: &str) -> usize {
     }
 }
 
+/// Mark all of `lhs_part` as novel, without diffing individual words.
+fn push_novel_positions(
+    lhs_part: &str,
+    lhs_offset: usize,
+    lhs_lp: &LinePositions,
+    mps: &mut Vec<MatchedPos>,
+) {
+    for lhs_pos in lhs_lp.from_region(lhs_offset, lhs_offset + lhs_part.len()) {
+        mps.push(MatchedPos {
+            kind: MatchKind::NovelWord {
+                highlight: TokenKind::Atom(AtomKind::Normal),
+            },
+            pos: lhs_pos,
+        });
+    }
+}
+
+/// Diff the words in `lhs_part` against `rhs_part`, and add the
+/// resulting positions for `lhs_part` to `mps`.
+///
+/// Returns false, without adding anything, if either side has too
+/// many words to diff.
+fn push_word_positions(
+    lhs_part: &str,
+    rhs_part: &str,
+    mut lhs_offset: usize,
+    mut rhs_offset: usize,
+    lhs_lp: &LinePositions,
+    rhs_lp: &LinePositions,
+    whitespace: Whitespace,
+    word_regex: Option<&Regex>,
+    mps: &mut Vec<MatchedPos>,
+) -> bool {
+    let lhs_words = split_words(lhs_part, word_regex);
+    let rhs_words = split_words(rhs_part, word_regex);
+    if lhs_words.len() > MAX_WORDS_IN_LINE || rhs_words.len() > MAX_WORDS_IN_LINE {
+        return false;
+    }
+
+    for diff_res in myers_diff::slice_unique_by_hash(&lhs_words, &rhs_words) {
+        match diff_res {
+            myers_diff::DiffResult::Left(lhs_word) => {
+                // Don't highlight whitespace that we're ignoring.
+                let ignored = whitespace != Whitespace::Exact && lhs_word.trim().is_empty();
+                if !ignored {
+                    let lhs_pos = lhs_lp.from_region(lhs_offset, lhs_offset + lhs_word.len());
+
+                    mps.push(MatchedPos {
+                        kind: MatchKind::NovelWord {
+                            highlight: TokenKind::Atom(AtomKind::Normal),
+                        },
+                        pos: lhs_pos[0],
+                    });
+                }
+
+                lhs_offset += lhs_word.len();
+            }
+            myers_diff::DiffResult::Both(lhs_word, rhs_word) => {
+                if *lhs_word != "\n" {
+                    let lhs_pos = lhs_lp.from_region(lhs_offset, lhs_offset + lhs_word.len());
+                    let rhs_pos = rhs_lp.from_region(rhs_offset, rhs_offset + rhs_word.len());
+
+                    mps.push(MatchedPos {
+                        kind: MatchKind::NovelLinePart {
+                            highlight: TokenKind::Atom(AtomKind::Normal),
+                            self_pos: lhs_pos[0],
+                            opposite_pos: rhs_pos,
+                        },
+                        pos: lhs_pos[0],
+                    });
+                }
+
+                lhs_offset += lhs_word.len();
+                rhs_offset += rhs_word.len();
+            }
+            myers_diff::DiffResult::Right(rhs_word) => {
+                rhs_offset += rhs_word.len();
+            }
+        }
+    }
+
+    true
+}
+
 // TODO: Prefer src/opposite_src nomenclature as this function is called from both sides.
-pub(crate) fn change_positions(lhs_src: &str, rhs_src: &str) -> Vec<MatchedPos> {
+pub(crate) fn change_positions(
+    lhs_src: &str,
+    rhs_src: &str,
+    whitespace: Whitespace,
+    word_regex: Option<&Regex>,
+) -> Vec<MatchedPos> {
     // TODO: If either side is "", don't split each line by words
     // pointlessly. This is common for file additions/removals.
     let lhs_lp = LinePositions::from(lhs_src);
@@ -118,7 +322,7 @@ pub(crate) fn change_positions(lhs_src: &str, rhs_src: &str) -> Vec<MatchedPos>
     let mut rhs_offset = 0;
 
     let mut mps = vec![];
-    for (kind, lhs_lines, rhs_lines) in changed_parts(lhs_src, rhs_src) {
+    for (kind, lhs_lines, rhs_lines) in changed_parts(lhs_src, rhs_src, whitespace) {
         match kind {
             TextChangeKind::Unchanged => {
                 for (lhs_line, rhs_line) in lhs_lines.iter().zip(rhs_lines) {
@@ -144,68 +348,39 @@ pub(crate) fn change_positions(lhs_src: &str, rhs_src: &str) -> Vec<MatchedPos>
                 let lhs_part = lhs_lines.join("");
                 let rhs_part = rhs_lines.join("");
 
-                let lhs_words = split_words(&lhs_part);
-                let rhs_words = split_words(&rhs_part);
-
                 // Myers Diff scales badly on large inputs, and
                 // word-level diffing is merely nice to have. If we
-                // have a very large number of words, don't diff
-                // individual words.
-                if lhs_words.len() > MAX_WORDS_IN_LINE || rhs_words.len() > MAX_WORDS_IN_LINE {
-                    for lhs_pos in lhs_lp.from_region(lhs_offset, lhs_offset + lhs_part.len()) {
-                        mps.push(MatchedPos {
-                            kind: MatchKind::NovelWord {
-                                highlight: TokenKind::Atom(AtomKind::Normal),
-                            },
-                            pos: lhs_pos,
-                        });
-                    }
-
-                    lhs_offset += lhs_part.len();
-                    rhs_offset += rhs_part.len();
-                    continue;
-                }
-
-                for diff_res in myers_diff::slice_unique_by_hash(&lhs_words, &rhs_words) {
-                    match diff_res {
-                        myers_diff::DiffResult::Left(lhs_word) => {
-                            let lhs_pos =
-                                lhs_lp.from_region(lhs_offset, lhs_offset + lhs_word.len());
-
-                            mps.push(MatchedPos {
-                                kind: MatchKind::NovelWord {
-                                    highlight: TokenKind::Atom(AtomKind::Normal),
-                                },
-                                pos: lhs_pos[0],
-                            });
-
-                            lhs_offset += lhs_word.len();
-                        }
-                        myers_diff::DiffResult::Both(lhs_word, rhs_word) => {
-                            if *lhs_word != "\n" {
-                                let lhs_pos =
-                                    lhs_lp.from_region(lhs_offset, lhs_offset + lhs_word.len());
-                                let rhs_pos =
-                                    rhs_lp.from_region(rhs_offset, rhs_offset + rhs_word.len());
-
-                                mps.push(MatchedPos {
-                                    kind: MatchKind::NovelLinePart {
-                                        highlight: TokenKind::Atom(AtomKind::Normal),
-                                        self_pos: lhs_pos[0],
-                                        opposite_pos: rhs_pos,
-                                    },
-                                    pos: lhs_pos[0],
-                                });
-                            }
-
-                            lhs_offset += lhs_word.len();
-                            rhs_offset += rhs_word.len();
-                        }
-                        myers_diff::DiffResult::Right(rhs_word) => {
-                            rhs_offset += rhs_word.len();
+                // have a very large number of words, diff each pair
+                // of changed lines separately instead.
+                if !push_word_positions(
+                    &lhs_part, &rhs_part, lhs_offset, rhs_offset, &lhs_lp, &rhs_lp, whitespace,
+                    word_regex, &mut mps,
+                ) {
+                    let mut line_lhs_offset = lhs_offset;
+                    let mut line_rhs_offset = rhs_offset;
+                    for (i, lhs_line) in lhs_lines.iter().enumerate() {
+                        let rhs_line = rhs_lines.get(i).copied().unwrap_or("");
+                        if !push_word_positions(
+                            lhs_line,
+                            rhs_line,
+                            line_lhs_offset,
+                            line_rhs_offset,
+                            &lhs_lp,
+                            &rhs_lp,
+                            whitespace,
+                            word_regex,
+                            &mut mps,
+                        ) {
+                            push_novel_positions(lhs_line, line_lhs_offset, &lhs_lp, &mut mps);
                         }
+
+                        line_lhs_offset += lhs_line.len();
+                        line_rhs_offset += rhs_line.len();
                     }
                 }
+
+                lhs_offset += lhs_part.len();
+                rhs_offset += rhs_part.len();
             }
         }
     }
@@ -220,6 +395,35 @@ mod tests {
 
     use super::*;
 
+    #[test]
+    fn test_has_text_changes() {
+        assert!(!has_text_changes("a\n", "a\n", Whitespace::Exact, false));
+        assert!(has_text_changes(
+            "a  b\n",
+            "a b\n",
+            Whitespace::Exact,
+            false
+        ));
+        assert!(!has_text_changes(
+            "a  b\n",
+            "a b \n",
+            Whitespace::IgnoreChange,
+            false
+        ));
+        assert!(has_text_changes(
+            "a\n\nb\n",
+            "a\nb\n",
+            Whitespace::Exact,
+            false
+        ));
+        assert!(!has_text_changes(
+            "a\n\nb\n",
+            "a\nb\n",
+            Whitespace::Exact,
+            true
+        ));
+    }
+
     #[test]
     fn test_split_newlines() {
         let s = "foo\nbar\nbaz";
@@ -229,7 +433,7 @@ mod tests {
 
     #[test]
     fn test_positions_no_changes() {
-        let positions = change_positions("foo", "foo");
+        let positions = change_positions("foo", "foo", Whitespace::Exact, None);
 
         assert_eq!(positions.len(), 1);
         assert!(!positions[0].kind.is_novel());
@@ -248,13 +452,13 @@ mod tests {
         // Even though the word exists on both sides, it should still
         // be treated as a change. We're doing a line-based diff and
         // the lines are different.
-        let positions = change_positions("foo", " foo");
+        let positions = change_positions("foo", " foo", Whitespace::Exact, None);
         assert!(positions[0].kind.is_novel());
     }
 
     #[test]
     fn test_no_changes_trailing_newlines() {
-        let positions = change_positions("foo\n", "foo\n");
+        let positions = change_positions("foo\n", "foo\n", Whitespace::Exact, None);
 
         assert_eq!(positions.len(), 1);
         assert!(!positions[0].kind.is_novel());
@@ -270,7 +474,7 @@ mod tests {
 
     #[test]
     fn test_novel_lhs_trailing_newlines() {
-        let positions = change_positions("foo\n", "");
+        let positions = change_positions("foo\n", "", Whitespace::Exact, None);
 
         assert_eq!(positions.len(), 2);
         assert!(positions[0].kind.is_novel());
@@ -278,9 +482,108 @@ mod tests {
 
     #[test]
     fn test_positions_novel_lhs() {
-        let positions = change_positions("foo", "");
+        let positions = change_positions("foo", "", Whitespace::Exact, None);
 
         assert_eq!(positions.len(), 1);
         assert!(positions[0].kind.is_novel());
     }
+
+    #[test]
+    fn test_positions_one_word_change() {
+        let lhs = "The quick brown fox jumps over the lazy dog.\n";
+        let rhs = "The quick red fox jumps over the lazy dog.\n";
+
+        let positions = change_positions(lhs, rhs, Whitespace::Exact, None);
+        let novel_words: Vec<_> = positions
+            .iter()
+            .filter(|mp| matches!(mp.kind, MatchKind::NovelWord { .. }))
+            .map(|mp| &lhs[mp.pos.start_col as usize..mp.pos.end_col as usize])
+            .collect();
+        assert_eq!(novel_words, vec!["brown"]);
+        assert!(positions
+            .iter()
+            .any(|mp| matches!(mp.kind, MatchKind::NovelLinePart { .. })));
+    }
+
+    #[test]
+    fn test_positions_word_regex() {
+        let lhs = "foo_bar baz\n";
+        let rhs = "foo_qux baz\n";
+        let novel_words = |word_regex: Option<&Regex>| -> Vec<&str> {
+            change_positions(lhs, rhs, Whitespace::Exact, word_regex)
+                .iter()
+                .filter(|mp| matches!(mp.kind, MatchKind::NovelWord { .. }))
+                .map(|mp| &lhs[mp.pos.start_col as usize..mp.pos.end_col as usize])
+                .collect()
+        };
+
+        assert_eq!(novel_words(None), vec!["foo_bar"]);
+        let regex = Regex::new("[a-z]+").unwrap();
+        assert_eq!(novel_words(Some(&regex)), vec!["bar"]);
+    }
+
+    #[test]
+    fn test_positions_long_line_is_novel() {
+        // Too many words on a single line to diff them at all.
+        let lhs = format!("{}x\n", "a ".repeat(MAX_WORDS_IN_LINE));
+        let rhs = format!("{}y\n", "a ".repeat(MAX_WORDS_IN_LINE));
+
+        let positions = change_positions(&lhs, &rhs, Whitespace::Exact, None);
+        assert!(positions
+            .iter()
+            .all(|mp| matches!(mp.kind, MatchKind::NovelWord { .. })));
+    }
+
+    #[test]
+    fn test_positions_large_region_diffs_lines() {
+        // Too many words to diff the region as a whole, but each
+        // line is small enough to diff against its counterpart.
+        let words = "a ".repeat(300);
+        let lhs = format!("x {}\ny {}\n", words, words);
+        let rhs = format!("z {}\nw {}\n", words, words);
+
+        let positions = change_positions(&lhs, &rhs, Whitespace::Exact, None);
+        assert!(positions
+            .iter()
+            .any(|mp| matches!(mp.kind, MatchKind::NovelLinePart { .. })));
+        assert!(positions
+            .iter()
+            .any(|mp| mp.pos.line.0 == 1 && matches!(mp.kind, MatchKind::NovelWord { .. })));
+    }
+
+    #[test]
+    fn test_ignore_space_change() {
+        // Tabs instead of spaces, and trailing whitespace.
+        let lhs = "if x:\n    foo  bar\n";
+        let rhs = "if x:\n\tfoo bar \n";
+
+        let positions = change_positions(lhs, rhs, Whitespace::IgnoreChange, None);
+        assert!(positions.iter().all(|mp| !mp.kind.is_novel()));
+
+        let positions = change_positions(lhs, rhs, Whitespace::Exact, None);
+        assert!(positions.iter().any(|mp| mp.kind.is_novel()));
+    }
+
+    #[test]
+    fn test_ignore_space_change_keeps_leading_whitespace() {
+        let positions = change_positions("foo\n", "    foo\n", Whitespace::IgnoreChange, None);
+        assert!(positions.iter().any(|mp| mp.kind.is_novel()));
+    }
+
+    #[test]
+    fn test_ignore_all_space() {
+        let positions =
+            change_positions("foo(a, b)\n", "\tfoo(a,b)  \n", Whitespace::IgnoreAll, None);
+        assert!(positions.iter().all(|mp| !mp.kind.is_novel()));
+
+        // The unchanged line is still shown with its own text.
+        assert_eq!(
+            positions[0].pos,
+            SingleLineSpan {
+                line: 0.into(),
+                start_col: 0,
+                end_col: 9
+            }
+        );
+    }
 }
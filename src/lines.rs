@@ -36,6 +36,17 @@ pub(crate) fn is_all_whitespace(s: &str) -> bool {
     s.chars().all(|c| c.is_whitespace())
 }
 
+/// The last line of `src`, if it has no trailing newline but
+/// `opposite_src` does. The lines would otherwise look the same, so
+/// the display marks this line.
+pub(crate) fn missing_final_newline(src: &str, opposite_src: &str) -> Option<LineNumber> {
+    let has_newline = |s: &str| s.is_empty() || s.ends_with('\n');
+    if has_newline(src) || !has_newline(opposite_src) || opposite_src.is_empty() {
+        return None;
+    }
+    Some((src.lines().count() as u32 - 1).into())
+}
+
 #[cfg(test)]
 mod tests {
     use pretty_assertions::assert_eq;
@@ -70,4 +81,12 @@ mod tests {
     fn test_is_all_whitespace() {
         assert!(is_all_whitespace(" \n\t"));
     }
+
+    #[test]
+    fn test_missing_final_newline() {
+        assert_eq!(missing_final_newline("a\nb", "a\nc\n"), Some(1.into()));
+        assert_eq!(missing_final_newline("a\nb\n", "a\nc"), None);
+        assert_eq!(missing_final_newline("a\nb", "a\nc"), None);
+        assert_eq!(missing_final_newline("a", ""), None);
+    }
 }
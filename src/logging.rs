@@ -0,0 +1,123 @@
+//! Diagnostics about how each file was diffed, printed to stderr
+//! with `--verbose`.
+//!
+//! `--verbose` shows the language detected for each file and why a
+//! file fell back to a text diff. `--verbose --verbose` also shows the
+//! time, memory and graph statistics for each file. Warnings are
+//! always shown. With `--log-format=json`, each event is a JSON object
+//! on its own line.
+//!
+//! This is for users. Debugging output for difftastic developers uses
+//! the `log` crate instead, see `DFT_LOG`.
+
+use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+
+use serde_json::{json, Value};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum Level {
+    Warning = 0,
+    /// Shown with `--verbose`.
+    Info = 1,
+    /// Shown with `--verbose --verbose`.
+    Debug = 2,
+}
+
+impl Level {
+    fn name(self) -> &'static str {
+        match self {
+            Level::Warning => "warning",
+            Level::Info => "info",
+            Level::Debug => "debug",
+        }
+    }
+}
+
+static VERBOSITY: AtomicU8 = AtomicU8::new(0);
+static JSON_FORMAT: AtomicBool = AtomicBool::new(false);
+
+/// Set the verbosity (the number of times `--verbose` was given) and
+/// whether to log JSON.
+pub(crate) fn init(verbosity: u8, json_format: bool) {
+    VERBOSITY.store(verbosity, Ordering::Relaxed);
+    JSON_FORMAT.store(json_format, Ordering::Relaxed);
+}
+
+pub(crate) fn enabled(level: Level) -> bool {
+    level as u8 <= VERBOSITY.load(Ordering::Relaxed)
+}
+
+pub(crate) fn json_format() -> bool {
+    JSON_FORMAT.load(Ordering::Relaxed)
+}
+
+fn format_json(level: Level, event: &str, message: &str, fields: Value) -> String {
+    // A serde_json Map is sorted by key, so write the common keys
+    // ourselves to keep them first.
+    let mut res = format!(
+        "{{\"level\":{},\"event\":{},\"message\":{}",
+        json!(level.name()),
+        json!(event),
+        json!(message)
+    );
+    if let Value::Object(fields) = fields {
+        for (key, value) in fields {
+            res.push_str(&format!(",{}:{}", json!(key), value));
+        }
+    }
+    res.push('}');
+    res
+}
+
+fn format_text(level: Level, message: &str) -> String {
+    match level {
+        Level::Warning => format!("warning: {}", message),
+        Level::Info | Level::Debug => message.to_owned(),
+    }
+}
+
+/// Print an event to stderr, if `level` is enabled. `event` names the
+/// kind of event, and `fields` (a JSON object) are only included in
+/// JSON output, so `message` should describe them too.
+pub(crate) fn log(level: Level, event: &str, message: &str, fields: Value) {
+    if !enabled(level) {
+        return;
+    }
+
+    if json_format() {
+        eprintln!("{}", format_json(level, event, message, fields));
+    } else {
+        eprintln!("{}", format_text(level, message));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_format_json() {
+        assert_eq!(
+            format_json(
+                Level::Info,
+                "language",
+                "foo.rs: Detected Rust",
+                json!({"path": "foo.rs", "language": "Rust"})
+            ),
+            r#"{"level":"info","event":"language","message":"foo.rs: Detected Rust","language":"Rust","path":"foo.rs"}"#
+        );
+    }
+
+    #[test]
+    fn test_format_text() {
+        assert_eq!(format_text(Level::Warning, "oops"), "warning: oops");
+        assert_eq!(format_text(Level::Debug, "foo.rs: 0.02s"), "foo.rs: 0.02s");
+    }
+
+    #[test]
+    fn test_warnings_always_enabled() {
+        assert!(enabled(Level::Warning));
+    }
+}
@@ -1,21 +1,50 @@
 //! CLI option parsing.
 
 use std::{
+    borrow::Cow,
     env,
-    ffi::OsStr,
+    ffi::{OsStr, OsString},
     fmt::Display,
     path::{Path, PathBuf},
+    sync::atomic::{AtomicBool, Ordering},
+    time::Duration,
 };
 
-use clap::{crate_authors, crate_description, Arg, Command};
+use clap::{crate_authors, crate_description, Arg, ArgMatches, Command, ValueSource};
+use clap_complete::Shell;
 use const_format::formatcp;
 use crossterm::tty::IsTty;
 use itertools::Itertools;
+use owo_colors::AnsiColors;
+use regex::Regex;
+use serde_json::json;
+use strum::IntoEnumIterator;
 
 use crate::{
-    display::style::BackgroundColor,
+    api::CancellationToken,
+    config::{self, Config, Value},
+    display::{
+        invisible::ShowInvisible,
+        style::{
+            BackgroundColor, Color, ColorDepth, Highlight, Palette, Theme, TrailingWhitespace,
+        },
+    },
     exit_codes::EXIT_BAD_ARGUMENTS,
-    parse::guess_language::{language_override_from_name, LanguageOverride},
+    files::{escape_path, glob_matches_path, read_pairs_file, PathFilter},
+    generated::{parse_generated_markers, DEFAULT_GENERATED_MARKERS},
+    git, hg,
+    line_parser::Whitespace,
+    logging::{self, Level},
+    p4,
+    pager::{self, pager_command},
+    parse::guess_language::{
+        language_name, language_override_from_name, Language, LanguageOverride,
+    },
+    prose::ProseGranularity,
+    reformat::ReformatJson,
+    summary::{FileFormat, SYMLINK_MODE},
+    terminal::detect_background,
+    textconv::GitSide,
     version::VERSION,
 };
 
@@ -24,11 +53,46 @@ pub(crate) const DEFAULT_BYTE_LIMIT: usize = 1_000_000;
 // files (the highest is slow_1.rs/slow_2.rs at 1.3M nodes), but
 // small enough to terminate in ~5 seconds like the test file in #306.
 pub(crate) const DEFAULT_GRAPH_LIMIT: usize = 3_000_000;
+
+/// The minimum number of tokens in a region of code for
+/// `--detect-moves` to consider it moved.
+pub(crate) const DEFAULT_MOVE_MIN_TOKENS: usize = 20;
 pub(crate) const DEFAULT_PARSE_ERROR_LIMIT: usize = 0;
 
+/// The minimum number of descendants of an unchanged node before the
+/// unchanged pre-pass marks it as unchanged, see `--unchanged-min-run`.
+pub(crate) const DEFAULT_UNCHANGED_MIN_RUN: u32 = 10;
+/// The number of unique subtrees that two lists must share to be
+/// considered mostly unchanged, see `--unchanged-ngram`.
+pub(crate) const DEFAULT_UNCHANGED_NGRAM: usize = 4;
+
 pub(crate) const DEFAULT_TAB_WIDTH: usize = 4;
 
-pub(crate) const USAGE: &str = concat!(env!("CARGO_BIN_NAME"), " [OPTIONS] OLD-PATH NEW-PATH");
+pub(crate) const USAGE: &str = concat!(bin_name!(), " [OPTIONS] OLD-PATH NEW-PATH");
+
+/// What to do with a file that exceeds `--byte-limit`, see
+/// `--byte-limit-action`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ByteLimitAction {
+    /// Use a text diff.
+    Text,
+    /// Stop with an error.
+    Error,
+    /// Structurally diff the start of each side.
+    Truncate,
+}
+
+/// The order of the files in a directory diff, see `--sort-paths`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum SortOrder {
+    /// By path, comparing bytes so the order doesn't depend on the
+    /// locale.
+    Path,
+    /// Largest files first.
+    Size,
+    /// Files with the most changed lines first.
+    Changes,
+}
 
 #[derive(Debug, Clone, Copy)]
 pub(crate) enum ColorOutput {
@@ -37,33 +101,220 @@ pub(crate) enum ColorOutput {
     Never,
 }
 
+#[derive(Debug, Clone, Copy)]
+enum PagerMode {
+    Always,
+    Auto,
+    Never,
+}
+
+/// Where to write the diff.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Output {
+    Stdout,
+    /// Pipe the diff to this pager command.
+    Pager(String),
+    /// Write the diff to the file given with `--output-file`.
+    File(PathBuf),
+    /// Show one hunk at a time, see `--interactive`.
+    Interactive,
+}
+
 #[derive(Debug, Clone)]
 pub(crate) struct DisplayOptions {
     pub(crate) background_color: BackgroundColor,
+    pub(crate) palette: Palette,
     pub(crate) use_color: bool,
+    /// Mark changes with text attributes, even if `use_color` is
+    /// false.
+    pub(crate) accessibility: bool,
     pub(crate) display_mode: DisplayMode,
     pub(crate) print_unchanged: bool,
+    /// Whether to hide binary files entirely, see `--skip-binary`.
+    pub(crate) skip_binary: bool,
     pub(crate) tab_width: usize,
     pub(crate) terminal_width: usize,
-    pub(crate) num_context_lines: u32,
+    pub(crate) before_context_lines: u32,
+    pub(crate) after_context_lines: u32,
+    pub(crate) merge_hunk_gap: u32,
+    /// Whether to show the whole enclosing declaration of each hunk
+    /// as context, instead of a fixed number of lines.
+    pub(crate) function_context: bool,
+    /// Whether to extend the context before hunks so it starts at the
+    /// beginning of a construct.
+    pub(crate) structural_context: bool,
+    /// If set, split hunks that span more lines than this.
+    pub(crate) max_hunk_lines: Option<usize>,
+    /// Whether to print a `@@ -a,b +c,d @@` line range after each
+    /// hunk header.
+    pub(crate) hunk_ranges: bool,
+    /// The hunks to display, as inclusive ranges of hunk
+    /// numbers. If empty, display all hunks.
+    pub(crate) hunk_selection: Vec<(usize, usize)>,
+    /// Whether `--hunk` applies to each of several files, so a file
+    /// without the selected hunks is skipped rather than an error.
+    pub(crate) hunk_selection_per_file: bool,
+    /// If set, only display hunks that change these lines.
+    pub(crate) line_ranges: Option<LineRanges>,
+    /// Whether to ignore changes that only add or remove blank
+    /// lines, see `--ignore-blank-lines`.
+    pub(crate) ignore_blank_lines: bool,
     pub(crate) syntax_highlight: bool,
-    pub(crate) sort_paths: bool,
+    pub(crate) full_syntax_highlight: bool,
+    pub(crate) dim_punctuation: bool,
+    pub(crate) trailing_whitespace: TrailingWhitespace,
+    /// When to escape characters that are hard to see in changed
+    /// text, see `--show-invisible`.
+    pub(crate) show_invisible: ShowInvisible,
+    pub(crate) novel_line_marker: Option<char>,
+    pub(crate) sort_order: SortOrder,
+    /// Show the content of files that only exist in one directory,
+    /// rather than just listing them.
+    pub(crate) new_file: bool,
+    /// Whether to print the number of changed, unchanged and binary
+    /// files after diffing several files, see `--no-summary`.
+    pub(crate) run_summary: bool,
+    pub(crate) markdown_max_bytes: Option<usize>,
+    /// Tab widths for specific languages, from `[language.NAME]`
+    /// tables in the config file.
+    pub(crate) language_tab_widths: Vec<(LanguageOverride, usize)>,
+}
+
+/// The lines selected with `--lines`, as inclusive ranges of line
+/// numbers counting from 1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct LineRanges {
+    /// The range on the LHS, if given. If not, hunks are only
+    /// selected by their RHS lines.
+    pub(crate) lhs: Option<(usize, usize)>,
+    pub(crate) rhs: (usize, usize),
 }
 
 pub(crate) const DEFAULT_TERMINAL_WIDTH: usize = 80;
 
+impl DisplayOptions {
+    /// Whether to style the file content, with colors or with
+    /// accessibility attributes.
+    pub(crate) fn style_content(&self) -> bool {
+        self.use_color || self.accessibility
+    }
+
+    /// The display options for a file in `file_format`, using the tab
+    /// width for its language if the config file sets one.
+    pub(crate) fn for_file(&self, file_format: &FileFormat) -> Cow<'_, DisplayOptions> {
+        let language = match file_format {
+            FileFormat::SupportedLanguage(language) => LanguageOverride::Language(*language),
+            FileFormat::PlainText => LanguageOverride::PlainText,
+            _ => return Cow::Borrowed(self),
+        };
+
+        match self
+            .language_tab_widths
+            .iter()
+            .find(|(lang, _)| *lang == language)
+        {
+            Some((_, tab_width)) => Cow::Owned(DisplayOptions {
+                tab_width: *tab_width,
+                ..self.clone()
+            }),
+            None => Cow::Borrowed(self),
+        }
+    }
+
+    /// Whether to display the hunk numbered `hunk_num`, counting
+    /// from 1.
+    pub(crate) fn shows_hunk(&self, hunk_num: usize) -> bool {
+        self.hunk_selection.is_empty()
+            || self
+                .hunk_selection
+                .iter()
+                .any(|(start, end)| *start <= hunk_num && hunk_num <= *end)
+    }
+
+    /// Check that the hunks selected with `--hunk` exist in a file
+    /// with `num_hunks` hunks, and return whether to display the
+    /// file.
+    ///
+    /// When diffing a single file, a hunk that doesn't exist is an
+    /// error. When diffing several files, a file is only displayed if
+    /// it has any of the selected hunks, see
+    /// [check_any_hunk_selected].
+    pub(crate) fn check_hunk_selection(&self, display_path: &str, num_hunks: usize) -> bool {
+        if self.hunk_selection_per_file {
+            let has_selected_hunk = self.hunk_selection.is_empty()
+                || self
+                    .hunk_selection
+                    .iter()
+                    .any(|(start, _)| *start <= num_hunks);
+            if has_selected_hunk {
+                FOUND_SELECTED_HUNK.store(true, Ordering::Relaxed);
+            }
+            return has_selected_hunk;
+        }
+
+        if let Some((_, end)) = self.hunk_selection.iter().find(|(_, end)| *end > num_hunks) {
+            eprintln!(
+                "error: --hunk {} is out of range, {} has {} hunk{}.",
+                end,
+                display_path,
+                num_hunks,
+                if num_hunks == 1 { "" } else { "s" }
+            );
+            std::process::exit(EXIT_BAD_ARGUMENTS);
+        }
+        true
+    }
+}
+
+/// Set when a file with any of the hunks selected with `--hunk` is
+/// displayed, see [DisplayOptions::check_hunk_selection].
+static FOUND_SELECTED_HUNK: AtomicBool = AtomicBool::new(false);
+
+/// Exit with an error if `--hunk` was given when diffing several
+/// files, but no file had any of the selected hunks.
+pub(crate) fn check_any_hunk_selected(display_options: &DisplayOptions) {
+    if display_options.hunk_selection.is_empty() || FOUND_SELECTED_HUNK.load(Ordering::Relaxed) {
+        return;
+    }
+
+    eprintln!("error: No file has the hunks selected with --hunk.");
+    std::process::exit(EXIT_BAD_ARGUMENTS);
+}
+
 impl Default for DisplayOptions {
     fn default() -> Self {
         Self {
             background_color: BackgroundColor::Dark,
+            palette: Palette::new(Theme::Dark, ColorDepth::Ansi16),
             use_color: false,
+            accessibility: false,
             display_mode: DisplayMode::SideBySide,
             print_unchanged: true,
+            skip_binary: false,
             tab_width: 8,
             terminal_width: DEFAULT_TERMINAL_WIDTH,
-            num_context_lines: 3,
+            before_context_lines: 3,
+            after_context_lines: 3,
+            merge_hunk_gap: 3,
+            function_context: false,
+            structural_context: false,
+            max_hunk_lines: None,
+            hunk_ranges: false,
+            hunk_selection: vec![],
+            hunk_selection_per_file: false,
+            line_ranges: None,
+            ignore_blank_lines: false,
             syntax_highlight: true,
-            sort_paths: false,
+            full_syntax_highlight: false,
+            dim_punctuation: false,
+            trailing_whitespace: TrailingWhitespace::Auto,
+            show_invisible: ShowInvisible::Auto,
+            novel_line_marker: None,
+            sort_order: SortOrder::Path,
+            new_file: false,
+            run_summary: true,
+            markdown_max_bytes: None,
+            language_tab_widths: vec![],
         }
     }
 }
@@ -72,10 +323,82 @@ impl Default for DisplayOptions {
 pub(crate) struct DiffOptions {
     pub(crate) graph_limit: usize,
     pub(crate) byte_limit: usize,
+    /// The byte limit for files matching each glob, which take
+    /// precedence over `byte_limit`. The first match wins.
+    pub(crate) byte_limit_overrides: Vec<(glob::Pattern, usize)>,
+    pub(crate) byte_limit_action: ByteLimitAction,
     pub(crate) parse_error_limit: usize,
     pub(crate) check_only: bool,
     pub(crate) ignore_comments: bool,
+    pub(crate) ignore_punctuation: bool,
+    /// How to compare whitespace in text diffs.
+    pub(crate) whitespace: Whitespace,
+    /// Whether text diffs compare lines, or paragraphs or sentences
+    /// regardless of where lines are wrapped, see
+    /// `--prose-granularity`.
+    pub(crate) prose_granularity: ProseGranularity,
+    /// How to split words when showing which words changed, instead
+    /// of the usual rules, see `--word-regex`.
+    pub(crate) word_regex: Option<Regex>,
+    /// When to pretty-print JSON and YAML before diffing, see
+    /// `--reformat-json`.
+    pub(crate) reformat_json: ReformatJson,
     pub(crate) strip_cr: bool,
+    /// Treat files as text even if they look binary, see `--text`.
+    pub(crate) force_text: bool,
+    pub(crate) detect_moves: bool,
+    pub(crate) detect_copies: bool,
+    pub(crate) move_min_tokens: usize,
+    /// The number of threads to use, or `None` to use one per CPU.
+    pub(crate) jobs: Option<usize>,
+    /// Where to cache diff results, see `--cache-dir`.
+    pub(crate) cache_dir: Option<PathBuf>,
+    /// Whether to mark obviously unchanged nodes before the tree
+    /// diff, see `unchanged::mark_unchanged`.
+    pub(crate) unchanged_heuristic: bool,
+    pub(crate) unchanged_min_run: u32,
+    pub(crate) unchanged_ngram: usize,
+    /// Whether to split the tree diff at nodes that are unique on
+    /// both sides, see `anchors::split_on_anchors`.
+    pub(crate) anchors: bool,
+    /// How long to spend on the structural diff of a file before
+    /// falling back to a text diff, see `--timeout`.
+    pub(crate) timeout: Option<Duration>,
+    /// Stop diffing when this is cancelled, e.g. by Ctrl-C while the
+    /// pager is running.
+    pub(crate) cancel: Option<CancellationToken>,
+    /// Whether to record the time taken by each phase of diffing
+    /// each file, see `--profile`.
+    pub(crate) profile: bool,
+    /// If set, files with one of these markers near the start are
+    /// only summarised, see `--collapse-generated`.
+    pub(crate) generated_markers: Option<Vec<Regex>>,
+    /// Whether directory diffs skip files matched by `.gitignore` and
+    /// `.ignore`, see `--no-ignore`.
+    pub(crate) respect_ignore_files: bool,
+    /// Whether directory diffs include dotfiles, see `--hidden`.
+    pub(crate) include_hidden: bool,
+    /// The files to diff in directories and argument files, see
+    /// `--include` and `--exclude`.
+    pub(crate) path_filter: PathFilter,
+    /// Whether directory diffs compare the files that symlinks point
+    /// to, rather than the link targets, see `--follow-symlinks`.
+    pub(crate) follow_symlinks: bool,
+    /// How many levels of subdirectories to diff in directory diffs,
+    /// see `--max-depth`.
+    pub(crate) max_depth: Option<usize>,
+    /// Whether directory diffs compare the mode of files, see
+    /// `--compare-metadata`.
+    pub(crate) compare_metadata: bool,
+    /// Whether directory diffs compare modification times, see
+    /// `--compare-mtime`.
+    pub(crate) compare_mtime: bool,
+    /// Whether directory diffs pair files whose paths only differ by
+    /// case, see `--ignore-path-case`.
+    pub(crate) ignore_path_case: bool,
+    /// Whether to diff the files in two archives, rather than the
+    /// archives themselves, see `--no-archive`.
+    pub(crate) diff_archives: bool,
 }
 
 impl Default for DiffOptions {
@@ -83,34 +406,87 @@ impl Default for DiffOptions {
         Self {
             graph_limit: DEFAULT_GRAPH_LIMIT,
             byte_limit: DEFAULT_BYTE_LIMIT,
+            byte_limit_overrides: vec![],
+            byte_limit_action: ByteLimitAction::Text,
             parse_error_limit: DEFAULT_PARSE_ERROR_LIMIT,
             check_only: false,
             ignore_comments: false,
+            ignore_punctuation: false,
+            whitespace: Whitespace::Exact,
+            prose_granularity: ProseGranularity::Line,
+            word_regex: None,
+            reformat_json: ReformatJson::Auto,
             strip_cr: false,
+            force_text: false,
+            detect_moves: false,
+            detect_copies: false,
+            move_min_tokens: DEFAULT_MOVE_MIN_TOKENS,
+            jobs: None,
+            cache_dir: None,
+            unchanged_heuristic: true,
+            unchanged_min_run: DEFAULT_UNCHANGED_MIN_RUN,
+            unchanged_ngram: DEFAULT_UNCHANGED_NGRAM,
+            anchors: true,
+            timeout: None,
+            cancel: None,
+            profile: false,
+            generated_markers: None,
+            respect_ignore_files: true,
+            include_hidden: false,
+            path_filter: PathFilter::default(),
+            follow_symlinks: false,
+            max_depth: None,
+            compare_metadata: false,
+            compare_mtime: false,
+            ignore_path_case: false,
+            diff_archives: true,
         }
     }
 }
 
+impl DiffOptions {
+    /// The byte limit for the file at `path`, from the first
+    /// `--byte-limit GLOB=LIMIT` that matches, or the plain
+    /// `--byte-limit` otherwise.
+    pub(crate) fn byte_limit_for(&self, path: &Path) -> usize {
+        self.byte_limit_overrides
+            .iter()
+            .find(|(pattern, _)| glob_matches_path(pattern, path))
+            .map_or(self.byte_limit, |(_, byte_limit)| *byte_limit)
+    }
+}
+
 fn app() -> clap::Command<'static> {
     Command::new("Difftastic")
         .override_usage(USAGE)
         .version(VERSION.as_str())
+        // We handle --version ourselves, so --version --verbose can
+        // print more.
+        .disable_version_flag(true)
         .about(crate_description!())
         .author(crate_authors!())
         .after_long_help(concat!(
             "You can compare two files with difftastic by specifying them as arguments.\n\n",
             "$ ",
-            env!("CARGO_BIN_NAME"),
+            bin_name!(),
             " old.js new.js\n\n",
             "You can also use directories as arguments. Difftastic will walk both directories and compare files with matching names.\n\n",
             "$ ",
-            env!("CARGO_BIN_NAME"),
+            bin_name!(),
             " old/ new/\n\n",
             "If you have a file with conflict markers, you can pass it as a single argument. Difftastic will diff the two conflicting file states.\n\n",
             "$ ",
-            env!("CARGO_BIN_NAME"),
+            bin_name!(),
             " file_with_conflicts.js\n\n",
+            "To diff many pairs of files at once, list them in a file with a tab between each pair, and pass it with @. Relative paths are relative to the file's directory.\n\n",
+            "$ ",
+            bin_name!(),
+            " @pairs.txt\n\n",
             "Difftastic can also be invoked with 7 arguments in the format that GIT_EXTERNAL_DIFF expects.\n\n",
+            "To use difftastic with Mercurial's extdiff extension, add the following to your hgrc and run `hg dft`.\n\n",
+            "[extensions]\nextdiff =\n\n[extdiff]\ncmd.dft = ",
+            bin_name!(),
+            "\nopts.dft = --hg\n\n",
             "See the full manual at: https://difftastic.wilfred.me.uk/")
         )
         .arg(
@@ -131,14 +507,113 @@ fn app() -> clap::Command<'static> {
                     "Parse a single file with tree-sitter and display the tree-sitter parse tree.",
                 ).help_heading("DEBUG OPTIONS"),
         )
+        .arg(
+            Arg::new("guess-language")
+                .long("guess-language")
+                .takes_value(true)
+                .value_name("PATH")
+                .long_help(
+                    "Print the language that difftastic would use for this file, taking --override and .gitattributes into account.",
+                ).help_heading("DEBUG OPTIONS"),
+        )
+        .arg(
+            Arg::new("dump-graph")
+                .long("dump-graph")
+                .takes_value(true)
+                .value_name("FORMAT")
+                .possible_values(["dot"])
+                .long_help(
+                    "Diff two files and write both syntax trees as a Graphviz graph, with edges between matched nodes. Novel nodes are outlined in red.",
+                ).help_heading("DEBUG OPTIONS"),
+        )
+        .arg(
+            Arg::new("dump-graph-depth")
+                .long("dump-graph-depth")
+                .takes_value(true)
+                .value_name("DEPTH")
+                .validator(|s| s.parse::<usize>())
+                .long_help(
+                    "Only include syntax nodes up to this depth in --dump-graph output. Useful for large files.",
+                ).help_heading("DEBUG OPTIONS"),
+        )
         .arg(
             Arg::new("context")
                 .long("context")
                 .takes_value(true)
                 .value_name("LINES")
-                .long_help("The number of contextual lines to show around changed lines.")
+                .long_help("The number of contextual lines to show around changed lines. This is a shorthand for setting both --before-context and --after-context.
+
+If this is 'function', show the whole declaration (e.g. the function) that contains each change, for languages that support --display=symbols. Very long declarations are truncated. This isn't supported by JSON or patch output.")
                 .default_value("3")
                 .env("DFT_CONTEXT")
+                .validator(parse_context)
+                .required(false),
+        )
+        .arg(
+            Arg::new("before-context")
+                .long("before-context")
+                .takes_value(true)
+                .value_name("LINES")
+                .long_help("The number of contextual lines to show before changed lines. Defaults to the value of --context.")
+                .env("DFT_BEFORE_CONTEXT")
+                .validator(|s| s.parse::<u32>())
+                .required(false),
+        )
+        .arg(
+            Arg::new("after-context")
+                .long("after-context")
+                .takes_value(true)
+                .value_name("LINES")
+                .long_help("The number of contextual lines to show after changed lines. Defaults to the value of --context.")
+                .env("DFT_AFTER_CONTEXT")
+                .validator(|s| s.parse::<u32>())
+                .required(false),
+        )
+        .arg(
+            Arg::new("hunk")
+                .long("hunk")
+                .takes_value(true)
+                .value_name("N")
+                .multiple_occurrences(true)
+                .validator(parse_hunk_range)
+                .help("Only display this hunk, counting from 1. This may be a range such as 2-4, and may be given more than once. When diffing directories, files that don't have this hunk are skipped.")
+        )
+        .arg(
+            Arg::new("lines")
+                .long("lines")
+                .takes_value(true)
+                .value_name("RANGE")
+                .validator(parse_line_ranges)
+                .help("Only display hunks that change these lines, e.g. 10-20. The whole file is still diffed. To give a range for each file, separate them with a comma, e.g. 10-20,12-25. A single range applies to the second file.")
+        )
+        .arg(
+            Arg::new("hunk-ranges")
+                .long("hunk-ranges")
+                .env("DFT_HUNK_RANGES")
+                .help("Print the lines shown in each hunk as a unified diff range, e.g. '@@ -120,15 +122,18 @@', after the hunk header.")
+        )
+        .arg(
+            Arg::new("max-hunk-lines")
+                .long("max-hunk-lines")
+                .takes_value(true)
+                .value_name("LINES")
+                .env("DFT_MAX_HUNK_LINES")
+                .validator(|s| s.parse::<usize>())
+                .help("Split hunks that span more than this many lines. Hunks are only split between separate changes.")
+        )
+        .arg(
+            Arg::new("structural-context")
+                .long("structural-context")
+                .env("DFT_STRUCTURAL_CONTEXT")
+                .help("Extend the context before each change (up to twice --before-context) so it starts at the beginning of a construct, rather than e.g. a '} else {' line.")
+        )
+        .arg(
+            Arg::new("merge-hunk-gap")
+                .long("merge-hunk-gap")
+                .takes_value(true)
+                .value_name("LINES")
+                .long_help("Merge hunks that are separated by this many unchanged lines or fewer, so they are shown under a single header. Defaults to the value of --context.")
+                .env("DFT_MERGE_HUNK_GAP")
                 .validator(|s| s.parse::<u32>())
                 .required(false),
         )
@@ -147,9 +622,11 @@ fn app() -> clap::Command<'static> {
                 .long("width")
                 .takes_value(true)
                 .value_name("COLUMNS")
-                .long_help("Use this many columns when calculating line wrapping. If not specified, difftastic will detect the terminal width.")
+                .long_help(formatcp!("Use this many columns when calculating line wrapping. If not specified, difftastic uses the COLUMNS environment variable, then the width of the terminal, then {}.
+
+With 'auto', difftastic ignores COLUMNS and asks the terminal for its width, even when the output is piped to a pager.", DEFAULT_TERMINAL_WIDTH))
                 .env("DFT_WIDTH")
-                .validator(|s| s.parse::<usize>())
+                .validator(parse_width)
                 .required(false),
         )
         .arg(
@@ -157,15 +634,19 @@ fn app() -> clap::Command<'static> {
                 .long("tab-width")
                 .takes_value(true)
                 .value_name("NUM_SPACES")
-                .long_help("Treat a tab as this many spaces.")
+                .long_help("Treat a tab as this many spaces.
+
+To set the tab width for one language, use LANG=NUM_SPACES, e.g. --tab-width Go=8. This may be given more than once, and a plain number sets the tab width for other languages.")
                 .env("DFT_TAB_WIDTH")
                 .default_value(formatcp!("{}", DEFAULT_TAB_WIDTH))
-                .validator(|s| s.parse::<usize>())
+                .multiple_occurrences(true)
+                .validator(parse_tab_width)
                 .required(false),
         )
         .arg(
             Arg::new("display").long("display")
-                .possible_values(["side-by-side", "side-by-side-show-both", "inline", "json"])
+                .visible_alias("output")
+                .possible_values(["side-by-side", "side-by-side-show-both", "inline", "json", "html", "patch", "stat", "sarif", "markdown", "json-patch", "symbols"])
                 .default_value("side-by-side")
                 .value_name("MODE")
                 .env("DFT_DISPLAY")
@@ -177,7 +658,48 @@ side-by-side-show-both: The same as side-by-side, but always uses two columns.
 
 inline: A single column display, closer to traditional diff display.
 
-json: Output the results as a machine-readable JSON array with an element per file.")
+json: Output the results as a machine-readable JSON array with an element per file.
+
+html: Output the results as a self-contained HTML document, with a side-by-side table for each file.
+
+patch: Output the results as a unified diff that can be applied with `patch` or `git apply`. Carriage returns are preserved, so the patch reproduces the new file exactly.
+
+stat: Show a summary of the number of changed lines in each file, similar to `git diff --stat`.
+
+sarif: Output the results as a SARIF 2.1.0 log, with a result for each changed region.
+
+markdown: Output the results as GitHub-flavored Markdown, with a collapsible section for each file. Suitable for pull request comments.
+
+json-patch: Output an RFC 6902 JSON Patch that transforms the old file into the new file. Both files must be JSON.
+
+symbols: Show which functions, classes and other declarations were changed in each file. For files without a known list of declarations, show the changed line ranges.")
+        )
+        .arg(
+            Arg::new("markdown-max-bytes").long("markdown-max-bytes")
+                .takes_value(true)
+                .value_name("LIMIT")
+                .env("DFT_MARKDOWN_MAX_BYTES")
+                .validator(|s| s.parse::<usize>())
+                .help("Truncate Markdown output so it doesn't exceed this many bytes, and add a note about the truncation. Useful for staying under the comment size limit of code review tools.")
+        )
+        .arg(
+            Arg::new("output-file").long("output-file")
+                .takes_value(true)
+                .value_name("PATH")
+                .allow_invalid_utf8(true)
+                .env("DFT_OUTPUT_FILE")
+                .help("Write the diff to this file rather than stdout, with any --display. Colors are off unless --color=always is given. Warnings are still printed to stderr.")
+        )
+        .arg(
+            Arg::new("pager").long("pager")
+                .possible_values(["always", "auto", "never"])
+                .default_value("auto")
+                .value_name("WHEN")
+                .help("When to pipe the output to a pager. 'auto' uses a pager when stdout is a TTY. The pager is $DFT_PAGER, then $PAGER, then 'less -RFX', which exits straight away if the output fits on one screen. Setting the pager to an empty string or 'cat' disables it.")
+        )
+        .arg(
+            Arg::new("interactive").long("interactive")
+                .help("Show one hunk at a time. Press n and p for the next and previous hunk, f and b for the next and previous file, and q to quit. Supports inline and side-by-side display. If stdin or stdout isn't a TTY, print the diff as usual.")
         )
         .arg(
             Arg::new("color").long("color")
@@ -191,23 +713,181 @@ json: Output the results as a machine-readable JSON array with an element per fi
             Arg::new("background").long("background")
                 .value_name("BACKGROUND")
                 .env("DFT_BACKGROUND")
-                .possible_values(["dark", "light"])
-                .default_value("dark")
-                .help("Set the background brightness. Difftastic will prefer brighter colours on dark backgrounds.")
+                .possible_values(["auto", "dark", "light"])
+                .default_value("auto")
+                .help("Set the background brightness. Difftastic will prefer brighter colours on dark backgrounds. auto asks the terminal for its background color, falling back to COLORFGBG and then to dark. Detection only happens when writing colored output to a TTY.
+
+Setting --theme also sets the background. The --color-* options override individual colors. Colors can be ANSI color names (e.g. red, bright-blue), 256-color palette indexes (e.g. 196), or hex RGB values (e.g. #00afff).")
+        )
+        .arg(
+            Arg::new("color-depth").long("color-depth")
+                .value_name("DEPTH")
+                .env("DFT_COLOR_DEPTH")
+                .possible_values(["16", "256", "24bit", "auto"])
+                .default_value("auto")
+                .help("The number of colors the terminal supports. Colors are converted to the nearest color the terminal can show. auto uses 24-bit color if COLORTERM is truecolor or 24bit, and otherwise 256 colors if TERM mentions 256color.")
+        )
+        .arg(
+            Arg::new("theme").long("theme")
+                .value_name("THEME")
+                .env("DFT_THEME")
+                .possible_values(["dark", "light", "solarized-dark", "solarized-light", "gruvbox", "dracula", "monochrome"])
+                .help("The color theme to use. Defaults to dark or light, depending on --background. monochrome only uses bold, underline and reverse video, for terminals without working color support.")
+        )
+        .arg(
+            Arg::new("color-novel-lhs").long("color-novel-lhs")
+                .takes_value(true)
+                .value_name("COLOR")
+                .env("DFT_COLOR_NOVEL_LHS")
+                .validator(parse_color)
+                .help("The color for removed content.")
+        )
+        .arg(
+            Arg::new("color-novel-rhs").long("color-novel-rhs")
+                .takes_value(true)
+                .value_name("COLOR")
+                .env("DFT_COLOR_NOVEL_RHS")
+                .validator(parse_color)
+                .help("The color for added content.")
+        )
+        .arg(
+            Arg::new("color-novel-word").long("color-novel-word")
+                .takes_value(true)
+                .value_name("COLOR")
+                .env("DFT_COLOR_NOVEL_WORD")
+                .validator(parse_color)
+                .help("The color for changed words inside changed lines. Defaults to the color of added or removed content.")
+        )
+        .arg(
+            Arg::new("color-line-number").long("color-line-number")
+                .takes_value(true)
+                .value_name("COLOR")
+                .env("DFT_COLOR_LINE_NUMBER")
+                .validator(parse_color)
+                .help("The color for line numbers of unchanged lines. Defaults to dimmed.")
+        )
+        .arg(
+            Arg::new("color-novel-line-number").long("color-novel-line-number")
+                .takes_value(true)
+                .value_name("COLOR")
+                .env("DFT_COLOR_NOVEL_LINE_NUMBER")
+                .validator(parse_color)
+                .help("The color for line numbers of changed lines. Defaults to the color of added or removed content.")
+        )
+        .arg(
+            Arg::new("color-header").long("color-header")
+                .takes_value(true)
+                .value_name("COLOR")
+                .env("DFT_COLOR_HEADER")
+                .validator(parse_color)
+                .help("The color for file headers.")
+        )
+        .arg(
+            Arg::new("color-comment").long("color-comment")
+                .takes_value(true)
+                .value_name("COLOR")
+                .env("DFT_COLOR_COMMENT")
+                .validator(parse_color)
+                .help("The color for comments.")
+        )
+        .arg(
+            Arg::new("color-string").long("color-string")
+                .takes_value(true)
+                .value_name("COLOR")
+                .env("DFT_COLOR_STRING")
+                .validator(parse_color)
+                .help("The color for string literals.")
+        )
+        .arg(
+            Arg::new("color-keyword").long("color-keyword")
+                .takes_value(true)
+                .value_name("COLOR")
+                .env("DFT_COLOR_KEYWORD")
+                .validator(parse_color)
+                .help("The color for keywords and types. Defaults to bold without a color.")
         )
         .arg(
             Arg::new("syntax-highlight").long("syntax-highlight")
-                .value_name("on/off")
+                .value_name("on/off/full")
                 .env("DFT_SYNTAX_HIGHLIGHT")
-                .possible_values(["on", "off"])
+                .possible_values(["on", "off", "full"])
                 .default_value("on")
-                .help("Enable or disable syntax highlighting.")
+                .help("Enable or disable syntax highlighting. 'full' also highlights types, function names and constants on unchanged lines, using the language's highlight queries.")
+        )
+        .arg(
+            Arg::new("accessibility").long("accessibility")
+                .env("DFT_ACCESSIBILITY")
+                .help("Strike through removed content and show added content in bold, so changes can be distinguished without color. With --color=never, these text attributes are the only styling used.")
         )
         .arg(
             Arg::new("exit-code").long("exit-code")
                 .env("DFT_EXIT_CODE")
                 .help("Set the exit code to 1 if there are syntactic changes in any files. For files where there is no detected language (e.g. unsupported language or binary files), sets the exit code if there are any byte changes.")
         )
+        .arg(
+            Arg::new("quiet").short('q').long("quiet")
+                .env("DFT_QUIET")
+                .help("Don't print anything, and exit with 1 if there are changes (implies --exit-code). When diffing directories, difftastic stops at the first file with changes.")
+        )
+        .arg(
+            Arg::new("fail-on-fallback").long("fail-on-fallback")
+                .env("DFT_FAIL_ON_FALLBACK")
+                .help("Exit with 3 if any file fell back to a text diff (e.g. parse errors, DFT_BYTE_LIMIT or DFT_GRAPH_LIMIT), or 4 if any file was only compared byte by byte (binary files, or directories beyond --max-depth). 4 takes precedence over 3, and both take precedence over 1 from --exit-code.")
+        )
+        .arg(
+            Arg::new("merge").long("merge")
+                .help("Show a three-way view of a merge. Takes three paths (base, ours and theirs), or a single file with diff3 style conflict markers. Changes from the base on each side are shown side by side, and changes to the same code on both sides are flagged as conflicts.")
+        )
+        .arg(
+            Arg::new("combined").long("combined")
+                .conflicts_with_all(&["merge", "watch"])
+                .help("Show what a merge commit did. Takes three paths (parent 1, parent 2 and the merged result). Each parent is diffed against the merged result, and changes are marked as coming from parent 1, from parent 2, or new in the merge.")
+        )
+        .arg(
+            Arg::new("from-patch").long("from-patch")
+                .conflicts_with_all(&["merge", "watch", "combined"])
+                .help("Read a patch in unified diff format, from the path given or from stdin, and show each file in it with a structural diff. Lines that aren't in the patch are unknown, so files with gaps between hunks are marked as partial.")
+        )
+        .arg(
+            Arg::new("git").long("git")
+                .takes_value(true)
+                .value_name("REV")
+                .min_values(1)
+                .max_values(2)
+                .conflicts_with_all(&["merge", "watch", "combined", "from-patch"])
+                .help(concat!("Compare files between two git revisions, or a revision and the working tree, without configuring git. Paths go after --, and directories show every changed file inside them. For example:
+
+$ ", bin_name!(), " --git HEAD~1 HEAD -- src/
+$ ", bin_name!(), " --git main -- README.md"))
+        )
+        .arg(
+            Arg::new("git-staged").long("git-staged")
+                .conflicts_with_all(&["merge", "watch", "combined", "from-patch", "git", "git-worktree"])
+                .help("Show the changes staged for the next commit, comparing HEAD with the git index. Positional arguments limit the diff to those paths.")
+        )
+        .arg(
+            Arg::new("git-worktree").long("git-worktree")
+                .conflicts_with_all(&["merge", "watch", "combined", "from-patch", "git"])
+                .help("Show the changes that aren't staged, comparing the git index with the working tree. Positional arguments limit the diff to those paths.")
+        )
+        .arg(
+            Arg::new("collapse-generated").long("collapse-generated")
+                .env("DFT_COLLAPSE_GENERATED")
+                .help("For generated files, only show a summary of the number of changed lines rather than the changes. A file is generated if one of its first few lines matches a --generated-marker.")
+        )
+        .arg(
+            Arg::new("generated-marker").long("generated-marker")
+                .takes_value(true)
+                .value_name("REGEX")
+                .multiple_occurrences(true)
+                .env("DFT_GENERATED_MARKER")
+                .validator(|s| Regex::new(s).map(|_| ()))
+                .help("A regular expression that marks a file as generated for --collapse-generated. May be given more than once. Replaces the default markers, which match Go's 'Code generated ... DO NOT EDIT.', the protobuf compiler's header and '@generated'.")
+        )
+        .arg(
+            Arg::new("watch").long("watch")
+                .help("Keep running, and show a new diff whenever either file changes. Parse trees are kept between diffs, so only the edited parts of a file are parsed again. Press Ctrl-C to stop.")
+        )
         .arg(
             Arg::new("strip-cr").long("strip-cr")
                 .value_name("on/off")
@@ -216,81 +896,457 @@ json: Output the results as a machine-readable JSON array with an element per fi
                 .default_value("on")
                 .help("Remove any carriage return characters before diffing. This can be helpful when dealing with files on Windows that contain CRLF, i.e. `\\r\\n`.\n\nWhen disabled, difftastic will consider multiline string literals (in code) or mutiline text (e.g. in HTML) to differ if the two input files have different line endings.")
         )
+        .arg(
+            Arg::new("text").long("text")
+                .env("DFT_TEXT")
+                .help("Treat all files as text, even if they look binary. Files that look like UTF-16 are decoded as UTF-16, and other invalid UTF-8 is replaced.")
+        )
+        .arg(
+            Arg::new("jobs").long("jobs")
+                .takes_value(true)
+                .value_name("N")
+                .env("DFT_JOBS")
+                .validator(parse_jobs)
+                .help("The number of threads to use when diffing directories and parsing files. Defaults to the number of CPUs. With 1, difftastic runs sequentially.")
+        )
+        .arg(
+            Arg::new("config").long("config")
+                .takes_value(true)
+                .value_name("PATH")
+                .env("DFT_CONFIG")
+                .allow_invalid_utf8(true)
+                .help("Read default values for options from this TOML file, rather than $XDG_CONFIG_HOME/difftastic/config.toml. Options on the command line and environment variables take precedence over the config file.")
+        )
+        .arg(
+            Arg::new("verbose").long("verbose")
+                .multiple_occurrences(true)
+                .env("DFT_VERBOSE")
+                .help("Print the language detected for each file to stderr, and why any file fell back to a text diff. Pass twice to also print the time, peak memory usage and graph statistics for each file.")
+        )
+        .arg(
+            Arg::new("log-format").long("log-format")
+                .possible_values(["text", "json"])
+                .default_value("text")
+                .env("DFT_LOG_FORMAT")
+                .value_name("FORMAT")
+                .help("The format of warnings and --verbose output on stderr. With json, each event is a JSON object on its own line.")
+        )
+        .arg(
+            Arg::new("profile").long("profile")
+                .env("DFT_PROFILE")
+                .help("Print a table to stderr of the time taken by each phase of diffing each file, with the number of nodes parsed, graph vertices explored, route length and bytes rendered. With --display=json, these are included under a \"profile\" key for each file instead.")
+        )
+        .arg(
+            Arg::new("cache-dir").long("cache-dir")
+                .takes_value(true)
+                .value_name("DIR")
+                .env("DFT_CACHE_DIR")
+                .allow_invalid_utf8(true)
+                .help("Cache diff results in this directory, so diffing the same files again doesn't need to parse them. Repeated diffs, such as running difftastic over git history, are much faster.")
+        )
+        .arg(
+            Arg::new("cache-clear").long("cache-clear")
+                .help("Remove all the cached diff results in --cache-dir, then exit.")
+        )
         .arg(
             Arg::new("check-only").long("check-only")
                 .env("DFT_CHECK_ONLY")
                 .help("Report whether there are any changes, but don't calculate them. Much faster.")
         )
         .arg(
-            Arg::new("ignore-comments").long("ignore-comments")
-                .env("DFT_IGNORE_COMMENTS")
-                .help("Don't consider comments when diffing.")
+            Arg::new("check").long("check")
+                .env("DFT_CHECK")
+                .help("Print the path of each file with changes, one per line, and exit with 1 if there are any. Like --check-only, this doesn't calculate the changes, so it's much faster than a full diff. --ignore-comments, --ignore-blank-lines and the whitespace options still apply.")
         )
         .arg(
-            Arg::new("skip-unchanged").long("skip-unchanged")
-                .env("DFT_SKIP_UNCHANGED")
-                .help("Don't display anything if a file is unchanged.")
+            Arg::new("ignore-all-space").short('w').long("ignore-all-space")
+                .env("DFT_IGNORE_ALL_SPACE")
+                .help("Ignore all whitespace when comparing lines in text diffs. Files are still shown as they are. Structural diffs already ignore whitespace between tokens.")
         )
         .arg(
-            Arg::new("missing-as-empty").long("missing-as-empty")
-                .help("Treat paths that don't exist as equivalent to an empty file. Only applies when diffing files, not directories.")
+            Arg::new("ignore-space-change").short('b').long("ignore-space-change")
+                .env("DFT_IGNORE_SPACE_CHANGE")
+                .help("Ignore changes in the amount of whitespace when comparing lines in text diffs, e.g. tabs instead of spaces or trailing whitespace. Files are still shown as they are.")
         )
         .arg(
-            Arg::new("override").long("override")
-                .value_name("GLOB:NAME")
-                .help(concat!("Associate this glob pattern with this language, overriding normal language detection. For example:
-
-$ ", env!("CARGO_BIN_NAME"), " --override='*.c:C++' old.c new.c
-
-See --list-languages for the list of language names. Language names are matched case insensitively. Overrides may also specify the language \"text\" to treat a file as plain text.
-
-This argument may be given more than once. For example:
-
-$ ", env!("CARGO_BIN_NAME"), " --override='CustomFile:json' --override='*.c:text' old.c new.c
-
-To configure multiple overrides using environment variables, difftastic also accepts DFT_OVERRIDE_1 up to DFT_OVERRIDE_9.
-
-$ export DFT_OVERRIDE='CustomFile:json'
-$ export DFT_OVERRIDE_1='*.c:text'
-$ export DFT_OVERRIDE_2='*.js:javascript jsx'
-
-When multiple overrides are specified, the first matching override wins."))
-                .env("DFT_OVERRIDE")
-                .multiple_occurrences(true)
+            Arg::new("prose-wrap-insensitive").long("prose-wrap-insensitive")
+                .env("DFT_PROSE_WRAP_INSENSITIVE")
+                .help("Diff text files as paragraphs of prose, so re-wrapping a paragraph at a different width isn't a change. Changed paragraphs are diffed word by word. Use --override='*.md:text' to diff Markdown this way. This is the same as --prose-granularity=word.")
         )
         .arg(
-            Arg::new("list-languages").long("list-languages")
-                .help("Print the all the languages supported by difftastic, along with their extensions.")
+            Arg::new("word-regex").long("word-regex")
+                .takes_value(true)
+                .value_name("REGEX")
+                .env("DFT_WORD_REGEX")
+                .validator(|s| Regex::new(s).map(|_| ()))
+                .help("A regular expression matching the words in text diffs, comments and strings, when showing which words changed. Everything between matches is a separator. For example, '[^,]+' only splits on commas, and \"[\\w']+\" keeps apostrophes in words. Words never span lines.")
         )
         .arg(
-            Arg::new("byte-limit").long("byte-limit")
+            Arg::new("prose-granularity").long("prose-granularity")
                 .takes_value(true)
-                .value_name("LIMIT")
-                .help("Use a text diff if either input file exceeds this size.")
-                .default_value(formatcp!("{}", DEFAULT_BYTE_LIMIT))
-                .env("DFT_BYTE_LIMIT")
-                .validator(|s| s.parse::<usize>())
-                .required(false),
+                .value_name("UNIT")
+                .possible_values(["line", "word", "sentence"])
+                .env("DFT_PROSE_GRANULARITY")
+                .help("How text diffs compare prose. 'line' compares lines, like other text. 'word' compares paragraphs by their words, regardless of where lines are wrapped, and highlights each changed word. 'sentence' compares sentences the same way, and highlights each changed sentence, emphasising the changed words. Defaults to 'line', or 'word' with --prose-wrap-insensitive.")
         )
         .arg(
-            Arg::new("graph-limit").long("graph-limit")
+            Arg::new("reformat-json").long("reformat-json")
                 .takes_value(true)
-                .value_name("LIMIT")
-                .help("Use a text diff if the structural graph exceed this number of nodes in memory.")
-                .default_value(formatcp!("{}", DEFAULT_GRAPH_LIMIT))
-                .env("DFT_GRAPH_LIMIT")
-                .validator(|s| s.parse::<usize>())
-                .required(false),
+                .value_name("WHEN")
+                .possible_values(["auto", "always", "never"])
+                .default_value("auto")
+                .env("DFT_REFORMAT_JSON")
+                .help("When to pretty-print JSON files before diffing, with each element of an object or array on its own line. This also applies to YAML files that are a single line in flow style, such as {a: 1, b: [2, 3]}. 'auto' only does this if either file has a line longer than 1000 bytes, e.g. minified JSON. Files that aren't valid are diffed as they are. Line numbers refer to the reformatted text.")
         )
         .arg(
-            Arg::new("parse-error-limit").long("parse-error-limit")
-                .takes_value(true)
-                .value_name("LIMIT")
-                .help("Use a text diff if the number of parse errors exceeds this value.")
-                .default_value(formatcp!("{}", DEFAULT_PARSE_ERROR_LIMIT))
-                .env("DFT_PARSE_ERROR_LIMIT")
-                .validator(|s| s.parse::<usize>())
-                .required(false),
+            Arg::new("ignore-blank-lines").long("ignore-blank-lines")
+                .env("DFT_IGNORE_BLANK_LINES")
+                .help("Ignore changes that only add or remove blank lines. Blank lines next to other changes are still shown.")
+        )
+        .arg(
+            Arg::new("ignore-comments").long("ignore-comments")
+                .env("DFT_IGNORE_COMMENTS")
+                .help("Don't consider comments when diffing.")
+        )
+        .arg(
+            Arg::new("detect-moves").long("detect-moves")
+                .env("DFT_DETECT_MOVES")
+                .help("Detect code that moved within a file, and show it dimmed rather than as a removal and an addition.")
+        )
+        .arg(
+            Arg::new("detect-copies").long("detect-copies")
+                .env("DFT_DETECT_COPIES")
+                .help("Detect added code that is almost the same as existing code, and only highlight the parts that differ from the original.")
+        )
+        .arg(
+            Arg::new("move-min-tokens").long("move-min-tokens")
+                .takes_value(true)
+                .value_name("TOKENS")
+                .help("With --detect-moves or --detect-copies, only consider regions with at least this many tokens as moved or copied.")
+                .default_value(formatcp!("{}", DEFAULT_MOVE_MIN_TOKENS))
+                .env("DFT_MOVE_MIN_TOKENS")
+                .validator(|s| s.parse::<usize>())
+                .required(false),
+        )
+        .arg(
+            Arg::new("novel-line-marker").long("novel-line-marker")
+                .takes_value(true)
+                .value_name("CHAR")
+                .env("DFT_NOVEL_LINE_MARKER")
+                .validator(parse_marker)
+                .help("Show this character before the line numbers of changed lines, e.g. '▌'. This is useful when color is disabled.")
+        )
+        .arg(
+            Arg::new("punctuation").long("punctuation")
+                .value_name("MODE")
+                .env("DFT_PUNCTUATION")
+                .possible_values(["normal", "dim", "ignore"])
+                .default_value("normal")
+                .help("How to show changes that only add or remove commas, semicolons or delimiters. 'dim' shows them with a dimmer highlight. 'ignore' doesn't consider them when diffing, so they don't affect --skip-unchanged or --exit-code.")
+        )
+        .arg(
+            Arg::new("show-invisible").long("show-invisible")
+                .value_name("WHEN")
+                .env("DFT_SHOW_INVISIBLE")
+                .possible_values(["auto", "always", "never"])
+                .default_value("auto")
+                .help("When to show characters that are hard to see in changed text, such as non-breaking spaces, zero-width spaces and curly quotes, as visible escapes. 'auto' only does this on lines that would look the same as the other side, and also shows spaces as ␣ and tabs as →.")
+        )
+        .arg(
+            Arg::new("show-trailing-whitespace").long("show-trailing-whitespace")
+                .value_name("WHEN")
+                .env("DFT_SHOW_TRAILING_WHITESPACE")
+                .possible_values(["auto", "always", "never"])
+                .default_value("auto")
+                .help("When to make added or removed trailing whitespace visible, by reversing its highlighting, or with · and ⇥ when color is off. 'auto' only does this on lines where the change is entirely whitespace.")
+        )
+        .arg(
+            Arg::new("skip-unchanged").long("skip-unchanged")
+                .env("DFT_SKIP_UNCHANGED")
+                .help("Don't display anything if a file is unchanged.")
+        )
+        .arg(
+            Arg::new("skip-binary").long("skip-binary")
+                .env("DFT_SKIP_BINARY")
+                .help("Don't display binary files, even if they changed, and don't count them for --exit-code or --fail-on-fallback.")
+        )
+        .arg(
+            Arg::new("no-ignore").long("no-ignore")
+                .env("DFT_NO_IGNORE")
+                .help("When diffing directories, include files that are matched by .gitignore or .ignore files. Version control metadata, such as .git directories, is always skipped.")
+        )
+        .arg(
+            Arg::new("hidden").long("hidden")
+                .env("DFT_HIDDEN")
+                .help("When diffing directories, include files and directories whose names start with a dot.")
+        )
+        .arg(
+            Arg::new("follow-symlinks").long("follow-symlinks")
+                .env("DFT_FOLLOW_SYMLINKS")
+                .help("When diffing directories, compare the files and directories that symlinks point to. By default, symlinks are compared by the paths they point to. Symlinks that would loop are reported and not followed.")
+        )
+        .arg(
+            Arg::new("compare-metadata").long("compare-metadata")
+                .env("DFT_COMPARE_METADATA")
+                .help("When diffing directories, also compare the mode of each file, such as its executable bit. Files with identical content but a different mode are reported as changed.")
+        )
+        .arg(
+            Arg::new("compare-mtime").long("compare-mtime")
+                .env("DFT_COMPARE_MTIME")
+                .help("When diffing directories, also compare the modification time of each file, to the second. Files with identical content but a different modification time are reported as changed.")
+        )
+        .arg(
+            Arg::new("ignore-path-case").long("ignore-path-case")
+                .env("DFT_IGNORE_PATH_CASE")
+                .help("When diffing directories, pair a file that only exists on one side with a file on the other side whose path only differs by case, such as Readme.md and README.md. The change of case is shown like a rename. If both casings exist on one side, difftastic warns and compares them by their exact paths.")
+        )
+        .arg(
+            Arg::new("no-archive").long("no-archive")
+                .env("DFT_NO_ARCHIVE")
+                .help("Diff zip files and tarballs as binary files. By default, when both files are zips, or both are tarballs (.tar, .tar.gz or .tar.zst), difftastic diffs the files inside them like a directory diff. Archives that expand to more than 512 MiB or 100,000 files are always diffed as binary files.")
+        )
+        .arg(
+            Arg::new("no-summary").long("no-summary")
+                .env("DFT_NO_SUMMARY")
+                .help("When diffing directories, patches or several files, don't print the number of changed, unchanged, binary and text fallback files to stderr at the end. Single files are never summarised.")
+        )
+        .arg(
+            Arg::new("include").long("include")
+                .takes_value(true)
+                .multiple_occurrences(true)
+                .value_name("GLOB")
+                .help("When diffing directories or an argument file, only diff files that match this glob. A glob without a '/', such as '*.rs', matches the name of a file or of any directory containing it. A glob with a '/', such as 'src/**/*.rs', matches the whole relative path. May be given more than once.")
+        )
+        .arg(
+            Arg::new("exclude").long("exclude")
+                .takes_value(true)
+                .multiple_occurrences(true)
+                .value_name("GLOB")
+                .help("When diffing directories or an argument file, skip files that match this glob, e.g. '*.lock' or 'vendor/**'. Globs are matched like --include, and --exclude takes precedence. May be given more than once.")
+        )
+        .arg(
+            Arg::new("max-depth").long("max-depth")
+                .takes_value(true)
+                .value_name("N")
+                .env("DFT_MAX_DEPTH")
+                .validator(|s| s.parse::<usize>())
+                .help("When diffing directories, only diff files up to N levels of subdirectories deep. With 0, only the files directly in the directories are diffed. Deeper subdirectories are only reported as differing or identical.")
+        )
+        .arg(
+            Arg::new("missing-as-empty").long("missing-as-empty")
+                .env("DFT_MISSING_AS_EMPTY")
+                .help("Treat paths that don't exist as equivalent to an empty file. Only applies when diffing files, not directories.")
+        )
+        .arg(
+            Arg::new("new-file").long("new-file")
+                .env("DFT_NEW_FILE")
+                .help("When diffing directories, show the whole content of files that only exist on one side, as additions or removals. Otherwise they're listed with their line count.")
+        )
+        .arg(
+            Arg::new("label-left").long("label-left")
+                .takes_value(true)
+                .value_name("LABEL")
+                .help("Show this name for the first file, rather than its path. Useful when reading the file from stdin with '-'.")
+        )
+        .arg(
+            // `svn diff --diff-cmd` passes `-u -L OLD-LABEL -L
+            // NEW-LABEL OLD-PATH NEW-PATH`, like GNU diff.
+            Arg::new("unified").short('u')
+                .hide(true)
+        )
+        // `p4 diff -dc` and similar pass the diff format flags to
+        // $P4DIFF. -b and -w are real options.
+        .arg(
+            Arg::new("context-format").short('c')
+                .hide(true)
+        )
+        .arg(
+            Arg::new("rcs-format").short('n')
+                .hide(true)
+        )
+        .arg(
+            Arg::new("summary-format").short('s')
+                .hide(true)
+        )
+        .arg(
+            Arg::new("ignore-line-endings").short('l')
+                .hide(true)
+        )
+        .arg(
+            Arg::new("svn-label").short('L')
+                .takes_value(true)
+                .multiple_occurrences(true)
+                .value_name("LABEL")
+                .hide(true)
+        )
+        .arg(
+            Arg::new("label-right").long("label-right")
+                .takes_value(true)
+                .value_name("LABEL")
+                .help("Show this name for the second file, rather than its path. Useful when reading the file from stdin with '-'.")
+        )
+        .arg(
+            Arg::new("strip-prefix").long("strip-prefix")
+                .env("DFT_STRIP_PREFIX")
+                .help("Remove the longest leading directory shared by the two paths from the name shown for each file. Useful when comparing files from two copies of a project in temporary directories.")
+        )
+        .arg(
+            Arg::new("prefix-left").long("prefix-left")
+                .takes_value(true)
+                .value_name("PREFIX")
+                .allow_invalid_utf8(true)
+                .help("Remove this leading directory from the first path in the name shown for each file. Takes precedence over --strip-prefix.")
+        )
+        .arg(
+            Arg::new("prefix-right").long("prefix-right")
+                .takes_value(true)
+                .value_name("PREFIX")
+                .allow_invalid_utf8(true)
+                .help("Remove this leading directory from the second path in the name shown for each file. Takes precedence over --strip-prefix.")
+        )
+        .arg(
+            Arg::new("hg").long("hg")
+                .help("Treat the arguments as snapshots from Mercurial's extdiff extension. Headers show the path in the repository and the changesets being compared. This is detected automatically for snapshots in the temporary directory.")
+        )
+        .arg(
+            Arg::new("textconv").long("textconv")
+                .takes_value(true)
+                .value_name("WHEN")
+                .env("DFT_TEXTCONV")
+                .possible_values(["auto", "never"])
+                .default_value("never")
+                .help("When git runs difftastic as an external diff, run the textconv filter for each file, as set by its diff attribute and diff.DRIVER.textconv, and diff the converted text. If a filter fails, the files are diffed as they are. Converted blobs are cached in --cache-dir.")
+        )
+        .arg(
+            Arg::new("jj").long("jj")
+                .help("Treat the arguments as the $left and $right directories from Jujutsu's `jj diff --tool`. Don't use the pager or --interactive, as jj pages the output itself. This is detected automatically for jj's temporary directories.")
+        )
+        .arg(
+            Arg::new("language").long("language")
+                .takes_value(true)
+                .value_name("NAME")
+                .help("Treat the input files as this language, rather than guessing from the file name and content. Takes precedence over --override. See --list-languages for the list of language names.")
+        )
+        .arg(
+            Arg::new("override").long("override")
+                .value_name("GLOB:NAME")
+                .help(concat!("Associate this glob pattern with this language, overriding normal language detection. For example:
+
+$ ", bin_name!(), " --override='*.c:C++' old.c new.c
+
+See --list-languages for the list of language names. Language names are matched case insensitively. Overrides may also specify the language \"text\" to treat a file as plain text.
+
+This argument may be given more than once. For example:
+
+$ ", bin_name!(), " --override='CustomFile:json' --override='*.c:text' old.c new.c
+
+To configure multiple overrides using environment variables, separate them with ';' in DFT_OVERRIDE. Difftastic also accepts DFT_OVERRIDE_1 up to DFT_OVERRIDE_9.
+
+$ export DFT_OVERRIDE='CustomFile:json;*.h:C++'
+$ export DFT_OVERRIDE_1='*.c:text'
+$ export DFT_OVERRIDE_2='*.js:javascript jsx'
+
+When multiple overrides are specified, the first matching override wins."))
+                .env("DFT_OVERRIDE")
+                .multiple_occurrences(true)
+        )
+        .arg(
+            Arg::new("version").short('V').long("version")
+                .help("Print version information. With --verbose, also print the commit difftastic was built from and the version of each bundled tree-sitter grammar.")
+        )
+        .arg(
+            Arg::new("list-languages").long("list-languages")
+                .help("Print the all the languages supported by difftastic, along with their extensions.\n\nWith --display=json, print a JSON array of languages sorted by name, with their extensions, file names and whether syntax highlighting is supported.")
+        )
+        .arg(
+            Arg::new("byte-limit").long("byte-limit")
+                .takes_value(true)
+                .value_name("LIMIT")
+                .long_help(formatcp!("Use a text diff if either input file exceeds this size, in bytes. Defaults to {}. See --byte-limit-action for other ways to handle larger files.
+
+To set the limit for some files, use GLOB=LIMIT, e.g. --byte-limit 'schema.json=20000000'. Globs match paths like --include. This may be given more than once, the first matching glob wins, and a plain number sets the limit for other files.", DEFAULT_BYTE_LIMIT))
+                .env("DFT_BYTE_LIMIT")
+                .multiple_occurrences(true)
+                .validator(parse_byte_limit)
+                .required(false),
+        )
+        .arg(
+            Arg::new("byte-limit-action").long("byte-limit-action")
+                .takes_value(true)
+                .value_name("ACTION")
+                .possible_values(["text", "error", "truncate"])
+                .default_value("text")
+                .env("DFT_BYTE_LIMIT_ACTION")
+                .help("What to do when a file that difftastic would parse exceeds --byte-limit. 'text' uses a text diff, 'error' stops with an error, and 'truncate' structurally diffs only the start of each side, up to the limit and cut at the end of a line. The header of a truncated file says how much was omitted.")
+        )
+        .arg(
+            Arg::new("graph-limit").long("graph-limit")
+                .takes_value(true)
+                .value_name("LIMIT")
+                .help("If the structural graph exceeds this number of nodes in memory, diff subtrees separately, and use a token diff for subtrees that are still too large.")
+                .default_value(formatcp!("{}", DEFAULT_GRAPH_LIMIT))
+                .env("DFT_GRAPH_LIMIT")
+                .validator(|s| s.parse::<usize>())
+                .required(false),
+        )
+        .arg(
+            Arg::new("timeout").long("timeout")
+                .takes_value(true)
+                .value_name("SECONDS")
+                .env("DFT_TIMEOUT")
+                .validator(parse_timeout)
+                .help("If the structural diff of a file takes longer than this, stop and use a text diff for that file instead.")
+        )
+        .arg(
+            Arg::new("no-anchors").long("no-anchors")
+                .env("DFT_NO_ANCHORS")
+                .help("Don't treat nodes that occur exactly once on each side as fixed matches before the tree diff. This is slower on large files, but may find a better diff when code has moved.")
+        )
+        .arg(
+            Arg::new("unchanged-min-run").long("unchanged-min-run")
+                .takes_value(true)
+                .value_name("NODES")
+                .help("Only mark a node as obviously unchanged before the tree diff if it has at least this many descendants. Lower values make diffing faster but can split changes unnecessarily.")
+                .default_value(formatcp!("{}", DEFAULT_UNCHANGED_MIN_RUN))
+                .env("DFT_UNCHANGED_MIN_RUN")
+                .validator(|s| s.parse::<u32>())
+                .hide(true)
+                .required(false),
+        )
+        .arg(
+            Arg::new("unchanged-ngram").long("unchanged-ngram")
+                .takes_value(true)
+                .value_name("SUBTREES")
+                .help("Diff top-level lists separately if they share at least this many unique subtrees.")
+                .default_value(formatcp!("{}", DEFAULT_UNCHANGED_NGRAM))
+                .env("DFT_UNCHANGED_NGRAM")
+                .validator(|s| s.parse::<usize>())
+                .hide(true)
+                .required(false),
+        )
+        .arg(
+            Arg::new("no-unchanged-heuristic").long("no-unchanged-heuristic")
+                .env("DFT_NO_UNCHANGED_HEURISTIC")
+                .help("Don't mark obviously unchanged nodes before the tree diff. This is slower, but useful for checking whether the heuristic makes a diff worse.")
+                .hide(true)
+        )
+        .arg(
+            Arg::new("generate-completions").long("generate-completions")
+                .takes_value(true)
+                .value_name("SHELL")
+                .possible_values(["bash", "zsh", "fish", "powershell"])
+                .help("Print a completion script for this shell to stdout, then exit.")
+                .hide(true)
+        )
+        .arg(
+            Arg::new("parse-error-limit").long("parse-error-limit")
+                .takes_value(true)
+                .value_name("LIMIT")
+                .help("Use a text diff if the number of parse errors exceeds this value.")
+                .default_value(formatcp!("{}", DEFAULT_PARSE_ERROR_LIMIT))
+                .env("DFT_PARSE_ERROR_LIMIT")
+                .validator(|s| s.parse::<usize>())
+                .required(false),
         )
         .arg(
             Arg::new("paths")
@@ -299,10 +1355,20 @@ When multiple overrides are specified, the first matching override wins."))
                 .hide(true)
                 .allow_invalid_utf8(true),
         )
+        .arg(
+            Arg::new("null").long("null")
+                .help("The argument file given with @PATH separates paths with NUL characters rather than tabs and newlines, like `find -print0`. Consecutive paths form a pair.")
+        )
         .arg(
             Arg::new("sort-paths").long("sort-paths")
+                .takes_value(true)
+                .value_name("ORDER")
+                .min_values(0)
+                .require_equals(true)
+                .default_missing_value("path")
+                .possible_values(["path", "size", "changes"])
                 .env("DFT_SORT_PATHS")
-                .help("When diffing a directory, output the results sorted by path. This is slower.")
+                .help("When diffing a directory, the order of the files in the output. Files are always sorted by path, comparing bytes, unless this is 'size' (largest files first) or 'changes' (files with the most changed lines first). Sorting by size or changes waits for every file to be diffed.")
         )
         .arg_required_else_help(true)
 }
@@ -313,9 +1379,18 @@ pub(crate) enum DisplayMode {
     SideBySide,
     SideBySideShowBoth,
     Json,
+    Html,
+    Patch,
+    Stat,
+    Sarif,
+    Markdown,
+    JsonPatch,
+    Symbols,
+    /// Only list the changed files, see `--check`.
+    Check,
 }
 
-#[derive(Eq, PartialEq, Debug)]
+#[derive(Eq, PartialEq, Debug, Clone)]
 pub(crate) enum FileArgument {
     NamedPath(std::path::PathBuf),
     Stdin,
@@ -357,14 +1432,24 @@ impl Display for FilePermissions {
     }
 }
 
+impl FilePermissions {
+    /// The mode of a symlink, in the same format as git.
+    pub(crate) fn symlink() -> Self {
+        Self(SYMLINK_MODE.to_owned())
+    }
+}
+
 impl TryFrom<&OsStr> for FilePermissions {
     type Error = ();
 
+    /// Parse a mode passed by git, such as `100644`. Git passes `.`
+    /// when the file doesn't exist on that side.
     fn try_from(s: &OsStr) -> Result<Self, Self::Error> {
-        if s == "." {
-            Err(())
+        let s = s.to_str().ok_or(())?;
+        if !s.is_empty() && s.bytes().all(|b| (b'0'..=b'7').contains(&b)) {
+            Ok(Self(s.to_owned()))
         } else {
-            Ok(Self(s.to_string_lossy().into_owned()))
+            Err(())
         }
     }
 }
@@ -406,11 +1491,17 @@ fn relative_to_current(path: &Path) -> PathBuf {
     path.into()
 }
 
+/// Is `arg` the null device? Git uses `/dev/null` on every platform,
+/// and Windows tools use `NUL`.
+fn is_null_path(arg: &OsStr) -> bool {
+    arg == "/dev/null" || (cfg!(windows) && arg.eq_ignore_ascii_case("NUL"))
+}
+
 impl FileArgument {
     /// Return a `FileArgument` representing this command line
     /// argument.
     pub(crate) fn from_cli_argument(arg: &OsStr) -> Self {
-        if arg == "/dev/null" {
+        if is_null_path(arg) {
             FileArgument::DevNull
         } else if arg == "-" {
             FileArgument::Stdin
@@ -423,7 +1514,7 @@ impl FileArgument {
     /// exists, with the exception of `/dev/null`, which is turned into [FileArgument::DevNull].
     pub(crate) fn from_path_argument(arg: &OsStr) -> Self {
         // For new and deleted files, Git passes `/dev/null` as the reference file.
-        if arg == "/dev/null" {
+        if is_null_path(arg) {
             FileArgument::DevNull
         } else {
             FileArgument::NamedPath(PathBuf::from(arg))
@@ -443,11 +1534,37 @@ impl Display for FileArgument {
     }
 }
 
+/// The versions of a file to compare with `--merge`.
+pub(crate) enum MergeInput {
+    /// The common ancestor and the two versions being merged.
+    Files {
+        base: FileArgument,
+        ours: FileArgument,
+        theirs: FileArgument,
+    },
+    /// A file with diff3 style conflict markers, which include the
+    /// base version.
+    Conflicts(FileArgument),
+    /// The two parents of a merge commit and the merged result, for
+    /// `--combined`.
+    Combined {
+        parent1: FileArgument,
+        parent2: FileArgument,
+        merged: FileArgument,
+    },
+}
+
 pub(crate) enum Mode {
     Diff {
         diff_options: DiffOptions,
         display_options: DisplayOptions,
         set_exit_code: bool,
+        /// Whether files that fell back to a text diff, or were only
+        /// compared byte by byte, set the exit code. See
+        /// `--fail-on-fallback`.
+        fail_on_fallback: bool,
+        /// Don't print the diff, only set the exit code.
+        quiet: bool,
         language_overrides: Vec<(LanguageOverride, Vec<glob::Pattern>)>,
         /// The path where we can read the LHS file. This is often a
         /// temporary file generated by source control.
@@ -459,31 +1576,149 @@ pub(crate) enum Mode {
         rhs_permissions: Option<FilePermissions>,
         /// The path that we show to the user.
         display_path: String,
-        /// If this file has been renamed, a description of the change.
-        renamed: Option<String>,
+        /// The previous path of this file, if git reported that it
+        /// was renamed.
+        renamed_from: Option<String>,
+        /// Additional information to show in the header of every
+        /// file, such as the Mercurial changesets being compared.
+        extra_info: Option<String>,
+        /// The paths and blobs passed by git, if we should run
+        /// textconv filters, see `--textconv`.
+        textconv: Option<(GitSide, GitSide)>,
+        /// Where to write the diff.
+        output: Output,
     },
     DiffFromConflicts {
         diff_options: DiffOptions,
         display_options: DisplayOptions,
         set_exit_code: bool,
+        /// Whether files that fell back to a text diff, or were only
+        /// compared byte by byte, set the exit code. See
+        /// `--fail-on-fallback`.
+        fail_on_fallback: bool,
+        /// Don't print the diff, only set the exit code.
+        quiet: bool,
         language_overrides: Vec<(LanguageOverride, Vec<glob::Pattern>)>,
         path: FileArgument,
         /// The path that we show to the user.
         display_path: String,
+        /// Where to write the diff.
+        output: Output,
+    },
+    /// Diff each pair of files listed in an argument file, `difft
+    /// @PATH`.
+    DiffPairs {
+        diff_options: DiffOptions,
+        display_options: DisplayOptions,
+        set_exit_code: bool,
+        /// Whether files that fell back to a text diff, or were only
+        /// compared byte by byte, set the exit code. See
+        /// `--fail-on-fallback`.
+        fail_on_fallback: bool,
+        /// Don't print the diff, only set the exit code.
+        quiet: bool,
+        language_overrides: Vec<(LanguageOverride, Vec<glob::Pattern>)>,
+        /// The display path and the paths to read for each pair.
+        pairs: Vec<(String, FileArgument, FileArgument)>,
+        /// Where to write the diff.
+        output: Output,
+    },
+    /// Diff the files reconstructed from a patch, `difft
+    /// --from-patch PATH`.
+    FromPatch {
+        diff_options: DiffOptions,
+        display_options: DisplayOptions,
+        set_exit_code: bool,
+        /// Whether files that fell back to a text diff, or were only
+        /// compared byte by byte, set the exit code. See
+        /// `--fail-on-fallback`.
+        fail_on_fallback: bool,
+        /// Don't print the diff, only set the exit code.
+        quiet: bool,
+        language_overrides: Vec<(LanguageOverride, Vec<glob::Pattern>)>,
+        /// The patch to read, which may be stdin.
+        patch: FileArgument,
+        /// Where to write the diff.
+        output: Output,
+    },
+    /// Diff files between two git revisions, `difft --git REV1 REV2
+    /// -- PATH...`, or with `--git-staged` or `--git-worktree`.
+    GitRevisions {
+        diff_options: DiffOptions,
+        display_options: DisplayOptions,
+        set_exit_code: bool,
+        /// Whether files that fell back to a text diff, or were only
+        /// compared byte by byte, set the exit code. See
+        /// `--fail-on-fallback`.
+        fail_on_fallback: bool,
+        /// Don't print the diff, only set the exit code.
+        quiet: bool,
+        language_overrides: Vec<(LanguageOverride, Vec<glob::Pattern>)>,
+        lhs: git::Source,
+        rhs: git::Source,
+        /// Only diff files under these paths. Empty means every file.
+        paths: Vec<PathBuf>,
+        /// Where to write the diff.
+        output: Output,
+    },
+    ClearCache {
+        cache_dir: PathBuf,
+    },
+    Merge {
+        diff_options: DiffOptions,
+        display_options: DisplayOptions,
+        language_overrides: Vec<(LanguageOverride, Vec<glob::Pattern>)>,
+        input: MergeInput,
+        /// The path that we show to the user.
+        display_path: String,
+        /// Where to write the diff.
+        output: Output,
+    },
+    Watch {
+        diff_options: DiffOptions,
+        display_options: DisplayOptions,
+        language_overrides: Vec<(LanguageOverride, Vec<glob::Pattern>)>,
+        lhs_path: PathBuf,
+        rhs_path: PathBuf,
+        /// The path that we show to the user.
+        display_path: String,
+    },
+    Version {
+        /// Print the commit and the grammar versions too, with
+        /// `--verbose`.
+        verbose: bool,
     },
     ListLanguages {
         use_color: bool,
         language_overrides: Vec<(LanguageOverride, Vec<glob::Pattern>)>,
+        /// Print the languages as JSON, with `--display=json`.
+        json: bool,
+    },
+    GenerateCompletions {
+        shell: Shell,
     },
     DumpTreeSitter {
         path: String,
         language_overrides: Vec<(LanguageOverride, Vec<glob::Pattern>)>,
     },
+    GuessLanguage {
+        path: String,
+        language_overrides: Vec<(LanguageOverride, Vec<glob::Pattern>)>,
+    },
     DumpSyntax {
         path: String,
         ignore_comments: bool,
         language_overrides: Vec<(LanguageOverride, Vec<glob::Pattern>)>,
     },
+    DumpGraph {
+        lhs_path: String,
+        rhs_path: String,
+        ignore_comments: bool,
+        graph_limit: usize,
+        /// Don't show syntax nodes deeper than this.
+        max_depth: Option<usize>,
+        language_overrides: Vec<(LanguageOverride, Vec<glob::Pattern>)>,
+    },
 }
 
 fn common_path_suffix(lhs_path: &Path, rhs_path: &Path) -> Option<String> {
@@ -501,7 +1736,7 @@ fn common_path_suffix(lhs_path: &Path, rhs_path: &Path) -> Option<String> {
     let mut common_components = vec![];
     for (lhs_component, rhs_component) in lhs_rev_components.iter().zip(rhs_rev_components.iter()) {
         if lhs_component == rhs_component {
-            common_components.push(lhs_component.to_string_lossy());
+            common_components.push(escape_path(Path::new(lhs_component)));
         } else {
             break;
         }
@@ -532,26 +1767,402 @@ fn is_git_tmpfile(path: &Path) -> bool {
         .starts_with("git-blob-")
 }
 
-fn build_display_path(lhs_path: &FileArgument, rhs_path: &FileArgument) -> String {
-    match (lhs_path, rhs_path) {
-        (FileArgument::NamedPath(lhs), FileArgument::NamedPath(rhs)) => {
-            if is_git_tmpfile(lhs) {
-                return rhs.display().to_string();
-            }
+/// Are `lhs_dir` and `rhs_dir` the "left" and "right" directories
+/// inside a temporary directory whose name starts with `prefix`?
+fn is_left_right_dirs(lhs_dir: &Path, rhs_dir: &Path, prefix: &str) -> bool {
+    let (Some(lhs_parent), Some(rhs_parent)) = (lhs_dir.parent(), rhs_dir.parent()) else {
+        return false;
+    };
 
-            match common_path_suffix(lhs, rhs) {
-                Some(common_suffix) => common_suffix,
-                None => rhs.display().to_string(),
-            }
-        }
-        (FileArgument::NamedPath(p), _) | (_, FileArgument::NamedPath(p)) => {
-            p.display().to_string()
-        }
-        (FileArgument::DevNull, _) | (_, FileArgument::DevNull) => "/dev/null".into(),
+    lhs_parent == rhs_parent
+        && lhs_dir.file_name() == Some(OsStr::new("left"))
+        && rhs_dir.file_name() == Some(OsStr::new("right"))
+        && lhs_parent
+            .file_name()
+            .map_or(false, |name| name.to_string_lossy().starts_with(prefix))
+}
+
+/// Do `lhs_dir` and `rhs_dir` look like
+/// "/tmp/git-difftool.AbCdEf/left" and "/tmp/git-difftool.AbCdEf/right"?
+/// These are the directories passed by `git difftool --dir-diff`.
+pub(crate) fn is_git_dir_diff(lhs_dir: &Path, rhs_dir: &Path) -> bool {
+    is_left_right_dirs(lhs_dir, rhs_dir, "git-difftool.")
+}
+
+/// Do `lhs_dir` and `rhs_dir` look like "/tmp/jj-diff-AbCdEf/left"
+/// and "/tmp/jj-diff-AbCdEf/right"? These are the `$left` and
+/// `$right` directories passed by Jujutsu's `jj diff --tool`.
+fn is_jj_diff(lhs_dir: &Path, rhs_dir: &Path) -> bool {
+    is_left_right_dirs(lhs_dir, rhs_dir, "jj-diff-")
+}
+
+/// The amount of context requested with `--context`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Context {
+    Lines(u32),
+    /// Show the whole enclosing declaration.
+    Function,
+}
+
+fn parse_context(s: &str) -> Result<Context, String> {
+    if s == "function" {
+        return Ok(Context::Function);
+    }
+    s.parse::<u32>()
+        .map(Context::Lines)
+        .map_err(|_| format!("'{}' is not a number or 'function'", s))
+}
+
+/// Parse the number of threads for `--jobs`.
+fn parse_jobs(s: &str) -> Result<usize, String> {
+    match s.parse::<usize>() {
+        Ok(jobs) if jobs > 0 => Ok(jobs),
+        _ => Err(format!("'{}' is not a positive number of jobs", s)),
+    }
+}
+
+fn parse_timeout(s: &str) -> Result<Duration, String> {
+    match s.parse::<f64>() {
+        Ok(secs) if secs > 0.0 && secs < u32::MAX as f64 => Ok(Duration::from_secs_f64(secs)),
+        _ => Err(format!("'{}' is not a positive number of seconds", s)),
+    }
+}
+
+/// Parse a `--hunk` value, which is a hunk number or an inclusive
+/// range such as `2-4`.
+fn parse_hunk_range(s: &str) -> Result<(usize, usize), String> {
+    let parse_num = |s: &str| match s.parse::<usize>() {
+        Ok(n) if n > 0 => Ok(n),
+        _ => Err(format!(
+            "'{}' is not a hunk number, expected e.g. 3 or 2-4",
+            s
+        )),
+    };
+
+    match s.split_once('-') {
+        Some((start, end)) => {
+            let (start, end) = (parse_num(start)?, parse_num(end)?);
+            if start > end {
+                return Err(format!("'{}' is an empty range of hunks", s));
+            }
+            Ok((start, end))
+        }
+        None => {
+            let n = parse_num(s)?;
+            Ok((n, n))
+        }
+    }
+}
+
+/// Parse a `--byte-limit` value, which is a number of bytes, or
+/// `GLOB=LIMIT` for files matching a glob.
+fn parse_byte_limit(s: &str) -> Result<(Option<glob::Pattern>, usize), String> {
+    let (pattern, limit) = match s.rsplit_once('=') {
+        Some((glob_str, limit)) => match glob::Pattern::new(glob_str) {
+            Ok(pattern) => (Some(pattern), limit),
+            Err(e) => return Err(format!("Invalid glob '{}': {}", glob_str, e.msg)),
+        },
+        None => (None, s),
+    };
+
+    match limit.parse::<usize>() {
+        Ok(limit) => Ok((pattern, limit)),
+        Err(_) => Err(format!("'{}' is not a number of bytes", limit)),
+    }
+}
+
+/// Parse a `--tab-width` value, which is a number of spaces, or
+/// `LANG=NUM_SPACES` for files in one language.
+fn parse_tab_width(s: &str) -> Result<(Option<LanguageOverride>, usize), String> {
+    let (language, width) = match s.rsplit_once('=') {
+        Some((name, width)) => match language_override_from_name(name) {
+            Some(language) => (Some(language), width),
+            None => {
+                return Err(format!(
+                    "No such language '{}'. See --list-languages for the names of all languages available.",
+                    name
+                ))
+            }
+        },
+        None => (None, s),
+    };
+
+    match width.parse::<usize>() {
+        Ok(width) => Ok((language, width)),
+        Err(_) => Err(format!("'{}' is not a number of spaces", width)),
+    }
+}
+
+/// Parse a `--lines` value, which is a range of RHS lines such as
+/// `10-20`, or a range for each side such as `10-20,12-25`.
+fn parse_line_ranges(s: &str) -> Result<LineRanges, String> {
+    let parse_range = |range: &str| {
+        let parse_num = |n: &str| {
+            n.parse::<usize>()
+                .map_err(|_| format!("'{}' is not a line range, expected e.g. 10-20", range))
+        };
+
+        let (start, end) = match range.split_once('-') {
+            Some((start, end)) => (parse_num(start)?, parse_num(end)?),
+            None => {
+                let n = parse_num(range)?;
+                (n, n)
+            }
+        };
+        if start > end {
+            return Err(format!("'{}' is an empty range of lines", range));
+        }
+        // Lines past the end of the file don't match any hunks, so
+        // only the start needs clamping.
+        Ok((std::cmp::max(start, 1), end))
+    };
+
+    match s.split_once(',') {
+        Some((lhs, rhs)) => Ok(LineRanges {
+            lhs: Some(parse_range(lhs)?),
+            rhs: parse_range(rhs)?,
+        }),
+        None => Ok(LineRanges {
+            lhs: None,
+            rhs: parse_range(s)?,
+        }),
+    }
+}
+
+/// Parse the character for `--novel-line-marker`.
+fn parse_marker(s: &str) -> Result<char, String> {
+    let mut chars = s.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) if !c.is_control() => Ok(c),
+        _ => Err(format!("'{}' is not a single character", s)),
+    }
+}
+
+/// Parse a color from the command line. This can be an ANSI color
+/// name (e.g. `red` or `bright-red`), a 256-color palette index, or a
+/// hex RGB value (e.g. `#00afff`).
+fn parse_color(s: &str) -> Result<Color, String> {
+    if let Some(hex) = s.strip_prefix('#') {
+        if hex.len() == 6 && hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            let component = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).unwrap();
+            return Ok(Color::Rgb(component(0), component(2), component(4)));
+        }
+        return Err(format!(
+            "'{}' is not a valid hex color, expected e.g. #00afff",
+            s
+        ));
+    }
+
+    if let Ok(index) = s.parse::<u8>() {
+        return Ok(Color::Xterm(index));
+    }
+
+    let ansi_color = match s.to_ascii_lowercase().replace('_', "-").as_str() {
+        "black" => AnsiColors::Black,
+        "red" => AnsiColors::Red,
+        "green" => AnsiColors::Green,
+        "yellow" => AnsiColors::Yellow,
+        "blue" => AnsiColors::Blue,
+        "magenta" => AnsiColors::Magenta,
+        "cyan" => AnsiColors::Cyan,
+        "white" => AnsiColors::White,
+        "bright-black" => AnsiColors::BrightBlack,
+        "bright-red" => AnsiColors::BrightRed,
+        "bright-green" => AnsiColors::BrightGreen,
+        "bright-yellow" => AnsiColors::BrightYellow,
+        "bright-blue" => AnsiColors::BrightBlue,
+        "bright-magenta" => AnsiColors::BrightMagenta,
+        "bright-cyan" => AnsiColors::BrightCyan,
+        "bright-white" => AnsiColors::BrightWhite,
+        _ => {
+            return Err(format!(
+                "'{}' is not a color name, 256-color index (0-255) or hex color (#rrggbb)",
+                s
+            ))
+        }
+    };
+    Ok(Color::Ansi(ansi_color))
+}
+
+fn build_display_path(lhs_path: &FileArgument, rhs_path: &FileArgument) -> String {
+    match (lhs_path, rhs_path) {
+        (FileArgument::NamedPath(lhs), FileArgument::NamedPath(rhs)) => {
+            if is_git_tmpfile(lhs) {
+                return escape_path(rhs);
+            }
+
+            match common_path_suffix(lhs, rhs) {
+                Some(common_suffix) => common_suffix,
+                None => escape_path(rhs),
+            }
+        }
+        (FileArgument::NamedPath(p), _) | (_, FileArgument::NamedPath(p)) => escape_path(p),
+        (FileArgument::DevNull, _) | (_, FileArgument::DevNull) => "/dev/null".into(),
         (FileArgument::Stdin, FileArgument::Stdin) => "-".into(),
     }
 }
 
+/// Replace the display paths of extdiff snapshots with the path in
+/// the repository, and describe the changesets being compared.
+fn hg_display_args(
+    lhs_arg: &FileArgument,
+    rhs_arg: &FileArgument,
+    lhs_display_arg: &mut FileArgument,
+    rhs_display_arg: &mut FileArgument,
+) -> String {
+    let extdiff_path = |arg: &FileArgument| match arg {
+        FileArgument::NamedPath(path) => Some(hg::extdiff_path(path)),
+        _ => None,
+    };
+    let lhs_extdiff = extdiff_path(lhs_arg);
+    let rhs_extdiff = extdiff_path(rhs_arg);
+
+    // Whole snapshot directories don't have a path in the
+    // repository, and the files inside them are already shown
+    // relative to the snapshot.
+    for (extdiff, display_arg) in [
+        (&lhs_extdiff, lhs_display_arg),
+        (&rhs_extdiff, rhs_display_arg),
+    ] {
+        if let Some(extdiff) = extdiff {
+            if extdiff.rel_path != Path::new("") {
+                *display_arg = FileArgument::NamedPath(extdiff.rel_path.clone());
+            }
+        }
+    }
+
+    hg::changesets_info(
+        lhs_extdiff
+            .as_ref()
+            .and_then(|extdiff| extdiff.changeset.as_deref()),
+        rhs_extdiff
+            .as_ref()
+            .and_then(|extdiff| extdiff.changeset.as_deref()),
+    )
+}
+
+/// The display path and a description of the revisions for the
+/// labels that Subversion passes with `-L`, such as
+/// "foo.c\t(revision 12)" and "foo.c\t(working copy)".
+fn svn_display_path(lhs_label: &str, rhs_label: &str) -> (String, Option<String>) {
+    let split = |label: &str| match label.split_once('\t') {
+        Some((path, revision)) => {
+            let revision = revision.trim();
+            let revision = revision
+                .strip_prefix('(')
+                .and_then(|revision| revision.strip_suffix(')'))
+                .unwrap_or(revision);
+            (path.to_owned(), Some(revision.to_owned()))
+        }
+        None => (label.to_owned(), None),
+    };
+    let (lhs_path, lhs_revision) = split(lhs_label);
+    let (rhs_path, rhs_revision) = split(rhs_label);
+
+    let info = match (lhs_revision, rhs_revision) {
+        (Some(lhs_revision), Some(rhs_revision)) if lhs_path == rhs_path => {
+            format!("Comparing {} to {}.", lhs_revision, rhs_revision)
+        }
+        _ if lhs_path == rhs_path => return (rhs_path, None),
+        _ => format!(
+            "Comparing {} to {}.",
+            lhs_label.replace('\t', " "),
+            rhs_label.replace('\t', " ")
+        ),
+    };
+    (rhs_path, Some(info))
+}
+
+/// Prefixes to remove from the two paths before choosing the name
+/// shown in the header, see `--strip-prefix`.
+#[derive(Debug, Default)]
+struct StripPrefixes {
+    /// Remove the longest common leading directory of the two paths.
+    common: bool,
+    lhs: Option<PathBuf>,
+    rhs: Option<PathBuf>,
+}
+
+/// The longest leading directory shared by `lhs_path` and
+/// `rhs_path`. This never includes the file names themselves.
+fn common_dir_prefix(lhs_path: &Path, rhs_path: &Path) -> PathBuf {
+    let lhs_dir = lhs_path.parent().unwrap_or_else(|| Path::new(""));
+    let rhs_dir = rhs_path.parent().unwrap_or_else(|| Path::new(""));
+    lhs_dir
+        .components()
+        .zip(rhs_dir.components())
+        .take_while(|(lhs_component, rhs_component)| lhs_component == rhs_component)
+        .map(|(component, _)| component)
+        .collect()
+}
+
+fn strip_path_prefix(path: &FileArgument, prefix: Option<&Path>) -> FileArgument {
+    match (path, prefix) {
+        (FileArgument::NamedPath(path), Some(prefix)) => match path.strip_prefix(prefix) {
+            Ok(stripped) if stripped != Path::new("") => {
+                FileArgument::NamedPath(stripped.to_owned())
+            }
+            _ => FileArgument::NamedPath(path.clone()),
+        },
+        _ => path.clone(),
+    }
+}
+
+/// The two paths with `prefixes` removed, for choosing the display
+/// path. The original paths are logged with `--verbose`.
+fn strip_prefixes(
+    lhs_path: &FileArgument,
+    rhs_path: &FileArgument,
+    prefixes: &StripPrefixes,
+) -> (FileArgument, FileArgument) {
+    let common_prefix = match (lhs_path, rhs_path) {
+        (FileArgument::NamedPath(lhs), FileArgument::NamedPath(rhs)) if prefixes.common => {
+            Some(common_dir_prefix(lhs, rhs))
+        }
+        _ => None,
+    };
+    let lhs_prefix = prefixes.lhs.as_deref().or(common_prefix.as_deref());
+    let rhs_prefix = prefixes.rhs.as_deref().or(common_prefix.as_deref());
+
+    let stripped_lhs = strip_path_prefix(lhs_path, lhs_prefix);
+    let stripped_rhs = strip_path_prefix(rhs_path, rhs_prefix);
+    if (&stripped_lhs, &stripped_rhs) != (lhs_path, rhs_path) {
+        logging::log(
+            Level::Info,
+            "strip_prefix",
+            &format!(
+                "Showing {} as {} and {} as {}",
+                lhs_path, stripped_lhs, rhs_path, stripped_rhs
+            ),
+            json!({
+                "lhs_path": lhs_path.to_string(),
+                "rhs_path": rhs_path.to_string(),
+                "lhs_display_path": stripped_lhs.to_string(),
+                "rhs_display_path": stripped_rhs.to_string(),
+            }),
+        );
+    }
+    (stripped_lhs, stripped_rhs)
+}
+
+/// The globs passed to `--include` or `--exclude`.
+fn parse_path_globs_or_die(matches: &ArgMatches, name: &str) -> Vec<glob::Pattern> {
+    let mut patterns = vec![];
+    for glob_str in matches.values_of(name).unwrap_or_default() {
+        match glob::Pattern::new(glob_str) {
+            Ok(pattern) => patterns.push(pattern),
+            Err(e) => {
+                eprintln!(
+                    "error: Invalid glob for --{} '{}': {}",
+                    name, glob_str, e.msg
+                );
+                std::process::exit(EXIT_BAD_ARGUMENTS);
+            }
+        }
+    }
+    patterns
+}
+
 fn parse_overrides_or_die(raw_overrides: &[String]) -> Vec<(LanguageOverride, Vec<glob::Pattern>)> {
     let mut overrides: Vec<(LanguageOverride, Vec<glob::Pattern>)> = vec![];
     let mut invalid_syntax = false;
@@ -601,8 +2212,272 @@ fn parse_overrides_or_die(raw_overrides: &[String]) -> Vec<(LanguageOverride, Ve
 }
 
 /// Parse CLI arguments passed to the binary.
+/// The command line interface, for generating shell completions.
+///
+/// Language overrides are in the form `GLOB:NAME`, so we can't
+/// validate them against a list of values, but we can offer the
+/// language names from `--list-languages` as completions.
+pub(crate) fn completion_app() -> clap::Command<'static> {
+    let mut language_names: Vec<&'static str> = Language::iter().map(language_name).collect();
+    language_names.push("text");
+
+    app()
+        .mut_arg("override", |arg| {
+            arg.possible_values(language_names.clone())
+        })
+        .mut_arg("language", |arg| arg.possible_values(language_names))
+}
+
+/// Options that can't be set in the config file.
+const NOT_CONFIGURABLE: &[&str] = &["config", "help", "version"];
+
+/// The command line arguments that set the options in `config`,
+/// skipping options that `matches` already sets from the command line
+/// or the environment.
+fn config_args(
+    config: &Config,
+    config_path: &Path,
+    matches: &ArgMatches,
+) -> Result<Vec<OsString>, String> {
+    let app = app();
+    let mut args: Vec<OsString> = vec![];
+
+    for entry in &config.options {
+        let arg = app.get_arguments().find(|arg| {
+            arg.get_long() == Some(entry.key.as_str()) && !NOT_CONFIGURABLE.contains(&arg.get_id())
+        });
+        let arg = match arg {
+            Some(arg) => arg,
+            None => {
+                logging::log(
+                    Level::Warning,
+                    "config",
+                    &format!(
+                        "Unknown key `{}` on line {} of {}",
+                        entry.key,
+                        entry.line_num,
+                        config_path.display()
+                    ),
+                    json!({"path": config_path.display().to_string(), "line": entry.line_num}),
+                );
+                continue;
+            }
+        };
+
+        if matches!(
+            matches.value_source(arg.get_id()),
+            Some(ValueSource::CommandLine | ValueSource::EnvVariable)
+        ) {
+            continue;
+        }
+
+        let flag = OsString::from(format!("--{}", entry.key));
+        match (&entry.value, arg.is_takes_value_set()) {
+            (Value::Boolean(true), false) => args.push(flag),
+            (Value::Boolean(false), false) => {}
+            (Value::Boolean(on), true) => {
+                args.push(flag);
+                args.push(if *on { "on" } else { "off" }.into());
+            }
+            // `--sort-paths` only accepts `--sort-paths=ORDER`.
+            (Value::Scalar(value), true) if arg.is_require_equals_set() => {
+                args.push(format!("--{}={}", entry.key, value).into());
+            }
+            (Value::Scalar(value), true) => {
+                args.push(flag);
+                args.push(value.into());
+            }
+            (Value::Array(values), true) if arg.is_multiple_occurrences_set() => {
+                for value in values {
+                    args.push(flag.clone());
+                    args.push(value.into());
+                }
+            }
+            (Value::Array(_), true) => {
+                return Err(format!(
+                    "line {}: `{}` only takes a single value",
+                    entry.line_num, entry.key
+                ));
+            }
+            (_, false) => {
+                return Err(format!(
+                    "line {}: `{}` is a flag, so its value must be true or false",
+                    entry.line_num, entry.key
+                ));
+            }
+        }
+    }
+
+    Ok(args)
+}
+
+/// Parse the `[language.NAME]` tables in `config`. Only `tab-width`
+/// can be set per language.
+fn language_tab_widths(
+    config: &Config,
+    config_path: &Path,
+) -> Result<Vec<(LanguageOverride, usize)>, String> {
+    let mut tab_widths = vec![];
+    for (name, entries) in &config.languages {
+        let language = match language_override_from_name(name) {
+            Some(language) => language,
+            None => {
+                logging::log(
+                    Level::Warning,
+                    "config",
+                    &format!(
+                        "Unknown language `{}` in {}. See --list-languages for the names of all languages available.",
+                        name,
+                        config_path.display()
+                    ),
+                    json!({"path": config_path.display().to_string()}),
+                );
+                continue;
+            }
+        };
+
+        for entry in entries {
+            match (entry.key.as_str(), &entry.value) {
+                ("tab-width", Value::Scalar(value)) => match value.parse::<usize>() {
+                    Ok(tab_width) => tab_widths.push((language, tab_width)),
+                    Err(_) => {
+                        return Err(format!(
+                            "line {}: `tab-width` must be a positive integer",
+                            entry.line_num
+                        ))
+                    }
+                },
+                ("tab-width", _) => {
+                    return Err(format!(
+                        "line {}: `tab-width` must be a positive integer",
+                        entry.line_num
+                    ))
+                }
+                _ => {
+                    logging::log(
+                        Level::Warning,
+                        "config",
+                        &format!(
+                            "Unknown key `{}` in [language.{}] on line {} of {}",
+                            entry.key,
+                            name,
+                            entry.line_num,
+                            config_path.display()
+                        ),
+                        json!({"path": config_path.display().to_string(), "line": entry.line_num}),
+                    );
+                }
+            }
+        }
+    }
+    Ok(tab_widths)
+}
+
+/// Unset any `DFT_*` environment variables that clap would reject,
+/// so we use the default instead. Git runs difftastic for every file
+/// in e.g. `git log -p`, and a typo in the environment shouldn't stop
+/// that part way through.
+fn ignore_invalid_env_vars(app: &Command<'static>) {
+    for arg in app.get_arguments() {
+        let env_name = match arg.get_env() {
+            Some(env_name) => env_name,
+            None => continue,
+        };
+        let value = match env::var_os(env_name) {
+            Some(value) => value,
+            None => continue,
+        };
+
+        // Parse with only this argument, so clap applies the same
+        // validation as it would to the full command line.
+        let single_arg_app = Command::new(bin_name!()).arg(arg.clone());
+        if let Err(e) = single_arg_app.try_get_matches_from([bin_name!()]) {
+            let message = e.to_string();
+            let reason = message.lines().next().unwrap_or_default();
+            logging::log(
+                Level::Warning,
+                "env_var",
+                &format!(
+                    "Ignoring {}={}, using the default. {}",
+                    env_name.to_string_lossy(),
+                    value.to_string_lossy(),
+                    reason.trim_start_matches("error: ")
+                ),
+                json!({"name": env_name.to_string_lossy()}),
+            );
+            env::remove_var(env_name);
+        }
+    }
+}
+
+/// Parse the command line arguments, using values from the config
+/// file for options that aren't set on the command line or in the
+/// environment.
+fn get_matches_with_config() -> (ArgMatches, Vec<(LanguageOverride, usize)>) {
+    ignore_invalid_env_vars(&app());
+
+    let args: Vec<OsString> = env::args_os().collect();
+    let matches = app().get_matches_from(&args);
+    // Set up logging before reading the config file, so warnings
+    // about it use the right format.
+    init_logging(&matches);
+
+    let (config_path, must_exist) = match matches.value_of_os("config") {
+        Some(path) => (PathBuf::from(path), true),
+        None => match config::default_path() {
+            Some(path) => (path, false),
+            None => return (matches, vec![]),
+        },
+    };
+
+    let parsed = config::read(&config_path, must_exist).and_then(|config| match config {
+        Some(config) => Ok((
+            config_args(&config, &config_path, &matches)?,
+            language_tab_widths(&config, &config_path)?,
+        )),
+        None => Ok((vec![], vec![])),
+    });
+    let (extra_args, tab_widths) = match parsed {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            std::process::exit(EXIT_BAD_ARGUMENTS);
+        }
+    };
+
+    if extra_args.is_empty() {
+        return (matches, tab_widths);
+    }
+
+    // Put the config file options before the command line arguments,
+    // so they don't come after a `--`.
+    let mut all_args: Vec<OsString> = args.iter().take(1).cloned().collect();
+    all_args.extend(extra_args);
+    all_args.extend(args.iter().skip(1).cloned());
+    let matches = app().get_matches_from(all_args);
+    init_logging(&matches);
+    (matches, tab_widths)
+}
+
+/// The number of times `--verbose` was passed. `DFT_VERBOSE` can
+/// only enable the first level.
+fn verbosity(matches: &ArgMatches) -> u8 {
+    if matches.value_source("verbose") == Some(ValueSource::CommandLine) {
+        matches.occurrences_of("verbose").min(u8::MAX as u64) as u8
+    } else {
+        matches.is_present("verbose") as u8
+    }
+}
+
+fn init_logging(matches: &ArgMatches) {
+    logging::init(
+        verbosity(matches),
+        matches.value_of("log-format") == Some("json"),
+    );
+}
+
 pub(crate) fn parse_args() -> Mode {
-    let matches = app().get_matches();
+    let (matches, language_tab_widths) = get_matches_with_config();
 
     let color_output = match matches.value_of("color").expect("color has a default") {
         "always" => ColorOutput::Always,
@@ -612,13 +2487,42 @@ pub(crate) fn parse_args() -> Mode {
             unreachable!("clap has already validated color")
         }
     };
-    let use_color = should_use_color(color_output);
+    // Files are usually read with tools that don't understand color,
+    // so --output-file only uses color if it's explicitly requested.
+    let use_color = if matches.value_of_os("output-file").is_some() {
+        matches!(color_output, ColorOutput::Always)
+    } else {
+        should_use_color(color_output)
+    };
+
+    let word_regex = matches
+        .value_of("word-regex")
+        .map(|regex| Regex::new(regex).expect("Value already validated by clap"));
 
     let ignore_comments = matches.is_present("ignore-comments");
+    let whitespace = if matches.is_present("ignore-all-space") {
+        Whitespace::IgnoreAll
+    } else if matches.is_present("ignore-space-change") {
+        Whitespace::IgnoreChange
+    } else {
+        Whitespace::Exact
+    };
 
     let mut raw_overrides: Vec<String> = vec![];
     if let Some(overrides) = matches.values_of("override") {
         raw_overrides = overrides.map(|s| s.into()).collect();
+
+        // An environment variable can only hold one value, so
+        // DFT_OVERRIDE separates overrides with `;`. We can't use
+        // `:`, as that separates the glob from the language name.
+        if matches.value_source("override") == Some(ValueSource::EnvVariable) {
+            raw_overrides = raw_overrides
+                .iter()
+                .flat_map(|s| s.split(';'))
+                .filter(|s| !s.is_empty())
+                .map(|s| s.to_owned())
+                .collect();
+        }
     }
     for i in 1..=9 {
         if let Ok(value) = env::var(format!("DFT_OVERRIDE_{}", i)) {
@@ -626,12 +2530,41 @@ pub(crate) fn parse_args() -> Mode {
         }
     }
 
+    // An override that matches every file name, so it comes first.
+    if let Some(language) = matches.value_of("language") {
+        raw_overrides.insert(0, format!("*:{}", language));
+    }
+
     let language_overrides = parse_overrides_or_die(&raw_overrides);
 
+    if matches.is_present("version") {
+        return Mode::Version {
+            verbose: logging::enabled(Level::Info),
+        };
+    }
+
     if matches.is_present("list-languages") {
         return Mode::ListLanguages {
             use_color,
             language_overrides,
+            json: matches.value_of("display") == Some("json"),
+        };
+    }
+
+    if let Some(shell) = matches.value_of("generate-completions") {
+        return Mode::GenerateCompletions {
+            shell: shell.parse().expect("Value already validated by clap"),
+        };
+    }
+
+    let cache_dir = matches.value_of_os("cache-dir").map(PathBuf::from);
+    if matches.is_present("cache-clear") {
+        return match cache_dir {
+            Some(cache_dir) => Mode::ClearCache { cache_dir },
+            None => {
+                eprintln!("error: --cache-clear requires --cache-dir or DFT_CACHE_DIR.");
+                std::process::exit(EXIT_BAD_ARGUMENTS);
+            }
         };
     }
 
@@ -650,15 +2583,55 @@ pub(crate) fn parse_args() -> Mode {
         };
     }
 
-    let terminal_width = if let Some(arg_width) = matches.value_of("width") {
-        arg_width
+    if let Some(path) = matches.value_of("guess-language") {
+        return Mode::GuessLanguage {
+            path: path.to_owned(),
+            language_overrides,
+        };
+    }
+
+    if matches.is_present("dump-graph") {
+        let paths: Vec<_> = matches.values_of_os("paths").unwrap_or_default().collect();
+        let (lhs_path, rhs_path) = match &paths[..] {
+            [lhs_path, rhs_path] => (
+                lhs_path.to_string_lossy().to_string(),
+                rhs_path.to_string_lossy().to_string(),
+            ),
+            _ => {
+                eprintln!("error: --dump-graph requires two file paths.");
+                std::process::exit(EXIT_BAD_ARGUMENTS);
+            }
+        };
+
+        let graph_limit = matches
+            .value_of("graph-limit")
+            .expect("Always present as we've given clap a default")
             .parse::<usize>()
-            .expect("Already validated by clap")
-    } else {
-        detect_terminal_width()
-    };
+            .expect("Value already validated by clap");
+        let max_depth = matches
+            .value_of("dump-graph-depth")
+            .map(|s| s.parse::<usize>().expect("Value already validated by clap"));
+
+        return Mode::DumpGraph {
+            lhs_path,
+            rhs_path,
+            ignore_comments,
+            graph_limit,
+            max_depth,
+            language_overrides,
+        };
+    }
+
+    let terminal_width = resolve_width(
+        matches
+            .value_of("width")
+            .map(|s| parse_width(s).expect("Value already validated by clap")),
+        env::var("COLUMNS").ok().as_deref(),
+        terminal_size,
+    );
 
     let display_mode = match matches.value_of("display").expect("display has a default") {
+        _ if matches.is_present("check") => DisplayMode::Check,
         "side-by-side" => DisplayMode::SideBySide,
         "side-by-side-show-both" => DisplayMode::SideBySideShowBoth,
         "inline" => DisplayMode::Inline,
@@ -670,23 +2643,195 @@ pub(crate) fn parse_args() -> Mode {
 
             DisplayMode::Json
         }
+        "html" => DisplayMode::Html,
+        "patch" => DisplayMode::Patch,
+        "stat" => DisplayMode::Stat,
+        "sarif" => DisplayMode::Sarif,
+        "markdown" => DisplayMode::Markdown,
+        "json-patch" => DisplayMode::JsonPatch,
+        "symbols" => DisplayMode::Symbols,
         _ => {
             unreachable!("clap has already validated display")
         }
     };
 
-    let background_color = match matches
-        .value_of("background")
+    let theme = matches.value_of("theme").map(|theme| match theme {
+        "dark" => Theme::Dark,
+        "light" => Theme::Light,
+        "solarized-dark" => Theme::SolarizedDark,
+        "solarized-light" => Theme::SolarizedLight,
+        "gruvbox" => Theme::Gruvbox,
+        "dracula" => Theme::Dracula,
+        "monochrome" => Theme::Monochrome,
+        _ => unreachable!("clap has already validated the values"),
+    });
+
+    let background_color = match theme {
+        // The theme determines the background, so don't query the
+        // terminal.
+        Some(theme) => theme.background(),
+        None => match matches
+            .value_of("background")
+            .expect("Always present as we've given clap a default")
+        {
+            "dark" => BackgroundColor::Dark,
+            "light" => BackgroundColor::Light,
+            "auto" => {
+                // Querying the terminal is only meaningful if we're
+                // writing colored output to it.
+                if use_color && std::io::stdout().is_tty() {
+                    detect_background().unwrap_or(BackgroundColor::Dark)
+                } else {
+                    BackgroundColor::Dark
+                }
+            }
+            _ => unreachable!("clap has already validated the values"),
+        },
+    };
+    let theme = theme.unwrap_or_else(|| Theme::default_for(background_color));
+
+    let color_depth = match matches
+        .value_of("color-depth")
         .expect("Always present as we've given clap a default")
     {
-        "dark" => BackgroundColor::Dark,
-        "light" => BackgroundColor::Light,
+        "16" => ColorDepth::Ansi16,
+        "256" => ColorDepth::Ansi256,
+        "24bit" => ColorDepth::TrueColor,
+        "auto" => detect_color_depth(),
         _ => unreachable!("clap has already validated the values"),
     };
 
-    let syntax_highlight = matches.value_of("syntax-highlight") == Some("on");
+    let mut palette = Palette::new(theme, color_depth);
+    let color_arg = |name: &str| {
+        matches
+            .value_of(name)
+            .map(|s| Highlight::Color(parse_color(s).expect("clap has already validated colors")))
+    };
+    if let Some(highlight) = color_arg("color-novel-lhs") {
+        palette.novel_lhs = highlight;
+    }
+    if let Some(highlight) = color_arg("color-novel-rhs") {
+        palette.novel_rhs = highlight;
+    }
+    if let Some(highlight) = color_arg("color-header") {
+        palette.header = highlight;
+    }
+    for (name, role) in [
+        ("color-novel-word", &mut palette.novel_word),
+        ("color-line-number", &mut palette.line_number),
+        ("color-novel-line-number", &mut palette.novel_line_number),
+        ("color-comment", &mut palette.comment),
+        ("color-string", &mut palette.string),
+        ("color-keyword", &mut palette.keyword),
+    ] {
+        if let Some(highlight) = color_arg(name) {
+            *role = Some(highlight);
+        }
+    }
+    let palette = palette.quantize(color_depth);
+
+    let accessibility = matches.is_present("accessibility");
+    let palette = match (accessibility, use_color) {
+        (true, true) => palette.accessible(),
+        (true, false) => Palette::attributes_only(),
+        (false, _) => palette,
+    };
 
-    let sort_paths = matches.is_present("sort-paths");
+    // Without color, syntax highlighting would only add bold and
+    // italics, which would be confused with the accessibility
+    // attributes.
+    let syntax_highlight = matches!(matches.value_of("syntax-highlight"), Some("on" | "full"))
+        && (use_color || !accessibility);
+    let full_syntax_highlight = matches.value_of("syntax-highlight") == Some("full");
+
+    let (dim_punctuation, ignore_punctuation) = match matches.value_of("punctuation") {
+        Some("dim") => (true, false),
+        Some("ignore") => (false, true),
+        _ => (false, false),
+    };
+
+    let trailing_whitespace = match matches.value_of("show-trailing-whitespace") {
+        Some("always") => TrailingWhitespace::Always,
+        Some("never") => TrailingWhitespace::Never,
+        _ => TrailingWhitespace::Auto,
+    };
+
+    let show_invisible = match matches.value_of("show-invisible") {
+        Some("always") => ShowInvisible::Always,
+        Some("never") => ShowInvisible::Never,
+        _ => ShowInvisible::Auto,
+    };
+
+    let novel_line_marker = matches
+        .value_of("novel-line-marker")
+        .map(|s| parse_marker(s).expect("Value already validated by clap"));
+
+    let sort_order = match matches.value_of("sort-paths") {
+        Some("size") => SortOrder::Size,
+        Some("changes") => SortOrder::Changes,
+        _ => SortOrder::Path,
+    };
+    let new_file = matches.is_present("new-file");
+
+    let output_file = matches.value_of_os("output-file").map(PathBuf::from);
+    let pager_mode = match matches.value_of("pager").expect("pager has a default") {
+        "always" => PagerMode::Always,
+        "never" => PagerMode::Never,
+        "auto" => PagerMode::Auto,
+        _ => {
+            unreachable!("clap has already validated pager")
+        }
+    };
+    // jj shows the output of diff tools in its own pager, so we
+    // shouldn't page or wait for keys ourselves.
+    let paths: Vec<_> = matches
+        .values_of_os("paths")
+        .unwrap_or_default()
+        .map(Path::new)
+        .collect();
+    let is_jj =
+        matches.is_present("jj") || matches!(&paths[..], [lhs, rhs] if is_jj_diff(lhs, rhs));
+
+    let interactive = matches.is_present("interactive");
+    if interactive {
+        if output_file.is_some() {
+            eprintln!("error: --interactive can't be used with --output-file.");
+            std::process::exit(EXIT_BAD_ARGUMENTS);
+        }
+        if !matches!(
+            display_mode,
+            DisplayMode::Inline | DisplayMode::SideBySide | DisplayMode::SideBySideShowBoth
+        ) {
+            eprintln!("error: --interactive only supports inline and side-by-side display.");
+            std::process::exit(EXIT_BAD_ARGUMENTS);
+        }
+    }
+    let output = match &output_file {
+        Some(output_file) => Output::File(output_file.clone()),
+        // We read keys from the terminal, so fall back to the usual
+        // output when the user isn't there to press them.
+        None if interactive
+            && !is_jj
+            && !matches.is_present("quiet")
+            && std::io::stdin().is_tty()
+            && std::io::stdout().is_tty() =>
+        {
+            Output::Interactive
+        }
+        None => {
+            // --quiet doesn't print anything to page.
+            let use_pager = !matches.is_present("quiet")
+                && match pager_mode {
+                    PagerMode::Always => true,
+                    PagerMode::Never => false,
+                    PagerMode::Auto => !is_jj && std::io::stdout().is_tty(),
+                };
+            match pager_command() {
+                Some(command) if use_pager => Output::Pager(command),
+                _ => Output::Stdout,
+            }
+        }
+    };
 
     let graph_limit = matches
         .value_of("graph-limit")
@@ -694,11 +2839,32 @@ pub(crate) fn parse_args() -> Mode {
         .parse::<usize>()
         .expect("Value already validated by clap");
 
-    let byte_limit = matches
-        .value_of("byte-limit")
-        .expect("Always present as we've given clap a default")
-        .parse::<usize>()
-        .expect("Value already validated by clap");
+    let mut byte_limit = DEFAULT_BYTE_LIMIT;
+    let mut byte_limit_overrides = vec![];
+    for value in matches.values_of("byte-limit").unwrap_or_default() {
+        match parse_byte_limit(value).expect("Value already validated by clap") {
+            (Some(pattern), limit) => byte_limit_overrides.push((pattern, limit)),
+            (None, limit) => byte_limit = limit,
+        }
+    }
+    let prose_granularity = match matches.value_of("prose-granularity") {
+        Some("line") => ProseGranularity::Line,
+        Some("word") => ProseGranularity::Word,
+        Some("sentence") => ProseGranularity::Sentence,
+        _ if matches.is_present("prose-wrap-insensitive") => ProseGranularity::Word,
+        _ => ProseGranularity::Line,
+    };
+    let reformat_json = match matches.value_of("reformat-json") {
+        Some("always") => ReformatJson::Always,
+        Some("never") => ReformatJson::Never,
+        _ => ReformatJson::Auto,
+    };
+
+    let byte_limit_action = match matches.value_of("byte-limit-action") {
+        Some("error") => ByteLimitAction::Error,
+        Some("truncate") => ByteLimitAction::Truncate,
+        _ => ByteLimitAction::Text,
+    };
 
     let parse_error_limit = matches
         .value_of("parse-error-limit")
@@ -706,33 +2872,174 @@ pub(crate) fn parse_args() -> Mode {
         .parse::<usize>()
         .expect("Value already validated by clap");
 
-    let tab_width = matches
-        .value_of("tab-width")
+    let mut tab_width = DEFAULT_TAB_WIDTH;
+    // Tab widths from the command line take precedence over the
+    // config file, and `for_file` uses the first match.
+    let mut cli_language_tab_widths = vec![];
+    for value in matches
+        .values_of("tab-width")
+        .expect("Always present as we've given clap a default")
+    {
+        match parse_tab_width(value).expect("Value already validated by clap") {
+            (Some(language), width) => cli_language_tab_widths.push((language, width)),
+            (None, width) => tab_width = width,
+        }
+    }
+    let language_tab_widths: Vec<(LanguageOverride, usize)> = cli_language_tab_widths
+        .into_iter()
+        .chain(language_tab_widths)
+        .collect();
+
+    let context = parse_context(
+        matches
+            .value_of("context")
+            .expect("Always present as we've given clap a default"),
+    )
+    .expect("Value already validated by clap");
+    let (num_context_lines, function_context) = match context {
+        Context::Lines(num_context_lines) => (num_context_lines, false),
+        Context::Function => (DisplayOptions::default().before_context_lines, true),
+    };
+    if function_context && matches!(display_mode, DisplayMode::Json | DisplayMode::Patch) {
+        eprintln!("error: --context=function isn't supported by --display=json or patch.");
+        std::process::exit(EXIT_BAD_ARGUMENTS);
+    }
+    let before_context_lines = matches
+        .value_of("before-context")
+        .map_or(num_context_lines, |s| {
+            s.parse::<u32>().expect("Value already validated by clap")
+        });
+    let after_context_lines = matches
+        .value_of("after-context")
+        .map_or(num_context_lines, |s| {
+            s.parse::<u32>().expect("Value already validated by clap")
+        });
+    let merge_hunk_gap = matches
+        .value_of("merge-hunk-gap")
+        .map_or(num_context_lines, |s| {
+            s.parse::<u32>().expect("Value already validated by clap")
+        });
+
+    let print_unchanged = !matches.is_present("skip-unchanged");
+    let structural_context = matches.is_present("structural-context");
+    let hunk_ranges = matches.is_present("hunk-ranges");
+    let hunk_selection: Vec<(usize, usize)> = matches
+        .values_of("hunk")
+        .map(|values| {
+            values
+                .map(|s| parse_hunk_range(s).expect("Value already validated by clap"))
+                .collect()
+        })
+        .unwrap_or_default();
+    let line_ranges = matches
+        .value_of("lines")
+        .map(|s| parse_line_ranges(s).expect("Value already validated by clap"));
+    let max_hunk_lines = matches
+        .value_of("max-hunk-lines")
+        .map(|s| s.parse::<usize>().expect("Value already validated by clap"));
+
+    let markdown_max_bytes = matches
+        .value_of("markdown-max-bytes")
+        .map(|s| s.parse::<usize>().expect("Value already validated by clap"));
+
+    let ignore_blank_lines = matches.is_present("ignore-blank-lines");
+    // Like `diff -q` and `git diff --quiet`, --quiet is only useful
+    // for the exit code.
+    let quiet = matches.is_present("quiet");
+    let set_exit_code = matches.is_present("exit-code") || quiet || matches.is_present("check");
+    let fail_on_fallback = matches.is_present("fail-on-fallback");
+
+    let prefixes = StripPrefixes {
+        common: matches.is_present("strip-prefix"),
+        lhs: matches.value_of_os("prefix-left").map(PathBuf::from),
+        rhs: matches.value_of_os("prefix-right").map(PathBuf::from),
+    };
+
+    // A patch must reproduce the new file byte-for-byte, so we can't
+    // discard carriage returns.
+    let strip_cr =
+        matches.value_of("strip-cr") == Some("on") && !matches!(display_mode, DisplayMode::Patch);
+
+    let check_only = matches.is_present("check-only") || matches.is_present("check");
+
+    let jobs = matches
+        .value_of("jobs")
+        .map(|s| parse_jobs(s).expect("Value already validated by clap"));
+    let timeout = matches
+        .value_of("timeout")
+        .map(|s| parse_timeout(s).expect("Value already validated by clap"));
+
+    let generated_markers = if matches.is_present("collapse-generated") {
+        let markers: Vec<&str> = match matches.values_of("generated-marker") {
+            Some(values) => values.collect(),
+            None => DEFAULT_GENERATED_MARKERS.to_vec(),
+        };
+        Some(parse_generated_markers(&markers).expect("Value already validated by clap"))
+    } else {
+        None
+    };
+
+    let detect_moves = matches.is_present("detect-moves");
+    let move_min_tokens = matches
+        .value_of("move-min-tokens")
         .expect("Always present as we've given clap a default")
         .parse::<usize>()
         .expect("Value already validated by clap");
 
-    let num_context_lines = matches
-        .value_of("context")
+    let unchanged_heuristic = !matches.is_present("no-unchanged-heuristic");
+    let unchanged_min_run = matches
+        .value_of("unchanged-min-run")
         .expect("Always present as we've given clap a default")
         .parse::<u32>()
         .expect("Value already validated by clap");
-
-    let print_unchanged = !matches.is_present("skip-unchanged");
-
-    let set_exit_code = matches.is_present("exit-code");
-
-    let strip_cr = matches.value_of("strip-cr") == Some("on");
-
-    let check_only = matches.is_present("check-only");
+    let unchanged_ngram = matches
+        .value_of("unchanged-ngram")
+        .expect("Always present as we've given clap a default")
+        .parse::<usize>()
+        .expect("Value already validated by clap");
 
     let diff_options = DiffOptions {
         graph_limit,
         byte_limit,
+        byte_limit_overrides,
+        byte_limit_action,
         parse_error_limit,
         check_only,
         ignore_comments,
+        ignore_punctuation,
+        whitespace,
+        prose_granularity,
+        word_regex,
+        reformat_json,
         strip_cr,
+        force_text: matches.is_present("text"),
+        detect_moves,
+        detect_copies: matches.is_present("detect-copies"),
+        move_min_tokens,
+        jobs,
+        cache_dir,
+        unchanged_heuristic,
+        unchanged_min_run,
+        unchanged_ngram,
+        anchors: !matches.is_present("no-anchors"),
+        timeout,
+        cancel: Some(pager::INTERRUPT.clone()),
+        profile: matches.is_present("profile"),
+        generated_markers,
+        respect_ignore_files: !matches.is_present("no-ignore"),
+        include_hidden: matches.is_present("hidden"),
+        path_filter: PathFilter {
+            include: parse_path_globs_or_die(&matches, "include"),
+            exclude: parse_path_globs_or_die(&matches, "exclude"),
+        },
+        follow_symlinks: matches.is_present("follow-symlinks"),
+        max_depth: matches
+            .value_of("max-depth")
+            .map(|s| s.parse().expect("Value already validated by clap")),
+        compare_metadata: matches.is_present("compare-metadata"),
+        compare_mtime: matches.is_present("compare-mtime"),
+        ignore_path_case: matches.is_present("ignore-path-case"),
+        diff_archives: !matches.is_present("no-archive"),
     };
 
     let args: Vec<_> = matches.values_of_os("paths").unwrap_or_default().collect();
@@ -746,155 +3053,556 @@ pub(crate) fn parse_args() -> Mode {
         }
     }
 
-    // TODO: document these different ways of calling difftastic.
-    let (display_path, lhs_path, rhs_path, lhs_permissions, rhs_permissions, renamed) = match &args
-        [..]
-    {
-        [lhs_path, rhs_path] => {
-            let lhs_arg = FileArgument::from_cli_argument(lhs_path);
-            let rhs_arg = FileArgument::from_cli_argument(rhs_path);
-            let display_path = build_display_path(&lhs_arg, &rhs_arg);
+    let display_options = DisplayOptions {
+        background_color,
+        palette,
+        use_color,
+        accessibility,
+        print_unchanged,
+        skip_binary: matches.is_present("skip-binary"),
+        tab_width,
+        display_mode,
+        terminal_width,
+        before_context_lines,
+        after_context_lines,
+        merge_hunk_gap,
+        function_context,
+        structural_context,
+        max_hunk_lines,
+        hunk_ranges,
+        hunk_selection,
+        hunk_selection_per_file: false,
+        line_ranges,
+        ignore_blank_lines,
+        syntax_highlight,
+        full_syntax_highlight,
+        dim_punctuation,
+        trailing_whitespace,
+        show_invisible,
+        novel_line_marker,
+        sort_order,
+        new_file,
+        run_summary: !matches.is_present("no-summary"),
+        markdown_max_bytes,
+        language_tab_widths,
+    };
 
-            let lhs_permissions = lhs_arg.permissions();
-            let rhs_permissions = rhs_arg.permissions();
+    if matches.is_present("merge") {
+        let (display_path, input) = match &args[..] {
+            [path] => (
+                path.to_string_lossy().to_string(),
+                MergeInput::Conflicts(FileArgument::from_path_argument(path)),
+            ),
+            [base_path, ours_path, theirs_path] => {
+                let ours = FileArgument::from_cli_argument(ours_path);
+                (
+                    ours.to_string(),
+                    MergeInput::Files {
+                        base: FileArgument::from_cli_argument(base_path),
+                        ours,
+                        theirs: FileArgument::from_cli_argument(theirs_path),
+                    },
+                )
+            }
+            _ => {
+                eprintln!("error: --merge requires three files (base, ours and theirs), or a single file with conflict markers.");
+                std::process::exit(EXIT_BAD_ARGUMENTS);
+            }
+        };
 
-            (
-                display_path,
-                lhs_arg,
-                rhs_arg,
-                lhs_permissions,
-                rhs_permissions,
-                None,
-            )
+        if !matches!(
+            display_options.display_mode,
+            DisplayMode::SideBySide | DisplayMode::SideBySideShowBoth
+        ) {
+            eprintln!("error: --merge only supports side-by-side display.");
+            std::process::exit(EXIT_BAD_ARGUMENTS);
         }
-        [display_path, lhs_tmp_file, _lhs_hash, lhs_mode, rhs_tmp_file, _rhs_hash, rhs_mode] => {
-            // https://git-scm.com/docs/git#Documentation/git.txt-codeGITEXTERNALDIFFcode
-            (
-                display_path.to_string_lossy().to_string(),
-                FileArgument::from_path_argument(lhs_tmp_file),
-                FileArgument::from_path_argument(rhs_tmp_file),
-                FilePermissions::try_from(*lhs_mode).ok(),
-                FilePermissions::try_from(*rhs_mode).ok(),
-                None,
-            )
+
+        return Mode::Merge {
+            diff_options,
+            display_options,
+            language_overrides,
+            input,
+            display_path,
+            output,
+        };
+    }
+
+    if matches.is_present("combined") {
+        let [parent1_path, parent2_path, merged_path] = &args[..] else {
+            eprintln!("error: --combined requires three files (parent 1, parent 2 and the merged result).");
+            std::process::exit(EXIT_BAD_ARGUMENTS);
+        };
+
+        if !matches!(
+            display_options.display_mode,
+            DisplayMode::SideBySide | DisplayMode::SideBySideShowBoth
+        ) {
+            eprintln!("error: --combined only supports side-by-side display.");
+            std::process::exit(EXIT_BAD_ARGUMENTS);
         }
-        [old_name, lhs_tmp_file, _lhs_hash, lhs_mode, rhs_tmp_file, _rhs_hash, rhs_mode, new_name, _similarity] =>
-        {
-            // Rename file.
-            // TODO: where does git document these 9 arguments?
 
-            let old_name = old_name.to_string_lossy().to_string();
-            let new_name = new_name.to_string_lossy().to_string();
-            let renamed = format!("Renamed from {} to {}", old_name, new_name);
+        let merged = FileArgument::from_cli_argument(merged_path);
+        return Mode::Merge {
+            diff_options,
+            display_options,
+            language_overrides,
+            display_path: merged.to_string(),
+            input: MergeInput::Combined {
+                parent1: FileArgument::from_cli_argument(parent1_path),
+                parent2: FileArgument::from_cli_argument(parent2_path),
+                merged,
+            },
+            output,
+        };
+    }
 
-            (
-                new_name,
-                FileArgument::from_path_argument(lhs_tmp_file),
-                FileArgument::from_path_argument(rhs_tmp_file),
-                FilePermissions::try_from(*lhs_mode).ok(),
-                FilePermissions::try_from(*rhs_mode).ok(),
-                Some(renamed),
-            )
+    if matches.is_present("from-patch") {
+        let patch = match &args[..] {
+            [] => FileArgument::Stdin,
+            [path] => FileArgument::from_cli_argument(path),
+            _ => {
+                eprintln!("error: --from-patch takes a single patch file.");
+                std::process::exit(EXIT_BAD_ARGUMENTS);
+            }
+        };
+        if matches!(display_options.display_mode, DisplayMode::JsonPatch) {
+            eprintln!("error: --display=json-patch requires two files.");
+            std::process::exit(EXIT_BAD_ARGUMENTS);
         }
-        [path] => {
-            let display_options = DisplayOptions {
-                background_color,
-                use_color,
-                print_unchanged,
-                tab_width,
-                display_mode,
-                terminal_width,
-                num_context_lines,
-                syntax_highlight,
-                sort_paths,
-            };
 
-            let display_path = path.to_string_lossy().to_string();
-            let path = FileArgument::from_path_argument(path);
-            return Mode::DiffFromConflicts {
-                display_path,
-                path,
+        return Mode::FromPatch {
+            diff_options,
+            display_options,
+            set_exit_code,
+            fail_on_fallback,
+            quiet,
+            language_overrides,
+            patch,
+            output,
+        };
+    }
+
+    let git_sources = if let Some(revs) = matches.values_of("git") {
+        Some(match &revs.collect::<Vec<_>>()[..] {
+            [lhs_rev] => (
+                git::Source::Revision(lhs_rev.to_string()),
+                git::Source::Worktree,
+            ),
+            [lhs_rev, rhs_rev] => (
+                git::Source::Revision(lhs_rev.to_string()),
+                git::Source::Revision(rhs_rev.to_string()),
+            ),
+            _ => unreachable!("clap has already validated git"),
+        })
+    } else if matches.is_present("git-staged") {
+        Some((git::Source::Revision("HEAD".to_owned()), git::Source::Index))
+    } else if matches.is_present("git-worktree") {
+        Some((git::Source::Index, git::Source::Worktree))
+    } else {
+        None
+    };
+    if let Some((lhs, rhs)) = git_sources {
+        if matches!(display_options.display_mode, DisplayMode::JsonPatch) {
+            eprintln!("error: --display=json-patch requires two files.");
+            std::process::exit(EXIT_BAD_ARGUMENTS);
+        }
+
+        return Mode::GitRevisions {
+            diff_options,
+            display_options,
+            set_exit_code,
+            fail_on_fallback,
+            quiet,
+            language_overrides,
+            lhs,
+            rhs,
+            paths: args.iter().map(PathBuf::from).collect(),
+            output,
+        };
+    }
+
+    if matches.is_present("watch") {
+        let (lhs_path, rhs_path) = match &args[..] {
+            [lhs_path, rhs_path]
+                if Path::new(lhs_path).is_file() && Path::new(rhs_path).is_file() =>
+            {
+                (PathBuf::from(lhs_path), PathBuf::from(rhs_path))
+            }
+            _ => {
+                eprintln!("error: --watch requires two files.");
+                std::process::exit(EXIT_BAD_ARGUMENTS);
+            }
+        };
+
+        if !matches!(
+            display_options.display_mode,
+            DisplayMode::Inline
+                | DisplayMode::SideBySide
+                | DisplayMode::SideBySideShowBoth
+                | DisplayMode::Patch
+        ) {
+            eprintln!("error: --watch only supports inline, side-by-side and patch display.");
+            std::process::exit(EXIT_BAD_ARGUMENTS);
+        }
+        if output_file.is_some() {
+            eprintln!("error: --output-file can't be used with --watch.");
+            std::process::exit(EXIT_BAD_ARGUMENTS);
+        }
+        if interactive {
+            eprintln!("error: --interactive can't be used with --watch.");
+            std::process::exit(EXIT_BAD_ARGUMENTS);
+        }
+        // Each diff clears the screen, so there's no need for a
+        // pager.
+
+        let (lhs_display_arg, rhs_display_arg) = strip_prefixes(
+            &FileArgument::NamedPath(lhs_path.clone()),
+            &FileArgument::NamedPath(rhs_path.clone()),
+            &prefixes,
+        );
+        let display_path = build_display_path(&lhs_display_arg, &rhs_display_arg);
+        return Mode::Watch {
+            diff_options,
+            display_options,
+            language_overrides,
+            lhs_path,
+            rhs_path,
+            display_path,
+        };
+    }
+
+    if let [arg] = &args[..] {
+        if let Some(pairs_path) = arg.to_str().and_then(|arg| arg.strip_prefix('@')) {
+            if matches!(display_options.display_mode, DisplayMode::JsonPatch) {
+                eprintln!("error: --display=json-patch requires two files.");
+                std::process::exit(EXIT_BAD_ARGUMENTS);
+            }
+
+            let pairs = match read_pairs_file(Path::new(pairs_path), matches.is_present("null")) {
+                Ok(pairs) => pairs,
+                Err(e) => {
+                    eprintln!("error: {}", e);
+                    std::process::exit(EXIT_BAD_ARGUMENTS);
+                }
+            };
+            let pairs = pairs
+                .into_iter()
+                .map(|(lhs_path, rhs_path)| {
+                    let lhs_path = FileArgument::from_path_argument(lhs_path.as_os_str());
+                    let rhs_path = FileArgument::from_path_argument(rhs_path.as_os_str());
+                    let (lhs_display_arg, rhs_display_arg) =
+                        strip_prefixes(&lhs_path, &rhs_path, &prefixes);
+                    (
+                        build_display_path(&lhs_display_arg, &rhs_display_arg),
+                        lhs_path,
+                        rhs_path,
+                    )
+                })
+                .collect();
+
+            return Mode::DiffPairs {
                 diff_options,
                 display_options,
                 set_exit_code,
+                fail_on_fallback,
+                quiet,
                 language_overrides,
+                pairs,
+                output,
             };
         }
-        _ => {
-            if !args.is_empty() {
-                eprintln!(
-                    "error: Difftastic does not support being called with {} argument{}.\n",
-                    args.len(),
-                    if args.len() == 1 { "" } else { "s" }
-                );
-            }
-            eprintln!("USAGE:\n\n    {}\n", USAGE);
-            eprintln!("For more information try --help");
-            std::process::exit(EXIT_BAD_ARGUMENTS);
-        }
-    };
+    }
+    if matches.is_present("null") {
+        eprintln!("error: --null requires an argument file, e.g. @pairs.txt.");
+        std::process::exit(EXIT_BAD_ARGUMENTS);
+    }
 
-    let display_options = DisplayOptions {
-        background_color,
-        use_color,
-        print_unchanged,
-        tab_width,
-        display_mode,
-        terminal_width,
-        num_context_lines,
-        syntax_highlight,
-        sort_paths,
+    let mut extra_info = None;
+    let mut textconv = None;
+    let textconv_auto = matches.value_of("textconv") == Some("auto");
+    let git_side = |path: &OsStr, hash: &OsStr| GitSide {
+        path: path.to_string_lossy().into_owned(),
+        hash: hash.to_string_lossy().into_owned(),
     };
 
+    // TODO: document these different ways of calling difftastic.
+    let (display_path, lhs_path, rhs_path, lhs_permissions, rhs_permissions, renamed_from) =
+        match &args[..] {
+            [lhs_path, rhs_path] => {
+                let lhs_arg = FileArgument::from_cli_argument(lhs_path);
+                let rhs_arg = FileArgument::from_cli_argument(rhs_path);
+                if matches!(
+                    (&lhs_arg, &rhs_arg),
+                    (FileArgument::Stdin, FileArgument::Stdin)
+                ) {
+                    eprintln!("error: Only one of the files can be read from stdin.");
+                    std::process::exit(EXIT_BAD_ARGUMENTS);
+                }
+
+                // Labels are shown instead of the paths, so treat them
+                // as paths when choosing the name in the header.
+                let label = |name: &str| {
+                    matches
+                        .value_of(name)
+                        .map(|label| FileArgument::NamedPath(PathBuf::from(label)))
+                };
+                let lhs_label = label("label-left");
+                let rhs_label = label("label-right");
+                let (mut lhs_display_arg, mut rhs_display_arg) =
+                    strip_prefixes(&lhs_arg, &rhs_arg, &prefixes);
+
+                let is_hg = matches.is_present("hg")
+                    || matches!(&lhs_arg, FileArgument::NamedPath(path) if hg::is_snapshot_path(path));
+                if is_hg {
+                    extra_info = Some(hg_display_args(
+                        &lhs_arg,
+                        &rhs_arg,
+                        &mut lhs_display_arg,
+                        &mut rhs_display_arg,
+                    ));
+                }
+
+                // Show the depot path rather than the name of the
+                // temporary copy of the depot revision.
+                if let (FileArgument::NamedPath(lhs), FileArgument::NamedPath(rhs)) =
+                    (&lhs_arg, &rhs_arg)
+                {
+                    if p4::is_p4_diff(lhs) {
+                        if let Some(depot_path) = p4::depot_path(rhs) {
+                            lhs_display_arg = FileArgument::NamedPath(PathBuf::from(&depot_path));
+                            rhs_display_arg = FileArgument::NamedPath(PathBuf::from(&depot_path));
+                        }
+                    }
+                }
+
+                let svn_labels: Vec<&str> = matches
+                    .values_of("svn-label")
+                    .map(|labels| labels.collect())
+                    .unwrap_or_default();
+                let display_path = match svn_labels[..] {
+                    [] => build_display_path(
+                        lhs_label.as_ref().unwrap_or(&lhs_display_arg),
+                        rhs_label.as_ref().unwrap_or(&rhs_display_arg),
+                    ),
+                    [lhs_svn_label, rhs_svn_label] => {
+                        let (display_path, svn_info) =
+                            svn_display_path(lhs_svn_label, rhs_svn_label);
+                        if let Some(svn_info) = svn_info {
+                            extra_info = Some(svn_info);
+                        }
+                        display_path
+                    }
+                    _ => {
+                        eprintln!("error: -L must be given twice, for the old and new file.");
+                        std::process::exit(EXIT_BAD_ARGUMENTS);
+                    }
+                };
+
+                let lhs_permissions = lhs_arg.permissions();
+                let rhs_permissions = rhs_arg.permissions();
+
+                (
+                    display_path,
+                    lhs_arg,
+                    rhs_arg,
+                    lhs_permissions,
+                    rhs_permissions,
+                    None,
+                )
+            }
+            [display_path, lhs_tmp_file, lhs_hash, lhs_mode, rhs_tmp_file, rhs_hash, rhs_mode] => {
+                // https://git-scm.com/docs/git#Documentation/git.txt-codeGITEXTERNALDIFFcode
+                if textconv_auto {
+                    textconv = Some((
+                        git_side(display_path, lhs_hash),
+                        git_side(display_path, rhs_hash),
+                    ));
+                }
+                (
+                    display_path.to_string_lossy().to_string(),
+                    FileArgument::from_path_argument(lhs_tmp_file),
+                    FileArgument::from_path_argument(rhs_tmp_file),
+                    FilePermissions::try_from(*lhs_mode).ok(),
+                    FilePermissions::try_from(*rhs_mode).ok(),
+                    None,
+                )
+            }
+            [old_name, lhs_tmp_file, lhs_hash, lhs_mode, rhs_tmp_file, rhs_hash, rhs_mode, new_name, _similarity] =>
+            {
+                // Rename file.
+                // TODO: where does git document these 9 arguments?
+                if textconv_auto {
+                    textconv = Some((git_side(old_name, lhs_hash), git_side(new_name, rhs_hash)));
+                }
+
+                let old_name = old_name.to_string_lossy().to_string();
+                let new_name = new_name.to_string_lossy().to_string();
+                let renamed_from = (old_name != new_name).then_some(old_name);
+
+                (
+                    new_name,
+                    FileArgument::from_path_argument(lhs_tmp_file),
+                    FileArgument::from_path_argument(rhs_tmp_file),
+                    FilePermissions::try_from(*lhs_mode).ok(),
+                    FilePermissions::try_from(*rhs_mode).ok(),
+                    renamed_from,
+                )
+            }
+            [path] => {
+                if matches!(display_options.display_mode, DisplayMode::JsonPatch) {
+                    eprintln!("error: --display=json-patch requires two files.");
+                    std::process::exit(EXIT_BAD_ARGUMENTS);
+                }
+
+                let display_path = path.to_string_lossy().to_string();
+                let path = FileArgument::from_path_argument(path);
+                return Mode::DiffFromConflicts {
+                    display_path,
+                    path,
+                    diff_options,
+                    display_options,
+                    set_exit_code,
+                    fail_on_fallback,
+                    quiet,
+                    language_overrides,
+                    output,
+                };
+            }
+            _ => {
+                if !args.is_empty() {
+                    eprintln!(
+                        "error: Difftastic does not support being called with {} argument{}.\n",
+                        args.len(),
+                        if args.len() == 1 { "" } else { "s" }
+                    );
+                }
+                eprintln!("USAGE:\n\n    {}\n", USAGE);
+                eprintln!("For more information try --help");
+                std::process::exit(EXIT_BAD_ARGUMENTS);
+            }
+        };
+
     Mode::Diff {
         diff_options,
         display_options,
         set_exit_code,
+        fail_on_fallback,
+        quiet,
         language_overrides,
         lhs_path,
         rhs_path,
         lhs_permissions,
         rhs_permissions,
         display_path,
-        renamed,
+        renamed_from,
+        extra_info,
+        textconv,
+        output,
     }
 }
 
-/// Try to work out the width of the terminal we're on, or fall back
-/// to a sensible default value.
-fn detect_terminal_width() -> usize {
-    if let Ok((columns, _rows)) = crossterm::terminal::size() {
-        if columns > 0 {
-            return columns.into();
-        }
+/// Parse a `--width` value, which is a number of columns or `auto`.
+fn parse_width(s: &str) -> Result<Option<usize>, String> {
+    if s == "auto" {
+        return Ok(None);
+    }
+    s.parse::<usize>()
+        .map(Some)
+        .map_err(|_| format!("'{}' is not a number of columns or 'auto'", s))
+}
+
+/// The width of the terminal we're on, if we can detect it. This
+/// also works when stdout is piped, as long as we have a controlling
+/// terminal.
+fn terminal_size() -> Option<usize> {
+    match crossterm::terminal::size() {
+        Ok((columns, _rows)) if columns > 0 => Some(columns.into()),
+        _ => None,
     }
+}
 
-    // If crossterm couldn't detect the terminal width, use the
-    // shell variable COLUMNS if it's set. This helps with terminals like eshell.
-    //
-    // https://github.com/Wilfred/difftastic/issues/707
-    // https://stackoverflow.com/a/48016366
-    if let Ok(columns_env_val) = std::env::var("COLUMNS") {
-        if let Ok(columns) = columns_env_val.parse::<usize>() {
-            if columns > 0 {
-                return columns;
-            }
+/// Work out the display width. `arg_width` is the value of `--width`,
+/// where `Some(None)` means `--width auto`.
+///
+/// COLUMNS takes precedence over the terminal size, as it's how
+/// terminals like eshell report their width, and how users set the
+/// width when piping output.
+///
+/// <https://github.com/Wilfred/difftastic/issues/707>
+/// <https://stackoverflow.com/a/48016366>
+fn resolve_width(
+    arg_width: Option<Option<usize>>,
+    columns_env: Option<&str>,
+    terminal_size: impl Fn() -> Option<usize>,
+) -> usize {
+    let columns = match arg_width {
+        Some(Some(width)) => return width,
+        // Use the terminal width, even if COLUMNS is set by a pager.
+        Some(None) => None,
+        None => columns_env
+            .and_then(|s| s.parse::<usize>().ok())
+            .filter(|columns| *columns > 0),
+    };
+
+    columns
+        .or_else(terminal_size)
+        .unwrap_or(DEFAULT_TERMINAL_WIDTH)
+}
+
+/// Guess how many colors the terminal supports from the environment.
+fn detect_color_depth() -> ColorDepth {
+    if let Ok(colorterm) = env::var("COLORTERM") {
+        if colorterm == "truecolor" || colorterm == "24bit" {
+            return ColorDepth::TrueColor;
         }
     }
 
-    DEFAULT_TERMINAL_WIDTH
+    match env::var("TERM") {
+        Ok(term) if term.contains("256color") => ColorDepth::Ansi256,
+        _ => ColorDepth::Ansi16,
+    }
 }
 
 pub(crate) fn should_use_color(color_output: ColorOutput) -> bool {
+    let no_color = env::var("NO_COLOR").ok();
+    let clicolor_force = env::var("CLICOLOR_FORCE").ok();
+    // Always enable colour if stdout is a TTY or if the git pager is active.
+    // TODO: consider following the env parsing logic in git_config_bool
+    // in config.c.
+    let is_tty = std::io::stdout().is_tty() || env::var("GIT_PAGER_IN_USE").is_ok();
+
+    decide_color(
+        color_output,
+        no_color.as_deref(),
+        clicolor_force.as_deref(),
+        is_tty,
+    )
+}
+
+/// Decide whether to use color. An explicit `--color=always` or
+/// `--color=never` wins, then `NO_COLOR` (see <https://no-color.org/>),
+/// then `CLICOLOR_FORCE`, then whether we're writing to a TTY.
+fn decide_color(
+    color_output: ColorOutput,
+    no_color: Option<&str>,
+    clicolor_force: Option<&str>,
+    is_tty: bool,
+) -> bool {
     match color_output {
         ColorOutput::Always => true,
+        ColorOutput::Never => false,
         ColorOutput::Auto => {
-            // Always enable colour if stdout is a TTY or if the git pager is active.
-            // TODO: consider following the env parsing logic in git_config_bool
-            // in config.c.
-            std::io::stdout().is_tty() || env::var("GIT_PAGER_IN_USE").is_ok()
+            // Both variables are ignored when set to the empty
+            // string, and CLICOLOR_FORCE=0 doesn't force color.
+            if no_color.map_or(false, |value| !value.is_empty()) {
+                false
+            } else if clicolor_force.map_or(false, |value| !value.is_empty() && value != "0") {
+                true
+            } else {
+                is_tty
+            }
         }
-        ColorOutput::Never => false,
     }
 }
 
@@ -907,9 +3615,373 @@ mod tests {
         app().debug_assert();
     }
 
+    /// Options that are actions, or only make sense for a single
+    /// invocation, rather than settings.
+    const NO_ENV_VAR: &[&str] = &[
+        "cache-clear",
+        "combined",
+        "context-format",
+        "dump-graph",
+        "dump-graph-depth",
+        "dump-syntax",
+        "dump-ts",
+        "exclude",
+        "from-patch",
+        "generate-completions",
+        "git",
+        "git-staged",
+        "git-worktree",
+        "guess-language",
+        // Added by clap.
+        "help",
+        // Describes how difftastic was invoked, like --merge.
+        "hg",
+        "hunk",
+        "ignore-line-endings",
+        "include",
+        // Interactive mode would stop scripts that read the output.
+        "interactive",
+        "jj",
+        "label-left",
+        "label-right",
+        "language",
+        "lines",
+        "list-languages",
+        "merge",
+        "null",
+        // DFT_PAGER is the pager command, like PAGER.
+        "pager",
+        "paths",
+        "prefix-left",
+        "prefix-right",
+        "rcs-format",
+        "summary-format",
+        "svn-label",
+        "unified",
+        "version",
+        "watch",
+    ];
+
+    #[test]
+    fn test_every_option_has_env_var() {
+        for arg in app().get_arguments() {
+            let name = arg.get_id();
+            let expected = if NO_ENV_VAR.contains(&name) {
+                None
+            } else {
+                Some(format!("DFT_{}", name.to_uppercase().replace('-', "_")))
+            };
+
+            assert_eq!(
+                arg.get_env()
+                    .map(|env_name| env_name.to_string_lossy().into_owned()),
+                expected,
+                "Unexpected environment variable for --{}",
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn test_completion_app_languages() {
+        let app = completion_app();
+        app.clone().debug_assert();
+
+        let override_arg = app
+            .get_arguments()
+            .find(|arg| arg.get_id() == "override")
+            .unwrap();
+        let values = override_arg.get_possible_values().unwrap();
+        assert!(values.iter().any(|value| value.get_name() == "Rust"));
+        assert!(values.iter().any(|value| value.get_name() == "text"));
+    }
+
+    #[test]
+    fn test_decide_color_explicit_flag() {
+        for no_color in [None, Some("1")] {
+            for clicolor_force in [None, Some("1")] {
+                for is_tty in [false, true] {
+                    assert!(decide_color(
+                        ColorOutput::Always,
+                        no_color,
+                        clicolor_force,
+                        is_tty
+                    ));
+                    assert!(!decide_color(
+                        ColorOutput::Never,
+                        no_color,
+                        clicolor_force,
+                        is_tty
+                    ));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_decide_color_no_color() {
+        for clicolor_force in [None, Some("1")] {
+            for is_tty in [false, true] {
+                assert!(!decide_color(
+                    ColorOutput::Auto,
+                    Some("1"),
+                    clicolor_force,
+                    is_tty
+                ));
+            }
+        }
+        // An empty NO_COLOR is ignored.
+        assert!(decide_color(ColorOutput::Auto, Some(""), None, true));
+    }
+
+    #[test]
+    fn test_decide_color_clicolor_force() {
+        assert!(decide_color(ColorOutput::Auto, None, Some("1"), false));
+        assert!(!decide_color(ColorOutput::Auto, None, Some("0"), false));
+        assert!(!decide_color(ColorOutput::Auto, None, Some(""), false));
+        assert!(decide_color(ColorOutput::Auto, Some(""), Some("1"), false));
+    }
+
+    #[test]
+    fn test_decide_color_tty() {
+        assert!(decide_color(ColorOutput::Auto, None, None, true));
+        assert!(!decide_color(ColorOutput::Auto, None, None, false));
+    }
+
     #[test]
     fn test_detect_display_width() {
         // Basic smoke test.
-        assert!(detect_terminal_width() > 10);
+        assert!(resolve_width(None, None, terminal_size) > 10);
+    }
+
+    #[test]
+    fn test_parse_marker() {
+        assert_eq!(parse_marker("▌"), Ok('▌'));
+        assert!(parse_marker("").is_err());
+        assert!(parse_marker("ab").is_err());
+        assert!(parse_marker("\t").is_err());
+    }
+
+    #[test]
+    fn test_parse_jobs() {
+        assert_eq!(parse_jobs("4"), Ok(4));
+        assert!(parse_jobs("0").is_err());
+        assert!(parse_jobs("all").is_err());
+    }
+
+    #[test]
+    fn test_parse_timeout() {
+        assert_eq!(parse_timeout("2.5"), Ok(Duration::from_millis(2500)));
+        assert!(parse_timeout("0").is_err());
+        assert!(parse_timeout("soon").is_err());
+    }
+
+    #[test]
+    fn test_parse_hunk_range() {
+        assert_eq!(parse_hunk_range("3"), Ok((3, 3)));
+        assert_eq!(parse_hunk_range("2-4"), Ok((2, 4)));
+        assert!(parse_hunk_range("0").is_err());
+        assert!(parse_hunk_range("4-2").is_err());
+        assert!(parse_hunk_range("x").is_err());
+    }
+
+    #[test]
+    fn test_resolve_width() {
+        let no_terminal = || None;
+        let terminal = || Some(120);
+
+        assert_eq!(resolve_width(Some(Some(50)), Some("90"), terminal), 50);
+        assert_eq!(resolve_width(None, Some("90"), terminal), 90);
+        assert_eq!(resolve_width(None, Some("not a number"), terminal), 120);
+        assert_eq!(resolve_width(None, None, terminal), 120);
+        assert_eq!(
+            resolve_width(None, None, no_terminal),
+            DEFAULT_TERMINAL_WIDTH
+        );
+    }
+
+    #[test]
+    fn test_resolve_width_auto() {
+        assert_eq!(resolve_width(Some(None), Some("90"), || Some(120)), 120);
+        assert_eq!(
+            resolve_width(Some(None), Some("90"), || None),
+            DEFAULT_TERMINAL_WIDTH
+        );
+        assert!(parse_width("wide").is_err());
+    }
+
+    #[test]
+    fn test_parse_tab_width() {
+        assert_eq!(parse_tab_width("4"), Ok((None, 4)));
+        assert_eq!(
+            parse_tab_width("go=8"),
+            Ok((Some(LanguageOverride::Language(Language::Go)), 8))
+        );
+        assert_eq!(
+            parse_tab_width("text=2"),
+            Ok((Some(LanguageOverride::PlainText), 2))
+        );
+        assert!(parse_tab_width("NoSuchLanguage=2").is_err());
+        assert!(parse_tab_width("Go=wide").is_err());
+    }
+
+    #[test]
+    fn test_parse_line_ranges() {
+        assert_eq!(
+            parse_line_ranges("10-20"),
+            Ok(LineRanges {
+                lhs: None,
+                rhs: (10, 20)
+            })
+        );
+        assert_eq!(
+            parse_line_ranges("0-5,7"),
+            Ok(LineRanges {
+                lhs: Some((1, 5)),
+                rhs: (7, 7)
+            })
+        );
+        assert!(parse_line_ranges("20-10").is_err());
+        assert!(parse_line_ranges("a-b").is_err());
+    }
+
+    #[test]
+    fn test_shows_hunk() {
+        let display_options = DisplayOptions {
+            hunk_selection: vec![(1, 1), (3, 4)],
+            ..DisplayOptions::default()
+        };
+
+        assert!(display_options.shows_hunk(1));
+        assert!(!display_options.shows_hunk(2));
+        assert!(display_options.shows_hunk(4));
+        assert!(DisplayOptions::default().shows_hunk(2));
+    }
+
+    #[test]
+    fn test_parse_context() {
+        assert_eq!(parse_context("5"), Ok(Context::Lines(5)));
+        assert_eq!(parse_context("function"), Ok(Context::Function));
+        assert!(parse_context("-1").is_err());
+    }
+
+    #[test]
+    fn test_parse_color() {
+        assert_eq!(parse_color("red"), Ok(Color::Ansi(AnsiColors::Red)));
+        assert_eq!(
+            parse_color("Bright-Blue"),
+            Ok(Color::Ansi(AnsiColors::BrightBlue))
+        );
+        assert_eq!(parse_color("196"), Ok(Color::Xterm(196)));
+        assert_eq!(parse_color("#00afff"), Ok(Color::Rgb(0, 0xaf, 0xff)));
+    }
+
+    #[test]
+    fn test_parse_color_invalid() {
+        assert!(parse_color("256").is_err());
+        assert!(parse_color("#00af").is_err());
+        assert!(parse_color("reddish").is_err());
+    }
+
+    fn named_path(path: &str) -> FileArgument {
+        FileArgument::NamedPath(PathBuf::from(path))
+    }
+
+    #[test]
+    fn test_strip_prefixes_common() {
+        let prefixes = StripPrefixes {
+            common: true,
+            ..StripPrefixes::default()
+        };
+        assert_eq!(
+            strip_prefixes(
+                &named_path("/tmp/a/release-1.2/src/old.rs"),
+                &named_path("/tmp/a/release-1.3/src/new.rs"),
+                &prefixes
+            ),
+            (
+                named_path("release-1.2/src/old.rs"),
+                named_path("release-1.3/src/new.rs")
+            )
+        );
+    }
+
+    #[test]
+    fn test_strip_prefixes_explicit() {
+        let prefixes = StripPrefixes {
+            common: true,
+            lhs: Some(PathBuf::from("release-1.2")),
+            rhs: Some(PathBuf::from("release-1.3/")),
+        };
+        assert_eq!(
+            strip_prefixes(
+                &named_path("release-1.2/src/old.rs"),
+                &named_path("release-1.3/src/new.rs"),
+                &prefixes
+            ),
+            (named_path("src/old.rs"), named_path("src/new.rs"))
+        );
+    }
+
+    #[test]
+    fn test_strip_prefixes_keeps_file_name() {
+        let prefixes = StripPrefixes {
+            common: true,
+            ..StripPrefixes::default()
+        };
+        assert_eq!(
+            strip_prefixes(&named_path("a/foo.rs"), &named_path("a/foo.rs"), &prefixes),
+            (named_path("foo.rs"), named_path("foo.rs"))
+        );
+    }
+
+    #[test]
+    fn test_is_jj_diff() {
+        assert!(is_jj_diff(
+            Path::new("/tmp/jj-diff-AbCdEf/left"),
+            Path::new("/tmp/jj-diff-AbCdEf/right")
+        ));
+        assert!(!is_jj_diff(
+            Path::new("/tmp/git-difftool.AbCdEf/left"),
+            Path::new("/tmp/git-difftool.AbCdEf/right")
+        ));
+    }
+
+    #[test]
+    fn test_svn_display_path() {
+        assert_eq!(
+            svn_display_path("foo.c\t(revision 12)", "foo.c\t(working copy)"),
+            (
+                "foo.c".to_owned(),
+                Some("Comparing revision 12 to working copy.".to_owned())
+            )
+        );
+        assert_eq!(
+            svn_display_path("old.c\t(revision 12)", "new.c\t(revision 13)"),
+            (
+                "new.c".to_owned(),
+                Some("Comparing old.c (revision 12) to new.c (revision 13).".to_owned())
+            )
+        );
+        assert_eq!(
+            svn_display_path("foo.c", "foo.c"),
+            ("foo.c".to_owned(), None)
+        );
+    }
+
+    #[test]
+    fn test_is_git_dir_diff() {
+        assert!(is_git_dir_diff(
+            Path::new("/tmp/git-difftool.AbCdEf/left/"),
+            Path::new("/tmp/git-difftool.AbCdEf/right/")
+        ));
+        assert!(!is_git_dir_diff(
+            Path::new("/tmp/git-difftool.AbCdEf/left"),
+            Path::new("/tmp/git-difftool.XyZ123/right")
+        ));
+        assert!(!is_git_dir_diff(
+            Path::new("sample_files/dir_1"),
+            Path::new("sample_files/dir_2")
+        ));
     }
 }
@@ -0,0 +1,61 @@
+//! Support for Perforce, which runs `$P4DIFF OLD NEW` for `p4 diff`.
+//!
+//! The old file is a copy of the depot revision in the temporary
+//! directory, with a meaningless name, so we ask `p4 where` for the
+//! depot path of the new file and show that instead.
+
+use std::{env, path::Path, process::Command};
+
+/// Are we being run by `p4 diff`? Perforce copies the depot revision
+/// to the temporary directory, and users who set `P4DIFF` usually
+/// have a Perforce client configured.
+pub(crate) fn is_p4_diff(lhs_path: &Path) -> bool {
+    let has_p4_env = ["P4DIFF", "P4CLIENT", "P4CONFIG"]
+        .iter()
+        .any(|name| env::var_os(name).is_some());
+    has_p4_env && lhs_path.starts_with(env::temp_dir())
+}
+
+/// The value of `depotFile` in the output of `p4 -ztag where`, which
+/// has one `... FIELD VALUE` line per field.
+fn parse_ztag_depot_file(stdout: &str) -> Option<String> {
+    stdout
+        .lines()
+        .find_map(|line| line.strip_prefix("... depotFile "))
+        .map(|depot_file| depot_file.trim_end().to_owned())
+}
+
+/// The depot path of `local_path`, such as "//depot/src/main.c", or
+/// `None` if it isn't in the Perforce client.
+pub(crate) fn depot_path(local_path: &Path) -> Option<String> {
+    let output = Command::new("p4")
+        .args(["-ztag", "where"])
+        .arg(local_path)
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    parse_ztag_depot_file(&String::from_utf8_lossy(&output.stdout))
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_ztag_depot_file() {
+        let stdout = "\
+... depotFile //depot/src/main c.c
+... clientFile //alice-ws/src/main c.c
+... path /home/alice/ws/src/main c.c
+";
+        assert_eq!(
+            parse_ztag_depot_file(stdout),
+            Some("//depot/src/main c.c".to_owned())
+        );
+        assert_eq!(parse_ztag_depot_file(""), None);
+    }
+}
@@ -0,0 +1,149 @@
+//! Pipe the diff to the user's pager, like git does, so long diffs
+//! don't fill the terminal's scrollback.
+
+use std::{
+    env,
+    io::{LineWriter, Write},
+    process::{Child, ChildStdin, Command, Stdio},
+};
+
+use lazy_static::lazy_static;
+
+use crate::api::CancellationToken;
+
+lazy_static! {
+    /// Cancelled when the user presses Ctrl-C while the pager is
+    /// running, see [handle_interrupts].
+    pub(crate) static ref INTERRUPT: CancellationToken = CancellationToken::new();
+}
+
+/// The pager used when neither `DFT_PAGER` nor `PAGER` is set. `-F`
+/// makes less exit straight away if the diff fits on one screen.
+const DEFAULT_PAGER: &str = "less -RFX";
+
+/// Choose the pager command from the values of `DFT_PAGER` and
+/// `PAGER`. Setting either to the empty string or `cat` disables the
+/// pager.
+fn choose_pager(dft_pager: Option<&str>, pager: Option<&str>) -> Option<String> {
+    let command = dft_pager.or(pager).unwrap_or(DEFAULT_PAGER).trim();
+    if command.is_empty() || command == "cat" {
+        None
+    } else {
+        Some(command.to_owned())
+    }
+}
+
+/// The pager command to use, if any.
+pub(crate) fn pager_command() -> Option<String> {
+    choose_pager(
+        env::var("DFT_PAGER").ok().as_deref(),
+        env::var("PAGER").ok().as_deref(),
+    )
+}
+
+/// A running pager. Writes go to the pager's stdin, and dropping the
+/// pager waits for the user to quit it.
+pub(crate) struct Pager {
+    stdin: Option<LineWriter<ChildStdin>>,
+    child: Child,
+}
+
+impl Pager {
+    pub(crate) fn spawn(command: &str) -> std::io::Result<Self> {
+        let mut cmd = if cfg!(windows) {
+            let mut words = command.split_whitespace();
+            let mut cmd = Command::new(words.next().unwrap_or(DEFAULT_PAGER));
+            cmd.args(words);
+            cmd
+        } else {
+            // Like git, run the pager with the shell so it can
+            // include arguments and quoting.
+            let mut cmd = Command::new("sh");
+            cmd.arg("-c").arg(command);
+            cmd
+        };
+        // Git sets the same default options for less, so a plain
+        // PAGER=less still shows colors.
+        if env::var_os("LESS").is_none() {
+            cmd.env("LESS", "FRX");
+        }
+
+        let mut child = cmd.stdin(Stdio::piped()).spawn()?;
+        let stdin = child.stdin.take().map(LineWriter::new);
+        handle_interrupts();
+
+        Ok(Self { stdin, child })
+    }
+
+    fn stdin(&mut self) -> &mut LineWriter<ChildStdin> {
+        self.stdin
+            .as_mut()
+            .expect("stdin is only taken when the pager is dropped")
+    }
+}
+
+impl Write for Pager {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.stdin().write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.stdin().flush()
+    }
+}
+
+impl Drop for Pager {
+    fn drop(&mut self) {
+        // Close stdin so the pager sees the end of the diff.
+        drop(self.stdin.take());
+        let _ = self.child.wait();
+    }
+}
+
+/// Ctrl-C is sent to both difftastic and the pager. The pager handles
+/// it (less stops searching or following), so we must keep running
+/// rather than exit and leave the pager in control of the
+/// terminal. If the user quits the pager, the next write gets
+/// SIGPIPE and we exit then.
+///
+/// We do stop diffing, by cancelling [INTERRUPT], so a slow
+/// directory diff doesn't carry on in the background.
+#[cfg(unix)]
+fn handle_interrupts() {
+    extern "C" fn on_interrupt(_signal: libc::c_int) {
+        INTERRUPT.cancel();
+    }
+
+    // Initialize the token now, as the signal handler mustn't
+    // allocate.
+    lazy_static::initialize(&INTERRUPT);
+    unsafe {
+        libc::signal(libc::SIGINT, on_interrupt as libc::sighandler_t);
+    }
+}
+
+#[cfg(not(unix))]
+fn handle_interrupts() {
+    // Do nothing.
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_choose_pager() {
+        assert_eq!(choose_pager(None, None), Some("less -RFX".to_owned()));
+        assert_eq!(
+            choose_pager(Some("most"), Some("more")),
+            Some("most".to_owned())
+        );
+        assert_eq!(choose_pager(None, Some("more")), Some("more".to_owned()));
+    }
+
+    #[test]
+    fn test_choose_pager_disabled() {
+        assert_eq!(choose_pager(Some(""), Some("more")), None);
+        assert_eq!(choose_pager(None, Some("cat")), None);
+    }
+}
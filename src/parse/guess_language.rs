@@ -16,8 +16,12 @@ use strum::{EnumIter, IntoEnumIterator};
 
 /// Languages supported by difftastic. Each language here has a
 /// corresponding tree-sitter parser.
+///
+/// This is part of the library API. New languages are added in minor
+/// releases.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, EnumIter)]
-pub(crate) enum Language {
+#[non_exhaustive]
+pub enum Language {
     Ada,
     Apex,
     Bash,
@@ -108,6 +112,44 @@ pub(crate) fn language_override_from_name(name: &str) -> Option<LanguageOverride
     None
 }
 
+/// The language for a `linguist-language` or `diff` value in
+/// `.gitattributes`. Attribute values can't contain spaces, so
+/// linguist accepts names with hyphens, such as `Emacs-Lisp`, and
+/// lowercase aliases. Git's built-in diff drivers have their own
+/// names, such as `cpp` and `golang`.
+pub(crate) fn language_from_attribute(value: &str) -> Option<Language> {
+    let name = value.trim().to_lowercase().replace('-', " ");
+
+    for language in Language::iter() {
+        if language_name(language).to_lowercase() == name {
+            return Some(language);
+        }
+    }
+
+    match name.as_str() {
+        "bash" | "sh" | "shell" | "zsh" => Some(Bash),
+        "cs" | "csharp" => Some(CSharp),
+        "cpp" => Some(CPlusPlus),
+        "dts" => Some(DeviceTree),
+        "elisp" | "emacs" => Some(EmacsLisp),
+        "golang" => Some(Go),
+        "js" | "node" => Some(JavaScript),
+        "jsx" => Some(JavascriptJsx),
+        "lisp" => Some(CommonLisp),
+        "bsdmake" | "makefile" | "mf" => Some(Make),
+        "obj c" | "objc" | "objectivec" => Some(ObjC),
+        "py" | "python3" => Some(Python),
+        "rb" => Some(Ruby),
+        "rs" => Some(Rust),
+        "terraform" => Some(Hcl),
+        "tex" => Some(LaTeX),
+        "ts" => Some(TypeScript),
+        "tsx" => Some(TypeScriptTsx),
+        "yml" => Some(Yaml),
+        _ => None,
+    }
+}
+
 /// The language name shown to the user.
 pub(crate) fn language_name(language: Language) -> &'static str {
     match language {
@@ -439,20 +481,11 @@ pub(crate) fn guess(
     src: &str,
     overrides: &[(LanguageOverride, Vec<glob::Pattern>)],
 ) -> Option<Language> {
-    if let Some(file_name) = path.file_name() {
-        let file_name = file_name.to_string_lossy();
-        for (lang_override, patterns) in overrides {
-            for pattern in patterns {
-                if pattern.matches(&file_name) {
-                    match lang_override {
-                        LanguageOverride::Language(lang) => return Some(*lang),
-                        LanguageOverride::PlainText => {
-                            return None;
-                        }
-                    }
-                }
-            }
-        }
+    if let Some(lang_override) = override_for(path, overrides) {
+        return match lang_override {
+            LanguageOverride::Language(lang) => Some(lang),
+            LanguageOverride::PlainText => None,
+        };
     }
 
     if let Some(lang) = from_emacs_mode_header(src) {
@@ -483,6 +516,39 @@ pub(crate) fn guess(
     None
 }
 
+/// Like [`guess`], but use `attribute_language`, the language set in
+/// `.gitattributes`, before looking at the content or the file
+/// name. Overrides still take precedence.
+pub(crate) fn guess_with_attribute(
+    path: &Path,
+    src: &str,
+    overrides: &[(LanguageOverride, Vec<glob::Pattern>)],
+    attribute_language: Option<Language>,
+) -> Option<Language> {
+    if let Some(lang_override) = override_for(path, overrides) {
+        return match lang_override {
+            LanguageOverride::Language(lang) => Some(lang),
+            LanguageOverride::PlainText => None,
+        };
+    }
+    if let Some(lang) = attribute_language {
+        return Some(lang);
+    }
+    guess(path, src, &[])
+}
+
+/// The first override whose patterns match the file name of `path`.
+fn override_for(
+    path: &Path,
+    overrides: &[(LanguageOverride, Vec<glob::Pattern>)],
+) -> Option<LanguageOverride> {
+    let file_name = path.file_name()?.to_string_lossy();
+    overrides
+        .iter()
+        .find(|(_, patterns)| patterns.iter().any(|pattern| pattern.matches(&file_name)))
+        .map(|(lang_override, _)| *lang_override)
+}
+
 /// Try to guess the language based on an Emacs mode comment at the
 /// beginning of the file.
 ///
@@ -620,6 +686,27 @@ mod tests {
 
     use super::*;
 
+    #[test]
+    fn test_language_from_attribute() {
+        assert_eq!(language_from_attribute("Rust"), Some(Rust));
+        assert_eq!(language_from_attribute("Emacs-Lisp"), Some(EmacsLisp));
+        assert_eq!(language_from_attribute("Shell"), Some(Bash));
+        assert_eq!(language_from_attribute("golang"), Some(Go));
+        assert_eq!(language_from_attribute("GLSL"), None);
+    }
+
+    #[test]
+    fn test_guess_with_attribute() {
+        let path = Path::new("foo.inc");
+        assert_eq!(guess_with_attribute(path, "", &[], Some(Php)), Some(Php));
+
+        let overrides = [(
+            LanguageOverride::PlainText,
+            vec![glob::Pattern::new("*.inc").unwrap()],
+        )];
+        assert_eq!(guess_with_attribute(path, "", &overrides, Some(Php)), None);
+    }
+
     #[test]
     fn test_guess_by_extension() {
         let path = Path::new("foo.el");
@@ -2,10 +2,11 @@
 
 #![allow(clippy::mutable_key_type)] // Hash for Syntax doesn't use mutable fields.
 
-use std::{cell::Cell, env, fmt, hash::Hash, num::NonZeroU32};
+use std::{borrow::Cow, cell::Cell, env, fmt, hash::Hash, num::NonZeroU32};
 
-use line_numbers::LinePositions;
 use line_numbers::SingleLineSpan;
+use line_numbers::{LineNumber, LinePositions};
+use regex::Regex;
 use typed_arena::Arena;
 
 use self::Syntax::*;
@@ -69,6 +70,11 @@ pub(crate) struct SyntaxInfo<'a> {
     ///
     /// Values are sequential, not hashes. Collisions never occur.
     content_id: Cell<u32>,
+    /// For atoms, a number that identifies the exact content of the
+    /// atom. Contents are interned per diff, so comparing atoms
+    /// doesn't need to compare strings. Unlike `content_id`, comments
+    /// that only differ in indentation have different values.
+    atom_id: Cell<u32>,
     /// Is this the only node with this content? Ignores nodes on the
     /// other side.
     content_is_unique: Cell<bool>,
@@ -85,6 +91,7 @@ impl<'a> SyntaxInfo<'a> {
             num_after: Cell::new(0),
             unique_id: Cell::new(NonZeroU32::new(u32::MAX).unwrap()),
             content_id: Cell::new(0),
+            atom_id: Cell::new(0),
             content_is_unique: Cell::new(false),
         }
     }
@@ -100,16 +107,16 @@ pub(crate) enum Syntax<'a> {
     List {
         info: SyntaxInfo<'a>,
         open_position: Vec<SingleLineSpan>,
-        open_content: String,
+        open_content: &'a str,
         children: Vec<&'a Syntax<'a>>,
         close_position: Vec<SingleLineSpan>,
-        close_content: String,
+        close_content: &'a str,
         num_descendants: u32,
     },
     Atom {
         info: SyntaxInfo<'a>,
         position: Vec<SingleLineSpan>,
-        content: String,
+        content: &'a str,
         kind: AtomKind,
     },
 }
@@ -194,10 +201,10 @@ impl<'a> fmt::Debug for Syntax<'a> {
 impl<'a> Syntax<'a> {
     pub(crate) fn new_list(
         arena: &'a Arena<Syntax<'a>>,
-        open_content: &str,
+        open_content: &'a str,
         open_position: Vec<SingleLineSpan>,
         children: Vec<&'a Syntax<'a>>,
-        close_content: &str,
+        close_content: &'a str,
         close_position: Vec<SingleLineSpan>,
     ) -> &'a Syntax<'a> {
         // Skip empty atoms: they aren't displayed, so there's no
@@ -237,8 +244,8 @@ impl<'a> Syntax<'a> {
         arena.alloc(List {
             info: SyntaxInfo::default(),
             open_position,
-            open_content: open_content.into(),
-            close_content: close_content.into(),
+            open_content,
+            close_content,
             close_position,
             children,
             num_descendants,
@@ -248,7 +255,7 @@ impl<'a> Syntax<'a> {
     pub(crate) fn new_atom(
         arena: &'a Arena<Syntax<'a>>,
         mut position: Vec<SingleLineSpan>,
-        mut content: &str,
+        mut content: &'a str,
         kind: AtomKind,
     ) -> &'a Syntax<'a> {
         // If a parser hasn't cleaned up \r on CRLF files with
@@ -265,7 +272,7 @@ impl<'a> Syntax<'a> {
         arena.alloc(Atom {
             info: SyntaxInfo::default(),
             position,
-            content: content.into(),
+            content,
             kind,
         })
     }
@@ -297,6 +304,13 @@ impl<'a> Syntax<'a> {
         self.info().content_id.get()
     }
 
+    /// The interned ID of this atom's content. Two atoms have the
+    /// same atom ID if and only if their content is identical. Lists
+    /// have an atom ID of 0.
+    pub(crate) fn atom_id(&self) -> u32 {
+        self.info().atom_id.get()
+    }
+
     pub(crate) fn content_is_unique(&self) -> bool {
         self.info().content_is_unique.get()
     }
@@ -373,13 +387,17 @@ fn init_info<'a>(lhs_roots: &[&'a Syntax<'a>], rhs_roots: &[&'a Syntax<'a>]) {
     set_content_id(lhs_roots, &mut existing);
     set_content_id(rhs_roots, &mut existing);
 
+    let mut atom_contents = DftHashMap::default();
+    set_atom_id(lhs_roots, &mut atom_contents);
+    set_atom_id(rhs_roots, &mut atom_contents);
+
     set_content_is_unique(lhs_roots);
     set_content_is_unique(rhs_roots);
 }
 
-type ContentKey = (Option<String>, Option<String>, Vec<u32>, bool, bool);
+type ContentKey<'a> = (Option<Cow<'a, str>>, Option<&'a str>, Vec<u32>, bool, bool);
 
-fn set_content_id(nodes: &[&Syntax], existing: &mut DftHashMap<ContentKey, u32>) {
+fn set_content_id<'a>(nodes: &[&'a Syntax<'a>], existing: &mut DftHashMap<ContentKey<'a>, u32>) {
     for node in nodes {
         let key: ContentKey = match node {
             List {
@@ -395,8 +413,8 @@ fn set_content_id(nodes: &[&Syntax], existing: &mut DftHashMap<ContentKey, u32>)
                     children.iter().map(|c| c.info().content_id.get()).collect();
 
                 (
-                    Some(open_content.clone()),
-                    Some(close_content.clone()),
+                    Some(Cow::Borrowed(*open_content)),
+                    Some(*close_content),
                     children_content_ids,
                     true,
                     true,
@@ -409,13 +427,15 @@ fn set_content_id(nodes: &[&Syntax], existing: &mut DftHashMap<ContentKey, u32>)
             } => {
                 let is_comment = *highlight == AtomKind::Comment;
                 let clean_content = if is_comment && content.lines().count() > 1 {
-                    content
-                        .lines()
-                        .map(|l| l.trim_start())
-                        .collect::<Vec<_>>()
-                        .join("\n")
+                    Cow::Owned(
+                        content
+                            .lines()
+                            .map(|l| l.trim_start())
+                            .collect::<Vec<_>>()
+                            .join("\n"),
+                    )
                 } else {
-                    content.clone()
+                    Cow::Borrowed(*content)
                 };
                 (Some(clean_content), None, vec![], false, is_comment)
             }
@@ -429,6 +449,21 @@ fn set_content_id(nodes: &[&Syntax], existing: &mut DftHashMap<ContentKey, u32>)
     }
 }
 
+fn set_atom_id<'a>(nodes: &[&'a Syntax<'a>], existing: &mut DftHashMap<&'a str, u32>) {
+    for node in nodes {
+        match node {
+            List { children, .. } => set_atom_id(children, existing),
+            Atom { content, .. } => {
+                // Start from 1, so atoms never have the same ID as
+                // lists.
+                let next_id = existing.len() as u32 + 1;
+                let atom_id = existing.entry(*content).or_insert(next_id);
+                node.info().atom_id.set(*atom_id);
+            }
+        }
+    }
+}
+
 fn set_num_after(nodes: &[&Syntax], parent_num_after: usize) {
     for (i, node) in nodes.iter().enumerate() {
         let num_after = parent_num_after + nodes.len() - 1 - i;
@@ -590,19 +625,33 @@ pub(crate) enum TokenKind {
 
 /// A matched token (an atom, a delimiter, or a comment word).
 #[derive(PartialEq, Eq, Debug, Clone)]
+#[cfg_attr(feature = "serde-types", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde-types",
+    serde(tag = "type", rename_all = "snake_case")
+)]
 pub(crate) enum MatchKind {
     UnchangedToken {
         highlight: TokenKind,
+        #[cfg_attr(feature = "serde-types", serde(with = "crate::serialize::spans"))]
         self_pos: Vec<SingleLineSpan>,
+        #[cfg_attr(feature = "serde-types", serde(with = "crate::serialize::spans"))]
         opposite_pos: Vec<SingleLineSpan>,
     },
     /// A novel token in an AST diff.
-    Novel { highlight: TokenKind },
+    Novel {
+        highlight: TokenKind,
+        /// Whether this token is only punctuation, see
+        /// [`is_punctuation`].
+        punctuation: bool,
+    },
     /// When doing a textual line-based diff, the part of a novel line
     /// that exists on both sides.
     NovelLinePart {
         highlight: TokenKind,
+        #[cfg_attr(feature = "serde-types", serde(with = "crate::serialize::span"))]
         self_pos: SingleLineSpan,
+        #[cfg_attr(feature = "serde-types", serde(with = "crate::serialize::spans"))]
         opposite_pos: Vec<SingleLineSpan>,
     },
     /// When doing a textual line-based diff, the part of a novel line
@@ -611,20 +660,115 @@ pub(crate) enum MatchKind {
     /// A syntactic token that was ignored by the AST diff (e.g. when
     /// ignoring comments for diffing).
     Ignored { highlight: TokenKind },
+    /// A novel token in an AST diff that also occurs elsewhere on the
+    /// other side, so it was moved rather than changed. See
+    /// `--detect-moves`.
+    Moved {
+        highlight: TokenKind,
+        /// The line that this token moved to or from on the other
+        /// side.
+        #[cfg_attr(feature = "serde-types", serde(with = "crate::serialize::line_number"))]
+        opposite_line: LineNumber,
+    },
+    /// A novel token on the RHS in code that was copied from the
+    /// LHS, where this token is the same as in the original. See
+    /// `--detect-copies`.
+    Copied {
+        highlight: TokenKind,
+        /// The first line of the code on the LHS that this was
+        /// copied from.
+        #[cfg_attr(feature = "serde-types", serde(rename = "copied_from"))]
+        #[cfg_attr(feature = "serde-types", serde(with = "crate::serialize::line_number"))]
+        source_line: LineNumber,
+    },
 }
 
 impl MatchKind {
     pub(crate) fn is_novel(&self) -> bool {
         matches!(
             self,
-            MatchKind::Novel { .. } | MatchKind::NovelWord { .. } | MatchKind::NovelLinePart { .. }
+            MatchKind::Novel { .. }
+                | MatchKind::NovelWord { .. }
+                | MatchKind::NovelLinePart { .. }
+                | MatchKind::Moved { .. }
+                | MatchKind::Copied { .. }
         )
     }
+
+    pub(crate) fn is_novel_punctuation(&self) -> bool {
+        matches!(
+            self,
+            MatchKind::Novel {
+                punctuation: true,
+                ..
+            }
+        )
+    }
+}
+
+/// Whether `content` only consists of separators and delimiters,
+/// such as a trailing comma or an extra pair of parentheses. Adding
+/// or removing these rarely changes the meaning of the code.
+///
+/// Operators are deliberately excluded: `!` or `-` are short, but a
+/// novel one is significant.
+pub(crate) fn is_punctuation(content: &str) -> bool {
+    !content.is_empty()
+        && content
+            .chars()
+            .all(|c| matches!(c, ',' | ';' | '(' | ')' | '[' | ']' | '{' | '}'))
+}
+
+/// Treat novel punctuation as ignored, so it doesn't create hunks.
+pub(crate) fn ignore_punctuation(positions: &[MatchedPos]) -> Vec<MatchedPos> {
+    positions
+        .iter()
+        .map(|mp| match mp.kind {
+            MatchKind::Novel {
+                highlight,
+                punctuation: true,
+            } => MatchedPos {
+                kind: MatchKind::Ignored { highlight },
+                pos: mp.pos,
+            },
+            _ => mp.clone(),
+        })
+        .collect()
+}
+
+/// Treat novel positions on lines that are empty or only contain
+/// whitespace as ignored, so that adding or removing blank lines
+/// doesn't create hunks.
+pub(crate) fn ignore_blank_lines(positions: &[MatchedPos], src: &str) -> Vec<MatchedPos> {
+    let lines: Vec<&str> = src.lines().collect();
+    positions
+        .iter()
+        .map(|mp| {
+            let is_blank = lines
+                .get(mp.pos.line.as_usize())
+                .map_or(true, |line| line.trim().is_empty());
+            match mp.kind {
+                MatchKind::Novel { highlight, .. }
+                | MatchKind::NovelWord { highlight }
+                | MatchKind::NovelLinePart { highlight, .. }
+                    if is_blank =>
+                {
+                    MatchedPos {
+                        kind: MatchKind::Ignored { highlight },
+                        pos: mp.pos,
+                    }
+                }
+                _ => mp.clone(),
+            }
+        })
+        .collect()
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde-types", derive(serde::Serialize, serde::Deserialize))]
 pub(crate) struct MatchedPos {
     pub(crate) kind: MatchKind,
+    #[cfg_attr(feature = "serde-types", serde(with = "crate::serialize::span"))]
     pub(crate) pos: SingleLineSpan,
 }
 
@@ -639,13 +783,14 @@ fn split_atom_words(
     opposite_content: &str,
     opposite_pos: &[SingleLineSpan],
     kind: AtomKind,
+    word_regex: Option<&Regex>,
 ) -> Vec<MatchedPos> {
     debug_assert!(kind == AtomKind::Comment || matches!(kind, AtomKind::String(_)));
 
     // TODO: merge adjacent single-line comments unless there are
     // blank lines between them.
-    let content_parts = split_words_and_numbers(content);
-    let other_parts = split_words_and_numbers(opposite_content);
+    let content_parts = split_words_and_numbers(content, word_regex);
+    let other_parts = split_words_and_numbers(opposite_content, word_regex);
 
     let word_diffs = myers_diff::slice_by_hash(&content_parts, &other_parts);
 
@@ -655,6 +800,7 @@ fn split_atom_words(
             .map(|line| MatchedPos {
                 kind: MatchKind::Novel {
                     highlight: TokenKind::Atom(kind),
+                    punctuation: false,
                 },
                 pos: *line,
             })
@@ -770,6 +916,8 @@ impl MatchedPos {
         highlight: TokenKind,
         pos: &[SingleLineSpan],
         is_close_delim: bool,
+        punctuation: bool,
+        word_regex: Option<&Regex>,
     ) -> Vec<Self> {
         // Don't create a MatchedPos for empty positions at the start
         // or end. We still want empty positions in the middle of
@@ -802,7 +950,14 @@ impl MatchedPos {
                     AtomKind::Comment
                 };
 
-                split_atom_words(this_content, &pos, opposite_content, opposite_pos, kind)
+                split_atom_words(
+                    this_content,
+                    &pos,
+                    opposite_content,
+                    opposite_pos,
+                    kind,
+                    word_regex,
+                )
             }
             Unchanged(opposite) => {
                 let opposite_pos = match opposite {
@@ -846,7 +1001,10 @@ impl MatchedPos {
                 mps
             }
             Novel => {
-                let kind = MatchKind::Novel { highlight };
+                let kind = MatchKind::Novel {
+                    highlight,
+                    punctuation,
+                };
                 // Create a MatchedPos for every line that `pos` covers.
                 let mut mps = vec![];
                 for line_pos in &pos {
@@ -870,18 +1028,23 @@ impl MatchedPos {
 }
 
 /// Walk `nodes` and return a vec of all the changed positions.
+///
+/// Words in replaced comments and strings are split with
+/// `word_regex`, if set, see `--word-regex`.
 pub(crate) fn change_positions<'a>(
     nodes: &[&'a Syntax<'a>],
     change_map: &ChangeMap<'a>,
+    word_regex: Option<&Regex>,
 ) -> Vec<MatchedPos> {
     let mut positions = Vec::new();
-    change_positions_(nodes, change_map, &mut positions);
+    change_positions_(nodes, change_map, word_regex, &mut positions);
     positions
 }
 
 fn change_positions_<'a>(
     nodes: &[&'a Syntax<'a>],
     change_map: &ChangeMap<'a>,
+    word_regex: Option<&Regex>,
     positions: &mut Vec<MatchedPos>,
 ) {
     for node in nodes {
@@ -892,8 +1055,10 @@ fn change_positions_<'a>(
         match node {
             List {
                 open_position,
+                open_content,
                 children,
                 close_position,
+                close_content,
                 ..
             } => {
                 positions.extend(MatchedPos::new(
@@ -901,23 +1066,34 @@ fn change_positions_<'a>(
                     TokenKind::Delimiter,
                     open_position,
                     false,
+                    is_punctuation(open_content),
+                    word_regex,
                 ));
 
-                change_positions_(children, change_map, positions);
+                change_positions_(children, change_map, word_regex, positions);
 
                 positions.extend(MatchedPos::new(
                     change,
                     TokenKind::Delimiter,
                     close_position,
                     true,
+                    is_punctuation(close_content),
+                    word_regex,
                 ));
             }
-            Atom { position, kind, .. } => {
+            Atom {
+                position,
+                kind,
+                content,
+                ..
+            } => {
                 positions.extend(MatchedPos::new(
                     change,
                     TokenKind::Atom(*kind),
                     position,
                     false,
+                    *kind == AtomKind::Normal && is_punctuation(content),
+                    word_regex,
                 ));
             }
         }
@@ -1006,7 +1182,7 @@ mod tests {
         match atom {
             List { .. } => unreachable!(),
             Atom { content, .. } => {
-                assert_eq!(content, "foo");
+                assert_eq!(*content, "foo");
             }
         }
     }
@@ -1035,7 +1211,7 @@ mod tests {
             Atom {
                 position, content, ..
             } => {
-                assert_eq!(content, ";; hello");
+                assert_eq!(*content, ";; hello");
                 assert_eq!(
                     *position,
                     vec![SingleLineSpan {
@@ -1122,6 +1298,25 @@ mod tests {
         assert_eq!(x, y);
     }
 
+    #[test]
+    fn test_atom_id_is_exact_content() {
+        let pos = vec![SingleLineSpan {
+            line: 0.into(),
+            start_col: 2,
+            end_col: 3,
+        }];
+
+        let arena = Arena::new();
+
+        let x = Syntax::new_atom(&arena, pos.clone(), "foo\nbar", AtomKind::Comment);
+        let y = Syntax::new_atom(&arena, pos.clone(), "foo\n    bar", AtomKind::Comment);
+        let z = Syntax::new_atom(&arena, pos, "foo\nbar", AtomKind::Comment);
+        init_all_info(&[x, y], &[z]);
+
+        assert_ne!(x.atom_id(), y.atom_id());
+        assert_eq!(x.atom_id(), z.atom_id());
+    }
+
     #[test]
     fn test_split_atom_words() {
         let content = "abc def ghi novel";
@@ -1144,6 +1339,7 @@ mod tests {
             opposite_content,
             &opposite_pos,
             AtomKind::Comment,
+            None,
         );
         assert_eq!(
             res,
@@ -1261,4 +1457,27 @@ mod tests {
             ],
         );
     }
+
+    #[test]
+    fn test_ignore_blank_lines() {
+        let novel_on_line = |line: u32| MatchedPos {
+            kind: MatchKind::NovelWord {
+                highlight: TokenKind::Atom(AtomKind::Normal),
+            },
+            pos: SingleLineSpan {
+                line: line.into(),
+                start_col: 0,
+                end_col: 1,
+            },
+        };
+
+        let positions = ignore_blank_lines(&[novel_on_line(0), novel_on_line(1)], "x\n  \n");
+        assert!(positions[0].kind.is_novel());
+        assert_eq!(
+            positions[1].kind,
+            MatchKind::Ignored {
+                highlight: TokenKind::Atom(AtomKind::Normal)
+            }
+        );
+    }
 }
@@ -1,13 +1,17 @@
 //! Load and configure parsers written with tree-sitter.
 
 use std::collections::HashSet;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
 
 use line_numbers::LinePositions;
+use line_numbers::SingleLineSpan;
 use tree_sitter as ts;
 use typed_arena::Arena;
 
 use super::syntax::MatchedPos;
 use super::syntax::{self, StringKind};
+use crate::api::CancellationToken;
 use crate::hash::DftHashMap;
 use crate::options::DiffOptions;
 use crate::parse::guess_language as guess;
@@ -1206,31 +1210,499 @@ pub(crate) fn from_language(language: guess::Language) -> TreeSitterConfig {
     }
 }
 
-/// Parse `src` with tree-sitter.
-pub(crate) fn to_tree(src: &str, config: &TreeSitterConfig) -> tree_sitter::Tree {
+/// Whether we have a tree-sitter query for syntax highlighting
+/// `language`.
+pub(crate) fn has_highlight_query(language: guess::Language) -> bool {
+    from_language(language).highlight_query.pattern_count() > 0
+}
+
+/// A tree-sitter node kind that declares a named item, such as a
+/// function or a class.
+pub(crate) struct DeclarationKind {
+    /// The tree-sitter node kind, e.g. `function_item`.
+    node_kind: &'static str,
+    /// How to describe this declaration to the user, e.g. `fn`.
+    label: &'static str,
+    /// The field of the node that contains its name.
+    name_field: &'static str,
+}
+
+const fn decl(
+    node_kind: &'static str,
+    label: &'static str,
+    name_field: &'static str,
+) -> DeclarationKind {
+    DeclarationKind {
+        node_kind,
+        label,
+        name_field,
+    }
+}
+
+const C_DECLARATIONS: &[DeclarationKind] = &[
+    decl("function_definition", "fn", "declarator"),
+    decl("struct_specifier", "struct", "name"),
+    decl("union_specifier", "union", "name"),
+    decl("enum_specifier", "enum", "name"),
+];
+
+const CPP_DECLARATIONS: &[DeclarationKind] = &[
+    decl("function_definition", "fn", "declarator"),
+    decl("class_specifier", "class", "name"),
+    decl("struct_specifier", "struct", "name"),
+    decl("union_specifier", "union", "name"),
+    decl("enum_specifier", "enum", "name"),
+    decl("namespace_definition", "namespace", "name"),
+];
+
+const CSHARP_DECLARATIONS: &[DeclarationKind] = &[
+    decl("method_declaration", "method", "name"),
+    decl("constructor_declaration", "constructor", "name"),
+    decl("class_declaration", "class", "name"),
+    decl("struct_declaration", "struct", "name"),
+    decl("interface_declaration", "interface", "name"),
+    decl("enum_declaration", "enum", "name"),
+    decl("namespace_declaration", "namespace", "name"),
+];
+
+const GO_DECLARATIONS: &[DeclarationKind] = &[
+    decl("function_declaration", "func", "name"),
+    decl("method_declaration", "func", "name"),
+    decl("type_spec", "type", "name"),
+];
+
+const JAVA_DECLARATIONS: &[DeclarationKind] = &[
+    decl("method_declaration", "method", "name"),
+    decl("constructor_declaration", "constructor", "name"),
+    decl("class_declaration", "class", "name"),
+    decl("interface_declaration", "interface", "name"),
+    decl("enum_declaration", "enum", "name"),
+    decl("record_declaration", "record", "name"),
+];
+
+const JAVASCRIPT_DECLARATIONS: &[DeclarationKind] = &[
+    decl("function_declaration", "function", "name"),
+    decl("generator_function_declaration", "function", "name"),
+    decl("method_definition", "method", "name"),
+    decl("class_declaration", "class", "name"),
+];
+
+const PHP_DECLARATIONS: &[DeclarationKind] = &[
+    decl("function_definition", "function", "name"),
+    decl("method_declaration", "method", "name"),
+    decl("class_declaration", "class", "name"),
+    decl("interface_declaration", "interface", "name"),
+    decl("trait_declaration", "trait", "name"),
+];
+
+const PYTHON_DECLARATIONS: &[DeclarationKind] = &[
+    decl("function_definition", "def", "name"),
+    decl("class_definition", "class", "name"),
+];
+
+const RUBY_DECLARATIONS: &[DeclarationKind] = &[
+    decl("method", "def", "name"),
+    decl("singleton_method", "def", "name"),
+    decl("class", "class", "name"),
+    decl("module", "module", "name"),
+];
+
+const RUST_DECLARATIONS: &[DeclarationKind] = &[
+    decl("function_item", "fn", "name"),
+    decl("struct_item", "struct", "name"),
+    decl("enum_item", "enum", "name"),
+    decl("union_item", "union", "name"),
+    decl("trait_item", "trait", "name"),
+    decl("impl_item", "impl", "type"),
+    decl("mod_item", "mod", "name"),
+    decl("macro_definition", "macro_rules!", "name"),
+];
+
+const SCALA_DECLARATIONS: &[DeclarationKind] = &[
+    decl("function_definition", "def", "name"),
+    decl("class_definition", "class", "name"),
+    decl("object_definition", "object", "name"),
+    decl("trait_definition", "trait", "name"),
+];
+
+const TYPESCRIPT_DECLARATIONS: &[DeclarationKind] = &[
+    decl("function_declaration", "function", "name"),
+    decl("generator_function_declaration", "function", "name"),
+    decl("method_definition", "method", "name"),
+    decl("class_declaration", "class", "name"),
+    decl("abstract_class_declaration", "class", "name"),
+    decl("interface_declaration", "interface", "name"),
+    decl("type_alias_declaration", "type", "name"),
+    decl("enum_declaration", "enum", "name"),
+];
+
+/// The node kinds that declare named items in `language`. This is
+/// empty for languages where we don't know the declaration kinds.
+pub(crate) fn declaration_kinds(language: guess::Language) -> &'static [DeclarationKind] {
+    use guess::Language::*;
+    match language {
+        C => C_DECLARATIONS,
+        CPlusPlus => CPP_DECLARATIONS,
+        CSharp => CSHARP_DECLARATIONS,
+        Go => GO_DECLARATIONS,
+        Java => JAVA_DECLARATIONS,
+        JavaScript | JavascriptJsx => JAVASCRIPT_DECLARATIONS,
+        Php => PHP_DECLARATIONS,
+        Python => PYTHON_DECLARATIONS,
+        Ruby => RUBY_DECLARATIONS,
+        Rust => RUST_DECLARATIONS,
+        Scala => SCALA_DECLARATIONS,
+        TypeScript | TypeScriptTsx => TYPESCRIPT_DECLARATIONS,
+        _ => &[],
+    }
+}
+
+/// A named declaration found in a file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Declaration {
+    /// The kind of declaration, e.g. `fn`.
+    pub(crate) label: &'static str,
+    pub(crate) name: String,
+    /// The first line of the declaration, zero-indexed.
+    pub(crate) start_line: usize,
+    /// The last line of the declaration, zero-indexed.
+    pub(crate) end_line: usize,
+    /// The byte offset on `start_line` where the declaration starts.
+    pub(crate) start_col: usize,
+    /// The byte offset on `end_line` just after the declaration.
+    pub(crate) end_col: usize,
+}
+
+/// The name of a declaration node, if it has one.
+fn declaration_name(src: &str, node: ts::Node, kind: &DeclarationKind) -> Option<String> {
+    let mut name_node = node.child_by_field_name(kind.name_field)?;
+    // C-like declarators nest, e.g. a function declarator contains
+    // an identifier declarator. Use the innermost one, so we get the
+    // function name without its parameters.
+    while let Some(inner) = name_node.child_by_field_name("declarator") {
+        name_node = inner;
+    }
+
+    let name = src.get(name_node.start_byte()..name_node.end_byte())?;
+    Some(name.split_whitespace().collect::<Vec<_>>().join(" "))
+}
+
+fn collect_declarations(
+    src: &str,
+    cursor: &mut ts::TreeCursor,
+    kinds: &[DeclarationKind],
+    declarations: &mut Vec<Declaration>,
+) {
+    loop {
+        let node = cursor.node();
+        if let Some(kind) = kinds.iter().find(|kind| kind.node_kind == node.kind()) {
+            if let Some(name) = declaration_name(src, node, kind) {
+                declarations.push(Declaration {
+                    label: kind.label,
+                    name,
+                    start_line: node.start_position().row,
+                    end_line: node.end_position().row,
+                    start_col: node.start_position().column,
+                    end_col: node.end_position().column,
+                });
+            }
+        }
+
+        if cursor.goto_first_child() {
+            collect_declarations(src, cursor, kinds, declarations);
+            cursor.goto_parent();
+        }
+
+        if !cursor.goto_next_sibling() {
+            break;
+        }
+    }
+}
+
+/// All the named declarations in `src`, outermost first.
+pub(crate) fn declarations(src: &str, language: guess::Language) -> Vec<Declaration> {
+    if declaration_kinds(language).is_empty() {
+        return vec![];
+    }
+
+    let config = from_language(language);
+    let tree = to_tree(src, &config);
+    tree_declarations(&tree, src, language)
+}
+
+/// All the named declarations in `tree`, which we've already parsed
+/// from `src`, outermost first.
+pub(crate) fn tree_declarations(
+    tree: &ts::Tree,
+    src: &str,
+    language: guess::Language,
+) -> Vec<Declaration> {
+    let kinds = declaration_kinds(language);
+    if kinds.is_empty() {
+        return vec![];
+    }
+
+    let mut declarations = vec![];
+    collect_declarations(src, &mut tree.walk(), kinds, &mut declarations);
+    declarations
+}
+
+/// Kinds of syntax that `--syntax-highlight=full` colors on
+/// unchanged lines.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum HighlightKind {
+    Keyword,
+    Type,
+    Function,
+    Constant,
+}
+
+fn highlight_kind(capture_name: &str) -> Option<HighlightKind> {
+    // As with `tree_highlights`, support namespaced names such as
+    // @function.builtin.
+    let has_name = |name: &str| {
+        capture_name == name
+            || (capture_name.starts_with(name) && capture_name[name.len()..].starts_with('.'))
+    };
+
+    if has_name("keyword")
+        || has_name("repeat")
+        || has_name("conditional")
+        || has_name("exception")
+        || has_name("include")
+    {
+        Some(HighlightKind::Keyword)
+    } else if has_name("type") || capture_name == "constructor" {
+        Some(HighlightKind::Type)
+    } else if has_name("function") || has_name("method") {
+        Some(HighlightKind::Function)
+    } else if has_name("constant") || has_name("number") || has_name("float") || has_name("boolean")
+    {
+        Some(HighlightKind::Constant)
+    } else {
+        None
+    }
+}
+
+/// Run the highlight query for `language` over `src`, and return the
+/// captures that `--syntax-highlight=full` colors. Only captures
+/// within `line_ranges` (zero-indexed, inclusive) are returned, so we
+/// don't run the query over lines that won't be displayed.
+pub(crate) fn highlight_captures(
+    src: &str,
+    language: guess::Language,
+    line_ranges: &[(usize, usize)],
+) -> Vec<(SingleLineSpan, HighlightKind)> {
+    if line_ranges.is_empty() {
+        return vec![];
+    }
+
+    let config = from_language(language);
+    let tree = to_tree(src, &config);
+    let kinds: Vec<Option<HighlightKind>> = config
+        .highlight_query
+        .capture_names()
+        .iter()
+        .map(|name| highlight_kind(name))
+        .collect();
+
+    let mut captures = vec![];
+    let mut qc = ts::QueryCursor::new();
+    for (start_line, end_line) in line_ranges {
+        qc.set_point_range(ts::Point::new(*start_line, 0)..ts::Point::new(end_line + 1, 0));
+        for m in qc.matches(&config.highlight_query, tree.root_node(), src.as_bytes()) {
+            for c in m.captures {
+                let kind = match kinds[c.index as usize] {
+                    Some(kind) => kind,
+                    None => continue,
+                };
+
+                let start = c.node.start_position();
+                let end = c.node.end_position();
+                if start.row != end.row {
+                    // Multiline nodes are strings or comments,
+                    // which we highlight already.
+                    continue;
+                }
+
+                captures.push((
+                    SingleLineSpan {
+                        line: (start.row as u32).into(),
+                        start_col: start.column as u32,
+                        end_col: end.column as u32,
+                    },
+                    kind,
+                ));
+            }
+        }
+    }
+
+    captures
+}
+
+fn new_parser(config: &TreeSitterConfig) -> ts::Parser {
     let mut parser = ts::Parser::new();
     parser
         .set_language(config.language)
         .expect("Incompatible tree-sitter version");
+    parser
+}
+
+/// Parse `src` with tree-sitter.
+pub(crate) fn to_tree(src: &str, config: &TreeSitterConfig) -> tree_sitter::Tree {
+    new_parser(config).parse(src, None).unwrap()
+}
+
+/// Parse `src`, starting from `old_tree` if given, or return `None`
+/// if `cancel` is cancelled first.
+fn to_tree_cancellable(
+    src: &str,
+    old_tree: Option<&ts::Tree>,
+    config: &TreeSitterConfig,
+    cancel: Option<&CancellationToken>,
+) -> Option<ts::Tree> {
+    let mut parser = new_parser(config);
+    if let Some(cancel) = cancel {
+        // SAFETY: The flag outlives the parser, which is dropped at
+        // the end of this function.
+        unsafe { parser.set_cancellation_flag(Some(cancel.flag())) };
+    }
+    parser.parse(src, old_tree)
+}
+
+/// Whether to keep the parse tree of each side for the next diff, as
+/// `--watch` diffs the same two files repeatedly.
+static REUSE_TREES: AtomicBool = AtomicBool::new(false);
+
+/// The source and parse tree of the LHS and RHS from the previous
+/// diff, when `REUSE_TREES` is set.
+static PREVIOUS_TREES: Mutex<[Option<(String, ts::Tree)>; 2]> = Mutex::new([None, None]);
+
+/// Keep parse trees between diffs, so a side that hasn't changed
+/// isn't parsed again and a side that has changed is parsed
+/// incrementally.
+pub(crate) fn reuse_trees_between_diffs() {
+    REUSE_TREES.store(true, Ordering::Relaxed);
+}
+
+/// The position of `offset` in `src`, as tree-sitter expects. Columns
+/// are in bytes.
+fn byte_point(src: &[u8], offset: usize) -> ts::Point {
+    let before = &src[..offset];
+    let row = before.iter().filter(|b| **b == b'\n').count();
+    let column = match before.iter().rposition(|b| *b == b'\n') {
+        Some(newline_offset) => offset - newline_offset - 1,
+        None => offset,
+    };
+    ts::Point::new(row, column)
+}
+
+/// Describe the change from `old_src` to `new_src` as a single edit
+/// of the region between their common prefix and common suffix.
+fn input_edit(old_src: &str, new_src: &str) -> ts::InputEdit {
+    let old_src = old_src.as_bytes();
+    let new_src = new_src.as_bytes();
+
+    let prefix_len = old_src
+        .iter()
+        .zip(new_src)
+        .take_while(|(old, new)| old == new)
+        .count();
+    let max_suffix_len = std::cmp::min(old_src.len(), new_src.len()) - prefix_len;
+    let suffix_len = old_src
+        .iter()
+        .rev()
+        .zip(new_src.iter().rev())
+        .take(max_suffix_len)
+        .take_while(|(old, new)| old == new)
+        .count();
+
+    let old_end_byte = old_src.len() - suffix_len;
+    let new_end_byte = new_src.len() - suffix_len;
+    ts::InputEdit {
+        start_byte: prefix_len,
+        old_end_byte,
+        new_end_byte,
+        start_position: byte_point(old_src, prefix_len),
+        old_end_position: byte_point(old_src, old_end_byte),
+        new_end_position: byte_point(new_src, new_end_byte),
+    }
+}
 
-    parser.parse(src, None).unwrap()
+/// Parse `src`, starting from `old_tree` which was parsed from
+/// `old_src`. Tree-sitter reuses the subtrees outside the edited
+/// region.
+fn to_tree_incremental(
+    old_src: &str,
+    mut old_tree: ts::Tree,
+    src: &str,
+    config: &TreeSitterConfig,
+    cancel: Option<&CancellationToken>,
+) -> Option<ts::Tree> {
+    old_tree.edit(&input_edit(old_src, src));
+    let tree = to_tree_cancellable(src, Some(&old_tree), config, cancel)?;
+
+    debug_assert_eq!(
+        tree.root_node().to_sexp(),
+        to_tree(src, config).root_node().to_sexp(),
+        "Incremental parse should match parsing from scratch"
+    );
+    Some(tree)
+}
+
+/// Parse `src` for the side at `side_index`, using the tree from the
+/// previous diff of this side if we're reusing trees. Returns `None`
+/// if `cancel` is cancelled first.
+fn to_tree_for_side(
+    side_index: usize,
+    src: &str,
+    config: &TreeSitterConfig,
+    cancel: Option<&CancellationToken>,
+) -> Option<ts::Tree> {
+    if !REUSE_TREES.load(Ordering::Relaxed) {
+        return to_tree_cancellable(src, None, config, cancel);
+    }
+
+    let previous = PREVIOUS_TREES.lock().unwrap()[side_index].take();
+    let tree = match previous {
+        Some((old_src, old_tree)) if old_src == src => old_tree,
+        Some((old_src, old_tree)) => to_tree_incremental(&old_src, old_tree, src, config, cancel)?,
+        None => to_tree_cancellable(src, None, config, cancel)?,
+    };
+
+    PREVIOUS_TREES.lock().unwrap()[side_index] = Some((src.to_owned(), tree.clone()));
+    Some(tree)
 }
 
+/// Why [to_tree_with_limit] didn't parse the files.
 #[derive(Debug)]
-pub(crate) struct ExceededByteLimit(pub(crate) usize);
+pub(crate) enum TreeError {
+    /// The larger file was this many bytes.
+    ExceededByteLimit(usize),
+    Cancelled,
+}
 
 pub(crate) fn to_tree_with_limit(
     diff_options: &DiffOptions,
     config: &TreeSitterConfig,
     lhs_src: &str,
     rhs_src: &str,
-) -> Result<(tree_sitter::Tree, tree_sitter::Tree), ExceededByteLimit> {
+) -> Result<(tree_sitter::Tree, tree_sitter::Tree), TreeError> {
     if lhs_src.len() > diff_options.byte_limit || rhs_src.len() > diff_options.byte_limit {
         let num_bytes = std::cmp::max(lhs_src.len(), rhs_src.len());
-        return Err(ExceededByteLimit(num_bytes));
+        return Err(TreeError::ExceededByteLimit(num_bytes));
     }
 
-    Ok((to_tree(lhs_src, config), to_tree(rhs_src, config)))
+    // Parsing is a large part of the time spent on big files, so
+    // parse both sides in parallel when there are threads available.
+    let cancel = diff_options.cancel.as_ref();
+    match rayon::join(
+        || to_tree_for_side(0, lhs_src, config, cancel),
+        || to_tree_for_side(1, rhs_src, config, cancel),
+    ) {
+        (Some(lhs_tree), Some(rhs_tree)) => Ok((lhs_tree, rhs_tree)),
+        _ => Err(TreeError::Cancelled),
+    }
 }
 
 /// Find any nodes that can be parsed as other languages (e.g. JavaScript embedded in HTML),
@@ -1422,29 +1894,82 @@ pub(crate) fn comment_positions(
 #[derive(Debug)]
 pub(crate) struct ExceededParseErrorLimit(pub(crate) usize);
 
+/// The arena for one side, moved to the thread that converts that
+/// side to syntax.
+///
+/// Syntax nodes contain `Cell`s, so neither the nodes nor their arena
+/// can be shared between threads, and `&Arena` isn't `Send`.
+struct SideArena<'a>(&'a Arena<Syntax<'a>>);
+
+// SAFETY: `to_syntax_with_limit` gives each side its own arena, and
+// moves each `SideArena` to exactly one closure passed to
+// `rayon::join`. Only that thread allocates in the arena or touches
+// the nodes in it until `rayon::join` returns, so the arena is never
+// used by two threads at once.
+unsafe impl Send for SideArena<'_> {}
+
+impl<'a> SideArena<'a> {
+    // Closures only capture the fields that they use, so use a
+    // method to ensure that they capture the whole `SideArena`.
+    fn into_inner(self) -> &'a Arena<Syntax<'a>> {
+        self.0
+    }
+}
+
+/// The syntax nodes and parse error count for one side, moved back
+/// from the thread that converted that side.
+struct SideNodes<'a>(Vec<&'a Syntax<'a>>, usize);
+
+// SAFETY: the nodes are in the arena from a `SideArena`. The thread
+// that created them keeps no references to them once its closure
+// returns, so after `rayon::join` they're only used by the calling
+// thread.
+unsafe impl Send for SideNodes<'_> {}
+
+/// Convert both sides to syntax trees, in parallel when there are
+/// threads available. `lhs_arena` and `rhs_arena` must be different
+/// arenas.
 pub(crate) fn to_syntax_with_limit<'a>(
-    lhs_src: &str,
-    rhs_src: &str,
+    lhs_src: &'a str,
+    rhs_src: &'a str,
     lhs_tree: &tree_sitter::Tree,
     rhs_tree: &tree_sitter::Tree,
-    arena: &'a Arena<Syntax<'a>>,
+    lhs_arena: &'a Arena<Syntax<'a>>,
+    rhs_arena: &'a Arena<Syntax<'a>>,
     config: &TreeSitterConfig,
     diff_options: &DiffOptions,
 ) -> Result<(Vec<&'a Syntax<'a>>, Vec<&'a Syntax<'a>>), ExceededParseErrorLimit> {
-    let (lhs_nodes, lhs_error_count) = to_syntax(
-        lhs_tree,
-        lhs_src,
-        arena,
-        config,
-        diff_options.ignore_comments,
-    );
-    let (rhs_nodes, rhs_error_count) = to_syntax(
-        rhs_tree,
-        rhs_src,
-        arena,
-        config,
-        diff_options.ignore_comments,
+    assert!(
+        !std::ptr::eq(lhs_arena, rhs_arena),
+        "Converting both sides in parallel requires separate arenas"
     );
+
+    let ignore_comments = diff_options.ignore_comments;
+    let lhs_arena = SideArena(lhs_arena);
+    let rhs_arena = SideArena(rhs_arena);
+    let (SideNodes(lhs_nodes, lhs_error_count), SideNodes(rhs_nodes, rhs_error_count)) =
+        rayon::join(
+            move || {
+                let (nodes, error_count) = to_syntax(
+                    lhs_tree,
+                    lhs_src,
+                    lhs_arena.into_inner(),
+                    config,
+                    ignore_comments,
+                );
+                SideNodes(nodes, error_count)
+            },
+            move || {
+                let (nodes, error_count) = to_syntax(
+                    rhs_tree,
+                    rhs_src,
+                    rhs_arena.into_inner(),
+                    config,
+                    ignore_comments,
+                );
+                SideNodes(nodes, error_count)
+            },
+        );
     syntax::init_all_info(&lhs_nodes, &rhs_nodes);
 
     let error_count = lhs_error_count + rhs_error_count;
@@ -1457,7 +1982,7 @@ pub(crate) fn to_syntax_with_limit<'a>(
 
 pub(crate) fn to_syntax<'a>(
     tree: &tree_sitter::Tree,
-    src: &str,
+    src: &'a str,
     arena: &'a Arena<Syntax<'a>>,
     config: &TreeSitterConfig,
     ignore_comments: bool,
@@ -1504,7 +2029,7 @@ pub(crate) fn to_syntax<'a>(
 /// Parse `src` with tree-sitter and convert to difftastic Syntax.
 pub(crate) fn parse<'a>(
     arena: &'a Arena<Syntax<'a>>,
-    src: &str,
+    src: &'a str,
     config: &TreeSitterConfig,
     ignore_comments: bool,
 ) -> Vec<&'a Syntax<'a>> {
@@ -1574,7 +2099,7 @@ pub(crate) struct HighlightedNodeIds {
 /// `cursor` should be pointing at the first tree-sitter node in a level.
 fn all_syntaxes_from_cursor<'a>(
     arena: &'a Arena<Syntax<'a>>,
-    src: &str,
+    src: &'a str,
     nl_pos: &LinePositions,
     cursor: &mut ts::TreeCursor,
     error_count: &mut usize,
@@ -1610,7 +2135,7 @@ fn all_syntaxes_from_cursor<'a>(
 /// node.
 fn syntax_from_cursor<'a>(
     arena: &'a Arena<Syntax<'a>>,
-    src: &str,
+    src: &'a str,
     nl_pos: &LinePositions,
     cursor: &mut ts::TreeCursor,
     error_count: &mut usize,
@@ -1669,7 +2194,7 @@ fn syntax_from_cursor<'a>(
 /// node.
 fn list_from_cursor<'a>(
     arena: &'a Arena<Syntax<'a>>,
-    src: &str,
+    src: &'a str,
     nl_pos: &LinePositions,
     cursor: &mut ts::TreeCursor,
     error_count: &mut usize,
@@ -1808,7 +2333,7 @@ fn list_from_cursor<'a>(
 /// Convert the tree-sitter node at `cursor` to a difftastic atom.
 fn atom_from_cursor<'a>(
     arena: &'a Arena<Syntax<'a>>,
-    src: &str,
+    src: &'a str,
     nl_pos: &LinePositions,
     cursor: &mut ts::TreeCursor,
     highlights: &HighlightedNodeIds,
@@ -1882,6 +2407,33 @@ mod tests {
         parse(&arena, ".foo {}", &css_config, false);
     }
 
+    #[test]
+    fn test_to_syntax_with_limit() {
+        let config = from_language(guess::Language::EmacsLisp);
+        let lhs_src = "(a b)";
+        let rhs_src = "(a b) c";
+        let lhs_tree = to_tree(lhs_src, &config);
+        let rhs_tree = to_tree(rhs_src, &config);
+
+        let lhs_arena = Arena::new();
+        let rhs_arena = Arena::new();
+        let (lhs, rhs) = to_syntax_with_limit(
+            lhs_src,
+            rhs_src,
+            &lhs_tree,
+            &rhs_tree,
+            &lhs_arena,
+            &rhs_arena,
+            &config,
+            &DiffOptions::default(),
+        )
+        .unwrap();
+
+        assert_eq!(lhs.len(), 1);
+        assert_eq!(rhs.len(), 2);
+        assert_eq!(lhs[0], rhs[0]);
+    }
+
     #[test]
     fn test_parse_empty_file() {
         let arena = Arena::new();
@@ -1915,6 +2467,53 @@ mod tests {
         };
     }
 
+    #[test]
+    fn test_declarations_rust() {
+        let src = "struct Foo {}\n\nimpl Foo {\n    fn bar(&self) {}\n}\n";
+        let declarations = declarations(src, guess::Language::Rust);
+
+        let names: Vec<_> = declarations
+            .iter()
+            .map(|d| format!("{} {}", d.label, d.name))
+            .collect();
+        assert_eq!(names, vec!["struct Foo", "impl Foo", "fn bar"]);
+        assert_eq!(
+            (declarations[2].start_line, declarations[2].end_line),
+            (3, 3)
+        );
+    }
+
+    #[test]
+    fn test_declarations_c_function_name() {
+        let src = "static int *foo(int x) {\n  return 0;\n}\n";
+        let declarations = declarations(src, guess::Language::C);
+
+        assert_eq!(declarations.len(), 1);
+        assert_eq!(declarations[0].name, "foo");
+    }
+
+    #[test]
+    fn test_highlight_kind() {
+        assert_eq!(highlight_kind("keyword"), Some(HighlightKind::Keyword));
+        assert_eq!(
+            highlight_kind("function.builtin"),
+            Some(HighlightKind::Function)
+        );
+        assert_eq!(highlight_kind("typeface"), None);
+        assert_eq!(highlight_kind("variable"), None);
+    }
+
+    #[test]
+    fn test_highlight_captures_line_ranges() {
+        let src = "fn foo() {}\nfn bar() {}\n";
+        let captures = highlight_captures(src, guess::Language::Rust, &[(1, 1)]);
+
+        assert!(captures
+            .iter()
+            .any(|(span, kind)| *kind == HighlightKind::Function && span.start_col == 3));
+        assert!(captures.iter().all(|(span, _)| span.line.as_usize() == 1));
+    }
+
     /// Ensure that we don't crash when loading any of the
     /// configs. This can happen on bad highlighting/foo.scm files.
     #[test]
@@ -1923,4 +2522,37 @@ mod tests {
             from_language(language);
         }
     }
+
+    #[test]
+    fn test_input_edit() {
+        let edit = input_edit("foo\nbar\nbaz\n", "foo\nbarx\nbaz\n");
+        assert_eq!(edit.start_byte, 7);
+        assert_eq!(edit.old_end_byte, 7);
+        assert_eq!(edit.new_end_byte, 8);
+        assert_eq!(edit.start_position, ts::Point::new(1, 3));
+        assert_eq!(edit.new_end_position, ts::Point::new(1, 4));
+    }
+
+    #[test]
+    fn test_input_edit_repeated_text() {
+        // The common prefix and suffix must not overlap.
+        let edit = input_edit("aa", "aaa");
+        assert_eq!(edit.start_byte, 2);
+        assert_eq!(edit.old_end_byte, 2);
+        assert_eq!(edit.new_end_byte, 3);
+    }
+
+    #[test]
+    fn test_to_tree_incremental() {
+        let config = from_language(guess::Language::Rust);
+        let old_src = "fn foo() {}\nfn bar() {}\n";
+        let src = "fn foo() {}\nfn bar(x: u8) {}\n";
+
+        let tree =
+            to_tree_incremental(old_src, to_tree(old_src, &config), src, &config, None).unwrap();
+        assert_eq!(
+            tree.root_node().to_sexp(),
+            to_tree(src, &config).root_node().to_sexp()
+        );
+    }
 }
@@ -0,0 +1,362 @@
+//! Time taken by each phase of diffing a file, and counters for the
+//! work done, reported with `--profile`.
+//!
+//! Each file is diffed on a single thread, so the profile for the
+//! file currently being diffed is thread-local. The counters are
+//! only updated at phase boundaries, so this is cheap enough to
+//! leave compiled in.
+
+use std::{
+    cell::RefCell,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use serde::{ser::SerializeStruct, Serialize, Serializer};
+
+use line_numbers::LineNumber;
+
+use crate::{
+    parse::syntax::Syntax,
+    summary::{DiffResult, FileContent},
+};
+
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum Phase {
+    /// Parsing both sides with tree-sitter and converting them to
+    /// syntax trees.
+    Parse,
+    /// Finding unchanged regions and anchors before the graph search.
+    Unchanged,
+    /// The graph search, including any fallback to diffing subtrees.
+    GraphSearch,
+    /// Printing the diff.
+    Display,
+}
+
+const PHASES: [Phase; 4] = [
+    Phase::Parse,
+    Phase::Unchanged,
+    Phase::GraphSearch,
+    Phase::Display,
+];
+
+impl Phase {
+    fn name(self) -> &'static str {
+        match self {
+            Phase::Parse => "parse",
+            Phase::Unchanged => "unchanged",
+            Phase::GraphSearch => "graph",
+            Phase::Display => "display",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub(crate) struct FileProfile {
+    display_path: String,
+    phase_times: [Duration; PHASES.len()],
+    nodes_parsed: usize,
+    vertices_explored: usize,
+    route_length: usize,
+    /// The number of bytes of source in the hunks that we printed.
+    bytes_rendered: usize,
+}
+
+impl Serialize for FileProfile {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut profile = serializer.serialize_struct("FileProfile", 8)?;
+        profile.serialize_field("parse_ms", &millis(self.phase_times[Phase::Parse as usize]))?;
+        profile.serialize_field(
+            "unchanged_ms",
+            &millis(self.phase_times[Phase::Unchanged as usize]),
+        )?;
+        profile.serialize_field(
+            "graph_ms",
+            &millis(self.phase_times[Phase::GraphSearch as usize]),
+        )?;
+        profile.serialize_field(
+            "display_ms",
+            &millis(self.phase_times[Phase::Display as usize]),
+        )?;
+        profile.serialize_field("nodes_parsed", &self.nodes_parsed)?;
+        profile.serialize_field("vertices_explored", &self.vertices_explored)?;
+        profile.serialize_field("route_length", &self.route_length)?;
+        profile.serialize_field("bytes_rendered", &self.bytes_rendered)?;
+        profile.end()
+    }
+}
+
+impl FileProfile {
+    /// The graph statistics, for `--verbose --verbose`.
+    pub(crate) fn format_stats(&self) -> String {
+        format!(
+            "{} nodes parsed, {} vertices explored, route length {}",
+            self.nodes_parsed, self.vertices_explored, self.route_length
+        )
+    }
+}
+
+fn millis(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+thread_local! {
+    /// The profile of the file being diffed on this thread, if
+    /// `--profile` is enabled.
+    static CURRENT: RefCell<Option<FileProfile>> = RefCell::new(None);
+}
+
+/// The profiles of every file that has been diffed.
+static FINISHED: Mutex<Vec<FileProfile>> = Mutex::new(Vec::new());
+
+/// Records the phases of diffing a file on the current thread, until
+/// dropped.
+pub(crate) struct Profiler {
+    /// The profile of the file that was being diffed on this thread
+    /// when we started. A rayon thread waiting for parsing to finish
+    /// can start diffing another file.
+    previous: Option<FileProfile>,
+}
+
+impl Profiler {
+    pub(crate) fn start(display_path: &str) -> Self {
+        let profile = FileProfile {
+            display_path: display_path.to_owned(),
+            ..FileProfile::default()
+        };
+        Self {
+            previous: CURRENT.with(|current| current.replace(Some(profile))),
+        }
+    }
+}
+
+impl Drop for Profiler {
+    fn drop(&mut self) {
+        let previous = self.previous.take();
+        if let Some(profile) = CURRENT.with(|current| current.replace(previous)) {
+            FINISHED.lock().unwrap().push(profile);
+        }
+    }
+}
+
+fn with_current(f: impl FnOnce(&mut FileProfile)) {
+    CURRENT.with(|current| {
+        if let Some(profile) = current.borrow_mut().as_mut() {
+            f(profile);
+        }
+    });
+}
+
+/// Add the time since `start` to `phase` for the file being diffed.
+pub(crate) fn record_phase(phase: Phase, start: Instant) {
+    let elapsed = start.elapsed();
+    with_current(|profile| profile.phase_times[phase as usize] += elapsed);
+}
+
+fn count_nodes(nodes: &[&Syntax]) -> usize {
+    nodes
+        .iter()
+        .map(|node| match node {
+            Syntax::List {
+                num_descendants, ..
+            } => *num_descendants as usize + 1,
+            Syntax::Atom { .. } => 1,
+        })
+        .sum()
+}
+
+pub(crate) fn record_nodes_parsed(lhs: &[&Syntax], rhs: &[&Syntax]) {
+    with_current(|profile| profile.nodes_parsed += count_nodes(lhs) + count_nodes(rhs));
+}
+
+/// Record the vertices visited by a graph search, including searches
+/// that exceeded the graph limit.
+pub(crate) fn record_vertices_explored(num_visited: usize) {
+    with_current(|profile| profile.vertices_explored += num_visited);
+}
+
+pub(crate) fn record_route_length(num_vertices: usize) {
+    with_current(|profile| profile.route_length += num_vertices);
+}
+
+/// The number of bytes of source on the lines of the hunks in
+/// `diff`, on both sides.
+fn hunk_bytes(diff: &DiffResult) -> usize {
+    let (lhs_src, rhs_src) = match (&diff.lhs_src, &diff.rhs_src) {
+        (FileContent::Text(lhs_src), FileContent::Text(rhs_src)) => (lhs_src, rhs_src),
+        _ => return 0,
+    };
+    let lhs_lines: Vec<&str> = lhs_src.lines().collect();
+    let rhs_lines: Vec<&str> = rhs_src.lines().collect();
+
+    let line_len = |lines: &[&str], line_num: Option<LineNumber>| {
+        line_num
+            .and_then(|line_num| lines.get(line_num.as_usize()))
+            .map_or(0, |line| line.len())
+    };
+
+    diff.hunks
+        .iter()
+        .flat_map(|hunk| &hunk.lines)
+        .map(|(lhs_line_num, rhs_line_num)| {
+            line_len(&lhs_lines, *lhs_line_num) + line_len(&rhs_lines, *rhs_line_num)
+        })
+        .sum()
+}
+
+/// Records the time taken to print a file, until dropped. Files are
+/// printed after they've been diffed, possibly on another thread.
+pub(crate) struct DisplayTimer<'a> {
+    diff: &'a DiffResult,
+    start: Instant,
+}
+
+impl<'a> DisplayTimer<'a> {
+    pub(crate) fn start(diff: &'a DiffResult) -> Self {
+        Self {
+            diff,
+            start: Instant::now(),
+        }
+    }
+}
+
+impl<'a> Drop for DisplayTimer<'a> {
+    fn drop(&mut self) {
+        let elapsed = self.start.elapsed();
+        let mut finished = FINISHED.lock().unwrap();
+        if let Some(profile) = finished
+            .iter_mut()
+            .rev()
+            .find(|profile| profile.display_path == self.diff.display_path)
+        {
+            profile.phase_times[Phase::Display as usize] += elapsed;
+            profile.bytes_rendered += hunk_bytes(self.diff);
+        }
+    }
+}
+
+/// The profile for `display_path`, if it has been diffed with
+/// `--profile`.
+pub(crate) fn file_profile(display_path: &str) -> Option<FileProfile> {
+    FINISHED
+        .lock()
+        .unwrap()
+        .iter()
+        .rev()
+        .find(|profile| profile.display_path == display_path)
+        .cloned()
+}
+
+/// Remove and return the profile for `display_path`, when it was
+/// only recorded for `--verbose --verbose` and shouldn't be reported
+/// with `--profile`.
+pub(crate) fn take_file_profile(display_path: &str) -> Option<FileProfile> {
+    let mut finished = FINISHED.lock().unwrap();
+    let i = finished
+        .iter()
+        .rposition(|profile| profile.display_path == display_path)?;
+    Some(finished.remove(i))
+}
+
+fn format_table(profiles: &[FileProfile]) -> String {
+    let path_width = profiles
+        .iter()
+        .map(|profile| profile.display_path.len())
+        .chain(std::iter::once("path".len()))
+        .max()
+        .unwrap_or_default();
+
+    let mut res = format!("{:path_width$}", "path", path_width = path_width);
+    for phase in PHASES {
+        res.push_str(&format!(" {:>10}", phase.name()));
+    }
+    res.push_str(&format!(
+        " {:>8} {:>10} {:>6} {:>8}\n",
+        "nodes", "vertices", "route", "bytes"
+    ));
+
+    for profile in profiles {
+        res.push_str(&format!(
+            "{:path_width$}",
+            profile.display_path,
+            path_width = path_width
+        ));
+        for time in profile.phase_times {
+            res.push_str(&format!(" {:>8.1}ms", millis(time)));
+        }
+        res.push_str(&format!(
+            " {:>8} {:>10} {:>6} {:>8}\n",
+            profile.nodes_parsed,
+            profile.vertices_explored,
+            profile.route_length,
+            profile.bytes_rendered
+        ));
+    }
+    res
+}
+
+/// Print a table of the profile of every file to stderr.
+pub(crate) fn print_table() {
+    eprint!("{}", format_table(&FINISHED.lock().unwrap()));
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_format_table() {
+        let profile = FileProfile {
+            display_path: "foo.rs".to_owned(),
+            phase_times: [
+                Duration::from_micros(1500),
+                Duration::from_micros(200),
+                Duration::from_millis(12),
+                Duration::from_millis(0),
+            ],
+            nodes_parsed: 120,
+            vertices_explored: 3456,
+            route_length: 98,
+            bytes_rendered: 512,
+        };
+
+        assert_eq!(
+            format_table(&[profile]),
+            "path        parse  unchanged      graph    display    nodes   vertices  route    bytes\n\
+             foo.rs      1.5ms      0.2ms     12.0ms      0.0ms      120       3456     98      512\n"
+        );
+    }
+
+    #[test]
+    fn test_nested_profilers() {
+        {
+            let _outer = Profiler::start("outer.rs");
+            record_vertices_explored(1);
+            {
+                let _inner = Profiler::start("inner.rs");
+                record_vertices_explored(10);
+            }
+            record_vertices_explored(2);
+        }
+
+        assert_eq!(file_profile("outer.rs").unwrap().vertices_explored, 3);
+        assert_eq!(file_profile("inner.rs").unwrap().vertices_explored, 10);
+    }
+
+    #[test]
+    fn test_take_file_profile() {
+        {
+            let _profiler = Profiler::start("taken.rs");
+            record_route_length(5);
+        }
+
+        assert_eq!(take_file_profile("taken.rs").unwrap().route_length, 5);
+        assert!(file_profile("taken.rs").is_none());
+    }
+}
@@ -0,0 +1,411 @@
+//! Diffing prose so that re-wrapping a paragraph isn't a change, see
+//! `--prose-granularity`.
+//!
+//! We split text into paragraphs at blank lines, or into sentences,
+//! and compare them by their words, regardless of where the lines
+//! break. Changed paragraphs or sentences are then diffed word by
+//! word, and each word is mapped back to its line on each side for
+//! display.
+
+use std::hash::{Hash, Hasher};
+
+use line_numbers::LinePositions;
+use regex::Regex;
+
+use crate::{
+    diff::myers_diff,
+    parse::syntax::{AtomKind, MatchKind, MatchedPos, TokenKind},
+    words::{is_sentence_end, split_words},
+};
+
+/// How finely text diffs compare prose, see `--prose-granularity`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ProseGranularity {
+    /// Compare lines, like any other text. This is handled by
+    /// [crate::line_parser].
+    Line,
+    /// Compare paragraphs by their words, and highlight each changed
+    /// word.
+    Word,
+    /// Compare sentences by their words, and highlight each changed
+    /// sentence, with the changed words in it emphasised.
+    Sentence,
+}
+
+/// The most words we diff at once. Larger regions are diffed one
+/// paragraph at a time, and larger paragraphs are shown as entirely
+/// novel.
+const MAX_WORDS: usize = 1000;
+
+/// A word, compared by its text, and its byte offset in the file.
+#[derive(Debug, Clone, Copy)]
+struct Word<'a> {
+    text: &'a str,
+    offset: usize,
+}
+
+impl<'a> PartialEq for Word<'a> {
+    fn eq(&self, other: &Self) -> bool {
+        self.text == other.text
+    }
+}
+
+impl<'a> Eq for Word<'a> {}
+
+impl<'a> Hash for Word<'a> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.text.hash(state);
+    }
+}
+
+/// A paragraph or a sentence, compared by its words alone.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct Chunk<'a> {
+    words: Vec<Word<'a>>,
+}
+
+/// Split `src` into runs of non-blank lines.
+fn split_paragraphs<'a>(src: &'a str, word_regex: Option<&Regex>) -> Vec<Chunk<'a>> {
+    let mut paragraphs = vec![];
+    let mut words = vec![];
+
+    let mut offset = 0;
+    for line in src.split_inclusive('\n') {
+        if line.trim().is_empty() {
+            if !words.is_empty() {
+                paragraphs.push(Chunk {
+                    words: std::mem::take(&mut words),
+                });
+            }
+        } else {
+            let mut word_offset = offset;
+            for text in split_words(line, word_regex) {
+                if !text.trim().is_empty() {
+                    words.push(Word {
+                        text,
+                        offset: word_offset,
+                    });
+                }
+                word_offset += text.len();
+            }
+        }
+        offset += line.len();
+    }
+
+    if !words.is_empty() {
+        paragraphs.push(Chunk { words });
+    }
+    paragraphs
+}
+
+/// Split each paragraph of `src` into sentences, see
+/// [is_sentence_end].
+fn split_sentences<'a>(src: &'a str, word_regex: Option<&Regex>) -> Vec<Chunk<'a>> {
+    let mut sentences = vec![];
+    for paragraph in split_paragraphs(src, word_regex) {
+        let texts: Vec<&str> = paragraph.words.iter().map(|word| word.text).collect();
+        let mut start = 0;
+        for i in 0..texts.len() {
+            if is_sentence_end(&texts, i) {
+                sentences.push(Chunk {
+                    words: paragraph.words[start..=i].to_vec(),
+                });
+                start = i + 1;
+            }
+        }
+    }
+    sentences
+}
+
+/// Whether `lhs_src` and `rhs_src` have different words in any
+/// paragraph, for `--check-only`.
+pub(crate) fn has_changes(lhs_src: &str, rhs_src: &str, word_regex: Option<&Regex>) -> bool {
+    lhs_src != rhs_src
+        && split_paragraphs(lhs_src, word_regex) != split_paragraphs(rhs_src, word_regex)
+}
+
+fn unchanged_pos(
+    word: &Word,
+    opposite_word: &Word,
+    lp: &LinePositions,
+    opposite_lp: &LinePositions,
+) -> MatchedPos {
+    let self_pos = lp.from_region(word.offset, word.offset + word.text.len());
+    let opposite_pos = opposite_lp.from_region(
+        opposite_word.offset,
+        opposite_word.offset + opposite_word.text.len(),
+    );
+    MatchedPos {
+        kind: MatchKind::UnchangedToken {
+            highlight: TokenKind::Atom(AtomKind::Normal),
+            self_pos: self_pos.clone(),
+            opposite_pos,
+        },
+        pos: self_pos[0],
+    }
+}
+
+/// A word that's on both sides, but in a sentence that changed.
+fn novel_part_pos(
+    word: &Word,
+    opposite_word: &Word,
+    lp: &LinePositions,
+    opposite_lp: &LinePositions,
+) -> MatchedPos {
+    let self_pos = lp.from_region(word.offset, word.offset + word.text.len())[0];
+    let opposite_pos = opposite_lp.from_region(
+        opposite_word.offset,
+        opposite_word.offset + opposite_word.text.len(),
+    );
+    MatchedPos {
+        kind: MatchKind::NovelLinePart {
+            highlight: TokenKind::Atom(AtomKind::Normal),
+            self_pos,
+            opposite_pos,
+        },
+        pos: self_pos,
+    }
+}
+
+fn novel_pos(word: &Word, lp: &LinePositions) -> MatchedPos {
+    MatchedPos {
+        kind: MatchKind::NovelWord {
+            highlight: TokenKind::Atom(AtomKind::Normal),
+        },
+        pos: lp.from_region(word.offset, word.offset + word.text.len())[0],
+    }
+}
+
+/// Diff `words` against `opposite_words`, and add the positions of
+/// `words` to `mps`. Words on both sides are still highlighted with
+/// [ProseGranularity::Sentence], as they're part of a changed
+/// sentence.
+///
+/// Returns false, without adding anything, if either side has too
+/// many words to diff.
+fn push_word_positions(
+    words: &[Word],
+    opposite_words: &[Word],
+    lp: &LinePositions,
+    opposite_lp: &LinePositions,
+    granularity: ProseGranularity,
+    mps: &mut Vec<MatchedPos>,
+) -> bool {
+    if words.len() > MAX_WORDS || opposite_words.len() > MAX_WORDS {
+        return false;
+    }
+
+    for diff_res in myers_diff::slice_unique_by_hash(words, opposite_words) {
+        match diff_res {
+            myers_diff::DiffResult::Left(word) => mps.push(novel_pos(word, lp)),
+            myers_diff::DiffResult::Both(word, opposite_word) => {
+                mps.push(if granularity == ProseGranularity::Sentence {
+                    novel_part_pos(word, opposite_word, lp, opposite_lp)
+                } else {
+                    unchanged_pos(word, opposite_word, lp, opposite_lp)
+                })
+            }
+            myers_diff::DiffResult::Right(_) => {}
+        }
+    }
+    true
+}
+
+/// Diff a run of changed chunks as a whole, so words can move
+/// between them. If that's too many words, diff each pair of chunks
+/// separately instead.
+fn push_changed_chunks(
+    chunks: &[&Chunk],
+    opposite_chunks: &[&Chunk],
+    lp: &LinePositions,
+    opposite_lp: &LinePositions,
+    granularity: ProseGranularity,
+    mps: &mut Vec<MatchedPos>,
+) {
+    let words: Vec<Word> = chunks
+        .iter()
+        .flat_map(|chunk| chunk.words.iter().copied())
+        .collect();
+    let opposite_words: Vec<Word> = opposite_chunks
+        .iter()
+        .flat_map(|chunk| chunk.words.iter().copied())
+        .collect();
+    if push_word_positions(&words, &opposite_words, lp, opposite_lp, granularity, mps) {
+        return;
+    }
+
+    for (i, chunk) in chunks.iter().enumerate() {
+        let opposite_words = opposite_chunks
+            .get(i)
+            .map_or(&[][..], |chunk| &chunk.words[..]);
+        if !push_word_positions(
+            &chunk.words,
+            opposite_words,
+            lp,
+            opposite_lp,
+            granularity,
+            mps,
+        ) {
+            mps.extend(chunk.words.iter().map(|word| novel_pos(word, lp)));
+        }
+    }
+}
+
+/// The positions of the words in `src`, diffed against
+/// `opposite_src` by paragraph or by sentence, without regard to how
+/// the paragraphs are wrapped.
+pub(crate) fn change_positions(
+    src: &str,
+    opposite_src: &str,
+    granularity: ProseGranularity,
+    word_regex: Option<&Regex>,
+) -> Vec<MatchedPos> {
+    let lp = LinePositions::from(src);
+    let opposite_lp = LinePositions::from(opposite_src);
+    let (chunks, opposite_chunks) = if granularity == ProseGranularity::Sentence {
+        (
+            split_sentences(src, word_regex),
+            split_sentences(opposite_src, word_regex),
+        )
+    } else {
+        (
+            split_paragraphs(src, word_regex),
+            split_paragraphs(opposite_src, word_regex),
+        )
+    };
+
+    let mut mps = vec![];
+    let mut novel = vec![];
+    let mut opposite_novel = vec![];
+    for diff_res in myers_diff::slice_unique_by_hash(&chunks, &opposite_chunks) {
+        match diff_res {
+            myers_diff::DiffResult::Left(chunk) => novel.push(chunk),
+            myers_diff::DiffResult::Right(opposite_chunk) => opposite_novel.push(opposite_chunk),
+            myers_diff::DiffResult::Both(chunk, opposite_chunk) => {
+                push_changed_chunks(
+                    &novel,
+                    &opposite_novel,
+                    &lp,
+                    &opposite_lp,
+                    granularity,
+                    &mut mps,
+                );
+                novel.clear();
+                opposite_novel.clear();
+
+                for (word, opposite_word) in chunk.words.iter().zip(&opposite_chunk.words) {
+                    mps.push(unchanged_pos(word, opposite_word, &lp, &opposite_lp));
+                }
+            }
+        }
+    }
+    push_changed_chunks(
+        &novel,
+        &opposite_novel,
+        &lp,
+        &opposite_lp,
+        granularity,
+        &mut mps,
+    );
+
+    mps
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    /// The text of the positions in `src` whose kind matches
+    /// `predicate`.
+    fn words_where(
+        src: &str,
+        opposite_src: &str,
+        granularity: ProseGranularity,
+        predicate: fn(&MatchKind) -> bool,
+    ) -> Vec<String> {
+        let lines: Vec<&str> = src.lines().collect();
+        change_positions(src, opposite_src, granularity, None)
+            .into_iter()
+            .filter(|mp| predicate(&mp.kind))
+            .map(|mp| {
+                lines[mp.pos.line.0 as usize][mp.pos.start_col as usize..mp.pos.end_col as usize]
+                    .to_owned()
+            })
+            .collect()
+    }
+
+    fn novel_words(src: &str, opposite_src: &str, granularity: ProseGranularity) -> Vec<String> {
+        words_where(src, opposite_src, granularity, MatchKind::is_novel)
+    }
+
+    #[test]
+    fn test_rewrapped_paragraph_is_unchanged() {
+        let lhs = "The quick brown fox\njumps over the lazy dog.\n\nThe end.\n";
+        let rhs = "The quick\nbrown fox jumps over\nthe lazy dog.\n\nThe end.\n";
+
+        for granularity in [ProseGranularity::Word, ProseGranularity::Sentence] {
+            assert!(novel_words(lhs, rhs, granularity).is_empty());
+            assert!(novel_words(rhs, lhs, granularity).is_empty());
+        }
+        assert!(!has_changes(lhs, rhs, None));
+    }
+
+    #[test]
+    fn test_rewrapped_paragraph_with_one_change() {
+        let lhs = "The quick brown fox\njumps over the lazy dog.\n";
+        let rhs = "The quick\nred fox jumps over\nthe lazy dog.\n";
+
+        assert_eq!(novel_words(lhs, rhs, ProseGranularity::Word), vec!["brown"]);
+        assert_eq!(novel_words(rhs, lhs, ProseGranularity::Word), vec!["red"]);
+        assert!(has_changes(lhs, rhs, None));
+    }
+
+    #[test]
+    fn test_new_paragraph() {
+        let lhs = "First.\n";
+        let rhs = "First.\n\nSecond\nparagraph.\n";
+
+        assert!(novel_words(lhs, rhs, ProseGranularity::Word).is_empty());
+        assert_eq!(
+            novel_words(rhs, lhs, ProseGranularity::Word),
+            vec!["Second", "paragraph", "."]
+        );
+    }
+
+    #[test]
+    fn test_markdown_sentence_change() {
+        let lhs =
+            "# Install\n\nRun the installer. It copies the files\ninto place. Then restart.\n";
+        let rhs =
+            "# Install\n\nRun the installer. It copies all the\nfiles into place. Then restart.\n";
+
+        // The whole changed sentence is highlighted, and the word
+        // that changed is emphasised.
+        assert_eq!(
+            novel_words(rhs, lhs, ProseGranularity::Sentence),
+            vec!["It", "copies", "all", "the", "files", "into", "place", "."]
+        );
+        assert_eq!(
+            words_where(rhs, lhs, ProseGranularity::Sentence, |kind| matches!(
+                kind,
+                MatchKind::NovelWord { .. }
+            )),
+            vec!["all"]
+        );
+        assert_eq!(novel_words(rhs, lhs, ProseGranularity::Word), vec!["all"]);
+    }
+
+    #[test]
+    fn test_markdown_sentence_moved_between_lines() {
+        let lhs = "Difftastic is a diff tool. It understands syntax.\n";
+        let rhs = "Difftastic is a diff tool.\nIt understands syntax!\n";
+
+        assert_eq!(
+            novel_words(lhs, rhs, ProseGranularity::Sentence),
+            vec!["It", "understands", "syntax", "."]
+        );
+        assert_eq!(novel_words(lhs, rhs, ProseGranularity::Word), vec!["."]);
+    }
+}
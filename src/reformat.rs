@@ -0,0 +1,240 @@
+//! Pretty-printing minified JSON and YAML before diffing, see
+//! `--reformat-json`.
+//!
+//! A minified file is one enormous line, so every change is in the
+//! same hunk. We put each element of an object or array on its own
+//! line, indented by its depth, and diff that instead. Strings and
+//! the order of keys are kept exactly as they are.
+
+use crate::{display::long_lines::LONG_LINE_THRESHOLD, parse::guess_language::Language};
+
+const INDENT: &str = "  ";
+
+/// When to reformat JSON and YAML, see `--reformat-json`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ReformatJson {
+    /// Only if either side has a line longer than
+    /// [LONG_LINE_THRESHOLD].
+    Auto,
+    Always,
+    Never,
+}
+
+/// Reformat both sides of a JSON or YAML file, if `reformat_json`
+/// applies. Returns `None` if the language can't be reformatted, or
+/// if either side isn't valid, so the files are diffed as they are.
+pub(crate) fn reformat_both(
+    lhs_src: &str,
+    rhs_src: &str,
+    language: Option<Language>,
+    reformat_json: ReformatJson,
+) -> Option<(String, String)> {
+    let is_json = match language {
+        Some(Language::Json) => true,
+        Some(Language::Yaml) => false,
+        _ => return None,
+    };
+    let has_long_line = |src: &str| src.lines().any(|line| line.len() > LONG_LINE_THRESHOLD);
+    match reformat_json {
+        ReformatJson::Never => return None,
+        ReformatJson::Auto if !has_long_line(lhs_src) && !has_long_line(rhs_src) => return None,
+        _ => {}
+    }
+    if lhs_src == rhs_src {
+        return None;
+    }
+
+    let reformat_side = |src: &str| {
+        if src.trim().is_empty() {
+            // An added or removed file.
+            Some(src.to_owned())
+        } else if is_json {
+            reformat_json_src(src)
+        } else {
+            reformat_yaml_flow(src)
+        }
+    };
+    Some((reformat_side(lhs_src)?, reformat_side(rhs_src)?))
+}
+
+fn reformat_json_src(src: &str) -> Option<String> {
+    serde_json::from_str::<serde::de::IgnoredAny>(src).ok()?;
+    reindent(src, true)
+}
+
+/// Reformat a YAML document that's a single line in flow style, such
+/// as `{a: 1, b: [x, y]}`. Flow collections may span lines, so the
+/// result is still the same YAML.
+fn reformat_yaml_flow(src: &str) -> Option<String> {
+    let doc = src.trim();
+    if doc.contains('\n') || !(doc.starts_with('{') || doc.starts_with('[')) {
+        return None;
+    }
+    reindent(doc, false)
+}
+
+/// Put each element of each collection in `src` on its own line. An
+/// empty collection stays on one line. Returns `None` if the
+/// brackets or quotes aren't balanced, or if there's a YAML comment.
+fn reindent(src: &str, is_json: bool) -> Option<String> {
+    let mut res = String::with_capacity(src.len() * 2);
+    let mut depth: usize = 0;
+    let mut pending_space = false;
+    let mut chars = src.trim().chars().peekable();
+
+    let newline = |res: &mut String, depth: usize| {
+        let trimmed_len = res.trim_end_matches(' ').len();
+        res.truncate(trimmed_len);
+        res.push('\n');
+        for _ in 0..depth {
+            res.push_str(INDENT);
+        }
+    };
+    let at_token_start = |res: &str| {
+        res.trim_end()
+            .chars()
+            .last()
+            .map_or(true, |c| matches!(c, '{' | '[' | ',' | ':'))
+    };
+
+    while let Some(c) = chars.next() {
+        let starts_string = c == '"' || (!is_json && c == '\'' && at_token_start(&res));
+        if starts_string {
+            if pending_space {
+                res.push(' ');
+                pending_space = false;
+            }
+            res.push(c);
+            loop {
+                let sc = chars.next()?;
+                res.push(sc);
+                if sc == '\\' && c == '"' {
+                    res.push(chars.next()?);
+                } else if sc == c {
+                    // YAML escapes a single quote by doubling it.
+                    if c == '\'' && chars.peek() == Some(&'\'') {
+                        res.push(chars.next()?);
+                    } else {
+                        break;
+                    }
+                }
+            }
+            continue;
+        }
+
+        match c {
+            '{' | '[' => {
+                if pending_space {
+                    res.push(' ');
+                    pending_space = false;
+                }
+                res.push(c);
+                while chars.peek().map_or(false, |c| c.is_whitespace()) {
+                    chars.next();
+                }
+                let close = if c == '{' { '}' } else { ']' };
+                if chars.peek() == Some(&close) {
+                    res.push(close);
+                    chars.next();
+                } else {
+                    depth += 1;
+                    newline(&mut res, depth);
+                }
+            }
+            '}' | ']' => {
+                depth = depth.checked_sub(1)?;
+                newline(&mut res, depth);
+                res.push(c);
+                pending_space = false;
+            }
+            ',' => {
+                res.push(c);
+                newline(&mut res, depth);
+                pending_space = false;
+            }
+            ':' if is_json => {
+                res.push_str(": ");
+                pending_space = false;
+            }
+            '#' if !is_json && (pending_space || at_token_start(&res)) => return None,
+            c if c.is_whitespace() => {
+                pending_space = !res.ends_with(' ') && !res.ends_with('\n');
+            }
+            c => {
+                if pending_space {
+                    res.push(' ');
+                    pending_space = false;
+                }
+                res.push(c);
+            }
+        }
+    }
+
+    if depth != 0 {
+        return None;
+    }
+    res.push('\n');
+    Some(res)
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_reindent_json() {
+        assert_eq!(
+            reformat_json_src(r#"{"a":[1, 2],"b":{},"c":"x, {y}"}"#).unwrap(),
+            "{\n  \"a\": [\n    1,\n    2\n  ],\n  \"b\": {},\n  \"c\": \"x, {y}\"\n}\n"
+        );
+    }
+
+    #[test]
+    fn test_reindent_json_escaped_quote() {
+        assert_eq!(
+            reformat_json_src(r#"["a\"]", "b"]"#).unwrap(),
+            "[\n  \"a\\\"]\",\n  \"b\"\n]\n"
+        );
+    }
+
+    #[test]
+    fn test_reformat_invalid_json() {
+        assert_eq!(reformat_json_src(r#"{"a": 1"#), None);
+        assert_eq!(
+            reformat_both("[1]", "[1,", Some(Language::Json), ReformatJson::Always),
+            None
+        );
+    }
+
+    #[test]
+    fn test_reformat_yaml_flow() {
+        assert_eq!(
+            reformat_yaml_flow("{a: hello world, b: ['it''s', x]}\n").unwrap(),
+            "{\n  a: hello world,\n  b: [\n    'it''s',\n    x\n  ]\n}\n"
+        );
+        assert_eq!(reformat_yaml_flow("a: 1\nb: 2\n"), None);
+        assert_eq!(reformat_yaml_flow("{a: 1} # comment\n"), None);
+    }
+
+    #[test]
+    fn test_reformat_auto() {
+        assert_eq!(
+            reformat_both("[1]", "[2]", Some(Language::Json), ReformatJson::Auto),
+            None
+        );
+
+        let long_lhs = format!("[{}]", vec!["1"; 1000].join(","));
+        let long_rhs = format!("[{}]", vec!["2"; 1000].join(","));
+        let (lhs, rhs) = reformat_both(
+            &long_lhs,
+            &long_rhs,
+            Some(Language::Json),
+            ReformatJson::Auto,
+        )
+        .unwrap();
+        assert_eq!(lhs.lines().count(), 1002);
+        assert_eq!(rhs.lines().count(), 1002);
+    }
+}
@@ -0,0 +1,243 @@
+//! Time, memory usage and other statistics, reported with
+//! `--verbose --verbose`, except for the number of identical files in
+//! directory diffs and of files that exceeded the byte limit, which
+//! `--verbose` shows.
+
+use std::{
+    sync::atomic::{AtomicUsize, Ordering},
+    time::{Duration, Instant},
+};
+
+use serde_json::json;
+
+use crate::{
+    logging::{self, Level},
+    profile,
+};
+
+/// The number of files whose syntax trees had identical content, so
+/// skipped the graph search.
+static IDENTICAL_TOKEN_FILES: AtomicUsize = AtomicUsize::new(0);
+
+pub(crate) fn record_identical_tokens() {
+    IDENTICAL_TOKEN_FILES.fetch_add(1, Ordering::Relaxed);
+}
+
+/// The number of file pairs in directory diffs that had identical
+/// content, so weren't parsed.
+static IDENTICAL_CONTENT_FILES: AtomicUsize = AtomicUsize::new(0);
+
+pub(crate) fn record_identical_content() {
+    IDENTICAL_CONTENT_FILES.fetch_add(1, Ordering::Relaxed);
+}
+
+/// The number of files that exceeded the byte limit and used a text
+/// diff.
+static BYTE_LIMIT_FALLBACK_FILES: AtomicUsize = AtomicUsize::new(0);
+
+pub(crate) fn record_byte_limit_fallback() {
+    BYTE_LIMIT_FALLBACK_FILES.fetch_add(1, Ordering::Relaxed);
+}
+
+/// The number of files that exceeded the byte limit and were
+/// truncated, with `--byte-limit-action truncate`.
+static TRUNCATED_FILES: AtomicUsize = AtomicUsize::new(0);
+
+pub(crate) fn record_truncated() {
+    TRUNCATED_FILES.fetch_add(1, Ordering::Relaxed);
+}
+
+fn format_summary(identical_token_files: usize) -> String {
+    format!(
+        "{} file{} had identical tokens and skipped the graph search",
+        identical_token_files,
+        if identical_token_files == 1 { "" } else { "s" }
+    )
+}
+
+/// Print a summary of the whole run to stderr.
+pub(crate) fn print_summary() {
+    let identical_content_files = IDENTICAL_CONTENT_FILES.load(Ordering::Relaxed);
+    if identical_content_files > 0 {
+        logging::log(
+            Level::Info,
+            "identical_content",
+            &format!(
+                "{} file{} had identical content and skipped diffing",
+                identical_content_files,
+                if identical_content_files == 1 {
+                    ""
+                } else {
+                    "s"
+                }
+            ),
+            json!({ "identical_content_files": identical_content_files }),
+        );
+    }
+
+    let byte_limit_files = [
+        (
+            BYTE_LIMIT_FALLBACK_FILES.load(Ordering::Relaxed),
+            "used a text diff",
+            "byte_limit_fallback_files",
+        ),
+        (
+            TRUNCATED_FILES.load(Ordering::Relaxed),
+            "were truncated",
+            "truncated_files",
+        ),
+    ];
+    for (num_files, action, key) in byte_limit_files {
+        if num_files > 0 {
+            logging::log(
+                Level::Info,
+                "byte_limit",
+                &format!(
+                    "{} file{} exceeded DFT_BYTE_LIMIT and {}",
+                    num_files,
+                    if num_files == 1 { "" } else { "s" },
+                    action
+                ),
+                json!({ key: num_files }),
+            );
+        }
+    }
+
+    let identical_token_files = IDENTICAL_TOKEN_FILES.load(Ordering::Relaxed);
+    logging::log(
+        Level::Debug,
+        "summary",
+        &format_summary(identical_token_files),
+        json!({ "identical_token_files": identical_token_files }),
+    );
+}
+
+/// The peak resident set size of this process, in bytes.
+#[cfg(unix)]
+pub(crate) fn peak_rss_bytes() -> Option<u64> {
+    let mut usage: libc::rusage = unsafe { std::mem::zeroed() };
+    if unsafe { libc::getrusage(libc::RUSAGE_SELF, &mut usage) } != 0 {
+        return None;
+    }
+
+    let max_rss = usage.ru_maxrss as u64;
+    // macOS reports bytes, other platforms report KiB.
+    if cfg!(target_os = "macos") {
+        Some(max_rss)
+    } else {
+        Some(max_rss * 1024)
+    }
+}
+
+#[cfg(not(unix))]
+pub(crate) fn peak_rss_bytes() -> Option<u64> {
+    None
+}
+
+fn format_report(
+    display_path: &str,
+    elapsed: Duration,
+    stats: Option<&str>,
+    peak_rss: Option<u64>,
+) -> String {
+    let mut res = format!("{}: {:.2}s", display_path, elapsed.as_secs_f64());
+    if let Some(stats) = stats {
+        res.push_str(", ");
+        res.push_str(stats);
+    }
+    if let Some(peak_rss) = peak_rss {
+        res.push_str(&format!(", peak RSS {} MiB", peak_rss / (1024 * 1024)));
+    }
+    res
+}
+
+/// Prints the time since it was created, the graph statistics from
+/// the file's profile, and the peak memory usage so far, to stderr
+/// when dropped.
+pub(crate) struct UsageReport<'a> {
+    display_path: &'a str,
+    start: Instant,
+    /// Whether the profile was requested with `--profile`, rather
+    /// than only recorded for this report.
+    keep_profile: bool,
+}
+
+impl<'a> UsageReport<'a> {
+    pub(crate) fn start(display_path: &'a str, keep_profile: bool) -> Self {
+        Self {
+            display_path,
+            start: Instant::now(),
+            keep_profile,
+        }
+    }
+}
+
+impl<'a> Drop for UsageReport<'a> {
+    fn drop(&mut self) {
+        let elapsed = self.start.elapsed();
+        let peak_rss = peak_rss_bytes();
+        let profile = if self.keep_profile {
+            profile::file_profile(self.display_path)
+        } else {
+            profile::take_file_profile(self.display_path)
+        };
+        let stats = profile.as_ref().map(|profile| profile.format_stats());
+
+        logging::log(
+            Level::Debug,
+            "usage",
+            &format_report(self.display_path, elapsed, stats.as_deref(), peak_rss),
+            json!({
+                "path": self.display_path,
+                "seconds": elapsed.as_secs_f64(),
+                "peak_rss_bytes": peak_rss,
+                "profile": profile,
+            }),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_format_report() {
+        assert_eq!(
+            format_report(
+                "foo.rs",
+                Duration::from_millis(1500),
+                None,
+                Some(3 * 1024 * 1024)
+            ),
+            "foo.rs: 1.50s, peak RSS 3 MiB"
+        );
+        assert_eq!(
+            format_report("foo.rs", Duration::from_millis(20), None, None),
+            "foo.rs: 0.02s"
+        );
+        assert_eq!(
+            format_report(
+                "foo.rs",
+                Duration::from_millis(20),
+                Some("120 nodes parsed"),
+                Some(3 * 1024 * 1024)
+            ),
+            "foo.rs: 0.02s, 120 nodes parsed, peak RSS 3 MiB"
+        );
+    }
+
+    #[test]
+    fn test_format_summary() {
+        assert_eq!(
+            format_summary(1),
+            "1 file had identical tokens and skipped the graph search"
+        );
+        assert_eq!(
+            format_summary(3),
+            "3 files had identical tokens and skipped the graph search"
+        );
+    }
+}
@@ -0,0 +1,334 @@
+//! Serde support for the types that describe a diff, with the
+//! `serde-types` feature, so diffs can be saved and loaded again
+//! later.
+//!
+//! Most types derive `Serialize` and `Deserialize` where they're
+//! defined. This module has the helpers for types from other crates,
+//! and for types that are written as a single string. Names match
+//! the JSON output where it has the same concept, e.g. a span is
+//! `{"line_number": 0, "start": 0, "end": 3}`.
+
+use std::collections::HashSet;
+
+use line_numbers::{LineNumber, SingleLineSpan};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::parse::{
+    guess_language::{language_name, Language},
+    syntax::{AtomKind, StringKind, TokenKind},
+};
+
+/// A [LineNumber], as the zero-indexed number.
+pub(crate) mod line_number {
+    use super::*;
+
+    pub(crate) fn serialize<S: Serializer>(line: &LineNumber, s: S) -> Result<S::Ok, S::Error> {
+        line.0.serialize(s)
+    }
+
+    pub(crate) fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<LineNumber, D::Error> {
+        u32::deserialize(d).map(LineNumber)
+    }
+}
+
+/// A set of line numbers, as a sorted list so the output is stable.
+pub(crate) mod line_number_set {
+    use super::*;
+
+    pub(crate) fn serialize<S: Serializer>(
+        lines: &HashSet<LineNumber>,
+        s: S,
+    ) -> Result<S::Ok, S::Error> {
+        let mut lines: Vec<u32> = lines.iter().map(|line| line.0).collect();
+        lines.sort_unstable();
+        lines.serialize(s)
+    }
+
+    pub(crate) fn deserialize<'de, D: Deserializer<'de>>(
+        d: D,
+    ) -> Result<HashSet<LineNumber>, D::Error> {
+        let lines = Vec::<u32>::deserialize(d)?;
+        Ok(lines.into_iter().map(LineNumber).collect())
+    }
+}
+
+/// Pairs of LHS and RHS line numbers, where either may be missing.
+pub(crate) mod line_number_pairs {
+    use super::*;
+
+    pub(crate) fn serialize<S: Serializer>(
+        pairs: &[(Option<LineNumber>, Option<LineNumber>)],
+        s: S,
+    ) -> Result<S::Ok, S::Error> {
+        let pairs: Vec<(Option<u32>, Option<u32>)> = pairs
+            .iter()
+            .map(|(lhs, rhs)| (lhs.map(|l| l.0), rhs.map(|r| r.0)))
+            .collect();
+        pairs.serialize(s)
+    }
+
+    pub(crate) fn deserialize<'de, D: Deserializer<'de>>(
+        d: D,
+    ) -> Result<Vec<(Option<LineNumber>, Option<LineNumber>)>, D::Error> {
+        let pairs = Vec::<(Option<u32>, Option<u32>)>::deserialize(d)?;
+        Ok(pairs
+            .into_iter()
+            .map(|(lhs, rhs)| (lhs.map(LineNumber), rhs.map(LineNumber)))
+            .collect())
+    }
+}
+
+/// A [SingleLineSpan], with the field names of a change in the JSON
+/// output.
+#[derive(Serialize, Deserialize)]
+struct Span {
+    line_number: u32,
+    start: u32,
+    end: u32,
+}
+
+impl From<&SingleLineSpan> for Span {
+    fn from(span: &SingleLineSpan) -> Self {
+        Span {
+            line_number: span.line.0,
+            start: span.start_col,
+            end: span.end_col,
+        }
+    }
+}
+
+impl From<Span> for SingleLineSpan {
+    fn from(span: Span) -> Self {
+        SingleLineSpan {
+            line: LineNumber(span.line_number),
+            start_col: span.start,
+            end_col: span.end,
+        }
+    }
+}
+
+pub(crate) mod span {
+    use super::*;
+
+    pub(crate) fn serialize<S: Serializer>(span: &SingleLineSpan, s: S) -> Result<S::Ok, S::Error> {
+        Span::from(span).serialize(s)
+    }
+
+    pub(crate) fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<SingleLineSpan, D::Error> {
+        Span::deserialize(d).map(SingleLineSpan::from)
+    }
+}
+
+pub(crate) mod spans {
+    use super::*;
+
+    pub(crate) fn serialize<S: Serializer>(
+        spans: &[SingleLineSpan],
+        s: S,
+    ) -> Result<S::Ok, S::Error> {
+        let spans: Vec<Span> = spans.iter().map(Span::from).collect();
+        spans.serialize(s)
+    }
+
+    pub(crate) fn deserialize<'de, D: Deserializer<'de>>(
+        d: D,
+    ) -> Result<Vec<SingleLineSpan>, D::Error> {
+        let spans = Vec::<Span>::deserialize(d)?;
+        Ok(spans.into_iter().map(SingleLineSpan::from).collect())
+    }
+}
+
+/// A language is written as its name, like the `language` in the
+/// JSON output, e.g. `"C++"`.
+impl Serialize for Language {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        s.serialize_str(language_name(*self))
+    }
+}
+
+impl<'de> Deserialize<'de> for Language {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(d)?;
+        Language::from_name(&name)
+            .ok_or_else(|| de::Error::custom(format!("unknown language: {}", name)))
+    }
+}
+
+/// The highlight of a token, as the `highlight` name in the JSON
+/// output. Text is `"text"` rather than `"normal"`, so it survives a
+/// round trip.
+impl Serialize for TokenKind {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        s.serialize_str(match self {
+            TokenKind::Delimiter => "delimiter",
+            TokenKind::Atom(AtomKind::Normal) => "normal",
+            TokenKind::Atom(AtomKind::String(StringKind::StringLiteral)) => "string",
+            TokenKind::Atom(AtomKind::String(StringKind::Text)) => "text",
+            TokenKind::Atom(AtomKind::Type) => "type",
+            TokenKind::Atom(AtomKind::Comment) => "comment",
+            TokenKind::Atom(AtomKind::Keyword) => "keyword",
+            TokenKind::Atom(AtomKind::TreeSitterError) => "tree_sitter_error",
+        })
+    }
+}
+
+impl<'de> Deserialize<'de> for TokenKind {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(d)?;
+        Ok(match name.as_str() {
+            "delimiter" => TokenKind::Delimiter,
+            "normal" => TokenKind::Atom(AtomKind::Normal),
+            "string" => TokenKind::Atom(AtomKind::String(StringKind::StringLiteral)),
+            "text" => TokenKind::Atom(AtomKind::String(StringKind::Text)),
+            "type" => TokenKind::Atom(AtomKind::Type),
+            "comment" => TokenKind::Atom(AtomKind::Comment),
+            "keyword" => TokenKind::Atom(AtomKind::Keyword),
+            "tree_sitter_error" => TokenKind::Atom(AtomKind::TreeSitterError),
+            _ => {
+                return Err(de::Error::unknown_variant(
+                    &name,
+                    &[
+                        "delimiter",
+                        "normal",
+                        "string",
+                        "text",
+                        "type",
+                        "comment",
+                        "keyword",
+                        "tree_sitter_error",
+                    ],
+                ))
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use strum::IntoEnumIterator;
+
+    use super::*;
+    use crate::{
+        api::{diff_files, DiffOptions, FileDiff},
+        constants::Side,
+        display::hunks::{EnclosingDeclaration, Hunk},
+        parse::syntax::{MatchKind, MatchedPos},
+        summary::{BinaryInfo, FileContent, FileFormat},
+    };
+
+    fn round_trip<T>(value: &T) -> T
+    where
+        T: Serialize + for<'de> Deserialize<'de>,
+    {
+        let json = serde_json::to_string(value).unwrap();
+        serde_json::from_str(&json).unwrap()
+    }
+
+    #[test]
+    fn test_language_round_trip() {
+        for language in Language::iter() {
+            assert_eq!(round_trip(&language), language);
+        }
+        assert_eq!(
+            serde_json::to_value(Language::CPlusPlus).unwrap(),
+            serde_json::json!("C++")
+        );
+    }
+
+    #[test]
+    fn test_matched_pos_round_trip() {
+        let span = |line, start_col, end_col| SingleLineSpan {
+            line: LineNumber(line),
+            start_col,
+            end_col,
+        };
+        let positions = vec![
+            MatchedPos {
+                kind: MatchKind::UnchangedToken {
+                    highlight: TokenKind::Delimiter,
+                    self_pos: vec![span(0, 0, 1)],
+                    opposite_pos: vec![span(1, 0, 1)],
+                },
+                pos: span(0, 0, 1),
+            },
+            MatchedPos {
+                kind: MatchKind::Novel {
+                    highlight: TokenKind::Atom(AtomKind::String(StringKind::Text)),
+                    punctuation: false,
+                },
+                pos: span(2, 4, 9),
+            },
+            MatchedPos {
+                kind: MatchKind::Copied {
+                    highlight: TokenKind::Atom(AtomKind::Keyword),
+                    source_line: LineNumber(7),
+                },
+                pos: span(3, 0, 2),
+            },
+        ];
+        assert_eq!(round_trip(&positions), positions);
+
+        assert_eq!(
+            serde_json::to_value(&positions[2]).unwrap(),
+            serde_json::json!({
+                "kind": {
+                    "type": "copied",
+                    "highlight": "keyword",
+                    "copied_from": 7,
+                },
+                "pos": {"line_number": 3, "start": 0, "end": 2},
+            })
+        );
+    }
+
+    #[test]
+    fn test_hunk_round_trip() {
+        let hunk = Hunk {
+            novel_lhs: [LineNumber(3)].into_iter().collect(),
+            novel_rhs: [LineNumber(3), LineNumber(4)].into_iter().collect(),
+            lines: vec![
+                (Some(LineNumber(3)), Some(LineNumber(3))),
+                (None, Some(LineNumber(4))),
+            ],
+            enclosing_declaration: Some(EnclosingDeclaration {
+                description: "fn foo".to_owned(),
+                side: Side::Right,
+                start_line: LineNumber(1),
+                end_line: LineNumber(6),
+            }),
+        };
+        let loaded = round_trip(&hunk);
+        assert_eq!(loaded.novel_lhs, hunk.novel_lhs);
+        assert_eq!(loaded.novel_rhs, hunk.novel_rhs);
+        assert_eq!(loaded.lines, hunk.lines);
+        assert_eq!(loaded.enclosing_declaration, hunk.enclosing_declaration);
+    }
+
+    #[test]
+    fn test_summary_round_trip() {
+        let formats = vec![
+            FileFormat::SupportedLanguage(Language::Rust),
+            FileFormat::TextFallback {
+                reason: "exceeded DFT_GRAPH_LIMIT".to_owned(),
+            },
+            FileFormat::Binary,
+        ];
+        assert_eq!(round_trip(&formats), formats);
+
+        let content = FileContent::Text("fn main() {}\n".to_owned());
+        assert_eq!(round_trip(&content), content);
+
+        let info = BinaryInfo::new(b"\x00\x01\x02");
+        assert_eq!(round_trip(&info), info);
+    }
+
+    #[test]
+    fn test_file_diff_round_trip() {
+        let lhs = "fn foo() {\n    bar(1);\n}\n\nfn baz() {}\n";
+        let rhs = "fn foo() {\n    bar(1, 2);\n}\n\nfn baz() { quux(); }\n";
+        let diff: FileDiff = diff_files(lhs, rhs, Some(Language::Rust), &DiffOptions::default());
+        assert!(!diff.hunks.is_empty());
+        assert_eq!(round_trip(&diff), diff);
+    }
+}
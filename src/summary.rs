@@ -1,27 +1,78 @@
 //! Data types summarising the result of diffing content.
 
-use std::fmt::Display;
+use std::{
+    borrow::Cow,
+    collections::BTreeMap,
+    fmt::Display,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use humansize::{format_size, BINARY};
+use itertools::Itertools;
 
 use crate::{
-    display::hunks::Hunk,
+    constants::Side,
+    display::{hunks::Hunk, long_lines::LineMap},
+    hash::content_hash,
     parse::{
         guess_language::{self, language_name},
         syntax::MatchedPos,
     },
 };
 
+/// The mode git uses for symbolic links. Git passes the link target
+/// as the content of the file.
+pub(crate) const SYMLINK_MODE: &str = "120000";
+
 #[derive(Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde-types", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde-types", serde(rename_all = "snake_case"))]
 pub(crate) enum FileContent {
     Text(String),
     Binary,
 }
 
-#[derive(Debug, Clone)]
+/// The size and hash of one side of a binary file, so we can say
+/// more than "Binary contents changed".
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde-types", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct BinaryInfo {
+    #[cfg_attr(feature = "serde-types", serde(rename = "size"))]
+    pub(crate) len: u64,
+    pub(crate) hash: u64,
+}
+
+impl BinaryInfo {
+    pub(crate) fn new(bytes: &[u8]) -> Self {
+        Self {
+            len: bytes.len() as u64,
+            hash: content_hash(bytes),
+        }
+    }
+
+    /// The first 8 hex digits of the hash, enough to tell files apart
+    /// at a glance.
+    pub(crate) fn short_hash(&self) -> String {
+        format!("{:016x}", self.hash)[..8].to_owned()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde-types", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde-types", serde(rename_all = "snake_case"))]
 pub(crate) enum FileFormat {
     SupportedLanguage(guess_language::Language),
     PlainText,
-    TextFallback { reason: String },
+    TextFallback {
+        reason: String,
+    },
+    /// A generated file, where we only show the number of changed
+    /// lines. See `--collapse-generated`.
+    Generated,
     Binary,
+    /// A subdirectory beyond `--max-depth`, which we only compare by
+    /// hashing its files.
+    Directory,
 }
 
 impl Display for FileFormat {
@@ -30,16 +81,40 @@ impl Display for FileFormat {
             FileFormat::SupportedLanguage(language) => write!(f, "{}", language_name(*language)),
             FileFormat::PlainText => write!(f, "Text"),
             FileFormat::TextFallback { reason } => write!(f, "Text ({})", reason),
+            FileFormat::Generated => write!(f, "Generated"),
             FileFormat::Binary => write!(f, "Binary"),
+            FileFormat::Directory => write!(f, "Directory"),
         }
     }
 }
 
 #[derive(Debug)]
+#[cfg_attr(feature = "serde-types", derive(serde::Serialize, serde::Deserialize))]
 pub(crate) struct DiffResult {
+    /// The path to show, with any bytes that aren't valid UTF-8
+    /// escaped, see [crate::files::escape_path].
+    #[cfg_attr(feature = "serde-types", serde(rename = "path"))]
     pub(crate) display_path: String,
+    /// The bytes of the path, if it isn't valid UTF-8, so JSON output
+    /// can include the path losslessly.
+    pub(crate) path_bytes: Option<Vec<u8>>,
+    /// The previous path of this file, if git reported that it was
+    /// renamed.
+    #[cfg_attr(feature = "serde-types", serde(rename = "old_path"))]
+    pub(crate) renamed_from: Option<String>,
+    /// In a directory diff, the side that doesn't have this file,
+    /// so the file was created or deleted.
+    pub(crate) missing_side: Option<Side>,
+    /// The old and new mode, if git reported that the file mode
+    /// changed, e.g. `("100644", "100755")`.
+    pub(crate) mode_change: Option<(String, String)>,
+    /// The old and new modification time, with `--compare-mtime`.
+    pub(crate) mtime_change: Option<(String, String)>,
+    /// The size and hash of each side, for binary files whose content
+    /// we read.
+    pub(crate) binary_info: Option<(BinaryInfo, BinaryInfo)>,
     /// Additional information to display about this file, such as
-    /// "Renamed from x.js to y.js".
+    /// "Mode changed 100644 → 100755.".
     pub(crate) extra_info: Option<String>,
 
     pub(crate) file_format: FileFormat,
@@ -49,13 +124,217 @@ pub(crate) struct DiffResult {
 
     pub(crate) lhs_positions: Vec<MatchedPos>,
     pub(crate) rhs_positions: Vec<MatchedPos>,
+    /// The source line of each display line, if long lines were
+    /// split for display.
+    pub(crate) lhs_line_map: LineMap,
+    pub(crate) rhs_line_map: LineMap,
+
+    /// Why this file wasn't diffed as accurately as usual, if it
+    /// wasn't, for the recap at the end of the run.
+    pub(crate) degradation: Option<Degradation>,
 
     pub(crate) has_byte_changes: bool,
     pub(crate) has_syntactic_changes: bool,
 }
 
+/// A reason that a file was diffed less accurately than usual,
+/// because it was too large or complex for a structural diff. This is
+/// always serializable, as it's the `reason` of `degraded` JSON log
+/// events.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, serde::Serialize)]
+#[cfg_attr(feature = "serde-types", derive(serde::Deserialize))]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum Degradation {
+    /// Some lines exceeded DFT_GRAPH_LIMIT and used a token diff.
+    GraphLimit,
+    /// Too many parse errors, so the file used a text diff.
+    ParseErrors,
+    /// The file exceeded DFT_BYTE_LIMIT, so it used a text diff.
+    ByteLimit,
+    /// The structural diff exceeded DFT_TIMEOUT, so the file used a
+    /// text diff.
+    Timeout,
+}
+
+impl Degradation {
+    /// The degradation of a file in `file_format`, if it fell back
+    /// to a text diff. The reason says which limit it exceeded.
+    pub(crate) fn from_file_format(file_format: &FileFormat) -> Option<Self> {
+        let FileFormat::TextFallback { reason } = file_format else {
+            return None;
+        };
+        if reason.ends_with("DFT_PARSE_ERROR_LIMIT") {
+            Some(Degradation::ParseErrors)
+        } else if reason.ends_with("DFT_BYTE_LIMIT") {
+            Some(Degradation::ByteLimit)
+        } else if reason.ends_with("DFT_TIMEOUT") {
+            Some(Degradation::Timeout)
+        } else {
+            None
+        }
+    }
+
+    fn short_name(self) -> &'static str {
+        match self {
+            Degradation::GraphLimit => "graph limit",
+            Degradation::ParseErrors => "parse errors",
+            Degradation::ByteLimit => "too large",
+            Degradation::Timeout => "timed out",
+        }
+    }
+
+    fn description(self) -> &'static str {
+        match self {
+            Degradation::GraphLimit => {
+                "exceeded DFT_GRAPH_LIMIT and used a token diff for some lines"
+            }
+            Degradation::ParseErrors => "had too many parse errors and used a text diff",
+            Degradation::ByteLimit => "exceeded DFT_BYTE_LIMIT and used a text diff",
+            Degradation::Timeout => "exceeded DFT_TIMEOUT and used a text diff",
+        }
+    }
+
+    /// The option that would avoid this degradation.
+    fn hint(self) -> &'static str {
+        match self {
+            Degradation::GraphLimit => {
+                "Use a larger --graph-limit to diff them structurally, at the cost of memory."
+            }
+            Degradation::ParseErrors => {
+                "Use a larger --parse-error-limit to diff them structurally anyway."
+            }
+            Degradation::ByteLimit => "Use a larger --byte-limit, or --byte-limit-action=truncate.",
+            Degradation::Timeout => "Use a larger --timeout to give the structural diff longer.",
+        }
+    }
+}
+
 impl DiffResult {
+    /// Add a line to the information shown in the header.
+    pub(crate) fn add_extra_info(&mut self, info: &str) {
+        self.extra_info = Some(match self.extra_info.take() {
+            Some(extra_info) => format!("{}\n{}", extra_info, info),
+            None => info.to_owned(),
+        });
+    }
+
+    /// Record the modes of both sides of this file, describing mode
+    /// changes and symlinks in the header.
+    pub(crate) fn set_modes(&mut self, lhs_mode: &str, rhs_mode: &str) {
+        if lhs_mode != rhs_mode {
+            self.mode_change = Some((lhs_mode.to_owned(), rhs_mode.to_owned()));
+        }
+
+        let info = match (lhs_mode == SYMLINK_MODE, rhs_mode == SYMLINK_MODE) {
+            (true, true) => "Symlink: comparing the link targets.".to_owned(),
+            (false, true) => format!(
+                "Changed from a regular file to a symlink, mode {} → {}.",
+                lhs_mode, rhs_mode
+            ),
+            (true, false) => format!(
+                "Changed from a symlink to a regular file, mode {} → {}.",
+                lhs_mode, rhs_mode
+            ),
+            (false, false) if lhs_mode != rhs_mode => {
+                format!("Mode changed {} → {}.", lhs_mode, rhs_mode)
+            }
+            (false, false) => return,
+        };
+        self.add_extra_info(&info);
+    }
+
+    /// Record the modification times of both sides of this file, to
+    /// the second, so copies that preserve the time aren't reported.
+    pub(crate) fn set_mtimes(&mut self, lhs_mtime: SystemTime, rhs_mtime: SystemTime) {
+        let lhs_mtime = format_mtime(lhs_mtime);
+        let rhs_mtime = format_mtime(rhs_mtime);
+        if lhs_mtime != rhs_mtime {
+            self.add_extra_info(&format!(
+                "Modification time changed {} → {}.",
+                lhs_mtime, rhs_mtime
+            ));
+            self.mtime_change = Some((lhs_mtime, rhs_mtime));
+        }
+    }
+
+    /// What changed in a binary file, with the size and hash of each
+    /// side when we have them, e.g. "Binary contents changed, 4.1 KiB
+    /// → 5.3 KiB (hash 1a2b3c4d → 5e6f7a8b).".
+    pub(crate) fn binary_message(&self) -> String {
+        let message = if self.has_byte_changes {
+            "Binary contents changed"
+        } else if self.has_metadata_change() {
+            "No content changes"
+        } else {
+            "No changes"
+        };
+        let size = |info: &BinaryInfo| format_size(info.len, BINARY);
+
+        match (&self.binary_info, self.missing_side) {
+            (Some((_, info)), Some(Side::Left)) | (Some((info, _)), Some(Side::Right)) => {
+                format!("{}, {} (hash {}).", message, size(info), info.short_hash())
+            }
+            (Some((lhs_info, rhs_info)), None) if lhs_info != rhs_info => format!(
+                "{}, {} → {} (hash {} → {}).",
+                message,
+                size(lhs_info),
+                size(rhs_info),
+                lhs_info.short_hash(),
+                rhs_info.short_hash()
+            ),
+            (Some((info, _)), None) => {
+                format!("{}, {} (hash {}).", message, size(info), info.short_hash())
+            }
+            (None, _) => format!("{}.", message),
+        }
+    }
+
+    /// Whether the mode or modification time changed, which is a
+    /// change even if the content is the same.
+    pub(crate) fn has_metadata_change(&self) -> bool {
+        self.mode_change.is_some() || self.mtime_change.is_some()
+    }
+
+    /// The path to show in headers, including the previous path if
+    /// the file was renamed, or whether it was created or deleted.
+    pub(crate) fn header_path(&self) -> Cow<'_, str> {
+        match (&self.renamed_from, self.missing_side) {
+            (Some(renamed_from), _) => {
+                Cow::Owned(format!("{} → {}", renamed_from, self.display_path))
+            }
+            (None, Some(Side::Left)) => Cow::Owned(format!("{} (new file)", self.display_path)),
+            (None, Some(Side::Right)) => Cow::Owned(format!("{} (deleted)", self.display_path)),
+            (None, None) => Cow::Borrowed(&self.display_path),
+        }
+    }
+
+    /// Whether we only compared the bytes of this file, because at
+    /// least one side is binary.
+    pub(crate) fn is_binary(&self) -> bool {
+        self.file_format == FileFormat::Binary
+    }
+
+    /// What this diff found. Binary files whose content differs are
+    /// changes for `--exit-code`, but we can't show what changed, so
+    /// they're reported separately.
+    pub(crate) fn outcome(&self) -> Outcome {
+        if !self.has_reportable_change() {
+            Outcome::Unchanged
+        } else if self.is_binary() && self.has_byte_changes {
+            Outcome::BinaryChanged
+        } else {
+            Outcome::Changed
+        }
+    }
+
     pub(crate) fn has_reportable_change(&self) -> bool {
+        // Creating or deleting an empty file is still a change, as is
+        // changing the mode or modification time without changing the
+        // content.
+        if self.missing_side.is_some() || self.has_metadata_change() {
+            return true;
+        }
+
         if matches!(self.lhs_src, FileContent::Binary)
             || matches!(self.rhs_src, FileContent::Binary)
         {
@@ -65,3 +344,334 @@ impl DiffResult {
         self.has_syntactic_changes
     }
 }
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Outcome {
+    Unchanged,
+    Changed,
+    /// The content of a binary file changed.
+    BinaryChanged,
+}
+
+/// The number of files of each kind in a diff of several files, for
+/// the summary printed at the end, see `--no-summary`. Changed binary
+/// files and files that fell back to a text diff are counted
+/// separately, rather than as changed or unchanged.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub(crate) struct RunSummary {
+    pub(crate) num_files: usize,
+    pub(crate) num_changed: usize,
+    pub(crate) num_unchanged: usize,
+    pub(crate) num_binary_changed: usize,
+    /// Binary files hidden by `--skip-binary`.
+    pub(crate) num_skipped: usize,
+    /// Subdirectories beyond `--max-depth`, which aren't counted as
+    /// files.
+    pub(crate) num_directories: usize,
+    pub(crate) num_directories_changed: usize,
+    /// The number of text fallbacks for each kind of reason.
+    pub(crate) text_fallbacks: BTreeMap<&'static str, usize>,
+    /// The paths of the files with each degradation, including token
+    /// diffs that aren't text fallbacks.
+    pub(crate) degraded_paths: BTreeMap<Degradation, Vec<String>>,
+}
+
+/// How many paths to list for each degradation in the recap.
+const MAX_RECAP_PATHS: usize = 3;
+
+impl RunSummary {
+    pub(crate) fn add(&mut self, diff_result: &DiffResult) {
+        if diff_result.file_format == FileFormat::Directory {
+            self.num_directories += 1;
+            if diff_result.has_reportable_change() {
+                self.num_directories_changed += 1;
+            }
+            return;
+        }
+
+        self.num_files += 1;
+        if let Some(degradation) = diff_result.degradation {
+            self.degraded_paths
+                .entry(degradation)
+                .or_default()
+                .push(diff_result.display_path.clone());
+        }
+        if let FileFormat::TextFallback { .. } = &diff_result.file_format {
+            let kind = Degradation::from_file_format(&diff_result.file_format)
+                .map_or("other", Degradation::short_name);
+            *self.text_fallbacks.entry(kind).or_default() += 1;
+            return;
+        }
+        match diff_result.outcome() {
+            Outcome::Unchanged => self.num_unchanged += 1,
+            Outcome::Changed => self.num_changed += 1,
+            Outcome::BinaryChanged => self.num_binary_changed += 1,
+        }
+    }
+
+    pub(crate) fn add_skipped(&mut self) {
+        self.num_files += 1;
+        self.num_skipped += 1;
+    }
+
+    /// A line for each kind of degradation, with the number of files,
+    /// the first few paths and the option that would help, e.g. "2
+    /// files exceeded DFT_BYTE_LIMIT and used a text diff: a.json,
+    /// b.json. Use a larger --byte-limit, or
+    /// --byte-limit-action=truncate."
+    pub(crate) fn recap(&self) -> Vec<String> {
+        self.degraded_paths
+            .iter()
+            .map(|(degradation, paths)| {
+                let mut paths = paths.clone();
+                paths.sort_unstable();
+                let mut listed = paths.iter().take(MAX_RECAP_PATHS).join(", ");
+                if paths.len() > MAX_RECAP_PATHS {
+                    listed.push_str(&format!(" and {} more", paths.len() - MAX_RECAP_PATHS));
+                }
+                format!(
+                    "{} file{} {}: {}. {}",
+                    paths.len(),
+                    if paths.len() == 1 { "" } else { "s" },
+                    degradation.description(),
+                    listed,
+                    degradation.hint()
+                )
+            })
+            .collect()
+    }
+}
+
+impl Display for RunSummary {
+    /// E.g. "Compared 12 files: 3 changed, 8 unchanged, 1 binary
+    /// changed".
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Compared {} file{}: {} changed, {} unchanged",
+            self.num_files,
+            if self.num_files == 1 { "" } else { "s" },
+            self.num_changed,
+            self.num_unchanged
+        )?;
+        if self.num_binary_changed > 0 {
+            write!(f, ", {} binary changed", self.num_binary_changed)?;
+        }
+
+        let num_fallbacks: usize = self.text_fallbacks.values().sum();
+        if num_fallbacks > 0 {
+            let kinds = if self.text_fallbacks.len() == 1 {
+                self.text_fallbacks.keys().join(", ")
+            } else {
+                self.text_fallbacks
+                    .iter()
+                    .map(|(kind, count)| format!("{} {}", count, kind))
+                    .join(", ")
+            };
+            write!(f, ", {} text fallback ({})", num_fallbacks, kinds)?;
+        }
+        if self.num_skipped > 0 {
+            write!(f, ", {} binary skipped", self.num_skipped)?;
+        }
+        if self.num_directories > 0 {
+            write!(
+                f,
+                ", {} director{} beyond --max-depth ({} changed)",
+                self.num_directories,
+                if self.num_directories == 1 {
+                    "y"
+                } else {
+                    "ies"
+                },
+                self.num_directories_changed
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Format `time` as e.g. `2024-03-01 13:05:09 UTC`. Times before 1970
+/// are shown as 1970.
+fn format_mtime(time: SystemTime) -> String {
+    let secs = time
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0);
+    let (days, secs_of_day) = (secs / 86400, secs % 86400);
+
+    // Convert days since 1970-01-01 to a date in the proleptic
+    // Gregorian calendar, see
+    // http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+    let z = days + 719468;
+    let era = z / 146097;
+    let day_of_era = z % 146097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + u64::from(month <= 2);
+
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02} UTC",
+        year,
+        month,
+        day,
+        secs_of_day / 3600,
+        secs_of_day % 3600 / 60,
+        secs_of_day % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_format_mtime() {
+        assert_eq!(format_mtime(UNIX_EPOCH), "1970-01-01 00:00:00 UTC");
+        assert_eq!(
+            format_mtime(UNIX_EPOCH + Duration::from_secs(1_709_298_309)),
+            "2024-03-01 13:05:09 UTC"
+        );
+    }
+
+    #[test]
+    fn test_run_summary_display() {
+        let mut summary = RunSummary {
+            num_files: 412,
+            num_changed: 37,
+            num_unchanged: 370,
+            num_binary_changed: 3,
+            ..RunSummary::default()
+        };
+        summary.text_fallbacks.insert("parse errors", 2);
+        assert_eq!(
+            summary.to_string(),
+            "Compared 412 files: 37 changed, 370 unchanged, 3 binary changed, 2 text fallback (parse errors)"
+        );
+
+        summary.num_files += 1;
+        summary.text_fallbacks.insert("too large", 1);
+        summary.add_skipped();
+        assert_eq!(
+            summary.to_string(),
+            "Compared 414 files: 37 changed, 370 unchanged, 3 binary changed, 3 text fallback (2 parse errors, 1 too large), 1 binary skipped"
+        );
+    }
+
+    #[test]
+    fn test_run_summary_one_file() {
+        let summary = RunSummary {
+            num_files: 1,
+            num_changed: 1,
+            ..RunSummary::default()
+        };
+        assert_eq!(
+            summary.to_string(),
+            "Compared 1 file: 1 changed, 0 unchanged"
+        );
+    }
+
+    #[test]
+    fn test_run_summary_directories() {
+        let summary = RunSummary {
+            num_files: 1,
+            num_unchanged: 1,
+            num_directories: 2,
+            num_directories_changed: 1,
+            ..RunSummary::default()
+        };
+        assert_eq!(
+            summary.to_string(),
+            "Compared 1 file: 0 changed, 1 unchanged, 2 directories beyond --max-depth (1 changed)"
+        );
+    }
+
+    #[test]
+    fn test_degradation_from_file_format() {
+        let fallback = |reason: &str| FileFormat::TextFallback {
+            reason: reason.to_owned(),
+        };
+        assert_eq!(
+            Degradation::from_file_format(&fallback(
+                "3 Rust parse errors, exceeded DFT_PARSE_ERROR_LIMIT"
+            )),
+            Some(Degradation::ParseErrors)
+        );
+        assert_eq!(
+            Degradation::from_file_format(&fallback("1.2 MiB exceeded DFT_BYTE_LIMIT")),
+            Some(Degradation::ByteLimit)
+        );
+        assert_eq!(Degradation::from_file_format(&FileFormat::PlainText), None);
+    }
+
+    #[test]
+    fn test_recap() {
+        let mut summary = RunSummary::default();
+        summary.degraded_paths.insert(
+            Degradation::GraphLimit,
+            vec!["e.rs", "b.rs", "d.rs", "a.rs", "c.rs"]
+                .into_iter()
+                .map(String::from)
+                .collect(),
+        );
+        summary
+            .degraded_paths
+            .insert(Degradation::Timeout, vec!["slow.js".to_owned()]);
+        assert_eq!(
+            summary.recap(),
+            vec![
+                "5 files exceeded DFT_GRAPH_LIMIT and used a token diff for some lines: a.rs, b.rs, c.rs and 2 more. Use a larger --graph-limit to diff them structurally, at the cost of memory.",
+                "1 file exceeded DFT_TIMEOUT and used a text diff: slow.js. Use a larger --timeout to give the structural diff longer.",
+            ]
+        );
+    }
+
+    fn binary_result(lhs_bytes: &[u8], rhs_bytes: &[u8]) -> DiffResult {
+        DiffResult {
+            display_path: "logo.png".to_owned(),
+            path_bytes: None,
+            renamed_from: None,
+            missing_side: None,
+            mode_change: None,
+            mtime_change: None,
+            binary_info: Some((BinaryInfo::new(lhs_bytes), BinaryInfo::new(rhs_bytes))),
+            extra_info: None,
+            file_format: FileFormat::Binary,
+            lhs_src: FileContent::Binary,
+            rhs_src: FileContent::Binary,
+            hunks: vec![],
+            lhs_positions: vec![],
+            rhs_positions: vec![],
+            lhs_line_map: LineMap::default(),
+            rhs_line_map: LineMap::default(),
+            degradation: None,
+            has_byte_changes: lhs_bytes != rhs_bytes,
+            has_syntactic_changes: false,
+        }
+    }
+
+    #[test]
+    fn test_binary_outcome() {
+        let identical = binary_result(b"\x00\x01", b"\x00\x01");
+        assert_eq!(identical.outcome(), Outcome::Unchanged);
+
+        let differing = binary_result(b"\x00\x01", b"\x00\x02");
+        assert_eq!(differing.outcome(), Outcome::BinaryChanged);
+        assert!(differing.has_reportable_change());
+
+        let mut summary = RunSummary::default();
+        summary.add(&identical);
+        summary.add(&differing);
+        assert_eq!(
+            summary.to_string(),
+            "Compared 2 files: 0 changed, 1 unchanged, 1 binary changed"
+        );
+    }
+}
@@ -0,0 +1,253 @@
+//! Detect whether the terminal has a light or dark background.
+//!
+//! We ask the terminal for its background color with an OSC 11
+//! query, and fall back to the `COLORFGBG` environment variable that
+//! some terminals set.
+
+use std::env;
+use std::time::Duration;
+
+use crate::display::style::BackgroundColor;
+
+/// How long to wait for the terminal to respond to a query. Local
+/// terminals respond almost immediately, but this also needs to allow
+/// for terminals over SSH.
+#[cfg(unix)]
+const QUERY_TIMEOUT: Duration = Duration::from_millis(100);
+
+/// Parse a color component from an OSC 11 response, which has between
+/// one and four hex digits, and scale it to 16 bits.
+fn parse_component(s: &str) -> Option<u16> {
+    if s.is_empty() || s.len() > 4 {
+        return None;
+    }
+
+    let value = u32::from_str_radix(s, 16).ok()?;
+    let max = (1 << (4 * s.len())) - 1;
+    Some((value * 0xffff / max) as u16)
+}
+
+/// Parse the color from a response to an OSC 11 query, e.g.
+/// `\x1b]11;rgb:1e1e/1e1e/1e1e\x07`. The response may be terminated
+/// by BEL or by ST (`\x1b\\`), and may be followed by other output.
+fn parse_osc11_response(response: &[u8]) -> Option<(u16, u16, u16)> {
+    let response = String::from_utf8_lossy(response);
+    let prefix = "\x1b]11;rgb:";
+    let start = response.find(prefix)? + prefix.len();
+    let rest = &response[start..];
+    let end = rest.find(|c: char| c == '\x07' || c == '\x1b')?;
+
+    let components: Vec<&str> = rest[..end].split('/').collect();
+    match components[..] {
+        [r, g, b] => Some((
+            parse_component(r)?,
+            parse_component(g)?,
+            parse_component(b)?,
+        )),
+        _ => None,
+    }
+}
+
+fn background_from_rgb((r, g, b): (u16, u16, u16)) -> BackgroundColor {
+    // Relative luminance, as defined by ITU-R BT.709.
+    let luminance = 0.2126 * r as f64 + 0.7152 * g as f64 + 0.0722 * b as f64;
+    if luminance < 0xffff as f64 / 2.0 {
+        BackgroundColor::Dark
+    } else {
+        BackgroundColor::Light
+    }
+}
+
+/// Parse `COLORFGBG`, which is of the form `fg;bg` or `fg;default;bg`,
+/// where `bg` is an ANSI color number.
+fn parse_colorfgbg(value: &str) -> Option<BackgroundColor> {
+    let bg: u8 = value.rsplit(';').next()?.parse().ok()?;
+    match bg {
+        0..=6 | 8 => Some(BackgroundColor::Dark),
+        7 | 9..=15 => Some(BackgroundColor::Light),
+        _ => None,
+    }
+}
+
+/// Whether `response` contains a complete response to a DA1 (primary
+/// device attributes) query, e.g. `\x1b[?62;22c`.
+#[cfg(unix)]
+fn has_da1_response(response: &[u8]) -> bool {
+    response
+        .windows(3)
+        .position(|w| w == b"\x1b[?")
+        .map_or(false, |start| response[start..].contains(&b'c'))
+}
+
+/// Write the OSC 11 query to `fd`, and return everything the terminal
+/// writes back before `timeout`.
+#[cfg(unix)]
+fn write_query_and_read(fd: libc::c_int, timeout: Duration) -> Option<Vec<u8>> {
+    use std::time::Instant;
+
+    // Ask for the background color, then the device
+    // attributes. Almost all terminals respond to DA1, so we know
+    // we've received everything once we see that response, even if
+    // the terminal ignored the OSC 11 query.
+    let query = b"\x1b]11;?\x07\x1b[c";
+    let written = unsafe { libc::write(fd, query.as_ptr() as *const libc::c_void, query.len()) };
+    if written < 0 || written as usize != query.len() {
+        return None;
+    }
+
+    let deadline = Instant::now() + timeout;
+    let mut response = vec![];
+    loop {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        let mut pollfd = libc::pollfd {
+            fd,
+            events: libc::POLLIN,
+            revents: 0,
+        };
+        let ready = unsafe { libc::poll(&mut pollfd, 1, remaining.as_millis() as libc::c_int) };
+        if ready <= 0 {
+            // Timed out, or poll failed.
+            break;
+        }
+
+        let mut buf = [0; 64];
+        let num_read = unsafe { libc::read(fd, buf.as_mut_ptr() as *mut libc::c_void, buf.len()) };
+        if num_read <= 0 {
+            break;
+        }
+        response.extend_from_slice(&buf[..num_read as usize]);
+
+        if has_da1_response(&response) {
+            break;
+        }
+    }
+
+    Some(response)
+}
+
+/// Ask the terminal connected to `fd` for its background color,
+/// giving up after `timeout`.
+#[cfg(unix)]
+fn query_background_color(fd: libc::c_int, timeout: Duration) -> Option<(u16, u16, u16)> {
+    let mut original: libc::termios = unsafe { std::mem::zeroed() };
+    if unsafe { libc::tcgetattr(fd, &mut original) } != 0 {
+        return None;
+    }
+
+    // Disable line buffering, so we can read the response without a
+    // newline, and disable echo, so the response isn't shown.
+    let mut raw = original;
+    raw.c_lflag &= !(libc::ICANON | libc::ECHO);
+    raw.c_cc[libc::VMIN] = 0;
+    raw.c_cc[libc::VTIME] = 0;
+    if unsafe { libc::tcsetattr(fd, libc::TCSANOW, &raw) } != 0 {
+        return None;
+    }
+
+    let response = write_query_and_read(fd, timeout);
+
+    unsafe {
+        libc::tcsetattr(fd, libc::TCSANOW, &original);
+    }
+
+    parse_osc11_response(&response?)
+}
+
+#[cfg(unix)]
+fn query_terminal() -> Option<BackgroundColor> {
+    use std::os::unix::io::AsRawFd;
+
+    let tty = std::fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open("/dev/tty")
+        .ok()?;
+    query_background_color(tty.as_raw_fd(), QUERY_TIMEOUT).map(background_from_rgb)
+}
+
+#[cfg(not(unix))]
+fn query_terminal() -> Option<BackgroundColor> {
+    None
+}
+
+/// Try to work out whether the terminal has a light or dark
+/// background. Callers should only use this when stdout is a TTY.
+pub(crate) fn detect_background() -> Option<BackgroundColor> {
+    if let Some(background) = query_terminal() {
+        return Some(background);
+    }
+
+    env::var("COLORFGBG")
+        .ok()
+        .and_then(|value| parse_colorfgbg(&value))
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_component() {
+        assert_eq!(parse_component("ffff"), Some(0xffff));
+        assert_eq!(parse_component("ff"), Some(0xffff));
+        assert_eq!(parse_component("8"), Some(0x8888));
+        assert_eq!(parse_component(""), None);
+        assert_eq!(parse_component("xyz"), None);
+    }
+
+    #[test]
+    fn test_parse_osc11_response() {
+        assert_eq!(
+            parse_osc11_response(b"\x1b]11;rgb:1e1e/1e1e/1e1e\x07"),
+            Some((0x1e1e, 0x1e1e, 0x1e1e))
+        );
+        assert_eq!(
+            parse_osc11_response(b"\x1b]11;rgb:ff/ff/ff\x1b\\\x1b[?62;22c"),
+            Some((0xffff, 0xffff, 0xffff))
+        );
+        assert_eq!(parse_osc11_response(b"\x1b[?62;22c"), None);
+    }
+
+    #[test]
+    fn test_background_from_rgb() {
+        assert!(background_from_rgb((0x1e1e, 0x1e1e, 0x1e1e)).is_dark());
+        assert!(!background_from_rgb((0xfdfd, 0xf6f6, 0xe3e3)).is_dark());
+    }
+
+    #[test]
+    fn test_parse_colorfgbg() {
+        assert!(parse_colorfgbg("15;0").unwrap().is_dark());
+        assert!(!parse_colorfgbg("0;default;15").unwrap().is_dark());
+        assert!(parse_colorfgbg("default").is_none());
+    }
+
+    /// A terminal that never responds must not hang difftastic.
+    #[cfg(unix)]
+    #[test]
+    fn test_query_timeout() {
+        use std::time::Instant;
+
+        unsafe {
+            let master = libc::posix_openpt(libc::O_RDWR | libc::O_NOCTTY);
+            assert!(master >= 0);
+            assert_eq!(libc::grantpt(master), 0);
+            assert_eq!(libc::unlockpt(master), 0);
+
+            let slave_name = libc::ptsname(master);
+            assert!(!slave_name.is_null());
+            let slave = libc::open(slave_name, libc::O_RDWR | libc::O_NOCTTY);
+            assert!(slave >= 0);
+
+            let start = Instant::now();
+            let color = query_background_color(slave, Duration::from_millis(50));
+
+            assert_eq!(color, None);
+            assert!(start.elapsed() < Duration::from_secs(5));
+
+            libc::close(slave);
+            libc::close(master);
+        }
+    }
+}
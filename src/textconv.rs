@@ -0,0 +1,159 @@
+//! Run git textconv filters with `--textconv auto`.
+//!
+//! When git runs difftastic as an external diff, it passes the files
+//! as they are stored, so formats that rely on a textconv filter (set
+//! with the `diff` attribute and `diff.DRIVER.textconv`) look binary.
+//! We look up the filter for each path and run it ourselves, like
+//! `git diff` does.
+
+use std::{
+    fs,
+    hash::Hasher,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use rustc_hash::FxHasher;
+
+use crate::git;
+
+/// One side of a file passed by git as an external diff.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) struct GitSide {
+    /// The path relative to the root of the repository.
+    pub(crate) path: String,
+    /// The blob hash, or `.` when the file doesn't exist on this
+    /// side.
+    pub(crate) hash: String,
+}
+
+/// The output of a textconv filter.
+pub(crate) struct Converted {
+    /// The name of the diff driver, e.g. `jupyter`.
+    pub(crate) driver: String,
+    pub(crate) bytes: Vec<u8>,
+}
+
+/// The value of the `diff` attribute in the output of `git check-attr
+/// -z diff -- PATH`, which is `PATH\0diff\0VALUE\0`. Only drivers
+/// are returned, not `set`, `unset` or `unspecified`.
+fn parse_diff_attr(stdout: &str) -> Option<String> {
+    let value = stdout.split('\0').nth(2)?;
+    match value {
+        "" | "set" | "unset" | "unspecified" => None,
+        driver => Some(driver.to_owned()),
+    }
+}
+
+/// The diff driver and textconv command for `path`, if it has one.
+fn textconv_command(path: &str) -> Option<(String, String)> {
+    let stdout = git::run_git(&["check-attr", "-z", "diff", "--", path]).ok()?;
+    let driver = parse_diff_attr(&String::from_utf8_lossy(&stdout))?;
+
+    // `git config --get` fails if the driver has no textconv.
+    let stdout = git::run_git(&["config", "--get", &format!("diff.{}.textconv", driver)]).ok()?;
+    let command = String::from_utf8_lossy(&stdout).trim_end().to_owned();
+    (!command.is_empty()).then_some((driver, command))
+}
+
+/// Whether `hash` identifies a blob that we can cache. Git passes
+/// all zeros for files in the working tree.
+fn is_cacheable(hash: &str) -> bool {
+    hash != "." && hash.chars().any(|c| c != '0')
+}
+
+fn cache_path(cache_dir: &Path, command: &str, hash: &str) -> PathBuf {
+    let mut hasher = FxHasher::default();
+    hasher.write(command.as_bytes());
+    hasher.write_u8(0);
+    hasher.write(hash.as_bytes());
+    cache_dir.join(format!("textconv-{:016x}.txt", hasher.finish()))
+}
+
+/// Run `command` on `file` with the shell, as git does, so commands
+/// can include arguments.
+fn run_textconv(command: &str, file: &Path) -> Result<Vec<u8>, String> {
+    let output = Command::new("sh")
+        .arg("-c")
+        .arg(format!("{} \"$@\"", command))
+        .arg(command)
+        .arg(file)
+        .output()
+        .map_err(|e| format!("could not run '{}': {}", command, e))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let stderr = stderr.trim();
+        // Commands that fail silently only have their exit status.
+        return Err(if stderr.is_empty() {
+            format!("'{}' failed with {}", command, output.status)
+        } else {
+            format!("'{}' failed: {}", command, stderr)
+        });
+    }
+    Ok(output.stdout)
+}
+
+/// Convert `file`, the content of `side`, with the textconv filter
+/// for its path. Returns `Ok(None)` if the path doesn't have a
+/// filter. Converted blobs are cached in `cache_dir`, if set.
+pub(crate) fn convert(
+    side: &GitSide,
+    file: &Path,
+    cache_dir: Option<&Path>,
+) -> Result<Option<Converted>, String> {
+    let Some((driver, command)) = textconv_command(&side.path) else {
+        return Ok(None);
+    };
+
+    let cache_path = cache_dir
+        .filter(|_| is_cacheable(&side.hash))
+        .map(|cache_dir| cache_path(cache_dir, &command, &side.hash));
+    if let Some(bytes) = cache_path.as_ref().and_then(|path| fs::read(path).ok()) {
+        return Ok(Some(Converted { driver, bytes }));
+    }
+
+    let bytes = run_textconv(&command, file)?;
+    if let Some(cache_path) = cache_path {
+        // Caching is best-effort.
+        if let Some(dir) = cache_path.parent() {
+            let _ = fs::create_dir_all(dir);
+        }
+        let _ = fs::write(&cache_path, &bytes);
+    }
+    Ok(Some(Converted { driver, bytes }))
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_diff_attr() {
+        assert_eq!(
+            parse_diff_attr("notes.ipynb\0diff\0jupyter\0"),
+            Some("jupyter".to_owned())
+        );
+        assert_eq!(parse_diff_attr("main.rs\0diff\0unspecified\0"), None);
+    }
+
+    #[test]
+    fn test_is_cacheable() {
+        assert!(is_cacheable("1a2b3c"));
+        assert!(!is_cacheable("0000000000000000000000000000000000000000"));
+        assert!(!is_cacheable("."));
+    }
+
+    #[test]
+    fn test_run_textconv_failure() {
+        assert_eq!(
+            run_textconv("exit 3 ||", Path::new("x")),
+            Err("'exit 3 ||' failed with exit status: 3".to_owned())
+        );
+        assert_eq!(
+            run_textconv("echo oops >&2; false", Path::new("x")),
+            Err("'echo oops >&2; false' failed: oops".to_owned())
+        );
+    }
+}
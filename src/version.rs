@@ -3,6 +3,7 @@ use std::fmt;
 use lazy_static::lazy_static;
 
 pub(crate) struct CommitInfo {
+    pub(crate) commit_hash: &'static str,
     pub(crate) short_commit_hash: &'static str,
     pub(crate) commit_date: &'static str,
 }
@@ -43,10 +44,12 @@ lazy_static! {
 pub(crate) const fn version() -> VersionInfo {
     let version = env!("CARGO_PKG_VERSION");
     let commit_info = match (
+        option_env!("DFT_COMMIT_HASH"),
         option_env!("DFT_COMMIT_SHORT_HASH"),
         option_env!("DFT_COMMIT_DATE"),
     ) {
-        (Some(short_commit_hash), Some(commit_date)) => Some(CommitInfo {
+        (Some(commit_hash), Some(short_commit_hash), Some(commit_date)) => Some(CommitInfo {
+            commit_hash,
             short_commit_hash,
             commit_date,
         }),
@@ -61,3 +64,99 @@ pub(crate) const fn version() -> VersionInfo {
         rustc_version,
     }
 }
+
+/// The version of a tree-sitter grammar in vendored_parsers/.
+pub(crate) struct GrammarVersion {
+    pub(crate) name: &'static str,
+    /// The version in the grammar's package.json.
+    pub(crate) version: Option<&'static str>,
+    /// The upstream commit that the git subtree was last updated to.
+    pub(crate) commit: Option<&'static str>,
+}
+
+// Generated by build.rs.
+include!(concat!(env!("OUT_DIR"), "/grammar_versions.rs"));
+
+fn format_build_info(version: &VersionInfo, grammars: &[GrammarVersion]) -> String {
+    let mut res = format!("Difftastic {}\n", version.version);
+    if let Some(commit_info) = &version.commit_info {
+        res.push_str(&format!(
+            "Commit: {} ({})\n",
+            commit_info.commit_hash, commit_info.commit_date
+        ));
+    }
+    if let Some(rustc_version) = version.rustc_version {
+        res.push_str(&format!("Built with rustc {}\n", rustc_version));
+    }
+
+    let name_width = grammars
+        .iter()
+        .map(|grammar| grammar.name.len())
+        .chain(std::iter::once("grammar".len()))
+        .max()
+        .unwrap_or_default();
+    res.push_str(&format!(
+        "\n{:name_width$} {:10} commit\n",
+        "grammar",
+        "version",
+        name_width = name_width
+    ));
+    for grammar in grammars {
+        res.push_str(&format!(
+            "{:name_width$} {:10} {}\n",
+            grammar.name,
+            grammar.version.unwrap_or("-"),
+            grammar.commit.unwrap_or("-"),
+            name_width = name_width
+        ));
+    }
+    res
+}
+
+/// The version, the commit difftastic was built from and the version
+/// of each bundled grammar, for `--version --verbose`.
+pub(crate) fn build_info() -> String {
+    format_build_info(&version(), GRAMMAR_VERSIONS)
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_format_build_info() {
+        let version = VersionInfo {
+            version: "0.59.0",
+            commit_info: Some(CommitInfo {
+                commit_hash: "0123456789abcdef",
+                short_commit_hash: "0123456",
+                commit_date: "2024-05-11",
+            }),
+            rustc_version: None,
+        };
+        let grammars = [
+            GrammarVersion {
+                name: "tree-sitter-ada",
+                version: Some("0.1.0"),
+                commit: Some("ba0894e"),
+            },
+            GrammarVersion {
+                name: "tree-sitter-c",
+                version: None,
+                commit: None,
+            },
+        ];
+
+        assert_eq!(
+            format_build_info(&version, &grammars),
+            "Difftastic 0.59.0\n\
+             Commit: 0123456789abcdef (2024-05-11)\n\
+             \n\
+             grammar         version    commit\n\
+             tree-sitter-ada 0.1.0      ba0894e\n\
+             tree-sitter-c   -          -\n"
+        );
+    }
+}
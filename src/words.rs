@@ -1,12 +1,47 @@
+use regex::Regex;
+
+/// Split `s` into the matches of `regex` and the individual
+/// characters between them. Empty matches are ignored, and each line
+/// is matched separately so no word contains a newline.
+fn split_regex_words<'a>(s: &'a str, regex: &Regex) -> Vec<&'a str> {
+    fn push_chars<'a>(s: &'a str, words: &mut Vec<&'a str>) {
+        for (idx, c) in s.char_indices() {
+            words.push(&s[idx..idx + c.len_utf8()]);
+        }
+    }
+
+    let mut words = vec![];
+    for line in s.split_inclusive('\n') {
+        let content = line.strip_suffix('\n').unwrap_or(line);
+        let mut offset = 0;
+        for m in regex.find_iter(content) {
+            if m.start() == m.end() {
+                continue;
+            }
+            push_chars(&content[offset..m.start()], &mut words);
+            words.push(m.as_str());
+            offset = m.end();
+        }
+        push_chars(&line[offset..], &mut words);
+    }
+    words
+}
+
 /// Split `s` into a vec of things that look like words and individual
 /// non-word characters.
 ///
 /// "foo..bar23" -> vec!["foo", ".", ".", "bar23"]
 ///
+/// If `word_regex` is set, it's used instead, see `--word-regex`.
+///
 /// See also `split_words_and_numbers`. Both these functions are hot,
 /// so they are separate implementations rather than passing a bool to
 /// customise number handling.
-pub(crate) fn split_words(s: &str) -> Vec<&str> {
+pub(crate) fn split_words<'a>(s: &'a str, word_regex: Option<&Regex>) -> Vec<&'a str> {
+    if let Some(regex) = word_regex {
+        return split_regex_words(s, regex);
+    }
+
     let mut words = vec![];
     let mut word_start: Option<usize> = None;
     for (idx, c) in s.char_indices() {
@@ -41,7 +76,13 @@ pub(crate) fn split_words(s: &str) -> Vec<&str> {
 /// non-word characters.
 ///
 /// "foo..bar23" -> vec!["foo", ".", ".", "bar23"]
-pub(crate) fn split_words_and_numbers(s: &str) -> Vec<&str> {
+///
+/// If `word_regex` is set, it's used instead, see `--word-regex`.
+pub(crate) fn split_words_and_numbers<'a>(s: &'a str, word_regex: Option<&Regex>) -> Vec<&'a str> {
+    if let Some(regex) = word_regex {
+        return split_regex_words(s, regex);
+    }
+
     let mut words = vec![];
     let mut word_start: Option<(usize, char)> = None;
     for (idx, c) in s.char_indices() {
@@ -80,6 +121,54 @@ pub(crate) fn split_words_and_numbers(s: &str) -> Vec<&str> {
     words
 }
 
+/// Words that are often followed by a full stop in the middle of a
+/// sentence.
+const ABBREVIATIONS: &[&str] = &[
+    "al", "cf", "Co", "Dr", "etc", "Fig", "Inc", "Jr", "Ltd", "Mr", "Mrs", "Ms", "No", "Prof",
+    "Sr", "St", "vs",
+];
+
+/// Whether a sentence ends after `words[i]`, where `words` are the
+/// words of a paragraph from [split_words] without the whitespace.
+///
+/// This is conservative: a sentence only ends at `.`, `!` or `?`,
+/// and any closing quotes or brackets after it, followed by a word
+/// starting with an uppercase letter. A full stop after a single
+/// letter or a common abbreviation, as in "e.g. Rust" or "Dr. Who",
+/// doesn't end a sentence.
+pub(crate) fn is_sentence_end(words: &[&str], i: usize) -> bool {
+    let is_terminator = |word: &str| matches!(word, "." | "!" | "?");
+    let is_closing = |word: &str| matches!(word, "\"" | "'" | ")" | "]" | "*" | "`" | "”" | "’");
+
+    let Some(next) = words.get(i + 1) else {
+        return true;
+    };
+    if is_terminator(next) || is_closing(next) {
+        return false;
+    }
+    if !next.chars().next().map_or(false, char::is_uppercase) {
+        return false;
+    }
+
+    let mut end = i;
+    while is_closing(words[end]) {
+        if end == 0 {
+            return false;
+        }
+        end -= 1;
+    }
+    if !is_terminator(words[end]) {
+        return false;
+    }
+    if words[end] == "." && end > 0 {
+        let previous = words[end - 1];
+        if previous.chars().count() == 1 || ABBREVIATIONS.contains(&previous) {
+            return false;
+        }
+    }
+    true
+}
+
 #[cfg(test)]
 mod tests {
     use pretty_assertions::assert_eq;
@@ -89,63 +178,106 @@ mod tests {
     #[test]
     fn test_split_words() {
         let s = "example.com";
-        let res = split_words(s);
+        let res = split_words(s, None);
         assert_eq!(res, vec!["example", ".", "com"])
     }
 
     #[test]
     fn test_split_words_punctuation() {
         let s = "example..";
-        let res = split_words(s);
+        let res = split_words(s, None);
         assert_eq!(res, vec!["example", ".", "."])
     }
 
     #[test]
     fn test_split_words_numbers() {
         let s = "foo123bar";
-        let res = split_words(s);
+        let res = split_words(s, None);
         assert_eq!(res, vec!["foo123bar"])
     }
 
     #[test]
     fn test_split_words_treats_newline_separately() {
         let s = "example.\ncom";
-        let res = split_words(s);
+        let res = split_words(s, None);
         assert_eq!(res, vec!["example", ".", "\n", "com"])
     }
 
     #[test]
     fn test_split_words_single_unicode() {
         let s = "a ö b";
-        let res = split_words(s);
+        let res = split_words(s, None);
         assert_eq!(res, vec!["a", " ", "ö", " ", "b"])
     }
 
     #[test]
     fn test_split_words_single_unicode_not_alphabetic() {
         let s = "a 💝 b";
-        let res = split_words(s);
+        let res = split_words(s, None);
         assert_eq!(res, vec!["a", " ", "💝", " ", "b"])
     }
 
     #[test]
     fn test_split_words_unicode() {
         let s = "a xöy b";
-        let res = split_words(s);
+        let res = split_words(s, None);
         assert_eq!(res, vec!["a", " ", "xöy", " ", "b"])
     }
 
+    #[test]
+    fn test_split_regex_words() {
+        let regex = Regex::new("[^,]+").unwrap();
+        assert_eq!(
+            split_regex_words("a b,c\nd", &regex),
+            vec!["a b", ",", "c", "\n", "d"]
+        );
+
+        let regex = Regex::new(r"[\w']+").unwrap();
+        assert_eq!(
+            split_regex_words("don't stop", &regex),
+            vec!["don't", " ", "stop"]
+        );
+    }
+
+    #[test]
+    fn test_split_regex_words_empty_matches() {
+        let regex = Regex::new("x*").unwrap();
+        assert_eq!(split_regex_words("axxb", &regex), vec!["a", "xx", "b"]);
+    }
+
+    #[test]
+    fn test_is_sentence_end() {
+        let sentence_ends = |s: &str| -> Vec<usize> {
+            let words: Vec<&str> = split_words(s, None)
+                .into_iter()
+                .filter(|word| !word.trim().is_empty())
+                .collect();
+            (0..words.len())
+                .filter(|i| is_sentence_end(&words, *i))
+                .collect()
+        };
+
+        // "Hi" "." "Bye" "!"
+        assert_eq!(sentence_ends("Hi. Bye!"), vec![1, 3]);
+        // Closing quotes belong to the sentence before them.
+        assert_eq!(sentence_ends("He said \"hi.\" Then left."), vec![5, 8]);
+        // Not followed by an uppercase letter.
+        assert_eq!(sentence_ends("See foo.rs for details."), vec![6]);
+        // Abbreviations and initials.
+        assert_eq!(sentence_ends("Ask Dr. Smith, e.g. Today."), vec![10]);
+    }
+
     #[test]
     fn test_split_words_and_numbers() {
         let s = "a123b";
-        let res = split_words_and_numbers(s);
+        let res = split_words_and_numbers(s, None);
         assert_eq!(res, vec!["a", "123", "b"])
     }
 
     #[test]
     fn test_split_words_and_numbers_spaces() {
         let s = "foo bar";
-        let res = split_words_and_numbers(s);
+        let res = split_words_and_numbers(s, None);
         assert_eq!(res, vec!["foo", " ", "bar"])
     }
 }
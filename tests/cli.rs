@@ -28,6 +28,10 @@ fn get_base_command() -> Command {
     } else {
         cmd = Command::new(path);
     }
+
+    // Don't use the config file of the person running the tests.
+    cmd.env("XDG_CONFIG_HOME", "sample_files/cli_tests")
+        .env_remove("DFT_CONFIG");
     cmd
 }
 
@@ -61,6 +65,205 @@ fn binary_changed() {
     cmd.assert().stdout(predicate_fn);
 }
 
+#[test]
+fn binary_changed_sizes() {
+    let mut cmd = get_base_command();
+
+    cmd.arg("--color=never")
+        .arg("img/logo.png")
+        .arg("/dev/null");
+
+    let predicate_fn = predicate::str::contains("Binary contents changed, 9.")
+        .and(predicate::str::contains("KiB (hash "));
+    cmd.assert().stdout(predicate_fn);
+}
+
+#[test]
+fn binary_changed_json() {
+    let mut cmd = get_base_command();
+
+    cmd.env("DFT_UNSTABLE", "yes")
+        .arg("--display=json")
+        .arg("img/logo.png")
+        .arg("/dev/null");
+
+    let predicate_fn = predicate::str::contains("\"old_size\":9506")
+        .and(predicate::str::contains("\"new_size\":0"))
+        .and(predicate::str::contains("\"old_hash\":"));
+    cmd.assert().stdout(predicate_fn);
+}
+
+#[test]
+fn binary_identical_exit_code() {
+    let mut cmd = get_base_command();
+
+    cmd.arg("--exit-code")
+        .arg("img/logo.png")
+        .arg("img/logo.png");
+    cmd.assert().success();
+}
+
+#[test]
+fn binary_changed_exit_code() {
+    let mut cmd = get_base_command();
+
+    cmd.arg("--exit-code").arg("img/logo.png").arg("/dev/null");
+    cmd.assert().failure().code(1);
+
+    let mut cmd = get_base_command();
+
+    cmd.arg("--exit-code")
+        .arg("--skip-binary")
+        .arg("img/logo.png")
+        .arg("/dev/null");
+    cmd.assert().success().stdout("");
+}
+
+#[test]
+fn directory_binary_and_text() {
+    let tmp_dir = std::env::temp_dir().join(format!("difft-binary.{}", std::process::id()));
+    let lhs_dir = tmp_dir.join("left");
+    let rhs_dir = tmp_dir.join("right");
+    std::fs::create_dir_all(&lhs_dir).unwrap();
+    std::fs::create_dir_all(&rhs_dir).unwrap();
+    std::fs::write(lhs_dir.join("notes.txt"), "foo\n").unwrap();
+    std::fs::write(rhs_dir.join("notes.txt"), "bar\n").unwrap();
+    // Short byte strings like b"\x00\x01" are valid UTF-8, so use a
+    // real image.
+    let png = std::fs::read("img/logo.png").unwrap();
+    let mut changed_png = png.clone();
+    changed_png.push(0);
+    std::fs::write(lhs_dir.join("changed.png"), &png).unwrap();
+    std::fs::write(rhs_dir.join("changed.png"), &changed_png).unwrap();
+    std::fs::write(lhs_dir.join("same.png"), &png).unwrap();
+    std::fs::write(rhs_dir.join("same.png"), &png).unwrap();
+
+    let mut cmd = get_base_command();
+    cmd.arg("--color=never").arg(&lhs_dir).arg(&rhs_dir);
+    let output = cmd.output().unwrap();
+
+    let mut cmd = get_base_command();
+    cmd.env("DFT_UNSTABLE", "yes")
+        .arg("--display=json")
+        .arg(&lhs_dir)
+        .arg(&rhs_dir);
+    let json_output = cmd.output().unwrap();
+
+    let mut cmd = get_base_command();
+    cmd.arg("--color=never")
+        .arg("--skip-binary")
+        .arg("--exit-code")
+        .arg(&lhs_dir)
+        .arg(&rhs_dir);
+    let skipped_output = cmd.output().unwrap();
+    std::fs::remove_dir_all(&tmp_dir).unwrap();
+
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("Compared 3 files: 1 changed, 1 unchanged, 1 binary changed"));
+
+    let stdout = String::from_utf8(json_output.stdout).unwrap();
+    assert!(stdout.contains("\"status\":\"binary_changed\""));
+
+    // The text file still changed.
+    assert_eq!(skipped_output.status.code(), Some(1));
+    let stdout = String::from_utf8(skipped_output.stdout).unwrap();
+    assert!(stdout.contains("notes.txt"));
+    assert!(!stdout.contains(".png"));
+    let stderr = String::from_utf8(skipped_output.stderr).unwrap();
+    assert!(stderr.contains("Compared 3 files: 1 changed, 0 unchanged, 2 binary skipped"));
+}
+
+#[test]
+fn byte_limit_truncate() {
+    let mut cmd = get_base_command();
+
+    cmd.arg("--color=never")
+        .arg("--byte-limit=simple_2.js=40")
+        .arg("--byte-limit-action=truncate")
+        .arg("sample_files/simple_1.js")
+        .arg("sample_files/simple_2.js");
+
+    let predicate_fn = predicate::str::contains(
+        "Truncated to DFT_BYTE_LIMIT (40 B), omitting 28 B on the left and 28 B on the right.",
+    )
+    .and(predicate::str::contains("useState"))
+    .and(predicate::str::contains("hello world").not());
+    cmd.assert().stdout(predicate_fn);
+}
+
+#[test]
+fn byte_limit_error() {
+    let mut cmd = get_base_command();
+
+    cmd.arg("--byte-limit=40")
+        .arg("--byte-limit-action=error")
+        .arg("sample_files/simple_1.js")
+        .arg("sample_files/simple_2.js");
+
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("exceeded DFT_BYTE_LIMIT"));
+}
+
+#[test]
+fn byte_limit_error_cached() {
+    let cache_dir = std::env::temp_dir().join(format!("difft-cache.{}", std::process::id()));
+
+    let mut cmd = get_base_command();
+    cmd.arg("--byte-limit=40")
+        .arg("--cache-dir")
+        .arg(&cache_dir)
+        .arg("sample_files/simple_1.js")
+        .arg("sample_files/simple_2.js");
+    let output = cmd.output().unwrap();
+    assert_eq!(output.status.code(), Some(0));
+
+    let mut cmd = get_base_command();
+    cmd.arg("--byte-limit=40")
+        .arg("--byte-limit-action=error")
+        .arg("--cache-dir")
+        .arg(&cache_dir)
+        .arg("sample_files/simple_1.js")
+        .arg("sample_files/simple_2.js");
+    let output = cmd.output().unwrap();
+    std::fs::remove_dir_all(&cache_dir).unwrap();
+
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("exceeded DFT_BYTE_LIMIT"));
+}
+
+#[test]
+fn compressed_file() {
+    let mut cmd = get_base_command();
+
+    cmd.arg("--color=never")
+        .arg("sample_files/cli_tests/config_1.json.gz")
+        .arg("sample_files/cli_tests/config_2.json");
+
+    let predicate_fn = predicate::str::contains("JSON")
+        .and(predicate::str::contains(
+            "Decompressed the left file from gzip.",
+        ))
+        .and(predicate::str::contains("8080"))
+        .and(predicate::str::contains("Binary").not());
+    cmd.assert().stdout(predicate_fn);
+}
+
+#[test]
+fn binary_as_text() {
+    let mut cmd = get_base_command();
+
+    cmd.arg("--color=never")
+        .arg("--text")
+        .arg("img/logo.png")
+        .arg("/dev/null");
+
+    let predicate_fn = predicate::str::contains("Binary").not();
+    cmd.assert().stdout(predicate_fn);
+}
+
 #[test]
 fn has_changes_default_exit_code() {
     let mut cmd = get_base_command();
@@ -80,6 +283,244 @@ fn has_changes_requested_exit_code() {
     cmd.assert().failure().code(1);
 }
 
+#[test]
+fn fail_on_fallback_text() {
+    let mut cmd = get_base_command();
+
+    cmd.arg("--fail-on-fallback")
+        .arg("--byte-limit=40")
+        .arg("sample_files/simple_1.js")
+        .arg("sample_files/simple_2.js");
+    cmd.assert().failure().code(3);
+}
+
+#[test]
+fn fail_on_fallback_binary() {
+    let mut cmd = get_base_command();
+
+    cmd.arg("--fail-on-fallback")
+        .arg("--exit-code")
+        .arg("img/logo.png")
+        .arg("/dev/null");
+    cmd.assert().failure().code(4);
+}
+
+#[test]
+fn fail_on_fallback_structural() {
+    let mut cmd = get_base_command();
+
+    cmd.arg("--fail-on-fallback")
+        .arg("sample_files/simple_1.js")
+        .arg("sample_files/simple_2.js");
+    cmd.assert().success();
+}
+
+#[cfg(unix)]
+#[test]
+fn pager_always() {
+    let mut cmd = get_base_command();
+
+    cmd.env("DFT_PAGER", "sed 's/^/paged: /'")
+        .arg("--pager=always")
+        .arg("sample_files/simple_1.js")
+        .arg("sample_files/simple_2.js");
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::starts_with("paged: "));
+}
+
+#[test]
+fn output_file() {
+    let output_path = std::env::temp_dir().join("difftastic_cli_test_output_file.txt");
+    let mut cmd = get_base_command();
+
+    cmd.arg("--output-file")
+        .arg(&output_path)
+        .arg("sample_files/simple_1.js")
+        .arg("sample_files/simple_2.js");
+    cmd.assert().success().stdout("");
+
+    let output = std::fs::read_to_string(&output_path).unwrap();
+    std::fs::remove_file(&output_path).unwrap();
+    assert!(output.contains("simple_2.js"));
+    // Color is off by default when writing to a file.
+    assert!(!output.contains('\x1b'));
+}
+
+#[test]
+fn argument_file() {
+    let mut cmd = get_base_command();
+
+    cmd.arg("--color=never")
+        .arg("--exit-code")
+        .arg("@sample_files/cli_tests/pairs.txt");
+    cmd.assert()
+        .failure()
+        .code(1)
+        .stdout(predicate::str::contains("simple_2.js"))
+        .stdout(predicate::str::contains("clojure.clj"));
+}
+
+#[test]
+fn argument_file_invalid_line() {
+    let mut cmd = get_base_command();
+
+    cmd.arg("@sample_files/cli_tests/pairs_invalid.txt");
+    cmd.assert()
+        .failure()
+        .code(2)
+        .stderr(predicate::str::contains("line 2:"));
+}
+
+#[test]
+fn function_context_patch_unsupported() {
+    let mut cmd = get_base_command();
+
+    cmd.arg("--context=function")
+        .arg("--display=patch")
+        .arg("sample_files/simple_1.js")
+        .arg("sample_files/simple_2.js");
+    cmd.assert()
+        .failure()
+        .code(2)
+        .stderr(predicate::str::contains("--context=function"));
+}
+
+#[test]
+fn interactive_not_a_tty() {
+    let mut cmd = get_base_command();
+
+    // Tests don't run in a terminal, so this prints the diff as
+    // usual.
+    cmd.arg("--interactive")
+        .arg("sample_files/simple_1.js")
+        .arg("sample_files/simple_2.js");
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("simple_2.js"));
+}
+
+#[test]
+fn strip_prefix() {
+    let mut cmd = get_base_command();
+
+    cmd.arg("--strip-prefix")
+        .arg("sample_files/simple_1.js")
+        .arg("sample_files/simple_2.js");
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("simple_2.js"))
+        .stdout(predicate::str::contains("sample_files/").not());
+}
+
+#[test]
+fn prefix_right() {
+    let mut cmd = get_base_command();
+
+    cmd.arg("--prefix-right=sample_files")
+        .arg("sample_files/simple_1.js")
+        .arg("sample_files/simple_2.js");
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("sample_files/").not());
+}
+
+#[test]
+fn version_single_line() {
+    let mut cmd = get_base_command();
+
+    cmd.arg("--version");
+    let output = cmd.output().unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success());
+    assert!(stdout.starts_with("Difftastic "));
+    assert_eq!(stdout.lines().count(), 1);
+}
+
+#[test]
+fn version_verbose() {
+    let mut cmd = get_base_command();
+
+    cmd.arg("--version").arg("--verbose");
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("tree-sitter-rust"));
+}
+
+#[test]
+fn verbose_language() {
+    let mut cmd = get_base_command();
+
+    cmd.arg("--verbose")
+        .arg("sample_files/simple_1.js")
+        .arg("sample_files/simple_2.js");
+    cmd.assert()
+        .stderr(predicate::str::contains("Detected JavaScript"))
+        .stderr(predicate::str::contains("peak RSS").not());
+}
+
+#[test]
+fn verbose_json_log_format() {
+    let mut cmd = get_base_command();
+
+    cmd.arg("--verbose")
+        .arg("--verbose")
+        .arg("--log-format=json")
+        .arg("sample_files/simple_1.js")
+        .arg("sample_files/simple_2.js");
+    cmd.assert()
+        .stderr(predicate::str::contains(r#""event":"language""#))
+        .stderr(predicate::str::contains(r#""event":"usage""#));
+}
+
+#[test]
+fn quiet_has_changes() {
+    let mut cmd = get_base_command();
+
+    cmd.arg("--quiet")
+        .arg("sample_files/simple_1.js")
+        .arg("sample_files/simple_2.js");
+    cmd.assert().failure().code(1).stdout("");
+}
+
+#[test]
+fn quiet_no_changes() {
+    let mut cmd = get_base_command();
+
+    cmd.arg("-q")
+        .arg("sample_files/simple_1.js")
+        .arg("sample_files/simple_1.js");
+    cmd.assert().success().stdout("");
+}
+
+#[test]
+fn quiet_directories() {
+    let mut cmd = get_base_command();
+
+    cmd.arg("--quiet")
+        .arg("sample_files/dir_1")
+        .arg("sample_files/dir_2");
+    cmd.assert().failure().code(1).stdout("");
+}
+
+#[test]
+fn quiet_binary_changes() {
+    let mut cmd = get_base_command();
+
+    cmd.arg("--quiet").arg("img/logo.png").arg("/dev/null");
+    cmd.assert().failure().code(1).stdout("");
+}
+
+#[test]
+fn quiet_missing_file() {
+    let mut cmd = get_base_command();
+
+    cmd.arg("--quiet")
+        .arg("sample_files/simple_1.js")
+        .arg("sample_files/does_not_exist.js");
+    cmd.assert().failure().code(2);
+}
+
 #[test]
 fn ignore_comments() {
     let mut cmd = get_base_command();
@@ -151,6 +592,52 @@ fn list_languages() {
     cmd.assert().stdout(predicate_fn);
 }
 
+#[test]
+fn guess_language() {
+    let mut cmd = get_base_command();
+    cmd.arg("--guess-language").arg("sample_files/simple_1.js");
+    cmd.assert().success().stdout("JavaScript\n");
+
+    let mut cmd = get_base_command();
+    cmd.arg("--override=*.js:text")
+        .arg("--guess-language")
+        .arg("sample_files/simple_1.js");
+    cmd.assert().success().stdout("Text\n");
+}
+
+#[test]
+fn list_languages_json() {
+    let mut cmd = get_base_command();
+
+    cmd.arg("--list-languages").arg("--display=json");
+
+    let output = cmd.assert().success().get_output().stdout.clone();
+    let languages: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    let languages = languages.as_array().unwrap();
+
+    let names: Vec<&str> = languages
+        .iter()
+        .map(|language| language["name"].as_str().unwrap())
+        .collect();
+    let mut sorted_names = names.clone();
+    sorted_names.sort();
+    assert_eq!(names, sorted_names);
+
+    let make = languages
+        .iter()
+        .find(|language| language["name"] == "Make")
+        .unwrap();
+    assert_eq!(
+        make["extensions"],
+        serde_json::json!(["d", "mak", "make", "makefile", "mk", "mkfile"])
+    );
+    assert!(make["filenames"]
+        .as_array()
+        .unwrap()
+        .contains(&serde_json::json!("Makefile")));
+    assert_eq!(make["highlighting"], true);
+}
+
 #[test]
 fn test_mime_type_false_positive() {
     let mut cmd = get_base_command();
@@ -185,27 +672,1044 @@ fn directory_arguments() {
 }
 
 #[test]
-fn git_style_arguments_rename() {
+fn directory_run_summary() {
     let mut cmd = get_base_command();
 
-    cmd.arg("elisp_oldname.el")
-        .arg("sample_files/elisp_1.el")
-        .arg("lhs_hash_placeholder")
-        .arg("lhs_mode_placeholder")
-        .arg("sample_files/elisp_2.el")
-        .arg("rhs_hash_placeholder")
-        .arg("rhs_mode_placeholder")
-        .arg("elisp_newname.el")
-        .arg("similarity_placeholder");
-    let predicate_fn = predicate::str::contains("Renamed");
-    cmd.assert().stdout(predicate_fn);
+    cmd.arg("sample_files/dir_1").arg("sample_files/dir_2");
+    cmd.assert()
+        .stderr(predicate::str::contains("Compared 5 files: "));
+
+    let mut cmd = get_base_command();
+
+    cmd.arg("--no-summary")
+        .arg("sample_files/dir_1")
+        .arg("sample_files/dir_2");
+    cmd.assert()
+        .stderr(predicate::str::contains("Compared").not());
 }
 
 #[test]
-fn git_style_arguments_new_file() {
+fn directory_degraded_recap() {
     let mut cmd = get_base_command();
 
-    cmd.arg("simple.txt")
+    cmd.arg("--byte-limit=40")
+        .arg("sample_files/dir_1")
+        .arg("sample_files/dir_2");
+    let predicate_fn = predicate::str::contains("exceeded DFT_BYTE_LIMIT and used a text diff: ")
+        .and(predicate::str::contains("Use a larger --byte-limit"));
+    cmd.assert().stderr(predicate_fn);
+}
+
+#[test]
+fn directory_check() {
+    let mut cmd = get_base_command();
+
+    cmd.arg("--check")
+        .arg("--no-summary")
+        .arg("sample_files/dir_1")
+        .arg("sample_files/dir_2");
+    let predicate_fn = predicate::str::contains("has_many_hunk.py\n")
+        .and(predicate::str::contains("only_in_2.rs\n"))
+        .and(predicate::str::contains("│").not());
+    cmd.assert().failure().code(1).stdout(predicate_fn);
+}
+
+#[test]
+fn directory_hunk() {
+    let mut cmd = get_base_command();
+
+    cmd.arg("--color=never")
+        .arg("--hunk=2")
+        .arg("sample_files/dir_1")
+        .arg("sample_files/dir_2");
+    let predicate_fn = predicate::str::contains("has_many_hunk.py --- 2/10 ")
+        .and(predicate::str::contains("has_many_hunk.py --- 1/10 ").not())
+        .and(predicate::str::contains("foo.js").not());
+    cmd.assert().success().stdout(predicate_fn);
+}
+
+#[test]
+fn directory_hunk_out_of_range() {
+    let mut cmd = get_base_command();
+
+    cmd.arg("--hunk=11")
+        .arg("sample_files/dir_1")
+        .arg("sample_files/dir_2");
+    cmd.assert()
+        .failure()
+        .code(2)
+        .stderr(predicate::str::contains("No file has the hunks"));
+}
+
+#[test]
+fn hunk_out_of_range() {
+    let mut cmd = get_base_command();
+
+    cmd.arg("--hunk=2")
+        .arg("sample_files/dir_1/foo.js")
+        .arg("sample_files/dir_2/foo.js");
+    cmd.assert()
+        .failure()
+        .code(2)
+        .stderr(predicate::str::contains("foo.js has 1 hunk."));
+}
+
+#[test]
+fn check_unchanged() {
+    let mut cmd = get_base_command();
+
+    cmd.arg("--check")
+        .arg("sample_files/simple_1.js")
+        .arg("sample_files/simple_1.js");
+    cmd.assert().success().stdout("");
+}
+
+#[test]
+fn check_ignore_blank_lines() {
+    let tmp_dir = std::env::temp_dir().join(format!("difft-check.{}", std::process::id()));
+    std::fs::create_dir_all(&tmp_dir).unwrap();
+    std::fs::write(tmp_dir.join("old.txt"), "foo\nbar\n").unwrap();
+    std::fs::write(tmp_dir.join("new.txt"), "foo\n\nbar\n").unwrap();
+
+    let mut cmd = get_base_command();
+    cmd.arg("--check")
+        .arg("--ignore-blank-lines")
+        .arg(tmp_dir.join("old.txt"))
+        .arg(tmp_dir.join("new.txt"));
+    let output = cmd.output().unwrap();
+    std::fs::remove_dir_all(&tmp_dir).unwrap();
+
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), "");
+}
+
+#[test]
+fn prose_wrap_insensitive() {
+    let tmp_dir = std::env::temp_dir().join(format!("difft-prose.{}", std::process::id()));
+    std::fs::create_dir_all(&tmp_dir).unwrap();
+    std::fs::write(
+        tmp_dir.join("old.txt"),
+        "The quick brown fox\njumps over the lazy dog.\n",
+    )
+    .unwrap();
+    std::fs::write(
+        tmp_dir.join("new.txt"),
+        "The quick brown\nfox jumps over\nthe lazy dog.\n",
+    )
+    .unwrap();
+
+    let mut cmd = get_base_command();
+    cmd.arg("--exit-code")
+        .arg("--prose-wrap-insensitive")
+        .arg(tmp_dir.join("old.txt"))
+        .arg(tmp_dir.join("new.txt"));
+    let prose_output = cmd.output().unwrap();
+
+    let mut cmd = get_base_command();
+    cmd.arg("--exit-code")
+        .arg("--prose-granularity=sentence")
+        .arg(tmp_dir.join("old.txt"))
+        .arg(tmp_dir.join("new.txt"));
+    let sentence_output = cmd.output().unwrap();
+
+    let mut cmd = get_base_command();
+    cmd.arg("--exit-code")
+        .arg(tmp_dir.join("old.txt"))
+        .arg(tmp_dir.join("new.txt"));
+    let output = cmd.output().unwrap();
+    std::fs::remove_dir_all(&tmp_dir).unwrap();
+
+    assert_eq!(prose_output.status.code(), Some(0));
+    assert_eq!(sentence_output.status.code(), Some(0));
+    assert_eq!(output.status.code(), Some(1));
+}
+
+#[test]
+fn invalid_word_regex() {
+    let mut cmd = get_base_command();
+
+    cmd.arg("--word-regex=(")
+        .arg("sample_files/simple_1.js")
+        .arg("sample_files/simple_2.js");
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("--word-regex"));
+}
+
+#[test]
+fn broken_pipe() {
+    use std::io::Read;
+    use std::process::Stdio;
+
+    let tmp_dir = std::env::temp_dir().join(format!("difft-pipe.{}", std::process::id()));
+    std::fs::create_dir_all(&tmp_dir).unwrap();
+    // Enough output that it can't all fit in the pipe's buffer.
+    let lines = |suffix: &str| -> String {
+        (0..20_000)
+            .map(|i| format!("line {} {}\n", i, suffix))
+            .collect()
+    };
+    std::fs::write(tmp_dir.join("old.txt"), lines("old")).unwrap();
+    std::fs::write(tmp_dir.join("new.txt"), lines("new")).unwrap();
+
+    let mut cmd = get_base_command();
+    let mut child = cmd
+        .arg("--color=never")
+        .arg(tmp_dir.join("old.txt"))
+        .arg(tmp_dir.join("new.txt"))
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    let mut buf = [0; 100];
+    child.stdout.take().unwrap().read_exact(&mut buf).unwrap();
+    // The read end of the pipe is closed here.
+    let output = child.wait_with_output().unwrap();
+    std::fs::remove_dir_all(&tmp_dir).unwrap();
+
+    assert_eq!(String::from_utf8_lossy(&output.stderr), "");
+    // Either killed by SIGPIPE, or exited quietly.
+    assert!(matches!(output.status.code(), None | Some(141)));
+}
+
+#[test]
+fn reformat_json() {
+    let tmp_dir = std::env::temp_dir().join(format!("difft-reformat.{}", std::process::id()));
+    std::fs::create_dir_all(&tmp_dir).unwrap();
+    std::fs::write(tmp_dir.join("old.json"), r#"{"a":1,"b":[2,3]}"#).unwrap();
+    std::fs::write(tmp_dir.join("new.json"), r#"{"a":1,"b":[2,4]}"#).unwrap();
+
+    let mut cmd = get_base_command();
+    cmd.arg("--color=never")
+        .arg("--reformat-json=always")
+        .arg(tmp_dir.join("old.json"))
+        .arg(tmp_dir.join("new.json"));
+    let output = cmd.output().unwrap();
+    std::fs::remove_dir_all(&tmp_dir).unwrap();
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("Reformatted JSON"));
+    assert!(!stdout.contains(r#"{"a":1"#));
+}
+
+#[test]
+fn show_invisible() {
+    let tmp_dir = std::env::temp_dir().join(format!("difft-invisible.{}", std::process::id()));
+    std::fs::create_dir_all(&tmp_dir).unwrap();
+    std::fs::write(tmp_dir.join("old.txt"), "foo bar\n").unwrap();
+    std::fs::write(tmp_dir.join("new.txt"), "foo\u{a0}bar\n").unwrap();
+
+    let mut cmd = get_base_command();
+    cmd.arg("--color=never")
+        .arg(tmp_dir.join("old.txt"))
+        .arg(tmp_dir.join("new.txt"));
+    let output = cmd.output().unwrap();
+
+    let mut cmd = get_base_command();
+    cmd.arg("--color=never")
+        .arg("--show-invisible=never")
+        .arg(tmp_dir.join("old.txt"))
+        .arg(tmp_dir.join("new.txt"));
+    let never_output = cmd.output().unwrap();
+    std::fs::remove_dir_all(&tmp_dir).unwrap();
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("foo␣bar"));
+    assert!(stdout.contains("foo⍽bar"));
+    let stdout = String::from_utf8(never_output.stdout).unwrap();
+    assert!(!stdout.contains('⍽'));
+}
+
+#[test]
+fn directory_sort_paths() {
+    let file_order = |sort_order: &str| {
+        let mut cmd = get_base_command();
+        cmd.arg("--color=never")
+            .arg(format!("--sort-paths={}", sort_order))
+            .arg("sample_files/dir_1")
+            .arg("sample_files/dir_2");
+        let output = cmd.output().unwrap();
+        let stdout = String::from_utf8(output.stdout).unwrap();
+
+        let mut names = vec![
+            "clojure.clj",
+            "foo.js",
+            "has_many_hunk.py",
+            "only_in_1.c",
+            "only_in_2.rs",
+        ];
+        names.sort_by_key(|name| stdout.find(name));
+        names
+    };
+
+    assert_eq!(
+        file_order("path"),
+        vec![
+            "clojure.clj",
+            "foo.js",
+            "has_many_hunk.py",
+            "only_in_1.c",
+            "only_in_2.rs"
+        ]
+    );
+    assert_eq!(file_order("changes")[0], "has_many_hunk.py");
+    assert_eq!(file_order("size")[0], "has_many_hunk.py");
+}
+
+#[test]
+fn directory_new_file() {
+    let mut cmd = get_base_command();
+
+    cmd.arg("--color=never")
+        .arg("sample_files/dir_1")
+        .arg("sample_files/dir_2");
+
+    let predicate_fn = predicate::str::contains("only_in_1.c (deleted)")
+        .and(predicate::str::contains("Deleted file with 7 lines."))
+        .and(predicate::str::contains("only_in_2.rs (new file)"))
+        .and(predicate::str::contains("New file with 4 lines."))
+        .and(predicate::str::contains("Say hello").not());
+    cmd.assert().stdout(predicate_fn);
+
+    let mut cmd = get_base_command();
+
+    cmd.arg("--color=never")
+        .arg("--new-file")
+        .arg("sample_files/dir_1")
+        .arg("sample_files/dir_2");
+
+    let predicate_fn = predicate::str::contains("only_in_2.rs (new file)")
+        .and(predicate::str::contains("Say hello"))
+        .and(predicate::str::contains("New file with").not());
+    cmd.assert().stdout(predicate_fn);
+}
+
+#[test]
+fn archive_entries() {
+    let mut cmd = get_base_command();
+
+    cmd.arg("--color=never")
+        .arg("sample_files/cli_tests/bundle_1.zip")
+        .arg("sample_files/cli_tests/bundle_2.zip");
+
+    let predicate_fn = predicate::str::contains("bundle_2.zip!/conf/app.yaml")
+        .and(predicate::str::contains("8080"))
+        .and(predicate::str::contains("bundle_2.zip!/old.txt (deleted)"))
+        .and(predicate::str::contains("bundle_2.zip!/new.txt (new file)"))
+        .and(predicate::str::contains("Binary").not());
+    cmd.assert().stdout(predicate_fn);
+}
+
+#[test]
+fn archive_entries_different_compression() {
+    let mut cmd = get_base_command();
+
+    cmd.arg("--color=never")
+        .arg("sample_files/cli_tests/bundle_1.tar.gz")
+        .arg("sample_files/cli_tests/bundle_2.tar");
+
+    let predicate_fn = predicate::str::contains("bundle_2.tar!/conf/app.yaml")
+        .and(predicate::str::contains("8080"));
+    cmd.assert().stdout(predicate_fn);
+}
+
+#[test]
+fn no_archive() {
+    let mut cmd = get_base_command();
+
+    cmd.arg("--color=never")
+        .arg("--no-archive")
+        .arg("sample_files/cli_tests/bundle_1.zip")
+        .arg("sample_files/cli_tests/bundle_2.zip");
+
+    let predicate_fn = predicate::str::contains("Binary contents changed")
+        .and(predicate::str::contains("conf/app.yaml").not());
+    cmd.assert().stdout(predicate_fn);
+}
+
+#[test]
+fn no_archive_compressed_tarball() {
+    let mut cmd = get_base_command();
+
+    cmd.arg("--color=never")
+        .arg("--no-archive")
+        .arg("sample_files/cli_tests/bundle_1.tar.gz")
+        .arg("sample_files/cli_tests/bundle_2.tar");
+
+    let predicate_fn = predicate::str::contains("Binary contents changed")
+        .and(predicate::str::contains("conf/app.yaml").not());
+    cmd.assert().stdout(predicate_fn);
+}
+
+#[test]
+fn directory_identical_files() {
+    let tmp_dir = std::env::temp_dir().join(format!("difft-identical.{}", std::process::id()));
+    let lhs_dir = tmp_dir.join("left");
+    let rhs_dir = tmp_dir.join("right");
+    std::fs::create_dir_all(&lhs_dir).unwrap();
+    std::fs::create_dir_all(&rhs_dir).unwrap();
+    for dir in [&lhs_dir, &rhs_dir] {
+        std::fs::write(dir.join("same.rs"), "fn main() {}\n").unwrap();
+        std::fs::write(dir.join("same.txt"), "hello\n").unwrap();
+        // Binary content after a long run of text, so the start of
+        // the file looks like text.
+        let mut binary = vec![b'a'; 2000];
+        binary.extend((0..=255).cycle().take(1000).map(|b: u8| b ^ 0x80));
+        std::fs::write(dir.join("same.bin"), binary).unwrap();
+    }
+    std::fs::write(lhs_dir.join("changed.rs"), "fn foo() {}\n").unwrap();
+    std::fs::write(rhs_dir.join("changed.rs"), "fn bar() {}\n").unwrap();
+
+    let mut cmd = get_base_command();
+    cmd.arg("--color=never")
+        .arg("--verbose")
+        .arg(&lhs_dir)
+        .arg(&rhs_dir);
+    let output = cmd.output().unwrap();
+    std::fs::remove_dir_all(&tmp_dir).unwrap();
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stdout.contains("same.rs --- Rust"));
+    assert!(stdout.contains("same.txt --- Text"));
+    assert!(stdout.contains("same.bin --- Binary"));
+    assert!(stdout.contains("bar"));
+    assert!(stderr.contains("3 files had identical content and skipped diffing"));
+}
+
+#[test]
+fn directory_include_exclude() {
+    let mut cmd = get_base_command();
+
+    cmd.arg("--color=never")
+        .arg("--include=*.c")
+        .arg("--include=*.rs")
+        .arg("--exclude=only_in_2.*")
+        .arg("sample_files/dir_1")
+        .arg("sample_files/dir_2");
+
+    let stdout_predicate = predicate::str::contains("only_in_1.c")
+        .and(predicate::str::contains("only_in_2.rs").not())
+        .and(predicate::str::contains("foo.js").not());
+    cmd.assert()
+        .stdout(stdout_predicate)
+        .stderr(predicate::str::contains(
+            "Skipped 4 files not selected by --include or --exclude.",
+        ));
+}
+
+#[test]
+fn directory_ignore_files() {
+    let tmp_dir = std::env::temp_dir().join(format!("difft-ignore.{}", std::process::id()));
+    let lhs_dir = tmp_dir.join("left");
+    let rhs_dir = tmp_dir.join("right");
+    for dir in [&lhs_dir, &rhs_dir] {
+        std::fs::create_dir_all(dir.join(".git")).unwrap();
+        std::fs::create_dir_all(dir.join("target")).unwrap();
+        std::fs::write(dir.join(".git/HEAD"), format!("{}\n", dir.display())).unwrap();
+        std::fs::write(dir.join("target/out.txt"), format!("{}\n", dir.display())).unwrap();
+        std::fs::write(dir.join(".env"), format!("{}\n", dir.display())).unwrap();
+        std::fs::write(dir.join("notes.log"), format!("{}\n", dir.display())).unwrap();
+    }
+    std::fs::write(lhs_dir.join(".gitignore"), "target/\n*.log\n").unwrap();
+    // Only ignored on the left, so it's still compared with the left
+    // file.
+    std::fs::write(rhs_dir.join(".gitignore"), "target/\n").unwrap();
+
+    let mut cmd = get_base_command();
+    cmd.arg("--color=never").arg(&lhs_dir).arg(&rhs_dir);
+    let output = cmd.output().unwrap();
+
+    let mut cmd = get_base_command();
+    cmd.arg("--color=never")
+        .arg("--no-ignore")
+        .arg("--hidden")
+        .arg(&lhs_dir)
+        .arg(&rhs_dir);
+    let all_output = cmd.output().unwrap();
+    std::fs::remove_dir_all(&tmp_dir).unwrap();
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("notes.log --- Text"));
+    assert!(!stdout.contains("notes.log (new file)"));
+    assert!(!stdout.contains("out.txt"));
+    assert!(!stdout.contains(".env"));
+    assert!(!stdout.contains("HEAD"));
+
+    let stdout = String::from_utf8(all_output.stdout).unwrap();
+    assert!(stdout.contains("out.txt --- Text"));
+    // .env files are parsed as Bash.
+    assert!(stdout.contains(".env --- "));
+    assert!(!stdout.contains("HEAD"));
+}
+
+#[test]
+fn directory_max_depth() {
+    let tmp_dir = std::env::temp_dir().join(format!("difft-max-depth.{}", std::process::id()));
+    let lhs_dir = tmp_dir.join("left");
+    let rhs_dir = tmp_dir.join("right");
+    for dir in [&lhs_dir, &rhs_dir] {
+        std::fs::create_dir_all(dir.join("changed/nested")).unwrap();
+        std::fs::create_dir_all(dir.join("same")).unwrap();
+        std::fs::write(dir.join("top.txt"), "top\n").unwrap();
+        std::fs::write(dir.join("same/a.txt"), "a\n").unwrap();
+    }
+    std::fs::write(lhs_dir.join("changed/nested/b.txt"), "old\n").unwrap();
+    std::fs::write(rhs_dir.join("changed/nested/b.txt"), "new\n").unwrap();
+
+    let mut cmd = get_base_command();
+    cmd.arg("--color=never")
+        .arg("--exit-code")
+        .arg("--max-depth=0")
+        .arg(&lhs_dir)
+        .arg(&rhs_dir);
+    let output = cmd.output().unwrap();
+    std::fs::remove_dir_all(&tmp_dir).unwrap();
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stdout.contains("changed/ --- Directory\nSubdirectory differs."));
+    assert!(stdout.contains("same/ --- Directory\nSubdirectory identical."));
+    assert!(!stdout.contains("b.txt"));
+
+    // Subdirectories aren't counted as files.
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains(
+        "Compared 1 file: 0 changed, 1 unchanged, 2 directories beyond --max-depth (1 changed)"
+    ));
+}
+
+#[cfg(unix)]
+#[test]
+fn directory_compare_metadata() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let tmp_dir = std::env::temp_dir().join(format!("difft-metadata.{}", std::process::id()));
+    let lhs_dir = tmp_dir.join("left");
+    let rhs_dir = tmp_dir.join("right");
+    std::fs::create_dir_all(&lhs_dir).unwrap();
+    std::fs::create_dir_all(&rhs_dir).unwrap();
+    std::fs::write(lhs_dir.join("run.sh"), "echo hi\n").unwrap();
+    std::fs::write(rhs_dir.join("run.sh"), "echo hi\n").unwrap();
+    std::fs::set_permissions(lhs_dir.join("run.sh"), PermissionsExt::from_mode(0o644)).unwrap();
+    std::fs::set_permissions(rhs_dir.join("run.sh"), PermissionsExt::from_mode(0o755)).unwrap();
+
+    let mut cmd = get_base_command();
+    cmd.arg("--color=never").arg(&lhs_dir).arg(&rhs_dir);
+    let output = cmd.output().unwrap();
+
+    let mut cmd = get_base_command();
+    cmd.arg("--color=never")
+        .arg("--compare-metadata")
+        .arg(&lhs_dir)
+        .arg(&rhs_dir);
+    let compared_output = cmd.output().unwrap();
+
+    let mut cmd = get_base_command();
+    cmd.env("DFT_UNSTABLE", "yes")
+        .arg("--display=json")
+        .arg("--compare-metadata")
+        .arg(&lhs_dir)
+        .arg(&rhs_dir);
+    let json_output = cmd.output().unwrap();
+    std::fs::remove_dir_all(&tmp_dir).unwrap();
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(!stdout.contains("Mode changed"));
+
+    let stdout = String::from_utf8(compared_output.stdout).unwrap();
+    assert!(stdout.contains("Mode changed 100644 → 100755."));
+    assert!(stdout.contains("No content changes."));
+
+    let stdout = String::from_utf8(json_output.stdout).unwrap();
+    assert!(stdout.contains("\"new_mode\":\"100755\""));
+    assert!(stdout.contains("\"status\":\"changed\""));
+}
+
+#[cfg(unix)]
+#[test]
+fn directory_non_utf8_path() {
+    use std::ffi::OsStr;
+    use std::os::unix::ffi::OsStrExt;
+
+    let tmp_dir = std::env::temp_dir().join(format!("difft-non-utf8.{}", std::process::id()));
+    let lhs_dir = tmp_dir.join("left");
+    let rhs_dir = tmp_dir.join("right");
+    std::fs::create_dir_all(&lhs_dir).unwrap();
+    std::fs::create_dir_all(&rhs_dir).unwrap();
+    let name = OsStr::from_bytes(b"caf\xe9.txt");
+    // Some filesystems, such as APFS, only allow UTF-8 names.
+    if std::fs::write(lhs_dir.join(name), "hello\n").is_err() {
+        std::fs::remove_dir_all(&tmp_dir).unwrap();
+        return;
+    }
+    std::fs::write(rhs_dir.join(name), "goodbye\n").unwrap();
+
+    let mut cmd = get_base_command();
+    cmd.arg("--color=never").arg(&lhs_dir).arg(&rhs_dir);
+    let output = cmd.output().unwrap();
+
+    let mut cmd = get_base_command();
+    cmd.env("DFT_UNSTABLE", "yes")
+        .arg("--display=json")
+        .arg(&lhs_dir)
+        .arg(&rhs_dir);
+    let json_output = cmd.output().unwrap();
+    std::fs::remove_dir_all(&tmp_dir).unwrap();
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("caf\\xe9.txt"));
+    assert!(stdout.contains("goodbye"));
+
+    let stdout = String::from_utf8(json_output.stdout).unwrap();
+    assert!(stdout.contains("\"path\":\"caf\\\\xe9.txt\""));
+    assert!(stdout.contains("\"path_bytes\":\"Y2Fm6S50eHQ=\""));
+}
+
+#[test]
+fn directory_ignore_path_case() {
+    let tmp_dir = std::env::temp_dir().join(format!("difft-path-case.{}", std::process::id()));
+    let lhs_dir = tmp_dir.join("left");
+    let rhs_dir = tmp_dir.join("right");
+    std::fs::create_dir_all(&lhs_dir).unwrap();
+    std::fs::create_dir_all(&rhs_dir).unwrap();
+    std::fs::write(lhs_dir.join("Readme.md"), "hello\n").unwrap();
+    std::fs::write(rhs_dir.join("README.md"), "goodbye\n").unwrap();
+
+    let mut cmd = get_base_command();
+    cmd.arg("--color=never")
+        .arg("--ignore-path-case")
+        .arg(&lhs_dir)
+        .arg(&rhs_dir);
+    let output = cmd.output().unwrap();
+    std::fs::remove_dir_all(&tmp_dir).unwrap();
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("Readme.md → README.md"));
+    assert!(stdout.contains("goodbye"));
+}
+
+#[cfg(unix)]
+#[test]
+fn directory_skips_fifo() {
+    let tmp_dir = std::env::temp_dir().join(format!("difft-fifo.{}", std::process::id()));
+    let lhs_dir = tmp_dir.join("left");
+    let rhs_dir = tmp_dir.join("right");
+    std::fs::create_dir_all(&lhs_dir).unwrap();
+    std::fs::create_dir_all(&rhs_dir).unwrap();
+    std::fs::write(lhs_dir.join("foo.txt"), "foo\n").unwrap();
+    std::fs::write(rhs_dir.join("foo.txt"), "bar\n").unwrap();
+    std::fs::write(lhs_dir.join("pipe"), "regular on this side\n").unwrap();
+
+    let mkfifo = std::process::Command::new("mkfifo")
+        .arg(rhs_dir.join("pipe"))
+        .arg(lhs_dir.join("only_pipe"))
+        .status();
+    if !mkfifo.map_or(false, |status| status.success()) {
+        // No mkfifo on this platform.
+        std::fs::remove_dir_all(&tmp_dir).unwrap();
+        return;
+    }
+
+    let mut cmd = get_base_command();
+    cmd.arg("--color=never")
+        .arg("--new-file")
+        .arg(&lhs_dir)
+        .arg(&rhs_dir);
+    // Fail rather than hang if we block on reading the FIFO.
+    let output = assert_cmd::Command::from_std(cmd)
+        .timeout(std::time::Duration::from_secs(30))
+        .output()
+        .unwrap();
+    std::fs::remove_dir_all(&tmp_dir).unwrap();
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert_eq!(output.status.code(), Some(0));
+    assert!(stdout.contains("foo.txt"));
+    assert!(stdout.contains("pipe (deleted)"));
+    assert!(stdout.contains("regular on this side"));
+    assert!(!stdout.contains("only_pipe"));
+    assert!(stderr.contains("left/only_pipe: Skipping special file (FIFO)"));
+    assert!(stderr.contains("right/pipe: Skipping special file (FIFO)"));
+}
+
+#[cfg(unix)]
+#[test]
+fn directory_symlinks() {
+    let tmp_dir = std::env::temp_dir().join(format!("difft-symlinks.{}", std::process::id()));
+    let lhs_dir = tmp_dir.join("left");
+    let rhs_dir = tmp_dir.join("right");
+    std::fs::create_dir_all(&lhs_dir).unwrap();
+    std::fs::create_dir_all(&rhs_dir).unwrap();
+    std::fs::write(tmp_dir.join("foo.txt"), "foo\n").unwrap();
+    std::fs::write(tmp_dir.join("bar.txt"), "bar\n").unwrap();
+
+    std::os::unix::fs::symlink("../foo.txt", lhs_dir.join("link.txt")).unwrap();
+    std::os::unix::fs::symlink("../bar.txt", rhs_dir.join("link.txt")).unwrap();
+    std::os::unix::fs::symlink("../foo.txt", lhs_dir.join("dangling.txt")).unwrap();
+    std::os::unix::fs::symlink("../missing.txt", rhs_dir.join("dangling.txt")).unwrap();
+    // Following this would loop forever.
+    std::os::unix::fs::symlink(".", rhs_dir.join("loop")).unwrap();
+
+    let mut cmd = get_base_command();
+    cmd.arg("--color=never").arg(&lhs_dir).arg(&rhs_dir);
+    let output = cmd.output().unwrap();
+
+    let mut cmd = get_base_command();
+    cmd.arg("--color=never")
+        .arg("--follow-symlinks")
+        .arg(&lhs_dir)
+        .arg(&rhs_dir);
+    let followed_output = cmd.output().unwrap();
+    std::fs::remove_dir_all(&tmp_dir).unwrap();
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(output.status.code(), Some(0));
+    assert!(stdout.contains("Symlink: comparing the link targets."));
+    assert!(stdout.contains("symlink → ../bar.txt"));
+    assert!(stdout.contains("loop (new file)"));
+
+    let stdout = String::from_utf8(followed_output.stdout).unwrap();
+    let stderr = String::from_utf8(followed_output.stderr).unwrap();
+    assert_eq!(followed_output.status.code(), Some(0));
+    assert!(stdout.contains("bar"));
+    assert!(stdout.contains("Dangling symlink on the right: ../missing.txt doesn't exist."));
+    assert!(stderr.contains("Not following symlink to a parent directory"));
+}
+
+#[cfg(unix)]
+#[test]
+fn git_dir_diff() {
+    let tmp_dir = std::env::temp_dir().join(format!("git-difftool.{}", std::process::id()));
+    let lhs_dir = tmp_dir.join("left");
+    let rhs_dir = tmp_dir.join("right");
+    std::fs::create_dir_all(&lhs_dir).unwrap();
+    std::fs::create_dir_all(&rhs_dir).unwrap();
+
+    std::fs::copy("sample_files/simple_1.js", lhs_dir.join("simple.js")).unwrap();
+    std::fs::write(lhs_dir.join("deleted.txt"), "old\n").unwrap();
+    // Git links files in the right tree to the working tree.
+    std::os::unix::fs::symlink(
+        std::fs::canonicalize("sample_files/simple_2.js").unwrap(),
+        rhs_dir.join("simple.js"),
+    )
+    .unwrap();
+    std::fs::write(rhs_dir.join("created.txt"), "").unwrap();
+
+    let mut cmd = get_base_command();
+    cmd.arg("--color=never").arg(&lhs_dir).arg(&rhs_dir);
+    let output = cmd.output().unwrap();
+    std::fs::remove_dir_all(&tmp_dir).unwrap();
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(output.status.code(), Some(0));
+    assert!(stdout.contains("simple.js --- "));
+    assert!(stdout.contains("created.txt (new file) --- Text"));
+    assert!(stdout.contains("deleted.txt (deleted) --- Text"));
+    assert!(!stdout.contains("git-difftool."));
+}
+
+#[test]
+fn jj_diff_tool() {
+    let tmp_dir = std::env::temp_dir().join(format!("jj-diff-{}", std::process::id()));
+    let lhs_dir = tmp_dir.join("left");
+    let rhs_dir = tmp_dir.join("right");
+    std::fs::create_dir_all(lhs_dir.join("src")).unwrap();
+    std::fs::create_dir_all(rhs_dir.join("src")).unwrap();
+
+    std::fs::copy("sample_files/simple_1.js", lhs_dir.join("src/simple.js")).unwrap();
+    std::fs::copy("sample_files/simple_2.js", rhs_dir.join("src/simple.js")).unwrap();
+    std::fs::write(lhs_dir.join("removed.txt"), "old\n").unwrap();
+    std::fs::write(rhs_dir.join("added.txt"), "new\n").unwrap();
+
+    let mut cmd = get_base_command();
+    cmd.arg("--color=never")
+        .arg("--pager=auto")
+        .arg(&lhs_dir)
+        .arg(&rhs_dir);
+    let output = cmd.output().unwrap();
+    std::fs::remove_dir_all(&tmp_dir).unwrap();
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(output.status.code(), Some(0));
+    assert!(stdout.contains("src/simple.js --- "));
+    assert!(stdout.contains("added.txt (new file) --- Text"));
+    assert!(stdout.contains("removed.txt (deleted) --- Text"));
+    assert!(!stdout.contains("jj-diff-"));
+}
+
+#[test]
+fn hg_extdiff_single_file() {
+    let tmp_dir = std::env::temp_dir().join(format!("extdiff.{}", std::process::id()));
+    let lhs_path = tmp_dir.join("project.1a2b3c4d5e6f/src/simple.js");
+    let rhs_path = tmp_dir.join("project/src/simple.js");
+    std::fs::create_dir_all(lhs_path.parent().unwrap()).unwrap();
+    std::fs::create_dir_all(rhs_path.parent().unwrap()).unwrap();
+    std::fs::copy("sample_files/simple_1.js", &lhs_path).unwrap();
+    std::fs::copy("sample_files/simple_2.js", &rhs_path).unwrap();
+
+    let mut cmd = get_base_command();
+    cmd.env_remove("HG_PARENT1")
+        .env_remove("HG_NODE")
+        .arg("--color=never")
+        .arg(&lhs_path)
+        .arg(&rhs_path);
+    let output = cmd.output().unwrap();
+    std::fs::remove_dir_all(&tmp_dir).unwrap();
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.starts_with("src/simple.js --- "));
+    assert!(stdout.contains("Comparing changeset 1a2b3c4d5e6f to working directory."));
+    assert!(!stdout.contains("extdiff."));
+}
+
+#[test]
+fn from_patch() {
+    let mut cmd = get_base_command();
+
+    cmd.arg("--color=never")
+        .arg("--from-patch")
+        .arg("sample_files/cli_tests/from_patch.diff");
+    let predicate_fn = predicate::str::contains("src/add.js --- JavaScript")
+        .and(predicate::str::contains(
+            "Partial file: only the lines in the patch are shown.",
+        ))
+        .and(predicate::str::contains("notes.txt (new file) --- Text"));
+    cmd.assert().success().stdout(predicate_fn);
+}
+
+#[test]
+fn from_patch_stdin() {
+    let mut cmd = assert_cmd::Command::from_std(get_base_command());
+
+    cmd.env("DFT_UNSTABLE", "yes")
+        .arg("--display=json")
+        .arg("--from-patch")
+        .write_stdin(std::fs::read("sample_files/cli_tests/from_patch.diff").unwrap());
+    let predicate_fn = predicate::str::contains("\"path\":\"notes.txt\"")
+        .and(predicate::str::contains("\"status\":\"created\""));
+    cmd.assert().success().stdout(predicate_fn);
+}
+
+#[test]
+fn git_revisions_unknown_revision() {
+    let mut cmd = get_base_command();
+
+    cmd.arg("--git")
+        .arg("no-such-revision")
+        .arg("--")
+        .arg("README.md");
+    // Source tarballs aren't git repositories.
+    let predicate_fn = predicate::str::contains("no-such-revision")
+        .or(predicate::str::contains("inside a git repository"));
+    cmd.assert().failure().stderr(predicate_fn);
+}
+
+#[test]
+fn git_revisions_outside_repository() {
+    let mut cmd = get_base_command();
+
+    cmd.current_dir(std::env::temp_dir())
+        .arg("--git")
+        .arg("HEAD")
+        .arg("--")
+        .arg("foo.rs");
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("inside a git repository"));
+}
+
+#[cfg(unix)]
+#[test]
+fn git_style_arguments_textconv() {
+    let tmp_dir = std::env::temp_dir().join(format!("difft-textconv.{}", std::process::id()));
+    std::fs::create_dir_all(&tmp_dir).unwrap();
+    let git = |args: &[&str]| {
+        let status = std::process::Command::new("git")
+            .args(args)
+            .current_dir(&tmp_dir)
+            .status()
+            .unwrap();
+        assert!(status.success());
+    };
+    git(&["init", "-q"]);
+    git(&["config", "diff.upper.textconv", "tr a-z A-Z <"]);
+    std::fs::write(tmp_dir.join(".gitattributes"), "*.upper diff=upper\n").unwrap();
+    std::fs::write(tmp_dir.join("old.upper"), "hello\n").unwrap();
+    std::fs::write(tmp_dir.join("new.upper"), "hello world\n").unwrap();
+
+    let mut cmd = get_base_command();
+    cmd.current_dir(&tmp_dir)
+        .arg("--color=never")
+        .arg("--textconv=auto")
+        .arg("notes.upper")
+        .arg("old.upper")
+        .arg("abcdef1234")
+        .arg("100644")
+        .arg("new.upper")
+        .arg("abcdef5678")
+        .arg("100644");
+    let output = cmd.output().unwrap();
+    std::fs::remove_dir_all(&tmp_dir).unwrap();
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("Converted with the textconv for 'upper'."));
+    assert!(stdout.contains("HELLO WORLD"));
+}
+
+#[cfg(unix)]
+#[test]
+fn gitattributes_language() {
+    let tmp_dir = std::env::temp_dir().join(format!("difft-gitattributes.{}", std::process::id()));
+    std::fs::create_dir_all(&tmp_dir).unwrap();
+    let status = std::process::Command::new("git")
+        .args(["init", "-q"])
+        .current_dir(&tmp_dir)
+        .status()
+        .unwrap();
+    assert!(status.success());
+    std::fs::write(
+        tmp_dir.join(".gitattributes"),
+        "*.inc linguist-language=Rust\n",
+    )
+    .unwrap();
+    std::fs::write(tmp_dir.join("old.inc"), "fn foo() {}\n").unwrap();
+    std::fs::write(tmp_dir.join("new.inc"), "fn bar() {}\n").unwrap();
+
+    let mut cmd = get_base_command();
+    cmd.current_dir(&tmp_dir)
+        .arg("--color=never")
+        .arg("old.inc")
+        .arg("new.inc");
+    let output = cmd.output().unwrap();
+
+    let mut cmd = get_base_command();
+    cmd.current_dir(&tmp_dir)
+        .arg("--color=never")
+        .arg("--override=*.inc:text")
+        .arg("old.inc")
+        .arg("new.inc");
+    let overridden = cmd.output().unwrap();
+    std::fs::remove_dir_all(&tmp_dir).unwrap();
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.starts_with("new.inc --- Rust"));
+    let stdout = String::from_utf8(overridden.stdout).unwrap();
+    assert!(stdout.starts_with("new.inc --- Text"));
+}
+
+#[test]
+fn svn_diff_cmd_arguments() {
+    let mut cmd = get_base_command();
+
+    cmd.arg("--color=never")
+        .arg("-u")
+        .arg("-L")
+        .arg("simple.txt\t(revision 12)")
+        .arg("-L")
+        .arg("simple.txt\t(working copy)")
+        .arg("sample_files/simple_1.txt")
+        .arg("sample_files/simple_2.txt");
+    let predicate_fn = predicate::str::starts_with("simple.txt --- ").and(
+        predicate::str::contains("Comparing revision 12 to working copy."),
+    );
+    cmd.assert().success().stdout(predicate_fn);
+}
+
+#[test]
+fn p4_diff_flags() {
+    let mut cmd = get_base_command();
+
+    cmd.arg("--color=never")
+        .arg("-c")
+        .arg("-l")
+        .arg("sample_files/simple_1.txt")
+        .arg("sample_files/simple_2.txt");
+    cmd.assert().success();
+}
+
+#[cfg(unix)]
+#[test]
+fn git_staged_and_worktree() {
+    let tmp_dir = std::env::temp_dir().join(format!("difft-git-staged.{}", std::process::id()));
+    std::fs::create_dir_all(tmp_dir.join("src")).unwrap();
+    let git = |args: &[&str]| {
+        let status = std::process::Command::new("git")
+            .args(["-c", "user.name=Test", "-c", "user.email=test@example.com"])
+            .args(args)
+            .current_dir(&tmp_dir)
+            .status()
+            .unwrap();
+        assert!(status.success());
+    };
+    git(&["init", "-q"]);
+    std::fs::write(tmp_dir.join("src/old.txt"), "one\ntwo\nthree\nfour\n").unwrap();
+    git(&["add", "."]);
+    git(&["commit", "-q", "-m", "Initial"]);
+    git(&["mv", "src/old.txt", "src/new.txt"]);
+    std::fs::write(tmp_dir.join("src/new.txt"), "one\ntwo\nthree\nfour\nfive\n").unwrap();
+
+    // Run from a subdirectory, with paths relative to it.
+    let run = |flag: &str| {
+        let mut cmd = get_base_command();
+        cmd.current_dir(tmp_dir.join("src"))
+            .arg("--color=never")
+            .arg(flag)
+            .arg(".");
+        String::from_utf8(cmd.output().unwrap().stdout).unwrap()
+    };
+    let staged = run("--git-staged");
+    let worktree = run("--git-worktree");
+    std::fs::remove_dir_all(&tmp_dir).unwrap();
+
+    assert!(staged.contains("src/old.txt → src/new.txt"));
+    assert!(worktree.contains("src/new.txt @ index → src/new.txt @ worktree"));
+    assert!(worktree.contains("five"));
+}
+
+#[test]
+fn git_style_arguments_rename() {
+    let mut cmd = get_base_command();
+
+    cmd.arg("elisp_oldname.el")
+        .arg("sample_files/elisp_1.el")
+        .arg("lhs_hash_placeholder")
+        .arg("lhs_mode_placeholder")
+        .arg("sample_files/elisp_2.el")
+        .arg("rhs_hash_placeholder")
+        .arg("rhs_mode_placeholder")
+        .arg("elisp_newname.el")
+        .arg("similarity_placeholder");
+    let predicate_fn = predicate::str::contains("elisp_oldname.el → elisp_newname.el");
+    cmd.assert().stdout(predicate_fn);
+}
+
+#[test]
+fn git_style_arguments_pure_rename() {
+    let mut cmd = get_base_command();
+
+    cmd.arg("--skip-unchanged")
+        .arg("elisp_oldname.el")
+        .arg("sample_files/elisp_1.el")
+        .arg("lhs_hash_placeholder")
+        .arg("lhs_mode_placeholder")
+        .arg("sample_files/elisp_1.el")
+        .arg("rhs_hash_placeholder")
+        .arg("rhs_mode_placeholder")
+        .arg("elisp_newname.el")
+        .arg("similarity_placeholder");
+    cmd.assert()
+        .success()
+        .stdout("elisp_oldname.el → elisp_newname.el --- renamed, no content changes\n\n");
+}
+
+#[test]
+fn git_style_arguments_new_file() {
+    let mut cmd = get_base_command();
+
+    cmd.arg("simple.txt")
         .arg("/dev/null")
         .arg(".")
         .arg(".")
@@ -217,45 +1721,637 @@ fn git_style_arguments_new_file() {
 }
 
 #[test]
-fn drop_different_path_starts() {
+fn git_style_arguments_mode_change() {
+    let mut cmd = get_base_command();
+
+    cmd.arg("--color=never")
+        .arg("--exit-code")
+        .arg("simple.txt")
+        .arg("sample_files/simple_1.txt")
+        .arg("abcdef1234")
+        .arg("100644")
+        .arg("sample_files/simple_1.txt")
+        .arg("abcdef1234")
+        .arg("100755");
+    let predicate_fn = predicate::str::contains("Mode changed 100644 → 100755.")
+        .and(predicate::str::contains("No content changes."));
+    cmd.assert().code(1).stdout(predicate_fn);
+}
+
+#[test]
+fn git_style_arguments_mode_change_json() {
+    let mut cmd = get_base_command();
+
+    cmd.env("DFT_UNSTABLE", "yes")
+        .arg("--display=json")
+        .arg("simple.txt")
+        .arg("sample_files/simple_1.txt")
+        .arg("abcdef1234")
+        .arg("100644")
+        .arg("sample_files/simple_1.txt")
+        .arg("abcdef1234")
+        .arg("100755");
+    let predicate_fn = predicate::str::contains("\"old_mode\":\"100644\"")
+        .and(predicate::str::contains("\"new_mode\":\"100755\""))
+        .and(predicate::str::contains("\"status\":\"changed\""));
+    cmd.assert().success().stdout(predicate_fn);
+}
+
+#[test]
+fn git_style_arguments_symlink_change() {
+    let mut cmd = get_base_command();
+
+    cmd.arg("--color=never")
+        .arg("simple.txt")
+        .arg("sample_files/simple_1.txt")
+        .arg("abcdef1234")
+        .arg("120000")
+        .arg("sample_files/simple_2.txt")
+        .arg("abcdef1234")
+        .arg("100644");
+    let predicate_fn =
+        predicate::str::contains("Changed from a symlink to a regular file, mode 120000 → 100644.");
+    cmd.assert().success().stdout(predicate_fn);
+}
+
+#[test]
+fn git_style_arguments_deleted_file() {
+    let mut cmd = get_base_command();
+
+    cmd.arg("--color=never")
+        .arg("simple.txt")
+        .arg("sample_files/simple_1.txt")
+        .arg("abcdef1234")
+        .arg("100644")
+        .arg("/dev/null")
+        .arg(".")
+        .arg(".");
+    cmd.assert()
+        .stdout(predicate::str::starts_with("simple.txt (deleted) --- Text"));
+}
+
+#[test]
+fn drop_different_path_starts() {
+    let mut cmd = get_base_command();
+
+    cmd.arg("sample_files/dir_1/clojure.clj")
+        .arg("sample_files/dir_2/clojure.clj");
+    let predicate_fn = predicate::str::contains("dir_2/clojure.clj").not();
+    cmd.assert().stdout(predicate_fn);
+}
+
+#[test]
+fn dump_tree_sitter() {
+    let mut cmd = get_base_command();
+
+    cmd.arg("--dump-ts")
+        .arg("sample_files/simple_1.js")
+        .arg("sample_files/simple_2.js");
+    cmd.assert().success();
+}
+
+#[test]
+fn dump_syntax() {
+    let mut cmd = get_base_command();
+
+    cmd.arg("--dump-syntax")
+        .arg("sample_files/simple_1.js")
+        .arg("sample_files/simple_2.js");
+    cmd.assert().success();
+}
+
+#[test]
+fn walk_hidden_items() {
+    let mut cmd = get_base_command();
+
+    cmd.args(["--hidden", "sample_files/hidden_1", "sample_files/hidden_2"]);
+
+    let predicate_fn =
+        predicate::str::contains(format!(".hidden{}doc.txt", std::path::MAIN_SEPARATOR))
+            .and(predicate::str::contains(".hidden.txt"))
+            .and(predicate::str::contains("before"))
+            .and(predicate::str::contains("after"));
+    cmd.assert().stdout(predicate_fn);
+}
+
+#[test]
+fn walk_skips_hidden_items_by_default() {
+    let mut cmd = get_base_command();
+
+    cmd.args(["sample_files/hidden_1", "sample_files/hidden_2"]);
+
+    let predicate_fn = predicate::str::contains(".hidden").not();
+    cmd.assert().stdout(predicate_fn);
+}
+
+#[test]
+fn html_output() {
     let mut cmd = get_base_command();
 
-    cmd.arg("sample_files/dir_1/clojure.clj")
-        .arg("sample_files/dir_2/clojure.clj");
-    let predicate_fn = predicate::str::contains("dir_2/clojure.clj").not();
+    cmd.arg("--output=html")
+        .arg("sample_files/simple_1.js")
+        .arg("sample_files/simple_2.js");
+
+    let predicate_fn =
+        predicate::str::starts_with("<!DOCTYPE html>").and(predicate::str::contains("simple_2.js"));
+    cmd.assert().success().stdout(predicate_fn);
+}
+
+#[test]
+fn html_output_directory_index() {
+    let mut cmd = get_base_command();
+
+    cmd.arg("--display=html")
+        .arg("sample_files/dir_1")
+        .arg("sample_files/dir_2");
+
+    let predicate_fn = predicate::str::contains("<nav class=\"index\">")
+        .and(predicate::str::contains("only_in_1.c"))
+        .and(predicate::str::contains("only_in_2.rs"));
+    cmd.assert().success().stdout(predicate_fn);
+}
+
+#[test]
+fn patch_output() {
+    let mut cmd = get_base_command();
+
+    cmd.arg("--display=patch")
+        .arg("sample_files/simple_1.js")
+        .arg("sample_files/simple_2.js");
+
+    let predicate_fn = predicate::str::starts_with("--- a/")
+        .and(predicate::str::contains("\n+++ b/"))
+        .and(predicate::str::contains("\n@@ -"));
+    cmd.assert().success().stdout(predicate_fn);
+}
+
+#[test]
+fn stat_output() {
+    let mut cmd = get_base_command();
+
+    cmd.arg("--display=stat")
+        .arg("sample_files/dir_1")
+        .arg("sample_files/dir_2");
+
+    let predicate_fn = predicate::str::contains("only_in_1.c")
+        .and(predicate::str::contains(" | "))
+        .and(predicate::str::contains("files changed"));
+    cmd.assert().success().stdout(predicate_fn);
+}
+
+#[test]
+fn sarif_output() {
+    let mut cmd = get_base_command();
+
+    cmd.arg("--display=sarif")
+        .arg("sample_files/simple_1.js")
+        .arg("sample_files/simple_2.js");
+
+    let predicate_fn = predicate::str::contains("\"version\": \"2.1.0\"")
+        .and(predicate::str::contains("\"ruleId\""))
+        .and(predicate::str::contains("\"startLine\""));
+    cmd.assert().success().stdout(predicate_fn);
+}
+
+#[test]
+fn markdown_output() {
+    let mut cmd = get_base_command();
+
+    cmd.arg("--display=markdown")
+        .arg("sample_files/simple_1.js")
+        .arg("sample_files/simple_2.js");
+
+    let predicate_fn = predicate::str::starts_with("<details>")
+        .and(predicate::str::contains("```diff"))
+        .and(predicate::str::contains("</details>"));
+    cmd.assert().success().stdout(predicate_fn);
+}
+
+#[test]
+fn dump_graph() {
+    let mut cmd = get_base_command();
+
+    cmd.arg("--dump-graph=dot")
+        .arg("sample_files/simple_1.js")
+        .arg("sample_files/simple_2.js");
+
+    let predicate_fn = predicate::str::starts_with("digraph difftastic {")
+        .and(predicate::str::contains("color=red"));
+    cmd.assert().success().stdout(predicate_fn);
+}
+
+#[test]
+fn json_patch_output() {
+    let mut cmd = get_base_command();
+
+    cmd.arg("--display=json-patch")
+        .arg("sample_files/json_1.json")
+        .arg("sample_files/json_2.json");
+
+    let predicate_fn = predicate::str::starts_with("[")
+        .and(predicate::str::contains("\"op\": "))
+        .and(predicate::str::contains("\"path\": "));
+    cmd.assert().success().stdout(predicate_fn);
+}
+
+#[test]
+fn symbols_output() {
+    let mut cmd = get_base_command();
+
+    cmd.arg("--output=symbols")
+        .arg("sample_files/simple_1.js")
+        .arg("sample_files/simple_2.js");
+
+    let predicate_fn = predicate::str::ends_with(": <top level> (modified)\n");
+    cmd.assert().success().stdout(predicate_fn);
+}
+
+#[test]
+fn invalid_color() {
+    let mut cmd = get_base_command();
+
+    cmd.arg("--color-novel-lhs=reddish")
+        .arg("sample_files/simple_1.js")
+        .arg("sample_files/simple_2.js");
+
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("is not a color name"));
+}
+
+#[test]
+fn accessibility_without_color() {
+    let mut cmd = get_base_command();
+
+    cmd.arg("--accessibility")
+        .arg("--color=never")
+        .arg("sample_files/simple_1.js")
+        .arg("sample_files/simple_2.js");
+
+    // Strikethrough and bold, but not the usual red.
+    let predicate_fn = predicate::str::contains("\x1b[9m")
+        .and(predicate::str::contains("\x1b[1m"))
+        .and(predicate::str::contains("\x1b[91m").not());
+    cmd.assert().success().stdout(predicate_fn);
+}
+
+/// The peak memory budget for diffing the large OCaml sample files,
+/// see "Memory usage" in the profiling section of the manual.
+///
+/// A graph search that reaches the default graph limit of 3,000,000
+/// vertices uses about 250 bytes per vertex (the 72 byte `Vertex`,
+/// its edges, and its entries in the seen map and the queue), so
+/// roughly 750 MiB. The budget leaves some room for the parsed trees
+/// on top of that.
+#[cfg(unix)]
+const LARGE_SAMPLE_MAX_RSS_MIB: u64 = 1024;
+
+#[test]
+#[cfg(unix)]
+fn large_sample_files_memory_budget() {
+    let mut cmd = get_base_command();
+
+    cmd.arg("--verbose")
+        .arg("--verbose")
+        .arg("sample_files/typing_1.ml")
+        .arg("sample_files/typing_2.ml");
+
+    let output = cmd.output().unwrap();
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let peak_rss_mib: u64 = stderr
+        .lines()
+        .find_map(|line| line.split("peak RSS ").nth(1))
+        .and_then(|rest| rest.strip_suffix(" MiB"))
+        .and_then(|num| num.parse().ok())
+        .expect("--verbose --verbose should report peak RSS");
+
+    assert!(
+        peak_rss_mib <= LARGE_SAMPLE_MAX_RSS_MIB,
+        "Peak RSS was {} MiB, budget is {} MiB",
+        peak_rss_mib,
+        LARGE_SAMPLE_MAX_RSS_MIB
+    );
+}
+
+#[test]
+fn anchors_stay_under_graph_limit() {
+    let mut cmd = get_base_command();
+
+    cmd.arg("--no-unchanged-heuristic")
+        .arg("--graph-limit=100000")
+        .arg("--verbose")
+        .arg("sample_files/b2_math_1.h")
+        .arg("sample_files/b2_math_2.h");
+
+    cmd.assert()
+        .success()
+        .stderr(predicate::str::contains("Exceeded DFT_GRAPH_LIMIT").not());
+}
+
+#[test]
+fn no_anchors_exceeds_graph_limit() {
+    let mut cmd = get_base_command();
+
+    cmd.arg("--no-anchors")
+        .arg("--no-unchanged-heuristic")
+        .arg("--graph-limit=100000")
+        .arg("--verbose")
+        .arg("sample_files/b2_math_1.h")
+        .arg("sample_files/b2_math_2.h");
+
+    cmd.assert()
+        .success()
+        .stderr(predicate::str::contains("Exceeded DFT_GRAPH_LIMIT"));
+}
+
+#[test]
+fn timeout_uses_text_diff() {
+    let mut cmd = get_base_command();
+
+    cmd.arg("--timeout=0.000001")
+        .arg("sample_files/typing_1.ml")
+        .arg("sample_files/typing_2.ml");
+
+    let predicate_fn = predicate::str::contains("exceeded DFT_TIMEOUT");
     cmd.assert().stdout(predicate_fn);
 }
 
 #[test]
-fn dump_tree_sitter() {
+fn generate_completions() {
     let mut cmd = get_base_command();
 
-    cmd.arg("--dump-ts")
+    cmd.arg("--generate-completions").arg("bash");
+
+    let predicate_fn = predicate::str::contains("side-by-side-show-both")
+        .and(predicate::str::contains("--override"))
+        .and(predicate::str::contains("Rust"));
+    cmd.assert().success().stdout(predicate_fn);
+}
+
+#[test]
+fn config_file_sets_defaults() {
+    let mut cmd = get_base_command();
+
+    cmd.arg("--config")
+        .arg("sample_files/cli_tests/config.toml")
         .arg("sample_files/simple_1.js")
         .arg("sample_files/simple_2.js");
-    cmd.assert().success();
+
+    cmd.assert()
+        .stdout(predicate::str::contains("1 file changed"))
+        .stderr(predicate::str::contains("Unknown key `not-an-option`"));
 }
 
 #[test]
-fn dump_syntax() {
+fn command_line_overrides_config_file() {
     let mut cmd = get_base_command();
 
-    cmd.arg("--dump-syntax")
+    cmd.arg("--config")
+        .arg("sample_files/cli_tests/config.toml")
+        .arg("--display=inline")
         .arg("sample_files/simple_1.js")
         .arg("sample_files/simple_2.js");
-    cmd.assert().success();
+
+    cmd.assert()
+        .stdout(predicate::str::contains("1 file changed").not());
 }
 
 #[test]
-fn walk_hidden_items() {
+fn missing_config_file() {
     let mut cmd = get_base_command();
 
-    cmd.args(["sample_files/hidden_1", "sample_files/hidden_2"]);
+    cmd.arg("--config")
+        .arg("sample_files/cli_tests/no_such_config.toml")
+        .arg("sample_files/simple_1.js")
+        .arg("sample_files/simple_2.js");
+
+    cmd.assert().failure().code(2);
+}
+
+#[test]
+fn stdin_with_label() {
+    let mut cmd = assert_cmd::Command::from_std(get_base_command());
+
+    cmd.arg("--label-right")
+        .arg("remote")
+        .arg("sample_files/simple_1.js")
+        .arg("-")
+        .write_stdin(std::fs::read("sample_files/simple_2.js").unwrap());
 
+    // The language is detected from the other file's name.
     let predicate_fn =
-        predicate::str::contains(format!(".hidden{}doc.txt", std::path::MAIN_SEPARATOR))
-            .and(predicate::str::contains(".hidden.txt"))
-            .and(predicate::str::contains("before"))
-            .and(predicate::str::contains("after"));
+        predicate::str::contains("remote").and(predicate::str::contains("JavaScript"));
+    cmd.assert().success().stdout(predicate_fn);
+}
+
+#[test]
+fn stdin_both_sides() {
+    let mut cmd = get_base_command();
+
+    cmd.arg("-").arg("-");
+
+    cmd.assert().failure().code(2);
+}
+
+#[test]
+fn language_flag() {
+    let mut cmd = get_base_command();
+
+    cmd.arg("--language")
+        .arg("text")
+        .arg("sample_files/simple_1.js")
+        .arg("sample_files/simple_2.js");
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Text"));
+}
+
+#[test]
+fn lines_outside_changes() {
+    let mut cmd = get_base_command();
+
+    cmd.arg("--lines")
+        .arg("100-200")
+        .arg("sample_files/simple_1.js")
+        .arg("sample_files/simple_2.js");
+
+    cmd.assert().success().stdout(predicate::str::contains(
+        "No changes in the selected lines.",
+    ));
+}
+
+#[test]
+fn ignore_space_change() {
+    let mut cmd = get_base_command();
+
+    cmd.arg("-b")
+        .arg("--exit-code")
+        .arg("sample_files/cli_tests/whitespace_1.txt")
+        .arg("sample_files/cli_tests/whitespace_2.txt");
+
+    cmd.assert().success();
+}
+
+#[test]
+fn trailing_whitespace_without_color() {
+    for display in ["inline", "side-by-side"] {
+        let mut cmd = get_base_command();
+
+        cmd.arg("--color=never")
+            .arg("--display")
+            .arg(display)
+            .arg("sample_files/cli_tests/trailing_whitespace_1.txt")
+            .arg("sample_files/cli_tests/trailing_whitespace_2.txt");
+
+        cmd.assert().stdout(predicate::str::contains("foo··\n"));
+    }
+}
+
+#[test]
+fn ignore_blank_lines() {
+    let mut cmd = get_base_command();
+
+    cmd.arg("--ignore-blank-lines")
+        .arg("--exit-code")
+        .arg("sample_files/cli_tests/blank_lines_1.txt")
+        .arg("sample_files/cli_tests/blank_lines_2.txt");
+
+    cmd.assert().success();
+}
+
+#[test]
+fn ignore_blank_lines_not_marked() {
+    // A blank line inserted next to a real change isn't marked as
+    // novel in either display mode.
+    for display in ["side-by-side", "inline"] {
+        let mut cmd = get_base_command();
+
+        cmd.arg("--color=never")
+            .arg("--ignore-blank-lines")
+            .arg("--novel-line-marker=+")
+            .arg(format!("--display={}", display))
+            .arg("sample_files/cli_tests/blank_lines_1.txt")
+            .arg("sample_files/cli_tests/blank_lines_changed.txt");
+
+        let predicate_fn = predicate::str::contains("+3 second line changed")
+            .and(predicate::str::contains("+2 \n").not());
+        cmd.assert().stdout(predicate_fn);
+    }
+}
+
+#[test]
+fn invalid_env_var_uses_default() {
+    let mut cmd = get_base_command();
+
+    cmd.env("DFT_TAB_WIDTH", "wide")
+        .env("DFT_DISPLAY", "stat")
+        .arg("sample_files/simple_1.js")
+        .arg("sample_files/simple_2.js");
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("1 file changed"))
+        .stderr(predicate::str::contains(
+            "warning: Ignoring DFT_TAB_WIDTH=wide",
+        ));
+}
+
+#[test]
+fn override_env_var_list() {
+    let mut cmd = get_base_command();
+
+    cmd.env("DFT_OVERRIDE", "*.foo:json;*.js:text")
+        .arg("sample_files/simple_1.js")
+        .arg("sample_files/simple_2.js");
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Text"));
+}
+
+#[test]
+fn profile_prints_table() {
+    let mut cmd = get_base_command();
+
+    cmd.arg("--profile")
+        .arg("sample_files/simple_1.js")
+        .arg("sample_files/simple_2.js");
+
+    let predicate_fn = predicate::str::contains("vertices")
+        .and(predicate::str::contains("sample_files/simple_2.js"));
+    cmd.assert().stderr(predicate_fn);
+}
+
+#[test]
+fn profile_in_json() {
+    let mut cmd = get_base_command();
+
+    cmd.env("DFT_UNSTABLE", "yes")
+        .arg("--profile")
+        .arg("--display=json")
+        .arg("sample_files/simple_1.js")
+        .arg("sample_files/simple_2.js");
+
+    cmd.assert()
+        .stdout(predicate::str::contains("\"profile\""))
+        .stderr(predicate::str::contains("vertices").not());
+}
+
+#[test]
+fn minified_files_split_long_lines() {
+    let mut cmd = get_base_command();
+
+    cmd.arg("--display=inline")
+        .arg("sample_files/minified_1.js")
+        .arg("sample_files/minified_2.js");
+
+    let predicate_fn = predicate::str::contains("have been split for display")
+        .and(predicate::str::contains("changed 137"));
+    cmd.assert().stdout(predicate_fn);
+}
+
+#[test]
+fn minified_files_show_source_line_numbers() {
+    let mut cmd = get_base_command();
+
+    cmd.arg("--display=inline")
+        .arg("--hunk-ranges")
+        .arg("--color=never")
+        .arg("sample_files/minified_1.js")
+        .arg("sample_files/minified_2.js");
+
+    // Each file is a single line, so every hunk is on line 1 and the
+    // rest of the line has no line number.
+    let predicate_fn = predicate::str::contains("@@ -1 +1 @@")
+        .and(predicate::str::contains("\n.    "))
+        .and(predicate::str::contains("\n2 ").not());
+    cmd.assert().stdout(predicate_fn);
+}
+
+#[test]
+fn minified_files_select_source_lines() {
+    let mut cmd = get_base_command();
+
+    cmd.arg("--display=inline")
+        .arg("--lines=1")
+        .arg("sample_files/minified_1.js")
+        .arg("sample_files/minified_2.js");
+
+    let predicate_fn = predicate::str::contains("1/4").and(predicate::str::contains("changed 137"));
+    cmd.assert().stdout(predicate_fn);
+}
+
+#[test]
+fn collapse_generated_summarises_file() {
+    let mut cmd = get_base_command();
+
+    cmd.arg("--collapse-generated")
+        .arg("--generated-marker=^console\\.log")
+        .arg("sample_files/simple_1.js")
+        .arg("sample_files/simple_2.js");
+
+    let predicate_fn = predicate::str::contains("Generated file changed")
+        .and(predicate::str::contains("React").not());
     cmd.assert().stdout(predicate_fn);
 }